@@ -447,6 +447,7 @@ impl fmt::Display for Enum<'_> {
                     pdb::Variant::I16(v) => format!("{}", v),
                     pdb::Variant::I32(v) => format!("{}", v),
                     pdb::Variant::I64(v) => format!("{}", v),
+                    other => format!("{}", other),
                 }
             )?;
         }
@@ -459,7 +460,7 @@ impl fmt::Display for Enum<'_> {
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct EnumValue<'p> {
     name: pdb::RawString<'p>,
-    value: pdb::Variant,
+    value: pdb::Variant<'p>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -587,7 +588,7 @@ impl<'p> Data<'p> {
 
 fn write_class(filename: &str, class_name: &str) -> pdb::Result<()> {
     let file = std::fs::File::open(filename)?;
-    let mut pdb = pdb::PDB::open(file)?;
+    let pdb = pdb::PDB::open(file)?;
 
     let type_information = pdb.type_information()?;
     let mut type_finder = type_information.finder();
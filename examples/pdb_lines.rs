@@ -7,7 +7,7 @@ use pdb::{FallibleIterator, SymbolData, PDB};
 
 fn dump_pdb(filename: &str) -> pdb::Result<()> {
     let file = std::fs::File::open(filename)?;
-    let mut pdb = PDB::open(file)?;
+    let pdb = PDB::open(file)?;
 
     let address_map = pdb.address_map()?;
     let string_table = pdb.string_table()?;
@@ -2,7 +2,7 @@ use std::ffi::OsStr;
 
 fn dump_stream_names(filename: &OsStr) -> pdb::Result<()> {
     let file = std::fs::File::open(filename)?;
-    let mut pdb = pdb::PDB::open(file)?;
+    let pdb = pdb::PDB::open(file)?;
     let info = pdb.pdb_information()?;
     let names = info.stream_names()?;
     println!("index, name");
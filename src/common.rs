@@ -6,6 +6,7 @@
 // copied, modified, or distributed except according to those terms.
 
 use std::borrow::Cow;
+use std::convert::TryFrom;
 use std::fmt;
 use std::io;
 use std::mem;
@@ -33,6 +34,14 @@ pub enum Error {
     /// This likely indicates file corruption.
     PageReferenceOutOfRange(u32),
 
+    /// The MSF header specifies an invalid free page map.
+    InvalidFreePageMap(u32),
+
+    /// A page referenced by the MSF stream table is marked as free in the free page map.
+    ///
+    /// This likely indicates file corruption.
+    PageMarkedFree(u32),
+
     /// The requested stream is not stored in this file.
     StreamNotFound(u32),
 
@@ -69,6 +78,9 @@ pub enum Error {
     /// Type or Id not found.
     TypeNotFound(u32),
 
+    /// A type index has no entry in a `TypeIndexMap`.
+    UnmappedTypeIndex(u32),
+
     /// Type or Id not indexed -- the requested type (`.0`) is larger than the maximum index covered
     /// by the `ItemFinder` (`.1`).
     TypeNotIndexed(u32, u32),
@@ -108,6 +120,56 @@ pub enum Error {
 
     /// An unknown binary annotation was encountered.
     UnknownBinaryAnnotation(u32),
+
+    /// The requested layout cannot be represented in an MSF file, e.g. because a stream or the
+    /// stream table is too large.
+    MsfLimitExceeded(&'static str),
+
+    /// A compressed chunk of an MSFZ file could not be decompressed.
+    ///
+    /// This likely indicates file corruption.
+    InvalidCompressedChunk(u32),
+
+    /// The data is compressed with an unknown or unsupported algorithm.
+    UnsupportedCompression(u32),
+
+    /// Reading the data would exceed the memory limit of the `PDB`.
+    MemoryLimitExceeded(usize),
+
+    /// A CodeView debug record has an unknown signature.
+    UnrecognizedCodeViewRecord([u8; 4]),
+
+    /// The PDB was linked with `/DEBUG:FASTLINK` and does not contain the requested information.
+    ///
+    /// Such "mini PDBs" only contain public symbols, section information and the module list. Type
+    /// information and module symbols remain in the object files, and are only merged into a full
+    /// PDB by converting it with `mspdbcmf.exe`.
+    MiniPdb,
+
+    /// The type does not have a size, or its size cannot be determined.
+    UnsizedType(u32),
+
+    /// The type is not a class, structure or union.
+    NotARecordType(u32),
+
+    /// A serialized `ItemFinder` index is malformed or does not belong to the stream.
+    InvalidFinderIndex(&'static str),
+
+    /// The types of a precompiled header object do not match the `LF_PRECOMP` record referring to
+    /// them.
+    PrecompiledTypesMismatch,
+
+    /// The type is neither a procedure nor a member function.
+    NotAFunctionType(u32),
+
+    /// The id record at the given index is not of the kind required by the operation.
+    UnexpectedIdKind(u32),
+
+    /// The string id at the given index contains itself through its substrings.
+    CyclicStringId(u32),
+
+    /// A record is too large to be written, given its size in bytes.
+    RecordTooLarge(usize),
 }
 
 impl std::error::Error for Error {
@@ -130,6 +192,18 @@ impl fmt::Display for Error {
                 "The MSF header specifies an invalid page size ({} bytes)",
                 n
             ),
+            Self::InvalidFreePageMap(n) => {
+                write!(
+                    f,
+                    "The MSF header specifies an invalid free page map ({})",
+                    n
+                )
+            }
+            Self::PageMarkedFree(p) => write!(
+                f,
+                "MSF page ({}) is in use but marked as free in the free page map",
+                p
+            ),
             Self::StreamNotFound(s) => {
                 write!(f, "The requested stream ({}) is not stored in this file", s)
             }
@@ -151,6 +225,9 @@ impl fmt::Display for Error {
                 write!(f, "The type information header was invalid: {}", reason)
             }
             Self::TypeNotFound(type_index) => write!(f, "Type {} not found", type_index),
+            Self::UnmappedTypeIndex(type_index) => {
+                write!(f, "Type {} has no entry in the type index map", type_index)
+            }
             Self::TypeNotIndexed(type_index, indexed_count) => write!(
                 f,
                 "Type {} not indexed (index covers {})",
@@ -186,6 +263,55 @@ impl fmt::Display for Error {
                 write!(f, "Invalid source file checksum offset {:#x}", offset)
             }
             Self::UnknownBinaryAnnotation(num) => write!(f, "Unknown binary annotation {}", num),
+            Self::MsfLimitExceeded(reason) => write!(f, "MSF limit exceeded: {}", reason),
+            Self::InvalidCompressedChunk(chunk) => {
+                write!(f, "MSFZ chunk ({}) could not be decompressed", chunk)
+            }
+            Self::UnsupportedCompression(kind) => {
+                write!(f, "Unsupported compression algorithm ({})", kind)
+            }
+            Self::MemoryLimitExceeded(bytes) => {
+                write!(f, "Reading {} bytes would exceed the memory limit", bytes)
+            }
+            Self::UnrecognizedCodeViewRecord(signature) => write!(
+                f,
+                "Unrecognized CodeView record with signature {:?}",
+                RawString::from(&signature[..])
+            ),
+            Self::UnsizedType(index) => write!(f, "Type {} does not have a known size", index),
+            Self::NotARecordType(index) => {
+                write!(f, "Type {} is not a class, structure or union", index)
+            }
+            Self::NotAFunctionType(index) => {
+                write!(
+                    f,
+                    "Type {} is neither a procedure nor a member function",
+                    index
+                )
+            }
+            Self::UnexpectedIdKind(index) => {
+                write!(f, "Id {} is not of the expected kind", index)
+            }
+            Self::CyclicStringId(index) => {
+                write!(
+                    f,
+                    "String id {} contains itself through its substrings",
+                    index
+                )
+            }
+            Self::RecordTooLarge(size) => {
+                write!(f, "Record of {} bytes is too large to be written", size)
+            }
+            Self::InvalidFinderIndex(reason) => write!(f, "Invalid item finder index: {}", reason),
+            Self::PrecompiledTypesMismatch => write!(
+                f,
+                "Precompiled header types do not match the object referring to them"
+            ),
+            Self::MiniPdb => write!(
+                f,
+                "PDB was linked with /DEBUG:FASTLINK and only contains minimal debug information; \
+                 convert it to a full PDB using mspdbcmf.exe"
+            ),
             _ => fmt::Debug::fmt(self, f),
         }
     }
@@ -597,6 +723,7 @@ pub trait ItemIndex:
 /// If this index is a [cross module reference](ItemIndex::is_cross_module), it must be resolved
 /// before lookup in the stream.
 #[derive(Clone, Copy, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TypeIndex(pub u32);
 
 impl_convert!(TypeIndex, u32);
@@ -839,34 +966,106 @@ impl<'b> fmt::LowerHex for ParseBuffer<'b> {
 
 /// Value of an enumerate type.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[allow(missing_docs)]
-pub enum Variant {
+pub enum Variant<'t> {
     U8(u8),
     U16(u16),
     U32(u32),
     U64(u64),
+    U128(u128),
     I8(i8),
     I16(i16),
     I32(i32),
     I64(i64),
+    I128(i128),
+    /// The bits of a 32-bit IEEE 754 floating point value.
+    F32(u32),
+    /// The bits of a 64-bit IEEE 754 floating point value.
+    F64(u64),
+    /// An 80-bit x87 extended precision floating point value, in little endian byte order.
+    F80([u8; 10]),
+    /// A 128-bit IEEE 754 floating point value, in little endian byte order.
+    F128([u8; 16]),
+    Decimal(Decimal),
+    /// A length-prefixed string.
+    String(RawString<'t>),
+}
+
+impl Variant<'_> {
+    /// Returns the value as an unsigned 64-bit integer, if it is an integer in range.
+    pub fn to_u64(&self) -> Option<u64> {
+        self.to_i128()
+            .and_then(|value| u64::try_from(value).ok())
+            .or_else(|| self.to_u128().and_then(|value| u64::try_from(value).ok()))
+    }
+
+    /// Returns the value as a signed 128-bit integer, if it is an integer in range.
+    pub fn to_i128(&self) -> Option<i128> {
+        Some(match *self {
+            Self::U8(value) => i128::from(value),
+            Self::U16(value) => i128::from(value),
+            Self::U32(value) => i128::from(value),
+            Self::U64(value) => i128::from(value),
+            Self::U128(value) => return i128::try_from(value).ok(),
+            Self::I8(value) => i128::from(value),
+            Self::I16(value) => i128::from(value),
+            Self::I32(value) => i128::from(value),
+            Self::I64(value) => i128::from(value),
+            Self::I128(value) => value,
+            _ => return None,
+        })
+    }
+
+    /// Returns the value as an unsigned 128-bit integer, if it is an integer in range.
+    pub fn to_u128(&self) -> Option<u128> {
+        match *self {
+            Self::U128(value) => Some(value),
+            _ => self.to_i128().and_then(|value| u128::try_from(value).ok()),
+        }
+    }
+
+    /// Returns the value as a 64-bit floating point value, if it is numeric.
+    ///
+    /// Integers, extended precision and decimal values are rounded to the nearest representable
+    /// value.
+    pub fn to_f64(&self) -> Option<f64> {
+        Some(match *self {
+            Self::F32(bits) => f64::from(f32::from_bits(bits)),
+            Self::F64(bits) => f64::from_bits(bits),
+            Self::F80(bytes) => f80_to_f64(bytes),
+            Self::F128(bytes) => f128_to_f64(bytes),
+            Self::Decimal(value) => value.to_f64(),
+            Self::U128(value) => value as f64,
+            Self::String(_) => return None,
+            _ => self.to_i128()? as f64,
+        })
+    }
 }
 
-impl fmt::Display for Variant {
+impl fmt::Display for Variant<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::U8(value) => write!(f, "{}", value),
             Self::U16(value) => write!(f, "{}", value),
             Self::U32(value) => write!(f, "{}", value),
             Self::U64(value) => write!(f, "{}", value),
+            Self::U128(value) => write!(f, "{}", value),
             Self::I8(value) => write!(f, "{}", value),
             Self::I16(value) => write!(f, "{}", value),
             Self::I32(value) => write!(f, "{}", value),
             Self::I64(value) => write!(f, "{}", value),
+            Self::I128(value) => write!(f, "{}", value),
+            Self::F32(_) | Self::F64(_) | Self::F80(_) | Self::F128(_) => {
+                write!(f, "{}", self.to_f64().unwrap_or_default())
+            }
+            Self::Decimal(value) => write!(f, "{}", value),
+            Self::String(value) => write!(f, "{:?}", value.to_string()),
         }
     }
 }
 
-impl<'a> TryFromCtx<'a, Endian> for Variant {
+impl<'a> TryFromCtx<'a, Endian> for Variant<'a> {
     type Error = Error;
 
     fn try_from_ctx(this: &'a [u8], le: Endian) -> Result<(Self, usize)> {
@@ -874,14 +1073,25 @@ impl<'a> TryFromCtx<'a, Endian> for Variant {
 
         let variant = match this.gread_with(&mut offset, le)? {
             value if value < constants::LF_NUMERIC => Self::U16(value),
-            constants::LF_CHAR => Self::U8(this.gread_with(&mut offset, le)?),
+            constants::LF_CHAR => Self::I8(this.gread_with(&mut offset, le)?),
             constants::LF_SHORT => Self::I16(this.gread_with(&mut offset, le)?),
             constants::LF_LONG => Self::I32(this.gread_with(&mut offset, le)?),
             constants::LF_QUADWORD => Self::I64(this.gread_with(&mut offset, le)?),
+            constants::LF_OCTWORD => Self::I128(this.gread_with(&mut offset, le)?),
             constants::LF_USHORT => Self::U16(this.gread_with(&mut offset, le)?),
             constants::LF_ULONG => Self::U32(this.gread_with(&mut offset, le)?),
             constants::LF_UQUADWORD => Self::U64(this.gread_with(&mut offset, le)?),
-            _ if cfg!(debug_assertions) => unreachable!(),
+            constants::LF_UOCTWORD => Self::U128(this.gread_with(&mut offset, le)?),
+            constants::LF_REAL32 => Self::F32(this.gread_with(&mut offset, le)?),
+            constants::LF_REAL64 => Self::F64(this.gread_with(&mut offset, le)?),
+            constants::LF_REAL80 => Self::F80(gread_array(this, &mut offset)?),
+            constants::LF_REAL128 => Self::F128(gread_array(this, &mut offset)?),
+            constants::LF_DECIMAL => Self::Decimal(this.gread_with(&mut offset, le)?),
+            constants::LF_VARSTRING => {
+                let length: u16 = this.gread_with(&mut offset, le)?;
+                let data = take_slice(this, &mut offset, length as usize)?;
+                Self::String(RawString::from(data))
+            }
             other => return Err(Error::UnexpectedNumericPrefix(other)),
         };
 
@@ -889,6 +1099,142 @@ impl<'a> TryFromCtx<'a, Endian> for Variant {
     }
 }
 
+/// Reads `length` bytes at the given offset and advances the offset.
+fn take_slice<'a>(data: &'a [u8], offset: &mut usize, length: usize) -> Result<&'a [u8]> {
+    let slice = offset
+        .checked_add(length)
+        .and_then(|end| data.get(*offset..end))
+        .ok_or(Error::UnexpectedEof)?;
+    *offset += length;
+    Ok(slice)
+}
+
+/// Reads a fixed size byte array at the given offset and advances the offset.
+fn gread_array<const N: usize>(data: &[u8], offset: &mut usize) -> Result<[u8; N]> {
+    let mut array = [0; N];
+    array.copy_from_slice(take_slice(data, offset, N)?);
+    Ok(array)
+}
+
+/// A 96-bit fixed point decimal value, as stored in an `LF_DECIMAL` leaf.
+///
+/// This has the layout of the OLE Automation `DECIMAL` structure.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Decimal {
+    /// Whether the value is negative.
+    pub negative: bool,
+    /// The power of ten by which the mantissa is divided, between 0 and 28.
+    pub scale: u8,
+    /// The 96-bit unsigned integer mantissa.
+    pub mantissa: u128,
+}
+
+impl Decimal {
+    /// Returns the value as a 64-bit floating point value, rounded to the nearest representable
+    /// value.
+    pub fn to_f64(self) -> f64 {
+        let value = self.mantissa as f64 / 10f64.powi(i32::from(self.scale));
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.mantissa.to_string();
+        let scale = self.scale as usize;
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - scale);
+
+        if self.negative {
+            f.write_str("-")?;
+        }
+
+        if fraction.is_empty() {
+            write!(f, "{}", integer)
+        } else {
+            write!(f, "{}.{}", integer, fraction)
+        }
+    }
+}
+
+impl<'a> TryFromCtx<'a, Endian> for Decimal {
+    type Error = scroll::Error;
+
+    fn try_from_ctx(this: &'a [u8], le: Endian) -> scroll::Result<(Self, usize)> {
+        let mut offset = 0;
+        let _reserved: u16 = this.gread_with(&mut offset, le)?;
+        let scale: u8 = this.gread_with(&mut offset, le)?;
+        let sign: u8 = this.gread_with(&mut offset, le)?;
+        let high: u32 = this.gread_with(&mut offset, le)?;
+        let low: u64 = this.gread_with(&mut offset, le)?;
+
+        let decimal = Self {
+            negative: sign & 0x80 != 0,
+            scale,
+            mantissa: u128::from(high) << 64 | u128::from(low),
+        };
+
+        Ok((decimal, offset))
+    }
+}
+
+/// Converts an 80-bit x87 extended precision value to the nearest 64-bit value.
+fn f80_to_f64(bytes: [u8; 10]) -> f64 {
+    let mut mantissa = [0; 8];
+    mantissa.copy_from_slice(&bytes[..8]);
+    let mantissa = u64::from_le_bytes(mantissa);
+    let sign_exponent = u16::from_le_bytes([bytes[8], bytes[9]]);
+    let negative = sign_exponent & 0x8000 != 0;
+    let exponent = i32::from(sign_exponent & 0x7fff);
+
+    // the integer bit of the mantissa is explicit
+    let value = match exponent {
+        0x7fff if mantissa << 1 == 0 => f64::INFINITY,
+        0x7fff => f64::NAN,
+        0 => scale(mantissa as f64, 1 - 16383 - 63),
+        _ => scale(mantissa as f64, exponent - 16383 - 63),
+    };
+
+    if negative {
+        -value
+    } else {
+        value
+    }
+}
+
+/// Converts a 128-bit IEEE 754 value to the nearest 64-bit value.
+fn f128_to_f64(bytes: [u8; 16]) -> f64 {
+    let bits = u128::from_le_bytes(bytes);
+    let negative = bits >> 127 != 0;
+    let exponent = ((bits >> 112) & 0x7fff) as i32;
+    let mantissa = bits & ((1 << 112) - 1);
+
+    // the integer bit of the mantissa is implicit, except for subnormal values
+    let value = match exponent {
+        0x7fff if mantissa == 0 => f64::INFINITY,
+        0x7fff => f64::NAN,
+        0 => scale(mantissa as f64, 1 - 16383 - 112),
+        _ => scale((mantissa | 1 << 112) as f64, exponent - 16383 - 112),
+    };
+
+    if negative {
+        -value
+    } else {
+        value
+    }
+}
+
+/// Multiplies a value by a power of two, without overflowing in intermediate results.
+fn scale(value: f64, exponent: i32) -> f64 {
+    let half = exponent / 2;
+    value * 2f64.powi(half) * 2f64.powi(exponent - half)
+}
+
 /// `RawString` refers to a `&[u8]` that physically resides somewhere inside a PDB data structure.
 ///
 /// A `RawString` may not be valid UTF-8.
@@ -901,6 +1247,17 @@ impl fmt::Debug for RawString<'_> {
     }
 }
 
+/// Serializes the string lossily as UTF-8, replacing invalid sequences.
+#[cfg(feature = "serde")]
+impl serde::Serialize for RawString<'_> {
+    fn serialize<S>(&self, serializer: S) -> result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
 impl fmt::Display for RawString<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.to_string())
@@ -955,6 +1312,49 @@ impl<'b> From<&'b [u8]> for RawString<'b> {
     }
 }
 
+/// Computes the hash of a name as used by the hash tables in PDB files.
+///
+/// This is `LHashPbCb` from the Microsoft PDB sources, which is also known as `hashStringV1`.
+pub(crate) fn hash_v1(data: &[u8]) -> u32 {
+    let mut chunks = data.chunks_exact(4);
+    let mut hash = 0u32;
+    for chunk in &mut chunks {
+        hash ^= u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+
+    let mut remainder = chunks.remainder();
+    if remainder.len() >= 2 {
+        hash ^= u32::from(u16::from_le_bytes([remainder[0], remainder[1]]));
+        remainder = &remainder[2..];
+    }
+    if let Some(&byte) = remainder.first() {
+        hash ^= u32::from(byte);
+    }
+
+    hash |= 0x2020_2020;
+    hash ^= hash >> 11;
+    hash ^ (hash >> 16)
+}
+
+/// Computes the hash of a record as used by the hash streams of the type and id streams.
+///
+/// This is `hashBufv8` from the Microsoft PDB sources, a CRC-32 without final inversion that is
+/// also known as JamCRC.
+pub(crate) fn hash_v8(data: &[u8]) -> u32 {
+    let mut crc = 0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
 /// Cast a binary slice to a slice of types.
 ///
 /// This function performs a cast of a binary slice to a slice of some type, returning `Some` if the
@@ -1244,6 +1644,94 @@ mod tests {
         }
     }
 
+    mod variant {
+        use crate::common::*;
+
+        #[test]
+        fn test_parse_variant() {
+            let mut buf = ParseBuffer::from(&[0x2a, 0x00][..]);
+            assert_eq!(buf.parse::<Variant>().expect("parse"), Variant::U16(42));
+
+            // LF_CHAR holds a signed char
+            let mut buf = ParseBuffer::from(&[0x00, 0x80, 0xff][..]);
+            assert_eq!(buf.parse::<Variant>().expect("parse"), Variant::I8(-1));
+            assert!(buf.is_empty());
+        }
+
+        #[test]
+        fn test_parse_octword() {
+            let mut data = vec![0x17, 0x80];
+            data.extend_from_slice(&(-2i128).to_le_bytes());
+            let value = ParseBuffer::from(&data[..])
+                .parse::<Variant>()
+                .expect("parse");
+            assert_eq!(value, Variant::I128(-2));
+            assert_eq!(value.to_i128(), Some(-2));
+            assert_eq!(value.to_u64(), None);
+
+            let mut data = vec![0x18, 0x80];
+            data.extend_from_slice(&u128::MAX.to_le_bytes());
+            let value = ParseBuffer::from(&data[..])
+                .parse::<Variant>()
+                .expect("parse");
+            assert_eq!(value, Variant::U128(u128::MAX));
+            assert_eq!(value.to_i128(), None);
+            assert_eq!(value.to_string(), u128::MAX.to_string());
+        }
+
+        #[test]
+        fn test_parse_real() {
+            // 1.5 as x87 extended precision
+            let data = [0x07, 0x80, 0, 0, 0, 0, 0, 0, 0, 0xc0, 0xff, 0x3f];
+            let value = ParseBuffer::from(&data[..])
+                .parse::<Variant>()
+                .expect("parse");
+            assert_eq!(value.to_f64(), Some(1.5));
+
+            // -0.25 as IEEE 754 quadruple precision
+            let mut data = vec![0x08, 0x80];
+            data.extend_from_slice(&(0xbffd_u128 << 112).to_le_bytes());
+            let value = ParseBuffer::from(&data[..])
+                .parse::<Variant>()
+                .expect("parse");
+            assert_eq!(value.to_f64(), Some(-0.25));
+            assert_eq!(value.to_string(), "-0.25");
+        }
+
+        #[test]
+        fn test_parse_decimal() {
+            // -12.345
+            let mut data = vec![0x19, 0x80, 0x00, 0x00, 0x03, 0x80];
+            data.extend_from_slice(&0u32.to_le_bytes());
+            data.extend_from_slice(&12345u64.to_le_bytes());
+            let value = ParseBuffer::from(&data[..])
+                .parse::<Variant>()
+                .expect("parse");
+            assert_eq!(value.to_string(), "-12.345");
+            assert_eq!(value.to_f64(), Some(-12.345));
+
+            let decimal = Decimal {
+                negative: false,
+                scale: 4,
+                mantissa: 5,
+            };
+            assert_eq!(decimal.to_string(), "0.0005");
+        }
+
+        #[test]
+        fn test_parse_varstring() {
+            let data = [0x10, 0x80, 0x03, 0x00, b'a', b'b', b'c', 0xf1];
+            let mut buf = ParseBuffer::from(&data[..]);
+            let value = buf.parse::<Variant>().expect("parse");
+            assert_eq!(value, Variant::String(RawString::from("abc")));
+            assert_eq!(value.to_f64(), None);
+            assert_eq!(buf.len(), 1);
+
+            let data = [0x10, 0x80, 0x04, 0x00, b'a'];
+            assert!(ParseBuffer::from(&data[..]).parse::<Variant>().is_err());
+        }
+    }
+
     mod cast_aligned {
         use crate::common::cast_aligned;
         use std::slice;
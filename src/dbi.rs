@@ -8,11 +8,15 @@
 // DBI = "Debug Information"
 
 use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
 use std::fmt;
 use std::result;
+use std::sync::OnceLock;
 
 use crate::common::*;
 use crate::msf::*;
+use crate::strings::StringTable;
 use crate::{FallibleIterator, SectionCharacteristics};
 
 /// Provides access to the "DBI" stream inside the PDB.
@@ -26,10 +30,9 @@ use crate::{FallibleIterator, SectionCharacteristics};
 /// #
 /// # fn test() -> pdb::Result<usize> {
 /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
-/// let mut pdb = pdb::PDB::open(file)?;
+/// let pdb = pdb::PDB::open(file)?;
 ///
 /// let dbi = pdb.debug_information()?;
-
 ///
 /// # let mut count: usize = 0;
 /// let mut modules = dbi.modules()?;
@@ -47,6 +50,10 @@ pub struct DebugInformation<'s> {
     stream: Stream<'s>,
     header: DBIHeader,
     header_len: usize,
+    /// Offsets of all modules in the module info substream, computed on first random access.
+    module_offsets: OnceLock<Vec<usize>>,
+    /// Section contributions sorted by address, computed on first address lookup.
+    sorted_contributions: OnceLock<Vec<DBISectionContribution>>,
 }
 
 impl<'s> DebugInformation<'s> {
@@ -59,10 +66,16 @@ impl<'s> DebugInformation<'s> {
             stream,
             header,
             header_len,
+            module_offsets: OnceLock::new(),
+            sorted_contributions: OnceLock::new(),
         })
     }
 
-    pub(crate) fn header(&self) -> DBIHeader {
+    /// Returns the header of the debug information stream.
+    ///
+    /// The header contains the versions of the toolchain and PDB writer that produced this PDB, as
+    /// well as flags and the sizes of all substreams. See [`DBIHeader`] for more information.
+    pub fn header(&self) -> DBIHeader {
         self.header
     }
 
@@ -99,6 +112,87 @@ impl<'s> DebugInformation<'s> {
         })
     }
 
+    /// Returns the number of modules.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the module info substream is truncated
+    pub fn module_count(&self) -> Result<usize> {
+        Ok(self.module_offsets()?.len())
+    }
+
+    /// Returns the module at the given index in [`modules`](Self::modules), if any.
+    ///
+    /// The first call indexes the module list, after which modules are accessed directly.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the module info substream is truncated
+    pub fn module(&self, index: usize) -> Result<Option<Module<'_>>> {
+        let offset = match self.module_offsets()?.get(index) {
+            Some(&offset) => offset,
+            None => return Ok(None),
+        };
+
+        let mut modules = self.modules()?;
+        modules.buf.seek(offset);
+        modules.next()
+    }
+
+    /// Finds a module by name and returns it along with its index.
+    ///
+    /// The name is compared case-insensitively and without distinguishing `/` and `\`. It
+    /// matches a module if it is one of:
+    ///
+    ///  - the [`module_name`](Module::module_name), such as `c:\src\foo.obj` or `* Linker *`,
+    ///  - the file name of the module, such as `foo.obj`,
+    ///  - the file names of the archive and the module of modules from static libraries, in the
+    ///    form `libcmt.lib(exe_main.obj)`.
+    ///
+    /// If several modules match, the first one is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let debug_information = pdb.debug_information()?;
+    ///
+    /// if let Some((index, module)) = debug_information.module_by_name("foo.obj")? {
+    ///     println!("module {}: {}", index, module.module_name());
+    /// }
+    /// # assert_eq!(debug_information.module_by_name("foo.obj")?.map(|(i, _)| i), Some(0));
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the module info substream is truncated
+    pub fn module_by_name(&self, name: &str) -> Result<Option<(usize, Module<'_>)>> {
+        let name = normalize_module_name(name);
+        let mut modules = self.modules()?.enumerate();
+        modules.find(|(_, module)| Ok(module.matches_name(&name)))
+    }
+
+    /// Returns the offsets of all modules in the module info substream.
+    fn module_offsets(&self) -> Result<&[usize]> {
+        if let Some(offsets) = self.module_offsets.get() {
+            return Ok(offsets);
+        }
+
+        let mut offsets = Vec::new();
+        let mut modules = self.modules()?;
+        while !modules.buf.is_empty() {
+            offsets.push(modules.buf.pos());
+            modules.next()?;
+        }
+
+        Ok(self.module_offsets.get_or_init(|| offsets))
+    }
+
     /// Returns an iterator that can traverse the section contributions list in sequential order.
     pub fn section_contributions(&self) -> Result<DBISectionContributionIter<'_>> {
         let mut buf = self.stream.parse_buffer();
@@ -107,6 +201,233 @@ impl<'s> DebugInformation<'s> {
         let contributions_buf = buf.take(self.header.section_contribution_size as usize)?;
         DBISectionContributionIter::parse(contributions_buf.into())
     }
+
+    /// Finds the index of the module contributing the code or data at an address.
+    ///
+    /// The module can then be obtained with [`module`](Self::module) and its symbols and line
+    /// information opened with [`PDB::module_info`](crate::PDB::module_info), without reading the
+    /// streams of other modules. Returns `None` if no module contributes to the address, for
+    /// instance in padding between contributions.
+    ///
+    /// The first call reads and sorts all section contributions, after which lookups are binary
+    /// searches.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let debug_information = pdb.debug_information()?;
+    ///
+    /// let address = pdb::PdbInternalSectionOffset::new(1, 0x5500);
+    /// if let Some(index) = debug_information.module_at(address)? {
+    ///     let module = debug_information.module(index)?.unwrap();
+    ///     let info = pdb.module_info(&module)?;
+    ///     # assert!(info.is_some());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the section contribution substream is truncated
+    /// * `Error::UnimplementedFeature` if the section contribution substream has an unknown
+    ///   version
+    pub fn module_at(&self, offset: PdbInternalSectionOffset) -> Result<Option<usize>> {
+        let contributions = self.sorted_contributions()?;
+        let key = (offset.section, offset.offset);
+
+        let index = contributions.partition_point(|contribution| {
+            (contribution.offset.section, contribution.offset.offset) <= key
+        });
+
+        let contribution = match index.checked_sub(1) {
+            Some(index) => &contributions[index],
+            None => return Ok(None),
+        };
+
+        let end = contribution.offset.offset as u64 + contribution.size as u64;
+        if contribution.offset.section == offset.section && (offset.offset as u64) < end {
+            Ok(Some(contribution.module))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns all non-empty section contributions, sorted by address.
+    fn sorted_contributions(&self) -> Result<&[DBISectionContribution]> {
+        if let Some(contributions) = self.sorted_contributions.get() {
+            return Ok(contributions);
+        }
+
+        let mut contributions: Vec<_> = self
+            .section_contributions()?
+            .filter(|contribution| Ok(contribution.size > 0))
+            .collect()?;
+        contributions
+            .sort_by_key(|contribution| (contribution.offset.section, contribution.offset.offset));
+
+        Ok(self.sorted_contributions.get_or_init(|| contributions))
+    }
+
+    /// Returns the section map, which describes the logical segments of the image.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let debug_information = pdb.debug_information()?;
+    /// let section_map = debug_information.section_map()?;
+    ///
+    /// let mut entries = section_map.iter();
+    /// while let Some(entry) = entries.next()? {
+    ///     println!("frame {} has {:#x} bytes", entry.frame, entry.section_length);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn section_map(&self) -> Result<DBISectionMap<'_>> {
+        let mut buf = self.stream.parse_buffer();
+        // drop the header, modules list and section contributions
+        buf.take(self.substream_offset(2)?)?;
+        let section_map_buf = buf.take(self.header.section_map_size as usize)?;
+        DBISectionMap::parse(section_map_buf.into())
+    }
+
+    /// Returns the file info substream, which lists the source files contributing to each module.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let debug_information = pdb.debug_information()?;
+    /// let file_info = debug_information.file_info()?;
+    ///
+    /// let mut modules = debug_information.modules()?.enumerate();
+    /// while let Some((index, module)) = modules.next()? {
+    ///     println!("{}:", module.module_name());
+    ///     if let Some(mut files) = file_info.module_files(index) {
+    ///         while let Some(file) = files.next()? {
+    ///             println!("    {}", file);
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the file info substream is truncated
+    /// * `Error::InvalidStreamLength` if the sizes of the substreams overflow
+    pub fn file_info(&self) -> Result<DBIFileInfo<'_>> {
+        let mut buf = self.stream.parse_buffer();
+        buf.take(self.substream_offset(3)?)?;
+        let file_info_buf = buf.take(self.header.file_info_size as usize)?;
+        DBIFileInfo::parse(file_info_buf.into())
+    }
+
+    /// Returns the optional debug header, which lists auxiliary streams copied from the image.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the DBI stream is truncated
+    /// * `Error::InvalidStreamLength` if the debug header has an odd length, or if the sizes of the
+    ///   substreams overflow
+    pub fn extra_streams(&self) -> Result<DBIExtraStreams> {
+        DBIExtraStreams::new(self)
+    }
+
+    /// Returns the type server map substream.
+    ///
+    /// See [`DBITypeServerMap`] for the extent to which this substream is supported.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the type server map is truncated
+    /// * `Error::InvalidStreamLength` if the sizes of the substreams overflow
+    pub fn type_server_map(&self) -> Result<DBITypeServerMap<'_>> {
+        let mut buf = self.stream.parse_buffer();
+        buf.take(self.substream_offset(4)?)?;
+        let data = buf.take(self.header.type_server_map_size as usize)?;
+        Ok(DBITypeServerMap {
+            data,
+            mfc_type_server_index: self.header.mfc_type_server_index,
+        })
+    }
+
+    /// Returns the names of edit-and-continue files from the EC substream.
+    ///
+    /// Modules compiled with edit-and-continue support refer to their source file and compiler
+    /// PDB by an offset into this table, see [`Module::ec_source_file`] and
+    /// [`Module::ec_pdb_file`]. The table uses the same format as the global string table.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let debug_information = pdb.debug_information()?;
+    /// let ec_names = debug_information.ec_names()?;
+    ///
+    /// let mut modules = debug_information.modules()?;
+    /// while let Some(module) = modules.next()? {
+    ///     if let Some(source_file) = module.ec_source_file() {
+    ///         println!("{}: {}", module.module_name(), ec_names.get(source_file)?);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the EC substream is truncated
+    /// * `Error::InvalidStreamLength` if the sizes of the substreams overflow
+    /// * `Error::UnimplementedFeature` if the EC substream is not a valid string table
+    pub fn ec_names(&self) -> Result<StringTable<'_>> {
+        let mut buf = self.stream.parse_buffer();
+        buf.take(self.substream_offset(5)?)?;
+        let ec_buf = buf.take(self.header.ec_substream_size as usize)?;
+        StringTable::parse_slice(ec_buf)
+    }
+
+    /// Returns the offset of a substream within the stream, given the number of substreams
+    /// preceding it.
+    ///
+    /// The substreams follow the header in the order module list, section contributions, section
+    /// map, file info, type server map, EC substream and debug header.
+    fn substream_offset(&self, preceding: usize) -> Result<usize> {
+        let header = &self.header;
+        let sizes = [
+            header.module_list_size,
+            header.section_contribution_size,
+            header.section_map_size,
+            header.file_info_size,
+            header.type_server_map_size,
+            header.ec_substream_size,
+        ];
+
+        let size = sizes[..preceding]
+            .iter()
+            .try_fold(0u32, |total, &size| total.checked_add(size))
+            .ok_or(Error::InvalidStreamLength("DBI substreams"))?;
+        Ok(self.header_len + size as usize)
+    }
 }
 
 /// The version of the PDB format.
@@ -138,16 +459,59 @@ impl From<u32> for HeaderVersion {
     }
 }
 
-/// A DBI header -- `NewDBIHdr`, really -- parsed from a stream.
+impl From<HeaderVersion> for u32 {
+    #[allow(clippy::inconsistent_digit_grouping)]
+    fn from(v: HeaderVersion) -> Self {
+        match v {
+            HeaderVersion::V41 => 93_08_03,
+            HeaderVersion::V50 => 1996_03_07,
+            HeaderVersion::V60 => 1997_06_06,
+            HeaderVersion::V70 => 1999_09_03,
+            HeaderVersion::V110 => 2009_12_01,
+            HeaderVersion::OtherValue(v) => v,
+        }
+    }
+}
+
+/// The header of the debug information stream -- `NewDBIHdr`, really.
+///
+/// The header describes the layout of the substreams of the debug information stream, refers to
+/// the streams holding global symbols, and records the toolchain that last wrote the PDB. The
+/// version of the toolchain and of the PDB writer are useful for compatibility checks.
+///
+/// Obtain the header using [`DebugInformation::header`].
 ///
 /// Reference:
 /// <https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/PDB/dbi/dbi.h#L124>
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(file)?;
+/// let header = pdb.debug_information()?.header();
+///
+/// if let Some((major, minor)) = header.toolchain_version() {
+///     println!("written by toolchain {}.{:02}", major, minor);
+/// }
+/// println!("incrementally linked: {}", header.flags.incremental_link());
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
 #[derive(Debug, Copy, Clone)]
-#[allow(dead_code)] // reason = "unused fields added for completeness"
-pub(crate) struct DBIHeader {
+pub struct DBIHeader {
+    /// The signature of the header, which is always `0xffffffff` for `NewDBIHdr`.
     pub signature: u32,
+    /// The version of the debug information stream format.
     pub version: HeaderVersion,
+    /// The age of the PDB when the linker last wrote it.
+    ///
+    /// See [`DebugInformation::age`] for how this relates to the age of the PDB information
+    /// stream.
     pub age: u32,
+    /// The stream containing the hash table of global symbols.
     pub gs_symbols_stream: StreamIndex,
 
     /*
@@ -166,47 +530,69 @@ pub(crate) struct DBIHeader {
         USHORT          usVerAll;
     };
     */
+    /// The packed major and minor version of the toolchain that last wrote this PDB.
+    ///
+    /// Use [`toolchain_version`](Self::toolchain_version) to decode it.
     pub internal_version: u16,
+    /// The stream containing the hash table of public symbols.
     pub ps_symbols_stream: StreamIndex,
-    // "build version of the pdb dll that built this pdb last."
+    /// The build number of the PDB writer (`mspdbXXX.dll`) that last wrote this PDB.
     pub pdb_dll_build_version: u16,
-
+    /// The stream containing the records of global and public symbols.
     pub symbol_records_stream: StreamIndex,
-
-    // "rbld version of the pdb dll that built this pdb last."
+    /// The rebuild number of the PDB writer that last wrote this PDB.
     pub pdb_dll_rbld_version: u16,
+    /// The size of the module info substream in bytes.
     pub module_list_size: u32,
+    /// The size of the section contribution substream in bytes.
     pub section_contribution_size: u32,
+    /// The size of the section map substream in bytes.
     pub section_map_size: u32,
+    /// The size of the file info substream in bytes.
     pub file_info_size: u32,
-
-    // "size of the Type Server Map substream"
+    /// The size of the type server map substream in bytes.
     pub type_server_map_size: u32,
-
-    // "index of MFC type server"
+    /// The index of the MFC type server in the type server map.
     pub mfc_type_server_index: u32,
-
-    // "size of optional DbgHdr info appended to the end of the stream"
+    /// The size of the optional debug header substream at the end of the stream in bytes.
     pub debug_header_size: u32,
-
-    // "number of bytes in EC substream, or 0 if EC no EC enabled Mods"
+    /// The size of the edit-and-continue substream in bytes, or 0 if no module supports it.
     pub ec_substream_size: u32,
-
-    /*
-    https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/PDB/dbi/dbi.h#L187-L192:
-        USHORT  fIncLink:1;     // true if linked incrmentally (really just if ilink thunks are present)
-        USHORT  fStripped:1;    // true if PDB::CopyTo stripped the private data out
-        USHORT  fCTypes:1;      // true if this PDB is using CTypes.
-        USHORT  unused:13;      // reserved, must be 0.
-    */
-    pub flags: u16,
-
+    /// Flags describing how the PDB was produced.
+    pub flags: DBIFlags,
+    /// The raw machine type, see [`DebugInformation::machine_type`].
     pub machine_type: u16,
+    /// Reserved, should be 0.
     pub reserved: u32,
 }
 
+/// The flags of the debug information stream header.
+///
+/// Reference:
+/// <https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/PDB/dbi/dbi.h>
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DBIFlags(pub u16);
+
+impl DBIFlags {
+    /// The image was linked incrementally, which means that incremental linking thunks are
+    /// present.
+    pub fn incremental_link(self) -> bool {
+        self.0 & 0x1 != 0
+    }
+
+    /// Private symbols and types were stripped from the PDB, for example with `/PDBSTRIPPED`.
+    pub fn stripped(self) -> bool {
+        self.0 & 0x2 != 0
+    }
+
+    /// The PDB uses CTypes.
+    pub fn ctypes(self) -> bool {
+        self.0 & 0x4 != 0
+    }
+}
+
 impl DBIHeader {
-    pub fn parse(stream: Stream<'_>) -> Result<Self> {
+    pub(crate) fn parse(stream: Stream<'_>) -> Result<Self> {
         Self::parse_buf(&mut stream.parse_buffer())
     }
 
@@ -229,12 +615,12 @@ impl DBIHeader {
             mfc_type_server_index: buf.parse_u32()?,
             debug_header_size: buf.parse_u32()?,
             ec_substream_size: buf.parse_u32()?,
-            flags: buf.parse_u16()?,
+            flags: DBIFlags(buf.parse_u16()?),
             machine_type: buf.parse_u16()?,
             reserved: buf.parse_u32()?,
         };
 
-        if header.signature != u32::max_value() {
+        if header.signature != u32::MAX {
             // this is likely a DBIHdr, not a NewDBIHdr
             // it could be promoted:
             //   https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/PDB/dbi/dbi.cpp#L291-L313
@@ -245,6 +631,20 @@ impl DBIHeader {
 
         Ok(header)
     }
+
+    /// Returns the major and minor version of the toolchain that last wrote this PDB.
+    ///
+    /// For example, the toolchain of Visual Studio 2015 has version 14.00. Returns `None` if the
+    /// header uses the legacy version format, which is not supported.
+    pub fn toolchain_version(&self) -> Option<(u8, u8)> {
+        if self.internal_version & 0x8000 == 0 {
+            return None;
+        }
+
+        let major = (self.internal_version >> 8) & 0x7f;
+        let minor = self.internal_version & 0xff;
+        Some((major as u8, minor as u8))
+    }
 }
 
 /// The target machine's architecture.
@@ -339,6 +739,38 @@ impl fmt::Display for MachineType {
     }
 }
 
+impl MachineType {
+    /// Returns the size of a data pointer on this machine in bytes.
+    ///
+    /// Returns `None` if the machine type does not imply a pointer size, such as for
+    /// [`MachineType::Unknown`] or EFI byte code.
+    pub fn pointer_size(self) -> Option<u8> {
+        match self {
+            Self::Amd64 | Self::Arm64 | Self::Ia64 | Self::RiscV64 => Some(8),
+            Self::RiscV128 => Some(16),
+            Self::Am33
+            | Self::Arm
+            | Self::ArmNT
+            | Self::X86
+            | Self::M32R
+            | Self::Mips16
+            | Self::MipsFpu
+            | Self::MipsFpu16
+            | Self::PowerPC
+            | Self::PowerPCFP
+            | Self::R4000
+            | Self::RiscV32
+            | Self::SH3
+            | Self::SH3DSP
+            | Self::SH4
+            | Self::SH5
+            | Self::Thumb
+            | Self::WceMipsV2 => Some(4),
+            Self::Ebc | Self::Unknown | Self::Invalid => None,
+        }
+    }
+}
+
 impl From<u16> for MachineType {
     fn from(value: u16) -> Self {
         match value {
@@ -376,7 +808,7 @@ impl From<u16> for MachineType {
 /// Information about a module's contribution to a section.
 /// `struct SC` in Microsoft's code:
 /// <https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/PDB/include/dbicommon.h#L42>
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct DBISectionContribution {
     /// Start offset of the section.
     pub offset: PdbInternalSectionOffset,
@@ -392,6 +824,10 @@ pub struct DBISectionContribution {
     pub data_crc: u32,
     /// CRC of relocations(?)
     pub reloc_crc: u32,
+    /// Index of the section in the COFF object file of the module, if known.
+    ///
+    /// This is only recorded in version 2 of the section contribution substream (`SC2`).
+    pub coff_section: Option<u32>,
 }
 
 impl DBISectionContribution {
@@ -411,6 +847,7 @@ impl DBISectionContribution {
             module,
             data_crc: buf.parse_u32()?,
             reloc_crc: buf.parse_u32()?,
+            coff_section: None,
         })
     }
 }
@@ -501,6 +938,73 @@ impl<'m> Module<'m> {
     pub fn object_file_name(&self) -> Cow<'m, str> {
         self.object_file_name.to_string()
     }
+    /// The index of the type server this module's types were taken from, if any.
+    ///
+    /// This is a one-based index into the type server map of the DBI stream. Modules compiled with
+    /// `/Zi` refer to the compiler's PDB through an `LF_TYPESERVER2` record instead, whose types
+    /// the linker merges into the type stream. Current linkers therefore leave this unset.
+    pub fn type_server_index(&self) -> Option<u8> {
+        match (self.info.flags >> 8) as u8 {
+            0 => None,
+            index => Some(index),
+        }
+    }
+    /// Returns whether a normalized name refers to this module, see
+    /// [`DebugInformation::module_by_name`].
+    fn matches_name(&self, name: &str) -> bool {
+        let module_name = normalize_module_name(&self.module_name());
+        if module_name == name {
+            return true;
+        }
+
+        let module_file = file_name(&module_name);
+        if module_file == name {
+            return true;
+        }
+
+        let object_file_name = normalize_module_name(&self.object_file_name());
+        if object_file_name == module_name {
+            return false;
+        }
+
+        // static library members, like `libcmt.lib(exe_main.obj)`
+        name.strip_prefix(file_name(&object_file_name))
+            .and_then(|rest| rest.strip_prefix('('))
+            .and_then(|rest| rest.strip_suffix(')'))
+            == Some(module_file)
+    }
+    /// Whether the module was compiled with edit-and-continue support.
+    pub fn is_ec_enabled(&self) -> bool {
+        self.info.flags & 0x2 != 0
+    }
+    /// The source file of the module in the edit-and-continue names, if any.
+    ///
+    /// Resolve the name using [`DebugInformation::ec_names`].
+    pub fn ec_source_file(&self) -> Option<StringRef> {
+        match self.info.source {
+            0 => None,
+            offset => Some(StringRef(offset)),
+        }
+    }
+    /// The PDB written by the compiler for the module in the edit-and-continue names, if any.
+    ///
+    /// Resolve the name using [`DebugInformation::ec_names`].
+    pub fn ec_pdb_file(&self) -> Option<StringRef> {
+        match self.info.compiler {
+            0 => None,
+            offset => Some(StringRef(offset)),
+        }
+    }
+}
+
+/// Normalizes a module or object file name for comparison.
+fn normalize_module_name(name: &str) -> String {
+    name.replace('/', "\\").to_ascii_lowercase()
+}
+
+/// Returns the last component of a normalized path.
+fn file_name(path: &str) -> &str {
+    path.rsplit('\\').next().unwrap_or(path)
 }
 
 /// A `ModuleIter` iterates over the modules in the DBI section, producing `Module`s.
@@ -532,6 +1036,9 @@ impl<'m> FallibleIterator for ModuleIter<'m> {
 }
 
 /// The version of the section contribution stream.
+///
+/// Version 2 appends the index of the COFF section to every entry (`struct SC2`):
+/// <https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/PDB/include/dbicommon.h#L60>
 #[derive(Debug, Copy, Clone, PartialEq)]
 #[allow(missing_docs)]
 enum DBISectionContributionStreamVersion {
@@ -561,7 +1068,19 @@ pub struct DBISectionContributionIter<'c> {
 
 impl<'c> DBISectionContributionIter<'c> {
     fn parse(mut buf: ParseBuffer<'c>) -> Result<Self> {
+        // stripped PDBs may omit the substream entirely, including its version
+        if buf.is_empty() {
+            let version = DBISectionContributionStreamVersion::V60;
+            return Ok(Self { buf, version });
+        }
+
         let version = buf.parse_u32()?.into();
+        if let DBISectionContributionStreamVersion::OtherValue(_) = version {
+            return Err(Error::UnimplementedFeature(
+                "unsupported section contribution substream version",
+            ));
+        }
+
         Ok(Self { buf, version })
     }
 }
@@ -576,18 +1095,382 @@ impl<'c> FallibleIterator for DBISectionContributionIter<'c> {
             return Ok(None);
         }
 
-        let contribution = DBISectionContribution::parse(&mut self.buf)?;
+        let mut contribution = DBISectionContribution::parse(&mut self.buf)?;
         if self.version == DBISectionContributionStreamVersion::V2 {
-            self.buf.parse_u32()?;
+            contribution.coff_section = Some(self.buf.parse_u32()?);
         }
         Ok(Some(contribution))
     }
 }
 
-/// A `DbgDataHdr`, which contains a series of (optional) MSF stream numbers.
-#[derive(Debug, Copy, Clone)]
-#[allow(dead_code)] // reason = "unused fields added for completeness"
-pub(crate) struct DBIExtraStreams {
+/// The flags of a [`DBISectionMapEntry`], called `OMFSegDescFlags` in LLVM.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DBISectionMapFlags(pub u16);
+
+impl DBISectionMapFlags {
+    /// The segment is readable.
+    pub fn read(self) -> bool {
+        self.0 & 0x1 != 0
+    }
+
+    /// The segment is writable.
+    pub fn write(self) -> bool {
+        self.0 & 0x2 != 0
+    }
+
+    /// The segment is executable.
+    pub fn execute(self) -> bool {
+        self.0 & 0x4 != 0
+    }
+
+    /// The segment uses 32-bit addresses.
+    pub fn address_is_32_bit(self) -> bool {
+        self.0 & 0x8 != 0
+    }
+
+    /// The frame of the segment is a selector.
+    pub fn is_selector(self) -> bool {
+        self.0 & 0x100 != 0
+    }
+
+    /// The frame of the segment is an absolute address.
+    pub fn is_absolute_address(self) -> bool {
+        self.0 & 0x200 != 0
+    }
+
+    /// The segment is a group.
+    pub fn is_group(self) -> bool {
+        self.0 & 0x400 != 0
+    }
+}
+
+/// A logical segment in the section map of the DBI stream.
+///
+/// `struct OMFSegMapDesc` in Microsoft's code.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DBISectionMapEntry {
+    /// Describes the access and addressing mode of the segment.
+    pub flags: DBISectionMapFlags,
+    /// The logical overlay number.
+    pub overlay: u16,
+    /// The group index into the descriptor array.
+    pub group: u16,
+    /// The frame of the segment, which is the one-based section number in the image for 32-bit
+    /// segments.
+    ///
+    /// This is the section of [`PdbInternalSectionOffset`]s in symbols referring to this segment.
+    pub frame: u16,
+    /// Byte index of the segment name in the `sstSegName` table, or `0xffff`.
+    pub section_name: u16,
+    /// Byte index of the class name in the `sstSegName` table, or `0xffff`.
+    pub class_name: u16,
+    /// Offset of the logical segment within the physical segment.
+    pub offset: u32,
+    /// The size of the segment in bytes.
+    pub section_length: u32,
+}
+
+impl DBISectionMapEntry {
+    fn parse(buf: &mut ParseBuffer<'_>) -> Result<Self> {
+        Ok(Self {
+            flags: DBISectionMapFlags(buf.parse_u16()?),
+            overlay: buf.parse_u16()?,
+            group: buf.parse_u16()?,
+            frame: buf.parse_u16()?,
+            section_name: buf.parse_u16()?,
+            class_name: buf.parse_u16()?,
+            offset: buf.parse_u32()?,
+            section_length: buf.parse_u32()?,
+        })
+    }
+
+    /// Returns whether a segment offset lies within this segment.
+    pub fn contains(&self, offset: u32) -> bool {
+        offset
+            .checked_sub(self.offset)
+            .is_some_and(|relative| relative < self.section_length)
+    }
+}
+
+/// The section map of the DBI stream, describing the logical segments of the image.
+///
+/// Segmented addresses in symbols refer to these segments by their [`frame`]. The map is modelled
+/// after the `sstSegMap` table of CodeView in OMF executables, and can be used to reconstruct such
+/// a table.
+///
+/// Obtain the section map using [`DebugInformation::section_map`].
+///
+/// [`frame`]: DBISectionMapEntry::frame
+#[derive(Clone, Debug)]
+pub struct DBISectionMap<'s> {
+    segments: u16,
+    logical_segments: u16,
+    buf: ParseBuffer<'s>,
+}
+
+impl<'s> DBISectionMap<'s> {
+    fn parse(mut buf: ParseBuffer<'s>) -> Result<Self> {
+        // stripped PDBs may omit the substream entirely
+        if buf.is_empty() {
+            return Ok(Self {
+                segments: 0,
+                logical_segments: 0,
+                buf,
+            });
+        }
+
+        let segments = buf.parse_u16()?;
+        let logical_segments = buf.parse_u16()?;
+        Ok(Self {
+            segments,
+            logical_segments,
+            buf,
+        })
+    }
+
+    /// The number of segment descriptors in the map.
+    pub fn segments(&self) -> u16 {
+        self.segments
+    }
+
+    /// The number of logical segment descriptors in the map.
+    pub fn logical_segments(&self) -> u16 {
+        self.logical_segments
+    }
+
+    /// Returns an iterator over the entries of the section map.
+    pub fn iter(&self) -> DBISectionMapIter<'s> {
+        DBISectionMapIter {
+            buf: self.buf.clone(),
+            remaining: self.segments,
+        }
+    }
+
+    /// Finds the segment descriptor of a frame.
+    ///
+    /// Use this to look up the segment of a [`PdbInternalSectionOffset`] by its `section`.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the section map is truncated
+    pub fn find_frame(&self, frame: u16) -> Result<Option<DBISectionMapEntry>> {
+        self.iter().find(|entry| Ok(entry.frame == frame))
+    }
+}
+
+/// An iterator over the entries of a [`DBISectionMap`].
+#[derive(Clone, Debug)]
+pub struct DBISectionMapIter<'s> {
+    buf: ParseBuffer<'s>,
+    remaining: u16,
+}
+
+impl<'s> FallibleIterator for DBISectionMapIter<'s> {
+    type Item = DBISectionMapEntry;
+    type Error = Error;
+
+    fn next(&mut self) -> result::Result<Option<Self::Item>, Self::Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+        DBISectionMapEntry::parse(&mut self.buf).map(Some)
+    }
+}
+
+/// The file info substream of the DBI stream, listing the source files of every module.
+///
+/// Source files are listed once per module they contribute to, so header files usually appear in
+/// many modules.
+///
+/// Obtain the file info using [`DebugInformation::file_info`].
+#[derive(Clone, Debug)]
+pub struct DBIFileInfo<'s> {
+    /// The index of the first file of every module in `offsets`.
+    ///
+    /// The substream stores 16-bit indices, which overflow for large PDBs, so they are recomputed
+    /// from the file counts.
+    starts: Vec<usize>,
+    /// The number of files of every module.
+    counts: Vec<u16>,
+    /// Offsets of all file names into `names`.
+    offsets: &'s [u8],
+    names: &'s [u8],
+}
+
+impl<'s> DBIFileInfo<'s> {
+    fn parse(mut buf: ParseBuffer<'s>) -> Result<Self> {
+        // stripped PDBs may omit the substream entirely
+        if buf.is_empty() {
+            return Ok(Self {
+                starts: Vec::new(),
+                counts: Vec::new(),
+                offsets: &[],
+                names: &[],
+            });
+        }
+
+        let module_count = buf.parse_u16()? as usize;
+        // the number of source files is truncated to 16 bits, so it is ignored
+        let _file_count = buf.parse_u16()?;
+
+        // skip the 16-bit start indices
+        buf.take(module_count * 2)?;
+
+        let mut starts = Vec::with_capacity(module_count);
+        let mut counts = Vec::with_capacity(module_count);
+        let mut file_count = 0;
+        for _ in 0..module_count {
+            let count = buf.parse_u16()?;
+            starts.push(file_count);
+            counts.push(count);
+            file_count += count as usize;
+        }
+
+        let offsets = buf.take(file_count * 4)?;
+        let names = buf.take(buf.len())?;
+
+        Ok(Self {
+            starts,
+            counts,
+            offsets,
+            names,
+        })
+    }
+
+    /// The number of modules listed in the file info.
+    pub fn module_count(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// The total number of source files of all modules, including duplicates.
+    pub fn file_count(&self) -> usize {
+        self.offsets.len() / 4
+    }
+
+    /// Returns an iterator over the source files of a module.
+    ///
+    /// `module` is the index of the module in [`DebugInformation::modules`]. Returns `None` if
+    /// there is no such module.
+    pub fn module_files(&self, module: usize) -> Option<DBIFileIter<'s>> {
+        let start = *self.starts.get(module)?;
+        let count = self.counts[module] as usize;
+        let offsets = &self.offsets[start * 4..(start + count) * 4];
+        Some(DBIFileIter {
+            offsets: offsets.into(),
+            names: self.names,
+        })
+    }
+
+    /// Returns the distinct source files of all modules, in the order they are first listed.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if a file name is out of bounds
+    pub fn unique_files(&self) -> Result<Vec<RawString<'s>>> {
+        let mut seen = HashSet::new();
+        let mut files = Vec::new();
+
+        let mut offsets = ParseBuffer::from(self.offsets);
+        while !offsets.is_empty() {
+            // every name is stored once, so equal names have equal offsets
+            let offset = offsets.parse_u32()?;
+            if seen.insert(offset) {
+                files.push(parse_file_name(self.names, offset)?);
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Returns an iterator over the source files of all modules, in the order of the modules.
+    ///
+    /// Files contributing to several modules are returned once for every module.
+    pub fn files(&self) -> DBIFileIter<'s> {
+        DBIFileIter {
+            offsets: self.offsets.into(),
+            names: self.names,
+        }
+    }
+}
+
+/// An iterator over source file names of a [`DBIFileInfo`].
+#[derive(Clone, Debug)]
+pub struct DBIFileIter<'s> {
+    offsets: ParseBuffer<'s>,
+    names: &'s [u8],
+}
+
+impl<'s> FallibleIterator for DBIFileIter<'s> {
+    type Item = RawString<'s>;
+    type Error = Error;
+
+    fn next(&mut self) -> result::Result<Option<Self::Item>, Self::Error> {
+        if self.offsets.is_empty() {
+            return Ok(None);
+        }
+
+        let offset = self.offsets.parse_u32()?;
+        parse_file_name(self.names, offset).map(Some)
+    }
+}
+
+/// Reads a file name from the names buffer of the file info substream.
+fn parse_file_name(names: &[u8], offset: u32) -> Result<RawString<'_>> {
+    let names = names.get(offset as usize..).ok_or(Error::UnexpectedEof)?;
+    ParseBuffer::from(names).parse_cstring()
+}
+
+/// The type server map substream of the DBI stream.
+///
+/// Older linkers could leave the types of modules in external type servers, such as compiler PDBs
+/// maintained by `mspdbsrv`, instead of merging them. Modules refer to their type server by a one-based
+/// [`type_server_index`](Module::type_server_index) into this map. The layout of the map is not
+/// documented, so only its raw contents are exposed.
+///
+/// Current linkers always merge the types of `LF_TYPESERVER2` records into the type stream and
+/// write an empty map. To access the types of such modules, see
+/// [`TypeServers`](crate::TypeServers).
+#[derive(Clone, Copy, Debug)]
+pub struct DBITypeServerMap<'s> {
+    data: &'s [u8],
+    mfc_type_server_index: u32,
+}
+
+impl<'s> DBITypeServerMap<'s> {
+    /// Returns whether the map is empty, which is the case for all PDBs written by current
+    /// linkers.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the raw contents of the type server map.
+    pub fn as_bytes(&self) -> &'s [u8] {
+        self.data
+    }
+
+    /// The index of the type server of the MFC library, if any.
+    ///
+    /// Like [`Module::type_server_index`], this is a one-based index into the map.
+    pub fn mfc_type_server_index(&self) -> Option<u32> {
+        match self.mfc_type_server_index {
+            0 => None,
+            index => Some(index),
+        }
+    }
+}
+
+/// The optional debug header of the DBI stream (`DbgDataHdr`), listing auxiliary streams.
+///
+/// Each field is the index of a stream containing a copy of data from the image or additional
+/// information written by the linker, or [`StreamIndex::none`] if the stream is absent. The
+/// streams can be read with the typed accessors of [`PDB`](crate::PDB), such as
+/// [`PDB::sections`](crate::PDB::sections), or raw with [`PDB::raw_stream`](crate::PDB::raw_stream).
+///
+/// Obtain the streams using [`DebugInformation::extra_streams`].
+#[derive(Debug, Copy, Clone, Default)]
+pub struct DBIExtraStreams {
     // The struct itself is defined at:
     //    https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/PDB/dbi/dbi.h#L250-L274
     // It's just an array of stream numbers; `u16`s where 0xffff means "no stream".
@@ -596,39 +1479,47 @@ pub(crate) struct DBIExtraStreams {
     //    https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/langapi/include/pdb.h#L439-L449
     // We'll map those to fields.
     //
-    // The struct itself can be truncated. This is an internal struct; we'll treat missing fields as
-    // StreamIndex::none() even if it's a short read, so long as the short read stops on a u16 boundary.
+    // The struct itself can be truncated. We'll treat missing fields as StreamIndex::none() even if
+    // it's a short read, so long as the short read stops on a u16 boundary.
+    /// Frame pointer omission data (`FPO_DATA`), see [`PDB::frame_table`](crate::PDB::frame_table).
     pub fpo: StreamIndex,
+    /// Exception data of the image.
     pub exception: StreamIndex,
+    /// Fixups applied to the image (`XFIXUP_DATA`), see [`PDB::fixups`](crate::PDB::fixups).
     pub fixup: StreamIndex,
+    /// OMAP from the transformed to the original address space, see
+    /// [`PDB::omap_to_src`](crate::PDB::omap_to_src).
     pub omap_to_src: StreamIndex,
+    /// OMAP from the original to the transformed address space, see
+    /// [`PDB::omap_from_src`](crate::PDB::omap_from_src).
     pub omap_from_src: StreamIndex,
+    /// Section headers of the image, see [`PDB::sections`](crate::PDB::sections).
     pub section_headers: StreamIndex,
+    /// Map from metadata token RIDs of managed code, see
+    /// [`PDB::token_rid_map`](crate::PDB::token_rid_map).
     pub token_rid_map: StreamIndex,
+    /// A copy of the `.xdata` section of the image, see [`PDB::xdata`](crate::PDB::xdata).
     pub xdata: StreamIndex,
+    /// A copy of the `.pdata` section of the image, see [`PDB::pdata`](crate::PDB::pdata).
     pub pdata: StreamIndex,
+    /// New frame data (`FRAMEDATA`), see [`PDB::frame_table`](crate::PDB::frame_table).
     pub framedata: StreamIndex,
+    /// Section headers of the image before it was transformed, see
+    /// [`PDB::original_sections`](crate::PDB::original_sections).
     pub original_section_headers: StreamIndex,
 }
 
 impl DBIExtraStreams {
     pub(crate) fn new(debug_info: &DebugInformation<'_>) -> Result<Self> {
         // calculate the location of the extra stream information
-        let header = debug_info.header;
-        let offset = debug_info.header_len
-            + (header.module_list_size
-                + header.section_contribution_size
-                + header.section_map_size
-                + header.file_info_size
-                + header.type_server_map_size
-                + header.ec_substream_size) as usize;
+        let offset = debug_info.substream_offset(6)?;
 
         // seek
         let mut buf = debug_info.stream.parse_buffer();
         buf.take(offset)?;
 
         // grab that section as bytes
-        let bytes = buf.take(header.debug_header_size as _)?;
+        let bytes = buf.take(debug_info.header.debug_header_size as _)?;
 
         // parse those bytes
         let mut extra_streams_buf = ParseBuffer::from(bytes);
@@ -638,7 +1529,7 @@ impl DBIExtraStreams {
     pub(crate) fn parse(buf: &mut ParseBuffer<'_>) -> Result<Self> {
         // short reads are okay, as are long reads -- this struct is actually an array
         // what's _not_ okay are
-        if buf.len() % 2 != 0 {
+        if !buf.len().is_multiple_of(2) {
             return Err(Error::InvalidStreamLength("DbgDataHdr"));
         }
 
@@ -666,6 +1557,561 @@ impl DBIExtraStreams {
     }
 }
 
+/// The signature of `NewDBIHdr`.
+const DBI_HEADER_SIGNATURE: u32 = 0xffff_ffff;
+
+/// The `fNewVerFmt` bit of the toolchain version in the DBI header.
+const TOOLCHAIN_NEW_VERSION_FORMAT: u16 = 0x8000;
+
+/// The version of section contribution substreams without COFF section indices.
+const SECTION_CONTRIBUTION_V60: u32 = 0xeffe_0000 + 19_970_605;
+
+/// The version of section contribution substreams with COFF section indices.
+const SECTION_CONTRIBUTION_V2: u32 = 0xeffe_0000 + 20_140_516;
+
+/// A module to be written by a [`DebugInfoWriter`].
+///
+/// Use [`DBIModuleEntry::new`] to describe a new module, or [`DBIModuleEntry::from_module`] to
+/// copy an existing one.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DBIModuleEntry {
+    /// The module name, usually the path of the object file.
+    pub module_name: Vec<u8>,
+    /// The object file name, which is the path of the archive for modules from static libraries.
+    pub object_file_name: Vec<u8>,
+    /// The first contribution of the module to a section of the image, if any.
+    pub section: Option<DBISectionContribution>,
+    /// The raw flags of the module, see [`Module::is_ec_enabled`] and
+    /// [`Module::type_server_index`].
+    pub flags: u16,
+    /// The stream containing the symbols and line information of the module.
+    pub stream: StreamIndex,
+    /// The size of the symbols in `stream`, including the signature.
+    pub symbols_size: u32,
+    /// The size of C11 line information in `stream`.
+    pub lines_size: u32,
+    /// The size of C13 line information in `stream`.
+    pub c13_lines_size: u32,
+    /// The source file in the edit-and-continue names, see [`Module::ec_source_file`].
+    pub ec_source_file: Option<StringRef>,
+    /// The compiler PDB in the edit-and-continue names, see [`Module::ec_pdb_file`].
+    pub ec_pdb_file: Option<StringRef>,
+    /// The source files contributing to the module, written to the file info substream.
+    pub source_files: Vec<Vec<u8>>,
+}
+
+impl DBIModuleEntry {
+    /// Creates a module without a stream, section contributions or source files.
+    pub fn new(module_name: &[u8], object_file_name: &[u8]) -> Self {
+        Self {
+            module_name: module_name.to_vec(),
+            object_file_name: object_file_name.to_vec(),
+            stream: StreamIndex::none(),
+            ..Self::default()
+        }
+    }
+
+    /// Creates an entry with the same information as an existing module.
+    ///
+    /// Source files are not part of the module list and must be set separately, for instance from
+    /// [`DBIFileInfo::module_files`].
+    pub fn from_module(module: &Module<'_>) -> Self {
+        let info = module.info();
+        // modules without contributions have a section index of 0xffff
+        let section = match info.section.offset.section {
+            0xffff => None,
+            _ => Some(info.section),
+        };
+
+        Self {
+            module_name: module.module_name.as_bytes().to_vec(),
+            object_file_name: module.object_file_name.as_bytes().to_vec(),
+            section,
+            flags: info.flags,
+            stream: info.stream,
+            symbols_size: info.symbols_size,
+            lines_size: info.lines_size,
+            c13_lines_size: info.c13_lines_size,
+            ec_source_file: module.ec_source_file(),
+            ec_pdb_file: module.ec_pdb_file(),
+            source_files: Vec::new(),
+        }
+    }
+}
+
+/// Builds a debug information (DBI) stream.
+///
+/// The writer holds the header, the module list, section contributions, the section map, the
+/// source files of every module and the optional debug header of a DBI stream. Start from an
+/// existing stream using [`DebugInfoWriter::from_information`], or from scratch using
+/// [`DebugInfoWriter::new`]. The resulting stream can be stored as stream 3 of a PDB, for instance
+/// using an [`MsfEditor`].
+///
+/// The type server map and edit-and-continue substreams are copied verbatim from existing streams
+/// and written empty otherwise.
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// let mut writer = pdb::DebugInfoWriter::new();
+/// writer.set_machine_type(0x8664);
+///
+/// let mut module = pdb::DBIModuleEntry::new(b"c:\\src\\main.obj", b"c:\\src\\main.obj");
+/// module.source_files.push(b"c:\\src\\main.cpp".to_vec());
+/// writer.add_module(module);
+///
+/// let mut data = Vec::new();
+/// writer.write(&mut data)?;
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Clone, Debug)]
+pub struct DebugInfoWriter {
+    version: HeaderVersion,
+    age: u32,
+    gs_symbols_stream: StreamIndex,
+    ps_symbols_stream: StreamIndex,
+    symbol_records_stream: StreamIndex,
+    internal_version: u16,
+    pdb_dll_build_version: u16,
+    pdb_dll_rbld_version: u16,
+    mfc_type_server_index: u32,
+    flags: DBIFlags,
+    machine_type: u16,
+    modules: Vec<DBIModuleEntry>,
+    section_contributions: Vec<DBISectionContribution>,
+    section_map: Vec<DBISectionMapEntry>,
+    type_server_map: Vec<u8>,
+    ec_names: Vec<u8>,
+    extra_streams: DBIExtraStreams,
+}
+
+impl DebugInfoWriter {
+    /// Creates a writer for a Visual C++ 7.0 debug information stream without modules, written by
+    /// the Visual Studio 2015 toolchain.
+    ///
+    /// The stream refers to no symbol streams and has age 1 and an unknown machine type.
+    pub fn new() -> Self {
+        Self {
+            version: HeaderVersion::V70,
+            age: 1,
+            gs_symbols_stream: StreamIndex::none(),
+            ps_symbols_stream: StreamIndex::none(),
+            symbol_records_stream: StreamIndex::none(),
+            internal_version: TOOLCHAIN_NEW_VERSION_FORMAT | (14 << 8),
+            pdb_dll_build_version: 0,
+            pdb_dll_rbld_version: 0,
+            mfc_type_server_index: 0,
+            flags: DBIFlags::default(),
+            machine_type: 0,
+            modules: Vec::new(),
+            section_contributions: Vec::new(),
+            section_map: Vec::new(),
+            type_server_map: Vec::new(),
+            ec_names: Vec::new(),
+            extra_streams: DBIExtraStreams::default(),
+        }
+    }
+
+    /// Creates a writer initialized with the contents of an existing debug information stream.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if one of the substreams is truncated
+    /// * `Error::InvalidStreamLength` if the sizes of the substreams overflow
+    /// * `Error::UnimplementedFeature` if the section contribution substream has an unknown
+    ///   version
+    pub fn from_information(information: &DebugInformation<'_>) -> Result<Self> {
+        let header = information.header();
+        let file_info = information.file_info()?;
+
+        let mut modules = Vec::new();
+        let mut iter = information.modules()?.enumerate();
+        while let Some((index, module)) = iter.next()? {
+            let mut entry = DBIModuleEntry::from_module(&module);
+            if let Some(files) = file_info.module_files(index) {
+                entry.source_files = files.map(|file| Ok(file.as_bytes().to_vec())).collect()?;
+            }
+            modules.push(entry);
+        }
+
+        let mut buf = information.stream.parse_buffer();
+        buf.take(information.substream_offset(4)?)?;
+        let type_server_map = buf.take(header.type_server_map_size as usize)?.to_vec();
+        let ec_names = buf.take(header.ec_substream_size as usize)?.to_vec();
+
+        Ok(Self {
+            version: header.version,
+            age: header.age,
+            gs_symbols_stream: header.gs_symbols_stream,
+            ps_symbols_stream: header.ps_symbols_stream,
+            symbol_records_stream: header.symbol_records_stream,
+            internal_version: header.internal_version,
+            pdb_dll_build_version: header.pdb_dll_build_version,
+            pdb_dll_rbld_version: header.pdb_dll_rbld_version,
+            mfc_type_server_index: header.mfc_type_server_index,
+            flags: header.flags,
+            machine_type: header.machine_type,
+            modules,
+            section_contributions: information.section_contributions()?.collect()?,
+            section_map: information.section_map()?.iter().collect()?,
+            type_server_map,
+            ec_names,
+            extra_streams: information.extra_streams()?,
+        })
+    }
+
+    /// Sets the number of times the PDB has been written by the linker.
+    pub fn set_age(&mut self, age: u32) -> &mut Self {
+        self.age = age;
+        self
+    }
+
+    /// Sets the streams containing the global symbol hash table, the public symbol hash table and
+    /// the symbol records.
+    pub fn set_symbol_streams(
+        &mut self,
+        global_symbols: StreamIndex,
+        public_symbols: StreamIndex,
+        symbol_records: StreamIndex,
+    ) -> &mut Self {
+        self.gs_symbols_stream = global_symbols;
+        self.ps_symbols_stream = public_symbols;
+        self.symbol_records_stream = symbol_records;
+        self
+    }
+
+    /// Sets the major and minor version of the toolchain writing the PDB, such as 14.00.
+    ///
+    /// The major version is truncated to 7 bits.
+    pub fn set_toolchain_version(&mut self, major: u8, minor: u8) -> &mut Self {
+        self.internal_version =
+            TOOLCHAIN_NEW_VERSION_FORMAT | (u16::from(major & 0x7f) << 8) | u16::from(minor);
+        self
+    }
+
+    /// Sets the build and rebuild number of the PDB writer.
+    pub fn set_pdb_dll_version(&mut self, build: u16, rbld: u16) -> &mut Self {
+        self.pdb_dll_build_version = build;
+        self.pdb_dll_rbld_version = rbld;
+        self
+    }
+
+    /// Sets the flags describing how the PDB was produced.
+    pub fn set_flags(&mut self, flags: DBIFlags) -> &mut Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Sets the raw machine type of the image, such as `0x8664` for x86-64.
+    pub fn set_machine_type(&mut self, machine_type: u16) -> &mut Self {
+        self.machine_type = machine_type;
+        self
+    }
+
+    /// Sets the streams listed in the optional debug header.
+    pub fn set_extra_streams(&mut self, extra_streams: DBIExtraStreams) -> &mut Self {
+        self.extra_streams = extra_streams;
+        self
+    }
+
+    /// Returns the modules to be written.
+    pub fn modules(&self) -> &[DBIModuleEntry] {
+        &self.modules
+    }
+
+    /// Returns the modules to be written for modification.
+    pub fn modules_mut(&mut self) -> &mut [DBIModuleEntry] {
+        &mut self.modules
+    }
+
+    /// Appends a module and returns its index, which section contributions refer to.
+    pub fn add_module(&mut self, module: DBIModuleEntry) -> usize {
+        self.modules.push(module);
+        self.modules.len() - 1
+    }
+
+    /// Appends a section contribution.
+    ///
+    /// Contributions should be added in order of their address. If any contribution has a
+    /// [`coff_section`](DBISectionContribution::coff_section), the substream is written in version
+    /// 2 and contributions without one are written with a COFF section of 0.
+    pub fn add_section_contribution(&mut self, contribution: DBISectionContribution) -> &mut Self {
+        self.section_contributions.push(contribution);
+        self
+    }
+
+    /// Appends an entry to the section map.
+    pub fn add_section_map_entry(&mut self, entry: DBISectionMapEntry) -> &mut Self {
+        self.section_map.push(entry);
+        self
+    }
+
+    /// Writes the debug information stream.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnimplementedFeature` if there are more modules, source files of a module or
+    ///   section map entries than the format supports
+    /// * `Error::IoError` if returned by the `Write`
+    pub fn write<W: std::io::Write>(&self, mut writer: W) -> Result<()> {
+        let modules = self.write_modules()?;
+        let section_contributions = self.write_section_contributions()?;
+        let section_map = self.write_section_map()?;
+        let file_info = self.write_file_info()?;
+        let ec_names = if self.ec_names.is_empty() {
+            empty_ec_names()
+        } else {
+            self.ec_names.clone()
+        };
+        let extra_streams = self.extra_streams.to_bytes();
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&DBI_HEADER_SIGNATURE.to_le_bytes());
+        data.extend_from_slice(&u32::from(self.version).to_le_bytes());
+        data.extend_from_slice(&self.age.to_le_bytes());
+        data.extend_from_slice(&self.gs_symbols_stream.0.to_le_bytes());
+        data.extend_from_slice(&self.internal_version.to_le_bytes());
+        data.extend_from_slice(&self.ps_symbols_stream.0.to_le_bytes());
+        data.extend_from_slice(&self.pdb_dll_build_version.to_le_bytes());
+        data.extend_from_slice(&self.symbol_records_stream.0.to_le_bytes());
+        data.extend_from_slice(&self.pdb_dll_rbld_version.to_le_bytes());
+        for substream in [
+            &modules,
+            &section_contributions,
+            &section_map,
+            &file_info,
+            &self.type_server_map,
+        ] {
+            data.extend_from_slice(&(substream.len() as u32).to_le_bytes());
+        }
+        data.extend_from_slice(&self.mfc_type_server_index.to_le_bytes());
+        data.extend_from_slice(&(extra_streams.len() as u32).to_le_bytes());
+        data.extend_from_slice(&(ec_names.len() as u32).to_le_bytes());
+        data.extend_from_slice(&self.flags.0.to_le_bytes());
+        data.extend_from_slice(&self.machine_type.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        for substream in [
+            &modules,
+            &section_contributions,
+            &section_map,
+            &file_info,
+            &self.type_server_map,
+            &ec_names,
+            &extra_streams,
+        ] {
+            data.extend_from_slice(substream);
+        }
+
+        writer.write_all(&data)?;
+        Ok(())
+    }
+
+    fn write_modules(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        for module in &self.modules {
+            // `opened` is only meaningful in memory
+            data.extend_from_slice(&0u32.to_le_bytes());
+            match module.section {
+                Some(section) => write_section_contribution(&mut data, &section)?,
+                None => {
+                    data.extend_from_slice(&0xffffu16.to_le_bytes());
+                    data.extend_from_slice(&0u16.to_le_bytes());
+                    data.extend_from_slice(&0u32.to_le_bytes());
+                    data.extend_from_slice(&u32::MAX.to_le_bytes());
+                    data.extend_from_slice(&0u32.to_le_bytes());
+                    data.extend_from_slice(&0xffffu16.to_le_bytes());
+                    data.extend_from_slice(&[0; 10]);
+                }
+            }
+            data.extend_from_slice(&module.flags.to_le_bytes());
+            data.extend_from_slice(&module.stream.0.to_le_bytes());
+            data.extend_from_slice(&module.symbols_size.to_le_bytes());
+            data.extend_from_slice(&module.lines_size.to_le_bytes());
+            data.extend_from_slice(&module.c13_lines_size.to_le_bytes());
+            data.extend_from_slice(&source_file_count(module)?.to_le_bytes());
+            data.extend_from_slice(&0u16.to_le_bytes());
+            // the offset of the file names is only meaningful in memory
+            data.extend_from_slice(&0u32.to_le_bytes());
+            data.extend_from_slice(&module.ec_source_file.map_or(0, |r| r.0).to_le_bytes());
+            data.extend_from_slice(&module.ec_pdb_file.map_or(0, |r| r.0).to_le_bytes());
+            data.extend_from_slice(&module.module_name);
+            data.push(0);
+            data.extend_from_slice(&module.object_file_name);
+            data.push(0);
+            align(&mut data);
+        }
+        Ok(data)
+    }
+
+    fn write_section_contributions(&self) -> Result<Vec<u8>> {
+        let v2 = self
+            .section_contributions
+            .iter()
+            .any(|contribution| contribution.coff_section.is_some());
+
+        let version = if v2 {
+            SECTION_CONTRIBUTION_V2
+        } else {
+            SECTION_CONTRIBUTION_V60
+        };
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&version.to_le_bytes());
+        for contribution in &self.section_contributions {
+            write_section_contribution(&mut data, contribution)?;
+            if v2 {
+                let coff_section = contribution.coff_section.unwrap_or(0);
+                data.extend_from_slice(&coff_section.to_le_bytes());
+            }
+        }
+        Ok(data)
+    }
+
+    fn write_section_map(&self) -> Result<Vec<u8>> {
+        if self.section_map.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let count = u16::try_from(self.section_map.len())
+            .map_err(|_| Error::UnimplementedFeature("too many section map entries"))?;
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&count.to_le_bytes());
+        data.extend_from_slice(&count.to_le_bytes());
+        for entry in &self.section_map {
+            data.extend_from_slice(&entry.flags.0.to_le_bytes());
+            data.extend_from_slice(&entry.overlay.to_le_bytes());
+            data.extend_from_slice(&entry.group.to_le_bytes());
+            data.extend_from_slice(&entry.frame.to_le_bytes());
+            data.extend_from_slice(&entry.section_name.to_le_bytes());
+            data.extend_from_slice(&entry.class_name.to_le_bytes());
+            data.extend_from_slice(&entry.offset.to_le_bytes());
+            data.extend_from_slice(&entry.section_length.to_le_bytes());
+        }
+        Ok(data)
+    }
+
+    fn write_file_info(&self) -> Result<Vec<u8>> {
+        let module_count = u16::try_from(self.modules.len())
+            .map_err(|_| Error::UnimplementedFeature("too many modules"))?;
+
+        // every name is stored once
+        let mut names = Vec::new();
+        let mut offsets = HashMap::new();
+        let mut name_offsets = Vec::new();
+        for module in &self.modules {
+            for file in &module.source_files {
+                let offset = *offsets.entry(file.as_slice()).or_insert_with(|| {
+                    let offset = names.len() as u32;
+                    names.extend_from_slice(file);
+                    names.push(0);
+                    offset
+                });
+                name_offsets.push(offset);
+            }
+        }
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&module_count.to_le_bytes());
+        // the file count and start indices are truncated to 16 bits, like the Microsoft toolchain
+        data.extend_from_slice(&(name_offsets.len() as u16).to_le_bytes());
+        let mut start = 0usize;
+        for module in &self.modules {
+            data.extend_from_slice(&(start as u16).to_le_bytes());
+            start += module.source_files.len();
+        }
+        for module in &self.modules {
+            data.extend_from_slice(&source_file_count(module)?.to_le_bytes());
+        }
+        for offset in name_offsets {
+            data.extend_from_slice(&offset.to_le_bytes());
+        }
+        data.extend_from_slice(&names);
+        align(&mut data);
+        Ok(data)
+    }
+}
+
+impl Default for DebugInfoWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DBIExtraStreams {
+    /// Serializes all stream indices of the optional debug header.
+    fn to_bytes(self) -> Vec<u8> {
+        let indices = [
+            self.fpo,
+            self.exception,
+            self.fixup,
+            self.omap_to_src,
+            self.omap_from_src,
+            self.section_headers,
+            self.token_rid_map,
+            self.xdata,
+            self.pdata,
+            self.framedata,
+            self.original_section_headers,
+        ];
+
+        indices
+            .iter()
+            .flat_map(|index| index.0.to_le_bytes())
+            .collect()
+    }
+}
+
+/// Returns the number of source files of a module, which is stored in 16 bits.
+fn source_file_count(module: &DBIModuleEntry) -> Result<u16> {
+    u16::try_from(module.source_files.len())
+        .map_err(|_| Error::UnimplementedFeature("too many source files in a module"))
+}
+
+/// Serializes a section contribution in the `SC` layout.
+fn write_section_contribution(
+    data: &mut Vec<u8>,
+    contribution: &DBISectionContribution,
+) -> Result<()> {
+    let module = u16::try_from(contribution.module)
+        .map_err(|_| Error::UnimplementedFeature("too many modules"))?;
+
+    data.extend_from_slice(&contribution.offset.section.to_le_bytes());
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(&contribution.offset.offset.to_le_bytes());
+    data.extend_from_slice(&contribution.size.to_le_bytes());
+    data.extend_from_slice(&contribution.characteristics.0.to_le_bytes());
+    data.extend_from_slice(&module.to_le_bytes());
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(&contribution.data_crc.to_le_bytes());
+    data.extend_from_slice(&contribution.reloc_crc.to_le_bytes());
+    Ok(())
+}
+
+/// Returns an edit-and-continue names table without any names.
+fn empty_ec_names() -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&0xeffe_effeu32.to_le_bytes());
+    // hash version, followed by the names, which only contain the empty string
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.push(0);
+    // a single empty hash bucket and the number of names
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data
+}
+
+/// Pads a substream to a multiple of four bytes.
+fn align(data: &mut Vec<u8>) {
+    data.resize(data.len().next_multiple_of(4), 0);
+}
+
 #[cfg(test)]
 mod tests {
     use crate::dbi::*;
@@ -689,4 +2135,41 @@ mod tests {
         assert_eq!(extra_streams.token_rid_map, StreamIndex::none());
         assert_eq!(extra_streams.original_section_headers, StreamIndex::none());
     }
+
+    #[test]
+    fn test_section_contributions_v2() {
+        let mut bytes = (0xeffe_0000u32 + 20_140_516).to_le_bytes().to_vec();
+        bytes.extend_from_slice(&[0x02, 0x00, 0x00, 0x00]); // section, padding
+        bytes.extend_from_slice(&0x1234u32.to_le_bytes()); // offset
+        bytes.extend_from_slice(&0x10u32.to_le_bytes()); // size
+        bytes.extend_from_slice(&0x6000_0020u32.to_le_bytes()); // characteristics
+        bytes.extend_from_slice(&[0x05, 0x00, 0x00, 0x00]); // module, padding
+        bytes.extend_from_slice(&[0; 8]); // data_crc, reloc_crc
+        bytes.extend_from_slice(&0x07u32.to_le_bytes()); // coff section
+
+        let mut iter = DBISectionContributionIter::parse(bytes.as_slice().into()).expect("parse");
+        let contribution = iter.next().expect("next").expect("contribution");
+        assert_eq!(contribution.offset.section, 2);
+        assert_eq!(contribution.offset.offset, 0x1234);
+        assert_eq!(contribution.size, 0x10);
+        assert_eq!(contribution.module, 5);
+        assert_eq!(contribution.coff_section, Some(7));
+        assert!(iter.next().expect("next").is_none());
+    }
+
+    #[test]
+    fn test_section_contributions_unknown_version() {
+        let bytes = 0xeffe_0000u32.to_le_bytes();
+        let result = DBISectionContributionIter::parse(bytes[..].into());
+        assert!(matches!(result, Err(Error::UnimplementedFeature(_))));
+    }
+
+    #[test]
+    fn test_machine_pointer_size() {
+        assert_eq!(MachineType::X86.pointer_size(), Some(4));
+        assert_eq!(MachineType::ArmNT.pointer_size(), Some(4));
+        assert_eq!(MachineType::Amd64.pointer_size(), Some(8));
+        assert_eq!(MachineType::Arm64.pointer_size(), Some(8));
+        assert_eq!(MachineType::Unknown.pointer_size(), None);
+    }
 }
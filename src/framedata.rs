@@ -480,7 +480,7 @@ fn binary_search_by_rva<R: AddrRange>(frames: &[R], rva: PdbInternalRva) -> usiz
 /// #
 /// # fn test() -> pdb::Result<()> {
 /// # let source = std::fs::File::open("fixtures/self/foo.pdb")?;
-/// let mut pdb = PDB::open(source)?;
+/// let pdb = PDB::open(source)?;
 ///
 /// // Read the frame table once and reuse it
 /// let frame_table = pdb.frame_table()?;
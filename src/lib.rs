@@ -20,7 +20,7 @@
 //! #
 //! # fn test() -> pdb::Result<usize> {
 //! let file = std::fs::File::open("fixtures/self/foo.pdb")?;
-//! let mut pdb = pdb::PDB::open(file)?;
+//! let pdb = pdb::PDB::open(file)?;
 //!
 //! let symbol_table = pdb.global_symbols()?;
 //! let address_map = pdb.address_map()?;
@@ -50,30 +50,44 @@
 mod common;
 mod dbi;
 mod framedata;
+mod link_info;
 mod modi;
 mod msf;
 mod omap;
 mod pdb;
 mod pdbi;
 mod pe;
+mod salvage;
 mod source;
 mod strings;
 mod symbol;
+mod tm_cache;
 mod tpi;
+mod type_server;
 
 // exports
 pub use crate::common::*;
 pub use crate::dbi::*;
 pub use crate::framedata::*;
+pub use crate::link_info::*;
 pub use crate::modi::*;
+#[cfg(feature = "hash")]
+pub use crate::msf::HashAlgorithm;
+pub use crate::msf::{
+    FreePageMap, FreePageMapIter, MemoryLimitPolicy, MsfEditor, MsfInfo, MsfVersion, MsfWriter,
+    PreviousDirectory, StreamEntry, StreamIter, StreamLayout, StreamReader,
+};
 pub use crate::omap::*;
 pub use crate::pdb::*;
 pub use crate::pdbi::*;
 pub use crate::pe::*;
+pub use crate::salvage::*;
 pub use crate::source::*;
 pub use crate::strings::*;
 pub use crate::symbol::*;
+pub use crate::tm_cache::*;
 pub use crate::tpi::*;
+pub use crate::type_server::*;
 
 // re-export FallibleIterator for convenience
 #[doc(no_inline)]
@@ -0,0 +1,185 @@
+//! Parsing of the `/LinkInfo` named stream.
+//!
+//! The linker records the environment it was invoked in within this stream: the working
+//! directory, the full command line and the path of the output file. It corresponds to the
+//! `LinkInfo` structure declared in `pdb.h` of the Microsoft PDB sources.
+
+use std::ops::Range;
+
+use crate::common::*;
+use crate::msf::Stream;
+
+/// Size of the fixed `LinkInfo` header in bytes.
+const LINK_INFO_HEADER_SIZE: usize = 24;
+
+/// Byte ranges of the strings within the `/LinkInfo` stream.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct LinkInfoLayout {
+    version: u32,
+    cwd: Range<usize>,
+    command: Range<usize>,
+    output_file: Range<usize>,
+    libraries: Range<usize>,
+}
+
+impl LinkInfoLayout {
+    fn parse(data: &[u8]) -> Result<Self> {
+        let mut buf = ParseBuffer::from(data);
+        let size = buf.parse_u32()? as usize;
+        let version = buf.parse_u32()?;
+        let cwd_offset = buf.parse_u32()? as usize;
+        let command_offset = buf.parse_u32()? as usize;
+        let output_file_index = buf.parse_u32()? as usize;
+        let libraries_offset = buf.parse_u32()? as usize;
+
+        if size < LINK_INFO_HEADER_SIZE || size > data.len() {
+            return Err(Error::InvalidStreamLength("LinkInfo"));
+        }
+
+        let data = &data[..size];
+        let cwd = cstring_range(data, cwd_offset)?;
+        let command = cstring_range(data, command_offset)?;
+
+        // The output file is stored as an index into the command line.
+        let output_file = match command.start.checked_add(output_file_index) {
+            Some(start) if start <= command.end => start..command.end,
+            _ => return Err(Error::UnexpectedEof),
+        };
+
+        if libraries_offset > size {
+            return Err(Error::UnexpectedEof);
+        }
+
+        Ok(Self {
+            version,
+            cwd,
+            command,
+            output_file,
+            libraries: libraries_offset..size,
+        })
+    }
+}
+
+/// Returns the range of the NUL-terminated string starting at `offset`, excluding the terminator.
+fn cstring_range(data: &[u8], offset: usize) -> Result<Range<usize>> {
+    let tail = data.get(offset..).ok_or(Error::UnexpectedEof)?;
+    let len = tail
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or(Error::UnexpectedEof)?;
+    Ok(offset..offset + len)
+}
+
+/// Information about the linker invocation that produced a PDB.
+///
+/// This is read from the `/LinkInfo` named stream by [`PDB::link_info`](crate::PDB::link_info).
+/// All strings are returned as raw bytes in the encoding used by the linker.
+#[derive(Debug)]
+pub struct LinkInfo<'s> {
+    stream: Stream<'s>,
+    layout: LinkInfoLayout,
+}
+
+impl<'s> LinkInfo<'s> {
+    pub(crate) fn parse(stream: Stream<'s>) -> Result<Self> {
+        let layout = LinkInfoLayout::parse(stream.as_slice())?;
+        Ok(Self { stream, layout })
+    }
+
+    fn string(&self, range: &Range<usize>) -> RawString<'_> {
+        RawString::from(&self.stream.as_slice()[range.clone()])
+    }
+
+    /// The version of the `LinkInfo` structure.
+    pub fn version(&self) -> u32 {
+        self.layout.version
+    }
+
+    /// The working directory of the linker.
+    pub fn cwd(&self) -> RawString<'_> {
+        self.string(&self.layout.cwd)
+    }
+
+    /// The command line passed to the linker.
+    pub fn command(&self) -> RawString<'_> {
+        self.string(&self.layout.command)
+    }
+
+    /// The path of the linked output file, as it appears within the command line.
+    pub fn output_file(&self) -> RawString<'_> {
+        self.string(&self.layout.output_file)
+    }
+
+    /// The libraries recorded after the command line.
+    ///
+    /// Current linkers usually leave this list empty.
+    pub fn libraries(&self) -> Vec<RawString<'_>> {
+        let data = &self.stream.as_slice()[self.layout.libraries.clone()];
+        data.split(|&b| b == 0)
+            .filter(|lib| !lib.is_empty())
+            .map(RawString::from)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(cwd: &[u8], command: &[u8], output_index: u32, libs: &[&[u8]]) -> Vec<u8> {
+        let cwd_offset = LINK_INFO_HEADER_SIZE;
+        let command_offset = cwd_offset + cwd.len() + 1;
+        let libs_offset = command_offset + command.len() + 1;
+        let libs_size: usize = libs.iter().map(|l| l.len() + 1).sum();
+        let size = libs_offset + libs_size;
+
+        let mut data = Vec::new();
+        for value in &[
+            size,
+            1,
+            cwd_offset,
+            command_offset,
+            output_index as usize,
+            libs_offset,
+        ] {
+            data.extend_from_slice(&(*value as u32).to_le_bytes());
+        }
+        data.extend_from_slice(cwd);
+        data.push(0);
+        data.extend_from_slice(command);
+        data.push(0);
+        for lib in libs {
+            data.extend_from_slice(lib);
+            data.push(0);
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse() {
+        let data = build(b"C:\\src", b"/OUT:foo.exe", 5, &[b"a.lib", b"b.lib"]);
+        let layout = LinkInfoLayout::parse(&data).expect("parse");
+
+        assert_eq!(layout.version, 1);
+        assert_eq!(&data[layout.cwd], b"C:\\src");
+        assert_eq!(&data[layout.command], b"/OUT:foo.exe");
+        assert_eq!(&data[layout.output_file], b"foo.exe");
+        assert_eq!(&data[layout.libraries], b"a.lib\0b.lib\0");
+    }
+
+    #[test]
+    fn test_parse_truncated() {
+        let data = build(b"C:\\src", b"/OUT:foo.exe", 5, &[]);
+        assert!(LinkInfoLayout::parse(&data[..LINK_INFO_HEADER_SIZE - 1]).is_err());
+        assert!(LinkInfoLayout::parse(&data[..data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_parse_invalid_output_index() {
+        let data = build(b"C:\\src", b"/OUT:foo.exe", 100, &[]);
+        assert!(matches!(
+            LinkInfoLayout::parse(&data),
+            Err(Error::UnexpectedEof)
+        ));
+    }
+}
@@ -38,7 +38,7 @@ enum DebugSubsectionKind {
 impl DebugSubsectionKind {
     fn parse(value: u32) -> Result<Option<Self>> {
         if (0xf1..=0xfd).contains(&value) {
-            Ok(Some(unsafe { std::mem::transmute(value) }))
+            Ok(Some(unsafe { std::mem::transmute::<u32, Self>(value) }))
         } else if value == constants::DEBUG_S_IGNORE {
             Ok(None)
         } else {
@@ -345,6 +345,7 @@ enum LineEntry {
     /// Declares a source line number.
     Number(LineNumberEntry),
     /// Declares a debugging marker.
+    #[allow(dead_code)] // reason = "debugger instructions are not exposed"
     Marker(LineMarkerEntry),
 }
 
@@ -587,7 +588,7 @@ impl FileChecksumKind {
     /// Parses the checksum kind from its raw value.
     fn parse(value: u8) -> Result<Self> {
         if value <= 3 {
-            Ok(unsafe { std::mem::transmute(value) })
+            Ok(unsafe { std::mem::transmute::<u8, Self>(value) })
         } else {
             Err(Error::UnimplementedFileChecksumKind(value))
         }
@@ -1032,7 +1033,7 @@ impl<'a> FallibleIterator for LineIterator<'a> {
                     kind: line_entry.kind,
                 };
 
-                let mut last_info = match std::mem::replace(&mut self.last_info, Some(line_info)) {
+                let mut last_info = match self.last_info.replace(line_info) {
                     Some(last_info) => last_info,
                     None => continue,
                 };
@@ -1222,7 +1223,7 @@ impl<'a> FallibleIterator for InlineeLineIterator<'a> {
 
             // Finish the previous record and emit it. The current record is stored so that the
             // length can be inferred from subsequent operators or the next line info.
-            if let Some(last_info) = std::mem::replace(&mut self.last_info, Some(line_info)) {
+            if let Some(last_info) = self.last_info.replace(line_info) {
                 return Ok(Some(last_info));
             }
         }
@@ -1241,6 +1242,18 @@ impl<'a> Inlinee<'a> {
         self.0.inlinee
     }
 
+    /// The index of the source file declaring the inlinee in the file checksums of this module.
+    ///
+    /// Resolve it using [`LineProgram::get_file_info`](crate::LineProgram::get_file_info).
+    pub fn file_index(&self) -> FileIndex {
+        self.0.file_id
+    }
+
+    /// The line number at which the inlinee is declared in its source file.
+    pub fn line(&self) -> u32 {
+        self.0.line
+    }
+
     /// Returns an iterator over line records for an inline site.
     ///
     /// Note that line records are not guaranteed to be ordered by source code offset. If a
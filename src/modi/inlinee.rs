@@ -0,0 +1,179 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::HashMap;
+
+use crate::common::*;
+use crate::modi::{Inlinee, LineProgram, ModuleInfo};
+use crate::strings::StringTable;
+use crate::tpi::{IdFinder, TypeFinder};
+use crate::FallibleIterator;
+
+/// An inlined function, as resolved by [`InlineeResolver::resolve`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InlineeInfo<'a> {
+    /// The index of the function in the id information stream (IPI).
+    pub index: IdIndex,
+    /// The qualified name of the function, see
+    /// [`IdFinder::function_name`](crate::ItemFinder::function_name).
+    pub name: String,
+    /// The path of the source file declaring the function.
+    ///
+    /// This is `None` if the module has no inlinee information for the function, or no string
+    /// table was given to the resolver.
+    pub file: Option<RawString<'a>>,
+    /// The line at which the function is declared in its source file.
+    ///
+    /// This is `None` if the module has no inlinee information for the function.
+    pub line: Option<u32>,
+}
+
+/// Resolves the inlinees of [`InlineSiteSymbol`](crate::InlineSiteSymbol)s in a module.
+///
+/// An inline site only refers to the inlined function by the index of its `LF_FUNC_ID` or
+/// `LF_MFUNC_ID` record. The name of the function is stored in the id information stream, the
+/// name of its class in the type information stream, and the source file and line declaring it in
+/// the inlinee lines of the module, whose file names are stored in the PDB's string table.
+/// `InlineeResolver` joins all of them.
+///
+/// Inlinee information is specific to the module containing the inline site, so a resolver is
+/// created for every module.
+///
+/// # Example
+///
+/// ```
+/// # use pdb::FallibleIterator;
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(file)?;
+/// let string_table = pdb.string_table()?;
+///
+/// let id_information = pdb.id_information()?;
+/// let mut id_finder = id_information.finder();
+/// let mut ids = id_information.iter();
+/// while let Some(_) = ids.next()? {
+///     id_finder.update(&ids);
+/// }
+///
+/// let debug_information = pdb.debug_information()?;
+/// let mut modules = debug_information.modules()?;
+/// while let Some(module) = modules.next()? {
+///     let info = match pdb.module_info(&module)? {
+///         Some(info) => info,
+///         None => continue,
+///     };
+///
+///     let mut resolver = pdb::InlineeResolver::new(&info, &id_finder)?;
+///     resolver.set_string_table(&string_table);
+///
+///     let mut symbols = info.symbols()?;
+///     while let Some(symbol) = symbols.next()? {
+///         if let Ok(pdb::SymbolData::InlineSite(site)) = symbol.parse() {
+///             let inlinee = resolver.resolve(site.inlinee)?;
+///             if let (Some(file), Some(line)) = (inlinee.file, inlinee.line) {
+///                 println!("{} ({}:{})", inlinee.name, file, line);
+///             }
+///         }
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+pub struct InlineeResolver<'a, 't, 's> {
+    id_finder: &'a IdFinder<'t>,
+    type_finder: Option<&'a TypeFinder<'t>>,
+    string_table: Option<&'a StringTable<'s>>,
+    line_program: Option<LineProgram<'a>>,
+    inlinees: HashMap<IdIndex, Inlinee<'a>>,
+}
+
+impl<'a, 't, 's> InlineeResolver<'a, 't, 's> {
+    /// Creates a resolver for the inline sites of a module.
+    ///
+    /// `id_finder` must know all function ids referenced by the module, so it should be fully
+    /// populated.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the line information of the module is truncated
+    pub fn new(module: &'a ModuleInfo<'_>, id_finder: &'a IdFinder<'t>) -> Result<Self> {
+        let line_program = match module.line_program() {
+            Ok(program) => Some(program),
+            Err(Error::UnimplementedFeature(_)) => None,
+            Err(error) => return Err(error),
+        };
+
+        let mut inlinees = HashMap::new();
+        let mut iter = module.inlinees()?;
+        while let Some(inlinee) = iter.next()? {
+            inlinees.entry(inlinee.index()).or_insert(inlinee);
+        }
+
+        Ok(Self {
+            id_finder,
+            type_finder: None,
+            string_table: None,
+            line_program,
+            inlinees,
+        })
+    }
+
+    /// Sets the type finder used to look up the classes of member functions.
+    ///
+    /// Without a type finder, member functions are named without their class.
+    pub fn set_type_finder(&mut self, type_finder: &'a TypeFinder<'t>) -> &mut Self {
+        self.type_finder = Some(type_finder);
+        self
+    }
+
+    /// Sets the string table of the PDB, which holds the names of source files.
+    ///
+    /// Without a string table, the source file of inlinees is not resolved.
+    pub fn set_string_table(&mut self, string_table: &'a StringTable<'s>) -> &mut Self {
+        self.string_table = Some(string_table);
+        self
+    }
+
+    /// Returns the inlinee information of the module for a function, if any.
+    ///
+    /// Use [`Inlinee::lines`] to compute the line information of an inline site.
+    pub fn inlinee(&self, index: IdIndex) -> Option<&Inlinee<'a>> {
+        self.inlinees.get(&index)
+    }
+
+    /// Resolves the name, source file and line of an inlined function.
+    ///
+    /// `index` is the [`inlinee`](crate::InlineSiteSymbol::inlinee) of an inline site in the
+    /// module of this resolver.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedIdKind(index)` if the id is not a function
+    /// * `Error::TypeNotFound(index)` if the id or one of the records it refers to does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if an id or type is not yet known by a finder
+    /// * `Error::UnexpectedEof` if the source file of the inlinee is out of bounds
+    pub fn resolve(&self, index: IdIndex) -> Result<InlineeInfo<'a>> {
+        let name = self.id_finder.function_name(index, self.type_finder)?;
+
+        let inlinee = self.inlinee(index);
+        let file = match (inlinee, &self.line_program, self.string_table) {
+            (Some(inlinee), Some(program), Some(strings)) => {
+                let file = program.get_file_info(inlinee.file_index())?;
+                Some(strings.get(file.name)?)
+            }
+            _ => None,
+        };
+
+        Ok(InlineeInfo {
+            index,
+            name,
+            file,
+            line: inlinee.map(Inlinee::line),
+        })
+    }
+}
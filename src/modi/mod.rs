@@ -3,16 +3,19 @@ use std::fmt;
 use crate::common::*;
 use crate::dbi::Module;
 use crate::msf::Stream;
-use crate::symbol::SymbolIter;
+use crate::symbol::constants::S_BUILDINFO;
+use crate::symbol::{SymbolData, SymbolIter};
 use crate::FallibleIterator;
 
 mod c13;
-mod constants;
+pub(crate) mod constants;
+mod inlinee;
 
 pub use c13::{
     CrossModuleExportIter, CrossModuleExports, CrossModuleImports, Inlinee, InlineeIterator,
     InlineeLineIterator,
 };
+pub use inlinee::{InlineeInfo, InlineeResolver};
 
 #[derive(Clone, Copy, Debug)]
 enum LinesSize {
@@ -48,8 +51,22 @@ impl<'s> ModuleInfo<'s> {
         }
     }
 
+    /// Creates a `ModuleInfo` from a stream with C13 line information, given the size of its
+    /// symbol records and line information.
+    pub(crate) fn with_c13_lines(
+        stream: Stream<'s>,
+        symbols_size: usize,
+        c13_lines_size: usize,
+    ) -> Self {
+        ModuleInfo {
+            stream,
+            symbols_size,
+            lines_size: LinesSize::C13(c13_lines_size),
+        }
+    }
+
     fn lines_data(&self, size: usize) -> &[u8] {
-        let start = self.symbols_size as usize;
+        let start = self.symbols_size;
         &self.stream[start..start + size]
     }
 
@@ -116,6 +133,29 @@ impl<'s> ModuleInfo<'s> {
             LinesSize::C13(size) => CrossModuleImports::parse(self.lines_data(size))?,
         })
     }
+
+    /// Returns the index of the build information of this module in the id information stream.
+    ///
+    /// This is read from the `S_BUILDINFO` symbol of the module. Resolve it with
+    /// [`IdFinder::build_info`](crate::ItemFinder::build_info). Modules not compiled by MSVC, such
+    /// as import libraries and `* Linker *`, usually have no build information.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnimplementedFeature` if the symbol data of the module is in an unsupported format
+    /// * `Error::UnexpectedEof` if the symbols of the module are truncated
+    pub fn build_info_id(&self) -> Result<Option<IdIndex>> {
+        let mut symbols = self.symbols()?;
+        while let Some(symbol) = symbols.next()? {
+            if symbol.raw_kind() == S_BUILDINFO {
+                if let SymbolData::BuildInfo(data) = symbol.parse()? {
+                    return Ok(Some(data.id));
+                }
+            }
+        }
+
+        Ok(None)
+    }
 }
 
 /// Checksum of a source file's contents.
@@ -151,20 +191,15 @@ pub struct FileInfo<'a> {
 }
 
 /// The kind of source construct a line info is referring to.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub enum LineInfoKind {
     /// A source code expression.
     Expression,
     /// A source code statement.
+    #[default]
     Statement,
 }
 
-impl Default for LineInfoKind {
-    fn default() -> Self {
-        Self::Statement
-    }
-}
-
 /// Mapping of a source code offset to a source file location.
 ///
 /// A line entry is always valid up to the subsequent entry.
@@ -201,7 +236,7 @@ impl LineInfo {
 
         if self.offset <= end_offset {
             let length = end_offset.offset - self.offset.offset;
-            if self.length.map_or(true, |l| l > length) {
+            if self.length.is_none_or(|l| l > length) {
                 self.length = Some(length);
             }
         }
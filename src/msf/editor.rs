@@ -0,0 +1,705 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io;
+
+use crate::common::*;
+use crate::msf::writer::write_msf;
+use crate::msf::{big, is_free_page_map, open_msf, Msf, MsfVersion, PageNumber};
+use crate::source::Source;
+
+/// Replaces, appends or removes individual streams of an existing multi-stream file.
+///
+/// The editor opens an existing MSF (such as a PDB) and records changes to its streams in memory.
+/// Calling [`write`](Self::write) emits a new file that contains the unmodified streams of the
+/// original file along with all changes, complete with a new stream table and free page maps.
+/// Stream numbers of unmodified streams are preserved.
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let mut editor = pdb::MsfEditor::open(file)?;
+///
+/// // swap out the contents of an existing stream and add a new one
+/// editor.replace_stream(208, b"goodbye world\n".to_vec())?;
+/// let stream_number = editor.append_stream(b"new stream".to_vec());
+///
+/// let mut output = Vec::new();
+/// editor.write(&mut output)?;
+///
+/// let pdb = pdb::PDB::open(std::io::Cursor::new(output))?;
+/// let stream = pdb.raw_stream(pdb::StreamIndex(208))?.expect("stream exists");
+/// assert_eq!(stream.as_slice(), b"goodbye world\n");
+/// # assert!(pdb.raw_stream(pdb::StreamIndex(stream_number as u16))?.is_some());
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+pub struct MsfEditor<'s, S> {
+    msf: Box<dyn Msf<'s, S> + 's>,
+    stream_count: u32,
+    changes: BTreeMap<u32, Option<Vec<u8>>>,
+}
+
+impl<'s, S: Source<'s> + 's> MsfEditor<'s, S> {
+    /// Opens an existing multi-stream file for editing.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnrecognizedFileFormat` if the `Source` does not appear to be an MSF file
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange`, `Error::InvalidPageSize` if the MSF file seems corrupt
+    pub fn open(source: S) -> Result<Self> {
+        let msf = open_msf(source)?;
+        let stream_count = msf.stream_count();
+
+        Ok(MsfEditor {
+            msf,
+            stream_count,
+            changes: BTreeMap::new(),
+        })
+    }
+
+    /// Returns the number of streams after applying all changes.
+    pub fn stream_count(&self) -> u32 {
+        self.stream_count
+    }
+
+    /// Replaces the contents of an existing stream.
+    ///
+    /// The stream may have been marked as not present in the original file.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the stream number exceeds the number of streams
+    pub fn replace_stream(&mut self, stream_number: u32, data: Vec<u8>) -> Result<()> {
+        if stream_number >= self.stream_count {
+            return Err(Error::StreamNotFound(stream_number));
+        }
+
+        self.changes.insert(stream_number, Some(data));
+        Ok(())
+    }
+
+    /// Appends a new stream and returns its stream number.
+    pub fn append_stream(&mut self, data: Vec<u8>) -> u32 {
+        let stream_number = self.stream_count;
+        self.changes.insert(stream_number, Some(data));
+        self.stream_count += 1;
+        stream_number
+    }
+
+    /// Marks an existing stream as not present.
+    ///
+    /// Stream numbers of subsequent streams are not affected.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the stream number exceeds the number of streams
+    pub fn remove_stream(&mut self, stream_number: u32) -> Result<()> {
+        if stream_number >= self.stream_count {
+            return Err(Error::StreamNotFound(stream_number));
+        }
+
+        self.changes.insert(stream_number, None);
+        Ok(())
+    }
+
+    /// Returns whether a stream has been replaced, appended or removed.
+    pub fn is_modified(&self, stream_number: u32) -> bool {
+        self.changes.contains_key(&stream_number)
+    }
+
+    /// Returns the numbers of all streams that have been replaced, appended or removed, in
+    /// ascending order.
+    pub fn modified_streams(&self) -> impl Iterator<Item = u32> + '_ {
+        self.changes.keys().copied()
+    }
+
+    /// Prepares the file to be written in its most compact form.
+    ///
+    /// [`write`](Self::write) always lays out every stream in consecutive pages and drops pages that
+    /// are no longer referenced, which undoes the fragmentation left behind by incremental linking.
+    /// Compacting additionally minimizes the stream directory:
+    ///
+    /// * stream 0, which holds the stream table as of the previous write, is emptied
+    /// * streams that are not present at the end of the stream table are dropped
+    ///
+    /// Stream numbers of all remaining streams are preserved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let original = std::fs::read("fixtures/self/foo.pdb")?;
+    /// let mut editor = pdb::MsfEditor::open(std::io::Cursor::new(original.as_slice()))?;
+    /// editor.compact();
+    ///
+    /// let mut output = Vec::new();
+    /// editor.write(&mut output)?;
+    /// assert!(output.len() < original.len());
+    ///
+    /// let pdb = pdb::PDB::open(std::io::Cursor::new(output))?;
+    /// assert!(pdb.previous_directory()?.is_none());
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn compact(&mut self) {
+        if self.stream_count > 0 {
+            self.changes.insert(0, Some(Vec::new()));
+        }
+
+        while self.stream_count > 0 && !self.is_present(self.stream_count - 1) {
+            self.stream_count -= 1;
+            self.changes.remove(&self.stream_count);
+        }
+    }
+
+    /// Returns whether a stream is present after applying all changes.
+    fn is_present(&self, stream_number: u32) -> bool {
+        match self.changes.get(&stream_number) {
+            Some(change) => change.is_some(),
+            None => matches!(self.msf.stream_size(stream_number), Ok(Some(_))),
+        }
+    }
+
+    /// Writes a new multi-stream file containing all changes.
+    ///
+    /// The new file uses the page size of the original file. Unmodified streams are copied from the
+    /// original file. Every stream is stored in consecutive pages, and pages of the original file
+    /// that are not referenced by any stream are not carried over.
+    ///
+    /// The new file is written sequentially and unmodified streams are copied a few pages at a
+    /// time, so memory use does not grow with the size of the file.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source` or the `Write`
+    /// * `Error::PageReferenceOutOfRange` if the original MSF file seems corrupt
+    /// * `Error::MsfLimitExceeded` if the resulting file cannot be represented
+    pub fn write<W: io::Write>(&self, writer: W) -> Result<()> {
+        let mut sizes = Vec::with_capacity(self.stream_count as usize);
+        for stream_number in 0..self.stream_count {
+            let size = match self.changes.get(&stream_number) {
+                Some(change) => change.as_ref().map(Vec::len),
+                None => match self.msf.stream_size(stream_number) {
+                    Ok(size) => size.map(|size| size as usize),
+                    Err(Error::StreamNotFound(_)) => None,
+                    Err(e) => return Err(e),
+                },
+            };
+            sizes.push(size);
+        }
+
+        write_msf(
+            self.msf.page_size(),
+            &sizes,
+            writer,
+            &mut |stream_number, output| match self.changes.get(&stream_number) {
+                Some(Some(data)) => Ok(output.write_all(data)?),
+                Some(None) => Ok(()),
+                None => self.msf.copy_stream_to(stream_number, output).map(|_| ()),
+            },
+        )
+    }
+
+    /// Saves all changes by updating the original file in place.
+    ///
+    /// `target` must refer to the same file this editor was opened from. Only the modified streams
+    /// and the stream directory are written, while unmodified streams stay in their original pages.
+    /// This is much cheaper than [`write`](Self::write) when a few streams of a large file change.
+    ///
+    /// Changes are saved the same way the Microsoft toolchain commits them: new data is written to
+    /// pages that are unused by the current version of the file, the inactive free page map is
+    /// updated, and the header is rewritten last to switch over to the new stream directory. The
+    /// previous stream directory is kept in stream 0. If saving is interrupted before the header is
+    /// written, the file still contains its previous version.
+    ///
+    /// The editor is consumed, since the file it was opened from has changed.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnimplementedFeature` if the original file is not an MSF 7.00 file
+    /// * `Error::InvalidFreePageMap` if the header does not specify a valid free page map
+    /// * `Error::IoError` if returned by the `Source` or the target
+    /// * `Error::PageReferenceOutOfRange` if the original MSF file seems corrupt
+    /// * `Error::MsfLimitExceeded` if the resulting file cannot be represented
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let mut file = std::fs::read("fixtures/self/foo.pdb")?;
+    /// let original_len = file.len();
+    ///
+    /// let source = std::io::Cursor::new(file.clone());
+    /// let mut editor = pdb::MsfEditor::open(source)?;
+    /// editor.replace_stream(208, b"goodbye world\n".to_vec())?;
+    /// assert!(editor.is_modified(208));
+    /// editor.save_incremental(std::io::Cursor::new(&mut file))?;
+    ///
+    /// // only a few pages were added
+    /// assert!(file.len() < original_len + 16 * 4096);
+    ///
+    /// let pdb = pdb::PDB::open(std::io::Cursor::new(file))?;
+    /// let stream = pdb.raw_stream(pdb::StreamIndex(208))?.expect("stream exists");
+    /// assert_eq!(stream.as_slice(), b"goodbye world\n");
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn save_incremental<W: io::Write + io::Seek>(self, mut target: W) -> Result<()> {
+        let info = self.msf.info();
+        if info.version() != MsfVersion::V700 {
+            return Err(Error::UnimplementedFeature(
+                "incremental saves of containers other than MSF 7.00",
+            ));
+        }
+
+        let active_free_page_map = match info.free_page_map() {
+            Some(index @ 1..=2) => index,
+            Some(index) => return Err(Error::InvalidFreePageMap(index)),
+            None => return Err(Error::InvalidFreePageMap(0)),
+        };
+
+        let page_size = info.page_size() as usize;
+        let mut allocator = IncrementalAllocator::new(page_size, info.pages_used());
+
+        // everything referenced by the current version of the file must stay intact, including the
+        // pages of stream 0, which the saved copy of the current stream directory still refers to
+        allocator.mark_used(0)?;
+        for page in self.msf.directory_pages()? {
+            allocator.mark_used(page)?;
+        }
+
+        let old_sizes = self.msf.stream_sizes();
+        let mut old_pages = Vec::with_capacity(old_sizes.len());
+        for (stream_number, size) in old_sizes.iter().enumerate() {
+            let pages = match size {
+                Some(_) => self
+                    .msf
+                    .stream_layout(stream_number as u32)?
+                    .pages()
+                    .to_vec(),
+                None => Vec::new(),
+            };
+
+            for &page in &pages {
+                allocator.mark_used(page)?;
+            }
+            old_pages.push(pages);
+        }
+
+        let previous_directory = stream_table(&old_sizes, &old_pages);
+
+        // write the modified streams to unused pages
+        let mut sizes = Vec::with_capacity(self.stream_count as usize);
+        let mut pages = Vec::with_capacity(self.stream_count as usize);
+        for stream_number in 0..self.stream_count {
+            let data = match self.changes.get(&stream_number) {
+                Some(change) => change.as_deref(),
+                None if stream_number == 0 => Some(previous_directory.as_slice()),
+                None => {
+                    let index = stream_number as usize;
+                    sizes.push(old_sizes.get(index).copied().flatten());
+                    pages.push(old_pages.get(index).cloned().unwrap_or_default());
+                    continue;
+                }
+            };
+
+            match data {
+                Some(data) => {
+                    let size = u32::try_from(data.len())
+                        .ok()
+                        .filter(|&size| size != u32::MAX)
+                        .ok_or(Error::MsfLimitExceeded("stream too large"))?;
+                    let stream_pages = allocator.allocate(data.len())?;
+                    write_pages(&mut target, page_size, &stream_pages, data)?;
+                    sizes.push(Some(size));
+                    pages.push(stream_pages);
+                }
+                None => {
+                    sizes.push(None);
+                    pages.push(Vec::new());
+                }
+            }
+        }
+
+        // write the new stream directory
+        let stream_table = stream_table(&sizes, &pages);
+        let directory_size = u32::try_from(stream_table.len())
+            .map_err(|_| Error::MsfLimitExceeded("stream table too large"))?;
+
+        let stream_table_pages = allocator.allocate(stream_table.len())?;
+        let mut stream_table_page_list = Vec::with_capacity(stream_table_pages.len() * 4);
+        for page in &stream_table_pages {
+            stream_table_page_list.extend_from_slice(&page.to_le_bytes());
+        }
+
+        let page_list_pages = allocator.allocate(stream_table_page_list.len())?;
+        if big::HEADER_SIZE + page_list_pages.len() * 4 > page_size {
+            return Err(Error::MsfLimitExceeded("stream table page list too large"));
+        }
+
+        write_pages(&mut target, page_size, &stream_table_pages, &stream_table)?;
+        write_pages(
+            &mut target,
+            page_size,
+            &page_list_pages,
+            &stream_table_page_list,
+        )?;
+
+        // write the inactive free page map, which marks the pages of the previous version as free
+        let page_count = allocator.page_count()?;
+        let mut used = vec![false; page_count as usize];
+        let in_use = std::iter::once(0)
+            .chain(stream_table_pages.iter().copied())
+            .chain(page_list_pages.iter().copied())
+            .chain(pages.iter().skip(1).flatten().copied());
+        for page in in_use {
+            used[page as usize] = true;
+        }
+
+        let intervals = (page_count as usize).div_ceil(page_size);
+        let mut free_page_map = vec![0xff; intervals * page_size];
+        for page in 0..page_count as usize {
+            if used[page] || is_free_page_map(allocator.page_size as u64, page as u64) {
+                free_page_map[page / 8] &= !(1 << (page % 8));
+            }
+        }
+
+        let inactive_free_page_map = 3 - active_free_page_map;
+        for (interval, chunk) in free_page_map.chunks(page_size).enumerate() {
+            for map in 1..=2 {
+                // the active map of intervals added by this save is not in use yet
+                let page = (interval * page_size) as u32 + map;
+                if map == inactive_free_page_map || page >= info.pages_used() {
+                    write_pages(&mut target, page_size, &[page], chunk)?;
+                }
+            }
+        }
+
+        // make sure that the file covers every page
+        let file_size = u64::from(page_count) * page_size as u64;
+        if target.seek(io::SeekFrom::End(0))? < file_size {
+            target.seek(io::SeekFrom::Start(file_size - 1))?;
+            target.write_all(&[0])?;
+        }
+        target.flush()?;
+
+        // commit by writing the header
+        let mut header = Vec::with_capacity(big::HEADER_SIZE + page_list_pages.len() * 4);
+        header.extend_from_slice(big::MAGIC);
+        header.extend_from_slice(&(page_size as u32).to_le_bytes());
+        header.extend_from_slice(&inactive_free_page_map.to_le_bytes());
+        header.extend_from_slice(&page_count.to_le_bytes());
+        header.extend_from_slice(&directory_size.to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes()); // reserved
+        for page in &page_list_pages {
+            header.extend_from_slice(&page.to_le_bytes());
+        }
+
+        target.seek(io::SeekFrom::Start(0))?;
+        target.write_all(&header)?;
+        target.flush()?;
+        Ok(())
+    }
+}
+
+/// Hands out pages that are not in use by the current version of a file, followed by pages beyond
+/// its end, skipping the free page map pages.
+#[derive(Debug)]
+struct IncrementalAllocator {
+    page_size: usize,
+    used: Vec<bool>,
+    next_page: u64,
+    end: u64,
+}
+
+impl IncrementalAllocator {
+    fn new(page_size: usize, page_count: u32) -> Self {
+        Self {
+            page_size,
+            used: vec![false; page_count as usize],
+            next_page: 3,
+            end: u64::from(page_count),
+        }
+    }
+
+    fn mark_used(&mut self, page: PageNumber) -> Result<()> {
+        match self.used.get_mut(page as usize) {
+            Some(used) => {
+                *used = true;
+                Ok(())
+            }
+            None => Err(Error::PageReferenceOutOfRange(page)),
+        }
+    }
+
+    /// Allocates enough pages to store the given number of bytes.
+    fn allocate(&mut self, bytes: usize) -> Result<Vec<PageNumber>> {
+        let count = bytes.div_ceil(self.page_size);
+        let mut pages = Vec::with_capacity(count);
+
+        while pages.len() < count {
+            let page = self.next_page;
+            self.next_page += 1;
+
+            let in_use = self.used.get(page as usize).copied().unwrap_or(false);
+            if !in_use && !is_free_page_map(self.page_size as u64, page) {
+                let page = PageNumber::try_from(page)
+                    .map_err(|_| Error::MsfLimitExceeded("too many pages"))?;
+                pages.push(page);
+            }
+        }
+
+        self.end = self.end.max(self.next_page);
+        Ok(pages)
+    }
+
+    /// Returns the total number of pages in the file, including the free page map pages of the
+    /// last interval.
+    fn page_count(&self) -> Result<u32> {
+        let mut end = self.end;
+        while is_free_page_map(self.page_size as u64, end) {
+            end += 1;
+        }
+
+        u32::try_from(end).map_err(|_| Error::MsfLimitExceeded("too many pages"))
+    }
+}
+
+/// Serializes a stream table in the MSF 7.00 format.
+fn stream_table(sizes: &[Option<u32>], pages: &[Vec<PageNumber>]) -> Vec<u8> {
+    let mut stream_table = Vec::new();
+    stream_table.extend_from_slice(&(sizes.len() as u32).to_le_bytes());
+    for size in sizes {
+        stream_table.extend_from_slice(&size.unwrap_or(u32::MAX).to_le_bytes());
+    }
+    for page in pages.iter().flatten() {
+        stream_table.extend_from_slice(&page.to_le_bytes());
+    }
+    stream_table
+}
+
+/// Writes `data` to the given pages of `target`, padding the last page.
+fn write_pages<W: io::Write + io::Seek>(
+    target: &mut W,
+    page_size: usize,
+    pages: &[PageNumber],
+    data: &[u8],
+) -> Result<()> {
+    let padding = vec![0; page_size];
+    for (page, chunk) in pages.iter().zip(data.chunks(page_size)) {
+        target.seek(io::SeekFrom::Start(u64::from(*page) * page_size as u64))?;
+        target.write_all(chunk)?;
+        target.write_all(&padding[chunk.len()..])?;
+    }
+    Ok(())
+}
+
+impl<S> fmt::Debug for MsfEditor<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MsfEditor")
+            .field("stream_count", &self.stream_count)
+            .field("changed_streams", &self.changes.keys())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::common::*;
+    use crate::msf::open_msf;
+    use crate::msf::{MsfEditor, MsfWriter, PreviousDirectory};
+
+    fn original() -> Vec<u8> {
+        let mut writer = MsfWriter::new();
+        writer.add_stream(b"zero".to_vec());
+        writer.add_stream(b"one".to_vec());
+        writer.reserve_stream();
+        writer.add_stream(vec![3; 5000]);
+        writer.reserve_stream();
+
+        let mut file = Vec::new();
+        writer.write(&mut file).expect("write");
+        file
+    }
+
+    #[test]
+    fn test_unchanged() {
+        let file = original();
+        let editor = MsfEditor::open(Cursor::new(file.as_slice())).expect("open");
+        assert_eq!(editor.stream_count(), 5);
+
+        let mut output = Vec::new();
+        editor.write(&mut output).expect("write");
+        assert_eq!(output, file);
+    }
+
+    #[test]
+    fn test_replace_append_remove() {
+        let file = original();
+        let mut editor = MsfEditor::open(Cursor::new(file.as_slice())).expect("open");
+
+        editor.replace_stream(2, b"two".to_vec()).expect("replace");
+        editor.remove_stream(3).expect("remove");
+        assert_eq!(editor.append_stream(b"five".to_vec()), 5);
+        assert!(matches!(
+            editor.replace_stream(7, Vec::new()),
+            Err(Error::StreamNotFound(7))
+        ));
+
+        let mut output = Vec::new();
+        editor.write(&mut output).expect("write");
+
+        let msf = open_msf(Cursor::new(output.as_slice())).expect("open");
+        assert_eq!(msf.stream_count(), 6);
+        assert_eq!(msf.get(1, None).expect("stream 1").as_slice(), b"one");
+        assert_eq!(msf.get(2, None).expect("stream 2").as_slice(), b"two");
+        assert!(matches!(msf.get(3, None), Err(Error::StreamNotFound(3))));
+        assert!(matches!(msf.get(4, None), Err(Error::StreamNotFound(4))));
+        assert_eq!(msf.get(5, None).expect("stream 5").as_slice(), b"five");
+    }
+
+    #[test]
+    fn test_save_incremental() {
+        let original = original();
+        let mut file = original.clone();
+
+        let mut editor = MsfEditor::open(Cursor::new(original.as_slice())).expect("open");
+        editor.replace_stream(1, b"uno".to_vec()).expect("replace");
+        editor.remove_stream(3).expect("remove");
+        editor.append_stream(b"five".to_vec());
+        assert_eq!(editor.modified_streams().collect::<Vec<_>>(), [1, 3, 5]);
+        assert!(!editor.is_modified(2));
+        editor
+            .save_incremental(Cursor::new(&mut file))
+            .expect("save");
+
+        // the original pages are untouched, apart from the header and the inactive free page map
+        assert_eq!(file[4096 * 3..original.len()], original[4096 * 3..]);
+        assert_eq!(file.len() % 4096, 0);
+
+        let msf = open_msf(Cursor::new(file.as_slice())).expect("open");
+        assert_eq!(msf.info().free_page_map(), Some(2));
+        assert_eq!(msf.stream_count(), 6);
+        assert_eq!(msf.get(1, None).expect("stream 1").as_slice(), b"uno");
+        assert!(matches!(msf.get(3, None), Err(Error::StreamNotFound(3))));
+        assert_eq!(msf.get(5, None).expect("stream 5").as_slice(), b"five");
+        msf.validate_free_page_map().expect("valid");
+
+        // the previous version is still reachable through stream 0
+        let (header, table) = msf
+            .previous_stream_table()
+            .expect("parse")
+            .expect("previous table");
+        let previous = PreviousDirectory::new(&*msf, header, table);
+        let stream = previous.raw_stream(StreamIndex(0)).expect("stream");
+        assert_eq!(stream.expect("present").as_slice(), b"zero");
+        let stream = previous.raw_stream(StreamIndex(1)).expect("stream");
+        assert_eq!(stream.expect("present").as_slice(), b"one");
+        drop(previous);
+        drop(msf);
+
+        // pages freed by the first save are reused by the second
+        let saved = file.clone();
+        let mut editor = MsfEditor::open(Cursor::new(saved.as_slice())).expect("open");
+        editor.replace_stream(1, b"eins".to_vec()).expect("replace");
+        editor
+            .save_incremental(Cursor::new(&mut file))
+            .expect("save");
+        assert_eq!(file.len(), saved.len());
+
+        let msf = open_msf(Cursor::new(file.as_slice())).expect("open");
+        assert_eq!(msf.info().free_page_map(), Some(1));
+        assert_eq!(msf.get(1, None).expect("stream 1").as_slice(), b"eins");
+        assert!(matches!(msf.get(3, None), Err(Error::StreamNotFound(3))));
+        assert_eq!(msf.get(5, None).expect("stream 5").as_slice(), b"five");
+        msf.validate_free_page_map().expect("valid");
+    }
+
+    #[test]
+    fn test_save_incremental_growth() {
+        let mut writer = MsfWriter::with_page_size(512).expect("page size");
+        writer.add_stream(Vec::new());
+        writer.add_stream(b"small".to_vec());
+        writer.add_stream(vec![2; 4096]);
+
+        let mut file = Vec::new();
+        writer.write(&mut file).expect("write");
+        let original = file.clone();
+
+        // the new stream spans several intervals of free page maps
+        let data: Vec<u8> = (0..600_000u32).map(|i| (i % 251) as u8).collect();
+        let mut editor = MsfEditor::open(Cursor::new(original.as_slice())).expect("open");
+        editor.append_stream(data.clone());
+        editor
+            .save_incremental(Cursor::new(&mut file))
+            .expect("save");
+
+        let msf = open_msf(Cursor::new(file.as_slice())).expect("open");
+        assert_eq!(msf.info().pages_used() as usize, file.len() / 512);
+        assert_eq!(msf.get(1, None).expect("stream 1").as_slice(), b"small");
+        assert_eq!(
+            msf.get(2, None).expect("stream 2").as_slice(),
+            &[2; 4096][..]
+        );
+        assert_eq!(
+            msf.get(3, None).expect("stream 3").as_slice(),
+            data.as_slice()
+        );
+        msf.validate_free_page_map().expect("valid");
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut writer = MsfWriter::new();
+        writer.add_stream(vec![0; 5000]);
+        writer.add_stream(b"one".to_vec());
+        writer.reserve_stream();
+        writer.add_stream(vec![3; 5000]);
+        writer.reserve_stream();
+        writer.reserve_stream();
+
+        let mut file = Vec::new();
+        writer.write(&mut file).expect("write");
+
+        let mut editor = MsfEditor::open(Cursor::new(file.as_slice())).expect("open");
+        editor.remove_stream(3).expect("remove");
+        editor.compact();
+        assert_eq!(editor.stream_count(), 2);
+
+        let mut output = Vec::new();
+        editor.write(&mut output).expect("write");
+        assert!(output.len() < file.len());
+
+        let msf = open_msf(Cursor::new(output.as_slice())).expect("open");
+        assert_eq!(msf.stream_sizes(), vec![Some(0), Some(3)]);
+        assert_eq!(msf.get(1, None).expect("stream 1").as_slice(), b"one");
+
+        // removing every other stream leaves only the empty stream 0
+        let mut editor = MsfEditor::open(Cursor::new(output.as_slice())).expect("open");
+        editor.remove_stream(1).expect("remove");
+        editor.compact();
+        assert_eq!(editor.stream_count(), 1);
+
+        let mut output = Vec::new();
+        editor.write(&mut output).expect("write");
+
+        let msf = open_msf(Cursor::new(output.as_slice())).expect("open");
+        assert_eq!(msf.stream_sizes(), vec![Some(0)]);
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::msf::PageNumber;
+
+/// The free page map (FPM) of a multi-stream file.
+///
+/// The free page map is a bitmap with one bit per page of the file, where a set bit indicates that
+/// the page is free. MSF files carry two free page maps so that changes can be committed
+/// atomically; the header indicates which of the two is active. The active map is spread across
+/// the file in pages at fixed intervals, which are concatenated to form the bitmap.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FreePageMap {
+    bitmap: Vec<u8>,
+    page_count: u32,
+}
+
+impl FreePageMap {
+    pub(crate) fn new(bitmap: Vec<u8>, page_count: u32) -> Self {
+        Self { bitmap, page_count }
+    }
+
+    /// Returns the number of pages in the file.
+    pub fn page_count(&self) -> u32 {
+        self.page_count
+    }
+
+    /// Returns `true` if the given page is marked as free.
+    ///
+    /// Pages beyond the end of the file are always free.
+    pub fn is_free(&self, page: u32) -> bool {
+        if page >= self.page_count {
+            return true;
+        }
+
+        match self.bitmap.get(page as usize / 8) {
+            Some(byte) => byte & (1 << (page % 8)) != 0,
+            None => true,
+        }
+    }
+
+    /// Returns `true` if the given page is marked as allocated.
+    pub fn is_allocated(&self, page: u32) -> bool {
+        !self.is_free(page)
+    }
+
+    /// Returns an iterator over all free pages in the file.
+    pub fn free_pages(&self) -> FreePageMapIter<'_> {
+        FreePageMapIter {
+            map: self,
+            next: 0,
+            free: true,
+        }
+    }
+
+    /// Returns an iterator over all allocated pages in the file.
+    pub fn allocated_pages(&self) -> FreePageMapIter<'_> {
+        FreePageMapIter {
+            map: self,
+            next: 0,
+            free: false,
+        }
+    }
+}
+
+/// An iterator over the free or allocated pages of a [`FreePageMap`].
+#[derive(Clone, Debug)]
+pub struct FreePageMapIter<'a> {
+    map: &'a FreePageMap,
+    next: PageNumber,
+    free: bool,
+}
+
+impl Iterator for FreePageMapIter<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.next < self.map.page_count {
+            let page = self.next;
+            self.next += 1;
+
+            if self.map.is_free(page) == self.free {
+                return Some(page);
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::msf::free_page_map::*;
+
+    #[test]
+    fn test_is_free() {
+        let map = FreePageMap::new(vec![0b1010_0000, 0b0000_0001], 12);
+
+        assert!(map.is_allocated(0));
+        assert!(map.is_allocated(4));
+        assert!(map.is_free(5));
+        assert!(map.is_allocated(6));
+        assert!(map.is_free(7));
+        assert!(map.is_free(8));
+        assert!(map.is_allocated(11));
+
+        // beyond the end of the file
+        assert!(map.is_free(12));
+        assert!(map.is_free(100));
+    }
+
+    #[test]
+    fn test_iterators() {
+        let map = FreePageMap::new(vec![0b1010_0000, 0b0000_0001], 12);
+
+        assert_eq!(map.free_pages().collect::<Vec<_>>(), vec![5, 7, 8]);
+        assert_eq!(
+            map.allocated_pages().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4, 6, 9, 10, 11]
+        );
+    }
+}
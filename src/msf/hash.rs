@@ -0,0 +1,102 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::hash::Hasher;
+use std::io;
+
+use sha2::{Digest, Sha256};
+use twox_hash::XxHash64;
+
+/// A hash algorithm for fingerprinting stream contents.
+///
+/// Used by [`PDB::stream_hash`](crate::PDB::stream_hash).
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum HashAlgorithm {
+    /// SHA-256, producing a 32 byte digest.
+    Sha256,
+    /// xxHash64 with a seed of zero, producing an 8 byte digest in big-endian byte order.
+    ///
+    /// This is considerably faster than SHA-256, but not suitable where collisions could be
+    /// crafted deliberately.
+    XxHash64,
+}
+
+/// Computes a digest of everything written to it.
+pub(crate) enum StreamHasher {
+    Sha256(Sha256),
+    XxHash64(XxHash64),
+}
+
+impl StreamHasher {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Sha256 => Self::Sha256(Sha256::new()),
+            HashAlgorithm::XxHash64 => Self::XxHash64(XxHash64::with_seed(0)),
+        }
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        match self {
+            Self::Sha256(hasher) => hasher.finalize().to_vec(),
+            Self::XxHash64(hasher) => hasher.finish().to_be_bytes().to_vec(),
+        }
+    }
+}
+
+impl io::Write for StreamHasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Sha256(hasher) => hasher.update(buf),
+            Self::XxHash64(hasher) => hasher.write(buf),
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn digest(algorithm: HashAlgorithm, pieces: &[&[u8]]) -> Vec<u8> {
+        let mut hasher = StreamHasher::new(algorithm);
+        for piece in pieces {
+            hasher.write_all(piece).expect("write");
+        }
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            digest(HashAlgorithm::Sha256, &[b"a", b"bc"]),
+            [
+                0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae,
+                0x22, 0x23, 0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61,
+                0xf2, 0x00, 0x15, 0xad,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_xxhash64() {
+        assert_eq!(
+            digest(HashAlgorithm::XxHash64, &[]),
+            0xef46_db37_51d8_e999u64.to_be_bytes()
+        );
+        assert_eq!(
+            digest(HashAlgorithm::XxHash64, &[b"a", b"bc"]),
+            digest(HashAlgorithm::XxHash64, &[b"abc"])
+        );
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+/// The container format of a PDB file.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum MsfVersion {
+    /// The "Microsoft C/C++ program database 2.00" format written by Visual C++ 2.0 to 6.0, using
+    /// 16-bit page numbers.
+    V200,
+    /// The "Microsoft C/C++ MSF 7.00" format written by all later toolchains, using 32-bit page
+    /// numbers.
+    V700,
+    /// A compressed MSFZ container, which stores streams in chunks rather than pages.
+    Msfz,
+}
+
+/// Metadata about the multi-stream file container of a PDB, as recorded in its header.
+///
+/// Returned by [`PDB::msf_info`](crate::PDB::msf_info).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MsfInfo {
+    version: MsfVersion,
+    page_size: u32,
+    pages_used: u32,
+    directory_size: u32,
+    free_page_map: Option<u32>,
+}
+
+impl MsfInfo {
+    pub(crate) fn new(
+        version: MsfVersion,
+        page_size: u32,
+        pages_used: u32,
+        directory_size: u32,
+        free_page_map: Option<u32>,
+    ) -> Self {
+        Self {
+            version,
+            page_size,
+            pages_used,
+            directory_size,
+            free_page_map,
+        }
+    }
+
+    /// Returns the detected container format.
+    pub fn version(&self) -> MsfVersion {
+        self.version
+    }
+
+    /// Returns the size of a page in bytes.
+    ///
+    /// MSFZ containers are not paged, and report the nominal page size of 4096 bytes.
+    pub fn page_size(&self) -> u32 {
+        self.page_size
+    }
+
+    /// Returns the number of pages in the file, according to the header.
+    ///
+    /// This is zero for MSFZ containers.
+    pub fn pages_used(&self) -> u32 {
+        self.pages_used
+    }
+
+    /// Returns the size of the stream directory in bytes.
+    ///
+    /// For MSFZ containers, this is the uncompressed size of the stream directory.
+    pub fn directory_size(&self) -> u32 {
+        self.directory_size
+    }
+
+    /// Returns the index of the active free page map, which is either 1 or 2 in valid files.
+    ///
+    /// This is `None` for MSFZ containers, which have no free page map.
+    pub fn free_page_map(&self) -> Option<u32> {
+        self.free_page_map
+    }
+}
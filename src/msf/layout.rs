@@ -0,0 +1,98 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::ops::Range;
+
+/// The physical layout of a stream within a multi-stream file.
+///
+/// Streams are stored in pages of a fixed size which need not be consecutive or in order. The page
+/// numbers are reported in stream order, exactly as listed in the stream table, and are not
+/// validated against the size of the file.
+///
+/// Returned by [`PDB::stream_layout`](crate::PDB::stream_layout).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamLayout {
+    page_size: u32,
+    size: u32,
+    pages: Vec<u32>,
+}
+
+impl StreamLayout {
+    pub(crate) fn new(page_size: u32, size: u32, pages: Vec<u32>) -> Self {
+        Self {
+            page_size,
+            size,
+            pages,
+        }
+    }
+
+    /// Returns the size of a page in bytes.
+    pub fn page_size(&self) -> u32 {
+        self.page_size
+    }
+
+    /// Returns the size of the stream in bytes.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns the page numbers storing the stream, in stream order.
+    pub fn pages(&self) -> &[u32] {
+        &self.pages
+    }
+
+    /// Returns the byte range of the file occupied by each page, in stream order.
+    ///
+    /// The range of the last page is truncated to the end of the stream.
+    pub fn page_ranges(&self) -> impl Iterator<Item = Range<u64>> + '_ {
+        let page_size = u64::from(self.page_size);
+        let mut remaining = u64::from(self.size);
+
+        self.pages.iter().map(move |&page| {
+            let start = u64::from(page) * page_size;
+            let len = remaining.min(page_size);
+            remaining -= len;
+            start..start + len
+        })
+    }
+
+    /// Returns the byte ranges of the file occupied by the stream, in stream order.
+    ///
+    /// Consecutive pages are combined into a single range, so a stream stored in sequential pages
+    /// is represented by a single range.
+    pub fn extents(&self) -> Vec<Range<u64>> {
+        let mut extents: Vec<Range<u64>> = Vec::new();
+        for range in self.page_ranges() {
+            match extents.last_mut() {
+                Some(last) if last.end == range.start => last.end = range.end,
+                _ => extents.push(range),
+            }
+        }
+        extents
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranges() {
+        let layout = StreamLayout::new(512, 1300, vec![7, 8, 3]);
+
+        let ranges: Vec<_> = layout.page_ranges().collect();
+        assert_eq!(ranges, [3584..4096, 4096..4608, 1536..1812]);
+        assert_eq!(layout.extents(), [3584..4608, 1536..1812]);
+    }
+
+    #[test]
+    fn test_empty() {
+        let layout = StreamLayout::new(4096, 0, vec![]);
+        assert_eq!(layout.page_ranges().count(), 0);
+        assert!(layout.extents().is_empty());
+    }
+}
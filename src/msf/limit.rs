@@ -0,0 +1,280 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::common::*;
+use crate::msf::{
+    FreePageMap, Header, Msf, MsfInfo, PageList, PageNumber, Stream, StreamLayout, StreamTable,
+    COPY_PAGES,
+};
+use crate::source::SourceView;
+
+/// Determines what happens when reading a stream would exceed the memory limit of a `PDB`.
+///
+/// See [`PDB::open_with_memory_limit`](crate::PDB::open_with_memory_limit).
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MemoryLimitPolicy {
+    /// Fail the read with `Error::MemoryLimitExceeded`.
+    Error,
+    /// Discard data cached internally, such as decompressed chunks of MSFZ containers, and fail
+    /// the read only if the limit would still be exceeded.
+    Evict,
+}
+
+/// Tracks the number of bytes held by views handed out under a memory limit.
+#[derive(Debug)]
+pub(crate) struct MemoryBudget {
+    limit: usize,
+    used: AtomicUsize,
+}
+
+impl MemoryBudget {
+    /// Reserves `bytes`, given that `cached` bytes are additionally held in internal caches.
+    pub(crate) fn try_reserve(
+        self: &Arc<Self>,
+        bytes: usize,
+        cached: usize,
+    ) -> Option<Reservation> {
+        self.used
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |used| {
+                used.checked_add(bytes)
+                    .filter(|&total| total.saturating_add(cached) <= self.limit)
+            })
+            .ok()?;
+
+        Some(Reservation {
+            budget: Arc::clone(self),
+            bytes,
+        })
+    }
+}
+
+/// Bytes reserved from a `MemoryBudget`, which are released when dropped.
+#[derive(Debug)]
+pub(crate) struct Reservation {
+    budget: Arc<MemoryBudget>,
+    bytes: usize,
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        self.budget.used.fetch_sub(self.bytes, Ordering::AcqRel);
+    }
+}
+
+/// A view that holds a reservation for as long as it is alive.
+struct ReservedView<'s> {
+    view: Box<dyn SourceView<'s> + 's>,
+    _reservation: Reservation,
+}
+
+impl fmt::Debug for ReservedView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.view.fmt(f)
+    }
+}
+
+impl<'s> SourceView<'s> for ReservedView<'s> {
+    fn as_slice(&self) -> &[u8] {
+        self.view.as_slice()
+    }
+}
+
+/// An `Msf` wrapper that limits the total size of the views handed out by another `Msf`.
+///
+/// Views are accounted for from the moment they are requested until they are dropped, including
+/// views which are only used temporarily, such as the pieces read by `copy_stream_to`.
+pub struct LimitedMsf<'s, S> {
+    inner: Box<dyn Msf<'s, S> + 's>,
+    budget: Arc<MemoryBudget>,
+    policy: MemoryLimitPolicy,
+}
+
+impl<'s, S> LimitedMsf<'s, S> {
+    pub fn new(
+        mut inner: Box<dyn Msf<'s, S> + 's>,
+        limit: usize,
+        policy: MemoryLimitPolicy,
+    ) -> Self {
+        let budget = Arc::new(MemoryBudget {
+            limit,
+            used: AtomicUsize::new(0),
+        });
+
+        inner.set_memory_budget(Arc::clone(&budget));
+        LimitedMsf {
+            inner,
+            budget,
+            policy,
+        }
+    }
+
+    fn reserve(&self, bytes: usize) -> Result<Reservation> {
+        if let Some(reservation) = self.budget.try_reserve(bytes, self.inner.cached_bytes()) {
+            return Ok(reservation);
+        }
+
+        if self.policy == MemoryLimitPolicy::Evict {
+            self.inner.evict_caches();
+            if let Some(reservation) = self.budget.try_reserve(bytes, self.inner.cached_bytes()) {
+                return Ok(reservation);
+            }
+        }
+
+        Err(Error::MemoryLimitExceeded(bytes))
+    }
+
+    /// Returns the number of bytes to be read for a stream, optionally restricted by a limit.
+    fn requested_size(&self, stream_number: u32, limit: Option<usize>) -> Result<usize> {
+        let size = match self.inner.stream_size(stream_number)? {
+            Some(size) => size as usize,
+            None => return Err(Error::StreamNotFound(stream_number)),
+        };
+
+        Ok(limit.map_or(size, |limit| limit.min(size)))
+    }
+}
+
+fn attach<'s>(stream: Stream<'s>, reservation: Reservation) -> Stream<'s> {
+    Stream {
+        source_view: Box::new(ReservedView {
+            view: stream.source_view,
+            _reservation: reservation,
+        }),
+        range: stream.range,
+    }
+}
+
+impl<S> fmt::Debug for LimitedMsf<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LimitedMsf")
+            .field("inner", &self.inner)
+            .field("budget", &self.budget)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl<'s, S> Msf<'s, S> for LimitedMsf<'s, S> {
+    fn page_size(&self) -> usize {
+        self.inner.page_size()
+    }
+
+    fn info(&self) -> MsfInfo {
+        self.inner.info()
+    }
+
+    fn stream_count(&self) -> u32 {
+        self.inner.stream_count()
+    }
+
+    fn stream_size(&self, stream_number: u32) -> Result<Option<u32>> {
+        self.inner.stream_size(stream_number)
+    }
+
+    fn stream_sizes(&self) -> Vec<Option<u32>> {
+        self.inner.stream_sizes()
+    }
+
+    fn free_page_map(&self) -> Result<FreePageMap> {
+        self.inner.free_page_map()
+    }
+
+    fn validate_free_page_map(&self) -> Result<()> {
+        self.inner.validate_free_page_map()
+    }
+
+    fn previous_stream_table(&self) -> Result<Option<(Header, StreamTable)>> {
+        let _reservation = match self.inner.stream_size(0) {
+            Ok(Some(size)) => Some(self.reserve(size as usize)?),
+            _ => None,
+        };
+        self.inner.previous_stream_table()
+    }
+
+    fn stream_layout(&self, stream_number: u32) -> Result<StreamLayout> {
+        self.inner.stream_layout(stream_number)
+    }
+
+    fn directory_pages(&self) -> Result<Vec<PageNumber>> {
+        self.inner.directory_pages()
+    }
+
+    fn look_up_stream(&self, stream_number: u32) -> Result<PageList> {
+        self.inner.look_up_stream(stream_number)
+    }
+
+    fn view_pages(&self, page_list: &PageList) -> Result<Box<dyn SourceView<'s> + 's>> {
+        let reservation = self.reserve(page_list.len())?;
+        Ok(Box::new(ReservedView {
+            view: self.inner.view_pages(page_list)?,
+            _reservation: reservation,
+        }))
+    }
+
+    fn get(&self, stream_number: u32, limit: Option<usize>) -> Result<Stream<'s>> {
+        let reservation = self.reserve(self.requested_size(stream_number, limit)?)?;
+        let stream = self.inner.get(stream_number, limit)?;
+        Ok(attach(stream, reservation))
+    }
+
+    fn get_with_progress(
+        &self,
+        stream_number: u32,
+        limit: Option<usize>,
+        progress: &mut dyn FnMut(usize, usize),
+    ) -> Result<Stream<'s>> {
+        let reservation = self.reserve(self.requested_size(stream_number, limit)?)?;
+        let stream = self
+            .inner
+            .get_with_progress(stream_number, limit, progress)?;
+        Ok(attach(stream, reservation))
+    }
+
+    fn read_pieces(
+        &self,
+        stream_number: u32,
+        size: usize,
+        f: &mut dyn FnMut(&[u8]) -> Result<()>,
+    ) -> Result<()> {
+        // only one piece is viewed at a time
+        let _reservation = self.reserve(size.min(self.page_size() * COPY_PAGES))?;
+        self.inner.read_pieces(stream_number, size, f)
+    }
+
+    fn get_range(&self, stream_number: u32, offset: usize, len: usize) -> Result<Stream<'s>> {
+        let reservation = self.reserve(len)?;
+        let stream = self.inner.get_range(stream_number, offset, len)?;
+        Ok(attach(stream, reservation))
+    }
+
+    fn prefetch(&self, stream_numbers: &[u32]) -> Result<()> {
+        let mut size = 0usize;
+        for &stream_number in stream_numbers {
+            size = size.saturating_add(self.requested_size(stream_number, None)?);
+        }
+
+        let _reservation = self.reserve(size)?;
+        self.inner.prefetch(stream_numbers)
+    }
+
+    fn cached_bytes(&self) -> usize {
+        self.inner.cached_bytes()
+    }
+
+    fn evict_caches(&self) {
+        self.inner.evict_caches()
+    }
+
+    fn memory_in_use(&self) -> Option<usize> {
+        Some(self.budget.used.load(Ordering::Acquire) + self.inner.cached_bytes())
+    }
+}
@@ -5,28 +5,58 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
+use std::convert::TryFrom;
 use std::fmt;
-use std::ops::Deref;
+use std::io;
+use std::ops::{Deref, Range};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
 use scroll::{ctx::TryFromCtx, Endian, Pread};
 
 use crate::common::*;
 use crate::source::*;
 
+mod editor;
+mod free_page_map;
+#[cfg(feature = "hash")]
+mod hash;
+mod info;
+mod layout;
+mod limit;
+#[cfg(feature = "msfz")]
+mod msfz;
 mod page_list;
+mod previous;
+mod stream_reader;
+mod writer;
+pub use self::editor::MsfEditor;
+pub use self::free_page_map::{FreePageMap, FreePageMapIter};
+#[cfg(feature = "hash")]
+pub use self::hash::HashAlgorithm;
+pub use self::info::{MsfInfo, MsfVersion};
+pub use self::layout::StreamLayout;
+pub use self::limit::MemoryLimitPolicy;
+pub(crate) use self::limit::{LimitedMsf, MemoryBudget};
 use self::page_list::PageList;
+pub use self::previous::PreviousDirectory;
+pub use self::stream_reader::StreamReader;
+pub use self::writer::MsfWriter;
 
 type PageNumber = u32;
 
+/// The number of pages read at a time by `Msf::read_pieces`, which backs `Msf::copy_stream_to` and
+/// `Msf::get_with_progress`.
+const COPY_PAGES: usize = 16;
+
 #[derive(Debug, Copy, Clone)]
-struct Header {
+pub(crate) struct Header {
     page_size: usize,
     maximum_valid_page_number: PageNumber,
 }
 
 impl Header {
     fn pages_needed_to_store(&self, bytes: usize) -> usize {
-        (bytes + (self.page_size - 1)) / self.page_size
+        bytes.div_ceil(self.page_size)
     }
 
     fn validate_page_number(&self, page_number: u32) -> Result<PageNumber> {
@@ -38,28 +68,145 @@ impl Header {
     }
 }
 
-/// Represents a stream table at various stages of access
-#[doc(hidden)]
+/// The parsed stream table, describing the size and location of every stream.
 #[derive(Debug)]
-enum StreamTable<'s> {
-    /// The MSF header gives us the size of the table in bytes, and the list of pages (usually one)
-    /// where we can find the list of pages that contain the stream table.
-    HeaderOnly {
-        size_in_bytes: usize,
-        stream_table_location_location: PageList,
-    },
+pub(crate) struct StreamTable {
+    /// The size of each stream in bytes, `u32::MAX` indicating "stream does not exist".
+    sizes: Vec<u32>,
+    /// The index into `pages` of the first page of each stream.
+    offsets: Vec<usize>,
+    /// The page numbers of all streams, in stream order.
+    pages: Vec<PageNumber>,
+}
+
+impl StreamTable {
+    /// Parses the stream table, which is structured as:
+    ///
+    /// ```text
+    /// stream_count
+    /// 0..stream_count: size of stream in bytes (0xffffffff indicating "stream does not exist")
+    /// stream 0: PageNumber
+    /// stream 1: PageNumber, PageNumber
+    /// stream 2: PageNumber, PageNumber, PageNumber, PageNumber, PageNumber
+    /// stream 3: PageNumber, PageNumber, PageNumber, PageNumber
+    /// (number of pages determined by number of bytes)
+    /// ```
+    fn parse(header: &Header, data: &[u8]) -> Result<Self> {
+        let mut buf = ParseBuffer::from(data);
+        let stream_count = buf.parse_u32()? as usize;
+
+        // large PDBs have tens of thousands of streams, so check the claimed stream count and page
+        // count against the size of the stream table before parsing anything
+        if stream_count > buf.len() / 4 {
+            return Err(Error::InvalidStreamLength("MSF stream table"));
+        }
+
+        let mut sizes = Vec::with_capacity(stream_count);
+        for _ in 0..stream_count {
+            sizes.push(buf.parse_u32()?);
+        }
+
+        let page_count = sizes
+            .iter()
+            .filter(|&&bytes| bytes != u32::MAX)
+            .map(|&bytes| header.pages_needed_to_store(bytes as usize))
+            .sum::<usize>();
+        if page_count > buf.len() / 4 {
+            return Err(Error::InvalidStreamLength("MSF stream table"));
+        }
+
+        let mut offsets = Vec::with_capacity(sizes.len());
+        let mut pages = Vec::with_capacity(page_count);
+        for &bytes in &sizes {
+            offsets.push(pages.len());
+            if bytes != u32::MAX {
+                for _ in 0..header.pages_needed_to_store(bytes as usize) {
+                    pages.push(buf.parse_u32()?);
+                }
+            }
+        }
 
-    /// Given the HeaderOnly information, we can do an initial read to get the actual location of
-    /// the stream table as a PageList.
-    TableFound { stream_table_location: PageList },
+        Ok(StreamTable {
+            sizes,
+            offsets,
+            pages,
+        })
+    }
+
+    fn stream_count(&self) -> u32 {
+        self.sizes.len() as u32
+    }
+
+    fn stream_size(&self, stream_number: u32) -> Result<Option<u32>> {
+        match self.sizes.get(stream_number as usize) {
+            Some(&u32::MAX) => Ok(None),
+            Some(&bytes) => Ok(Some(bytes)),
+            None => Err(Error::StreamNotFound(stream_number)),
+        }
+    }
+
+    fn stream_sizes(&self) -> Vec<Option<u32>> {
+        self.sizes
+            .iter()
+            .map(|&bytes| if bytes == u32::MAX { None } else { Some(bytes) })
+            .collect()
+    }
+
+    /// Returns the raw page numbers of a stream, or `None` if the stream is not present.
+    fn stream_pages(&self, stream_number: u32) -> Result<Option<&[PageNumber]>> {
+        let index = stream_number as usize;
+        let bytes = match self.stream_size(stream_number)? {
+            Some(bytes) => bytes,
+            None => return Ok(None),
+        };
+
+        let start = self.offsets[index];
+        let end = match self.offsets.get(index + 1) {
+            Some(&end) => end,
+            None => self.pages.len(),
+        };
+
+        debug_assert!(bytes == 0 || end > start);
+        Ok(Some(&self.pages[start..end]))
+    }
+
+    /// Describes the pages of a stream as listed in the table.
+    fn layout(&self, header: &Header, stream_number: u32) -> Result<StreamLayout> {
+        match self.stream_pages(stream_number)? {
+            Some(pages) => Ok(StreamLayout::new(
+                header.page_size as u32,
+                self.sizes[stream_number as usize],
+                pages.to_vec(),
+            )),
+            None => Err(Error::StreamNotFound(stream_number)),
+        }
+    }
 
-    // Given the table location, we can access the stream table itself
-    Available {
-        stream_table_view: Box<dyn SourceView<'s>>,
-    },
+    /// Looks up the pages of a stream by stream number, validating them against `header`.
+    fn look_up_stream(&self, header: &Header, stream_number: u32) -> Result<PageList> {
+        let (bytes_in_stream, pages) = match self.stream_pages(stream_number)? {
+            Some(pages) => (self.sizes[stream_number as usize], pages),
+            None => return Err(Error::StreamNotFound(stream_number)),
+        };
+
+        // accumulate the stream's pages into a PageList
+        let mut list = PageList::new(header.page_size);
+        for &page_number in pages {
+            list.push(header.validate_page_number(page_number)?);
+        }
+
+        // truncate to the size of the stream
+        list.truncate(bytes_in_stream as usize);
+
+        // done!
+        Ok(list)
+    }
 }
 
-fn view<'s>(source: &mut dyn Source<'s>, page_list: &PageList) -> Result<Box<dyn SourceView<'s>>> {
+fn view<'s>(
+    source: &mut dyn Source<'s>,
+    page_list: &PageList,
+) -> Result<Box<dyn SourceView<'s> + 's>> {
     // view it
     let view = source.view(page_list.source_slices())?;
 
@@ -72,11 +219,59 @@ fn view<'s>(source: &mut dyn Source<'s>, page_list: &PageList) -> Result<Box<dyn
     Ok(view)
 }
 
+/// Views the pages of a stream layout, which are not validated.
+pub(crate) fn view_layout<'s>(
+    source: &mut dyn Source<'s>,
+    layout: &StreamLayout,
+) -> Result<Stream<'s>> {
+    let mut page_list = PageList::new(layout.page_size() as usize);
+    for &page in layout.pages() {
+        page_list.push(page);
+    }
+    page_list.truncate(layout.size() as usize);
+
+    Ok(Stream::new(view(source, &page_list)?))
+}
+
+/// Returns the page size declared by the header of a 7.00 multi-stream file, if the header is
+/// intact.
+pub(crate) fn header_page_size(header: &[u8]) -> Option<u32> {
+    if !header_matches(header, big::MAGIC) {
+        return None;
+    }
+
+    let mut buf = ParseBuffer::from(&header[big::MAGIC.len()..]);
+    match buf.parse_u32() {
+        Ok(page_size) if page_size.is_power_of_two() && page_size >= 0x200 => Some(page_size),
+        _ => None,
+    }
+}
+
+/// Returns whether a page of a 7.00 multi-stream file holds one of the free page maps.
+///
+/// Every interval of `page_size` pages reserves its second and third page for the two free page
+/// maps, whether or not the file is large enough to use them.
+pub(crate) fn is_free_page_map(page_size: u64, page: u64) -> bool {
+    matches!(page % page_size, 1 | 2)
+}
+
+/// Locks the source for reading.
+///
+/// A panic in another thread can only leave the source at an arbitrary seek position, which does
+/// not affect subsequent reads, so poisoning is ignored.
+fn lock<S>(source: &Mutex<S>) -> MutexGuard<'_, S> {
+    source.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
 mod big {
     use super::*;
 
     pub const MAGIC: &[u8] = b"Microsoft C/C++ MSF 7.00\r\n\x1a\x44\x53\x00\x00\x00";
 
+    /// The size of the fixed part of the header, i.e. everything up to the list of pages that
+    /// store the stream table page list.
+    pub const HEADER_SIZE: usize = 52;
+
     /// The PDB header as stored on disk.
     ///
     /// See the Microsoft code for reference: <https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/PDB/msf/msf.cpp#L946>
@@ -113,14 +308,22 @@ mod big {
     }
 
     #[derive(Debug)]
-    pub struct BigMSF<'s, S> {
+    pub struct BigMSF<S> {
         header: Header,
-        source: S,
-        stream_table: StreamTable<'s>,
+        free_page_map: u32,
+        info: MsfInfo,
+        source: Mutex<S>,
+        stream_table_location_location: PageList,
+        stream_table_location: PageList,
+        stream_table: StreamTable,
     }
 
-    impl<'s, S: Source<'s>> BigMSF<'s, S> {
-        pub fn new(source: S, header_view: Box<dyn SourceView<'_>>) -> Result<BigMSF<'s, S>> {
+    impl<'s, S: Source<'s>> BigMSF<S> {
+        pub fn new(
+            mut source: S,
+            header_view: Box<dyn SourceView<'s> + 's>,
+            directory_limit: usize,
+        ) -> Result<BigMSF<S>> {
             let mut buf = ParseBuffer::from(header_view.as_slice());
             let header: RawHeader = buf.parse()?;
 
@@ -140,6 +343,10 @@ mod big {
                 maximum_valid_page_number: header.pages_used,
             };
 
+            if header.directory_size as usize > directory_limit {
+                return Err(Error::MemoryLimitExceeded(header.directory_size as usize));
+            }
+
             // calculate how many pages are needed to store the stream table
             let size_of_stream_table_in_pages =
                 header_object.pages_needed_to_store(header.directory_size as usize);
@@ -149,6 +356,15 @@ mod big {
             let size_of_stream_table_page_list_in_pages =
                 header_object.pages_needed_to_store(size_of_stream_table_in_pages * 4);
 
+            // the list of stream table page list pages must fit into the header page, which limits
+            // the size of the stream table for small page sizes
+            let header_capacity = (header_object.page_size - HEADER_SIZE) / 4;
+            if size_of_stream_table_page_list_in_pages > header_capacity {
+                return Err(Error::MsfLimitExceeded(
+                    "stream table page list exceeds the header page",
+                ));
+            }
+
             // read the list of stream table page list pages, which immediately follow the header
             // yes, this is a stupid level of indirection
             let mut stream_table_page_list_page_list = PageList::new(header_object.page_size);
@@ -160,204 +376,376 @@ mod big {
             // truncate the stream table location location to the correct size
             stream_table_page_list_page_list.truncate(size_of_stream_table_in_pages * 4);
 
+            // the header indicated we need to read size_in_pages page numbers from the
+            // specified PageList, so ask to view the location location
+            let location_location = view(&mut source, &stream_table_page_list_page_list)?;
+
+            // build a PageList
+            let mut stream_table_location = PageList::new(header_object.page_size);
+            let mut buf = ParseBuffer::from(location_location.as_slice());
+            while !buf.is_empty() {
+                let n = buf.parse_u32()?;
+                stream_table_location.push(header_object.validate_page_number(n)?);
+            }
+
+            stream_table_location.truncate(header.directory_size as usize);
+
+            // given the table location, we can access the stream table itself
+            let stream_table_view = view(&mut source, &stream_table_location)?;
+            let stream_table = StreamTable::parse(&header_object, stream_table_view.as_slice())?;
+
             Ok(BigMSF {
                 header: header_object,
-                source,
-                stream_table: StreamTable::HeaderOnly {
-                    size_in_bytes: header.directory_size as usize,
-                    stream_table_location_location: stream_table_page_list_page_list,
-                },
+                free_page_map: header.free_page_map,
+                info: MsfInfo::new(
+                    MsfVersion::V700,
+                    header.page_size,
+                    header.pages_used,
+                    header.directory_size,
+                    Some(header.free_page_map),
+                ),
+                source: Mutex::new(source),
+                stream_table_location_location: stream_table_page_list_page_list,
+                stream_table_location,
+                stream_table,
             })
         }
+    }
 
-        fn find_stream_table(&mut self) -> Result<()> {
-            let mut new_stream_table: Option<StreamTable<'_>> = None;
+    impl<'s, S: Source<'s>> Msf<'s, S> for BigMSF<S> {
+        fn page_size(&self) -> usize {
+            self.header.page_size
+        }
 
-            if let StreamTable::HeaderOnly {
-                size_in_bytes,
-                ref stream_table_location_location,
-            } = self.stream_table
-            {
-                // the header indicated we need to read size_in_pages page numbers from the
-                // specified PageList.
-
-                // ask to view the location location
-                let location_location = view(&mut self.source, stream_table_location_location)?;
-
-                // build a PageList
-                let mut page_list = PageList::new(self.header.page_size);
-                let mut buf = ParseBuffer::from(location_location.as_slice());
-                while !buf.is_empty() {
-                    let n = buf.parse_u32()?;
-                    page_list.push(self.header.validate_page_number(n)?);
-                }
+        fn info(&self) -> MsfInfo {
+            self.info
+        }
 
-                page_list.truncate(size_in_bytes);
+        fn stream_count(&self) -> u32 {
+            self.stream_table.stream_count()
+        }
 
-                // remember what we learned
-                new_stream_table = Some(StreamTable::TableFound {
-                    stream_table_location: page_list,
-                });
-            }
+        fn stream_size(&self, stream_number: u32) -> Result<Option<u32>> {
+            self.stream_table.stream_size(stream_number)
+        }
 
-            if let Some(st) = new_stream_table {
-                self.stream_table = st;
+        fn stream_sizes(&self) -> Vec<Option<u32>> {
+            self.stream_table.stream_sizes()
+        }
+
+        fn previous_stream_table(&self) -> Result<Option<(Header, StreamTable)>> {
+            match self.stream_table.stream_size(0) {
+                Ok(Some(size)) if size > 0 => (),
+                _ => return Ok(None),
             }
 
-            Ok(())
+            let stream = self.get(0, None)?;
+            let stream_table = StreamTable::parse(&self.header, stream.as_slice())?;
+            Ok(Some((self.header, stream_table)))
+        }
+
+        fn stream_layout(&self, stream_number: u32) -> Result<StreamLayout> {
+            self.stream_table.layout(&self.header, stream_number)
         }
 
-        fn make_stream_table_available(&mut self) -> Result<()> {
-            // do the initial read if we must
-            if let StreamTable::HeaderOnly { .. } = self.stream_table {
-                self.find_stream_table()?;
+        fn directory_pages(&self) -> Result<Vec<PageNumber>> {
+            let mut pages = self.stream_table_location_location.pages();
+            pages.extend(self.stream_table_location.pages());
+            Ok(pages)
+        }
+
+        fn look_up_stream(&self, stream_number: u32) -> Result<PageList> {
+            self.stream_table
+                .look_up_stream(&self.header, stream_number)
+        }
+
+        fn view_pages(&self, page_list: &PageList) -> Result<Box<dyn SourceView<'s> + 's>> {
+            view(&mut *lock(&self.source), page_list)
+        }
+
+        fn free_page_map(&self) -> Result<FreePageMap> {
+            if self.free_page_map != 1 && self.free_page_map != 2 {
+                return Err(Error::InvalidFreePageMap(self.free_page_map));
             }
 
-            // do we need to map the stream table itself?
-            let mut new_stream_table = None;
-            if let StreamTable::TableFound {
-                ref stream_table_location,
-            } = self.stream_table
-            {
-                // ask the source to view it
-                let stream_table_view = view(&mut self.source, stream_table_location)?;
-                new_stream_table = Some(StreamTable::Available { stream_table_view });
+            // the free page map is a bitmap with one bit per page, which is stored in one page per
+            // interval of page_size pages at offset free_page_map within that interval
+            let page_size = self.header.page_size;
+            let page_count = self.header.maximum_valid_page_number;
+            let bitmap_size = (page_count as usize).div_ceil(8);
+
+            let mut page_list = PageList::new(page_size);
+            for interval in 0..bitmap_size.div_ceil(page_size) {
+                let page = (interval * page_size) as u32 + self.free_page_map;
+                page_list.push(self.header.validate_page_number(page)?);
             }
+            page_list.truncate(bitmap_size);
 
-            if let Some(st) = new_stream_table {
-                self.stream_table = st;
+            let bitmap = view(&mut *lock(&self.source), &page_list)?;
+            Ok(FreePageMap::new(bitmap.as_slice().to_vec(), page_count))
+        }
+
+        fn validate_free_page_map(&self) -> Result<()> {
+            let free_page_map = self.free_page_map()?;
+            let check = |page: PageNumber| {
+                if free_page_map.is_free(page) {
+                    Err(Error::PageMarkedFree(page))
+                } else {
+                    Ok(())
+                }
+            };
+
+            // the header is always in use
+            check(0)?;
+
+            for page in self.stream_table_location_location.pages() {
+                check(page)?;
+            }
+            for page in self.stream_table_location.pages() {
+                check(page)?;
             }
 
-            // stream table is available
-            assert!(matches!(self.stream_table, StreamTable::Available { .. }));
+            // stream 0 holds the previous stream table, whose pages are released when the current
+            // stream table is committed
+            for stream_number in 1..self.stream_table.stream_count() {
+                if let Some(pages) = self.stream_table.stream_pages(stream_number)? {
+                    for &page_number in pages {
+                        check(self.header.validate_page_number(page_number)?)?;
+                    }
+                }
+            }
 
             Ok(())
         }
+    }
+}
 
-        fn look_up_stream(&mut self, stream_number: u32) -> Result<PageList> {
-            // ensure the stream table is available
-            self.make_stream_table_available()?;
+mod small {
+    use super::*;
 
-            let header = self.header;
+    pub const MAGIC: &[u8] = b"Microsoft C/C++ program database 2.00\r\n\x1a\x4a\x47";
 
-            // declare the things we're going to find
-            let bytes_in_stream: u32;
-            let page_list: PageList;
+    /// The size of the fixed part of the header, including the padded magic.
+    const HEADER_SIZE: usize = 60;
 
-            if let StreamTable::Available {
-                ref stream_table_view,
-            } = self.stream_table
-            {
-                let stream_table_slice = stream_table_view.as_slice();
-                let mut stream_table = ParseBuffer::from(stream_table_slice);
-
-                // the stream table is structured as:
-                // stream_count
-                // 0..stream_count: size of stream in bytes (0xffffffff indicating "stream does not exist")
-                // stream 0: PageNumber
-                // stream 1: PageNumber, PageNumber
-                // stream 2: PageNumber, PageNumber, PageNumber, PageNumber, PageNumber
-                // stream 3: PageNumber, PageNumber, PageNumber, PageNumber
-                // (number of pages determined by number of bytes)
-
-                let stream_count = stream_table.parse_u32()?;
-
-                // check if we've already outworn our welcome
-                if stream_number >= stream_count {
-                    return Err(Error::StreamNotFound(stream_number));
-                }
+    /// The PDB 2.00 header as stored on disk.
+    ///
+    /// Page numbers are 16 bits wide, and the pages of the stream table are listed directly in the
+    /// header following the fixed part.
+    #[derive(Debug, Copy, Clone)]
+    struct RawHeader {
+        page_size: u32,
+        free_page_map: u16,
+        pages_used: u16,
+        directory_size: u32,
+    }
 
-                // we now have {stream_count} u32s describing the length of each stream
-
-                // walk over the streams before the requested stream
-                // we need to pay attention to how big each one is, since their page numbers come
-                // before our page numbers in the stream table
-                let mut page_numbers_to_skip: usize = 0;
-                for _ in 0..stream_number {
-                    let bytes = stream_table.parse_u32()?;
-                    if bytes == u32::max_value() {
-                        // stream is not present, ergo nothing to skip
-                    } else {
-                        page_numbers_to_skip += header.pages_needed_to_store(bytes as usize);
-                    }
-                }
+    impl RawHeader {
+        fn parse(buf: &mut ParseBuffer<'_>) -> Result<Self> {
+            // the magic is padded to 44 bytes
+            buf.take(44)?;
+            let header = RawHeader {
+                page_size: buf.parse_u32()?,
+                free_page_map: buf.parse_u16()?,
+                pages_used: buf.parse_u16()?,
+                directory_size: buf.parse_u32()?,
+            };
 
-                // read our stream's size
-                bytes_in_stream = stream_table.parse_u32()?;
-                if bytes_in_stream == u32::max_value() {
-                    return Err(Error::StreamNotFound(stream_number));
-                }
-                let pages_in_stream = header.pages_needed_to_store(bytes_in_stream as usize);
+            // unused in-memory pointer of the stream table
+            buf.parse_u32()?;
+            debug_assert_eq!(buf.pos(), HEADER_SIZE);
+            Ok(header)
+        }
+    }
 
-                // skip the remaining streams' byte counts
-                let _ = stream_table.take((stream_count - stream_number - 1) as usize * 4)?;
+    /// Parses the stream table of a PDB 2.00 file, which is structured as:
+    ///
+    /// ```text
+    /// stream_count: u16, reserved: u16
+    /// 0..stream_count: size of stream in bytes (0xffffffff indicating "stream does not exist"),
+    ///                  followed by an unused u32
+    /// 0..stream_count: u16 page numbers of each stream
+    /// ```
+    fn parse_stream_table(header: &Header, data: &[u8]) -> Result<StreamTable> {
+        let mut buf = ParseBuffer::from(data);
+        let stream_count = buf.parse_u16()? as usize;
+        buf.parse_u16()?;
+
+        if stream_count > buf.len() / 8 {
+            return Err(Error::InvalidStreamLength("MSF stream table"));
+        }
 
-                // skip the preceding streams' page numbers
-                let _ = stream_table.take((page_numbers_to_skip as usize) * 4)?;
+        let mut sizes = Vec::with_capacity(stream_count);
+        for _ in 0..stream_count {
+            sizes.push(buf.parse_u32()?);
+            buf.parse_u32()?;
+        }
 
-                // we're now at the list of pages for our stream
-                // accumulate them into a PageList
-                let mut list = PageList::new(header.page_size);
-                for _ in 0..pages_in_stream {
-                    let page_number = stream_table.parse_u32()?;
-                    list.push(self.header.validate_page_number(page_number)?);
+        let mut offsets = Vec::with_capacity(sizes.len());
+        let mut pages = Vec::with_capacity(buf.len() / 2);
+        for &bytes in &sizes {
+            offsets.push(pages.len());
+            if bytes != u32::MAX {
+                for _ in 0..header.pages_needed_to_store(bytes as usize) {
+                    pages.push(PageNumber::from(buf.parse_u16()?));
                 }
+            }
+        }
 
-                // truncate to the size of the stream
-                list.truncate(bytes_in_stream as usize);
+        Ok(StreamTable {
+            sizes,
+            offsets,
+            pages,
+        })
+    }
 
-                page_list = list;
-            } else {
-                unreachable!();
+    /// A multi-stream file in the format used by PDB 2.00, as written by Visual C++ 2.0 to 6.0.
+    #[derive(Debug)]
+    pub struct SmallMSF<S> {
+        header: Header,
+        info: MsfInfo,
+        source: Mutex<S>,
+        stream_table: StreamTable,
+    }
+
+    impl<'s, S: Source<'s>> SmallMSF<S> {
+        pub fn new(
+            mut source: S,
+            header_view: Box<dyn SourceView<'s> + 's>,
+            directory_limit: usize,
+        ) -> Result<Self> {
+            let mut buf = ParseBuffer::from(header_view.as_slice());
+            let header = RawHeader::parse(&mut buf)?;
+
+            if header.page_size.count_ones() != 1
+                || header.page_size < 0x100
+                || header.page_size > 0x1000
+            {
+                return Err(Error::InvalidPageSize(header.page_size));
             }
 
-            // done!
-            Ok(page_list)
+            let header_object = Header {
+                page_size: header.page_size as usize,
+                maximum_valid_page_number: PageNumber::from(header.pages_used),
+            };
+
+            if header.directory_size as usize > directory_limit {
+                return Err(Error::MemoryLimitExceeded(header.directory_size as usize));
+            }
+
+            // the pages of the stream table are listed directly after the fixed header
+            let size_of_stream_table_in_pages =
+                header_object.pages_needed_to_store(header.directory_size as usize);
+            let header_capacity = (header_object.page_size - HEADER_SIZE) / 2;
+            if size_of_stream_table_in_pages > header_capacity {
+                return Err(Error::MsfLimitExceeded(
+                    "stream table exceeds the header page",
+                ));
+            }
+
+            let mut stream_table_location = PageList::new(header_object.page_size);
+            for _ in 0..size_of_stream_table_in_pages {
+                let n = PageNumber::from(buf.parse_u16()?);
+                stream_table_location.push(header_object.validate_page_number(n)?);
+            }
+            stream_table_location.truncate(header.directory_size as usize);
+
+            let stream_table_view = view(&mut source, &stream_table_location)?;
+            let stream_table = parse_stream_table(&header_object, stream_table_view.as_slice())?;
+
+            Ok(SmallMSF {
+                header: header_object,
+                info: MsfInfo::new(
+                    MsfVersion::V200,
+                    header.page_size,
+                    u32::from(header.pages_used),
+                    header.directory_size,
+                    Some(u32::from(header.free_page_map)),
+                ),
+                source: Mutex::new(source),
+                stream_table,
+            })
         }
     }
 
-    impl<'s, S: Source<'s>> Msf<'s, S> for BigMSF<'s, S> {
-        fn get(&mut self, stream_number: u32, limit: Option<usize>) -> Result<Stream<'s>> {
-            // look up the stream
-            let mut page_list = self.look_up_stream(stream_number)?;
+    impl<'s, S: Source<'s>> Msf<'s, S> for SmallMSF<S> {
+        fn page_size(&self) -> usize {
+            self.header.page_size
+        }
+
+        fn info(&self) -> MsfInfo {
+            self.info
+        }
+
+        fn stream_count(&self) -> u32 {
+            self.stream_table.stream_count()
+        }
+
+        fn stream_size(&self, stream_number: u32) -> Result<Option<u32>> {
+            self.stream_table.stream_size(stream_number)
+        }
+
+        fn stream_sizes(&self) -> Vec<Option<u32>> {
+            self.stream_table.stream_sizes()
+        }
 
-            // apply any limits we have
-            if let Some(limit) = limit {
-                page_list.truncate(limit);
+        fn previous_stream_table(&self) -> Result<Option<(Header, StreamTable)>> {
+            match self.stream_table.stream_size(0) {
+                Ok(Some(size)) if size > 0 => (),
+                _ => return Ok(None),
             }
 
-            // now that we know where this stream lives, we can view it
-            let view = view(&mut self.source, &page_list)?;
+            let stream = self.get(0, None)?;
+            let stream_table = parse_stream_table(&self.header, stream.as_slice())?;
+            Ok(Some((self.header, stream_table)))
+        }
 
-            // pack it into a Stream
-            let stream = Stream { source_view: view };
+        fn stream_layout(&self, stream_number: u32) -> Result<StreamLayout> {
+            self.stream_table.layout(&self.header, stream_number)
+        }
 
-            Ok(stream)
+        fn look_up_stream(&self, stream_number: u32) -> Result<PageList> {
+            self.stream_table
+                .look_up_stream(&self.header, stream_number)
         }
-    }
-}
 
-mod small {
-    pub const MAGIC: &[u8] = b"Microsoft C/C++ program database 2.00\r\n\x1a\x4a\x47";
-    // TODO: implement SmallMSF
+        fn view_pages(&self, page_list: &PageList) -> Result<Box<dyn SourceView<'s> + 's>> {
+            view(&mut *lock(&self.source), page_list)
+        }
+
+        fn free_page_map(&self) -> Result<FreePageMap> {
+            Err(Error::UnimplementedFeature(
+                "free page map of PDB 2.00 files",
+            ))
+        }
+
+        fn validate_free_page_map(&self) -> Result<()> {
+            self.free_page_map().map(|_| ())
+        }
+    }
 }
 
 /// Represents a single Stream within the multi-stream file.
 #[derive(Debug)]
 pub struct Stream<'s> {
-    source_view: Box<dyn SourceView<'s>>,
+    source_view: Box<dyn SourceView<'s> + 's>,
+    range: Range<usize>,
 }
 
 impl<'s> Stream<'s> {
+    fn new(source_view: Box<dyn SourceView<'s> + 's>) -> Self {
+        let range = 0..source_view.as_slice().len();
+        Stream { source_view, range }
+    }
+
     #[inline]
     pub(crate) fn parse_buffer(&self) -> ParseBuffer<'_> {
-        let slice = self.source_view.as_slice();
-        ParseBuffer::from(slice)
+        ParseBuffer::from(self.as_slice())
     }
 
     #[inline]
     pub fn as_slice(&self) -> &[u8] {
-        self.source_view.as_slice()
+        &self.source_view.as_slice()[self.range.clone()]
     }
 }
 
@@ -370,17 +758,305 @@ impl Deref for Stream<'_> {
     }
 }
 
+/// A view over stream data that was assembled in memory.
+struct OwnedView {
+    bytes: Vec<u8>,
+}
+
+impl fmt::Debug for OwnedView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OwnedView({} bytes)", self.bytes.len())
+    }
+}
+
+impl SourceView<'_> for OwnedView {
+    fn as_slice(&self) -> &[u8] {
+        self.bytes.as_slice()
+    }
+}
+
+/// Describes a stream that is present in the multi-stream file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StreamEntry {
+    /// The index of the stream.
+    pub index: StreamIndex,
+    /// The size of the stream in bytes.
+    pub size: u32,
+}
+
+/// An iterator over all present streams of a multi-stream file.
+///
+/// Returned by [`PDB::present_streams`](crate::PDB::present_streams).
+#[derive(Debug)]
+pub struct StreamIter {
+    sizes: std::iter::Enumerate<std::vec::IntoIter<Option<u32>>>,
+}
+
+impl Iterator for StreamIter {
+    type Item = StreamEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (number, size) in &mut self.sizes {
+            // streams beyond the range of StreamIndex cannot be referenced by PDB structures
+            let index = match u16::try_from(number) {
+                Ok(index) if index != u16::MAX => StreamIndex(index),
+                _ => return None,
+            };
+
+            if let Some(size) = size {
+                return Some(StreamEntry { index, size });
+            }
+        }
+
+        None
+    }
+}
+
 /// Provides access to a "multi-stream file", which is the container format used by PDBs.
-pub trait Msf<'s, S>: fmt::Debug {
+pub trait Msf<'s, S>: fmt::Debug + MaybeSendSync {
+    /// Returns the size of a page in bytes.
+    fn page_size(&self) -> usize;
+
+    /// Returns metadata about the container, as recorded in its header.
+    fn info(&self) -> MsfInfo;
+
+    /// Returns the number of streams, including streams that are not present.
+    fn stream_count(&self) -> u32;
+
+    /// Returns the size of a stream in bytes, or `None` if the stream is not present.
+    fn stream_size(&self, stream_number: u32) -> Result<Option<u32>>;
+
+    /// Returns the sizes of all streams in bytes, with `None` for streams that are not present.
+    fn stream_sizes(&self) -> Vec<Option<u32>>;
+
+    /// Returns an iterator over all present streams.
+    fn present_streams(&self) -> StreamIter {
+        StreamIter {
+            sizes: self.stream_sizes().into_iter().enumerate(),
+        }
+    }
+
+    /// Reads the active free page map.
+    fn free_page_map(&self) -> Result<FreePageMap>;
+
+    /// Checks that every page referenced by the header and the stream table is marked as allocated
+    /// in the active free page map.
+    fn validate_free_page_map(&self) -> Result<()>;
+
+    /// Parses the stream table as of the previous write, which is kept in stream 0.
+    ///
+    /// Returns `None` if stream 0 is empty or not present.
+    fn previous_stream_table(&self) -> Result<Option<(Header, StreamTable)>>;
+
+    /// Describes which pages store a stream, without validating them.
+    fn stream_layout(&self, stream_number: u32) -> Result<StreamLayout>;
+
+    /// Returns the pages holding the stream table and the list of its pages.
+    fn directory_pages(&self) -> Result<Vec<PageNumber>> {
+        Err(Error::UnimplementedFeature(
+            "directory pages of containers other than MSF 7.00",
+        ))
+    }
+
+    /// Looks up the pages of a stream by stream number.
+    fn look_up_stream(&self, stream_number: u32) -> Result<PageList>;
+
+    /// Provides a contiguous view of the given pages.
+    fn view_pages(&self, page_list: &PageList) -> Result<Box<dyn SourceView<'s> + 's>>;
+
+    /// Returns the number of bytes held in internal caches.
+    fn cached_bytes(&self) -> usize {
+        0
+    }
+
+    /// Discards all data held in internal caches.
+    fn evict_caches(&self) {}
+
+    /// Charges allocations made internally while reading streams, such as for decompressing data,
+    /// against `budget`.
+    fn set_memory_budget(&mut self, _budget: Arc<MemoryBudget>) {}
+
+    /// Returns the number of bytes of views which are currently alive, including internal caches,
+    /// if this is tracked.
+    fn memory_in_use(&self) -> Option<usize> {
+        None
+    }
+
     /// Accesses a stream by stream number, optionally restricted by a byte limit.
-    fn get(&mut self, stream_number: u32, limit: Option<usize>) -> Result<Stream<'s>>;
+    fn get(&self, stream_number: u32, limit: Option<usize>) -> Result<Stream<'s>> {
+        // look up the stream
+        let mut page_list = self.look_up_stream(stream_number)?;
+
+        // apply any limits we have
+        if let Some(limit) = limit {
+            page_list.truncate(limit);
+        }
+
+        // now that we know where this stream lives, we can view it
+        let source_view = self.view_pages(&page_list)?;
+
+        // pack it into a Stream
+        Ok(Stream::new(source_view))
+    }
+
+    /// Accesses a stream like `get`, calling `progress` with the number of bytes read so far and
+    /// the total number of bytes to read.
+    ///
+    /// Streams spanning more than a few pages are read in pieces and assembled into a single owned
+    /// buffer, so that `progress` is called repeatedly while large streams are loaded. Smaller
+    /// streams are viewed directly.
+    fn get_with_progress(
+        &self,
+        stream_number: u32,
+        limit: Option<usize>,
+        progress: &mut dyn FnMut(usize, usize),
+    ) -> Result<Stream<'s>> {
+        let size = match self.stream_size(stream_number)? {
+            Some(size) => limit.map_or(size as usize, |limit| limit.min(size as usize)),
+            None => return Err(Error::StreamNotFound(stream_number)),
+        };
+
+        progress(0, size);
+
+        let piece = self.page_size() * COPY_PAGES;
+        if size <= piece {
+            let stream = self.get(stream_number, limit)?;
+            progress(size, size);
+            return Ok(stream);
+        }
+
+        let mut bytes = Vec::with_capacity(size);
+        self.read_pieces(stream_number, size, &mut |piece| {
+            bytes.extend_from_slice(piece);
+            progress(bytes.len(), size);
+            Ok(())
+        })?;
+
+        Ok(Stream::new(Box::new(OwnedView { bytes })))
+    }
+
+    /// Views the pages of several streams with a single request, in the order of their location
+    /// in the file.
+    ///
+    /// The contents are discarded, so this only has an effect if the `Source` retains them.
+    fn prefetch(&self, stream_numbers: &[u32]) -> Result<()> {
+        let page_size = self.page_size() as u64;
+
+        let mut pages = Vec::new();
+        for &stream_number in stream_numbers {
+            let page_list = self.look_up_stream(stream_number)?;
+            for slice in page_list.source_slices() {
+                let first = slice.offset / page_size;
+                let last = (slice.offset + slice.size as u64).div_ceil(page_size);
+                pages.extend((first..last).map(|page| page as PageNumber));
+            }
+        }
+
+        pages.sort_unstable();
+        pages.dedup();
+
+        // sorted pages are coalesced into as few slices as possible
+        let mut page_list = PageList::new(self.page_size());
+        for page in pages {
+            page_list.push(page);
+        }
+
+        self.view_pages(&page_list)?;
+        Ok(())
+    }
+
+    /// Writes the contents of a stream to `writer`, returning the number of bytes written.
+    ///
+    /// The stream is read in bounded pieces of a few pages each, so copying does not require
+    /// holding the entire stream in memory.
+    fn copy_stream_to(&self, stream_number: u32, writer: &mut dyn io::Write) -> Result<u64> {
+        let size = match self.stream_size(stream_number)? {
+            Some(size) => size as usize,
+            None => return Err(Error::StreamNotFound(stream_number)),
+        };
+
+        self.read_pieces(stream_number, size, &mut |piece| {
+            Ok(writer.write_all(piece)?)
+        })?;
+        Ok(size as u64)
+    }
+
+    /// Calls `f` with consecutive pieces of the first `size` bytes of a stream, each spanning at
+    /// most `COPY_PAGES` pages.
+    ///
+    /// The pages of the stream are looked up once, and only one piece is viewed at a time.
+    fn read_pieces(
+        &self,
+        stream_number: u32,
+        size: usize,
+        f: &mut dyn FnMut(&[u8]) -> Result<()>,
+    ) -> Result<()> {
+        let mut page_list = self.look_up_stream(stream_number)?;
+        if size > page_list.len() {
+            return Err(Error::UnexpectedEof);
+        }
+
+        page_list.truncate(size);
+        for piece in page_list.chunks(self.page_size() * COPY_PAGES) {
+            let source_view = self.view_pages(&piece)?;
+            f(source_view.as_slice())?;
+        }
+
+        Ok(())
+    }
+
+    /// Computes a digest of the contents of a stream.
+    ///
+    /// Like `copy_stream_to`, this reads the stream in bounded pieces.
+    #[cfg(feature = "hash")]
+    fn stream_hash(&self, stream_number: u32, algorithm: HashAlgorithm) -> Result<Vec<u8>> {
+        let mut hasher = hash::StreamHasher::new(algorithm);
+        self.copy_stream_to(stream_number, &mut hasher)?;
+        Ok(hasher.finish())
+    }
+
+    /// Accesses `len` bytes of a stream starting at `offset`.
+    ///
+    /// Only the pages covering the requested range are viewed, which avoids mapping the entire
+    /// stream when only a small window is needed.
+    fn get_range(&self, stream_number: u32, offset: usize, len: usize) -> Result<Stream<'s>> {
+        let page_list = self.look_up_stream(stream_number)?;
+        self.view_range(&page_list, offset, len)
+    }
+
+    /// Accesses `len` bytes starting at `offset` of a stream whose pages were already looked up.
+    fn view_range(&self, page_list: &PageList, offset: usize, len: usize) -> Result<Stream<'s>> {
+        match offset.checked_add(len) {
+            Some(end) if end <= page_list.len() => (),
+            _ => return Err(Error::UnexpectedEof),
+        }
+
+        let (page_list, start) = page_list.range(offset, len);
+        let source_view = self.view_pages(&page_list)?;
+
+        Ok(Stream {
+            source_view,
+            range: start..start + len,
+        })
+    }
 }
 
 fn header_matches(actual: &[u8], expected: &[u8]) -> bool {
     actual.len() >= expected.len() && &actual[0..expected.len()] == expected
 }
 
-pub fn open_msf<'s, S: Source<'s> + 's>(mut source: S) -> Result<Box<dyn Msf<'s, S> + 's>> {
+/// The signature of compressed MSFZ containers.
+const MSFZ_MAGIC: &[u8] = b"Microsoft MSFZ Container\r\n\x1aALD\0\0";
+
+pub fn open_msf<'s, S: Source<'s> + 's>(source: S) -> Result<Box<dyn Msf<'s, S> + 's>> {
+    open_msf_with_directory_limit(source, usize::MAX)
+}
+
+/// Opens a multi-stream file, refusing to read stream directories larger than `directory_limit`.
+pub fn open_msf_with_directory_limit<'s, S: Source<'s> + 's>(
+    mut source: S,
+    directory_limit: usize,
+) -> Result<Box<dyn Msf<'s, S> + 's>> {
     // map the header
     let mut header_location = PageList::new(4096);
     header_location.push(0);
@@ -401,13 +1077,27 @@ pub fn open_msf<'s, S: Source<'s> + 's>(mut source: S) -> Result<Box<dyn Msf<'s,
     // see if it's a BigMSF
     if header_matches(header_view.as_slice(), big::MAGIC) {
         // claimed!
-        let bigmsf = big::BigMSF::new(source, header_view)?;
+        let bigmsf = big::BigMSF::new(source, header_view, directory_limit)?;
         return Ok(Box::new(bigmsf));
     }
 
+    if header_matches(header_view.as_slice(), MSFZ_MAGIC) {
+        #[cfg(feature = "msfz")]
+        return Ok(Box::new(msfz::MsfzFile::new(
+            source,
+            header_view,
+            directory_limit,
+        )?));
+
+        #[cfg(not(feature = "msfz"))]
+        return Err(Error::UnimplementedFeature(
+            "MSFZ containers require the `msfz` feature",
+        ));
+    }
+
     if header_matches(header_view.as_slice(), small::MAGIC) {
-        // sorry
-        return Err(Error::UnimplementedFeature("small MSF file format"));
+        let smallmsf = small::SmallMSF::new(source, header_view, directory_limit)?;
+        return Ok(Box::new(smallmsf));
     }
 
     Err(Error::UnrecognizedFileFormat)
@@ -471,5 +1161,585 @@ mod tests {
                 },
             };
         }
+
+        #[test]
+        fn test_stream_table_page_list_exceeds_header() {
+            // with 512 byte pages, the header holds 115 page list pages of 128 entries each
+            let directory_size: u32 = 116 * 128 * 512;
+
+            let mut file = Vec::new();
+            file.extend_from_slice(crate::msf::big::MAGIC);
+            file.extend_from_slice(&512u32.to_le_bytes());
+            file.extend_from_slice(&1u32.to_le_bytes());
+            file.extend_from_slice(&0x0010_0000u32.to_le_bytes());
+            file.extend_from_slice(&directory_size.to_le_bytes());
+            file.extend_from_slice(&0u32.to_le_bytes());
+            file.resize(4096, 0);
+
+            assert!(matches!(
+                open_msf(std::io::Cursor::new(file)),
+                Err(Error::MsfLimitExceeded(_))
+            ));
+        }
+    }
+
+    mod small {
+        use std::io::Cursor;
+
+        use crate::common::*;
+        use crate::msf::{open_msf, small, MsfInfo, MsfVersion};
+
+        /// Builds a PDB 2.00 file with 1024 byte pages.
+        fn small_msf() -> Vec<u8> {
+            const PAGE_SIZE: usize = 1024;
+            let mut file = vec![0; 6 * PAGE_SIZE];
+
+            // stream table on page 2: four streams, one of them not present
+            let mut stream_table = Vec::new();
+            stream_table.extend_from_slice(&4u16.to_le_bytes());
+            stream_table.extend_from_slice(&0u16.to_le_bytes());
+            for size in &[0u32, 5, u32::MAX, 1500] {
+                stream_table.extend_from_slice(&size.to_le_bytes());
+                stream_table.extend_from_slice(&0u32.to_le_bytes());
+            }
+            for page in &[3u16, 5, 4] {
+                stream_table.extend_from_slice(&page.to_le_bytes());
+            }
+            file[2 * PAGE_SIZE..2 * PAGE_SIZE + stream_table.len()].copy_from_slice(&stream_table);
+
+            // stream contents
+            file[3 * PAGE_SIZE..3 * PAGE_SIZE + 5].copy_from_slice(b"hello");
+            for (i, byte) in file[4 * PAGE_SIZE..6 * PAGE_SIZE].iter_mut().enumerate() {
+                *byte = if i < PAGE_SIZE { 2 } else { 1 };
+            }
+
+            let mut header = small::MAGIC.to_vec();
+            header.resize(44, 0);
+            header.extend_from_slice(&(PAGE_SIZE as u32).to_le_bytes());
+            header.extend_from_slice(&1u16.to_le_bytes());
+            header.extend_from_slice(&6u16.to_le_bytes());
+            header.extend_from_slice(&(stream_table.len() as u32).to_le_bytes());
+            header.extend_from_slice(&0u32.to_le_bytes());
+            header.extend_from_slice(&2u16.to_le_bytes());
+            file[..header.len()].copy_from_slice(&header);
+
+            file
+        }
+
+        #[test]
+        fn test_streams() {
+            let file = small_msf();
+            let msf = open_msf(Cursor::new(file.as_slice())).expect("open");
+
+            assert_eq!(msf.page_size(), 1024);
+            assert_eq!(
+                msf.info(),
+                MsfInfo::new(MsfVersion::V200, 1024, 6, 42, Some(1))
+            );
+            assert_eq!(msf.stream_count(), 4);
+            assert_eq!(msf.stream_sizes(), vec![Some(0), Some(5), None, Some(1500)]);
+
+            assert!(msf.get(0, None).expect("stream 0").as_slice().is_empty());
+            assert_eq!(msf.get(1, None).expect("stream 1").as_slice(), b"hello");
+            assert!(matches!(msf.get(2, None), Err(Error::StreamNotFound(2))));
+
+            let stream = msf.get(3, None).expect("stream 3");
+            assert_eq!(&stream[..1024], &[1; 1024][..]);
+            assert_eq!(&stream[1024..], &[2; 476][..]);
+        }
+
+        #[test]
+        fn test_page_out_of_range() {
+            let mut file = small_msf();
+            // point the stream table at a page beyond the end of the file
+            file[60..62].copy_from_slice(&7u16.to_le_bytes());
+
+            assert!(matches!(
+                open_msf(Cursor::new(file.as_slice())),
+                Err(Error::PageReferenceOutOfRange(7))
+            ));
+        }
+    }
+
+    mod stream_table {
+        use std::io::Cursor;
+
+        use crate::common::*;
+        use crate::msf::{open_msf, Header, MsfWriter, StreamTable};
+
+        const HEADER: Header = Header {
+            page_size: 4096,
+            maximum_valid_page_number: 15,
+        };
+
+        fn table(values: &[u32]) -> Vec<u8> {
+            values.iter().flat_map(|v| v.to_le_bytes()).collect()
+        }
+
+        #[test]
+        fn test_parse() {
+            let data = table(&[3, 5000, u32::MAX, 0, 7, 8]);
+            let stream_table = StreamTable::parse(&HEADER, &data).expect("parse");
+
+            assert_eq!(stream_table.stream_count(), 3);
+            assert_eq!(stream_table.stream_sizes(), vec![Some(5000), None, Some(0)]);
+            assert_eq!(
+                stream_table.stream_pages(0).expect("pages"),
+                Some(&[7, 8][..])
+            );
+            assert_eq!(stream_table.stream_pages(1).expect("pages"), None);
+            assert_eq!(stream_table.stream_pages(2).expect("pages"), Some(&[][..]));
+        }
+
+        #[test]
+        fn test_invalid_counts() {
+            // more streams than sizes
+            let data = table(&[1_000_000, 0, 0]);
+            assert!(matches!(
+                StreamTable::parse(&HEADER, &data),
+                Err(Error::InvalidStreamLength(_))
+            ));
+
+            // more pages than page numbers
+            let data = table(&[1, u32::MAX - 1, 7]);
+            assert!(matches!(
+                StreamTable::parse(&HEADER, &data),
+                Err(Error::InvalidStreamLength(_))
+            ));
+        }
+
+        #[test]
+        fn test_many_streams() {
+            // 70000 streams need a stream table of 280 KB, whose page list spans several pages
+            let mut writer = MsfWriter::with_page_size(512).expect("page size");
+            for i in 0..70000u32 {
+                if i % 1000 == 0 {
+                    writer.add_stream(i.to_le_bytes().to_vec());
+                } else {
+                    writer.add_stream(Vec::new());
+                }
+            }
+
+            let mut file = Vec::new();
+            writer.write(&mut file).expect("write");
+            let msf = open_msf(Cursor::new(file.as_slice())).expect("open");
+
+            assert_eq!(msf.stream_count(), 70000);
+            assert_eq!(msf.stream_size(69999).expect("size"), Some(0));
+            assert_eq!(
+                msf.get(69000, None).expect("stream").as_slice(),
+                &69000u32.to_le_bytes()
+            );
+        }
+    }
+
+    mod streams {
+        use std::io::Cursor;
+
+        use crate::common::*;
+        use crate::msf::{open_msf, MsfWriter, StreamEntry};
+
+        #[test]
+        fn test_stream_enumeration() {
+            let mut writer = MsfWriter::new();
+            writer.add_stream(Vec::new());
+            writer.reserve_stream();
+            writer.add_stream(vec![2; 5000]);
+
+            let mut file = Vec::new();
+            writer.write(&mut file).expect("write");
+            let msf = open_msf(Cursor::new(file.as_slice())).expect("open");
+
+            assert_eq!(msf.stream_count(), 3);
+            assert_eq!(msf.stream_size(0).expect("size"), Some(0));
+            assert_eq!(msf.stream_size(1).expect("size"), None);
+            assert_eq!(msf.stream_size(2).expect("size"), Some(5000));
+            assert!(matches!(msf.stream_size(3), Err(Error::StreamNotFound(3))));
+            assert_eq!(msf.stream_sizes(), vec![Some(0), None, Some(5000)]);
+
+            let streams: Vec<_> = msf.present_streams().collect();
+            assert_eq!(
+                streams,
+                vec![
+                    StreamEntry {
+                        index: StreamIndex(0),
+                        size: 0
+                    },
+                    StreamEntry {
+                        index: StreamIndex(2),
+                        size: 5000
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn test_get_range() {
+            let data: Vec<u8> = (0..10000u32).map(|i| (i % 251) as u8).collect();
+
+            let mut writer = MsfWriter::new();
+            writer.add_stream(Vec::new());
+            writer.add_stream(data.clone());
+
+            let mut file = Vec::new();
+            writer.write(&mut file).expect("write");
+            let msf = open_msf(Cursor::new(file.as_slice())).expect("open");
+
+            let stream = msf.get_range(1, 4000, 200).expect("range");
+            assert_eq!(stream.as_slice(), &data[4000..4200]);
+
+            let stream = msf.get_range(1, 9000, 1000).expect("range");
+            assert_eq!(stream.as_slice(), &data[9000..]);
+
+            let stream = msf.get_range(1, 10000, 0).expect("range");
+            assert!(stream.as_slice().is_empty());
+
+            assert!(matches!(
+                msf.get_range(1, 9000, 1001),
+                Err(Error::UnexpectedEof)
+            ));
+            assert!(matches!(
+                msf.get_range(2, 0, 0),
+                Err(Error::StreamNotFound(2))
+            ));
+        }
+    }
+
+    mod copy {
+        use std::io::Cursor;
+
+        use crate::common::*;
+        use crate::msf::{open_msf, MsfWriter};
+
+        #[test]
+        fn test_copy_stream_to() {
+            let data: Vec<u8> = (0..70000u32).map(|i| (i % 251) as u8).collect();
+
+            let mut writer = MsfWriter::with_page_size(512).expect("page size");
+            writer.add_stream(Vec::new());
+            writer.add_stream(data.clone());
+            writer.reserve_stream();
+
+            let mut file = Vec::new();
+            writer.write(&mut file).expect("write");
+            let msf = open_msf(Cursor::new(file.as_slice())).expect("open");
+
+            let mut output = Vec::new();
+            assert_eq!(msf.copy_stream_to(1, &mut output).expect("copy"), 70000);
+            assert_eq!(output, data);
+
+            let mut output = Vec::new();
+            assert_eq!(msf.copy_stream_to(0, &mut output).expect("copy"), 0);
+            assert!(output.is_empty());
+
+            assert!(matches!(
+                msf.copy_stream_to(2, &mut output),
+                Err(Error::StreamNotFound(2))
+            ));
+        }
+    }
+
+    #[cfg(feature = "hash")]
+    mod hash {
+        use std::io::Cursor;
+
+        use crate::common::*;
+        use crate::msf::{open_msf, HashAlgorithm, MsfWriter};
+
+        #[test]
+        fn test_stream_hash() {
+            let data: Vec<u8> = (0..70000u32).map(|i| (i % 251) as u8).collect();
+            let mut changed = data.clone();
+            changed[69999] ^= 1;
+
+            let mut writer = MsfWriter::with_page_size(512).expect("page size");
+            writer.add_stream(Vec::new());
+            writer.add_stream(data.clone());
+            writer.add_stream(data);
+            writer.add_stream(changed);
+            writer.reserve_stream();
+
+            let mut file = Vec::new();
+            writer.write(&mut file).expect("write");
+            let msf = open_msf(Cursor::new(file.as_slice())).expect("open");
+
+            for algorithm in [HashAlgorithm::Sha256, HashAlgorithm::XxHash64] {
+                let hash = msf.stream_hash(1, algorithm).expect("hash");
+                assert_eq!(msf.stream_hash(2, algorithm).expect("hash"), hash);
+                assert_ne!(msf.stream_hash(3, algorithm).expect("hash"), hash);
+                assert!(matches!(
+                    msf.stream_hash(4, algorithm),
+                    Err(Error::StreamNotFound(4))
+                ));
+            }
+        }
+    }
+
+    mod progress {
+        use std::io::Cursor;
+
+        use crate::common::*;
+        use crate::msf::{open_msf, MsfWriter};
+
+        #[test]
+        fn test_get_with_progress() {
+            let data: Vec<u8> = (0..70000u32).map(|i| (i % 251) as u8).collect();
+
+            let mut writer = MsfWriter::with_page_size(512).expect("page size");
+            writer.add_stream(b"small".to_vec());
+            writer.add_stream(data.clone());
+
+            let mut file = Vec::new();
+            writer.write(&mut file).expect("write");
+            let msf = open_msf(Cursor::new(file.as_slice())).expect("open");
+
+            let mut reports = Vec::new();
+            let stream = msf
+                .get_with_progress(1, None, &mut |read, total| reports.push((read, total)))
+                .expect("get");
+            assert_eq!(stream.as_slice(), data.as_slice());
+            assert_eq!(reports.len(), 10);
+            assert_eq!(reports[0], (0, 70000));
+            assert_eq!(reports[1], (8192, 70000));
+            assert_eq!(reports[9], (70000, 70000));
+
+            let mut reports = Vec::new();
+            let stream = msf
+                .get_with_progress(1, Some(100), &mut |read, total| reports.push((read, total)))
+                .expect("get");
+            assert_eq!(stream.as_slice(), &data[..100]);
+            assert_eq!(reports, [(0, 100), (100, 100)]);
+
+            let stream = msf.get_with_progress(0, None, &mut |_, _| ()).expect("get");
+            assert_eq!(stream.as_slice(), b"small");
+
+            assert!(matches!(
+                msf.get_with_progress(2, None, &mut |_, _| ()),
+                Err(Error::StreamNotFound(2))
+            ));
+        }
+    }
+
+    mod previous {
+        use std::io::Cursor;
+
+        use crate::common::*;
+        use crate::msf::{open_msf, MsfWriter, PreviousDirectory};
+
+        #[test]
+        fn test_previous_directory() {
+            // stream 0 occupies page 3 and lists stream 1 as living on page 5, which is where the
+            // writer places the current stream 2
+            let mut previous = Vec::new();
+            for value in [3u32, 0, 8, u32::MAX, 5] {
+                previous.extend_from_slice(&value.to_le_bytes());
+            }
+
+            let mut writer = MsfWriter::with_page_size(512).expect("page size");
+            writer.add_stream(previous);
+            writer.add_stream(b"new contents".to_vec());
+            writer.add_stream(b"old contents".to_vec());
+
+            let mut file = Vec::new();
+            writer.write(&mut file).expect("write");
+            let msf = open_msf(Cursor::new(file.as_slice())).expect("open");
+
+            let (header, table) = msf
+                .previous_stream_table()
+                .expect("parse")
+                .expect("previous table");
+            let directory = PreviousDirectory::new(&*msf, header, table);
+            assert_eq!(directory.stream_count(), 3);
+            assert_eq!(
+                directory.stream_size(StreamIndex(1)).expect("size"),
+                Some(8)
+            );
+            assert_eq!(directory.stream_size(StreamIndex(2)).expect("size"), None);
+
+            let stream = directory
+                .raw_stream(StreamIndex(1))
+                .expect("stream")
+                .expect("present");
+            assert_eq!(stream.as_slice(), b"old cont");
+
+            assert!(matches!(
+                directory.raw_stream(StreamIndex(2)),
+                Err(Error::StreamNotFound(2))
+            ));
+            assert!(directory
+                .raw_stream(StreamIndex::none())
+                .expect("none")
+                .is_none());
+        }
+
+        #[test]
+        fn test_no_previous_directory() {
+            let mut writer = MsfWriter::new();
+            writer.add_stream(Vec::new());
+            writer.add_stream(b"contents".to_vec());
+
+            let mut file = Vec::new();
+            writer.write(&mut file).expect("write");
+            let msf = open_msf(Cursor::new(file.as_slice())).expect("open");
+
+            assert!(msf.previous_stream_table().expect("parse").is_none());
+        }
+    }
+
+    mod concurrency {
+        use std::io::Cursor;
+
+        use crate::msf::big::BigMSF;
+        use crate::msf::{view, Msf, MsfWriter, PageList};
+
+        #[test]
+        fn test_concurrent_get() {
+            let mut writer = MsfWriter::new();
+            for i in 0..16u8 {
+                writer.add_stream(vec![i; 5000 + usize::from(i)]);
+            }
+
+            let mut file = Vec::new();
+            writer.write(&mut file).expect("write");
+
+            let mut source = Cursor::new(file);
+            let mut header_location = PageList::new(4096);
+            header_location.push(0);
+            let header_view = view(&mut source, &header_location).expect("header");
+            let msf = BigMSF::new(source, header_view, usize::MAX).expect("open");
+
+            std::thread::scope(|scope| {
+                for i in 0..16u8 {
+                    let msf = &msf;
+                    scope.spawn(move || {
+                        let stream = msf.get(u32::from(i), None).expect("get");
+                        assert_eq!(stream.as_slice(), vec![i; 5000 + usize::from(i)]);
+                    });
+                }
+            });
+        }
+    }
+
+    mod free_page_map {
+        use std::io::Cursor;
+
+        use crate::common::Error;
+        use crate::msf::{open_msf, MsfVersion, MsfWriter};
+
+        fn file() -> Vec<u8> {
+            let mut writer = MsfWriter::new();
+            writer.add_stream(Vec::new());
+            writer.add_stream(vec![1; 10000]);
+
+            let mut file = Vec::new();
+            writer.write(&mut file).expect("write");
+            file
+        }
+
+        #[test]
+        fn test_free_page_map() {
+            let file = file();
+            let msf = open_msf(Cursor::new(file.as_slice())).expect("open");
+
+            let info = msf.info();
+            assert_eq!(info.version(), MsfVersion::V700);
+            assert_eq!(info.page_size(), 4096);
+            assert_eq!(info.pages_used() as usize, file.len() / 4096);
+            assert_eq!(info.directory_size(), 24);
+            assert_eq!(info.free_page_map(), Some(1));
+
+            let free_page_map = msf.free_page_map().expect("free page map");
+            assert_eq!(free_page_map.page_count() as usize, file.len() / 4096);
+            assert_eq!(free_page_map.free_pages().count(), 0);
+            assert!(free_page_map.is_free(free_page_map.page_count()));
+
+            msf.validate_free_page_map().expect("valid");
+        }
+
+        #[test]
+        fn test_page_marked_free() {
+            let mut file = file();
+
+            // mark page 3, the first page of stream 1, as free
+            file[4096] |= 1 << 3;
+
+            let msf = open_msf(Cursor::new(file.as_slice())).expect("open");
+            assert!(msf.free_page_map().expect("free page map").is_free(3));
+            assert!(matches!(
+                msf.validate_free_page_map(),
+                Err(Error::PageMarkedFree(3))
+            ));
+        }
+
+        #[test]
+        fn test_invalid_free_page_map() {
+            let mut file = file();
+
+            // the header refers to free page map 3
+            file[36] = 3;
+
+            let msf = open_msf(Cursor::new(file.as_slice())).expect("open");
+            assert!(matches!(
+                msf.free_page_map(),
+                Err(Error::InvalidFreePageMap(3))
+            ));
+        }
+    }
+
+    mod limit {
+        use std::io::Cursor;
+
+        use crate::common::*;
+        use crate::msf::{
+            open_msf, open_msf_with_directory_limit, LimitedMsf, MemoryLimitPolicy, Msf, MsfWriter,
+        };
+
+        fn file() -> Vec<u8> {
+            let mut writer = MsfWriter::with_page_size(512).expect("page size");
+            writer.add_stream(vec![1; 1000]);
+            writer.add_stream(vec![2; 3000]);
+
+            let mut file = Vec::new();
+            writer.write(&mut file).expect("write");
+            file
+        }
+
+        #[test]
+        fn test_limit_exceeded() {
+            let file = file();
+            let inner = open_msf(Cursor::new(file.as_slice())).expect("open");
+            let msf = LimitedMsf::new(inner, 3500, MemoryLimitPolicy::Error);
+            assert_eq!(msf.memory_in_use(), Some(0));
+
+            let first = msf.get(0, None).expect("get");
+            assert_eq!(msf.memory_in_use(), Some(1000));
+
+            assert!(matches!(
+                msf.get(1, None),
+                Err(Error::MemoryLimitExceeded(3000))
+            ));
+
+            // limited reads only account for the requested bytes
+            let partial = msf.get(1, Some(2000)).expect("get");
+            assert_eq!(partial.as_slice(), &[2; 2000][..]);
+            assert_eq!(msf.memory_in_use(), Some(3000));
+
+            drop(first);
+            drop(partial);
+            assert_eq!(msf.memory_in_use(), Some(0));
+
+            let second = msf.get(1, None).expect("get");
+            assert_eq!(second.as_slice(), &[2; 3000][..]);
+        }
+
+        #[test]
+        fn test_directory_limit() {
+            let file = file();
+            assert!(matches!(
+                open_msf_with_directory_limit(Cursor::new(file.as_slice()), 4),
+                Err(Error::MemoryLimitExceeded(_))
+            ));
+            open_msf_with_directory_limit(Cursor::new(file.as_slice()), 4096).expect("open");
+        }
     }
 }
@@ -0,0 +1,759 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Support for compressed MSFZ ("PDZ") containers.
+//!
+//! MSFZ files store the same streams as an MSF, but instead of pages they describe each stream as
+//! a list of fragments. A fragment is either stored uncompressed at an arbitrary file offset, or it
+//! is located at an offset within a compressed chunk. The file is laid out as follows, all values
+//! little endian:
+//!
+//! ```text
+//! header:
+//!   signature: [u8; 32]
+//!   version: u64                          (always 0)
+//!   stream_dir_offset: u64
+//!   chunk_table_offset: u64
+//!   num_streams: u32
+//!   stream_dir_compression: u32
+//!   stream_dir_size_compressed: u32
+//!   stream_dir_size_uncompressed: u32
+//!   num_chunks: u32
+//!   chunk_table_size: u32
+//!
+//! chunk table, num_chunks entries:
+//!   file_offset: u64
+//!   compression: u32
+//!   compressed_size: u32
+//!   uncompressed_size: u32
+//!
+//! stream directory, for each stream:
+//!   0xffffffff                            (stream is not present)
+//! or
+//!   { size: u32, location_lo: u32, location_hi: u32 }*
+//!   0                                     (end of fragments)
+//! ```
+//!
+//! If bit 31 of `location_hi` is set, the fragment is located in the chunk with the index given by
+//! the remaining bits of `location_hi`, at offset `location_lo` within the decompressed chunk.
+//! Otherwise, `location_hi:location_lo` is the file offset of the uncompressed fragment.
+
+use std::io::Read;
+
+use super::*;
+
+const SUPPORTED_VERSION: u64 = 0;
+const HEADER_SIZE: usize = 80;
+const CHUNK_ENTRY_SIZE: usize = 20;
+
+const NIL_STREAM_SIZE: u32 = u32::MAX;
+const LOCATION_CHUNK_BIT: u32 = 1 << 31;
+
+const COMPRESSION_NONE: u32 = 0;
+const COMPRESSION_ZSTD: u32 = 1;
+const COMPRESSION_DEFLATE: u32 = 2;
+
+/// MSFZ files are not paged, but the MSF layer and writers expect a page size.
+const NOMINAL_PAGE_SIZE: usize = 4096;
+
+/// Sources expect requests aligned to this boundary.
+const READ_ALIGNMENT: u64 = 4096;
+
+#[derive(Debug, Copy, Clone)]
+struct Chunk {
+    file_offset: u64,
+    compression: u32,
+    compressed_size: u32,
+    uncompressed_size: u32,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Location {
+    File(u64),
+    Chunk { index: u32, offset: u32 },
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Fragment {
+    size: u32,
+    location: Location,
+}
+
+#[derive(Debug)]
+struct StreamEntry {
+    size: u32,
+    fragments: Vec<Fragment>,
+}
+
+/// Decompresses `data`, returning `None` if it is corrupt or does not decompress to `size` bytes.
+fn decompress(compression: u32, data: &[u8], size: usize) -> Result<Option<Vec<u8>>> {
+    let decompressed = match compression {
+        COMPRESSION_NONE => Some(data.to_vec()),
+        COMPRESSION_ZSTD => {
+            // read one byte more than expected, so that oversized output is detected without
+            // decompressing all of it. The size is not trusted for the initial allocation, as a
+            // tiny chunk can claim gigabytes of output.
+            let mut output = Vec::with_capacity(size.min(data.len().saturating_mul(32)));
+            ruzstd::decoding::StreamingDecoder::new(data)
+                .ok()
+                .and_then(|decoder| decoder.take(size as u64 + 1).read_to_end(&mut output).ok())
+                .map(|_| output)
+        }
+        COMPRESSION_DEFLATE => miniz_oxide::inflate::decompress_to_vec_with_limit(data, size).ok(),
+        _ => return Err(Error::UnsupportedCompression(compression)),
+    };
+
+    Ok(decompressed.filter(|output| output.len() == size))
+}
+
+/// Reads `size` bytes at an arbitrary file offset.
+fn read<'s, S: Source<'s>>(source: &mut S, offset: u64, size: usize) -> Result<Vec<u8>> {
+    let aligned = offset - offset % READ_ALIGNMENT;
+    let skip = (offset - aligned) as usize;
+
+    let slice = SourceSlice {
+        offset: aligned,
+        size: skip + size,
+    };
+
+    let view = source.view(&[slice])?;
+    Ok(view.as_slice()[skip..].to_vec())
+}
+
+#[derive(Debug)]
+struct DecompressedView {
+    bytes: Vec<u8>,
+}
+
+impl SourceView<'_> for DecompressedView {
+    fn as_slice(&self) -> &[u8] {
+        self.bytes.as_slice()
+    }
+}
+
+#[derive(Debug)]
+pub struct MsfzFile<S> {
+    source: Mutex<S>,
+    chunks: Vec<Chunk>,
+    streams: Vec<Option<StreamEntry>>,
+    directory_size: u32,
+    /// The most recently decompressed chunk, since consecutive fragments usually share chunks.
+    last_chunk: Mutex<Option<(u32, Arc<Vec<u8>>)>>,
+    /// The budget charged for decompressing chunks, if the memory of this file is limited.
+    budget: Option<Arc<MemoryBudget>>,
+}
+
+impl<'s, S: Source<'s>> MsfzFile<S> {
+    pub fn new(
+        mut source: S,
+        header_view: Box<dyn SourceView<'s> + 's>,
+        directory_limit: usize,
+    ) -> Result<Self> {
+        let mut buf = ParseBuffer::from(header_view.as_slice());
+        if buf.take(MSFZ_MAGIC.len())? != MSFZ_MAGIC {
+            return Err(Error::UnrecognizedFileFormat);
+        }
+
+        let version = buf.parse_u64()?;
+        if version != SUPPORTED_VERSION {
+            return Err(Error::UnimplementedFeature(
+                "unknown MSFZ container version",
+            ));
+        }
+
+        let stream_dir_offset = buf.parse_u64()?;
+        let chunk_table_offset = buf.parse_u64()?;
+        let num_streams = buf.parse_u32()?;
+        let stream_dir_compression = buf.parse_u32()?;
+        let stream_dir_size_compressed = buf.parse_u32()?;
+        let stream_dir_size_uncompressed = buf.parse_u32()?;
+        let num_chunks = buf.parse_u32()?;
+        let chunk_table_size = buf.parse_u32()?;
+        debug_assert_eq!(buf.pos(), HEADER_SIZE);
+
+        if chunk_table_size as usize != num_chunks as usize * CHUNK_ENTRY_SIZE {
+            return Err(Error::InvalidStreamLength("MSFZ chunk table"));
+        }
+
+        let directory_size = [
+            chunk_table_size,
+            stream_dir_size_compressed,
+            stream_dir_size_uncompressed,
+        ]
+        .iter()
+        .fold(0usize, |acc, &size| acc.saturating_add(size as usize));
+        if directory_size > directory_limit {
+            return Err(Error::MemoryLimitExceeded(directory_size));
+        }
+
+        let chunk_table = read(&mut source, chunk_table_offset, chunk_table_size as usize)?;
+        let mut buf = ParseBuffer::from(chunk_table.as_slice());
+        let mut chunks = Vec::with_capacity(num_chunks as usize);
+        while !buf.is_empty() {
+            chunks.push(Chunk {
+                file_offset: buf.parse_u64()?,
+                compression: buf.parse_u32()?,
+                compressed_size: buf.parse_u32()?,
+                uncompressed_size: buf.parse_u32()?,
+            });
+        }
+
+        let compressed = read(
+            &mut source,
+            stream_dir_offset,
+            stream_dir_size_compressed as usize,
+        )?;
+        let directory = decompress(
+            stream_dir_compression,
+            &compressed,
+            stream_dir_size_uncompressed as usize,
+        )?
+        .ok_or(Error::InvalidStreamLength("MSFZ stream directory"))?;
+
+        let streams = Self::parse_directory(&directory, num_streams, &chunks)?;
+
+        Ok(MsfzFile {
+            source: Mutex::new(source),
+            chunks,
+            streams,
+            directory_size: stream_dir_size_uncompressed,
+            last_chunk: Mutex::new(None),
+            budget: None,
+        })
+    }
+
+    fn parse_directory(
+        directory: &[u8],
+        num_streams: u32,
+        chunks: &[Chunk],
+    ) -> Result<Vec<Option<StreamEntry>>> {
+        let mut buf = ParseBuffer::from(directory);
+
+        // don't trust the stream count when reserving memory
+        let mut streams = Vec::with_capacity((num_streams as usize).min(directory.len() / 4));
+        for _ in 0..num_streams {
+            let mut size = buf.parse_u32()?;
+            if size == NIL_STREAM_SIZE {
+                streams.push(None);
+                continue;
+            }
+
+            let mut entry = StreamEntry {
+                size: 0,
+                fragments: Vec::new(),
+            };
+
+            while size != 0 {
+                let lo = buf.parse_u32()?;
+                let hi = buf.parse_u32()?;
+
+                let location = if hi & LOCATION_CHUNK_BIT != 0 {
+                    let index = hi & !LOCATION_CHUNK_BIT;
+                    if index as usize >= chunks.len() {
+                        return Err(Error::InvalidCompressedChunk(index));
+                    }
+                    Location::Chunk { index, offset: lo }
+                } else {
+                    Location::File((u64::from(hi) << 32) | u64::from(lo))
+                };
+
+                entry.size = entry
+                    .size
+                    .checked_add(size)
+                    .filter(|&total| total != NIL_STREAM_SIZE)
+                    .ok_or(Error::InvalidStreamLength("MSFZ stream"))?;
+                entry.fragments.push(Fragment { size, location });
+
+                size = buf.parse_u32()?;
+            }
+
+            streams.push(Some(entry));
+        }
+
+        Ok(streams)
+    }
+
+    fn stream_entry(&self, stream_number: u32) -> Result<&StreamEntry> {
+        match self.streams.get(stream_number as usize) {
+            Some(Some(entry)) => Ok(entry),
+            _ => Err(Error::StreamNotFound(stream_number)),
+        }
+    }
+
+    fn chunk(&self, index: u32) -> Result<Arc<Vec<u8>>> {
+        let mut last_chunk = lock(&self.last_chunk);
+        if let Some((cached, ref data)) = *last_chunk {
+            if cached == index {
+                return Ok(Arc::clone(data));
+            }
+        }
+
+        // the cached chunk is replaced, so only the new chunk counts against the budget
+        *last_chunk = None;
+
+        let chunk = self.chunks[index as usize];
+        let size = chunk.uncompressed_size as usize;
+        let _reservation = match self.budget {
+            Some(ref budget) => Some(
+                budget
+                    .try_reserve(size, 0)
+                    .ok_or(Error::MemoryLimitExceeded(size))?,
+            ),
+            None => None,
+        };
+
+        let compressed = read(
+            &mut *lock(&self.source),
+            chunk.file_offset,
+            chunk.compressed_size as usize,
+        )?;
+        let data = decompress(chunk.compression, &compressed, size)?
+            .ok_or(Error::InvalidCompressedChunk(index))?;
+
+        let data = Arc::new(data);
+        *last_chunk = Some((index, Arc::clone(&data)));
+        Ok(data)
+    }
+
+    /// Appends `size` bytes starting at `offset` within the decompressed chunk `index` to
+    /// `output`. The data may continue into the following chunks.
+    fn read_chunks(
+        &self,
+        mut index: u32,
+        mut offset: usize,
+        mut size: usize,
+        output: &mut Vec<u8>,
+    ) -> Result<()> {
+        while size > 0 {
+            if index as usize >= self.chunks.len() {
+                return Err(Error::InvalidCompressedChunk(index));
+            }
+
+            let data = self.chunk(index)?;
+            let available = match data.len().checked_sub(offset) {
+                Some(available) => available,
+                None => return Err(Error::InvalidCompressedChunk(index)),
+            };
+
+            let take = available.min(size);
+            output.extend_from_slice(&data[offset..offset + take]);
+
+            size -= take;
+            offset = 0;
+            index += 1;
+        }
+
+        Ok(())
+    }
+
+    fn read_range(&self, stream_number: u32, offset: usize, len: usize) -> Result<Vec<u8>> {
+        let entry = self.stream_entry(stream_number)?;
+        match offset.checked_add(len) {
+            Some(end) if end <= entry.size as usize => (),
+            _ => return Err(Error::UnexpectedEof),
+        }
+
+        let mut output = Vec::with_capacity(len);
+        let mut fragment_start = 0;
+
+        for fragment in &entry.fragments {
+            let fragment_end = fragment_start + fragment.size as usize;
+            if output.len() == len {
+                break;
+            }
+
+            if fragment_end > offset {
+                let skip = offset.saturating_sub(fragment_start);
+                let take = (fragment.size as usize - skip).min(len - output.len());
+
+                match fragment.location {
+                    Location::File(file_offset) => {
+                        let data = read(&mut *lock(&self.source), file_offset + skip as u64, take)?;
+                        output.extend_from_slice(&data);
+                    }
+                    Location::Chunk { index, offset } => {
+                        self.read_chunks(index, offset as usize + skip, take, &mut output)?;
+                    }
+                }
+            }
+
+            fragment_start = fragment_end;
+        }
+
+        Ok(output)
+    }
+}
+
+impl<'s, S: Source<'s>> Msf<'s, S> for MsfzFile<S> {
+    fn page_size(&self) -> usize {
+        NOMINAL_PAGE_SIZE
+    }
+
+    fn info(&self) -> MsfInfo {
+        MsfInfo::new(
+            MsfVersion::Msfz,
+            NOMINAL_PAGE_SIZE as u32,
+            0,
+            self.directory_size,
+            None,
+        )
+    }
+
+    fn stream_count(&self) -> u32 {
+        self.streams.len() as u32
+    }
+
+    fn stream_size(&self, stream_number: u32) -> Result<Option<u32>> {
+        match self.streams.get(stream_number as usize) {
+            Some(entry) => Ok(entry.as_ref().map(|entry| entry.size)),
+            None => Err(Error::StreamNotFound(stream_number)),
+        }
+    }
+
+    fn stream_sizes(&self) -> Vec<Option<u32>> {
+        self.streams
+            .iter()
+            .map(|entry| entry.as_ref().map(|entry| entry.size))
+            .collect()
+    }
+
+    fn free_page_map(&self) -> Result<FreePageMap> {
+        Err(Error::UnimplementedFeature(
+            "MSFZ containers have no free page map",
+        ))
+    }
+
+    fn validate_free_page_map(&self) -> Result<()> {
+        // there are no pages which could be inconsistent
+        Ok(())
+    }
+
+    fn previous_stream_table(&self) -> Result<Option<(Header, StreamTable)>> {
+        // streams are not stored in pages, so no previous versions of them are retained
+        Ok(None)
+    }
+
+    fn prefetch(&self, _stream_numbers: &[u32]) -> Result<()> {
+        // decompressed chunks are not retained beyond the most recent one
+        Ok(())
+    }
+
+    fn stream_layout(&self, _stream_number: u32) -> Result<StreamLayout> {
+        Err(Error::UnimplementedFeature("MSFZ containers are not paged"))
+    }
+
+    fn cached_bytes(&self) -> usize {
+        lock(&self.last_chunk)
+            .as_ref()
+            .map_or(0, |(_, data)| data.len())
+    }
+
+    fn evict_caches(&self) {
+        *lock(&self.last_chunk) = None;
+    }
+
+    fn set_memory_budget(&mut self, budget: Arc<MemoryBudget>) {
+        self.budget = Some(budget);
+    }
+
+    fn look_up_stream(&self, _stream_number: u32) -> Result<PageList> {
+        Err(Error::UnimplementedFeature("MSFZ containers are not paged"))
+    }
+
+    fn view_pages(&self, _page_list: &PageList) -> Result<Box<dyn SourceView<'s> + 's>> {
+        Err(Error::UnimplementedFeature("MSFZ containers are not paged"))
+    }
+
+    fn get(&self, stream_number: u32, limit: Option<usize>) -> Result<Stream<'s>> {
+        let size = self.stream_entry(stream_number)?.size as usize;
+        let len = limit.map_or(size, |limit| limit.min(size));
+        self.get_range(stream_number, 0, len)
+    }
+
+    fn get_range(&self, stream_number: u32, offset: usize, len: usize) -> Result<Stream<'s>> {
+        let bytes = self.read_range(stream_number, offset, len)?;
+        Ok(Stream::new(Box::new(DecompressedView { bytes })))
+    }
+
+    fn read_pieces(
+        &self,
+        stream_number: u32,
+        size: usize,
+        f: &mut dyn FnMut(&[u8]) -> Result<()>,
+    ) -> Result<()> {
+        let piece = self.page_size() * COPY_PAGES;
+        let mut offset = 0;
+        while offset < size {
+            let len = piece.min(size - offset);
+            f(&self.read_range(stream_number, offset, len)?)?;
+            offset += len;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    enum TestFragment {
+        File(Vec<u8>),
+        Chunk(u32, u32, u32),
+    }
+
+    /// Assembles an MSFZ file from compressed chunks and stream fragments.
+    fn build(
+        chunks: &[(u32, Vec<u8>)],
+        streams: &[Option<Vec<TestFragment>>],
+        dir_compression: u32,
+    ) -> Vec<u8> {
+        let mut file = vec![0; HEADER_SIZE];
+
+        let mut chunk_table = Vec::new();
+        for (compression, data) in chunks {
+            let compressed = match *compression {
+                COMPRESSION_NONE => data.clone(),
+                COMPRESSION_ZSTD => ruzstd::encoding::compress_to_vec(
+                    data.as_slice(),
+                    ruzstd::encoding::CompressionLevel::Fastest,
+                ),
+                COMPRESSION_DEFLATE => miniz_oxide::deflate::compress_to_vec(data, 6),
+                _ => unreachable!(),
+            };
+
+            chunk_table.extend_from_slice(&(file.len() as u64).to_le_bytes());
+            chunk_table.extend_from_slice(&compression.to_le_bytes());
+            chunk_table.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+            chunk_table.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            file.extend_from_slice(&compressed);
+        }
+
+        let mut directory = Vec::new();
+        for stream in streams {
+            match stream {
+                Some(fragments) => {
+                    for fragment in fragments {
+                        let (size, lo, hi) = match fragment {
+                            TestFragment::File(data) => {
+                                let offset = file.len() as u64;
+                                file.extend_from_slice(data);
+                                (data.len() as u32, offset as u32, (offset >> 32) as u32)
+                            }
+                            TestFragment::Chunk(size, index, offset) => {
+                                (*size, *offset, index | LOCATION_CHUNK_BIT)
+                            }
+                        };
+                        directory.extend_from_slice(&size.to_le_bytes());
+                        directory.extend_from_slice(&lo.to_le_bytes());
+                        directory.extend_from_slice(&hi.to_le_bytes());
+                    }
+                    directory.extend_from_slice(&0u32.to_le_bytes());
+                }
+                None => directory.extend_from_slice(&NIL_STREAM_SIZE.to_le_bytes()),
+            }
+        }
+
+        let compressed_directory = match dir_compression {
+            COMPRESSION_DEFLATE => miniz_oxide::deflate::compress_to_vec(&directory, 6),
+            _ => directory.clone(),
+        };
+
+        let chunk_table_offset = file.len() as u64;
+        file.extend_from_slice(&chunk_table);
+        let stream_dir_offset = file.len() as u64;
+        file.extend_from_slice(&compressed_directory);
+
+        // the MSF layer views the first 4096 bytes to detect the format
+        file.resize(file.len().max(4096), 0);
+
+        let mut header = Vec::new();
+        header.extend_from_slice(MSFZ_MAGIC);
+        header.extend_from_slice(&SUPPORTED_VERSION.to_le_bytes());
+        header.extend_from_slice(&stream_dir_offset.to_le_bytes());
+        header.extend_from_slice(&chunk_table_offset.to_le_bytes());
+        header.extend_from_slice(&(streams.len() as u32).to_le_bytes());
+        header.extend_from_slice(&dir_compression.to_le_bytes());
+        header.extend_from_slice(&(compressed_directory.len() as u32).to_le_bytes());
+        header.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        header.extend_from_slice(&(chunks.len() as u32).to_le_bytes());
+        header.extend_from_slice(&(chunk_table.len() as u32).to_le_bytes());
+        file[..HEADER_SIZE].copy_from_slice(&header);
+
+        file
+    }
+
+    fn pattern(len: usize, seed: u8) -> Vec<u8> {
+        (0..len).map(|i| (i as u8).wrapping_mul(seed)).collect()
+    }
+
+    #[test]
+    fn test_streams() {
+        let zstd_chunk = pattern(10000, 3);
+        let deflate_chunk = pattern(5000, 7);
+
+        let file = build(
+            &[
+                (COMPRESSION_ZSTD, zstd_chunk.clone()),
+                (COMPRESSION_DEFLATE, deflate_chunk.clone()),
+            ],
+            &[
+                Some(vec![]),
+                None,
+                Some(vec![TestFragment::File(b"uncompressed".to_vec())]),
+                Some(vec![
+                    TestFragment::Chunk(100, 0, 50),
+                    TestFragment::File(b"middle".to_vec()),
+                    TestFragment::Chunk(100, 1, 4900),
+                ]),
+            ],
+            COMPRESSION_DEFLATE,
+        );
+
+        let msf = open_msf(Cursor::new(file)).expect("open");
+        assert_eq!(
+            msf.info(),
+            MsfInfo::new(MsfVersion::Msfz, 4096, 0, 64, None)
+        );
+        assert_eq!(msf.stream_count(), 4);
+        assert_eq!(msf.stream_sizes(), vec![Some(0), None, Some(12), Some(206)]);
+        assert!(matches!(msf.get(1, None), Err(Error::StreamNotFound(1))));
+        assert!(matches!(msf.get(4, None), Err(Error::StreamNotFound(4))));
+
+        assert!(msf.get(0, None).expect("stream 0").as_slice().is_empty());
+        assert_eq!(
+            msf.get(2, None).expect("stream 2").as_slice(),
+            b"uncompressed"
+        );
+
+        let mut expected = zstd_chunk[50..150].to_vec();
+        expected.extend_from_slice(b"middle");
+        expected.extend_from_slice(&deflate_chunk[4900..]);
+        assert_eq!(
+            msf.get(3, None).expect("stream 3").as_slice(),
+            expected.as_slice()
+        );
+
+        let range = msf.get_range(3, 98, 10).expect("range");
+        assert_eq!(range.as_slice(), &expected[98..108]);
+    }
+
+    #[test]
+    fn test_fragment_spanning_chunks() {
+        let first = pattern(3000, 5);
+        let second = pattern(3000, 11);
+
+        let file = build(
+            &[
+                (COMPRESSION_NONE, first.clone()),
+                (COMPRESSION_ZSTD, second.clone()),
+            ],
+            &[
+                Some(vec![TestFragment::Chunk(2000, 0, 2000)]),
+                Some(vec![TestFragment::Chunk(2000, 1, 2000)]),
+            ],
+            COMPRESSION_NONE,
+        );
+
+        let msf = open_msf(Cursor::new(file)).expect("open");
+        let mut expected = first[2000..].to_vec();
+        expected.extend_from_slice(&second[..1000]);
+        assert_eq!(
+            msf.get(0, None).expect("stream").as_slice(),
+            expected.as_slice()
+        );
+        assert_eq!(
+            msf.get(0, Some(10)).expect("stream").as_slice(),
+            &expected[..10]
+        );
+
+        // the second stream extends past the last chunk
+        assert!(matches!(
+            msf.get(1, None),
+            Err(Error::InvalidCompressedChunk(2))
+        ));
+    }
+
+    #[test]
+    fn test_decompressed_size() {
+        let data = pattern(10000, 3);
+        let compressed = ruzstd::encoding::compress_to_vec(
+            data.as_slice(),
+            ruzstd::encoding::CompressionLevel::Fastest,
+        );
+
+        let decompressed = decompress(COMPRESSION_ZSTD, &compressed, 10000).expect("decompress");
+        assert_eq!(decompressed.as_deref(), Some(data.as_slice()));
+
+        // output longer or shorter than the recorded size is rejected
+        assert_eq!(
+            decompress(COMPRESSION_ZSTD, &compressed, 9999).expect("longer"),
+            None
+        );
+        assert_eq!(
+            decompress(COMPRESSION_ZSTD, &compressed, 10001).expect("shorter"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_oversized_chunk() {
+        let mut file = build(
+            &[(COMPRESSION_ZSTD, pattern(100, 3))],
+            &[Some(vec![TestFragment::Chunk(100, 0, 0)])],
+            COMPRESSION_NONE,
+        );
+
+        // patch the uncompressed size of the chunk
+        let mut chunk_table_offset = [0; 8];
+        chunk_table_offset.copy_from_slice(&file[48..56]);
+        let size_offset = u64::from_le_bytes(chunk_table_offset) as usize + 16;
+        file[size_offset..size_offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        let msf = open_msf(Cursor::new(file)).expect("open");
+        assert!(matches!(
+            msf.get(0, None),
+            Err(Error::InvalidCompressedChunk(0))
+        ));
+    }
+
+    #[test]
+    fn test_memory_budget() {
+        let file = build(
+            &[(COMPRESSION_ZSTD, pattern(10000, 3))],
+            &[Some(vec![TestFragment::Chunk(100, 0, 0)])],
+            COMPRESSION_NONE,
+        );
+
+        // decompressing the chunk is charged, even though only a small range is requested
+        let inner = open_msf(Cursor::new(file.as_slice())).expect("open");
+        let msf = LimitedMsf::new(inner, 5000, MemoryLimitPolicy::Error);
+        assert!(matches!(
+            msf.get_range(0, 0, 10),
+            Err(Error::MemoryLimitExceeded(10000))
+        ));
+
+        let inner = open_msf(Cursor::new(file.as_slice())).expect("open");
+        let msf = LimitedMsf::new(inner, 20000, MemoryLimitPolicy::Error);
+        let range = msf.get_range(0, 0, 10).expect("range");
+        assert_eq!(range.as_slice(), &pattern(10, 3)[..]);
+    }
+
+    #[test]
+    fn test_unsupported_compression() {
+        let mut file = build(&[], &[Some(vec![])], COMPRESSION_NONE);
+        // patch the stream directory compression
+        file[60..64].copy_from_slice(&7u32.to_le_bytes());
+
+        assert!(matches!(
+            open_msf(Cursor::new(file)),
+            Err(Error::UnsupportedCompression(7))
+        ));
+    }
+}
@@ -10,7 +10,7 @@ use crate::source::SourceSlice;
 
 /// Represents a list of `PageNumbers`, which are likely (but not certainly) sequential, and which
 /// will be presented as a slice of `SourceSlice`s.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct PageList {
     page_size: usize,
     source_slices: Vec<SourceSlice>,
@@ -94,6 +94,116 @@ impl PageList {
     pub fn source_slices(&self) -> &[SourceSlice] {
         self.source_slices.as_slice()
     }
+
+    /// Return a new `PageList` covering the pages that hold `len` bytes starting at `offset`,
+    /// along with the position of `offset` within the new list.
+    ///
+    /// Sources expect requests to be aligned to pages, so the new list starts at the beginning of
+    /// the page containing `offset` and extends to the end of the page containing the last
+    /// requested byte. The requested range is clamped to the length of this `PageList`.
+    pub fn range(&self, offset: usize, len: usize) -> (PageList, usize) {
+        let mut skip = offset;
+        let mut remaining = len;
+        let mut start = 0;
+        let mut source_slices = Vec::new();
+
+        for slice in &self.source_slices {
+            if remaining == 0 {
+                break;
+            }
+
+            if skip >= slice.size {
+                // the range starts after this slice
+                skip -= slice.size;
+                continue;
+            }
+
+            // align the start down and the end up to page boundaries within this slice
+            let aligned_skip = skip - skip % self.page_size;
+            let take = (slice.size - skip).min(remaining);
+            let aligned_end = (skip + take)
+                .div_ceil(self.page_size)
+                .saturating_mul(self.page_size)
+                .min(slice.size);
+
+            if source_slices.is_empty() {
+                start = skip - aligned_skip;
+            }
+
+            source_slices.push(SourceSlice {
+                offset: slice.offset + aligned_skip as u64,
+                size: aligned_end - aligned_skip,
+            });
+
+            skip = 0;
+            remaining -= take;
+        }
+
+        (self.with_slices(source_slices), start)
+    }
+
+    /// Return consecutive `PageList`s of `bytes` bytes each, covering this list in order.
+    ///
+    /// The last list holds the remaining bytes. `bytes` should be a multiple of the page size, so
+    /// that every list starts at the beginning of a page.
+    pub fn chunks(&self, bytes: usize) -> Vec<PageList> {
+        assert!(bytes > 0);
+
+        let mut lists = Vec::new();
+        let mut source_slices = Vec::new();
+        let mut len = 0;
+
+        for slice in &self.source_slices {
+            let mut slice = *slice;
+            while slice.size > 0 {
+                let take = slice.size.min(bytes - len);
+                source_slices.push(SourceSlice {
+                    offset: slice.offset,
+                    size: take,
+                });
+
+                slice.offset += take as u64;
+                slice.size -= take;
+                len += take;
+
+                if len == bytes {
+                    lists.push(self.with_slices(std::mem::take(&mut source_slices)));
+                    len = 0;
+                }
+            }
+        }
+
+        if !source_slices.is_empty() {
+            lists.push(self.with_slices(source_slices));
+        }
+
+        lists
+    }
+
+    fn with_slices(&self, source_slices: Vec<SourceSlice>) -> PageList {
+        PageList {
+            page_size: self.page_size,
+            source_slices,
+            last_page: None,
+            truncated: true,
+        }
+    }
+
+    /// Return the page numbers covered by this PageList, in order.
+    ///
+    /// Pages that are only partially covered after truncation are included.
+    pub fn pages(&self) -> Vec<PageNumber> {
+        let page_size = self.page_size as u64;
+        let mut pages = Vec::new();
+
+        for slice in &self.source_slices {
+            let first = slice.offset / page_size;
+            let count = (slice.size as u64).div_ceil(page_size);
+            pages.extend((first..first + count).map(|page| page as PageNumber));
+        }
+
+        pages
+    }
 }
 
 #[cfg(test)]
@@ -267,6 +377,124 @@ mod tests {
         assert_eq!(list.len(), 0);
     }
 
+    #[test]
+    fn test_pages() {
+        let mut list = PageList::new(4096);
+        list.push(0);
+        list.push(1);
+        list.push(4);
+        list.push(5);
+        list.push(2);
+        assert_eq!(list.pages(), vec![0, 1, 4, 5, 2]);
+
+        // partially covered pages are still reported
+        list.truncate(10000);
+        assert_eq!(list.pages(), vec![0, 1, 4]);
+    }
+
+    #[test]
+    fn test_range() {
+        let mut list = PageList::new(4096);
+        list.push(0);
+        list.push(1);
+        list.push(4);
+        list.push(5);
+        list.push(2);
+        list.truncate(18000);
+
+        // a range within a single page
+        let (range, start) = list.range(100, 50);
+        let expected = vec![SourceSlice {
+            offset: 0,
+            size: 4096,
+        }];
+        assert_eq!(range.source_slices(), expected.as_slice());
+        assert_eq!(start, 100);
+
+        // a range spanning discontinuous pages
+        let (range, start) = list.range(8000, 5000);
+        let expected = vec![
+            SourceSlice {
+                offset: 4096,
+                size: 4096,
+            },
+            SourceSlice {
+                offset: 16384,
+                size: 8192,
+            },
+        ];
+        assert_eq!(range.source_slices(), expected.as_slice());
+        assert_eq!(start, 3904);
+
+        // a range reaching into the truncated last page
+        let (range, start) = list.range(16384, 10000);
+        let expected = vec![SourceSlice {
+            offset: 8192,
+            size: 1616,
+        }];
+        assert_eq!(range.source_slices(), expected.as_slice());
+        assert_eq!(start, 0);
+
+        // a range beyond the end
+        let (range, _) = list.range(20000, 100);
+        assert_eq!(range.len(), 0);
+    }
+
+    #[test]
+    fn test_chunks() {
+        let mut list = PageList::new(4096);
+        list.push(0);
+        list.push(1);
+        list.push(4);
+        list.push(5);
+        list.push(2);
+        list.truncate(18000);
+
+        let chunks = list.chunks(8192);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(
+            chunks[0].source_slices(),
+            &[SourceSlice {
+                offset: 0,
+                size: 8192,
+            }]
+        );
+        assert_eq!(
+            chunks[1].source_slices(),
+            &[SourceSlice {
+                offset: 16384,
+                size: 8192,
+            }]
+        );
+        assert_eq!(
+            chunks[2].source_slices(),
+            &[SourceSlice {
+                offset: 8192,
+                size: 1616,
+            }]
+        );
+
+        // a chunk spanning discontinuous pages
+        let chunks = list.chunks(12288);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(
+            chunks[0].source_slices(),
+            &[
+                SourceSlice {
+                    offset: 0,
+                    size: 8192,
+                },
+                SourceSlice {
+                    offset: 16384,
+                    size: 4096,
+                },
+            ]
+        );
+        assert_eq!(chunks[1].len(), 18000 - 12288);
+
+        assert!(PageList::new(4096).chunks(4096).is_empty());
+    }
+
     #[test]
     #[should_panic]
     fn test_push_after_truncate() {
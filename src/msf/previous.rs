@@ -0,0 +1,111 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::fmt;
+
+use crate::common::*;
+use crate::msf::{Header, Msf, Stream, StreamIter, StreamLayout, StreamTable};
+
+/// The stream directory of a multi-stream file as of its previous write.
+///
+/// When a PDB is updated incrementally, the stream table that was current before the update is
+/// preserved in stream 0. Pages that were superseded by the update are not reused until the next
+/// write, so this directory can be used to read streams as they were before the update. This is
+/// useful when examining partially updated or damaged PDBs.
+///
+/// Note that pages of the previous write may have been released and overwritten since, in which
+/// case the streams returned by this directory contain unrelated data.
+///
+/// Returned by [`PDB::previous_directory`](crate::PDB::previous_directory).
+pub struct PreviousDirectory<'a, 's, S> {
+    msf: &'a (dyn Msf<'s, S> + 's),
+    header: Header,
+    stream_table: StreamTable,
+}
+
+impl<'a, 's, S> PreviousDirectory<'a, 's, S> {
+    pub(crate) fn new(
+        msf: &'a (dyn Msf<'s, S> + 's),
+        header: Header,
+        stream_table: StreamTable,
+    ) -> Self {
+        Self {
+            msf,
+            header,
+            stream_table,
+        }
+    }
+
+    /// Returns the number of streams in the previous directory, including streams that are not
+    /// present.
+    pub fn stream_count(&self) -> u32 {
+        self.stream_table.stream_count()
+    }
+
+    /// Returns the size of a stream in bytes as of the previous write.
+    ///
+    /// Returns `None` if this index is none or the stream was not present.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the index exceeds the number of streams
+    pub fn stream_size(&self, index: StreamIndex) -> Result<Option<u32>> {
+        match index.msf_number() {
+            Some(number) => self.stream_table.stream_size(number),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns an iterator over all streams that were present as of the previous write.
+    pub fn present_streams(&self) -> StreamIter {
+        StreamIter {
+            sizes: self.stream_table.stream_sizes().into_iter().enumerate(),
+        }
+    }
+
+    /// Describes which pages stored a stream as of the previous write.
+    ///
+    /// Returns `None` if this index is none.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the stream was not present
+    pub fn stream_layout(&self, index: StreamIndex) -> Result<Option<StreamLayout>> {
+        match index.msf_number() {
+            Some(number) => self.stream_table.layout(&self.header, number).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Retrieve a stream as of the previous write.
+    ///
+    /// Returns `None` if this index is none.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the stream was not present
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the previous directory seems corrupt
+    pub fn raw_stream(&self, index: StreamIndex) -> Result<Option<Stream<'s>>> {
+        let number = match index.msf_number() {
+            Some(number) => number,
+            None => return Ok(None),
+        };
+
+        let page_list = self.stream_table.look_up_stream(&self.header, number)?;
+        let source_view = self.msf.view_pages(&page_list)?;
+        Ok(Some(Stream::new(source_view)))
+    }
+}
+
+impl<S> fmt::Debug for PreviousDirectory<'_, '_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreviousDirectory")
+            .field("stream_count", &self.stream_count())
+            .finish()
+    }
+}
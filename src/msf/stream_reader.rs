@@ -0,0 +1,174 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::fmt;
+use std::io;
+
+use crate::common::*;
+use crate::msf::{Msf, PageList};
+
+/// Reads a stream incrementally through `std::io::Read` and `std::io::Seek`.
+///
+/// Unlike [`PDB::raw_stream`](crate::PDB::raw_stream), which views the entire stream at once, a
+/// `StreamReader` only requests the pages needed to satisfy each individual read from the `Source`.
+/// This allows large streams to be consumed by code written against the standard I/O traits without
+/// holding the whole stream in memory.
+///
+/// # Example
+///
+/// ```
+/// # use std::io::{Read, Seek, SeekFrom};
+/// #
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(file)?;
+///
+/// let mut reader = pdb.stream_reader(pdb::StreamIndex(208))?.expect("stream exists");
+/// reader.seek(SeekFrom::Start(6))?;
+///
+/// let mut buf = String::new();
+/// reader.read_to_string(&mut buf)?;
+/// assert_eq!(buf, "world\n");
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+pub struct StreamReader<'a, 's, S> {
+    msf: &'a (dyn Msf<'s, S> + 's),
+    stream_number: u32,
+    page_list: Option<PageList>,
+    len: u32,
+    position: u64,
+}
+
+impl<'a, 's, S> StreamReader<'a, 's, S> {
+    pub(crate) fn new(msf: &'a (dyn Msf<'s, S> + 's), stream_number: u32, len: u32) -> Self {
+        // the pages of paged containers are looked up once instead of for every read
+        let page_list = msf.look_up_stream(stream_number).ok();
+
+        Self {
+            msf,
+            stream_number,
+            page_list,
+            len,
+            position: 0,
+        }
+    }
+
+    /// Returns the length of the stream in bytes.
+    pub fn len(&self) -> u64 {
+        u64::from(self.len)
+    }
+
+    /// Returns `true` if the stream is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<S> io::Read for StreamReader<'_, '_, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.len().saturating_sub(self.position);
+        let count = (buf.len() as u64).min(remaining) as usize;
+        if count == 0 {
+            return Ok(0);
+        }
+
+        let offset = self.position as usize;
+        let stream = match self.page_list {
+            Some(ref page_list) => self.msf.view_range(page_list, offset, count),
+            None => self.msf.get_range(self.stream_number, offset, count),
+        };
+
+        let stream = stream.map_err(|e| match e {
+            Error::IoError(e) => e,
+            e => io::Error::new(io::ErrorKind::InvalidData, e),
+        })?;
+
+        buf[..count].copy_from_slice(stream.as_slice());
+        self.position += count as u64;
+        Ok(count)
+    }
+}
+
+impl<S> io::Seek for StreamReader<'_, '_, S> {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            io::SeekFrom::Start(offset) => Some(offset),
+            io::SeekFrom::End(offset) => self.len().checked_add_signed(offset),
+            io::SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+impl<S> fmt::Debug for StreamReader<'_, '_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamReader")
+            .field("len", &self.len())
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+
+    use crate::msf::stream_reader::*;
+    use crate::msf::{open_msf, MsfWriter};
+
+    #[test]
+    fn test_read_and_seek() {
+        let data: Vec<u8> = (0..20000u32).map(|i| (i % 251) as u8).collect();
+
+        let mut writer = MsfWriter::with_page_size(512).expect("page size");
+        writer.add_stream(Vec::new());
+        writer.add_stream(b"interleaved".to_vec());
+        writer.add_stream(data.clone());
+
+        let mut file = Vec::new();
+        writer.write(&mut file).expect("write");
+
+        let msf = open_msf(Cursor::new(file.as_slice())).expect("open");
+        let mut reader = StreamReader::new(&*msf, 2, 20000);
+        assert_eq!(reader.len(), 20000);
+
+        // read across page boundaries
+        reader.seek(SeekFrom::Start(500)).expect("seek");
+        let mut buf = [0; 1000];
+        reader.read_exact(&mut buf).expect("read");
+        assert_eq!(&buf[..], &data[500..1500]);
+
+        // read to the end
+        reader.seek(SeekFrom::End(-100)).expect("seek");
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).expect("read");
+        assert_eq!(rest, &data[19900..]);
+
+        // reading past the end yields nothing
+        assert_eq!(reader.read(&mut buf).expect("read"), 0);
+
+        // seeking before the start fails
+        assert!(reader.seek(SeekFrom::Current(-30000)).is_err());
+
+        reader.rewind().expect("rewind");
+        let mut all = Vec::new();
+        reader.read_to_end(&mut all).expect("read");
+        assert_eq!(all, data);
+    }
+}
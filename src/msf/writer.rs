@@ -0,0 +1,537 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::convert::TryFrom;
+use std::io::{self, Write};
+
+use crate::common::*;
+use crate::msf::{big, is_free_page_map, PageNumber};
+
+/// The default page size used by the Microsoft toolchain.
+const DEFAULT_PAGE_SIZE: usize = 4096;
+
+/// Builds a new multi-stream file ("Big MSF", version 7.00) from scratch.
+///
+/// Streams are added in memory and laid out contiguously when the file is written. Writing
+/// allocates pages for every stream, generates the stream table and both free page maps, and emits
+/// the complete file to an `std::io::Write`.
+///
+/// The writer operates on the container level only. To produce a valid PDB, the caller is
+/// responsible for placing the PDB information, TPI, DBI and IPI streams at their well-known stream
+/// numbers. By convention, stream 0 holds the previous stream table and may be left empty.
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// let mut writer = pdb::MsfWriter::new();
+/// writer.add_stream(Vec::new());
+/// let stream_number = writer.add_stream(b"hello world".to_vec());
+///
+/// let mut file = Vec::new();
+/// writer.write(&mut file)?;
+/// # assert_eq!(stream_number, 1);
+/// # assert_eq!(file.len() % 4096, 0);
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Debug)]
+pub struct MsfWriter {
+    page_size: usize,
+    streams: Vec<Option<Vec<u8>>>,
+}
+
+impl MsfWriter {
+    /// Creates a new, empty `MsfWriter` using the default page size of 4096 bytes.
+    pub fn new() -> Self {
+        Self {
+            page_size: DEFAULT_PAGE_SIZE,
+            streams: Vec::new(),
+        }
+    }
+
+    /// Creates a new, empty `MsfWriter` using the given page size.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidPageSize` if the page size is not a power of two between 256 bytes and 8 MiB
+    pub fn with_page_size(page_size: u32) -> Result<Self> {
+        if page_size.count_ones() != 1 || !(0x100..=128 * 0x10000).contains(&page_size) {
+            return Err(Error::InvalidPageSize(page_size));
+        }
+
+        Ok(Self {
+            page_size: page_size as usize,
+            streams: Vec::new(),
+        })
+    }
+
+    /// Returns the page size of the file to be written.
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    /// Returns the number of streams, including streams that are not present.
+    pub fn stream_count(&self) -> u32 {
+        self.streams.len() as u32
+    }
+
+    /// Appends a new stream and returns its stream number.
+    pub fn add_stream(&mut self, data: Vec<u8>) -> u32 {
+        self.streams.push(Some(data));
+        self.stream_count() - 1
+    }
+
+    /// Appends a new stream that is marked as not present and returns its stream number.
+    pub fn reserve_stream(&mut self) -> u32 {
+        self.streams.push(None);
+        self.stream_count() - 1
+    }
+
+    /// Sets the contents of the stream with the given number.
+    ///
+    /// If `stream_number` is beyond the current number of streams, the gap is filled with streams
+    /// that are marked as not present.
+    pub fn set_stream(&mut self, stream_number: u32, data: Vec<u8>) {
+        let index = stream_number as usize;
+        if index >= self.streams.len() {
+            self.streams.resize(index + 1, None);
+        }
+        self.streams[index] = Some(data);
+    }
+
+    /// Marks the stream with the given number as not present.
+    ///
+    /// Stream numbers of subsequent streams are not affected.
+    pub fn remove_stream(&mut self, stream_number: u32) {
+        if let Some(stream) = self.streams.get_mut(stream_number as usize) {
+            *stream = None;
+        }
+    }
+
+    /// Returns the contents of the stream with the given number, if it is present.
+    pub fn stream(&self, stream_number: u32) -> Option<&[u8]> {
+        self.streams
+            .get(stream_number as usize)
+            .and_then(|stream| stream.as_deref())
+    }
+
+    /// Writes the multi-stream file.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::MsfLimitExceeded` if a stream or the stream table is too large to be represented
+    /// * `Error::IoError` if returned by the `Write`
+    pub fn write<W: io::Write>(&self, writer: W) -> Result<()> {
+        let sizes: Vec<_> = self
+            .streams
+            .iter()
+            .map(|stream| stream.as_ref().map(Vec::len))
+            .collect();
+
+        write_msf(
+            self.page_size,
+            &sizes,
+            writer,
+            &mut |stream_number, output| {
+                if let Some(data) = self.stream(stream_number) {
+                    output.write_all(data)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Writes a multi-stream file with streams of the given sizes.
+///
+/// Pages are emitted to `writer` in order, so the file is never held in memory. `write_stream` is
+/// called once for every present stream, in ascending order, and must write exactly the number of
+/// bytes given in `sizes`.
+pub(crate) fn write_msf<W: io::Write>(
+    page_size: usize,
+    sizes: &[Option<usize>],
+    writer: W,
+    write_stream: &mut dyn FnMut(u32, &mut dyn io::Write) -> Result<()>,
+) -> Result<()> {
+    let stream_count =
+        u32::try_from(sizes.len()).map_err(|_| Error::MsfLimitExceeded("too many streams"))?;
+    let mut allocator = PageAllocator::new(page_size);
+
+    // lay out the stream contents first
+    let mut stream_pages = Vec::with_capacity(sizes.len());
+    for size in sizes {
+        let pages = match *size {
+            Some(size) => {
+                if size >= u32::MAX as usize {
+                    return Err(Error::MsfLimitExceeded("stream too large"));
+                }
+                allocator.allocate(size)?
+            }
+            None => Vec::new(),
+        };
+        stream_pages.push(pages);
+    }
+
+    // the stream table is structured as:
+    //   stream_count
+    //   0..stream_count: size of stream in bytes (0xffffffff indicating "stream does not exist")
+    //   0..stream_count: page numbers of each stream
+    let mut stream_table = Vec::new();
+    stream_table.extend_from_slice(&stream_count.to_le_bytes());
+    for size in sizes {
+        let size = size.map_or(u32::MAX, |size| size as u32);
+        stream_table.extend_from_slice(&size.to_le_bytes());
+    }
+    for page in stream_pages.iter().flatten() {
+        stream_table.extend_from_slice(&page.to_le_bytes());
+    }
+
+    if stream_table.len() >= u32::MAX as usize {
+        return Err(Error::MsfLimitExceeded("stream table too large"));
+    }
+
+    // the stream table is stored in pages, which are listed in pages, which are in turn listed
+    // in the header
+    let stream_table_pages = allocator.allocate(stream_table.len())?;
+    let mut stream_table_page_list = Vec::with_capacity(stream_table_pages.len() * 4);
+    for page in &stream_table_pages {
+        stream_table_page_list.extend_from_slice(&page.to_le_bytes());
+    }
+
+    let page_list_pages = allocator.allocate(stream_table_page_list.len())?;
+    if big::HEADER_SIZE + page_list_pages.len() * 4 > page_size {
+        return Err(Error::MsfLimitExceeded("stream table page list too large"));
+    }
+
+    let page_count = allocator.finish()?;
+    let mut output = PageWriter::new(writer, page_size, page_count);
+
+    // header
+    let mut header = Vec::with_capacity(big::HEADER_SIZE + page_list_pages.len() * 4);
+    header.extend_from_slice(big::MAGIC);
+    header.extend_from_slice(&(page_size as u32).to_le_bytes());
+    header.extend_from_slice(&1u32.to_le_bytes()); // free page map
+    header.extend_from_slice(&page_count.to_le_bytes());
+    header.extend_from_slice(&(stream_table.len() as u32).to_le_bytes());
+    header.extend_from_slice(&0u32.to_le_bytes()); // reserved
+    for page in &page_list_pages {
+        header.extend_from_slice(&page.to_le_bytes());
+    }
+    output.write_all(&header)?;
+    output.end_page(header.len())?;
+
+    // stream contents and stream table, in the order their pages were allocated
+    for (stream_number, size) in sizes.iter().enumerate() {
+        if let Some(size) = *size {
+            write_stream(stream_number as u32, &mut output)?;
+            output.end_page(size)?;
+        }
+    }
+    output.write_all(&stream_table)?;
+    output.end_page(stream_table.len())?;
+    output.write_all(&stream_table_page_list)?;
+    output.end_page(stream_table_page_list.len())?;
+
+    output.finish()
+}
+
+impl Default for MsfWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Hands out page numbers sequentially, skipping the header and free page map pages.
+#[derive(Debug)]
+struct PageAllocator {
+    page_size: usize,
+    next_page: u64,
+}
+
+impl PageAllocator {
+    fn new(page_size: usize) -> Self {
+        // page 0 holds the header, pages 1 and 2 hold the free page maps
+        Self {
+            page_size,
+            next_page: 3,
+        }
+    }
+
+    /// Allocates enough pages to store the given number of bytes.
+    fn allocate(&mut self, bytes: usize) -> Result<Vec<PageNumber>> {
+        let count = bytes.div_ceil(self.page_size);
+        let mut pages = Vec::with_capacity(count);
+
+        while pages.len() < count {
+            let page = self.next_page;
+            self.next_page += 1;
+
+            if !is_free_page_map(self.page_size as u64, page) {
+                let page = PageNumber::try_from(page)
+                    .map_err(|_| Error::MsfLimitExceeded("too many pages"))?;
+                pages.push(page);
+            }
+        }
+
+        Ok(pages)
+    }
+
+    /// Returns the total number of pages in the file.
+    ///
+    /// If the last allocated page starts a new interval, the free page map pages of that interval
+    /// are included.
+    fn finish(mut self) -> Result<u32> {
+        while is_free_page_map(self.page_size as u64, self.next_page) {
+            self.next_page += 1;
+        }
+
+        u32::try_from(self.next_page).map_err(|_| Error::MsfLimitExceeded("too many pages"))
+    }
+}
+
+/// Writes the pages of a file in order, filling in the free page maps as they are reached.
+///
+/// Each interval of `page_size` pages contains two free page map pages at offsets 1 and 2. Taken
+/// together, the pages at the same offset form a bitmap with one bit per page, where a set bit
+/// indicates a free page. Every page inside the file is in use.
+struct PageWriter<W> {
+    writer: W,
+    page_size: usize,
+    page_count: u32,
+    free_page_map: Vec<u8>,
+    /// The number of the next page to be written.
+    next_page: u64,
+    /// The contents of the current page, which has not been written yet.
+    page: Vec<u8>,
+    /// The number of bytes written since the last call to `end_page`.
+    written: usize,
+}
+
+impl<W: io::Write> PageWriter<W> {
+    fn new(writer: W, page_size: usize, page_count: u32) -> Self {
+        let intervals = (page_count as usize).div_ceil(page_size);
+        let mut free_page_map = vec![0xff; intervals * page_size];
+        for page in 0..page_count as usize {
+            free_page_map[page / 8] &= !(1 << (page % 8));
+        }
+
+        Self {
+            writer,
+            page_size,
+            page_count,
+            free_page_map,
+            next_page: 0,
+            page: Vec::with_capacity(page_size),
+            written: 0,
+        }
+    }
+
+    /// Writes the free page maps preceding the next page.
+    fn write_free_page_maps(&mut self) -> io::Result<()> {
+        while is_free_page_map(self.page_size as u64, self.next_page) {
+            let start = (self.next_page as usize / self.page_size) * self.page_size;
+            self.writer
+                .write_all(&self.free_page_map[start..start + self.page_size])?;
+            self.next_page += 1;
+        }
+        Ok(())
+    }
+
+    /// Writes a complete page.
+    fn write_page(&mut self, page: &[u8]) -> io::Result<()> {
+        self.write_free_page_maps()?;
+        self.writer.write_all(page)?;
+        self.next_page += 1;
+        Ok(())
+    }
+
+    /// Pads the current page with zeros and checks that `size` bytes have been written since the
+    /// previous call.
+    fn end_page(&mut self, size: usize) -> Result<()> {
+        if self.written != size {
+            return Err(Error::UnexpectedEof);
+        }
+        self.written = 0;
+
+        if !self.page.is_empty() {
+            let mut page = std::mem::take(&mut self.page);
+            page.resize(self.page_size, 0);
+            self.write_page(&page)?;
+            page.clear();
+            self.page = page;
+        }
+        Ok(())
+    }
+
+    /// Writes the free page maps of the last interval and flushes the writer.
+    fn finish(mut self) -> Result<()> {
+        self.write_free_page_maps()?;
+        debug_assert_eq!(self.next_page, u64::from(self.page_count));
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+impl<W: io::Write> io::Write for PageWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len();
+        self.written += len;
+
+        let mut buf = buf;
+        while !buf.is_empty() {
+            if self.page.is_empty() && buf.len() >= self.page_size {
+                let (page, rest) = buf.split_at(self.page_size);
+                self.write_page(page)?;
+                buf = rest;
+                continue;
+            }
+
+            let take = buf.len().min(self.page_size - self.page.len());
+            self.page.extend_from_slice(&buf[..take]);
+            buf = &buf[take..];
+
+            if self.page.len() == self.page_size {
+                let page = std::mem::take(&mut self.page);
+                self.write_page(&page)?;
+                self.page = page;
+                self.page.clear();
+            }
+        }
+
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::msf::open_msf;
+    use crate::msf::writer::*;
+
+    fn write(writer: &MsfWriter) -> Vec<u8> {
+        let mut file = Vec::new();
+        writer.write(&mut file).expect("write");
+        file
+    }
+
+    #[test]
+    fn test_invalid_page_size() {
+        assert!(matches!(
+            MsfWriter::with_page_size(1000),
+            Err(Error::InvalidPageSize(1000))
+        ));
+        assert!(matches!(
+            MsfWriter::with_page_size(128),
+            Err(Error::InvalidPageSize(128))
+        ));
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut writer = MsfWriter::new();
+        writer.add_stream(Vec::new());
+        writer.add_stream(b"hello world".to_vec());
+        writer.set_stream(3, (0..10000u32).map(|i| i as u8).collect());
+
+        let file = write(&writer);
+        assert_eq!(file.len() % 4096, 0);
+
+        let msf = open_msf(Cursor::new(file.as_slice())).expect("open");
+        assert_eq!(msf.get(0, None).expect("stream 0").as_slice(), b"");
+        assert_eq!(
+            msf.get(1, None).expect("stream 1").as_slice(),
+            b"hello world"
+        );
+        assert!(matches!(msf.get(2, None), Err(Error::StreamNotFound(2))));
+        assert_eq!(
+            msf.get(3, None).expect("stream 3").as_slice(),
+            writer.stream(3).unwrap()
+        );
+        assert!(matches!(msf.get(4, None), Err(Error::StreamNotFound(4))));
+    }
+
+    #[test]
+    fn test_write_pages_in_order() {
+        /// Records the largest single write.
+        struct Recorder {
+            file: Vec<u8>,
+            largest_write: usize,
+        }
+
+        impl io::Write for Recorder {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.largest_write = self.largest_write.max(buf.len());
+                self.file.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut writer = MsfWriter::with_page_size(512).expect("page size");
+        writer.add_stream(vec![1; 300_000]);
+        writer.add_stream(b"tail".to_vec());
+
+        let mut recorder = Recorder {
+            file: Vec::new(),
+            largest_write: 0,
+        };
+        writer.write(&mut recorder).expect("write");
+        assert_eq!(recorder.largest_write, 512);
+        assert_eq!(recorder.file, write(&writer));
+
+        let msf = open_msf(Cursor::new(recorder.file.as_slice())).expect("open");
+        assert_eq!(msf.get(1, None).expect("stream 1").as_slice(), b"tail");
+    }
+
+    #[test]
+    fn test_write_wrong_stream_size() {
+        let result = write_msf(4096, &[Some(10)], Vec::new(), &mut |_, output| {
+            Ok(output.write_all(b"short")?)
+        });
+        assert!(matches!(result, Err(Error::UnexpectedEof)));
+    }
+
+    #[test]
+    fn test_free_page_map_intervals() {
+        // with 512 byte pages, each interval covers 512 pages and this stream spans several
+        let mut writer = MsfWriter::with_page_size(512).expect("page size");
+        let data: Vec<u8> = (0..600_000u32).map(|i| (i % 251) as u8).collect();
+        writer.add_stream(data.clone());
+
+        let file = write(&writer);
+        let page_count = file.len() / 512;
+        assert!(page_count > 1024);
+
+        // pages 1 and 2 of every interval are reserved for the free page maps
+        for interval in 0..page_count.div_ceil(512) {
+            for map in 1..=2 {
+                let offset = (interval * 512 + map) * 512;
+                let bitmap = &file[offset..offset + 512];
+                for (index, byte) in bitmap.iter().enumerate() {
+                    for bit in 0..8 {
+                        let page = (interval * 512 + index) * 8 + bit;
+                        let free = byte & (1 << bit) != 0;
+                        assert_eq!(free, page >= page_count, "page {}", page);
+                    }
+                }
+            }
+        }
+
+        let msf = open_msf(Cursor::new(file.as_slice())).expect("open");
+        assert_eq!(msf.get(0, None).expect("stream 0").as_slice(), &data[..]);
+    }
+}
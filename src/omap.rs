@@ -23,7 +23,7 @@ use crate::pe::ImageSectionHeader;
 /// `next_record.source_address` ).
 #[repr(C)]
 #[derive(Clone, Copy, Eq, PartialEq)]
-pub(crate) struct OMAPRecord {
+pub struct OMAPRecord {
     source_address: u32,
     target_address: u32,
 }
@@ -77,7 +77,7 @@ impl fmt::Debug for OMAPRecord {
 impl PartialOrd for OMAPRecord {
     #[inline]
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.source_address().partial_cmp(&other.source_address())
+        Some(self.cmp(other))
     }
 }
 
@@ -91,7 +91,7 @@ impl Ord for OMAPRecord {
 /// PDBs can contain OMAP tables, which translate relative virtual addresses (RVAs) from one address
 /// space into another.
 ///
-/// For more information on the pratical use of OMAPs, see the [module level documentation] and
+/// For more information on the pratical use of OMAPs, see the [address map documentation] and
 /// [`AddressMap`]. A PDB can contain two OMAPs:
 ///
 ///  - `omap_from_src`: A mapping from the original address space to the transformed address space
@@ -117,8 +117,8 @@ impl Ord for OMAPRecord {
 /// storing target addresses), but given that OMAP tables are an uncommon PDBs feature, the obvious
 /// binary search implementation seems appropriate.
 ///
-/// [module level documentation]: self
-pub(crate) struct OMAPTable<'s> {
+/// [address map documentation]: crate::PDB::address_map
+pub struct OMAPTable<'s> {
     stream: Stream<'s>,
 }
 
@@ -191,7 +191,10 @@ impl fmt::Debug for OMAPTable<'_> {
 }
 
 /// An iterator over mapped target ranges in an OMAP.
-pub(crate) struct RangeIter<'t> {
+///
+/// Returned by [`OMAPTable::lookup_range`].
+#[derive(Clone, Debug)]
+pub struct RangeIter<'t> {
     /// Iterator over subsequent OMAP records.
     records: std::slice::Iter<'t, OMAPRecord>,
     /// The record that spans the current start address.
@@ -359,7 +362,7 @@ impl FusedIterator for PdbInternalRvaRangeIter<'_> {}
 /// #
 /// # fn test() -> pdb::Result<()> {
 /// # let source = std::fs::File::open("fixtures/self/foo.pdb")?;
-/// let mut pdb = pdb::PDB::open(source)?;
+/// let pdb = pdb::PDB::open(source)?;
 ///
 /// // Compute the address map once and reuse it
 /// let address_map = pdb.address_map()?;
@@ -5,18 +5,34 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
+use std::fmt;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use fallible_iterator::FallibleIterator;
+use uuid::Uuid;
+
 use crate::common::*;
-use crate::dbi::{DBIExtraStreams, DBIHeader, DebugInformation, Module};
+use crate::dbi::{DBIExtraStreams, DBIHeader, DebugInformation, MachineType, Module};
 use crate::framedata::FrameTable;
+use crate::link_info::LinkInfo;
 use crate::modi::ModuleInfo;
-use crate::msf::{self, Msf, Stream};
+#[cfg(feature = "hash")]
+use crate::msf::HashAlgorithm;
+use crate::msf::{
+    self, FreePageMap, LimitedMsf, MemoryLimitPolicy, Msf, MsfInfo, PreviousDirectory, Stream,
+    StreamIter, StreamLayout, StreamReader,
+};
 use crate::omap::{AddressMap, OMAPTable};
-use crate::pdbi::PDBInformation;
-use crate::pe::ImageSectionHeader;
+use crate::pdbi::{PDBInformation, PdbIdentifier};
+use crate::pe::{CodeViewRecord, ImageFixup, ImageSectionHeader};
 use crate::source::Source;
 use crate::strings::StringTable;
 use crate::symbol::SymbolTable;
-use crate::tpi::{IdInformation, TypeInformation};
+use crate::tm_cache::TypeMergeCache;
+use crate::tpi::{
+    find_definition, Header as TypeHeader, IdData, IdHashes, IdInformation, ItemHashes, TypeHashes,
+    TypeInformation, UdtQuery, UdtSource, UserDefinedTypeSourceFileRef, UserDefinedTypeSourceId,
+};
 
 // Some streams have a fixed stream index.
 // http://llvm.org/docs/PDB/index.html
@@ -37,10 +53,87 @@ pub struct PDB<'s, S> {
     msf: Box<dyn Msf<'s, S> + 's>,
 
     /// Memoize the `dbi::Header`, since it contains stream numbers we sometimes need
-    dbi_header: Option<DBIHeader>,
+    dbi_header: OnceLock<DBIHeader>,
 
     /// Memoize the `dbi::DBIExtraStreams`, since it too contains stream numbers we sometimes need
-    dbi_extra_streams: Option<DBIExtraStreams>,
+    dbi_extra_streams: OnceLock<DBIExtraStreams>,
+
+    /// Memoize whether this is a `/DEBUG:FASTLINK` PDB
+    mini_pdb: OnceLock<bool>,
+
+    /// Receives progress updates while streams are read, if installed
+    progress_handler: Option<ProgressHandler<'s>>,
+}
+
+/// Progress of a stream being read from a PDB file.
+///
+/// Passed to the handler installed using [`PDB::set_progress_handler`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Progress {
+    /// The stream being read.
+    pub stream: StreamIndex,
+    /// The number of bytes of the stream that have been read so far.
+    pub bytes_read: usize,
+    /// The total number of bytes that will be read from the stream.
+    pub bytes_total: usize,
+}
+
+struct ProgressHandler<'s>(Mutex<Box<dyn FnMut(Progress) + Send + 's>>);
+
+impl fmt::Debug for ProgressHandler<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressHandler")
+    }
+}
+
+/// Describes what a stream of a PDB file contains.
+///
+/// Returned as part of a [`StreamSummary`] by [`PDB::streams`].
+#[non_exhaustive]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StreamKind {
+    /// The stream table of the previous write of this PDB (stream 0).
+    PreviousDirectory,
+    /// The PDB information stream (stream 1).
+    PdbInformation,
+    /// The type information stream (TPI, stream 2).
+    TypeInformation,
+    /// The debug information stream (DBI, stream 3).
+    DebugInformation,
+    /// The id information stream (IPI, stream 4).
+    IdInformation,
+    /// A hash stream of the type information stream.
+    TypeHash,
+    /// A hash stream of the id information stream.
+    IdHash,
+    /// The debug information of the module with the given index.
+    Module(usize),
+    /// The global symbol index (GSI).
+    GlobalSymbols,
+    /// The public symbol index (PSI).
+    PublicSymbols,
+    /// The symbol records referenced by the global and public symbol indices.
+    SymbolRecords,
+    /// An optional debug stream referenced by the debug information stream, such as the section
+    /// headers or FPO data. The name describes the stream, for example `"section_headers"`.
+    DebugStream(&'static str),
+    /// A stream listed in the named stream map, with its name converted lossily to UTF-8.
+    Named(String),
+    /// A stream that is not referenced by any known structure.
+    Unknown,
+}
+
+/// A stream of a PDB file along with its classification and size.
+///
+/// Returned by [`PDB::streams`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamSummary {
+    /// The index of the stream.
+    pub index: StreamIndex,
+    /// What the stream contains.
+    pub kind: StreamKind,
+    /// The size of the stream in bytes, or `None` if the stream is not present.
+    pub size: Option<u32>,
 }
 
 impl<'s, S: Source<'s> + 's> PDB<'s, S> {
@@ -50,20 +143,125 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// involves reading the header, a block near the end of the file, and finally the stream table
     /// itself. It does not access or validate any of the contents of the rest of the PDB.
     ///
+    /// With the `msfz` feature enabled, compressed MSFZ containers are opened transparently.
+    ///
     /// # Errors
     ///
-    /// * `Error::UnimplementedFeature` if the PDB file predates ~2002
     /// * `Error::UnrecognizedFileFormat` if the `Source` does not appear to be a PDB file
     /// * `Error::IoError` if returned by the `Source`
     /// * `Error::PageReferenceOutOfRange`, `Error::InvalidPageSize` if the PDB file seems corrupt
     pub fn open(source: S) -> Result<PDB<'s, S>> {
         Ok(PDB {
             msf: msf::open_msf(source)?,
-            dbi_header: None,
-            dbi_extra_streams: None,
+            dbi_header: OnceLock::new(),
+            dbi_extra_streams: OnceLock::new(),
+            mini_pdb: OnceLock::new(),
+            progress_handler: None,
+        })
+    }
+
+    /// Create a new `PDB` for a `Source`, limiting the amount of stream data held in memory.
+    ///
+    /// Every view of stream data returned or used by this `PDB` is accounted for until it is
+    /// dropped, including the `Stream`s returned by [`raw_stream`](Self::raw_stream) and the
+    /// parsed structures borrowing them, such as [`TypeInformation`]. Reading data that would
+    /// bring the total above `limit` bytes is handled according to `policy`. The stream directory
+    /// is read before the limit is in effect, but files whose directory exceeds `limit` are
+    /// rejected.
+    ///
+    /// This allows services processing untrusted PDBs to enforce a hard ceiling on memory usage,
+    /// regardless of the stream sizes claimed by the file.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::MemoryLimitExceeded` if the stream directory exceeds the limit
+    /// * `Error::UnrecognizedFileFormat` if the `Source` does not appear to be a PDB file
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange`, `Error::InvalidPageSize` if the PDB file seems corrupt
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open_with_memory_limit(file, 16 * 1024, pdb::MemoryLimitPolicy::Error)?;
+    ///
+    /// let s = pdb.raw_stream(pdb::StreamIndex(208))?.expect("stream exists");
+    /// assert_eq!(pdb.memory_in_use(), Some(12));
+    ///
+    /// // the type information does not fit
+    /// assert!(matches!(
+    ///     pdb.type_information(),
+    ///     Err(pdb::Error::MemoryLimitExceeded(_))
+    /// ));
+    ///
+    /// drop(s);
+    /// assert_eq!(pdb.memory_in_use(), Some(0));
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn open_with_memory_limit(
+        source: S,
+        limit: usize,
+        policy: MemoryLimitPolicy,
+    ) -> Result<PDB<'s, S>> {
+        let msf = msf::open_msf_with_directory_limit(source, limit)?;
+        Ok(PDB {
+            msf: Box::new(LimitedMsf::new(msf, limit, policy)),
+            dbi_header: OnceLock::new(),
+            dbi_extra_streams: OnceLock::new(),
+            mini_pdb: OnceLock::new(),
+            progress_handler: None,
         })
     }
 
+    /// Returns the number of bytes of stream data currently held in memory, if this `PDB` was
+    /// opened using [`open_with_memory_limit`](Self::open_with_memory_limit).
+    pub fn memory_in_use(&self) -> Option<usize> {
+        self.msf.memory_in_use()
+    }
+
+    /// Install a handler that is notified about the progress of reading streams.
+    ///
+    /// The handler is called whenever this `PDB` reads an entire stream, such as when loading the
+    /// type information or symbol tables, or when calling [`raw_stream`](Self::raw_stream). It
+    /// receives an update before the first byte is read and after every piece of a few pages, so
+    /// that tools can display progress while huge streams are loaded. Streams spanning more than a
+    /// few pages are assembled in memory rather than viewed directly while a handler is installed.
+    ///
+    /// Replaces any previously installed handler.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let mut pdb = pdb::PDB::open(file)?;
+    ///
+    /// pdb.set_progress_handler(|progress| {
+    ///     eprintln!(
+    ///         "stream {}: {}/{} bytes",
+    ///         progress.stream, progress.bytes_read, progress.bytes_total
+    ///     );
+    /// });
+    /// let type_information = pdb.type_information()?;
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn set_progress_handler<F>(&mut self, handler: F)
+    where
+        F: FnMut(Progress) + Send + 's,
+    {
+        self.progress_handler = Some(ProgressHandler(Mutex::new(Box::new(handler))));
+    }
+
+    /// Remove the handler installed using [`set_progress_handler`](Self::set_progress_handler).
+    pub fn clear_progress_handler(&mut self) {
+        self.progress_handler = None;
+    }
+
     /// Retrieve the `PDBInformation` for this PDB.
     ///
     /// The `PDBInformation` object contains the GUID and age fields that can be used to verify
@@ -74,11 +272,143 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// * `Error::StreamNotFound` if the PDB somehow does not contain the PDB information stream
     /// * `Error::IoError` if returned by the `Source`
     /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
-    pub fn pdb_information(&mut self) -> Result<PDBInformation<'s>> {
-        let stream = self.msf.get(PDB_STREAM, None)?;
+    pub fn pdb_information(&self) -> Result<PDBInformation<'s>> {
+        let stream = self.get_stream(PDB_STREAM, None)?;
         PDBInformation::parse(stream)
     }
 
+    /// Checks whether this PDB matches an image referring to it by GUID and age.
+    ///
+    /// The GUID is compared against the PDB information stream. The age is compared against the
+    /// age recorded by the linker in the debug information stream, which equals the age of the
+    /// image. If that is not available, the PDB matches if its own age is equal or higher, since
+    /// tools other than the linker may bump it.
+    ///
+    /// PDBs written before Visual C++ 7.0 do not have a GUID and never match.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB somehow does not contain the PDB information stream
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// let guid = "2B3C3FA5-5A2E-44B8-8BBA-C3300FF69F62".parse().unwrap();
+    /// assert!(pdb.matches(&guid, 1)?);
+    /// assert!(!pdb.matches(&guid, 2)?);
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn matches(&self, guid: &Uuid, age: u32) -> Result<bool> {
+        let info = self.pdb_information()?;
+        if !info.has_guid() || info.guid != *guid {
+            return Ok(false);
+        }
+
+        self.matches_age(&info, age)
+    }
+
+    /// Checks whether this PDB matches an image referring to it by the signature and age of a
+    /// PDB 2.0 file.
+    ///
+    /// The age is compared the same way as in [`matches`](Self::matches).
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB somehow does not contain the PDB information stream
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    pub fn matches_signature(&self, signature: u32, age: u32) -> Result<bool> {
+        let info = self.pdb_information()?;
+        if info.signature != signature {
+            return Ok(false);
+        }
+
+        self.matches_age(&info, age)
+    }
+
+    /// Checks whether this PDB matches the CodeView record from the debug directory of an image.
+    ///
+    /// `RSDS` records are checked using [`matches`](Self::matches), and `NB10` records using
+    /// [`matches_signature`](Self::matches_signature). The path in the record is not compared.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB somehow does not contain the PDB information stream
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    pub fn matches_codeview(&self, record: &CodeViewRecord<'_>) -> Result<bool> {
+        self.matches_identifier(&PdbIdentifier::from(*record))
+    }
+
+    /// Checks whether this PDB matches a [`PdbIdentifier`], such as one obtained from an image.
+    ///
+    /// GUIDs are checked using [`matches`](Self::matches), and signatures using
+    /// [`matches_signature`](Self::matches_signature).
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB somehow does not contain the PDB information stream
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    pub fn matches_identifier(&self, identifier: &PdbIdentifier) -> Result<bool> {
+        match *identifier {
+            PdbIdentifier::Guid { ref guid, age } => self.matches(guid, age),
+            PdbIdentifier::Signature { signature, age } => self.matches_signature(signature, age),
+        }
+    }
+
+    /// Returns the identifier of this PDB, as referenced by matching images.
+    ///
+    /// PDBs with a GUID are identified by the GUID, and older PDBs by their signature. The age is
+    /// taken from the debug information stream if available, and from the PDB information stream
+    /// otherwise.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB somehow does not contain the PDB information stream
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    pub fn identifier(&self) -> Result<PdbIdentifier> {
+        let info = self.pdb_information()?;
+        let age = self.linker_age()?.unwrap_or(info.age);
+
+        Ok(if info.has_guid() {
+            PdbIdentifier::Guid {
+                guid: info.guid,
+                age,
+            }
+        } else {
+            PdbIdentifier::Signature {
+                signature: info.signature,
+                age,
+            }
+        })
+    }
+
+    /// Returns the age recorded by the linker in the debug information stream, if available.
+    fn linker_age(&self) -> Result<Option<u32>> {
+        match self.debug_information() {
+            Ok(debug_information) => Ok(debug_information.age()),
+            Err(Error::StreamNotFound(_)) | Err(Error::UnimplementedFeature(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn matches_age(&self, info: &PDBInformation<'_>, age: u32) -> Result<bool> {
+        Ok(match self.linker_age()? {
+            Some(linker_age) => linker_age == age,
+            None => info.age >= age,
+        })
+    }
+
     /// Retrieve the `TypeInformation` for this PDB.
     ///
     /// The `TypeInformation` object owns a `SourceView` for the type information ("TPI") stream.
@@ -91,12 +421,75 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
     /// * `Error::InvalidTypeInformationHeader` if the type information stream header was not
     ///   understood
-    pub fn type_information(&mut self) -> Result<TypeInformation<'s>> {
-        let stream = self.msf.get(TPI_STREAM, None)?;
-        TypeInformation::parse(stream)
+    /// * `Error::MiniPdb` if the PDB was linked with `/DEBUG:FASTLINK` and contains no types
+    pub fn type_information(&self) -> Result<TypeInformation<'s>> {
+        let stream = self
+            .get_stream(TPI_STREAM, None)
+            .or_else(|e| self.mini_pdb_error(e))?;
+        let mut type_info = TypeInformation::parse(stream)?;
+        if type_info.is_empty() && self.is_mini_pdb().unwrap_or(false) {
+            return Err(Error::MiniPdb);
+        }
+
+        // PDBs without debug information leave the pointer size unknown
+        let machine_type = self
+            .dbi_header()
+            .ok()
+            .map(|h| MachineType::from(h.machine_type));
+        type_info.set_pointer_size(machine_type.and_then(MachineType::pointer_size));
+        Ok(type_info)
+    }
+
+    /// Retrieve the hash stream of the type information stream.
+    ///
+    /// The hash stream allows looking up types by name without iterating over all types. See
+    /// [`TypeHashes`] for more information. Returns `None` if the PDB does not contain type
+    /// information or its type information has no hash stream.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    /// * `Error::InvalidTypeInformationHeader` if the type information stream header was not
+    ///   understood
+    /// * `Error::UnexpectedEof` if the hash stream is truncated
+    pub fn type_hashes(&self) -> Result<Option<TypeHashes>> {
+        self.item_hashes(TPI_STREAM)
+    }
+
+    /// Retrieve the hash stream of the id information stream.
+    ///
+    /// The hash stream allows looking up ids by their contents without iterating over all ids. See
+    /// [`IdHashes`] for more information. Returns `None` if the PDB does not contain id
+    /// information or its id information has no hash stream.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    /// * `Error::InvalidTypeInformationHeader` if the id information stream header was not
+    ///   understood
+    /// * `Error::UnexpectedEof` if the hash stream is truncated
+    pub fn id_hashes(&self) -> Result<Option<IdHashes>> {
+        self.item_hashes(IPI_STREAM)
+    }
+
+    fn item_hashes<I: ItemIndex>(&self, stream: u32) -> Result<Option<ItemHashes<I>>> {
+        let header_size = match self.stream_size(StreamIndex(stream as u16))? {
+            Some(0) | None => return Ok(None),
+            // the header is at most 1 KiB
+            Some(size) => size.min(1024) as usize,
+        };
+
+        let header_data = self.msf.get_range(stream, 0, header_size)?;
+        let header = TypeHeader::parse(&mut header_data.parse_buffer())?;
+
+        match self.raw_stream(StreamIndex(header.tpi_hash_stream))? {
+            Some(stream) => ItemHashes::parse(stream, &header).map(Some),
+            None => Ok(None),
+        }
     }
 
-    /// Retrieve the `IdInformation` for this PDB.
     ///
     /// The `IdInformation` object owns a `SourceView` for the type information ("IPI") stream.
     ///
@@ -107,9 +500,16 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
     /// * `Error::InvalidTypeInformationHeader` if the id information stream header was not
     ///   understood
-    pub fn id_information(&mut self) -> Result<IdInformation<'s>> {
-        let stream = self.msf.get(IPI_STREAM, None)?;
-        IdInformation::parse(stream)
+    /// * `Error::MiniPdb` if the PDB was linked with `/DEBUG:FASTLINK` and contains no ids
+    pub fn id_information(&self) -> Result<IdInformation<'s>> {
+        let stream = self
+            .get_stream(IPI_STREAM, None)
+            .or_else(|e| self.mini_pdb_error(e))?;
+        let id_info = IdInformation::parse(stream)?;
+        if id_info.is_empty() && self.is_mini_pdb().unwrap_or(false) {
+            return Err(Error::MiniPdb);
+        }
+        Ok(id_info)
     }
 
     /// Retrieve the `DebugInformation` for this PDB.
@@ -122,26 +522,26 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// * `Error::IoError` if returned by the `Source`
     /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
     /// * `Error::UnimplementedFeature` if the debug information header predates ~1995
-    pub fn debug_information(&mut self) -> Result<DebugInformation<'s>> {
-        let stream = self.msf.get(DBI_STREAM, None)?;
+    pub fn debug_information(&self) -> Result<DebugInformation<'s>> {
+        let stream = self.get_stream(DBI_STREAM, None)?;
         let debug_info = DebugInformation::parse(stream)?;
 
         // Grab its header, since we need that for unrelated operations
-        self.dbi_header = Some(debug_info.header());
+        let _ = self.dbi_header.set(debug_info.header());
         Ok(debug_info)
     }
 
-    fn dbi_header(&mut self) -> Result<DBIHeader> {
+    fn dbi_header(&self) -> Result<DBIHeader> {
         // see if we've already got a header
-        if let Some(ref h) = self.dbi_header {
+        if let Some(h) = self.dbi_header.get() {
             return Ok(*h);
         }
 
         // get just the first little bit of the DBI stream
-        let stream = self.msf.get(DBI_STREAM, Some(1024))?;
+        let stream = self.get_stream(DBI_STREAM, Some(1024))?;
         let header = DBIHeader::parse(stream)?;
 
-        self.dbi_header = Some(header);
+        let _ = self.dbi_header.set(header);
         Ok(header)
     }
 
@@ -165,7 +565,7 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     ///
     /// * `Error::StreamNotFound` if the PDB somehow does not contain a debug information stream
     /// * `Error::UnimplementedFeature` if the debug information header predates ~1995
-    pub fn global_symbols(&mut self) -> Result<SymbolTable<'s>> {
+    pub fn global_symbols(&self) -> Result<SymbolTable<'s>> {
         // the global symbol table is stored in a stream number described by the DBI header
         // so, start by getting the DBI header
         let dbi_header = self.dbi_header()?;
@@ -191,6 +591,8 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// * `Error::IoError` if returned by the `Source`
     /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
     /// * `Error::UnimplementedFeature` if the module information stream is an unsupported version
+    /// * `Error::MiniPdb` if the PDB was linked with `/DEBUG:FASTLINK` and the module has no
+    ///   information stream
     ///
     /// # Example
     ///
@@ -199,7 +601,7 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// #
     /// # fn test() -> pdb::Result<()> {
     /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
-    /// let mut pdb = pdb::PDB::open(file)?;
+    /// let pdb = pdb::PDB::open(file)?;
     /// let dbi = pdb.debug_information()?;
     /// let mut modules = dbi.modules()?;
     /// if let Some(module) = modules.next()? {
@@ -214,10 +616,55 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn module_info<'m>(&mut self, module: &Module<'m>) -> Result<Option<ModuleInfo<'s>>> {
-        Ok(self
-            .raw_stream(module.info().stream)?
-            .map(|stream| ModuleInfo::parse(stream, module)))
+    pub fn module_info<'m>(&self, module: &Module<'m>) -> Result<Option<ModuleInfo<'s>>> {
+        match self.raw_stream(module.info().stream)? {
+            Some(stream) => Ok(Some(ModuleInfo::parse(stream, module))),
+            None if self.is_mini_pdb().unwrap_or(false) => Err(Error::MiniPdb),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns whether this PDB was linked with `/DEBUG:FASTLINK`.
+    ///
+    /// Such "mini PDBs" declare the `MinimalDebugInfo` feature. They contain the PDB information,
+    /// the module list, public symbols and section headers, but type information and module
+    /// symbols remain in the object files. Accessors that need this information return
+    /// `Error::MiniPdb` for these PDBs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// assert!(!pdb.is_mini_pdb()?);
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain the PDB information stream
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    pub fn is_mini_pdb(&self) -> Result<bool> {
+        if let Some(mini_pdb) = self.mini_pdb.get() {
+            return Ok(*mini_pdb);
+        }
+
+        let features = self.pdb_information()?.features()?;
+        let mini_pdb = features.minimal_debug_info();
+        let _ = self.mini_pdb.set(mini_pdb);
+        Ok(mini_pdb)
+    }
+
+    /// Replaces a stream lookup error with `Error::MiniPdb` for `/DEBUG:FASTLINK` PDBs.
+    fn mini_pdb_error<T>(&self, error: Error) -> Result<T> {
+        match error {
+            Error::StreamNotFound(_) if self.is_mini_pdb().unwrap_or(false) => Err(Error::MiniPdb),
+            error => Err(error),
+        }
     }
 
     /// Retrieve the executable's section headers, as stored inside this PDB.
@@ -238,7 +685,7 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     ///
     /// * `Error::StreamNotFound` if the PDB somehow does not contain a debug information stream
     /// * `Error::UnimplementedFeature` if the debug information header predates ~1995
-    pub fn sections(&mut self) -> Result<Option<Vec<ImageSectionHeader>>> {
+    pub fn sections(&self) -> Result<Option<Vec<ImageSectionHeader>>> {
         let index = self.extra_streams()?.section_headers;
         let stream = match self.raw_stream(index)? {
             Some(stream) => stream,
@@ -275,7 +722,7 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// #
     /// # fn test() -> pdb::Result<()> {
     /// # let source = std::fs::File::open("fixtures/self/foo.pdb")?;
-    /// let mut pdb = PDB::open(source)?;
+    /// let pdb = PDB::open(source)?;
     ///
     /// // Read the tables once and reuse them
     /// let address_map = pdb.address_map()?;
@@ -290,14 +737,28 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// # }
     /// # test().unwrap()
     /// ```
-    pub fn frame_table(&mut self) -> Result<FrameTable<'s>> {
+    pub fn frame_table(&self) -> Result<FrameTable<'s>> {
         let extra = self.extra_streams()?;
         let old_stream = self.raw_stream(extra.fpo)?;
         let new_stream = self.raw_stream(extra.framedata)?;
         FrameTable::parse(old_stream, new_stream)
     }
 
-    pub(crate) fn original_sections(&mut self) -> Result<Option<Vec<ImageSectionHeader>>> {
+    /// Retrieve the executable's section headers before it was transformed.
+    ///
+    /// Tools that reorder or instrument binaries after linking, such as profile guided
+    /// optimizations, keep the original section headers. Symbols refer to addresses in the
+    /// original sections, which can be translated using [`address_map`](Self::address_map).
+    ///
+    /// Returns `None` if the image was not transformed.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain the referenced stream
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    /// * `Error::UnexpectedEof` if the section headers are truncated mid-record
+    pub fn original_sections(&self) -> Result<Option<Vec<ImageSectionHeader>>> {
         let index = self.extra_streams()?.original_section_headers;
         let stream = match self.raw_stream(index)? {
             Some(stream) => stream,
@@ -313,7 +774,18 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
         Ok(Some(headers))
     }
 
-    pub(crate) fn omap_from_src(&mut self) -> Result<Option<OMAPTable<'s>>> {
+    /// Retrieve the OMAP translating addresses of the original image to the transformed image.
+    ///
+    /// Returns `None` if the image was not transformed. For most purposes, the
+    /// [`address_map`](Self::address_map) is more convenient.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain the referenced stream
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    /// * `Error::InvalidStreamLength` if the OMAP is not a sequence of records
+    pub fn omap_from_src(&self) -> Result<Option<OMAPTable<'s>>> {
         let index = self.extra_streams()?.omap_from_src;
         match self.raw_stream(index)? {
             Some(stream) => OMAPTable::parse(stream).map(Some),
@@ -321,7 +793,18 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
         }
     }
 
-    pub(crate) fn omap_to_src(&mut self) -> Result<Option<OMAPTable<'s>>> {
+    /// Retrieve the OMAP translating addresses of the transformed image to the original image.
+    ///
+    /// Returns `None` if the image was not transformed. For most purposes, the
+    /// [`address_map`](Self::address_map) is more convenient.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain the referenced stream
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    /// * `Error::InvalidStreamLength` if the OMAP is not a sequence of records
+    pub fn omap_to_src(&self) -> Result<Option<OMAPTable<'s>>> {
         let index = self.extra_streams()?.omap_to_src;
         match self.raw_stream(index)? {
             Some(stream) => OMAPTable::parse(stream).map(Some),
@@ -329,39 +812,136 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
         }
     }
 
-    /// Build a map translating between different kinds of offsets and virtual addresses.
-    ///
-    /// For more information on address translation, see [`AddressMap`].
+    /// Retrieve the fixups the linker applied to the image.
     ///
-    /// This reads `omap_from_src` and either `original_sections` or `sections` from this PDB and
-    /// chooses internally which strategy to use for resolving RVAs. Consider to reuse this instance
-    /// for multiple translations.
+    /// Returns `None` if the linker did not record fixups, which it only does for images linked
+    /// with `/DEBUGTYPE:FIXUP`.
     ///
     /// # Errors
     ///
-    /// * `Error::OmapNotFound` if an OMAP is required for translation but missing
-    /// * `Error::StreamNotFound` if the PDB somehow does not contain section headers
+    /// * `Error::StreamNotFound` if the PDB does not contain the referenced stream
     /// * `Error::IoError` if returned by the `Source`
     /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
-    /// * `Error::UnexpectedEof` if the section headers are truncated mid-record
-    ///
-    /// If `debug_information()` was not already called, `omap_table()` will additionally read the
-    /// debug information header, in which case it can also return:
-    ///
-    /// * `Error::StreamNotFound` if the PDB somehow does not contain a debug information stream
-    /// * `Error::UnimplementedFeature` if the debug information header predates ~1995
-    ///
-    /// # Example
+    /// * `Error::UnexpectedEof` if the fixups are truncated mid-record
+    pub fn fixups(&self) -> Result<Option<Vec<ImageFixup>>> {
+        let index = self.extra_streams()?.fixup;
+        let stream = match self.raw_stream(index)? {
+            Some(stream) => stream,
+            None => return Ok(None),
+        };
+
+        let mut buf = stream.parse_buffer();
+        let mut fixups = Vec::with_capacity(buf.len() / 12);
+        while !buf.is_empty() {
+            fixups.push(ImageFixup::parse(&mut buf)?);
+        }
+
+        Ok(Some(fixups))
+    }
+
+    /// Retrieve the map of metadata token RIDs of managed code.
     ///
-    /// ```rust
-    /// # use pdb::{Rva, FallibleIterator};
-    /// #
-    /// # fn test() -> pdb::Result<()> {
-    /// # let source = std::fs::File::open("fixtures/self/foo.pdb")?;
-    /// let mut pdb = pdb::PDB::open(source)?;
+    /// Returns `None` if the PDB does not describe managed code.
     ///
-    /// // Compute the address map once and reuse it
-    /// let address_map = pdb.address_map()?;
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain the referenced stream
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    /// * `Error::UnexpectedEof` if the map is truncated mid-record
+    pub fn token_rid_map(&self) -> Result<Option<Vec<u32>>> {
+        let index = self.extra_streams()?.token_rid_map;
+        let stream = match self.raw_stream(index)? {
+            Some(stream) => stream,
+            None => return Ok(None),
+        };
+
+        let mut buf = stream.parse_buffer();
+        let mut rids = Vec::with_capacity(buf.len() / 4);
+        while !buf.is_empty() {
+            rids.push(buf.parse_u32()?);
+        }
+
+        Ok(Some(rids))
+    }
+
+    /// Retrieve the exception data stream of the image, if present.
+    ///
+    /// The contents of this stream are returned raw.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain the referenced stream
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    pub fn exception_data(&self) -> Result<Option<Stream<'s>>> {
+        let index = self.extra_streams()?.exception;
+        self.raw_stream(index)
+    }
+
+    /// Retrieve the copy of the `.xdata` section of the image, if present.
+    ///
+    /// The contents of this stream are returned raw. It contains the unwind information referenced
+    /// by [`pdata`](Self::pdata).
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain the referenced stream
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    pub fn xdata(&self) -> Result<Option<Stream<'s>>> {
+        let index = self.extra_streams()?.xdata;
+        self.raw_stream(index)
+    }
+
+    /// Retrieve the copy of the `.pdata` section of the image, if present.
+    ///
+    /// The contents of this stream are returned raw. It contains the function table used for
+    /// exception handling and stack unwinding.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain the referenced stream
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    pub fn pdata(&self) -> Result<Option<Stream<'s>>> {
+        let index = self.extra_streams()?.pdata;
+        self.raw_stream(index)
+    }
+
+    /// Build a map translating between different kinds of offsets and virtual addresses.
+    ///
+    /// For more information on address translation, see [`AddressMap`].
+    ///
+    /// This reads `omap_from_src` and either `original_sections` or `sections` from this PDB and
+    /// chooses internally which strategy to use for resolving RVAs. Consider to reuse this instance
+    /// for multiple translations.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::OmapNotFound` if an OMAP is required for translation but missing
+    /// * `Error::StreamNotFound` if the PDB somehow does not contain section headers
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    /// * `Error::UnexpectedEof` if the section headers are truncated mid-record
+    ///
+    /// If `debug_information()` was not already called, `omap_table()` will additionally read the
+    /// debug information header, in which case it can also return:
+    ///
+    /// * `Error::StreamNotFound` if the PDB somehow does not contain a debug information stream
+    /// * `Error::UnimplementedFeature` if the debug information header predates ~1995
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use pdb::{Rva, FallibleIterator};
+    /// #
+    /// # fn test() -> pdb::Result<()> {
+    /// # let source = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(source)?;
+    ///
+    /// // Compute the address map once and reuse it
+    /// let address_map = pdb.address_map()?;
     ///
     /// # let symbol_table = pdb.global_symbols()?;
     /// # let symbol = symbol_table.iter().next()?.unwrap();
@@ -382,7 +962,7 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// # }
     /// # test().unwrap()
     /// ```
-    pub fn address_map(&mut self) -> Result<AddressMap<'s>> {
+    pub fn address_map(&self) -> Result<AddressMap<'s>> {
         let sections = self.sections()?.unwrap_or_default();
         Ok(match self.original_sections()? {
             Some(original_sections) => {
@@ -422,7 +1002,7 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// #
     /// # fn test() -> pdb::Result<()> {
     /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
-    /// let mut pdb = PDB::open(file)?;
+    /// let pdb = PDB::open(file)?;
     /// let strings = pdb.string_table()?;
     ///
     /// // obtain a string ref somehow
@@ -443,11 +1023,205 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// * `Error::IoError` if returned by the `Source`
     /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
     /// * `Error::UnexpectedEof` if the string table ends prematurely
-    pub fn string_table(&mut self) -> Result<StringTable<'s>> {
+    pub fn string_table(&self) -> Result<StringTable<'s>> {
         let stream = self.named_stream(b"/names")?;
         StringTable::parse(stream)
     }
 
+    /// Looks up the source file and line declaring a class, structure, union or enumeration.
+    ///
+    /// The type is identified by its index in the type information stream or by its fully
+    /// qualified name, see [`UdtQuery`]. This joins the type information stream, the source
+    /// locations recorded in the id information stream, the string table holding file names and
+    /// the module list of the debug information stream. Their hash streams are used if present, so
+    /// that no stream has to be iterated entirely.
+    ///
+    /// Returns `None` if the type does not exist, is only forward declared or has no recorded
+    /// source location. To look up many types, collect all source locations at once using
+    /// [`IdInformation::type_sources`](crate::ItemInformation::type_sources) instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// if let Some(source) = pdb.udt_source("Baz")? {
+    ///     println!("{}:{}", source.file, source.line);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain the type or id information stream
+    /// * `Error::StreamNameNotFound` if a file name refers to the string table, but the PDB has none
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    /// * `Error::UnexpectedEof` if a stream is truncated
+    pub fn udt_source<'q, Q>(&self, udt: Q) -> Result<Option<UdtSource>>
+    where
+        Q: Into<UdtQuery<'q>>,
+    {
+        let type_information = self.type_information()?;
+        let index = match udt.into() {
+            UdtQuery::Index(index) => {
+                let mut finder = type_information.finder();
+                finder.index_until(index)?;
+                match finder.resolve_definition(index) {
+                    Ok(Some(index)) => index,
+                    Ok(None) | Err(Error::TypeNotFound(_)) => return Ok(None),
+                    Err(error) => return Err(error),
+                }
+            }
+            UdtQuery::Name(name) => {
+                let index = match self.type_hashes()? {
+                    Some(hashes) => hashes.find_by_name(&type_information, name)?,
+                    None => find_definition(&type_information, name)?,
+                };
+                match index {
+                    Some(index) => index,
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        let id_information = self.id_information()?;
+        let source = match self.udt_source_record(&id_information, index)? {
+            Some(source) => source,
+            None => return Ok(None),
+        };
+
+        let (file, module) = match source.source_file {
+            UserDefinedTypeSourceFileRef::Local(file) => {
+                let mut finder = id_information.finder();
+                finder.index_until(file)?;
+                let name = finder.resolve_string(file)?;
+                (String::from_utf8_lossy(&name).into_owned(), None)
+            }
+            UserDefinedTypeSourceFileRef::Remote(module, file) => {
+                let strings = self.string_table()?;
+                let name = strings.get(file)?.to_string().into_owned();
+                // module numbers are one-based
+                (name, usize::from(module).checked_sub(1))
+            }
+        };
+
+        let module_name = match module {
+            Some(module) => self
+                .debug_information()?
+                .modules()?
+                .nth(module)?
+                .map(|module| module.module_name().into_owned()),
+            None => None,
+        };
+
+        Ok(Some(UdtSource {
+            index,
+            file,
+            line: source.line,
+            module,
+            module_name,
+        }))
+    }
+
+    /// Finds the source location record of a user-defined type definition.
+    fn udt_source_record(
+        &self,
+        ids: &IdInformation<'s>,
+        udt: TypeIndex,
+    ) -> Result<Option<UserDefinedTypeSourceId>> {
+        if let Some(hashes) = self.id_hashes()? {
+            return match hashes.find_udt_source(ids, udt)? {
+                Some(index) => match hashes.find_item(ids, index)?.parse()? {
+                    IdData::UserDefinedTypeSource(source) => Ok(Some(source)),
+                    _ => Ok(None),
+                },
+                None => Ok(None),
+            };
+        }
+
+        let mut iter = ids.iter();
+        while let Some(item) = iter.next()? {
+            if let Ok(IdData::UserDefinedTypeSource(source)) = item.parse() {
+                if source.udt == udt {
+                    return Ok(Some(source));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Retrieve information about the linker invocation from the `/LinkInfo` stream.
+    ///
+    /// Returns `None` if the PDB does not contain this stream or if it is empty, which is common
+    /// for PDBs produced by recent toolchains.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// if let Some(link_info) = pdb.link_info()? {
+    ///     println!("linked in {}", link_info.cwd());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    /// * `Error::InvalidStreamLength` if the stream is shorter than its declared size
+    /// * `Error::UnexpectedEof` if a string lies outside of the stream
+    pub fn link_info(&self) -> Result<Option<LinkInfo<'s>>> {
+        let stream = match self.named_stream(b"/LinkInfo") {
+            Ok(stream) => stream,
+            Err(Error::StreamNameNotFound) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        if stream.is_empty() {
+            return Ok(None);
+        }
+
+        LinkInfo::parse(stream).map(Some)
+    }
+
+    /// Retrieve the type merge cache from the `/TMCache` stream.
+    ///
+    /// The cache lists the streams holding the type maps the linker computed per module while
+    /// de-duplicating types. Returns `None` if the PDB does not contain this stream or if it is
+    /// empty. See [`TypeMergeCache`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    /// * `Error::InvalidStreamLength` if the stream header is malformed
+    /// * `Error::UnexpectedEof` if the stream ends prematurely
+    pub fn type_merge_cache(&self) -> Result<Option<TypeMergeCache>> {
+        let stream = match self.named_stream(b"/TMCache") {
+            Ok(stream) => stream,
+            Err(Error::StreamNameNotFound) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        if stream.is_empty() {
+            return Ok(None);
+        }
+
+        TypeMergeCache::parse(stream).map(Some)
+    }
+
     /// Retrieve a stream by its index to read its contents as bytes.
     ///
     /// # Errors
@@ -461,20 +1235,414 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// ```
     /// # fn test() -> pdb::Result<()> {
     /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
-    /// let mut pdb = pdb::PDB::open(file)?;
+    /// let pdb = pdb::PDB::open(file)?;
     /// // This is the index of the "mystream" stream that was added using pdbstr.exe.
     /// let s = pdb.raw_stream(pdb::StreamIndex(208))?.expect("stream exists");
     /// assert_eq!(s.as_slice(), b"hello world\n");
     /// # Ok(())
     /// # }
     /// ```
-    pub fn raw_stream(&mut self, index: StreamIndex) -> Result<Option<Stream<'s>>> {
+    pub fn raw_stream(&self, index: StreamIndex) -> Result<Option<Stream<'s>>> {
+        match index.msf_number() {
+            Some(number) => self.get_stream(number, None).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Retrieve `len` bytes of a stream starting at `offset`.
+    ///
+    /// Unlike [`raw_stream`](Self::raw_stream), this only reads the pages covering the requested
+    /// range, which is considerably cheaper when only a small window of a large stream is needed.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain this stream
+    /// * `Error::UnexpectedEof` if the range exceeds the size of the stream
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let s = pdb.raw_stream_range(pdb::StreamIndex(208), 6, 5)?.expect("stream exists");
+    /// assert_eq!(s.as_slice(), b"world");
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn raw_stream_range(
+        &self,
+        index: StreamIndex,
+        offset: usize,
+        len: usize,
+    ) -> Result<Option<Stream<'s>>> {
         match index.msf_number() {
-            Some(number) => self.msf.get(number, None).map(Some),
+            Some(number) => self.msf.get_range(number, offset, len).map(Some),
             None => Ok(None),
         }
     }
 
+    /// Write the contents of a stream to `writer`, returning the number of bytes written.
+    ///
+    /// The stream is copied a few pages at a time rather than being viewed as a whole, which keeps
+    /// memory usage bounded even for streams that are gigabytes in size.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain this stream, or the index is none
+    /// * `Error::IoError` if returned by the `Source` or the `Write`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// let mut output = Vec::new();
+    /// pdb.copy_stream_to(pdb::StreamIndex(208), &mut output)?;
+    /// assert_eq!(output, b"hello world\n");
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn copy_stream_to<W: std::io::Write>(
+        &self,
+        index: StreamIndex,
+        mut writer: W,
+    ) -> Result<u64> {
+        let number = index
+            .msf_number()
+            .ok_or(Error::StreamNotFound(u32::from(index.0)))?;
+        self.msf.copy_stream_to(number, &mut writer)
+    }
+
+    /// Compute a digest of the contents of a stream.
+    ///
+    /// The stream is hashed a few pages at a time without holding it in memory. Comparing the
+    /// digests of corresponding streams allows identifying exactly which streams differ between
+    /// two builds of a PDB.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain this stream, or the index is none
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// let hash = pdb.stream_hash(pdb::StreamIndex(208), pdb::HashAlgorithm::XxHash64)?;
+    /// assert_eq!(hash.len(), 8);
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    #[cfg(feature = "hash")]
+    pub fn stream_hash(&self, index: StreamIndex, algorithm: HashAlgorithm) -> Result<Vec<u8>> {
+        let number = index
+            .msf_number()
+            .ok_or(Error::StreamNotFound(u32::from(index.0)))?;
+        self.msf.stream_hash(number, algorithm)
+    }
+
+    /// Request the contents of several streams from the `Source` at once.
+    ///
+    /// The pages of all given streams are requested with a single call to [`Source::view`],
+    /// ordered by their location in the file so that adjacent pages form contiguous slices. The
+    /// contents are not returned; this is meant to warm a `Source` that retains data, such as
+    /// [`PrefetchSource`](crate::PrefetchSource), before the streams are accessed. For example,
+    /// tools about to process a set of modules can prefetch all of their module info streams,
+    /// which include their line information, in a single round trip.
+    ///
+    /// Indices that are none are ignored.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain one of the streams
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    pub fn prefetch(&self, indices: &[StreamIndex]) -> Result<()> {
+        let numbers: Vec<u32> = indices
+            .iter()
+            .filter_map(|index| index.msf_number())
+            .collect();
+        self.msf.prefetch(&numbers)
+    }
+
+    /// Retrieve a stream by its index to read its contents incrementally.
+    ///
+    /// The returned [`StreamReader`] implements `std::io::Read` and `std::io::Seek` and only reads
+    /// the pages required for each individual read from the `Source`.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain this stream
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    pub fn stream_reader(&self, index: StreamIndex) -> Result<Option<StreamReader<'_, 's, S>>> {
+        match index.msf_number() {
+            Some(number) => match self.msf.stream_size(number)? {
+                Some(size) => Ok(Some(StreamReader::new(&*self.msf, number, size))),
+                None => Err(Error::StreamNotFound(number)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the number of streams in this PDB, including streams that are not present.
+    pub fn stream_count(&self) -> u32 {
+        self.msf.stream_count()
+    }
+
+    /// Returns the size of a stream in bytes.
+    ///
+    /// Returns `None` if this index is none or the stream is not present.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the index exceeds the number of streams
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    pub fn stream_size(&self, index: StreamIndex) -> Result<Option<u32>> {
+        match index.msf_number() {
+            Some(number) => self.msf.stream_size(number),
+            None => Ok(None),
+        }
+    }
+
+    /// Describes which pages of the file store a stream.
+    ///
+    /// The returned [`StreamLayout`] lists the page numbers in stream order, as well as the byte
+    /// ranges of the file they occupy. Page numbers are reported as stored in the stream table and
+    /// are not validated, which makes this suitable for examining damaged files.
+    ///
+    /// Returns `None` if this index is none.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNotFound` if the PDB does not contain this stream
+    /// * `Error::UnimplementedFeature` if the PDB is stored in a compressed MSFZ container
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// let layout = pdb.stream_layout(pdb::StreamIndex(208))?.expect("stream exists");
+    /// assert_eq!(layout.size(), 12);
+    /// assert_eq!(layout.pages().len(), 1);
+    ///
+    /// let page = u64::from(layout.pages()[0]) * u64::from(layout.page_size());
+    /// assert_eq!(layout.extents(), [page..page + 12]);
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn stream_layout(&self, index: StreamIndex) -> Result<Option<StreamLayout>> {
+        match index.msf_number() {
+            Some(number) => self.msf.stream_layout(number).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns an iterator over the index and size of all streams present in this PDB.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// for entry in pdb.present_streams() {
+    ///     println!("stream {} has {} bytes", entry.index, entry.size);
+    /// }
+    /// # assert_eq!(pdb.stream_size(pdb::StreamIndex(208))?, Some(12));
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn present_streams(&self) -> StreamIter {
+        self.msf.present_streams()
+    }
+
+    /// Lists every stream of this PDB along with its classification and size.
+    ///
+    /// Streams are classified by their well-known stream numbers, the stream numbers recorded in
+    /// the TPI, IPI and DBI headers, the module list, and the named stream map. Streams that are
+    /// not referenced from any of these are reported as [`StreamKind::Unknown`]. If a stream is
+    /// referenced more than once, the first classification in that order wins.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// for stream in pdb.streams()? {
+    ///     println!("{}: {:?} ({:?} bytes)", stream.index, stream.kind, stream.size);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    /// * `Error::UnexpectedEof` if one of the referencing structures is truncated
+    pub fn streams(&self) -> Result<Vec<StreamSummary>> {
+        let count = self.stream_count();
+        let mut kinds = vec![StreamKind::Unknown; count as usize];
+        let mut classify = |index: StreamIndex, kind: StreamKind| {
+            if let Some(number) = index.msf_number() {
+                if let Some(slot) = kinds.get_mut(number as usize) {
+                    if *slot == StreamKind::Unknown {
+                        *slot = kind;
+                    }
+                }
+            }
+        };
+
+        classify(StreamIndex(0), StreamKind::PreviousDirectory);
+        classify(StreamIndex(PDB_STREAM as u16), StreamKind::PdbInformation);
+        classify(StreamIndex(TPI_STREAM as u16), StreamKind::TypeInformation);
+        classify(StreamIndex(DBI_STREAM as u16), StreamKind::DebugInformation);
+        classify(StreamIndex(IPI_STREAM as u16), StreamKind::IdInformation);
+
+        for (number, kind) in &[
+            (TPI_STREAM, StreamKind::TypeHash),
+            (IPI_STREAM, StreamKind::IdHash),
+        ] {
+            for index in self.hash_streams(*number)? {
+                classify(index, kind.clone());
+            }
+        }
+
+        if self
+            .stream_size(StreamIndex(DBI_STREAM as u16))?
+            .unwrap_or(0)
+            > 0
+        {
+            let header = self.dbi_header()?;
+            classify(header.gs_symbols_stream, StreamKind::GlobalSymbols);
+            classify(header.ps_symbols_stream, StreamKind::PublicSymbols);
+            classify(header.symbol_records_stream, StreamKind::SymbolRecords);
+
+            let extra = self.extra_streams()?;
+            for &(index, name) in &[
+                (extra.fpo, "fpo"),
+                (extra.exception, "exception"),
+                (extra.fixup, "fixup"),
+                (extra.omap_to_src, "omap_to_src"),
+                (extra.omap_from_src, "omap_from_src"),
+                (extra.section_headers, "section_headers"),
+                (extra.token_rid_map, "token_rid_map"),
+                (extra.xdata, "xdata"),
+                (extra.pdata, "pdata"),
+                (extra.framedata, "framedata"),
+                (extra.original_section_headers, "original_section_headers"),
+            ] {
+                classify(index, StreamKind::DebugStream(name));
+            }
+
+            let debug_info = self.debug_information()?;
+            let mut modules = debug_info.modules()?.enumerate();
+            while let Some((index, module)) = modules.next()? {
+                classify(module.info().stream, StreamKind::Module(index));
+            }
+        }
+
+        if self
+            .stream_size(StreamIndex(PDB_STREAM as u16))?
+            .unwrap_or(0)
+            > 0
+        {
+            let info = self.pdb_information()?;
+            for name in &info.stream_names()? {
+                classify(
+                    name.stream_id,
+                    StreamKind::Named(name.name_lossy().into_owned()),
+                );
+            }
+        }
+
+        kinds
+            .into_iter()
+            .enumerate()
+            .map(|(number, kind)| {
+                let index = StreamIndex(number as u16);
+                Ok(StreamSummary {
+                    index,
+                    kind,
+                    size: self.stream_size(index)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Reads the hash stream numbers from the header of the TPI or IPI stream.
+    fn hash_streams(&self, number: u32) -> Result<Vec<StreamIndex>> {
+        // the hash stream numbers are stored at offset 20 of the header
+        let index = StreamIndex(number as u16);
+        if self.stream_size(index)?.unwrap_or(0) < 24 {
+            return Ok(Vec::new());
+        }
+
+        let data = self.msf.get_range(number, 20, 4)?;
+        let mut buf = data.parse_buffer();
+        Ok(vec![buf.parse()?, buf.parse()?])
+    }
+
+    /// Retrieve the stream directory as of the previous write of this PDB.
+    ///
+    /// Tools that update PDBs incrementally keep the previous stream table in stream 0. The
+    /// returned [`PreviousDirectory`] allows reading streams as they were before the last update,
+    /// as long as their pages have not been reused since.
+    ///
+    /// Returns `None` if stream 0 is empty, which is the case for PDBs written only once.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::InvalidStreamLength` if the previous stream table seems corrupt
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// // "mystream" was added to this PDB using pdbstr.exe, which also updated the info stream
+    /// let previous = pdb.previous_directory()?.expect("PDB was updated");
+    /// assert_eq!(previous.stream_count(), 208);
+    /// assert_eq!(previous.stream_size(pdb::StreamIndex(1))?, Some(118));
+    /// assert_eq!(pdb.stream_size(pdb::StreamIndex(1))?, Some(135));
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn previous_directory(&self) -> Result<Option<PreviousDirectory<'_, 's, S>>> {
+        Ok(self
+            .msf
+            .previous_stream_table()?
+            .map(|(header, table)| PreviousDirectory::new(&*self.msf, header, table)))
+    }
+
     /// Retrieve a stream by its name, as declared in the PDB info stream.
     ///
     /// # Errors
@@ -483,17 +1651,84 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// * `Error::StreamNotFound` if the PDB does not contain the stream referred to
     /// * `Error::IoError` if returned by the `Source`
     /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
-    pub fn named_stream(&mut self, name: &[u8]) -> Result<Stream<'s>> {
+    pub fn named_stream(&self, name: &[u8]) -> Result<Stream<'s>> {
         let info = self.pdb_information()?;
         let names = info.stream_names()?;
-        for named_stream in &names {
-            if named_stream.name.as_bytes() == name {
-                return self
-                    .raw_stream(named_stream.stream_id)?
-                    .ok_or(Error::StreamNameNotFound);
-            }
+        match names.get(name) {
+            Some(stream_id) => self.raw_stream(stream_id)?.ok_or(Error::StreamNameNotFound),
+            None => Err(Error::StreamNameNotFound),
         }
-        Err(Error::StreamNameNotFound)
+    }
+
+    /// Retrieve metadata about the underlying multi-stream file.
+    ///
+    /// This reports the container format along with the values recorded in its header, such as
+    /// the page size and the size of the stream directory. See [`MsfInfo`] for more information.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// let info = pdb.msf_info();
+    /// assert_eq!(info.version(), pdb::MsfVersion::V700);
+    /// assert_eq!(info.page_size(), 4096);
+    /// println!(
+    ///     "{} pages, {} byte directory, free page map {:?}",
+    ///     info.pages_used(),
+    ///     info.directory_size(),
+    ///     info.free_page_map()
+    /// );
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn msf_info(&self) -> MsfInfo {
+        self.msf.info()
+    }
+
+    /// Retrieve the free page map of the underlying multi-stream file.
+    ///
+    /// The free page map indicates which pages of the file are in use. See [`FreePageMap`] for
+    /// more information.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidFreePageMap` if the MSF header does not specify a valid free page map
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    pub fn free_page_map(&self) -> Result<FreePageMap> {
+        self.msf.free_page_map()
+    }
+
+    /// Check that every page referenced by the stream table is marked as allocated in the free page
+    /// map.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::PageMarkedFree` if a page is in use but marked as free
+    /// * `Error::InvalidFreePageMap` if the MSF header does not specify a valid free page map
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    ///
+    /// pdb.validate_free_page_map()?;
+    /// let free_page_map = pdb.free_page_map()?;
+    /// println!("{} pages are free", free_page_map.free_pages().count());
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn validate_free_page_map(&self) -> Result<()> {
+        self.msf.validate_free_page_map()
     }
 
     /// Loads the Optional Debug Header Stream, which contains offsets into extra streams.
@@ -502,18 +1737,37 @@ impl<'s, S: Source<'s> + 's> PDB<'s, S> {
     /// present in the PDB.
     ///
     /// The optional header begins at offset 0 immediately after the EC Substream ends.
-    fn extra_streams(&mut self) -> Result<DBIExtraStreams> {
-        if let Some(extra) = self.dbi_extra_streams {
-            return Ok(extra);
+    fn extra_streams(&self) -> Result<DBIExtraStreams> {
+        if let Some(extra) = self.dbi_extra_streams.get() {
+            return Ok(*extra);
         }
 
         // Parse and grab information on extra streams, since we might also need that
         let debug_info = self.debug_information()?;
         let extra = DBIExtraStreams::new(&debug_info)?;
-        self.dbi_extra_streams = Some(extra);
+        let _ = self.dbi_extra_streams.set(extra);
 
         Ok(extra)
     }
+
+    /// Reads a stream, notifying the progress handler if one is installed.
+    fn get_stream(&self, number: u32, limit: Option<usize>) -> Result<Stream<'s>> {
+        let handler = match self.progress_handler {
+            Some(ref handler) => handler,
+            None => return self.msf.get(number, limit),
+        };
+
+        let mut handler = handler.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let stream = StreamIndex(number as u16);
+        self.msf
+            .get_with_progress(number, limit, &mut |bytes_read, bytes_total| {
+                handler(Progress {
+                    stream,
+                    bytes_read,
+                    bytes_total,
+                })
+            })
+    }
 }
 
 impl StreamIndex {
@@ -527,7 +1781,7 @@ impl StreamIndex {
     /// * `Error::StreamNotFound` if the PDB does not contain this stream
     /// * `Error::IoError` if returned by the `Source`
     /// * `Error::PageReferenceOutOfRange` if the PDB file seems corrupt
-    pub fn get<'s, S>(self, pdb: &mut PDB<'s, S>) -> Result<Option<Stream<'s>>>
+    pub fn get<'s, S>(self, pdb: &PDB<'s, S>) -> Result<Option<Stream<'s>>>
     where
         S: Source<'s> + 's,
     {
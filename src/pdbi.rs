@@ -5,7 +5,9 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
+use std::borrow::Cow;
 use std::convert::TryInto;
+use std::fmt;
 use std::mem;
 
 use uuid::Uuid;
@@ -13,6 +15,10 @@ use uuid::Uuid;
 use crate::common::*;
 use crate::dbi::HeaderVersion;
 use crate::msf::*;
+use crate::pe::CodeViewRecord;
+
+/// The last version of the PDB information stream without a GUID.
+const IMPV_VC70_DEP: u32 = 19_990_604;
 
 /// A PDB info stream header parsed from a stream.
 ///
@@ -37,11 +43,15 @@ pub struct PDBInformation<'s> {
     /// than the image's age.
     pub age: u32,
     /// A `Uuid` generated when this PDB file was created that should uniquely identify it.
+    ///
+    /// PDB files written before Visual C++ 7.0 do not contain a GUID, in which case this is nil and
+    /// the file is identified by its `signature` instead.
     pub guid: Uuid,
     /// The offset of the start of the stream name data within the stream.
     pub names_offset: usize,
     /// The size of the stream name data, in bytes.
     pub names_size: usize,
+    has_guid: bool,
     stream: Stream<'s>,
 }
 
@@ -50,31 +60,49 @@ impl<'s> PDBInformation<'s> {
     pub(crate) fn parse(stream: Stream<'s>) -> Result<Self> {
         let (version, signature, age, guid, names_size, names_offset) = {
             let mut buf = stream.parse_buffer();
-            let version = From::from(buf.parse_u32()?);
+            let raw_version = buf.parse_u32()?;
             let signature = buf.parse_u32()?;
             let age = buf.parse_u32()?;
-            let guid = Uuid::from_fields(
-                buf.parse_u32()?,
-                buf.parse_u16()?,
-                buf.parse_u16()?,
-                buf.take(8)?.try_into().unwrap(),
-            );
+            let guid = if raw_version > IMPV_VC70_DEP {
+                Some(Uuid::from_fields(
+                    buf.parse_u32()?,
+                    buf.parse_u16()?,
+                    buf.parse_u16()?,
+                    buf.take(8)?.try_into().unwrap(),
+                ))
+            } else {
+                None
+            };
             let names_size = buf.parse_u32()? as usize;
             let names_offset = buf.pos();
-            (version, signature, age, guid, names_size, names_offset)
+            (
+                From::from(raw_version),
+                signature,
+                age,
+                guid,
+                names_size,
+                names_offset,
+            )
         };
 
         Ok(PDBInformation {
             version,
             signature,
             age,
-            guid,
-            names_size,
+            guid: guid.unwrap_or_else(Uuid::nil),
             names_offset,
+            names_size,
+            has_guid: guid.is_some(),
             stream,
         })
     }
 
+    /// Returns whether this PDB records a GUID, which is the case for all PDBs written by
+    /// Visual C++ 7.0 and later.
+    pub fn has_guid(&self) -> bool {
+        self.has_guid
+    }
+
     /// Get a `StreamNames` object that can be used to iterate over named streams contained
     /// within the PDB file.
     ///
@@ -87,7 +115,7 @@ impl<'s> PDBInformation<'s> {
     /// #
     /// # fn test() -> pdb::Result<()> {
     /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
-    /// let mut pdb = pdb::PDB::open(file)?;
+    /// let pdb = pdb::PDB::open(file)?;
     /// let info = pdb.pdb_information()?;
     /// let names = info.stream_names()?;
     /// let mut v: Vec<_> = names.iter().map(|n| n.name.to_string()).collect();
@@ -116,33 +144,240 @@ impl<'s> PDBInformation<'s> {
         // [3]: https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/PDB/include/iset.h#L62
         // [4]: https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/PDB/include/array.h#L209
 
-        let mut names = vec![];
         let mut buf = self.stream.parse_buffer();
 
         // Seek forward to the name map.
         buf.take(self.names_offset + self.names_size)?;
-        let count = buf.parse_u32()?;
-        // We don't actually use most of these.
-        let _entries_size = buf.parse_u32()?;
-        let ok_words = buf.parse_u32()?;
-        let _ok_bits = buf.take(ok_words as usize * mem::size_of::<u32>())?;
-        let deleted_words = buf.parse_u32()?;
-        let _deleted_bits = buf.take(deleted_words as usize * mem::size_of::<u32>())?;
+        let size = buf.parse_u32()?;
+        let capacity = buf.parse_u32()?;
+        let present = parse_bit_array(&mut buf)?;
+        let deleted = parse_bit_array(&mut buf)?;
 
         // Skip over the header here.
         let mut names_reader = self.stream.parse_buffer();
         names_reader.take(self.names_offset)?;
         // And take just the name data.
         let names_buf = names_reader.take(self.names_size)?;
-        for _ in 0..count {
-            let name_offset = buf.parse_u32()? as usize;
+
+        // Entries are stored in the order of their buckets, one for each bit set in `present`.
+        let mut buckets = (0..present.len() as u32 * 32)
+            .filter(|&bucket| present[bucket as usize / 32] & (1 << (bucket % 32)) != 0);
+
+        // Each entry takes eight bytes, so a damaged size cannot reserve more than the stream holds.
+        let reserved = (size as usize).min(buf.len() / 8);
+        let mut names = Vec::with_capacity(reserved);
+        let mut entries = Vec::with_capacity(reserved);
+        for _ in 0..size {
+            let name_offset = buf.parse_u32()?;
             let stream_id = StreamIndex(buf.parse_u32()? as u16);
-            let name = ParseBuffer::from(&names_buf[name_offset..]).parse_cstring()?;
+            // A name missing its terminator extends to the end of the name data, so that a single
+            // damaged name does not prevent reading the others.
+            let name = match names_buf.get(name_offset as usize..) {
+                Some(data) => {
+                    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+                    RawString::from(&data[..end])
+                }
+                None => return Err(Error::UnexpectedEof),
+            };
+
             names.push(StreamName { name, stream_id });
+            entries.push(NameTableEntry {
+                bucket: buckets.next().unwrap_or(u32::MAX),
+                name_offset,
+                stream_id,
+            });
+        }
+
+        let layout = NameTableLayout {
+            capacity,
+            present,
+            deleted,
+            entries,
+        };
+
+        Ok(StreamNames { names, layout })
+    }
+
+    /// Returns the feature codes recorded after the named stream map.
+    ///
+    /// Feature codes indicate which toolchain wrote the PDB and which optional formats it uses,
+    /// for example whether the PDB was produced with `/DEBUG:FASTLINK` and therefore refers to
+    /// debug information stored in the object files. See [`PdbFeatures`] for more information.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let features = pdb.pdb_information()?.features()?;
+    ///
+    /// assert!(features.has_id_stream());
+    /// assert!(!features.minimal_debug_info());
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn features(&self) -> Result<PdbFeatures> {
+        let mut buf = self.stream.parse_buffer();
+
+        // skip the name data and the hash table of the named stream map
+        buf.take(self.names_offset + self.names_size)?;
+        let size = buf.parse_u32()? as usize;
+        let _capacity = buf.parse_u32()?;
+        parse_bit_array(&mut buf)?;
+        parse_bit_array(&mut buf)?;
+        buf.take(size * 2 * mem::size_of::<u32>())?;
+
+        // the remainder of the stream is a list of feature codes, which the Microsoft toolchain
+        // precedes with a zero word
+        let mut codes = Vec::with_capacity(buf.len() / mem::size_of::<u32>());
+        while buf.len() >= mem::size_of::<u32>() {
+            match buf.parse_u32()? {
+                0 => continue,
+                code => codes.push(code),
+            }
+        }
+
+        Ok(PdbFeatures { codes })
+    }
+}
+
+/// Identifies a PDB file independently of its format version.
+///
+/// PDB 7.0 files are identified by a GUID and an age, while PDB 2.0 files are identified by a
+/// 32-bit signature and an age. The age is the one recorded in the image, which equals the age of
+/// the debug information stream of the matching PDB.
+///
+/// The `Display` implementation produces the key used by symbol servers: the GUID in uppercase
+/// hexadecimal without separators followed by the age in uppercase hexadecimal, or the signature as
+/// eight uppercase hexadecimal digits followed by the age in lowercase hexadecimal.
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(file)?;
+///
+/// let identifier = pdb.identifier()?;
+/// assert_eq!(identifier.to_string(), "2B3C3FA55A2E44B88BBAC3300FF69F621");
+/// assert!(pdb.matches_identifier(&identifier)?);
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum PdbIdentifier {
+    /// Identifies a PDB 7.0 file.
+    Guid {
+        /// The GUID of the PDB.
+        guid: Uuid,
+        /// The age of the PDB.
+        age: u32,
+    },
+    /// Identifies a PDB 2.0 file.
+    Signature {
+        /// The signature of the PDB, which is a timestamp.
+        signature: u32,
+        /// The age of the PDB.
+        age: u32,
+    },
+}
+
+impl PdbIdentifier {
+    /// Returns the age of the PDB.
+    pub fn age(&self) -> u32 {
+        match *self {
+            Self::Guid { age, .. } | Self::Signature { age, .. } => age,
+        }
+    }
+}
+
+impl fmt::Display for PdbIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Guid { guid, age } => write!(f, "{:X}{:X}", guid.simple(), age),
+            Self::Signature { signature, age } => write!(f, "{:08X}{:x}", signature, age),
         }
+    }
+}
+
+impl From<CodeViewRecord<'_>> for PdbIdentifier {
+    fn from(record: CodeViewRecord<'_>) -> Self {
+        match record {
+            CodeViewRecord::Rsds { guid, age, .. } => Self::Guid { guid, age },
+            CodeViewRecord::Nb10 { signature, age, .. } => Self::Signature { signature, age },
+        }
+    }
+}
+
+/// A feature code indicating that the PDB was written by Visual C++ 11.0 (Visual Studio 2012) or
+/// later.
+const FEATURE_VC110: u32 = 20_091_201;
+/// A feature code indicating that the PDB was written by Visual C++ 14.0 (Visual Studio 2015) or
+/// later, and contains an IPI stream.
+const FEATURE_VC140: u32 = 20_140_508;
+/// A feature code indicating that types have not been merged into the TPI stream.
+const FEATURE_NO_TYPE_MERGE: u32 = 0x4D54_4F4E; // "MTON"
+/// A feature code indicating that the PDB was linked with `/DEBUG:FASTLINK`.
+const FEATURE_MINIMAL_DEBUG_INFO: u32 = 0x494E_494D; // "MINI"
+
+/// The feature codes of a PDB, as recorded in the PDB information stream.
+///
+/// Returned by [`PDBInformation::features`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PdbFeatures {
+    codes: Vec<u32>,
+}
+
+impl PdbFeatures {
+    /// Returns whether the PDB was written by Visual C++ 11.0 or later.
+    pub fn vc110(&self) -> bool {
+        self.codes.contains(&FEATURE_VC110)
+    }
+
+    /// Returns whether the PDB was written by Visual C++ 14.0 or later.
+    pub fn vc140(&self) -> bool {
+        self.codes.contains(&FEATURE_VC140)
+    }
+
+    /// Returns whether the PDB is expected to contain an IPI stream.
+    ///
+    /// This is indicated by the Visual C++ 14.0 feature code.
+    pub fn has_id_stream(&self) -> bool {
+        self.vc140()
+    }
+
+    /// Returns whether type records have not been merged into the TPI stream.
+    pub fn no_type_merge(&self) -> bool {
+        self.codes.contains(&FEATURE_NO_TYPE_MERGE)
+    }
+
+    /// Returns whether the PDB was linked with `/DEBUG:FASTLINK`.
+    ///
+    /// Such PDBs only contain minimal debug information, and refer to the object files for type
+    /// information and symbols.
+    pub fn minimal_debug_info(&self) -> bool {
+        self.codes.contains(&FEATURE_MINIMAL_DEBUG_INFO)
+    }
+
+    /// Returns all feature codes in the order they are stored, including unknown ones.
+    pub fn codes(&self) -> &[u32] {
+        &self.codes
+    }
+}
 
-        Ok(StreamNames { names })
+/// Parses a bit array consisting of a 32-bit word count followed by that many words.
+pub(crate) fn parse_bit_array(buf: &mut ParseBuffer<'_>) -> Result<Vec<u32>> {
+    let words = buf.parse_u32()? as usize;
+    if words > buf.len() / mem::size_of::<u32>() {
+        return Err(Error::UnexpectedEof);
     }
+
+    (0..words).map(|_| buf.parse_u32()).collect()
 }
 
 /// A named stream contained within the PDB file.
@@ -154,14 +389,32 @@ pub struct StreamName<'n> {
     pub stream_id: StreamIndex,
 }
 
+impl<'n> StreamName<'n> {
+    /// Returns the raw bytes of the name.
+    ///
+    /// Stream names are not guaranteed to be valid UTF-8, so this is the exact name to use when
+    /// looking up or rewriting streams.
+    #[inline]
+    pub fn raw_name(&self) -> &'n [u8] {
+        self.name.as_bytes()
+    }
+
+    /// Returns the name as a string, replacing invalid UTF-8 sequences with `U+FFFD`.
+    #[inline]
+    pub fn name_lossy(&self) -> Cow<'n, str> {
+        self.name.to_string()
+    }
+}
+
 /// A list of named streams contained within the PDB file.
 ///
 /// Call [`StreamNames::iter`] to iterate over the names. The iterator produces [`StreamName`]
-/// objects.
+/// objects. Individual streams can be looked up using [`StreamNames::get`].
 #[derive(Debug)]
 pub struct StreamNames<'s> {
     /// The list of streams and their names.
     names: Vec<StreamName<'s>>,
+    layout: NameTableLayout,
 }
 
 /// An iterator over [`StreamName`]s.
@@ -173,6 +426,49 @@ impl<'s> StreamNames<'s> {
     pub fn iter(&self) -> NameIter<'_, 's> {
         self.names.iter()
     }
+
+    /// Returns the number of named streams.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns whether there are no named streams.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Looks up the index of the stream with the given name.
+    ///
+    /// Names are compared byte by byte, which is how the Microsoft tools compare them as well.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let info = pdb.pdb_information()?;
+    /// let names = info.stream_names()?;
+    ///
+    /// assert!(names.get(b"/LinkInfo").is_some());
+    /// assert!(names.get(b"/TMCache").is_none());
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn get(&self, name: &[u8]) -> Option<StreamIndex> {
+        self.names
+            .iter()
+            .find(|stream_name| stream_name.name.as_bytes() == name)
+            .map(|stream_name| stream_name.stream_id)
+    }
+
+    /// Returns the raw layout of the hash table storing the names.
+    pub fn layout(&self) -> &NameTableLayout {
+        &self.layout
+    }
 }
 
 impl<'a, 's> IntoIterator for &'a StreamNames<'s> {
@@ -184,3 +480,284 @@ impl<'a, 's> IntoIterator for &'a StreamNames<'s> {
         self.names.iter()
     }
 }
+
+/// The on-disk layout of the hash table mapping stream names to stream indices.
+///
+/// The table consists of `capacity` buckets. Two bit arrays mark which buckets are occupied and
+/// which have been deleted, and one entry is stored for every occupied bucket. This is mostly of
+/// interest for tools that inspect or rewrite the PDB information stream.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NameTableLayout {
+    capacity: u32,
+    present: Vec<u32>,
+    deleted: Vec<u32>,
+    entries: Vec<NameTableEntry>,
+}
+
+impl NameTableLayout {
+    /// Returns the number of buckets in the hash table.
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Returns the words of the bit array marking occupied buckets.
+    pub fn present_words(&self) -> &[u32] {
+        &self.present
+    }
+
+    /// Returns the words of the bit array marking deleted buckets.
+    pub fn deleted_words(&self) -> &[u32] {
+        &self.deleted
+    }
+
+    /// Returns the entries of all occupied buckets, in the order of their buckets.
+    pub fn entries(&self) -> &[NameTableEntry] {
+        &self.entries
+    }
+}
+
+/// An entry of the hash table mapping stream names to stream indices.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NameTableEntry {
+    /// The bucket holding this entry.
+    ///
+    /// This is `u32::MAX` if the table holds more entries than there are occupied buckets.
+    pub bucket: u32,
+    /// The offset of the name within the name data.
+    pub name_offset: u32,
+    /// The index of the named stream.
+    pub stream_id: StreamIndex,
+}
+
+/// Initial number of buckets of the named stream map, as used by the Microsoft toolchain.
+const NAME_TABLE_INITIAL_CAPACITY: u32 = 1;
+
+/// Computes the hash of a stream name used to place it in the named stream map.
+///
+/// This is `LHashPbCb` from the Microsoft PDB sources, truncated to 16 bits.
+fn hash_stream_name(name: &[u8]) -> u32 {
+    hash_v1(name) & 0xffff
+}
+
+/// Inserts a name into the first free bucket of the named stream map using linear probing.
+fn insert_name_bucket(
+    buckets: &mut [Option<usize>],
+    names: &[(Vec<u8>, StreamIndex)],
+    index: usize,
+) {
+    let capacity = buckets.len() as u32;
+    let mut bucket = hash_stream_name(&names[index].0) % capacity;
+    while buckets[bucket as usize].is_some() {
+        bucket = (bucket + 1) % capacity;
+    }
+    buckets[bucket as usize] = Some(index);
+}
+
+/// Builds a PDB information stream.
+///
+/// The writer holds the header fields, the named stream map and the feature codes of a PDB
+/// information stream. Start from an existing stream using [`PdbInfoWriter::from_information`] to
+/// stamp a PDB with a new identity, or from scratch using [`PdbInfoWriter::new`]. The resulting
+/// stream can be stored as stream 1 of a PDB, for instance using an
+/// [`MsfEditor`].
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(file)?;
+/// let info = pdb.pdb_information()?;
+///
+/// let mut writer = pdb::PdbInfoWriter::from_information(&info)?;
+/// writer.set_age(info.age + 1);
+///
+/// let mut data = Vec::new();
+/// writer.write(&mut data)?;
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Clone, Debug)]
+pub struct PdbInfoWriter {
+    version: HeaderVersion,
+    signature: u32,
+    age: u32,
+    guid: Uuid,
+    names: Vec<(Vec<u8>, StreamIndex)>,
+    features: Vec<u32>,
+}
+
+impl PdbInfoWriter {
+    /// Creates a writer for a Visual C++ 7.0 information stream with a nil GUID, age 1 and no
+    /// named streams.
+    ///
+    /// The stream declares the `VC140` feature, which indicates that the PDB contains an IPI
+    /// stream.
+    pub fn new() -> Self {
+        Self {
+            version: HeaderVersion::V70,
+            signature: 0,
+            age: 1,
+            guid: Uuid::nil(),
+            names: Vec::new(),
+            features: vec![FEATURE_VC140],
+        }
+    }
+
+    /// Creates a writer initialized with the contents of an existing information stream.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the named stream map or the feature codes cannot be parsed
+    pub fn from_information(info: &PDBInformation<'_>) -> Result<Self> {
+        let names = info.stream_names()?;
+
+        // keep the original order of the name data so that unchanged streams round-trip
+        let mut entries: Vec<_> = names.layout().entries().iter().zip(&names).collect();
+        entries.sort_by_key(|(entry, _)| entry.name_offset);
+
+        Ok(Self {
+            version: info.version,
+            signature: info.signature,
+            age: info.age,
+            guid: info.guid,
+            names: entries
+                .into_iter()
+                .map(|(_, name)| (name.name.as_bytes().to_vec(), name.stream_id))
+                .collect(),
+            features: info.features()?.codes().to_vec(),
+        })
+    }
+
+    /// Sets the version of the PDB format.
+    ///
+    /// The GUID is only written for versions later than Visual C++ 7.0 beta.
+    pub fn set_version(&mut self, version: HeaderVersion) -> &mut Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets the 32-bit timestamp of the PDB.
+    pub fn set_signature(&mut self, signature: u32) -> &mut Self {
+        self.signature = signature;
+        self
+    }
+
+    /// Sets the number of times the PDB has been written.
+    pub fn set_age(&mut self, age: u32) -> &mut Self {
+        self.age = age;
+        self
+    }
+
+    /// Sets the GUID identifying the PDB.
+    pub fn set_guid(&mut self, guid: Uuid) -> &mut Self {
+        self.guid = guid;
+        self
+    }
+
+    /// Maps the given name to a stream, replacing any previous stream with that name.
+    pub fn set_stream_name(&mut self, name: &[u8], stream: StreamIndex) -> &mut Self {
+        match self.names.iter_mut().find(|(n, _)| n == name) {
+            Some(entry) => entry.1 = stream,
+            None => self.names.push((name.to_vec(), stream)),
+        }
+        self
+    }
+
+    /// Removes the given name from the named stream map and returns the stream it referred to.
+    pub fn remove_stream_name(&mut self, name: &[u8]) -> Option<StreamIndex> {
+        let index = self.names.iter().position(|(n, _)| n == name)?;
+        Some(self.names.remove(index).1)
+    }
+
+    /// Sets the feature codes written after the named stream map.
+    pub fn set_features(&mut self, features: Vec<u32>) -> &mut Self {
+        self.features = features;
+        self
+    }
+
+    /// Writes the information stream.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Write`
+    pub fn write<W: std::io::Write>(&self, mut writer: W) -> Result<()> {
+        let raw_version = u32::from(self.version);
+        let mut data = Vec::new();
+        data.extend_from_slice(&raw_version.to_le_bytes());
+        data.extend_from_slice(&self.signature.to_le_bytes());
+        data.extend_from_slice(&self.age.to_le_bytes());
+        if raw_version > IMPV_VC70_DEP {
+            let (d1, d2, d3, d4) = self.guid.as_fields();
+            data.extend_from_slice(&d1.to_le_bytes());
+            data.extend_from_slice(&d2.to_le_bytes());
+            data.extend_from_slice(&d3.to_le_bytes());
+            data.extend_from_slice(d4);
+        }
+
+        // name data, followed by the hash table mapping name offsets to streams
+        let mut names = Vec::new();
+        let mut offsets = Vec::with_capacity(self.names.len());
+        for (name, _) in &self.names {
+            offsets.push(names.len() as u32);
+            names.extend_from_slice(name);
+            names.push(0);
+        }
+        data.extend_from_slice(&(names.len() as u32).to_le_bytes());
+        data.extend_from_slice(&names);
+
+        // insert the names and grow the table the same way as the Microsoft toolchain does, so
+        // that unchanged maps are written identically
+        let mut buckets = vec![None; NAME_TABLE_INITIAL_CAPACITY as usize];
+        for index in 0..self.names.len() {
+            insert_name_bucket(&mut buckets, &self.names, index);
+
+            let capacity = buckets.len() as u32;
+            let max_load = capacity * 2 / 3 + 1;
+            if index as u32 + 1 >= max_load {
+                let old = mem::replace(&mut buckets, vec![None; max_load as usize * 2]);
+                for index in old.into_iter().flatten() {
+                    insert_name_bucket(&mut buckets, &self.names, index);
+                }
+            }
+        }
+        let capacity = buckets.len() as u32;
+
+        let mut present = vec![0u32; (capacity as usize).div_ceil(32)];
+        for (bucket, _) in buckets.iter().enumerate().filter(|(_, b)| b.is_some()) {
+            present[bucket / 32] |= 1 << (bucket % 32);
+        }
+        while present.last() == Some(&0) {
+            present.pop();
+        }
+
+        data.extend_from_slice(&(self.names.len() as u32).to_le_bytes());
+        data.extend_from_slice(&capacity.to_le_bytes());
+        data.extend_from_slice(&(present.len() as u32).to_le_bytes());
+        for word in &present {
+            data.extend_from_slice(&word.to_le_bytes());
+        }
+        // no deleted buckets
+        data.extend_from_slice(&0u32.to_le_bytes());
+        for index in buckets.iter().flatten() {
+            data.extend_from_slice(&offsets[*index].to_le_bytes());
+            data.extend_from_slice(&u32::from(self.names[*index].1 .0).to_le_bytes());
+        }
+
+        // the feature codes are preceded by a zero word, as written by the Microsoft toolchain
+        data.extend_from_slice(&0u32.to_le_bytes());
+        for code in &self.features {
+            data.extend_from_slice(&code.to_le_bytes());
+        }
+
+        writer.write_all(&data)?;
+        Ok(())
+    }
+}
+
+impl Default for PdbInfoWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -9,10 +9,12 @@
 
 // PDBs contain PE section headers in one or two streams. `pdb::pe` is responsible for parsing them.
 
+use std::convert::TryInto;
 use std::fmt;
 
 use scroll::ctx::TryFromCtx;
 use scroll::Endian;
+use uuid::Uuid;
 
 use crate::common::*;
 
@@ -341,6 +343,32 @@ pub struct ImageSectionHeader {
     pub characteristics: SectionCharacteristics,
 }
 
+/// A fixup applied to the image, as stored in the fixup debug stream (`XFIXUP_DATA`).
+///
+/// Use [`PDB::fixups`](crate::PDB::fixups) to read all fixups of an image.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ImageFixup {
+    /// The raw type of the fixup.
+    pub kind: u16,
+    /// Additional data depending on the type of the fixup.
+    pub extra: u16,
+    /// The address of the fixed up data.
+    pub rva: Rva,
+    /// The address the fixed up data refers to.
+    pub target: Rva,
+}
+
+impl ImageFixup {
+    pub(crate) fn parse(parse_buffer: &mut ParseBuffer<'_>) -> Result<Self> {
+        Ok(Self {
+            kind: parse_buffer.parse_u16()?,
+            extra: parse_buffer.parse_u16()?,
+            rva: Rva(parse_buffer.parse_u32()?),
+            target: Rva(parse_buffer.parse_u32()?),
+        })
+    }
+}
+
 impl ImageSectionHeader {
     pub(crate) fn parse(parse_buffer: &mut ParseBuffer<'_>) -> Result<Self> {
         let name_bytes = parse_buffer.take(8)?;
@@ -411,6 +439,112 @@ impl fmt::Debug for ImageSectionHeader {
     }
 }
 
+/// A CodeView record from the debug directory of a PE image, which identifies the matching PDB.
+///
+/// The record is stored in an `IMAGE_DEBUG_DIRECTORY` entry of type `IMAGE_DEBUG_TYPE_CODEVIEW`.
+/// Images linked against PDB 7.0 files carry an `RSDS` record with a GUID, while images linked
+/// against PDB 2.0 files carry an `NB10` record with a 32-bit signature. Both also contain the age
+/// of the PDB and its path at link time.
+///
+/// Use [`PDB::matches_codeview`](crate::PDB::matches_codeview) to check whether a PDB belongs to
+/// an image.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CodeViewRecord<'a> {
+    /// An `RSDS` record referring to a PDB 7.0 file.
+    Rsds {
+        /// The GUID of the PDB.
+        guid: Uuid,
+        /// The age of the PDB.
+        age: u32,
+        /// The path of the PDB at link time.
+        path: RawString<'a>,
+    },
+    /// An `NB10` record referring to a PDB 2.0 file.
+    Nb10 {
+        /// The signature of the PDB, which is a timestamp.
+        signature: u32,
+        /// The age of the PDB.
+        age: u32,
+        /// The path of the PDB at link time.
+        path: RawString<'a>,
+    },
+}
+
+impl<'a> CodeViewRecord<'a> {
+    /// Parses a CodeView record from the raw data of a debug directory entry.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnrecognizedCodeViewRecord` if the record has an unknown signature
+    /// * `Error::UnexpectedEof` if the record is truncated
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let mut data = b"RSDS".to_vec();
+    /// data.extend_from_slice(&[0xa5, 0x3f, 0x3c, 0x2b, 0x2e, 0x5a, 0xb8, 0x44]);
+    /// data.extend_from_slice(&[0x8b, 0xba, 0xc3, 0x30, 0x0f, 0xf6, 0x9f, 0x62]);
+    /// data.extend_from_slice(&1u32.to_le_bytes());
+    /// data.extend_from_slice(b"foo.pdb\0");
+    ///
+    /// let record = pdb::CodeViewRecord::parse(&data)?;
+    /// assert_eq!(record.age(), 1);
+    /// assert_eq!(record.path().to_string(), "foo.pdb");
+    ///
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// assert!(pdb.matches_codeview(&record)?);
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        let mut buf = ParseBuffer::from(data);
+        let signature = buf.take(4)?;
+
+        match signature {
+            b"RSDS" => Ok(Self::Rsds {
+                guid: Uuid::from_fields(
+                    buf.parse_u32()?,
+                    buf.parse_u16()?,
+                    buf.parse_u16()?,
+                    buf.take(8)?.try_into().unwrap(),
+                ),
+                age: buf.parse_u32()?,
+                path: buf.parse_cstring()?,
+            }),
+            b"NB10" => {
+                // the offset of the debug information, which is always zero for PDBs
+                buf.parse_u32()?;
+                Ok(Self::Nb10 {
+                    signature: buf.parse_u32()?,
+                    age: buf.parse_u32()?,
+                    path: buf.parse_cstring()?,
+                })
+            }
+            _ => Err(Error::UnrecognizedCodeViewRecord(
+                signature.try_into().unwrap(),
+            )),
+        }
+    }
+
+    /// Returns the age of the PDB.
+    pub fn age(&self) -> u32 {
+        match *self {
+            Self::Rsds { age, .. } | Self::Nb10 { age, .. } => age,
+        }
+    }
+
+    /// Returns the path of the PDB at link time.
+    pub fn path(&self) -> RawString<'a> {
+        match *self {
+            Self::Rsds { path, .. } | Self::Nb10 { path, .. } => path,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -468,4 +602,35 @@ mod tests {
         assert_eq!(ish.number_of_line_numbers, 0);
         assert_eq!(ish.characteristics, SectionCharacteristics(0xc800_0040));
     }
+
+    #[test]
+    fn test_codeview_nb10() {
+        let mut data = b"NB10".to_vec();
+        for value in [0u32, 0x3a2b_1c0d, 7] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(b"C:\\old.pdb\0");
+
+        let record = CodeViewRecord::parse(&data).expect("parse");
+        assert_eq!(
+            record,
+            CodeViewRecord::Nb10 {
+                signature: 0x3a2b_1c0d,
+                age: 7,
+                path: RawString::from("C:\\old.pdb"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_codeview_invalid() {
+        assert!(matches!(
+            CodeViewRecord::parse(b"NB09\0\0\0\0"),
+            Err(Error::UnrecognizedCodeViewRecord(signature)) if &signature == b"NB09"
+        ));
+        assert!(matches!(
+            CodeViewRecord::parse(b"RSDS\0\0"),
+            Err(Error::UnexpectedEof)
+        ));
+    }
 }
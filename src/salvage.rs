@@ -0,0 +1,599 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Best-effort recovery of streams from PDB files whose stream directory is damaged.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::common::*;
+use crate::dbi::DebugInformation;
+use crate::modi::constants::{CV_SIGNATURE_C13, DEBUG_S_IGNORE};
+use crate::modi::ModuleInfo;
+use crate::msf::{self, Stream, StreamLayout};
+use crate::pdbi::PDBInformation;
+use crate::source::Source;
+use crate::symbol::constants::{S_COMPILE2, S_COMPILE3, S_OBJNAME};
+use crate::tpi::constants::{LF_FUNC_ID, LF_UDT_MOD_SRC_LINE};
+use crate::tpi::{IdInformation, TypeInformation};
+
+/// The page size assumed if the MSF header is unreadable.
+const DEFAULT_PAGE_SIZE: u32 = 4096;
+
+/// The size of the DBI stream header.
+const DBI_HEADER_SIZE: usize = 64;
+
+/// The size of the TPI and IPI stream headers.
+const TPI_HEADER_SIZE: u32 = 56;
+
+/// The first type index of the TPI and IPI streams.
+const TPI_MINIMUM_INDEX: u32 = 0x1000;
+
+/// The kind of a stream recovered by [`Salvage`], as identified by its contents.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SalvagedStreamKind {
+    /// The PDB information stream.
+    PdbInformation,
+    /// The type information (TPI) stream.
+    TypeInformation,
+    /// The id information (IPI) stream.
+    IdInformation,
+    /// The debug information (DBI) stream.
+    DebugInformation,
+    /// A module info stream.
+    ModuleInfo {
+        /// The size of the symbol records, including the leading signature.
+        symbols_size: u32,
+        /// The size of the C13 line information following the symbol records.
+        c13_lines_size: u32,
+    },
+}
+
+/// A stream recovered by [`Salvage`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SalvagedStream {
+    kind: SalvagedStreamKind,
+    layout: StreamLayout,
+}
+
+impl SalvagedStream {
+    /// Returns the kind of the stream.
+    pub fn kind(&self) -> SalvagedStreamKind {
+        self.kind
+    }
+
+    /// Returns the pages presumed to store the stream.
+    pub fn layout(&self) -> &StreamLayout {
+        &self.layout
+    }
+}
+
+/// Recovers streams from a PDB file by scanning its pages, without using the stream directory.
+///
+/// PDB files taken from crash archives are frequently truncated or damaged, which leaves their
+/// stream directory unreadable and [`PDB::open`](crate::PDB::open) unable to open them.
+/// `Salvage` instead reads every page of the file and looks for the headers of well-known
+/// streams: the PDB information stream, the type and id information streams, the debug
+/// information stream and module info streams.
+///
+/// The recovered streams are assumed to continue in the pages following their first page, skipping
+/// the pages of the free page map. This matches how linkers usually lay out PDBs, but is not
+/// guaranteed, so the contents of recovered streams may be incomplete or contain unrelated data.
+/// Streams that extend past the end of a truncated file are cut short. Stream indices cannot be
+/// recovered, so streams referenced by index, such as the symbol records of the global symbol
+/// table, are not available.
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let mut salvage = pdb::Salvage::scan(file)?;
+///
+/// let streams = salvage.streams().to_vec();
+/// for stream in &streams {
+///     if stream.kind() == pdb::SalvagedStreamKind::TypeInformation {
+///         let type_information = salvage.type_information(stream)?;
+///         println!("recovered {} types", type_information.len());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+pub struct Salvage<'s, S> {
+    source: S,
+    page_size: u32,
+    page_count: u32,
+    streams: Vec<SalvagedStream>,
+    _phantom: PhantomData<&'s ()>,
+}
+
+impl<'s, S: Source<'s> + 's> Salvage<'s, S> {
+    /// Scans a `Source` for recognizable streams.
+    ///
+    /// The page size is taken from the MSF header if it is intact, and assumed to be 4096 bytes
+    /// otherwise.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source`
+    pub fn scan(mut source: S) -> Result<Self> {
+        // the header fits into the smallest possible page
+        let page_size = match read_pages(&mut source, 0x200, &[0])? {
+            Some(header) => msf::header_page_size(header.as_slice()).unwrap_or(DEFAULT_PAGE_SIZE),
+            None => DEFAULT_PAGE_SIZE,
+        };
+
+        Self::scan_with_page_size(source, page_size)
+    }
+
+    /// Scans a `Source` for recognizable streams, assuming the given page size.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidPageSize` if the page size is not a power of two of at least 512 bytes
+    /// * `Error::IoError` if returned by the `Source`
+    pub fn scan_with_page_size(mut source: S, page_size: u32) -> Result<Self> {
+        if !page_size.is_power_of_two() || page_size < 0x200 {
+            return Err(Error::InvalidPageSize(page_size));
+        }
+
+        // identify streams by the contents of their first page
+        let mut candidates = Vec::new();
+        let mut page_count = 1;
+        loop {
+            if !msf::is_free_page_map(page_size.into(), page_count.into()) {
+                match read_pages(&mut source, page_size, &[page_count])? {
+                    Some(page) => {
+                        if let Some(candidate) = identify(page.as_slice()) {
+                            candidates.push((page_count, candidate));
+                        }
+                    }
+                    None => break,
+                }
+            }
+            page_count += 1;
+        }
+
+        // candidates are sorted by their first page, so their pages are a suffix of all pages
+        let all_pages: Vec<u32> = (1..page_count)
+            .filter(|&page| !msf::is_free_page_map(page_size.into(), page.into()))
+            .collect();
+
+        let mut streams = Vec::with_capacity(candidates.len());
+        for (index, &(first_page, candidate)) in candidates.iter().enumerate() {
+            let pages = match all_pages.binary_search(&first_page) {
+                Ok(start) => &all_pages[start..],
+                Err(_) => &[],
+            };
+
+            let (kind, size) = match candidate {
+                Candidate::Sized(kind, size) => (kind, size),
+                Candidate::Module => {
+                    // module streams do not declare their size, so walk their contents up to the
+                    // next recognized stream
+                    let limit = candidates
+                        .get(index + 1)
+                        .map_or(page_count, |&(page, _)| page);
+                    let count = pages.iter().take_while(|&&p| p < limit).count();
+                    let data = read_pages(&mut source, page_size, &pages[..count])?;
+                    let data = data.as_ref().map_or(&[][..], |data| data.as_slice());
+
+                    let symbols_size = module_symbols_size(data);
+                    let c13_lines_size = module_c13_lines_size(&data[symbols_size..]);
+                    let kind = SalvagedStreamKind::ModuleInfo {
+                        symbols_size: symbols_size as u32,
+                        c13_lines_size: c13_lines_size as u32,
+                    };
+                    (kind, symbols_size + c13_lines_size)
+                }
+            };
+
+            // streams cannot extend past the end of the file
+            let size = size.min(pages.len() * page_size as usize);
+            let pages = pages[..size.div_ceil(page_size as usize)].to_vec();
+            streams.push(SalvagedStream {
+                kind,
+                layout: StreamLayout::new(page_size, size as u32, pages),
+            });
+        }
+
+        Ok(Salvage {
+            source,
+            page_size,
+            page_count,
+            streams,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Returns the page size used for scanning.
+    pub fn page_size(&self) -> u32 {
+        self.page_size
+    }
+
+    /// Returns the number of pages found in the `Source`.
+    pub fn page_count(&self) -> u32 {
+        self.page_count
+    }
+
+    /// Returns the recovered streams, ordered by their first page.
+    pub fn streams(&self) -> &[SalvagedStream] {
+        &self.streams
+    }
+
+    /// Reads the contents of a recovered stream.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source`
+    pub fn raw_stream(&mut self, stream: &SalvagedStream) -> Result<Stream<'s>> {
+        msf::view_layout(&mut self.source, &stream.layout)
+    }
+
+    /// Parses a recovered PDB information stream.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::UnexpectedEof` if the stream is not a complete PDB information stream
+    pub fn pdb_information(&mut self, stream: &SalvagedStream) -> Result<PDBInformation<'s>> {
+        PDBInformation::parse(self.raw_stream(stream)?)
+    }
+
+    /// Parses a recovered type information stream.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::UnexpectedEof` if the stream is not a complete type information stream
+    pub fn type_information(&mut self, stream: &SalvagedStream) -> Result<TypeInformation<'s>> {
+        TypeInformation::parse(self.raw_stream(stream)?)
+    }
+
+    /// Parses a recovered id information stream.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::UnexpectedEof` if the stream is not a complete id information stream
+    pub fn id_information(&mut self, stream: &SalvagedStream) -> Result<IdInformation<'s>> {
+        IdInformation::parse(self.raw_stream(stream)?)
+    }
+
+    /// Parses a recovered debug information stream.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source`
+    /// * `Error::UnexpectedEof` if the stream is not a complete debug information stream
+    pub fn debug_information(&mut self, stream: &SalvagedStream) -> Result<DebugInformation<'s>> {
+        DebugInformation::parse(self.raw_stream(stream)?)
+    }
+
+    /// Parses a recovered module info stream.
+    ///
+    /// Returns `None` if the stream was not identified as a module info stream.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Source`
+    pub fn module_info(&mut self, stream: &SalvagedStream) -> Result<Option<ModuleInfo<'s>>> {
+        match stream.kind {
+            SalvagedStreamKind::ModuleInfo {
+                symbols_size,
+                c13_lines_size,
+            } => Ok(Some(ModuleInfo::with_c13_lines(
+                self.raw_stream(stream)?,
+                symbols_size as usize,
+                c13_lines_size as usize,
+            ))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Unwraps this `Salvage`, returning the underlying `Source`.
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+}
+
+impl<S> fmt::Debug for Salvage<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Salvage")
+            .field("page_size", &self.page_size)
+            .field("page_count", &self.page_count)
+            .field("streams", &self.streams)
+            .finish()
+    }
+}
+
+/// A stream identified by its first page.
+#[derive(Clone, Copy, Debug)]
+enum Candidate {
+    /// A stream which declares its size in its header.
+    Sized(SalvagedStreamKind, usize),
+    /// A module info stream, whose size must be determined from its contents.
+    Module,
+}
+
+/// Reads the given pages, returning `None` if they extend past the end of the `Source`.
+fn read_pages<'s>(
+    source: &mut dyn Source<'s>,
+    page_size: u32,
+    pages: &[u32],
+) -> Result<Option<Stream<'s>>> {
+    let size = (page_size as usize)
+        .checked_mul(pages.len())
+        .and_then(|size| u32::try_from(size).ok())
+        .ok_or(Error::MsfLimitExceeded("stream is too large"))?;
+
+    let layout = StreamLayout::new(page_size, size, pages.to_vec());
+    match msf::view_layout(source, &layout) {
+        Ok(stream) => Ok(Some(stream)),
+        Err(Error::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Identifies a stream by the header at the start of its first page.
+fn identify(data: &[u8]) -> Option<Candidate> {
+    identify_debug_information(data)
+        .or_else(|| identify_type_information(data))
+        .or_else(|| identify_pdb_information(data))
+        .or_else(|| identify_module_info(data))
+}
+
+#[allow(clippy::inconsistent_digit_grouping)]
+fn identify_debug_information(data: &[u8]) -> Option<Candidate> {
+    let mut buf = ParseBuffer::from(data);
+    if buf.parse_u32().ok()? != u32::MAX {
+        return None;
+    }
+
+    match buf.parse_u32().ok()? {
+        93_08_03 | 1996_03_07 | 1997_06_06 | 1999_09_03 | 2009_12_01 => (),
+        _ => return None,
+    }
+
+    // module list, section contributions, section map, file info, type server map
+    buf.seek(24);
+    let mut size = DBI_HEADER_SIZE;
+    for _ in 0..5 {
+        size = size.checked_add(buf.parse_u32().ok()? as usize)?;
+    }
+
+    // skip the MFC type server index, then add the debug header and EC substream
+    buf.parse_u32().ok()?;
+    for _ in 0..2 {
+        size = size.checked_add(buf.parse_u32().ok()? as usize)?;
+    }
+
+    Some(Candidate::Sized(SalvagedStreamKind::DebugInformation, size))
+}
+
+#[allow(clippy::inconsistent_digit_grouping)]
+fn identify_type_information(data: &[u8]) -> Option<Candidate> {
+    let mut buf = ParseBuffer::from(data);
+    match buf.parse_u32().ok()? {
+        1995_04_10 | 1995_11_22 | 1996_10_31 | 1999_09_03 | 2004_02_03 => (),
+        _ => return None,
+    }
+
+    let header_size = buf.parse_u32().ok()?;
+    let minimum_index = buf.parse_u32().ok()?;
+    let maximum_index = buf.parse_u32().ok()?;
+    let records_size = buf.parse_u32().ok()?;
+    if header_size != TPI_HEADER_SIZE
+        || minimum_index != TPI_MINIMUM_INDEX
+        || maximum_index < minimum_index
+    {
+        return None;
+    }
+
+    // both streams share the same header, but the id stream only contains id records
+    buf.seek(TPI_HEADER_SIZE as usize);
+    let kind = match buf.parse_u16().and_then(|_| buf.parse_u16()) {
+        Ok(leaf) if (LF_FUNC_ID..=LF_UDT_MOD_SRC_LINE).contains(&leaf) => {
+            SalvagedStreamKind::IdInformation
+        }
+        _ => SalvagedStreamKind::TypeInformation,
+    };
+
+    let size = (header_size as usize).checked_add(records_size as usize)?;
+    Some(Candidate::Sized(kind, size))
+}
+
+#[allow(clippy::inconsistent_digit_grouping)]
+fn identify_pdb_information(data: &[u8]) -> Option<Candidate> {
+    let mut buf = ParseBuffer::from(data);
+    match buf.parse_u32().ok()? {
+        1994_16_10 | 1995_06_23 | 1995_08_14 | 1996_03_07 | 1997_06_04 | 2000_04_04 => (),
+        _ => return None,
+    }
+
+    // signature and age
+    buf.parse_u32().ok()?;
+    if buf.parse_u32().ok()? == 0 {
+        return None;
+    }
+
+    // the size of the stream is not declared, but it rarely exceeds a single page
+    Some(Candidate::Sized(
+        SalvagedStreamKind::PdbInformation,
+        data.len(),
+    ))
+}
+
+fn identify_module_info(data: &[u8]) -> Option<Candidate> {
+    let mut buf = ParseBuffer::from(data);
+    if buf.parse_u32().ok()? != CV_SIGNATURE_C13 {
+        return None;
+    }
+
+    // modules start with the name of their object file or their compiler flags
+    buf.parse_u16().ok()?;
+    match buf.parse_u16().ok()? {
+        S_OBJNAME | S_COMPILE2 | S_COMPILE3 => Some(Candidate::Module),
+        _ => None,
+    }
+}
+
+/// Determines the size of the symbol records at the start of a module info stream, including the
+/// signature, by following the chain of records until it is broken.
+fn module_symbols_size(data: &[u8]) -> usize {
+    let mut buf = ParseBuffer::from(data);
+    if !matches!(buf.parse_u32(), Ok(CV_SIGNATURE_C13)) {
+        return 0;
+    }
+
+    loop {
+        let start = buf.pos();
+        let len = match (buf.parse_u16(), buf.parse_u16()) {
+            // records are aligned to four bytes and their kind is never zero
+            (Ok(len), Ok(kind))
+                if len >= 2 && (usize::from(len) + 2) % 4 == 0 && kind != 0 && kind < 0x2000 =>
+            {
+                usize::from(len)
+            }
+            _ => return start,
+        };
+
+        let end = start + 2 + len;
+        if end > data.len() {
+            return start;
+        }
+        buf.seek(end);
+    }
+}
+
+/// Determines the size of the C13 line information subsections at the start of `data`.
+fn module_c13_lines_size(data: &[u8]) -> usize {
+    let mut buf = ParseBuffer::from(data);
+    loop {
+        let start = buf.pos();
+        let len = match (buf.parse_u32(), buf.parse_u32()) {
+            (Ok(kind), Ok(len)) if (0xf1..=0xfd).contains(&(kind & !DEBUG_S_IGNORE)) => len,
+            _ => return start,
+        };
+
+        match (len as usize).checked_add(buf.pos()) {
+            Some(end) if end <= data.len() => buf.seek((end + 3) & !3),
+            _ => return start,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::msf::MsfWriter;
+
+    fn type_stream(records: &[u8]) -> Vec<u8> {
+        let mut stream = Vec::new();
+        for value in [20_040_203u32, 56, 0x1000, 0x1001, records.len() as u32] {
+            stream.extend_from_slice(&value.to_le_bytes());
+        }
+        stream.resize(56, 0);
+        stream.extend_from_slice(records);
+        stream
+    }
+
+    fn module_stream() -> Vec<u8> {
+        let mut stream = CV_SIGNATURE_C13.to_le_bytes().to_vec();
+        // S_OBJNAME with signature, name "ab.obj" and padding
+        stream.extend_from_slice(&[14, 0, 0x01, 0x11, 0, 0, 0, 0]);
+        stream.extend_from_slice(b"ab.obj\0\0");
+        // DEBUG_S_FILECHKSMS with 8 bytes
+        stream.extend_from_slice(&[0xf4, 0, 0, 0, 8, 0, 0, 0]);
+        stream.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        stream
+    }
+
+    #[test]
+    fn test_scan() {
+        let records = [6, 0, 0x01, 0x16, 0, 0, 0, 0]; // LF_FUNC_ID
+        let ids = type_stream(&records);
+        let types = type_stream(&vec![0; 5000]);
+        let module = module_stream();
+
+        let mut writer = MsfWriter::with_page_size(512).expect("page size");
+        writer.add_stream(Vec::new());
+        writer.add_stream(types.clone());
+        writer.add_stream(module.clone());
+        writer.add_stream(ids.clone());
+
+        let mut file = Vec::new();
+        writer.write(&mut file).expect("write");
+
+        // destroy the header, making the stream directory unreadable
+        file[..512].fill(0xcc);
+
+        let mut salvage =
+            Salvage::scan_with_page_size(Cursor::new(file.as_slice()), 512).expect("scan");
+        let kinds: Vec<_> = salvage.streams().iter().map(|s| s.kind()).collect();
+        assert_eq!(
+            kinds,
+            [
+                SalvagedStreamKind::TypeInformation,
+                SalvagedStreamKind::ModuleInfo {
+                    symbols_size: 20,
+                    c13_lines_size: 16,
+                },
+                SalvagedStreamKind::IdInformation,
+            ]
+        );
+
+        let streams = salvage.streams().to_vec();
+        let data = salvage.raw_stream(&streams[0]).expect("types");
+        assert_eq!(data.as_slice(), types.as_slice());
+        assert_eq!(streams[0].layout().pages().len(), 10);
+
+        let data = salvage.raw_stream(&streams[1]).expect("module");
+        assert_eq!(data.as_slice(), module.as_slice());
+
+        let data = salvage.raw_stream(&streams[2]).expect("ids");
+        assert_eq!(data.as_slice(), ids.as_slice());
+
+        let info = salvage
+            .module_info(&streams[1])
+            .expect("parse")
+            .expect("module");
+        let mut symbols = info.symbols().expect("symbols");
+        assert!(crate::FallibleIterator::next(&mut symbols)
+            .expect("symbol")
+            .is_some());
+        assert!(salvage.module_info(&streams[0]).expect("parse").is_none());
+    }
+
+    #[test]
+    fn test_truncated() {
+        let types = type_stream(&vec![0; 5000]);
+
+        let mut writer = MsfWriter::with_page_size(512).expect("page size");
+        writer.add_stream(Vec::new());
+        writer.add_stream(types.clone());
+
+        let mut file = Vec::new();
+        writer.write(&mut file).expect("write");
+        file.truncate(6 * 512);
+
+        let mut salvage = Salvage::scan(Cursor::new(file.as_slice())).expect("scan");
+        assert_eq!(salvage.page_size(), 512);
+        assert_eq!(salvage.page_count(), 6);
+
+        let streams = salvage.streams().to_vec();
+        assert_eq!(streams.len(), 1);
+        let data = salvage.raw_stream(&streams[0]).expect("types");
+        assert_eq!(data.as_slice(), &types[..3 * 512]);
+    }
+}
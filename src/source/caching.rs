@@ -0,0 +1,293 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+use crate::source::{Source, SourceSlice, SourceView};
+
+/// A `Source` adapter that keeps recently read slices of another `Source` in memory.
+///
+/// The MSF layer requests the same slices whenever the same stream is accessed, for example the
+/// stream table, the type information or the string table. `CachingSource` remembers the contents
+/// of each requested `SourceSlice` up to a configurable budget in bytes and serves repeated
+/// requests without touching the underlying `Source`. When the budget is exceeded, the least
+/// recently used slices are evicted first. Slices larger than the entire budget are never cached.
+///
+/// This is most useful in front of sources with expensive reads, such as files on a network
+/// share or remote files.
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let source = pdb::CachingSource::new(file, 16 * 1024 * 1024);
+///
+/// let pdb = pdb::PDB::open(source)?;
+/// let s = pdb.raw_stream(pdb::StreamIndex(208))?.expect("stream exists");
+/// assert_eq!(s.as_slice(), b"hello world\n");
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+pub struct CachingSource<S> {
+    source: S,
+    budget: usize,
+    cached_bytes: usize,
+    tick: u64,
+    entries: HashMap<SourceSlice, CacheEntry>,
+    recency: BTreeMap<u64, SourceSlice>,
+}
+
+struct CacheEntry {
+    data: Arc<[u8]>,
+    last_used: u64,
+}
+
+impl<S> CachingSource<S> {
+    /// Wraps a `Source`, caching up to `budget` bytes of its contents.
+    pub fn new(source: S, budget: usize) -> Self {
+        Self {
+            source,
+            budget,
+            cached_bytes: 0,
+            tick: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the maximum number of bytes held in the cache.
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// Returns the number of bytes currently held in the cache.
+    pub fn cached_bytes(&self) -> usize {
+        self.cached_bytes
+    }
+
+    /// Discards all cached contents.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+        self.cached_bytes = 0;
+    }
+
+    /// Returns a reference to the underlying `Source`.
+    pub fn get_ref(&self) -> &S {
+        &self.source
+    }
+
+    /// Unwraps this `CachingSource`, returning the underlying `Source`.
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn lookup(&mut self, slice: &SourceSlice) -> Option<Arc<[u8]>> {
+        let tick = self.next_tick();
+        let entry = self.entries.get_mut(slice)?;
+
+        self.recency.remove(&entry.last_used);
+        self.recency.insert(tick, *slice);
+        entry.last_used = tick;
+
+        Some(Arc::clone(&entry.data))
+    }
+
+    fn insert(&mut self, slice: SourceSlice, data: Arc<[u8]>) {
+        if data.len() > self.budget || self.entries.contains_key(&slice) {
+            return;
+        }
+
+        while self.cached_bytes + data.len() > self.budget {
+            let (_, evicted) = match self.recency.pop_first() {
+                Some(oldest) => oldest,
+                None => break,
+            };
+
+            if let Some(entry) = self.entries.remove(&evicted) {
+                self.cached_bytes -= entry.data.len();
+            }
+        }
+
+        let tick = self.next_tick();
+        self.cached_bytes += data.len();
+        self.recency.insert(tick, slice);
+        self.entries.insert(
+            slice,
+            CacheEntry {
+                data,
+                last_used: tick,
+            },
+        );
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for CachingSource<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachingSource")
+            .field("source", &self.source)
+            .field("budget", &self.budget)
+            .field("cached_bytes", &self.cached_bytes)
+            .finish()
+    }
+}
+
+impl<'s, S: Source<'s>> Source<'s> for CachingSource<S> {
+    fn view(&mut self, slices: &[SourceSlice]) -> Result<Box<dyn SourceView<'s> + 's>, io::Error> {
+        let mut parts: Vec<Option<Arc<[u8]>>> = slices.iter().map(|s| self.lookup(s)).collect();
+
+        // fetch everything that was not cached with a single request
+        let missing: Vec<SourceSlice> = slices
+            .iter()
+            .zip(&parts)
+            .filter(|(_, part)| part.is_none())
+            .map(|(slice, _)| *slice)
+            .collect();
+
+        if !missing.is_empty() {
+            let view = self.source.view(&missing)?;
+            let mut bytes = view.as_slice();
+
+            for (slice, part) in slices.iter().zip(&mut parts) {
+                if part.is_none() {
+                    let (data, rest) = bytes.split_at(slice.size);
+                    let data: Arc<[u8]> = Arc::from(data);
+                    self.insert(*slice, Arc::clone(&data));
+                    *part = Some(data);
+                    bytes = rest;
+                }
+            }
+        }
+
+        // a single slice can be shared with the cache without copying
+        if let [Some(data)] = parts.as_slice() {
+            return Ok(Box::new(CachedView {
+                data: Arc::clone(data),
+            }));
+        }
+
+        let len = slices.iter().fold(0, |acc, s| acc + s.size);
+        let mut bytes = Vec::with_capacity(len);
+        for part in parts.iter().flatten() {
+            bytes.extend_from_slice(part);
+        }
+
+        Ok(Box::new(CachedView {
+            data: Arc::from(bytes),
+        }))
+    }
+}
+
+struct CachedView {
+    data: Arc<[u8]>,
+}
+
+impl fmt::Debug for CachedView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CachedView({} bytes)", self.data.len())
+    }
+}
+
+impl SourceView<'_> for CachedView {
+    fn as_slice(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::source::caching::*;
+
+    /// A `Source` counting the number of requested slices.
+    #[derive(Debug)]
+    struct CountingSource {
+        inner: Cursor<Vec<u8>>,
+        requests: usize,
+    }
+
+    impl<'s> Source<'s> for CountingSource {
+        fn view(
+            &mut self,
+            slices: &[SourceSlice],
+        ) -> Result<Box<dyn SourceView<'s> + 's>, io::Error> {
+            self.requests += slices.len();
+            self.inner.view(slices)
+        }
+    }
+
+    fn source(budget: usize) -> CachingSource<CountingSource> {
+        let data = (0..4096u32).map(|i| (i % 251) as u8).collect();
+        let source = CountingSource {
+            inner: Cursor::new(data),
+            requests: 0,
+        };
+        CachingSource::new(source, budget)
+    }
+
+    fn slice(offset: u64, size: usize) -> SourceSlice {
+        SourceSlice { offset, size }
+    }
+
+    #[test]
+    fn test_cache_hits() {
+        let mut source = source(1024);
+
+        let first = source.view(&[slice(0, 100)]).expect("view");
+        let second = source.view(&[slice(0, 100)]).expect("view");
+        assert_eq!(first.as_slice(), second.as_slice());
+        assert_eq!(source.get_ref().requests, 1);
+        assert_eq!(source.cached_bytes(), 100);
+
+        // only the missing slice is requested
+        let view = source.view(&[slice(200, 10), slice(0, 100)]).expect("view");
+        assert_eq!(
+            &view.as_slice()[..10],
+            &[200, 201, 202, 203, 204, 205, 206, 207, 208, 209]
+        );
+        assert_eq!(&view.as_slice()[10..], first.as_slice());
+        assert_eq!(source.get_ref().requests, 2);
+    }
+
+    #[test]
+    fn test_eviction() {
+        let mut source = source(300);
+
+        source.view(&[slice(0, 100)]).expect("view");
+        source.view(&[slice(100, 100)]).expect("view");
+        source.view(&[slice(200, 100)]).expect("view");
+
+        // touch the first slice so the second one is the least recently used
+        source.view(&[slice(0, 100)]).expect("view");
+        assert_eq!(source.get_ref().requests, 3);
+
+        source.view(&[slice(300, 100)]).expect("view");
+        assert_eq!(source.get_ref().requests, 4);
+        assert_eq!(source.cached_bytes(), 300);
+
+        source.view(&[slice(0, 100)]).expect("view");
+        assert_eq!(source.get_ref().requests, 4);
+        source.view(&[slice(100, 100)]).expect("view");
+        assert_eq!(source.get_ref().requests, 5);
+
+        // slices exceeding the budget are not cached
+        source.view(&[slice(0, 400)]).expect("view");
+        source.view(&[slice(0, 400)]).expect("view");
+        assert_eq!(source.get_ref().requests, 7);
+    }
+}
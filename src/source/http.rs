@@ -0,0 +1,250 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::fmt;
+use std::io::{self, Read};
+
+use crate::source::{Source, SourceSlice, SourceView};
+
+/// A `Source` that fetches the requested byte ranges from a remote file using HTTP range requests.
+///
+/// Every `SourceSlice` requested by the MSF layer is retrieved with a separate `GET` request
+/// carrying a `Range` header. Only the pages needed to locate and read the accessed streams are
+/// transferred, so a few streams can be read from a large PDB on a symbol server without
+/// downloading the whole file. Servers must support range requests and respond with
+/// `206 Partial Content`.
+///
+/// Symbol servers store PDBs at `<server>/<name>/<GUID><age>/<name>`, where the GUID is formatted
+/// as 32 uppercase hexadecimal digits without dashes and the age is in hexadecimal.
+///
+/// This type is only available with the `http` feature.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn test() -> pdb::Result<()> {
+/// let url = "https://msdl.microsoft.com/download/symbols/\
+///            ntkrnlmp.pdb/3844DBB920174967BE7AA4A2C20430FA2/ntkrnlmp.pdb";
+/// let pdb = pdb::PDB::open(pdb::HttpSource::new(url))?;
+/// let info = pdb.pdb_information()?;
+/// println!("{}", info.guid);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct HttpSource {
+    agent: ureq::Agent,
+    url: String,
+}
+
+impl HttpSource {
+    /// Creates a new `HttpSource` for the file at the given URL.
+    pub fn new<U: Into<String>>(url: U) -> Self {
+        Self::with_agent(ureq::Agent::new_with_defaults(), url)
+    }
+
+    /// Creates a new `HttpSource` that issues its requests through the given agent.
+    ///
+    /// This allows configuring timeouts, proxies and TLS settings, as well as sharing connection
+    /// pools between sources.
+    pub fn with_agent<U: Into<String>>(agent: ureq::Agent, url: U) -> Self {
+        Self {
+            agent,
+            url: url.into(),
+        }
+    }
+
+    /// Returns the URL of the remote file.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn fetch(&self, slice: &SourceSlice, buf: &mut [u8]) -> io::Result<()> {
+        if slice.size == 0 {
+            return Ok(());
+        }
+
+        let last = slice.offset + slice.size as u64 - 1;
+        let mut response = self
+            .agent
+            .get(&self.url)
+            .header("Range", format!("bytes={}-{}", slice.offset, last))
+            .call()
+            .map_err(ureq::Error::into_io)?;
+
+        let status = response.status().as_u16();
+        if status != 206 {
+            return Err(io::Error::other(format!(
+                "expected 206 Partial Content for range request, got status {}",
+                status
+            )));
+        }
+
+        response.body_mut().as_reader().read_exact(buf)
+    }
+}
+
+impl fmt::Debug for HttpSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpSource")
+            .field("url", &self.url)
+            .finish()
+    }
+}
+
+impl<'s> Source<'s> for HttpSource {
+    fn view(&mut self, slices: &[SourceSlice]) -> Result<Box<dyn SourceView<'s> + 's>, io::Error> {
+        let len = slices.iter().fold(0, |acc, s| acc + s.size);
+        let mut bytes = vec![0; len];
+
+        let mut output_offset = 0;
+        for slice in slices {
+            self.fetch(slice, &mut bytes[output_offset..output_offset + slice.size])?;
+            output_offset += slice.size;
+        }
+
+        Ok(Box::new(HttpView { bytes }))
+    }
+}
+
+struct HttpView {
+    bytes: Vec<u8>,
+}
+
+impl fmt::Debug for HttpView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HttpView({} bytes)", self.bytes.len())
+    }
+}
+
+impl SourceView<'_> for HttpView {
+    fn as_slice(&self) -> &[u8] {
+        self.bytes.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::thread;
+
+    use crate::source::http::*;
+
+    /// Serves `data` over HTTP on a local port, answering range requests only if `ranges` is set.
+    fn serve(data: Vec<u8>, ranges: bool) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let url = format!("http://{}/file.pdb", listener.local_addr().expect("addr"));
+        let data = Arc::new(data);
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.expect("accept");
+                let mut reader = BufReader::new(stream.try_clone().expect("clone"));
+
+                let mut range = None;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).expect("read");
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+
+                    if let Some(value) = line.strip_prefix("range: bytes=") {
+                        let (start, end) = value.split_once('-').expect("range");
+                        range = Some((
+                            start.parse::<usize>().expect("start"),
+                            end.parse::<usize>().expect("end"),
+                        ));
+                    }
+                }
+
+                let (status, body) = match range {
+                    Some((start, end)) if ranges => (
+                        "206 Partial Content",
+                        &data[start..=end.min(data.len() - 1)],
+                    ),
+                    _ => ("200 OK", &data[..]),
+                };
+
+                let header = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    body.len()
+                );
+                stream.write_all(header.as_bytes()).expect("write");
+                stream.write_all(body).expect("write");
+            }
+        });
+
+        url
+    }
+
+    #[test]
+    fn test_range_requests() {
+        let data: Vec<u8> = (0..8192u32).map(|i| (i % 251) as u8).collect();
+        let mut source = HttpSource::new(serve(data.clone(), true));
+
+        let slices = [
+            SourceSlice {
+                offset: 4096,
+                size: 100,
+            },
+            SourceSlice {
+                offset: 40,
+                size: 4,
+            },
+        ];
+        let view = source.view(&slices).expect("view");
+        assert_eq!(&view.as_slice()[..100], &data[4096..4196]);
+        assert_eq!(&view.as_slice()[100..], &data[40..44]);
+    }
+
+    #[test]
+    fn test_short_response() {
+        let mut source = HttpSource::new(serve(vec![0; 4096], true));
+
+        let slices = [SourceSlice {
+            offset: 4095,
+            size: 2,
+        }];
+        match source.view(&slices) {
+            Ok(_) => panic!("should have failed"),
+            Err(e) => assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof),
+        }
+    }
+
+    #[test]
+    fn test_ranges_unsupported() {
+        let mut source = HttpSource::new(serve(vec![0; 4096], false));
+
+        let slices = [SourceSlice {
+            offset: 0,
+            size: 4096,
+        }];
+        assert!(source.view(&slices).is_err());
+    }
+
+    #[test]
+    fn test_open_pdb() {
+        let mut writer = crate::MsfWriter::new();
+        writer.add_stream(Vec::new());
+        writer.add_stream(b"remote".to_vec());
+
+        let mut file = Vec::new();
+        writer.write(&mut file).expect("write");
+
+        let pdb = crate::PDB::open(HttpSource::new(serve(file, true))).expect("open");
+        let stream = pdb
+            .raw_stream(crate::StreamIndex(1))
+            .expect("stream")
+            .expect("present");
+        assert_eq!(stream.as_slice(), b"remote");
+    }
+}
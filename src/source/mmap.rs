@@ -0,0 +1,206 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+use memmap2::Mmap;
+
+use crate::source::{Source, SourceSlice, SourceView};
+
+/// A `Source` backed by a memory-mapped file.
+///
+/// Requests for a single contiguous range of the file are served directly from the mapping without
+/// copying. Requests spanning multiple discontiguous ranges cannot be represented as a single slice
+/// of the mapping, so they are copied into a buffer, just like the default `Source` implementation
+/// does.
+///
+/// Views hold a reference to the mapping, so they remain valid even after the `MmapSource` and the
+/// [`PDB`](crate::PDB) using it have been dropped.
+///
+/// This type is only available with the `mmap` feature.
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let mmap = unsafe { memmap2::Mmap::map(&file)? };
+///
+/// let pdb = pdb::PDB::open(pdb::MmapSource::new(mmap))?;
+/// let s = pdb.raw_stream(pdb::StreamIndex(208))?.expect("stream exists");
+/// assert_eq!(s.as_slice(), b"hello world\n");
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Clone)]
+pub struct MmapSource {
+    mmap: Arc<Mmap>,
+}
+
+impl MmapSource {
+    /// Creates a new `MmapSource` from an existing memory map.
+    pub fn new(mmap: Mmap) -> Self {
+        Self {
+            mmap: Arc::new(mmap),
+        }
+    }
+
+    /// Opens and maps the file at the given path.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified, truncated or otherwise changed while it is mapped. This is
+    /// the same requirement as for [`Mmap::map`].
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        Ok(Self::new(Mmap::map(&file)?))
+    }
+
+    fn range(&self, slice: &SourceSlice) -> io::Result<Range<usize>> {
+        let start = usize::try_from(slice.offset).ok();
+        let end = start.and_then(|start| start.checked_add(slice.size));
+
+        match (start, end) {
+            (Some(start), Some(end)) if end <= self.mmap.len() => Ok(start..end),
+            _ => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "requested range exceeds the mapped file",
+            )),
+        }
+    }
+}
+
+impl fmt::Debug for MmapSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MmapSource({} bytes)", self.mmap.len())
+    }
+}
+
+impl<'s> Source<'s> for MmapSource {
+    fn view(&mut self, slices: &[SourceSlice]) -> Result<Box<dyn SourceView<'s> + 's>, io::Error> {
+        if let [slice] = slices {
+            let range = self.range(slice)?;
+            return Ok(Box::new(MappedView {
+                mmap: Arc::clone(&self.mmap),
+                range,
+            }));
+        }
+
+        let len = slices.iter().fold(0, |acc, s| acc + s.size);
+        let mut bytes = Vec::with_capacity(len);
+        for slice in slices {
+            let range = self.range(slice)?;
+            bytes.extend_from_slice(&self.mmap[range]);
+        }
+
+        Ok(Box::new(CopiedView { bytes }))
+    }
+}
+
+struct MappedView {
+    mmap: Arc<Mmap>,
+    range: Range<usize>,
+}
+
+impl fmt::Debug for MappedView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MappedView({} bytes)", self.range.len())
+    }
+}
+
+impl SourceView<'_> for MappedView {
+    fn as_slice(&self) -> &[u8] {
+        &self.mmap[self.range.clone()]
+    }
+}
+
+struct CopiedView {
+    bytes: Vec<u8>,
+}
+
+impl fmt::Debug for CopiedView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CopiedView({} bytes)", self.bytes.len())
+    }
+}
+
+impl SourceView<'_> for CopiedView {
+    fn as_slice(&self) -> &[u8] {
+        self.bytes.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{ErrorKind, Write};
+
+    use crate::source::mmap::*;
+
+    fn source(data: &[u8]) -> MmapSource {
+        let mut mmap = memmap2::MmapMut::map_anon(data.len()).expect("map");
+        (&mut mmap[..]).write_all(data).expect("write");
+        MmapSource::new(mmap.make_read_only().expect("read only"))
+    }
+
+    #[test]
+    fn test_contiguous_view() {
+        let mut data = vec![0; 4096];
+        data[42] = 42;
+        let mut source = source(&data);
+
+        let view = source
+            .view(&[SourceSlice {
+                offset: 40,
+                size: 4,
+            }])
+            .expect("view");
+        assert_eq!(view.as_slice(), &[0, 0, 42, 0]);
+        assert_eq!(view.as_slice().as_ptr(), source.mmap[40..].as_ptr());
+    }
+
+    #[test]
+    fn test_discontinuous_view() {
+        let mut data = vec![0; 4096];
+        data[42] = 42;
+        data[88] = 88;
+        let mut source = source(&data);
+
+        let slices = [
+            SourceSlice {
+                offset: 88,
+                size: 1,
+            },
+            SourceSlice {
+                offset: 40,
+                size: 4,
+            },
+        ];
+        let view = source.view(&slices).expect("view");
+        assert_eq!(view.as_slice(), &[88, 0, 0, 42, 0]);
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        let mut source = source(&[0; 4096]);
+
+        let slices = [SourceSlice {
+            offset: 4095,
+            size: 2,
+        }];
+        match source.view(&slices) {
+            Ok(_) => panic!("should have failed"),
+            Err(e) => assert_eq!(e.kind(), ErrorKind::UnexpectedEof),
+        }
+    }
+}
@@ -8,11 +8,66 @@
 use std::fmt;
 use std::io;
 
+mod caching;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod owned;
+mod prefetch;
+mod slice;
+pub use self::caching::CachingSource;
+#[cfg(feature = "http")]
+pub use self::http::HttpSource;
+#[cfg(feature = "mmap")]
+pub use self::mmap::MmapSource;
+pub use self::owned::OwnedSource;
+pub use self::prefetch::PrefetchSource;
+pub use self::slice::SliceSource;
+
+/// Marker for types that can be moved to another thread if the `sync` feature is enabled.
+///
+/// With the `sync` feature, this requires `Send`, which is needed for `Source`s so that a
+/// `PDB` can be shared across threads. Otherwise, it is implemented for every type.
+#[cfg(feature = "sync")]
+pub trait MaybeSend: Send {}
+#[cfg(feature = "sync")]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+/// Marker for types that can be moved to another thread if the `sync` feature is enabled.
+///
+/// With the `sync` feature, this requires `Send`, which is needed for `Source`s so that a
+/// `PDB` can be shared across threads. Otherwise, it is implemented for every type.
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSend {}
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> MaybeSend for T {}
+
+/// Marker for types that can be shared between threads if the `sync` feature is enabled.
+///
+/// With the `sync` feature, this requires `Send + Sync`, which is needed for `SourceView`s so that
+/// a `PDB` and the `Stream`s read from it can be shared across threads. Otherwise, it is
+/// implemented for every type.
+#[cfg(feature = "sync")]
+pub trait MaybeSendSync: Send + Sync {}
+#[cfg(feature = "sync")]
+impl<T: Send + Sync + ?Sized> MaybeSendSync for T {}
+
+/// Marker for types that can be shared between threads if the `sync` feature is enabled.
+///
+/// With the `sync` feature, this requires `Send + Sync`, which is needed for `SourceView`s so that
+/// a `PDB` and the `Stream`s read from it can be shared across threads. Otherwise, it is
+/// implemented for every type.
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSendSync {}
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> MaybeSendSync for T {}
+
 /// Represents an offset + size of the source file.
 ///
 /// The multi-stream file implementation (used by `pdb::PDB`) determines which byte ranges it needs
 /// to satisfy its requests, and it describes those requests as a `&[SourceSlice]`.
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
 pub struct SourceSlice {
     /// Offset into the source file.
     pub offset: u64,
@@ -23,7 +78,9 @@ pub struct SourceSlice {
 /// The `pdb` crate accesses PDB files via the `pdb::Source` trait.
 ///
 /// This library is written with zero-copy in mind. `Source`s provide [`SourceView`]s which need not
-/// outlive their parent, supporting implementations of e.g. memory mapped files.
+/// outlive their parent, supporting implementations of e.g. memory mapped files. Views may also
+/// borrow data for the lifetime `'s`, which allows [`SliceSource`] to hand out subslices of an
+/// in-memory buffer without copying.
 ///
 /// PDB files are "multi-stream files" (MSF) under the hood. MSFs have various layers of
 /// indirection, but ultimately the MSF code asks a `Source` to view a series of
@@ -35,7 +92,8 @@ pub struct SourceSlice {
 /// There is a default `Source` implementation for `std::io::Read` + `std::io::Seek` +
 /// `std::fmt::Debug`, allowing a `std::fs::File` to be treated as `pdb::Source`. This
 /// implementation provides views by allocating a buffer, seeking, and reading the contents into
-/// that buffer.
+/// that buffer. For data that is already in memory, prefer [`SliceSource`] or [`OwnedSource`] over
+/// wrapping it in a `std::io::Cursor`.
 ///
 /// # Alignment
 ///
@@ -46,16 +104,21 @@ pub struct SourceSlice {
 /// PDB files are specified as always being a multiple of the page size, so `Source` implementations
 /// are free to e.g. map whole pages and return a sub-slice of the requested length.
 ///
-pub trait Source<'s>: fmt::Debug {
+/// # Threads
+///
+/// With the `sync` feature enabled, `Source`s must be `Send` and `SourceView`s must be
+/// `Send + Sync`. In exchange, `PDB` and the structures read from it are `Send + Sync`.
+///
+pub trait Source<'s>: fmt::Debug + MaybeSend {
     /// Provides a contiguous view of the source file composed of the requested position(s).
     ///
     /// Note that the SourceView's as_slice() method cannot fail, so `view()` is the time to raise
     /// IO errors.
-    fn view(&mut self, slices: &[SourceSlice]) -> Result<Box<dyn SourceView<'s>>, io::Error>;
+    fn view(&mut self, slices: &[SourceSlice]) -> Result<Box<dyn SourceView<'s> + 's>, io::Error>;
 }
 
 /// An owned, droppable, read-only view of the source file which can be referenced as a byte slice.
-pub trait SourceView<'s>: fmt::Debug {
+pub trait SourceView<'s>: fmt::Debug + MaybeSendSync {
     /// Returns a view to the raw data.
     fn as_slice(&self) -> &[u8];
 }
@@ -79,9 +142,9 @@ impl SourceView<'_> for ReadView {
 
 impl<'s, T> Source<'s> for T
 where
-    T: io::Read + io::Seek + fmt::Debug + 's,
+    T: io::Read + io::Seek + fmt::Debug + MaybeSend + 's,
 {
-    fn view(&mut self, slices: &[SourceSlice]) -> Result<Box<dyn SourceView<'s>>, io::Error> {
+    fn view(&mut self, slices: &[SourceSlice]) -> Result<Box<dyn SourceView<'s> + 's>, io::Error> {
         let len = slices.iter().fold(0, |acc, s| acc + s.size);
 
         let mut v = ReadView {
@@ -199,4 +262,46 @@ mod tests {
             }
         }
     }
+
+    #[cfg(feature = "sync")]
+    mod sync {
+        use std::fs::File;
+        use std::sync::Arc;
+        use std::thread;
+
+        use crate::msf::Stream;
+        use crate::*;
+
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        #[test]
+        fn test_send_sync() {
+            assert_send_sync::<PDB<'static, File>>();
+            assert_send_sync::<PDB<'static, OwnedSource>>();
+            assert_send_sync::<Stream<'static>>();
+            assert_send_sync::<TypeInformation<'static>>();
+            assert_send_sync::<DebugInformation<'static>>();
+            assert_send_sync::<SymbolTable<'static>>();
+        }
+
+        #[test]
+        fn test_shared_pdb() {
+            let data = std::fs::read("fixtures/self/foo.pdb").expect("read");
+            let pdb = Arc::new(PDB::open(OwnedSource::new(data)).expect("open"));
+
+            let threads: Vec<_> = (0..4)
+                .map(|_| {
+                    let pdb = Arc::clone(&pdb);
+                    thread::spawn(move || {
+                        let stream = pdb.raw_stream(StreamIndex(208)).expect("stream");
+                        stream.expect("stream exists").as_slice().to_vec()
+                    })
+                })
+                .collect();
+
+            for thread in threads {
+                assert_eq!(thread.join().expect("join"), b"hello world\n");
+            }
+        }
+    }
 }
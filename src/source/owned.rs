@@ -0,0 +1,202 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::io;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::source::{Source, SourceSlice, SourceView};
+
+/// A `Source` that owns its data in memory.
+///
+/// Unlike [`SliceSource`](crate::SliceSource), views do not borrow from the caller. Requests for a
+/// single contiguous range share the underlying buffer without copying, so a `PDB` opened from an
+/// `OwnedSource` is a `PDB<'static, _>`. The `PDB` and the structures read from it, such as
+/// [`TypeInformation<'static>`](crate::TypeInformation), can then be stored in long-lived structs
+/// without keeping the original buffer alive separately.
+///
+/// Requests spanning multiple discontiguous ranges are copied into a buffer.
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// struct Symbolicator {
+///     pdb: pdb::PDB<'static, pdb::OwnedSource>,
+///     type_information: pdb::TypeInformation<'static>,
+/// }
+///
+/// let data = std::fs::read("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(pdb::OwnedSource::new(data))?;
+/// let type_information = pdb.type_information()?;
+///
+/// let symbolicator = Symbolicator {
+///     pdb,
+///     type_information,
+/// };
+/// assert!(symbolicator.type_information.len() > 0);
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Clone)]
+pub struct OwnedSource {
+    data: Arc<[u8]>,
+}
+
+impl OwnedSource {
+    /// Creates a new `OwnedSource` taking ownership of the given data.
+    pub fn new(data: impl Into<Arc<[u8]>>) -> Self {
+        Self { data: data.into() }
+    }
+
+    /// Creates a new `OwnedSource` by reading everything from `reader`.
+    pub fn from_reader<R: io::Read>(mut reader: R) -> io::Result<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Ok(Self::new(data))
+    }
+
+    /// Returns the underlying data.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn range(&self, slice: &SourceSlice) -> io::Result<Range<usize>> {
+        let start = usize::try_from(slice.offset).ok();
+        let end = start.and_then(|start| start.checked_add(slice.size));
+
+        match (start, end) {
+            (Some(start), Some(end)) if end <= self.data.len() => Ok(start..end),
+            _ => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "requested range exceeds the source data",
+            )),
+        }
+    }
+}
+
+impl From<Vec<u8>> for OwnedSource {
+    fn from(data: Vec<u8>) -> Self {
+        Self::new(data)
+    }
+}
+
+impl From<Arc<[u8]>> for OwnedSource {
+    fn from(data: Arc<[u8]>) -> Self {
+        Self::new(data)
+    }
+}
+
+impl fmt::Debug for OwnedSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OwnedSource({} bytes)", self.data.len())
+    }
+}
+
+impl<'s> Source<'s> for OwnedSource {
+    fn view(&mut self, slices: &[SourceSlice]) -> Result<Box<dyn SourceView<'s> + 's>, io::Error> {
+        if let [slice] = slices {
+            return Ok(Box::new(SharedView {
+                data: Arc::clone(&self.data),
+                range: self.range(slice)?,
+            }));
+        }
+
+        let len = slices.iter().fold(0, |acc, s| acc + s.size);
+        let mut bytes = Vec::with_capacity(len);
+        for slice in slices {
+            bytes.extend_from_slice(&self.data[self.range(slice)?]);
+        }
+
+        Ok(Box::new(SharedView {
+            range: 0..bytes.len(),
+            data: bytes.into(),
+        }))
+    }
+}
+
+struct SharedView {
+    data: Arc<[u8]>,
+    range: Range<usize>,
+}
+
+impl fmt::Debug for SharedView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SharedView({} bytes)", self.range.len())
+    }
+}
+
+impl SourceView<'_> for SharedView {
+    fn as_slice(&self) -> &[u8] {
+        &self.data[self.range.clone()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use crate::source::owned::*;
+
+    #[test]
+    fn test_contiguous_view() {
+        let mut data = vec![0; 4096];
+        data[42] = 42;
+        let mut source = OwnedSource::new(data);
+
+        let view = source
+            .view(&[SourceSlice {
+                offset: 40,
+                size: 4,
+            }])
+            .expect("view");
+        assert_eq!(view.as_slice(), &[0, 0, 42, 0]);
+        assert_eq!(view.as_slice().as_ptr(), source.as_slice()[40..].as_ptr());
+
+        // the view outlives the source
+        drop(source);
+        assert_eq!(view.as_slice(), &[0, 0, 42, 0]);
+    }
+
+    #[test]
+    fn test_discontinuous_view() {
+        let mut data = vec![0; 4096];
+        data[42] = 42;
+        data[88] = 88;
+        let mut source = OwnedSource::new(data);
+
+        let slices = [
+            SourceSlice {
+                offset: 88,
+                size: 1,
+            },
+            SourceSlice {
+                offset: 40,
+                size: 4,
+            },
+        ];
+        let view = source.view(&slices).expect("view");
+        assert_eq!(view.as_slice(), &[88, 0, 0, 42, 0]);
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        let mut source = OwnedSource::new(vec![0; 4096]);
+
+        let slices = [SourceSlice {
+            offset: 4095,
+            size: 2,
+        }];
+        match source.view(&slices) {
+            Ok(_) => panic!("should have failed"),
+            Err(e) => assert_eq!(e.kind(), ErrorKind::UnexpectedEof),
+        };
+    }
+}
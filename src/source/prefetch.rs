@@ -0,0 +1,462 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::source::{Source, SourceSlice, SourceView};
+
+/// A `Source` adapter that coalesces reads and keeps the fetched blocks for subsequent requests.
+///
+/// Whenever a request cannot be served from previously fetched data, `PrefetchSource` sorts the
+/// missing ranges by offset, merges ranges that are adjacent or separated by at most the
+/// read-ahead distance, extends each merged range by the read-ahead distance, and fetches all of
+/// them from the underlying `Source` in a single request. The fetched blocks are retained up to a
+/// budget in bytes, oldest blocks being discarded first, and serve any later request for bytes
+/// they contain.
+///
+/// Unlike [`CachingSource`](crate::CachingSource), which only serves repeated requests for the
+/// exact same slices, this serves any request falling into data fetched earlier. Together with
+/// [`PDB::prefetch`](crate::PDB::prefetch), which requests the pages of several streams at once,
+/// this considerably reduces the number of round trips to sources with expensive reads, such as
+/// files on a network share or remote files, when the cache is cold.
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let mut source = pdb::PrefetchSource::new(file, 16 * 1024 * 1024);
+/// source.set_read_ahead(64 * 1024);
+///
+/// let pdb = pdb::PDB::open(source)?;
+/// pdb.prefetch(&[pdb::StreamIndex(1), pdb::StreamIndex(208)])?;
+///
+/// // served from the prefetched blocks
+/// let s = pdb.raw_stream(pdb::StreamIndex(208))?.expect("stream exists");
+/// assert_eq!(s.as_slice(), b"hello world\n");
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+pub struct PrefetchSource<S> {
+    source: S,
+    budget: usize,
+    read_ahead: usize,
+    cached_bytes: usize,
+    blocks: BTreeMap<u64, Arc<[u8]>>,
+    order: VecDeque<u64>,
+}
+
+impl<S> PrefetchSource<S> {
+    /// Wraps a `Source`, retaining up to `budget` bytes of fetched blocks.
+    ///
+    /// The read-ahead distance is initially zero, so only adjacent ranges are merged.
+    pub fn new(source: S, budget: usize) -> Self {
+        Self {
+            source,
+            budget,
+            read_ahead: 0,
+            cached_bytes: 0,
+            blocks: BTreeMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Returns the maximum number of bytes retained.
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// Returns the number of bytes fetched beyond each merged range.
+    pub fn read_ahead(&self) -> usize {
+        self.read_ahead
+    }
+
+    /// Sets the number of bytes fetched beyond each merged range.
+    ///
+    /// Ranges separated by gaps of up to this many bytes are also merged into a single read.
+    pub fn set_read_ahead(&mut self, bytes: usize) {
+        self.read_ahead = bytes;
+    }
+
+    /// Returns the number of bytes currently retained.
+    pub fn cached_bytes(&self) -> usize {
+        self.cached_bytes
+    }
+
+    /// Discards all retained blocks.
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+        self.order.clear();
+        self.cached_bytes = 0;
+    }
+
+    /// Returns a reference to the underlying `Source`.
+    pub fn get_ref(&self) -> &S {
+        &self.source
+    }
+
+    /// Unwraps this `PrefetchSource`, returning the underlying `Source`.
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+
+    /// Returns the retained data starting at `offset`, up to the end of the block containing it.
+    fn lookup(&self, offset: u64) -> Option<&[u8]> {
+        let (&start, data) = self.blocks.range(..=offset).next_back()?;
+        let skip = usize::try_from(offset - start).ok()?;
+        data.get(skip..).filter(|rest| !rest.is_empty())
+    }
+
+    /// Returns the retained block containing all of `slice`, along with the range of `slice`
+    /// within the block.
+    fn retained_block(&self, slice: &SourceSlice) -> Option<(Arc<[u8]>, Range<usize>)> {
+        let (&start, data) = self.blocks.range(..=slice.offset).next_back()?;
+        block_range(start, data, slice).map(|range| (Arc::clone(data), range))
+    }
+
+    fn is_retained(&self, slice: &SourceSlice) -> bool {
+        let mut offset = slice.offset;
+        let mut remaining = slice.size;
+        while remaining > 0 {
+            match self.lookup(offset) {
+                Some(data) => {
+                    let len = data.len().min(remaining);
+                    offset += len as u64;
+                    remaining -= len;
+                }
+                None => return false,
+            }
+        }
+        true
+    }
+
+    fn insert(&mut self, offset: u64, data: Arc<[u8]>) {
+        if data.len() > self.budget {
+            return;
+        }
+
+        if let Some(replaced) = self.blocks.remove(&offset) {
+            self.cached_bytes -= replaced.len();
+            self.order.retain(|&o| o != offset);
+        }
+
+        while self.cached_bytes + data.len() > self.budget {
+            let evicted = match self.order.pop_front() {
+                Some(oldest) => oldest,
+                None => break,
+            };
+
+            if let Some(data) = self.blocks.remove(&evicted) {
+                self.cached_bytes -= data.len();
+            }
+        }
+
+        self.cached_bytes += data.len();
+        self.order.push_back(offset);
+        self.blocks.insert(offset, data);
+    }
+}
+
+impl<'s, S: Source<'s>> PrefetchSource<S> {
+    /// Fetches the given sorted, non-overlapping ranges with a single request.
+    fn fetch(&mut self, ranges: &[SourceSlice]) -> io::Result<Vec<(u64, Arc<[u8]>)>> {
+        let extended: Vec<SourceSlice> = ranges
+            .iter()
+            .map(|range| SourceSlice {
+                offset: range.offset,
+                size: range.size + self.read_ahead,
+            })
+            .collect();
+
+        // the read-ahead may extend past the end of the file, in which case the exact ranges are
+        // requested instead
+        let (ranges, view) = match self.source.view(&extended) {
+            Ok(view) => (extended.as_slice(), view),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof && self.read_ahead > 0 => {
+                (ranges, self.source.view(ranges)?)
+            }
+            Err(e) => return Err(e),
+        };
+
+        let mut bytes = view.as_slice();
+        let mut blocks = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            let (data, rest) = bytes.split_at(range.size);
+            blocks.push((range.offset, Arc::from(data)));
+            bytes = rest;
+        }
+
+        Ok(blocks)
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for PrefetchSource<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrefetchSource")
+            .field("source", &self.source)
+            .field("budget", &self.budget)
+            .field("read_ahead", &self.read_ahead)
+            .field("cached_bytes", &self.cached_bytes)
+            .finish()
+    }
+}
+
+impl<'s, S: Source<'s>> Source<'s> for PrefetchSource<S> {
+    fn view(&mut self, slices: &[SourceSlice]) -> Result<Box<dyn SourceView<'s> + 's>, io::Error> {
+        let mut missing: Vec<SourceSlice> = slices
+            .iter()
+            .filter(|slice| slice.size > 0 && !self.is_retained(slice))
+            .copied()
+            .collect();
+        missing.sort_unstable_by_key(|slice| slice.offset);
+
+        // merge ranges that are adjacent, overlapping or close enough to be covered by read-ahead
+        let mut ranges: Vec<SourceSlice> = Vec::with_capacity(missing.len());
+        for slice in missing {
+            let end = slice.offset + slice.size as u64;
+            match ranges.last_mut() {
+                Some(last)
+                    if slice.offset <= last.offset + (last.size + self.read_ahead) as u64 =>
+                {
+                    last.size = last.size.max((end - last.offset) as usize);
+                }
+                _ => ranges.push(slice),
+            }
+        }
+
+        let fetched = if ranges.is_empty() {
+            Vec::new()
+        } else {
+            self.fetch(&ranges)?
+        };
+
+        // a single slice within one block can be shared without copying
+        if let [slice] = slices {
+            let shared = fetched
+                .iter()
+                .find_map(|(start, data)| {
+                    block_range(*start, data, slice).map(|range| (Arc::clone(data), range))
+                })
+                .or_else(|| self.retained_block(slice));
+
+            if let Some((data, range)) = shared {
+                for (offset, data) in fetched {
+                    self.insert(offset, data);
+                }
+                return Ok(Box::new(PrefetchedView { data, range }));
+            }
+        }
+
+        // assemble the requested slices, preferring freshly fetched blocks since retained blocks
+        // may be evicted to make room for them
+        let len = slices.iter().fold(0, |acc, s| acc + s.size);
+        let mut bytes = Vec::with_capacity(len);
+        for slice in slices {
+            let mut offset = slice.offset;
+            let mut remaining = slice.size;
+            while remaining > 0 {
+                let fresh = fetched.iter().find_map(|(start, data)| {
+                    let skip = usize::try_from(offset.checked_sub(*start)?).ok()?;
+                    data.get(skip..).filter(|rest| !rest.is_empty())
+                });
+
+                let data = match fresh.or_else(|| self.lookup(offset)) {
+                    Some(data) => data,
+                    None => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "source returned less data than requested",
+                        ))
+                    }
+                };
+
+                let len = data.len().min(remaining);
+                bytes.extend_from_slice(&data[..len]);
+                offset += len as u64;
+                remaining -= len;
+            }
+        }
+
+        for (offset, data) in fetched {
+            self.insert(offset, data);
+        }
+
+        Ok(Box::new(PrefetchedView {
+            range: 0..bytes.len(),
+            data: bytes.into(),
+        }))
+    }
+}
+
+/// Returns the range of `slice` within the block starting at `start`, if the block contains all of
+/// `slice`.
+fn block_range(start: u64, data: &[u8], slice: &SourceSlice) -> Option<Range<usize>> {
+    let skip = usize::try_from(slice.offset.checked_sub(start)?).ok()?;
+    let end = skip.checked_add(slice.size)?;
+    if end <= data.len() {
+        Some(skip..end)
+    } else {
+        None
+    }
+}
+
+struct PrefetchedView {
+    data: Arc<[u8]>,
+    range: Range<usize>,
+}
+
+impl fmt::Debug for PrefetchedView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PrefetchedView({} bytes)", self.range.len())
+    }
+}
+
+impl SourceView<'_> for PrefetchedView {
+    fn as_slice(&self) -> &[u8] {
+        &self.data[self.range.clone()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::source::prefetch::*;
+
+    /// A `Source` counting the number of requests and requested slices.
+    #[derive(Debug)]
+    struct CountingSource {
+        inner: Cursor<Vec<u8>>,
+        requests: usize,
+        slices: usize,
+    }
+
+    impl<'s> Source<'s> for CountingSource {
+        fn view(
+            &mut self,
+            slices: &[SourceSlice],
+        ) -> Result<Box<dyn SourceView<'s> + 's>, io::Error> {
+            self.requests += 1;
+            self.slices += slices.len();
+            self.inner.view(slices)
+        }
+    }
+
+    fn source(budget: usize) -> PrefetchSource<CountingSource> {
+        let data = (0..4096u32).map(|i| (i % 251) as u8).collect();
+        let source = CountingSource {
+            inner: Cursor::new(data),
+            requests: 0,
+            slices: 0,
+        };
+        PrefetchSource::new(source, budget)
+    }
+
+    fn slice(offset: u64, size: usize) -> SourceSlice {
+        SourceSlice { offset, size }
+    }
+
+    fn expected(slices: &[SourceSlice]) -> Vec<u8> {
+        slices
+            .iter()
+            .flat_map(|s| (s.offset..s.offset + s.size as u64).map(|i| (i % 251) as u8))
+            .collect()
+    }
+
+    #[test]
+    fn test_coalescing() {
+        let mut source = source(4096);
+
+        // out-of-order adjacent slices are fetched as a single range
+        let slices = [slice(200, 100), slice(100, 100), slice(0, 100)];
+        let view = source.view(&slices).expect("view");
+        assert_eq!(view.as_slice(), expected(&slices).as_slice());
+        assert_eq!(source.get_ref().requests, 1);
+        assert_eq!(source.get_ref().slices, 1);
+        assert_eq!(source.cached_bytes(), 300);
+
+        // any part of the fetched range is served without another request
+        let slices = [slice(150, 100), slice(0, 10)];
+        let view = source.view(&slices).expect("view");
+        assert_eq!(view.as_slice(), expected(&slices).as_slice());
+        assert_eq!(source.get_ref().requests, 1);
+
+        // slices that are not entirely retained are fetched again
+        let slices = [slice(250, 100)];
+        let view = source.view(&slices).expect("view");
+        assert_eq!(view.as_slice(), expected(&slices).as_slice());
+        assert_eq!(source.get_ref().requests, 2);
+    }
+
+    #[test]
+    fn test_read_ahead() {
+        let mut source = source(4096);
+        source.set_read_ahead(512);
+
+        // the gap between both slices is bridged and the range is extended by the read-ahead
+        let slices = [slice(0, 100), slice(400, 100)];
+        let view = source.view(&slices).expect("view");
+        assert_eq!(view.as_slice(), expected(&slices).as_slice());
+        assert_eq!(source.get_ref().slices, 1);
+        assert_eq!(source.cached_bytes(), 1012);
+
+        let slices = [slice(900, 100)];
+        let view = source.view(&slices).expect("view");
+        assert_eq!(view.as_slice(), expected(&slices).as_slice());
+        assert_eq!(source.get_ref().requests, 1);
+
+        // reading ahead past the end of the source falls back to the exact range
+        let slices = [slice(4000, 96)];
+        let view = source.view(&slices).expect("view");
+        assert_eq!(view.as_slice(), expected(&slices).as_slice());
+        assert_eq!(source.get_ref().requests, 3);
+    }
+
+    #[test]
+    fn test_shared_view() {
+        let mut source = source(4096);
+        source.view(&[slice(0, 300)]).expect("view");
+
+        // single slices within a retained block share its data
+        let first = source.view(&[slice(100, 50)]).expect("view");
+        let second = source.view(&[slice(100, 50)]).expect("view");
+        assert_eq!(first.as_slice(), expected(&[slice(100, 50)]).as_slice());
+        assert_eq!(first.as_slice().as_ptr(), second.as_slice().as_ptr());
+        assert_eq!(source.get_ref().requests, 1);
+
+        // the view remains valid after the block is discarded
+        source.clear();
+        assert_eq!(first.as_slice(), expected(&[slice(100, 50)]).as_slice());
+    }
+
+    #[test]
+    fn test_eviction() {
+        let mut source = source(250);
+
+        source.view(&[slice(0, 100)]).expect("view");
+        source.view(&[slice(1000, 100)]).expect("view");
+        source.view(&[slice(2000, 100)]).expect("view");
+        assert_eq!(source.cached_bytes(), 200);
+
+        source.view(&[slice(0, 100)]).expect("view");
+        assert_eq!(source.get_ref().requests, 4);
+        source.view(&[slice(2000, 100)]).expect("view");
+        assert_eq!(source.get_ref().requests, 4);
+
+        // requests exceeding the budget are served, but not retained
+        let slices = [slice(0, 1000)];
+        let view = source.view(&slices).expect("view");
+        assert_eq!(view.as_slice(), expected(&slices).as_slice());
+        assert_eq!(source.cached_bytes(), 200);
+    }
+}
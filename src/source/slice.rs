@@ -0,0 +1,186 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::io;
+
+use crate::source::{Source, SourceSlice, SourceView};
+
+/// A `Source` over a byte slice that is already in memory.
+///
+/// Requests for a single contiguous range are served as borrowed subslices of the underlying data
+/// without copying. Streams whose pages are stored sequentially, which is the common case for PDBs
+/// written by the linker, therefore do not cost any additional memory. Requests spanning multiple
+/// discontiguous ranges are copied into a buffer.
+///
+/// Wrapping the data in a `std::io::Cursor` also works, but uses the default `Source`
+/// implementation which always copies.
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// let data = std::fs::read("fixtures/self/foo.pdb")?;
+///
+/// let pdb = pdb::PDB::open(pdb::SliceSource::new(&data))?;
+/// let s = pdb.raw_stream(pdb::StreamIndex(208))?.expect("stream exists");
+/// assert_eq!(s.as_slice(), b"hello world\n");
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Clone, Copy)]
+pub struct SliceSource<'s> {
+    data: &'s [u8],
+}
+
+impl<'s> SliceSource<'s> {
+    /// Creates a new `SliceSource` over the given data.
+    pub fn new(data: &'s [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Returns the underlying data.
+    pub fn as_slice(&self) -> &'s [u8] {
+        self.data
+    }
+
+    fn get(&self, slice: &SourceSlice) -> io::Result<&'s [u8]> {
+        let start = usize::try_from(slice.offset).ok();
+        let end = start.and_then(|start| start.checked_add(slice.size));
+
+        match (start, end) {
+            (Some(start), Some(end)) if end <= self.data.len() => Ok(&self.data[start..end]),
+            _ => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "requested range exceeds the source data",
+            )),
+        }
+    }
+}
+
+impl<'s> From<&'s [u8]> for SliceSource<'s> {
+    fn from(data: &'s [u8]) -> Self {
+        Self::new(data)
+    }
+}
+
+impl fmt::Debug for SliceSource<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SliceSource({} bytes)", self.data.len())
+    }
+}
+
+impl<'s> Source<'s> for SliceSource<'s> {
+    fn view(&mut self, slices: &[SourceSlice]) -> Result<Box<dyn SourceView<'s> + 's>, io::Error> {
+        if let [slice] = slices {
+            return Ok(Box::new(BorrowedView {
+                bytes: self.get(slice)?,
+            }));
+        }
+
+        let len = slices.iter().fold(0, |acc, s| acc + s.size);
+        let mut bytes = Vec::with_capacity(len);
+        for slice in slices {
+            bytes.extend_from_slice(self.get(slice)?);
+        }
+
+        Ok(Box::new(OwnedView { bytes }))
+    }
+}
+
+struct BorrowedView<'s> {
+    bytes: &'s [u8],
+}
+
+impl fmt::Debug for BorrowedView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BorrowedView({} bytes)", self.bytes.len())
+    }
+}
+
+impl<'s> SourceView<'s> for BorrowedView<'s> {
+    fn as_slice(&self) -> &[u8] {
+        self.bytes
+    }
+}
+
+struct OwnedView {
+    bytes: Vec<u8>,
+}
+
+impl fmt::Debug for OwnedView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OwnedView({} bytes)", self.bytes.len())
+    }
+}
+
+impl SourceView<'_> for OwnedView {
+    fn as_slice(&self) -> &[u8] {
+        self.bytes.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use crate::source::slice::*;
+
+    #[test]
+    fn test_contiguous_view() {
+        let mut data = vec![0; 4096];
+        data[42] = 42;
+        let mut source = SliceSource::new(&data);
+
+        let view = source
+            .view(&[SourceSlice {
+                offset: 40,
+                size: 4,
+            }])
+            .expect("view");
+        assert_eq!(view.as_slice(), &[0, 0, 42, 0]);
+        assert_eq!(view.as_slice().as_ptr(), data[40..].as_ptr());
+    }
+
+    #[test]
+    fn test_discontinuous_view() {
+        let mut data = vec![0; 4096];
+        data[42] = 42;
+        data[88] = 88;
+        let mut source = SliceSource::new(&data);
+
+        let slices = [
+            SourceSlice {
+                offset: 88,
+                size: 1,
+            },
+            SourceSlice {
+                offset: 40,
+                size: 4,
+            },
+        ];
+        let view = source.view(&slices).expect("view");
+        assert_eq!(view.as_slice(), &[88, 0, 0, 42, 0]);
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        let data = vec![0; 4096];
+        let mut source = SliceSource::new(&data);
+
+        let slices = [SourceSlice {
+            offset: 4095,
+            size: 2,
+        }];
+        match source.view(&slices) {
+            Ok(_) => panic!("should have failed"),
+            Err(e) => assert_eq!(e.kind(), ErrorKind::UnexpectedEof),
+        };
+    }
+}
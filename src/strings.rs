@@ -73,24 +73,52 @@ impl StringTableHeader {
     }
 }
 
+/// The data backing a [`StringTable`].
+#[derive(Debug)]
+enum StringTableData<'s> {
+    /// The string table is a stream of its own, such as `/names`.
+    Stream(Stream<'s>),
+    /// The string table is embedded in another stream, such as the EC substream of the DBI.
+    Slice(&'s [u8]),
+}
+
+impl StringTableData<'_> {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            Self::Stream(stream) => stream.as_slice(),
+            Self::Slice(slice) => slice,
+        }
+    }
+}
+
 /// The global string table of a PDB.
 ///
 /// The string table is a two-way mapping from offset to string and back. It can be used to resolve
 /// [`StringRef`] offsets to their string values. Sometimes, it is also referred to as "Name table".
 /// The mapping from string to offset has not been implemented yet.
 ///
-/// Use [`PDB::string_table`](crate::PDB::string_table) to obtain an instance.
+/// Use [`PDB::string_table`](crate::PDB::string_table) to obtain an instance. The names of
+/// edit-and-continue files are stored in a separate table of the same format, which is obtained
+/// with [`DebugInformation::ec_names`](crate::DebugInformation::ec_names).
 #[derive(Debug)]
 pub struct StringTable<'s> {
     header: StringTableHeader,
     #[allow(dead_code)] // reason = "reverse-lookups through hash table not implemented"
     hash_version: StringTableHashVersion,
-    stream: Stream<'s>,
+    data: StringTableData<'s>,
 }
 
 impl<'s> StringTable<'s> {
     pub(crate) fn parse(stream: Stream<'s>) -> Result<Self> {
-        let mut buf = stream.parse_buffer();
+        Self::parse_data(StringTableData::Stream(stream))
+    }
+
+    pub(crate) fn parse_slice(slice: &'s [u8]) -> Result<Self> {
+        Self::parse_data(StringTableData::Slice(slice))
+    }
+
+    fn parse_data(data: StringTableData<'s>) -> Result<Self> {
+        let mut buf = ParseBuffer::from(data.as_slice());
         let header = buf.parse::<StringTableHeader>()?;
 
         if header.magic != PDB_NMT_HDR {
@@ -118,7 +146,7 @@ impl<'s> StringTable<'s> {
         Ok(StringTable {
             header,
             hash_version,
-            stream,
+            data,
         })
     }
 }
@@ -133,7 +161,7 @@ impl<'s> StringTable<'s> {
         }
 
         let string_offset = self.header.names_start() + offset.0 as usize;
-        let data = &self.stream.as_slice()[string_offset..self.header.names_end()];
+        let data = &self.data.as_slice()[string_offset..self.header.names_end()];
         ParseBuffer::from(data).parse_cstring()
     }
 }
@@ -14,7 +14,7 @@ use crate::msf::*;
 use crate::FallibleIterator;
 
 mod annotations;
-mod constants;
+pub(crate) mod constants;
 
 use self::constants::*;
 pub use self::constants::{CPUType, SourceLanguage};
@@ -256,6 +256,43 @@ impl<'t> SymbolData<'t> {
             Self::SeparatedCode(_) => None,
         }
     }
+
+    /// Returns the name of this symbol with MSVC name decoration undone, if it has a name.
+    ///
+    /// Decorated names, such as `?static_f_public@Baz@@SAXXZ`, are rendered as the C++ signature
+    /// they encode (`public: static void __cdecl Baz::static_f_public(void)`). Names that are not
+    /// decorated, or that cannot be undecorated, are returned unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let symbol_table = pdb.global_symbols()?;
+    ///
+    /// let mut symbols = symbol_table.iter();
+    /// while let Some(symbol) = symbols.next()? {
+    ///     if let Ok(data @ pdb::SymbolData::Public(_)) = symbol.parse() {
+    ///         println!("{}", data.name_demangled().unwrap_or_default());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    #[cfg(feature = "demangle")]
+    pub fn name_demangled(&self) -> Option<String> {
+        let name = self.name()?.to_string();
+        let demangled = if name.starts_with('?') {
+            msvc_demangler::demangle(&name, msvc_demangler::DemangleFlags::llvm()).ok()
+        } else {
+            None
+        };
+
+        Some(demangled.unwrap_or_else(|| name.into_owned()))
+    }
 }
 
 impl<'t> TryFromCtx<'t> for SymbolData<'t> {
@@ -641,7 +678,7 @@ pub struct ConstantSymbol<'t> {
     /// The type of this constant or metadata token.
     pub type_index: TypeIndex,
     /// The value of this constant.
-    pub value: Variant,
+    pub value: Variant<'t>,
     /// Name of the constant.
     pub name: RawString<'t>,
 }
@@ -1512,7 +1549,7 @@ impl<'t> TryFromCtx<'t, SymbolKind> for SeparatedCodeSymbol {
 /// #
 /// # fn test() -> pdb::Result<usize> {
 /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
-/// let mut pdb = pdb::PDB::open(file)?;
+/// let pdb = pdb::PDB::open(file)?;
 ///
 /// let symbol_table = pdb.global_symbols()?;
 /// let address_map = pdb.address_map()?;
@@ -0,0 +1,144 @@
+//! Parsing of the `/TMCache` named stream.
+//!
+//! When linking with type de-duplication, MSVC caches the type maps it computed for every module.
+//! These maps translate type and id indices from a module's type server (or its own `.debug$T`
+//! section) into the merged TPI and IPI streams of the PDB. The `/TMCache` stream records, for
+//! each module, which streams hold these cached maps.
+
+use crate::common::*;
+use crate::msf::Stream;
+
+/// Size of the fixed `/TMCache` header in bytes.
+const TM_CACHE_HEADER_SIZE: u32 = 12;
+
+/// Size of a single `/TMCache` entry in bytes.
+const TM_CACHE_ENTRY_SIZE: usize = 8;
+
+/// A cached type map of a single module, as recorded in the `/TMCache` stream.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TypeMergeCacheEntry {
+    /// Index of the module in the DBI module list.
+    pub module: usize,
+    /// Stream containing the cached type index map of this module.
+    ///
+    /// This is [`StreamIndex::none`] if no type map was cached.
+    pub type_map_stream: StreamIndex,
+    /// Stream containing the cached id index map of this module.
+    ///
+    /// This is [`StreamIndex::none`] if no id map was cached.
+    pub id_map_stream: StreamIndex,
+    /// Checksum of the type information the maps were computed from.
+    pub checksum: u32,
+}
+
+/// The type merge cache of a PDB, read from the `/TMCache` named stream.
+///
+/// Use [`PDB::type_merge_cache`](crate::PDB::type_merge_cache) to load it.
+#[derive(Debug)]
+pub struct TypeMergeCache {
+    version: u32,
+    entries: Vec<TypeMergeCacheEntry>,
+}
+
+impl TypeMergeCache {
+    pub(crate) fn parse(stream: Stream<'_>) -> Result<Self> {
+        Self::parse_bytes(stream.as_slice())
+    }
+
+    fn parse_bytes(data: &[u8]) -> Result<Self> {
+        let mut buf = ParseBuffer::from(data);
+        let version = buf.parse_u32()?;
+        let header_size = buf.parse_u32()?;
+        let count = buf.parse_u32()? as usize;
+
+        if header_size < TM_CACHE_HEADER_SIZE {
+            return Err(Error::InvalidStreamLength("TMCache"));
+        }
+        buf.take((header_size - TM_CACHE_HEADER_SIZE) as usize)?;
+
+        if buf.len() / TM_CACHE_ENTRY_SIZE < count {
+            return Err(Error::UnexpectedEof);
+        }
+
+        let mut entries = Vec::with_capacity(count);
+        for module in 0..count {
+            entries.push(TypeMergeCacheEntry {
+                module,
+                type_map_stream: buf.parse()?,
+                id_map_stream: buf.parse()?,
+                checksum: buf.parse_u32()?,
+            });
+        }
+
+        Ok(Self { version, entries })
+    }
+
+    /// The version of the cache format.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// All entries of the cache, in module order.
+    pub fn entries(&self) -> &[TypeMergeCacheEntry] {
+        &self.entries
+    }
+
+    /// Returns the cache entry of the module at the given index.
+    pub fn get(&self, module: usize) -> Option<&TypeMergeCacheEntry> {
+        self.entries.get(module)
+    }
+
+    /// Returns an iterator over the entries that have at least one cached map.
+    pub fn cached(&self) -> impl Iterator<Item = &TypeMergeCacheEntry> {
+        self.entries
+            .iter()
+            .filter(|e| !e.type_map_stream.is_none() || !e.id_map_stream.is_none())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(entries: &[(u16, u16, u32)]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&TM_CACHE_HEADER_SIZE.to_le_bytes());
+        data.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+        for &(tm, id, checksum) in entries {
+            data.extend_from_slice(&tm.to_le_bytes());
+            data.extend_from_slice(&id.to_le_bytes());
+            data.extend_from_slice(&checksum.to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse() {
+        let data = build(&[(12, 13, 0xdead_beef), (0xffff, 0xffff, 0)]);
+        let cache = TypeMergeCache::parse_bytes(&data).expect("parse");
+
+        assert_eq!(cache.version(), 1);
+        assert_eq!(cache.entries().len(), 2);
+        assert_eq!(
+            cache.get(0),
+            Some(&TypeMergeCacheEntry {
+                module: 0,
+                type_map_stream: StreamIndex(12),
+                id_map_stream: StreamIndex(13),
+                checksum: 0xdead_beef,
+            })
+        );
+        assert!(cache.get(1).unwrap().type_map_stream.is_none());
+        assert_eq!(cache.cached().count(), 1);
+    }
+
+    #[test]
+    fn test_parse_truncated() {
+        let data = build(&[(12, 13, 0)]);
+        assert!(matches!(
+            TypeMergeCache::parse_bytes(&data[..data.len() - 1]),
+            Err(Error::UnexpectedEof)
+        ));
+    }
+}
@@ -0,0 +1,120 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::common::*;
+use crate::tpi::data::TypeData;
+
+/// Maximum number of vectors of each kind retained by a [`TypeArena`].
+const MAX_RETAINED: usize = 64;
+
+/// A pool of vectors reused while parsing type records.
+///
+/// Parsing field lists and argument lists allocates a vector for every record, which adds up when
+/// parsing millions of records. Parse records with [`Type::parse_in`](crate::Type::parse_in) and
+/// hand them back with [`recycle`](Self::recycle) once they are no longer needed, so that the
+/// vectors of subsequent records reuse the memory of previous ones.
+///
+/// Using an arena is optional; [`Type::parse`](crate::Type::parse) allocates fresh vectors.
+///
+/// # Example
+///
+/// ```
+/// # use pdb::FallibleIterator;
+/// # fn test() -> pdb::Result<usize> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(file)?;
+/// let type_information = pdb.type_information()?;
+///
+/// let mut arena = pdb::TypeArena::new();
+/// let mut fields = 0;
+///
+/// let mut iter = type_information.iter();
+/// while let Some(typ) = iter.next()? {
+///     if let Ok(data) = typ.parse_in(&mut arena) {
+///         if let pdb::TypeData::FieldList(ref list) = data {
+///             fields += list.fields.len();
+///         }
+///         arena.recycle(data);
+///     }
+/// }
+/// # Ok(fields)
+/// # }
+/// # assert!(test().expect("test") > 0);
+/// ```
+#[derive(Debug, Default)]
+pub struct TypeArena<'t> {
+    fields: Vec<Vec<TypeData<'t>>>,
+    indices: Vec<Vec<TypeIndex>>,
+}
+
+impl<'t> TypeArena<'t> {
+    /// Creates an empty arena.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the vectors of a parsed record to the arena.
+    ///
+    /// Vectors of field lists and argument lists are cleared and retained for subsequent calls to
+    /// [`Type::parse_in`](crate::Type::parse_in). Other records do not hold reusable vectors.
+    pub fn recycle(&mut self, data: TypeData<'t>) {
+        match data {
+            TypeData::FieldList(mut list) if self.fields.len() < MAX_RETAINED => {
+                list.fields.clear();
+                self.fields.push(list.fields);
+            }
+            TypeData::ArgumentList(mut list) if self.indices.len() < MAX_RETAINED => {
+                list.arguments.clear();
+                self.indices.push(list.arguments);
+            }
+            _ => {}
+        }
+    }
+
+    /// Takes an empty vector for the fields of a field list.
+    pub(crate) fn fields(&mut self) -> Vec<TypeData<'t>> {
+        self.fields.pop().unwrap_or_default()
+    }
+
+    /// Takes an empty vector for the type indices of a list.
+    pub(crate) fn indices(&mut self) -> Vec<TypeIndex> {
+        self.indices.pop().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tpi::data::parse_type_data_in;
+
+    #[test]
+    fn test_reuse_argument_list() {
+        // LF_ARGLIST with two arguments
+        let record = [
+            0x01, 0x12, 0x02, 0x00, 0x00, 0x00, 0x74, 0x00, 0x00, 0x00, 0x01, 0x10, 0x00, 0x00,
+        ];
+
+        let mut arena = TypeArena::new();
+        let first =
+            parse_type_data_in(&mut ParseBuffer::from(&record[..]), &mut arena).expect("parse");
+        let pointer = match first {
+            TypeData::ArgumentList(ref list) => list.arguments.as_ptr(),
+            _ => panic!("unexpected type {:?}", first),
+        };
+
+        arena.recycle(first);
+        let second =
+            parse_type_data_in(&mut ParseBuffer::from(&record[..]), &mut arena).expect("parse");
+        match second {
+            TypeData::ArgumentList(ref list) => {
+                assert_eq!(list.arguments, [TypeIndex(0x74), TypeIndex(0x1001)]);
+                assert_eq!(list.arguments.as_ptr(), pointer);
+            }
+            _ => panic!("unexpected type {:?}", second),
+        }
+    }
+}
@@ -0,0 +1,703 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+
+use crate::common::*;
+use crate::tpi::data::*;
+use crate::tpi::enums::EnumValue;
+use crate::tpi::layout::RecordLayout;
+use crate::tpi::primitive::{PrimitiveKind, PrimitiveType};
+use crate::tpi::size::{TypeSizer, MAX_DEPTH};
+
+/// Keywords that cannot be used as identifiers in generated code.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "union", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// A field of a generated structure or union.
+struct Field {
+    offset: u64,
+    size: u64,
+    alignment: u64,
+    name: String,
+    rust_type: String,
+    doc: Option<String>,
+}
+
+/// Generates `#[repr(C)]` Rust definitions for the types of a [`TypeInformation`] stream.
+///
+/// Every type added to the generator is translated into a Rust type, and definitions are emitted
+/// for all structures, classes, unions and enumerations it refers to, directly or through
+/// pointers. Types are translated as follows:
+///
+///  - Structures and classes become `#[repr(C)]` structs. Base classes and virtual function
+///    table pointers are emitted as fields, and gaps between fields are filled with explicit
+///    padding, so that every field has the offset recorded in the PDB. Records with misaligned
+///    fields become `#[repr(C, packed)]`. Virtual base table pointers and virtual base class
+///    subobjects are emitted as padding.
+///  - Unions become `#[repr(C)]` unions.
+///  - Adjacent bitfields sharing a storage unit are emitted as a single field of the underlying
+///    type, documented with the bit ranges of the original members.
+///  - Members overlapping a previous member, such as the members of anonymous unions, are omitted
+///    and noted in a comment.
+///  - Enumerations become enums with the underlying type as representation. Enumerators with
+///    duplicate values are emitted as associated constants.
+///  - Pointers and references become raw pointers, and pointers to functions become optional
+///    `unsafe extern` function pointers. Pointers to members are emitted as opaque byte arrays.
+///  - Types that are only declared become opaque structs.
+///
+/// C++ names are turned into identifiers by replacing all characters that are not valid in
+/// identifiers with underscores. Every struct is followed by a compile-time assertion of its
+/// size. As pointers have the size of the machine that the PDB was created for, the generated code
+/// only compiles for targets with the same pointer size.
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(file)?;
+/// let type_information = pdb.type_information()?;
+/// let sizer = type_information.sizer()?;
+///
+/// let mut generator = pdb::RustGenerator::new(&sizer);
+/// let name = generator.add(pdb::TypeIndex(0x1064))?;
+/// assert_eq!(name, "Baz");
+///
+/// let code = generator.generate()?;
+/// assert!(code.contains("pub struct Baz {"));
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+///
+/// [`TypeInformation`]: crate::TypeInformation
+#[derive(Debug)]
+pub struct RustGenerator<'a, 't> {
+    sizer: &'a TypeSizer<'t>,
+    names: HashMap<TypeIndex, String>,
+    used_names: HashSet<String>,
+    pending: VecDeque<TypeIndex>,
+}
+
+impl<'a, 't> RustGenerator<'a, 't> {
+    /// Creates a generator for the types known by the given sizer.
+    pub fn new(sizer: &'a TypeSizer<'t>) -> Self {
+        Self {
+            sizer,
+            names: HashMap::new(),
+            used_names: HashSet::new(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Adds a type to the generated code, and returns the Rust type representing it.
+    ///
+    /// Definitions for the type and all types it refers to are emitted by
+    /// [`generate`](Self::generate).
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnsizedType(index)` if the type cannot be represented, such as a procedure
+    /// * `Error::TypeNotFound(index)` if the type or one of the types it refers to does not exist
+    /// * `Error::UnimplementedTypeKind(kind)` if a type record is not understood by this library
+    pub fn add(&mut self, index: TypeIndex) -> Result<String> {
+        self.rust_type(index)
+    }
+
+    /// Emits the definitions of all added types and the types they refer to.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnsizedType(index)` if a member of a record cannot be represented
+    /// * `Error::TypeNotFound(index)` if a type does not exist
+    /// * `Error::UnimplementedTypeKind(kind)` if a type record is not understood by this library
+    pub fn generate(mut self) -> Result<String> {
+        let mut output = String::new();
+
+        while let Some(index) = self.pending.pop_front() {
+            if !output.is_empty() {
+                output.push('\n');
+            }
+
+            let name = self.names[&index].clone();
+            match self.sizer.parse(index)? {
+                TypeData::Enumeration(data) => self.emit_enum(&mut output, &name, &data)?,
+                TypeData::Class(data) if data.properties.forward_reference() => {
+                    emit_opaque(&mut output, &name);
+                }
+                TypeData::Union(data) if data.properties.forward_reference() => {
+                    emit_opaque(&mut output, &name);
+                }
+                TypeData::Union(_) => {
+                    let layout = self.sizer.layout_of(index)?;
+                    self.emit_union(&mut output, &name, &layout)?;
+                }
+                _ => {
+                    let layout = self.sizer.layout_of(index)?;
+                    self.emit_struct(&mut output, &name, &layout)?;
+                }
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Returns the Rust type of a value of the given type.
+    fn rust_type(&mut self, index: TypeIndex) -> Result<String> {
+        let mut current = index;
+
+        for _ in 0..MAX_DEPTH {
+            let next = match self.sizer.parse(current)? {
+                TypeData::Primitive(primitive) => return self.primitive_type(current, primitive),
+                TypeData::Pointer(pointer) => return self.pointer_type(current, &pointer),
+                TypeData::Array(array) => return self.array_type(current, &array),
+                TypeData::Class(data) => return self.named_type(current, data.name),
+                TypeData::Union(data) => return self.named_type(current, data.name),
+                TypeData::Enumeration(data) => return self.named_type(current, data.name),
+                TypeData::Modifier(modifier) => modifier.underlying_type,
+                TypeData::Alias(alias) => alias.underlying_type,
+                _ => return Err(Error::UnsizedType(current.0)),
+            };
+
+            current = next;
+        }
+
+        Err(Error::UnsizedType(index.0))
+    }
+
+    /// Returns the Rust type that a pointer to the given type points to.
+    fn pointee_type(&mut self, index: TypeIndex) -> Result<String> {
+        match self.sizer.parse(index)? {
+            TypeData::Primitive(PrimitiveType {
+                kind: PrimitiveKind::Void | PrimitiveKind::NoType,
+                indirection: None,
+            }) => Ok("core::ffi::c_void".to_string()),
+            TypeData::Modifier(modifier) => self.pointee_type(modifier.underlying_type),
+            _ => match self.rust_type(index) {
+                Ok(rust_type) => Ok(rust_type),
+                // pointers to types without a representation are still valid pointers
+                Err(Error::UnsizedType(_)) => Ok("core::ffi::c_void".to_string()),
+                Err(e) => Err(e),
+            },
+        }
+    }
+
+    fn primitive_type(&mut self, index: TypeIndex, primitive: PrimitiveType) -> Result<String> {
+        if primitive.indirection.is_some() {
+            let pointee = TypeIndex(index.0 & 0xff);
+            return Ok(format!("*mut {}", self.pointee_type(pointee)?));
+        }
+
+        Ok(match primitive.kind {
+            PrimitiveKind::Char | PrimitiveKind::RChar | PrimitiveKind::I8 => "i8",
+            PrimitiveKind::UChar | PrimitiveKind::U8 | PrimitiveKind::Char8 => "u8",
+            PrimitiveKind::Short | PrimitiveKind::I16 => "i16",
+            PrimitiveKind::WChar
+            | PrimitiveKind::RChar16
+            | PrimitiveKind::UShort
+            | PrimitiveKind::U16
+            | PrimitiveKind::F16
+            | PrimitiveKind::Bool16 => "u16",
+            PrimitiveKind::Long | PrimitiveKind::I32 | PrimitiveKind::HRESULT => "i32",
+            PrimitiveKind::RChar32
+            | PrimitiveKind::ULong
+            | PrimitiveKind::U32
+            | PrimitiveKind::Bool32
+            | PrimitiveKind::Bool32FF => "u32",
+            PrimitiveKind::Quad | PrimitiveKind::I64 => "i64",
+            PrimitiveKind::UQuad | PrimitiveKind::U64 | PrimitiveKind::Bool64 => "u64",
+            PrimitiveKind::Octa | PrimitiveKind::I128 => "i128",
+            PrimitiveKind::UOcta | PrimitiveKind::U128 => "u128",
+            PrimitiveKind::F32 | PrimitiveKind::F32PP => "f32",
+            PrimitiveKind::F64 => "f64",
+            PrimitiveKind::Bool8 => "bool",
+            _ => return Ok(format!("[u8; {}]", self.sizer.size_of(index)?)),
+        }
+        .to_string())
+    }
+
+    fn pointer_type(&mut self, index: TypeIndex, pointer: &PointerType) -> Result<String> {
+        if pointer.attributes.pointer_to_member() {
+            return Ok(format!("[u8; {}]", self.sizer.size_of(index)?));
+        }
+
+        let (constant, target) = match self.sizer.parse(pointer.underlying_type)? {
+            TypeData::Modifier(modifier) => (modifier.constant, modifier.underlying_type),
+            _ => (false, pointer.underlying_type),
+        };
+
+        if let TypeData::Procedure(procedure) = self.sizer.parse(target)? {
+            return self.function_pointer(&procedure);
+        }
+
+        let pointee = self.pointee_type(target)?;
+        Ok(if constant {
+            format!("*const {}", pointee)
+        } else {
+            format!("*mut {}", pointee)
+        })
+    }
+
+    fn function_pointer(&mut self, procedure: &ProcedureType) -> Result<String> {
+        let abi = match procedure.attributes.calling_convention() {
+            Some(CallingConvention::NearFast) | Some(CallingConvention::FarFast) => "fastcall",
+            Some(CallingConvention::NearStd) | Some(CallingConvention::FarStd) => "system",
+            Some(CallingConvention::ThisCall) => "thiscall",
+            _ => "C",
+        };
+
+        let mut arguments = Vec::new();
+        if let TypeData::ArgumentList(list) = self.sizer.parse(procedure.argument_list)? {
+            for argument in list.arguments {
+                match self.sizer.parse(argument)? {
+                    // a trailing `NoType` denotes variadic arguments
+                    TypeData::Primitive(PrimitiveType {
+                        kind: PrimitiveKind::NoType,
+                        indirection: None,
+                    }) => arguments.push("...".to_string()),
+                    _ => arguments.push(self.rust_type(argument)?),
+                }
+            }
+        }
+
+        // variadic functions need at least one named argument
+        if arguments.len() == 1 && arguments[0] == "..." {
+            arguments.clear();
+        }
+
+        let return_type = match procedure.return_type {
+            Some(index) => match self.sizer.parse(index)? {
+                TypeData::Primitive(PrimitiveType {
+                    kind: PrimitiveKind::Void,
+                    indirection: None,
+                }) => String::new(),
+                _ => format!(" -> {}", self.rust_type(index)?),
+            },
+            None => String::new(),
+        };
+
+        Ok(format!(
+            "Option<unsafe extern \"{}\" fn({}){}>",
+            abi,
+            arguments.join(", "),
+            return_type
+        ))
+    }
+
+    fn array_type(&mut self, index: TypeIndex, array: &ArrayType) -> Result<String> {
+        let mut rust_type = self.rust_type(array.element_type)?;
+        let mut element_size = self.sizer.size_of(array.element_type)?;
+
+        // dimensions are the sizes in bytes of the nested arrays, from the innermost outwards
+        for &size in &array.dimensions {
+            let count = size.checked_div(element_size).unwrap_or(0);
+            rust_type = format!("[{}; {}]", rust_type, count);
+            element_size = size;
+        }
+
+        if array.dimensions.is_empty() {
+            return Err(Error::UnsizedType(index.0));
+        }
+
+        Ok(rust_type)
+    }
+
+    /// Returns the name of a record or enumeration, and queues its definition.
+    fn named_type(&mut self, index: TypeIndex, name: RawString<'t>) -> Result<String> {
+        let definition = self.sizer.definition(index)?.unwrap_or(index);
+        if let Some(name) = self.names.get(&definition) {
+            return Ok(name.clone());
+        }
+
+        let mut identifier = sanitize(&name.to_string());
+        if self.used_names.contains(&identifier) || KEYWORDS.contains(&identifier.as_str()) {
+            identifier = format!("{}_{:x}", identifier, definition.0);
+        }
+
+        self.used_names.insert(identifier.clone());
+        self.names.insert(definition, identifier.clone());
+        self.pending.push_back(definition);
+        Ok(identifier)
+    }
+
+    fn emit_struct(
+        &mut self,
+        output: &mut String,
+        name: &str,
+        layout: &RecordLayout<'t>,
+    ) -> Result<()> {
+        let mut fields = Vec::new();
+
+        if let Some(ref vfptr) = layout.vfptr {
+            fields.push(Field {
+                offset: vfptr.start,
+                size: vfptr.end - vfptr.start,
+                alignment: vfptr.end - vfptr.start,
+                name: "vfptr".to_string(),
+                rust_type: "*const *const core::ffi::c_void".to_string(),
+                doc: None,
+            });
+        }
+
+        for (i, base) in layout.base_classes.iter().enumerate() {
+            let name = match layout.base_classes.len() {
+                1 => "base".to_string(),
+                _ => format!("base_{}", i),
+            };
+
+            fields.push(Field {
+                offset: base.offset,
+                size: base.size,
+                alignment: base.alignment,
+                name,
+                rust_type: self.rust_type(base.base_class)?,
+                doc: None,
+            });
+        }
+
+        fields.extend(self.member_fields(layout)?);
+        fields.sort_by_key(|field| field.offset);
+
+        let packed = fields
+            .iter()
+            .any(|field| !field.offset.is_multiple_of(field.alignment.max(1)))
+            || !layout.size.is_multiple_of(layout.alignment.max(1));
+
+        writeln!(output, "#[repr(C{})]", if packed { ", packed" } else { "" }).ok();
+        writeln!(output, "#[derive(Clone, Copy)]").ok();
+        writeln!(output, "#[allow(non_camel_case_types, non_snake_case)]").ok();
+        writeln!(output, "pub struct {} {{", name).ok();
+
+        let mut position = 0;
+        let mut padding = 0;
+        let mut used = HashSet::new();
+
+        for field in fields {
+            if field.offset < position {
+                writeln!(
+                    output,
+                    "    // `{}` at offset {} overlaps the previous field",
+                    field.name, field.offset
+                )
+                .ok();
+                continue;
+            }
+
+            if field.offset > position {
+                writeln!(
+                    output,
+                    "    _padding_{}: [u8; {}],",
+                    padding,
+                    field.offset - position
+                )
+                .ok();
+                padding += 1;
+            }
+
+            emit_field(output, &field, &mut used);
+            position = field.offset + field.size;
+        }
+
+        // virtual base classes are placed after the fields
+        if layout.size > position {
+            writeln!(
+                output,
+                "    _padding_{}: [u8; {}],",
+                padding,
+                layout.size - position
+            )
+            .ok();
+        }
+
+        writeln!(output, "}}").ok();
+        writeln!(
+            output,
+            "const _: () = assert!(core::mem::size_of::<{}>() == {});",
+            name, layout.size
+        )
+        .ok();
+
+        Ok(())
+    }
+
+    fn emit_union(
+        &mut self,
+        output: &mut String,
+        name: &str,
+        layout: &RecordLayout<'t>,
+    ) -> Result<()> {
+        let fields = self.member_fields(layout)?;
+        let largest = fields.iter().map(|field| field.offset + field.size).max();
+
+        writeln!(output, "#[repr(C)]").ok();
+        writeln!(output, "#[derive(Clone, Copy)]").ok();
+        writeln!(output, "#[allow(non_camel_case_types, non_snake_case)]").ok();
+        writeln!(output, "pub union {} {{", name).ok();
+
+        let mut used = HashSet::new();
+        for field in &fields {
+            if field.offset != 0 {
+                writeln!(
+                    output,
+                    "    // `{}` at offset {} is not supported in unions",
+                    field.name, field.offset
+                )
+                .ok();
+                continue;
+            }
+
+            emit_field(output, field, &mut used);
+        }
+
+        if largest.is_none_or(|largest| largest < layout.size) {
+            writeln!(output, "    _size: [u8; {}],", layout.size).ok();
+        }
+
+        writeln!(output, "}}").ok();
+        writeln!(
+            output,
+            "const _: () = assert!(core::mem::size_of::<{}>() == {});",
+            name, layout.size
+        )
+        .ok();
+
+        Ok(())
+    }
+
+    /// Returns the fields for the data members of a record, merging bitfields sharing storage.
+    fn member_fields(&mut self, layout: &RecordLayout<'t>) -> Result<Vec<Field>> {
+        let mut fields: Vec<Field> = Vec::new();
+        // the storage unit of the last bitfield, by offset and underlying type
+        let mut storage: Option<(u64, TypeIndex)> = None;
+
+        for member in &layout.members {
+            let member_name = member.name.to_string();
+
+            let bitfield = match member.bitfield {
+                Some(bitfield) => bitfield,
+                None => {
+                    storage = None;
+                    fields.push(Field {
+                        offset: member.offset,
+                        size: member.size,
+                        alignment: member.alignment,
+                        name: sanitize(&member_name),
+                        rust_type: self.rust_type(member.field_type)?,
+                        doc: None,
+                    });
+                    continue;
+                }
+            };
+
+            let underlying_type = match self.sizer.parse(member.field_type)? {
+                TypeData::Bitfield(data) => data.underlying_type,
+                _ => member.field_type,
+            };
+
+            let bits = format!(
+                "`{}`: bits {}..{}",
+                member_name,
+                bitfield.position,
+                u32::from(bitfield.position) + u32::from(bitfield.length)
+            );
+
+            if storage == Some((member.offset, underlying_type)) {
+                if let Some(field) = fields.last_mut() {
+                    if let Some(ref mut doc) = field.doc {
+                        doc.push_str(", ");
+                        doc.push_str(&bits);
+                    }
+                    continue;
+                }
+            }
+
+            storage = Some((member.offset, underlying_type));
+            fields.push(Field {
+                offset: member.offset,
+                size: member.size,
+                alignment: member.alignment,
+                name: format!("_bitfield_{}", member.offset),
+                rust_type: self.rust_type(underlying_type)?,
+                doc: Some(format!("Bitfields {}", bits)),
+            });
+        }
+
+        Ok(fields)
+    }
+
+    fn emit_enum(
+        &mut self,
+        output: &mut String,
+        name: &str,
+        data: &EnumerationType<'t>,
+    ) -> Result<()> {
+        let kind = data.underlying_kind().unwrap_or(PrimitiveKind::I32);
+        let representation = self.rust_type(data.underlying_type)?;
+        let integer = matches!(
+            representation.as_str(),
+            "i8" | "u8" | "i16" | "u16" | "i32" | "u32" | "i64" | "u64" | "i128" | "u128"
+        );
+
+        let mut variants = Vec::new();
+        let mut aliases = Vec::new();
+        let mut used = HashSet::new();
+        let mut values = HashMap::new();
+
+        let mut fields = Some(data.fields);
+        while let Some(index) = fields {
+            let list = match self.sizer.parse(index)? {
+                TypeData::FieldList(list) => list,
+                _ => return Err(Error::UnimplementedFeature("unexpected field list type")),
+            };
+
+            for field in list.fields {
+                if let TypeData::Enumerate(enumerate) = field {
+                    let value = EnumValue::from_variant(enumerate.value, kind)
+                        .ok_or(Error::UnimplementedFeature("non-integer enumerator values"))?;
+
+                    let identifier = unique(sanitize(&enumerate.name.to_string()), &mut used);
+                    match values.get(&value) {
+                        Some(existing) => aliases.push((identifier, String::clone(existing))),
+                        None => {
+                            values.insert(value, identifier.clone());
+                            variants.push((identifier, value));
+                        }
+                    }
+                }
+            }
+
+            fields = list.continuation;
+        }
+
+        if variants.is_empty() || !integer {
+            writeln!(output, "#[repr(transparent)]").ok();
+            writeln!(output, "#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]").ok();
+            writeln!(output, "#[allow(non_camel_case_types)]").ok();
+            writeln!(output, "pub struct {}(pub {});", name, representation).ok();
+            return Ok(());
+        }
+
+        writeln!(output, "#[repr({})]", representation).ok();
+        writeln!(output, "#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]").ok();
+        writeln!(output, "#[allow(non_camel_case_types)]").ok();
+        writeln!(output, "pub enum {} {{", name).ok();
+        for (identifier, value) in &variants {
+            writeln!(output, "    {} = {},", identifier, value).ok();
+        }
+        writeln!(output, "}}").ok();
+
+        if !aliases.is_empty() {
+            writeln!(output, "#[allow(non_upper_case_globals)]").ok();
+            writeln!(output, "impl {} {{", name).ok();
+            for (identifier, existing) in &aliases {
+                writeln!(
+                    output,
+                    "    pub const {}: Self = Self::{};",
+                    identifier, existing
+                )
+                .ok();
+            }
+            writeln!(output, "}}").ok();
+        }
+
+        Ok(())
+    }
+}
+
+fn emit_field(output: &mut String, field: &Field, used: &mut HashSet<String>) {
+    if let Some(ref doc) = field.doc {
+        writeln!(output, "    /// {}", doc).ok();
+    }
+
+    let name = unique(field.name.clone(), used);
+    writeln!(output, "    pub {}: {},", name, field.rust_type).ok();
+}
+
+fn emit_opaque(output: &mut String, name: &str) {
+    writeln!(output, "#[repr(C)]").ok();
+    writeln!(output, "#[allow(non_camel_case_types)]").ok();
+    writeln!(output, "pub struct {} {{", name).ok();
+    writeln!(output, "    _opaque: [u8; 0],").ok();
+    writeln!(output, "}}").ok();
+}
+
+/// Turns a C++ name into a valid Rust identifier.
+fn sanitize(name: &str) -> String {
+    let mut identifier = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            identifier.push(c);
+        } else if !identifier.ends_with('_') {
+            identifier.push('_');
+        }
+    }
+
+    let identifier = identifier.trim_matches('_');
+    let mut identifier = if identifier.is_empty() {
+        "anonymous".to_string()
+    } else {
+        identifier.to_string()
+    };
+
+    if identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+
+    if KEYWORDS.contains(&identifier.as_str()) {
+        identifier.push('_');
+    }
+
+    identifier
+}
+
+/// Makes an identifier unique among the given identifiers.
+fn unique(identifier: String, used: &mut HashSet<String>) -> String {
+    let mut candidate = identifier.clone();
+    let mut suffix = 1;
+    while used.contains(&candidate) {
+        candidate = format!("{}_{}", identifier, suffix);
+        suffix += 1;
+    }
+
+    used.insert(candidate.clone());
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(sanitize("Baz"), "Baz");
+        assert_eq!(sanitize("std::exception"), "std_exception");
+        assert_eq!(sanitize("foo<int, bar *>"), "foo_int_bar");
+        assert_eq!(sanitize("<unnamed-tag>"), "unnamed_tag");
+        assert_eq!(
+            sanitize("`anonymous namespace'::x"),
+            "anonymous_namespace_x"
+        );
+        assert_eq!(sanitize("type"), "type_");
+        assert_eq!(sanitize("3d"), "_3d");
+        assert_eq!(sanitize("::"), "anonymous");
+    }
+
+    #[test]
+    fn test_unique() {
+        let mut used = HashSet::new();
+        assert_eq!(unique("a".to_string(), &mut used), "a");
+        assert_eq!(unique("a".to_string(), &mut used), "a_1");
+        assert_eq!(unique("a".to_string(), &mut used), "a_2");
+    }
+}
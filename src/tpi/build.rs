@@ -0,0 +1,111 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::common::*;
+use crate::tpi::id::IdData;
+use crate::tpi::IdFinder;
+
+/// Positions of the arguments of an `LF_BUILDINFO` record, see `CV_BuildInfo_e` in cvinfo.h.
+const CURRENT_DIRECTORY: usize = 0;
+const BUILD_TOOL: usize = 1;
+const SOURCE_FILE: usize = 2;
+const PDB_FILE: usize = 3;
+const COMMAND_ARGUMENTS: usize = 4;
+
+/// The tool, paths and command line used to compile a module.
+///
+/// This is the resolved form of a [`BuildInfoId`](crate::BuildInfoId), as returned by
+/// [`IdFinder::build_info`](crate::ItemFinder::build_info). The compiler emits one per object
+/// file and references it from an `S_BUILDINFO` symbol in the module, see
+/// [`ModuleInfo::build_info_id`](crate::ModuleInfo::build_info_id).
+///
+/// All strings are converted to UTF-8 lossily. Arguments that are missing from the record are
+/// `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildInfo {
+    /// The working directory of the compiler.
+    pub current_directory: Option<String>,
+    /// The path of the compiler executable.
+    pub build_tool: Option<String>,
+    /// The path of the compiled source file, usually relative to the working directory.
+    pub source_file: Option<String>,
+    /// The path of the PDB the compiler wrote type information to, such as `vc140.pdb`.
+    pub pdb_file: Option<String>,
+    /// The command line arguments passed to the compiler.
+    ///
+    /// The compiler splits long command lines into several string records, which are concatenated
+    /// here. Arguments from response files and environment variables such as `CL` are included.
+    pub command_arguments: Option<String>,
+}
+
+impl<'t> IdFinder<'t> {
+    /// Resolves an `LF_BUILDINFO` record into its strings.
+    ///
+    /// All string records referenced by the build information must be known by this `IdFinder`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let id_information = pdb.id_information()?;
+    /// let mut id_finder = id_information.finder();
+    /// let mut iter = id_information.iter();
+    /// while let Some(_) = iter.next()? {
+    ///     id_finder.update(&iter);
+    /// }
+    ///
+    /// let debug_information = pdb.debug_information()?;
+    /// let mut modules = debug_information.modules()?;
+    /// while let Some(module) = modules.next()? {
+    ///     let info = match pdb.module_info(&module)? {
+    ///         Some(info) => info,
+    ///         None => continue,
+    ///     };
+    ///
+    ///     if let Some(id) = info.build_info_id()? {
+    ///         let build_info = id_finder.build_info(id)?;
+    ///         println!("{}: {:?}", module.module_name(), build_info.command_arguments);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedIdKind(index)` if the id is not a build information record, or one of
+    ///   its arguments is not a string
+    /// * `Error::TypeNotFound(index)` if the id or one of the strings does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if an id is not yet known by this finder
+    pub fn build_info(&self, index: IdIndex) -> Result<BuildInfo> {
+        let arguments = match self.find(index)?.parse()? {
+            IdData::BuildInfo(data) => data.arguments,
+            _ => return Err(Error::UnexpectedIdKind(index.0)),
+        };
+
+        let argument = |position: usize| -> Result<Option<String>> {
+            let index = match arguments.get(position) {
+                Some(&index) if index.0 != 0 => index,
+                _ => return Ok(None),
+            };
+            let string = self.resolve_string(index)?;
+            Ok(Some(String::from_utf8_lossy(&string).into_owned()))
+        };
+
+        Ok(BuildInfo {
+            current_directory: argument(CURRENT_DIRECTORY)?,
+            build_tool: argument(BUILD_TOOL)?,
+            source_file: argument(SOURCE_FILE)?,
+            pdb_file: argument(PDB_FILE)?,
+            command_arguments: argument(COMMAND_ARGUMENTS)?,
+        })
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::HashSet;
+
+use crate::common::*;
+use crate::tpi::data::TypeData;
+use crate::tpi::TypeFinder;
+
+/// Placeholder replacing the indices of non-primitive types while comparing records.
+const PLACEHOLDER: TypeIndex = TypeIndex(u32::MAX);
+
+/// Decides whether types are structurally identical, regardless of their type indices.
+///
+/// Two types are structurally identical if their records are equal after replacing all references
+/// to other type records with the structure of those records. Primitive types are compared by
+/// their index, as these are fixed. Since forward references only consist of a name, two forward
+/// references with the same name and properties are identical, which also terminates the
+/// comparison of recursive types.
+///
+/// The types may come from two different PDBs. Results are cached, so reuse a comparator to
+/// compare many types from the same pair of streams.
+///
+/// # Example
+///
+/// ```
+/// # use pdb::FallibleIterator;
+/// # fn test() -> pdb::Result<()> {
+/// let open = || -> pdb::Result<_> {
+///     let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+///     pdb::PDB::open(file)
+/// };
+///
+/// let left_pdb = open()?;
+/// let left_types = left_pdb.type_information()?;
+/// let mut left = left_types.finder();
+/// let mut iter = left_types.iter();
+/// while let Some(_) = iter.next()? {
+///     left.update(&iter);
+/// }
+///
+/// let right_pdb = open()?;
+/// let right_types = right_pdb.type_information()?;
+/// let mut right = right_types.finder();
+/// let mut iter = right_types.iter();
+/// while let Some(_) = iter.next()? {
+///     right.update(&iter);
+/// }
+///
+/// let mut comparator = pdb::TypeComparator::new(&left, &right);
+/// assert!(comparator.equivalent(pdb::TypeIndex(0x1064), pdb::TypeIndex(0x1064))?);
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Debug)]
+pub struct TypeComparator<'a, 'l, 'r> {
+    left: &'a TypeFinder<'l>,
+    right: &'a TypeFinder<'r>,
+    equivalent: HashSet<(TypeIndex, TypeIndex)>,
+}
+
+impl<'a, 'l, 'r> TypeComparator<'a, 'l, 'r> {
+    /// Creates a comparator for types from the `left` and `right` streams.
+    ///
+    /// Both finders must be fully populated.
+    pub fn new(left: &'a TypeFinder<'l>, right: &'a TypeFinder<'r>) -> Self {
+        Self {
+            left,
+            right,
+            equivalent: HashSet::new(),
+        }
+    }
+
+    /// Returns whether the type `left` of the left stream is structurally identical to the type
+    /// `right` of the right stream.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::TypeNotFound(index)` if one of the types or a type they refer to does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if a type is not known by its finder
+    pub fn equivalent(&mut self, left: TypeIndex, right: TypeIndex) -> Result<bool> {
+        // Pairs under comparison are assumed to be identical. This is only confirmed once all
+        // pairs reachable from them have been compared successfully.
+        let mut assumed = HashSet::new();
+        let mut pending = vec![(left, right)];
+
+        while let Some(pair) = pending.pop() {
+            if self.equivalent.contains(&pair) || !assumed.insert(pair) {
+                continue;
+            }
+
+            match self.compare_records(pair.0, pair.1)? {
+                Some(references) => pending.extend(references),
+                None => return Ok(false),
+            }
+        }
+
+        self.equivalent.extend(assumed);
+        Ok(true)
+    }
+
+    /// Compares two records without following their references.
+    ///
+    /// Returns the pairs of referenced types that must be identical for the records to be
+    /// identical, or `None` if the records differ.
+    fn compare_records(
+        &self,
+        left: TypeIndex,
+        right: TypeIndex,
+    ) -> Result<Option<Vec<(TypeIndex, TypeIndex)>>> {
+        let left_primitive = left.0 < self.left.minimum_index;
+        let right_primitive = right.0 < self.right.minimum_index;
+        if left_primitive || right_primitive {
+            let identical = left_primitive && right_primitive && left == right;
+            return Ok(if identical { Some(Vec::new()) } else { None });
+        }
+
+        let left_item = self.left.find(left)?;
+        let right_item = self.right.find(right)?;
+
+        let (mut left_data, mut right_data) = match (left_item.parse(), right_item.parse()) {
+            (Ok(left_data), Ok(right_data)) => (left_data, right_data),
+            // records that are not understood can only be compared byte by byte
+            (Err(Error::UnimplementedTypeKind(_)), _)
+            | (_, Err(Error::UnimplementedTypeKind(_))) => {
+                let identical = left_item.data == right_item.data;
+                return Ok(if identical { Some(Vec::new()) } else { None });
+            }
+            (Err(e), _) | (_, Err(e)) => return Err(e),
+        };
+
+        let left_references = normalize(&mut left_data, self.left.minimum_index);
+        let right_references = normalize(&mut right_data, self.right.minimum_index);
+
+        if left_data != right_data || left_references.len() != right_references.len() {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            left_references.into_iter().zip(right_references).collect(),
+        ))
+    }
+}
+
+/// Replaces all references to type records with a placeholder and returns the replaced indices.
+fn normalize(data: &mut TypeData<'_>, minimum_index: u32) -> Vec<TypeIndex> {
+    let mut references = Vec::new();
+    data.visit_type_indices_mut(&mut |index| {
+        if index.0 >= minimum_index {
+            references.push(*index);
+            *index = PLACEHOLDER;
+        }
+    });
+    references
+}
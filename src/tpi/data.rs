@@ -7,13 +7,20 @@
 
 #![allow(missing_docs)]
 
+use std::convert::{TryFrom, TryInto};
+
+use uuid::Uuid;
+
 use crate::common::*;
+use crate::pdbi::PdbIdentifier;
+use crate::tpi::arena::TypeArena;
 use crate::tpi::constants::*;
 use crate::tpi::primitive::*;
 
 /// Encapsulates parsed data about a `Type`.
 #[non_exhaustive]
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum TypeData<'t> {
     Primitive(PrimitiveType),
     Class(ClassType<'t>),
@@ -23,6 +30,7 @@ pub enum TypeData<'t> {
     Method(MethodType<'t>),
     StaticMember(StaticMemberType<'t>),
     Nested(NestedType<'t>),
+    MemberModify(MemberModifyType<'t>),
     BaseClass(BaseClassType),
     VirtualBaseClass(VirtualBaseClassType),
     VirtualFunctionTablePointer(VirtualFunctionTablePointerType),
@@ -32,11 +40,24 @@ pub enum TypeData<'t> {
     Enumeration(EnumerationType<'t>),
     Enumerate(EnumerateType<'t>),
     Array(ArrayType),
+    DimensionedArray(DimensionedArrayType<'t>),
     Union(UnionType<'t>),
     Bitfield(BitfieldType),
     FieldList(FieldList<'t>),
     ArgumentList(ArgumentList),
     MethodList(MethodList),
+    VirtualTableShape(VirtualTableShapeType),
+    VirtualFunctionTable(VirtualFunctionTableType<'t>),
+    VirtualFunctionTablePath(VirtualFunctionTablePathType),
+    Label(LabelType),
+    Alias(AliasType<'t>),
+    FriendFunction(FriendFunctionType<'t>),
+    FriendClass(FriendClassType),
+    Managed(ManagedType<'t>),
+    Oem(OemType),
+    Precompiled(PrecompiledType<'t>),
+    EndPrecompiled(EndPrecompiledType),
+    TypeServer(TypeServerType<'t>),
 }
 
 impl<'t> TypeData<'t> {
@@ -48,18 +69,208 @@ impl<'t> TypeData<'t> {
             | Self::OverloadedMethod(OverloadedMethodType { ref name, .. })
             | Self::StaticMember(StaticMemberType { ref name, .. })
             | Self::Nested(NestedType { ref name, .. })
+            | Self::MemberModify(MemberModifyType { ref name, .. })
+            | Self::DimensionedArray(DimensionedArrayType { ref name, .. })
             | Self::Enumeration(EnumerationType { ref name, .. })
             | Self::Enumerate(EnumerateType { ref name, .. })
-            | Self::Union(UnionType { ref name, .. }) => name,
+            | Self::Union(UnionType { ref name, .. })
+            | Self::Alias(AliasType { ref name, .. })
+            | Self::FriendFunction(FriendFunctionType { ref name, .. })
+            | Self::Managed(ManagedType { ref name, .. }) => name,
             _ => return None,
         };
 
         Some(*name)
     }
+
+    /// Return the unique (decorated) name of this type, if it has one.
+    ///
+    /// Only classes, structures, unions and enumerations carry a unique name, and only if the
+    /// compiler recorded one. See [`UniqueName`](crate::UniqueName) to decode it.
+    pub fn unique_name(&self) -> Option<RawString<'t>> {
+        match self {
+            Self::Class(ClassType { unique_name, .. })
+            | Self::Union(UnionType { unique_name, .. })
+            | Self::Enumeration(EnumerationType { unique_name, .. }) => *unique_name,
+            _ => None,
+        }
+    }
+
+    /// Returns the indices of all types directly referenced by this type.
+    ///
+    /// For field lists, this includes the types referenced by each field and the continuation of
+    /// the list. Primitive types are included. The indices are returned in the order they appear
+    /// in the record and may contain duplicates.
+    pub fn referenced_types(&self) -> Vec<TypeIndex> {
+        let mut indices = Vec::new();
+        self.clone()
+            .visit_type_indices_mut(&mut |index| indices.push(*index));
+        indices
+    }
+
+    /// Calls `f` for every type index in this record, in the order returned by
+    /// [`referenced_types`](Self::referenced_types).
+    pub(crate) fn visit_type_indices_mut<F>(&mut self, f: &mut F)
+    where
+        F: FnMut(&mut TypeIndex),
+    {
+        match self {
+            Self::Class(data) => {
+                data.fields.iter_mut().for_each(&mut *f);
+                data.derived_from.iter_mut().for_each(&mut *f);
+                data.vtable_shape.iter_mut().for_each(f);
+            }
+            Self::Member(data) => f(&mut data.field_type),
+            Self::MemberFunction(data) => {
+                f(&mut data.return_type);
+                f(&mut data.class_type);
+                data.this_pointer_type.iter_mut().for_each(&mut *f);
+                f(&mut data.argument_list);
+            }
+            Self::OverloadedMethod(data) => f(&mut data.method_list),
+            Self::Method(data) => f(&mut data.method_type),
+            Self::StaticMember(data) => f(&mut data.field_type),
+            Self::Nested(data) => f(&mut data.nested_type),
+            Self::MemberModify(data) => f(&mut data.base_class),
+            Self::BaseClass(data) => f(&mut data.base_class),
+            Self::VirtualBaseClass(data) => {
+                f(&mut data.base_class);
+                f(&mut data.base_pointer);
+            }
+            Self::VirtualFunctionTablePointer(data) => f(&mut data.table),
+            Self::Procedure(data) => {
+                data.return_type.iter_mut().for_each(&mut *f);
+                f(&mut data.argument_list);
+            }
+            Self::Pointer(data) => {
+                f(&mut data.underlying_type);
+                data.containing_class.iter_mut().for_each(f);
+            }
+            Self::Modifier(data) => f(&mut data.underlying_type),
+            Self::Enumeration(data) => {
+                f(&mut data.underlying_type);
+                f(&mut data.fields);
+            }
+            Self::Array(data) => {
+                f(&mut data.element_type);
+                f(&mut data.indexing_type);
+            }
+            Self::DimensionedArray(data) => {
+                f(&mut data.element_type);
+                f(&mut data.dimensions);
+            }
+            Self::Union(data) => f(&mut data.fields),
+            Self::Bitfield(data) => f(&mut data.underlying_type),
+            Self::FieldList(data) => {
+                for field in &mut data.fields {
+                    field.visit_type_indices_mut(f);
+                }
+                data.continuation.iter_mut().for_each(f);
+            }
+            Self::ArgumentList(data) => data.arguments.iter_mut().for_each(f),
+            Self::MethodList(data) => {
+                for method in &mut data.methods {
+                    f(&mut method.method_type);
+                }
+            }
+            Self::VirtualFunctionTable(data) => {
+                f(&mut data.owner);
+                data.base_table.iter_mut().for_each(f);
+            }
+            Self::VirtualFunctionTablePath(data) => data.bases.iter_mut().for_each(f),
+            Self::Alias(data) => f(&mut data.underlying_type),
+            Self::FriendFunction(data) => f(&mut data.function_type),
+            Self::FriendClass(data) => f(&mut data.class_type),
+            Self::Oem(data) => data.indices.iter_mut().for_each(f),
+            Self::Primitive(_)
+            | Self::Enumerate(_)
+            | Self::VirtualTableShape(_)
+            | Self::Label(_)
+            | Self::Managed(_)
+            | Self::Precompiled(_)
+            | Self::EndPrecompiled(_)
+            | Self::TypeServer(_) => {}
+        }
+    }
+
+    /// Calls `f` for every string in this record, including the strings of fields in field
+    /// lists.
+    pub(crate) fn visit_strings_mut<F>(&mut self, f: &mut F)
+    where
+        F: FnMut(&mut RawString<'t>),
+    {
+        match self {
+            Self::Class(data) => {
+                f(&mut data.name);
+                data.unique_name.iter_mut().for_each(f);
+            }
+            Self::Member(data) => f(&mut data.name),
+            Self::OverloadedMethod(data) => f(&mut data.name),
+            Self::Method(data) => f(&mut data.name),
+            Self::StaticMember(data) => f(&mut data.name),
+            Self::Nested(data) => f(&mut data.name),
+            Self::MemberModify(data) => f(&mut data.name),
+            Self::DimensionedArray(data) => f(&mut data.name),
+            Self::Enumeration(data) => {
+                f(&mut data.name);
+                data.unique_name.iter_mut().for_each(f);
+            }
+            Self::Enumerate(data) => {
+                if let Variant::String(ref mut value) = data.value {
+                    f(value);
+                }
+                f(&mut data.name);
+            }
+            Self::Union(data) => {
+                f(&mut data.name);
+                data.unique_name.iter_mut().for_each(f);
+            }
+            Self::FieldList(data) => {
+                for field in &mut data.fields {
+                    field.visit_strings_mut(f);
+                }
+            }
+            Self::VirtualFunctionTable(data) => {
+                f(&mut data.name);
+                data.method_names.iter_mut().for_each(f);
+            }
+            Self::Alias(data) => f(&mut data.name),
+            Self::FriendFunction(data) => f(&mut data.name),
+            Self::Managed(data) => f(&mut data.name),
+            Self::Precompiled(data) => f(&mut data.name),
+            Self::TypeServer(data) => f(&mut data.name),
+            Self::Primitive(_)
+            | Self::MemberFunction(_)
+            | Self::BaseClass(_)
+            | Self::VirtualBaseClass(_)
+            | Self::VirtualFunctionTablePointer(_)
+            | Self::Procedure(_)
+            | Self::Pointer(_)
+            | Self::Modifier(_)
+            | Self::Array(_)
+            | Self::Bitfield(_)
+            | Self::ArgumentList(_)
+            | Self::MethodList(_)
+            | Self::VirtualTableShape(_)
+            | Self::VirtualFunctionTablePath(_)
+            | Self::Label(_)
+            | Self::FriendClass(_)
+            | Self::Oem(_)
+            | Self::EndPrecompiled(_) => {}
+        }
+    }
 }
 
 /// Parse a type out of a `ParseBuffer`.
 pub(crate) fn parse_type_data<'t>(buf: &mut ParseBuffer<'t>) -> Result<TypeData<'t>> {
+    parse_type_data_in(buf, &mut TypeArena::new())
+}
+
+/// Parse a type out of a `ParseBuffer`, taking the vectors of lists from `arena`.
+pub(crate) fn parse_type_data_in<'t>(
+    buf: &mut ParseBuffer<'t>,
+    arena: &mut TypeArena<'t>,
+) -> Result<TypeData<'t>> {
     let leaf = buf.parse_u16()?;
 
     match leaf {
@@ -166,7 +377,7 @@ pub(crate) fn parse_type_data<'t>(buf: &mut ParseBuffer<'t>) -> Result<TypeData<
                 attributes: attr,
                 method_type: buf.parse()?,
                 vtable_offset: if attr.is_intro_virtual() {
-                    Some(buf.parse_u32()? as u32)
+                    Some(buf.parse_u32()?)
                 } else {
                     // yes, this is variable length
                     None
@@ -210,16 +421,26 @@ pub(crate) fn parse_type_data<'t>(buf: &mut ParseBuffer<'t>) -> Result<TypeData<
             let underlying_type = buf.parse()?;
             let attributes = PointerAttributes(buf.parse()?);
 
-            let containing_class = if attributes.pointer_to_member() {
-                Some(buf.parse()?)
+            let (containing_class, member_representation) = if attributes.pointer_to_member() {
+                let containing_class = buf.parse()?;
+                // records written before VC8 may omit the representation
+                let representation = if buf.is_empty() {
+                    MemberPointerRepresentation::Undefined
+                } else {
+                    let raw = buf.parse_u16()?;
+                    MemberPointerRepresentation::from_raw(raw)
+                        .ok_or(Error::UnimplementedFeature("unknown member pointer format"))?
+                };
+                (Some(containing_class), Some(representation))
             } else {
-                None
+                (None, None)
             };
 
             Ok(TypeData::Pointer(PointerType {
                 underlying_type,
                 attributes,
                 containing_class,
+                member_representation,
             }))
         }
 
@@ -281,14 +502,10 @@ pub(crate) fn parse_type_data<'t>(buf: &mut ParseBuffer<'t>) -> Result<TypeData<
                 None
             };
 
-            let mut dimensions: Vec<u32> = Vec::new();
+            let mut dimensions: Vec<u64> = Vec::new();
 
             loop {
-                let dim = parse_unsigned(buf)?;
-                if dim > u64::from(u32::max_value()) {
-                    return Err(Error::UnimplementedFeature("u64 array sizes"));
-                }
-                dimensions.push(dim as u32);
+                dimensions.push(parse_unsigned(buf)?);
 
                 if buf.is_empty() {
                     // shouldn't run out here
@@ -345,14 +562,157 @@ pub(crate) fn parse_type_data<'t>(buf: &mut ParseBuffer<'t>) -> Result<TypeData<
 
         // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L1819-L1823
         LF_VTSHAPE => {
-            // TODO
-            Err(Error::UnimplementedTypeKind(leaf))
+            let count = buf.parse_u16()?;
+
+            // descriptors are packed into four bits each, starting with the low nibble
+            let packed = buf.take((count as usize).div_ceil(2))?;
+            let descriptors = (0..count as usize)
+                .map(|i| VirtualTableShapeDescriptor::from_u8(packed[i / 2] >> ((i % 2) * 4)))
+                .collect();
+
+            Ok(TypeData::VirtualTableShape(VirtualTableShapeType {
+                descriptors,
+            }))
         }
 
         // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L1825-L1837
         LF_VFTABLE => {
-            // TODO
-            Err(Error::UnimplementedTypeKind(leaf))
+            let owner = buf.parse()?;
+            let base_table = parse_optional_type_index(buf)?;
+            let vfptr_offset = buf.parse_u32()?;
+            let names_size = buf.parse_u32()?;
+
+            // the first name is the name of the table, followed by the names of the methods
+            let mut names_buf = ParseBuffer::from(buf.take(names_size as usize)?);
+            let name = names_buf.parse_cstring()?;
+            let mut method_names = Vec::new();
+            while !names_buf.is_empty() {
+                method_names.push(names_buf.parse_cstring()?);
+            }
+
+            Ok(TypeData::VirtualFunctionTable(VirtualFunctionTableType {
+                owner,
+                base_table,
+                vfptr_offset,
+                name,
+                method_names,
+            }))
+        }
+
+        // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L1935-L1940
+        LF_VFTPATH => {
+            let count = buf.parse_u32()?;
+            let mut bases = Vec::with_capacity(count.min(0x1000) as usize);
+            for _ in 0..count {
+                bases.push(buf.parse()?);
+            }
+            Ok(TypeData::VirtualFunctionTablePath(
+                VirtualFunctionTablePathType { bases },
+            ))
+        }
+
+        // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L1898-L1902
+        LF_LABEL => Ok(TypeData::Label(LabelType {
+            far: buf.parse_u16()? == 4,
+        })),
+
+        // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L2045-L2050
+        LF_ALIAS | LF_ALIAS_ST => Ok(TypeData::Alias(AliasType {
+            underlying_type: buf.parse()?,
+            name: parse_string(leaf, buf)?,
+        })),
+
+        // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L2461-L2466
+        LF_FRIENDFCN | LF_FRIENDFCN_ST => {
+            // discard padding
+            buf.parse_u16()?;
+            Ok(TypeData::FriendFunction(FriendFunctionType {
+                function_type: buf.parse()?,
+                name: parse_string(leaf, buf)?,
+            }))
+        }
+
+        // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L2485-L2489
+        LF_FRIENDCLS => {
+            // discard padding
+            buf.parse_u16()?;
+            Ok(TypeData::FriendClass(FriendClassType {
+                class_type: buf.parse()?,
+            }))
+        }
+
+        // `lfMemberModify` in cvinfo.h
+        LF_MEMBERMODIFY | LF_MEMBERMODIFY_ST => Ok(TypeData::MemberModify(MemberModifyType {
+            attributes: FieldAttributes(buf.parse_u16()?),
+            base_class: buf.parse()?,
+            name: parse_string(leaf, buf)?,
+        })),
+
+        // `lfDimArray` in cvinfo.h
+        LF_DIMARRAY | LF_DIMARRAY_ST => Ok(TypeData::DimensionedArray(DimensionedArrayType {
+            element_type: buf.parse()?,
+            dimensions: buf.parse()?,
+            name: parse_string(leaf, buf)?,
+        })),
+
+        // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L1683-L1687
+        LF_MANAGED | LF_MANAGED_ST => Ok(TypeData::Managed(ManagedType {
+            name: parse_string(leaf, buf)?,
+        })),
+
+        // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L1959-L1972
+        LF_OEM | LF_OEM2 => {
+            let identifier = if leaf == LF_OEM2 {
+                OemIdentifier::Guid(parse_guid(buf)?)
+            } else {
+                OemIdentifier::Id {
+                    oem: buf.parse_u16()?,
+                    record: buf.parse_u16()?,
+                }
+            };
+
+            let count = buf.parse_u32()?;
+            let mut indices = Vec::with_capacity(count.min(0x1000) as usize);
+            for _ in 0..count {
+                indices.push(buf.parse()?);
+            }
+
+            Ok(TypeData::Oem(OemType {
+                identifier,
+                indices,
+            }))
+        }
+
+        // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L1910-L1916
+        LF_PRECOMP | LF_PRECOMP_ST => Ok(TypeData::Precompiled(PrecompiledType {
+            start_index: buf.parse_u32()?,
+            count: buf.parse_u32()?,
+            signature: buf.parse_u32()?,
+            name: parse_string(leaf, buf)?,
+        })),
+
+        // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L1927-L1930
+        LF_ENDPRECOMP => Ok(TypeData::EndPrecompiled(EndPrecompiledType {
+            signature: buf.parse_u32()?,
+        })),
+
+        // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L2016-L2035
+        LF_TYPESERVER | LF_TYPESERVER_ST => {
+            let signature = buf.parse_u32()?;
+            let age = buf.parse_u32()?;
+            Ok(TypeData::TypeServer(TypeServerType {
+                identifier: PdbIdentifier::Signature { signature, age },
+                name: parse_string(leaf, buf)?,
+            }))
+        }
+
+        LF_TYPESERVER2 => {
+            let guid = parse_guid(buf)?;
+            let age = buf.parse_u32()?;
+            Ok(TypeData::TypeServer(TypeServerType {
+                identifier: PdbIdentifier::Guid { guid, age },
+                name: parse_string(leaf, buf)?,
+            }))
         }
 
         // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L2521-L2528
@@ -370,7 +730,7 @@ pub(crate) fn parse_type_data<'t>(buf: &mut ParseBuffer<'t>) -> Result<TypeData<
 
         // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L2112-L2115
         LF_FIELDLIST => {
-            let mut fields: Vec<TypeData<'t>> = Vec::new();
+            let mut fields = arena.fields();
             let mut continuation: Option<TypeIndex> = None;
 
             while !buf.is_empty() {
@@ -386,7 +746,7 @@ pub(crate) fn parse_type_data<'t>(buf: &mut ParseBuffer<'t>) -> Result<TypeData<
                     _ => {
                         // other type
                         // recurse because recursion is endless fun because [STACK OVERFLOW]
-                        fields.push(parse_type_data(buf)?);
+                        fields.push(parse_type_data_in(buf, arena)?);
                     }
                 }
 
@@ -402,7 +762,8 @@ pub(crate) fn parse_type_data<'t>(buf: &mut ParseBuffer<'t>) -> Result<TypeData<
 
         LF_ARGLIST => {
             let count = buf.parse_u32()?;
-            let mut arglist: Vec<TypeIndex> = Vec::with_capacity(count as usize);
+            let mut arglist = arena.indices();
+            arglist.reserve(count.min(0x1000) as usize);
             for _ in 0..count {
                 arglist.push(buf.parse()?);
             }
@@ -435,6 +796,16 @@ pub(crate) fn parse_type_data<'t>(buf: &mut ParseBuffer<'t>) -> Result<TypeData<
     }
 }
 
+#[inline]
+fn parse_guid(buf: &mut ParseBuffer<'_>) -> Result<Uuid> {
+    Ok(Uuid::from_fields(
+        buf.parse_u32()?,
+        buf.parse_u16()?,
+        buf.parse_u16()?,
+        buf.take(8)?.try_into().unwrap(),
+    ))
+}
+
 #[inline]
 fn parse_optional_type_index(buf: &mut ParseBuffer<'_>) -> Result<Option<TypeIndex>> {
     let index = buf.parse()?;
@@ -455,7 +826,7 @@ fn parse_string<'t>(leaf: u16, buf: &mut ParseBuffer<'t>) -> Result<RawString<'t
 }
 
 #[inline]
-fn parse_padding(buf: &mut ParseBuffer<'_>) -> Result<()> {
+pub(crate) fn parse_padding(buf: &mut ParseBuffer<'_>) -> Result<()> {
     while !buf.is_empty() && buf.peek_u8()? >= 0xf0 {
         let padding = buf.parse_u8()?;
         if padding > 0xf0 {
@@ -475,19 +846,21 @@ fn parse_unsigned(buf: &mut ParseBuffer<'_>) -> Result<u64> {
         return Ok(u64::from(leaf));
     }
 
-    match leaf {
-        LF_CHAR => Ok(u64::from(buf.parse_u8()?)),
-        LF_USHORT => Ok(u64::from(buf.parse_u16()?)),
-        LF_ULONG => Ok(u64::from(buf.parse_u32()?)),
-        LF_UQUADWORD => Ok(buf.parse_u64()?),
-        _ => {
-            if cfg!(debug_assertions) {
-                unreachable!();
-            } else {
-                Err(Error::UnexpectedNumericPrefix(leaf))
-            }
-        }
-    }
+    // signed leaves are accepted as long as the value is not negative
+    let value = match leaf {
+        LF_CHAR => Some(u64::from(buf.parse_u8()?)),
+        LF_SHORT => u64::try_from(buf.parse_i16()?).ok(),
+        LF_USHORT => Some(u64::from(buf.parse_u16()?)),
+        LF_LONG => u64::try_from(buf.parse_i32()?).ok(),
+        LF_ULONG => Some(u64::from(buf.parse_u32()?)),
+        LF_QUADWORD => u64::try_from(buf.parse_i64()?).ok(),
+        LF_UQUADWORD => Some(buf.parse_u64()?),
+        LF_OCTWORD => u64::try_from(buf.parse::<i128>()?).ok(),
+        LF_UOCTWORD => u64::try_from(buf.parse::<u128>()?).ok(),
+        _ => None,
+    };
+
+    value.ok_or(Error::UnexpectedNumericPrefix(leaf))
 }
 
 /*
@@ -509,6 +882,7 @@ unsigned short  mocom       :2;     // CV_MOCOM_UDT_e
 } CV_prop_t;
 */
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TypeProperties(u16);
 impl TypeProperties {
     /// Indicates if a type is packed via `#pragma pack` or similar.
@@ -596,17 +970,39 @@ typedef enum CV_methodprop_e {
 
 */
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub struct FieldAttributes(u16);
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FieldAttributes(pub(crate) u16);
 impl FieldAttributes {
+    /// Returns the access protection of the field or method.
     #[inline]
-    pub fn access(self) -> u8 {
-        (self.0 & 0x0003) as u8
+    pub fn access(self) -> Access {
+        match self.0 & 0x0003 {
+            0x00 => Access::None,
+            0x01 => Access::Private,
+            0x02 => Access::Protected,
+            _ => Access::Public,
+        }
     }
     #[inline]
     fn method_properties(self) -> u8 {
         ((self.0 & 0x001c) >> 2) as u8
     }
 
+    /// Returns the kind of method, or `None` if the method properties are invalid.
+    #[inline]
+    pub fn method_kind(self) -> Option<MethodKind> {
+        Some(match self.method_properties() {
+            0x00 => MethodKind::Vanilla,
+            0x01 => MethodKind::Virtual,
+            0x02 => MethodKind::Static,
+            0x03 => MethodKind::Friend,
+            0x04 => MethodKind::IntroVirtual,
+            0x05 => MethodKind::PureVirtual,
+            0x06 => MethodKind::PureIntroVirtual,
+            _ => return None,
+        })
+    }
+
     #[inline]
     pub fn is_static(self) -> bool {
         self.method_properties() == 0x02
@@ -617,26 +1013,200 @@ impl FieldAttributes {
         self.method_properties() == 0x01
     }
 
+    /// Returns whether the method is pure virtual, regardless of whether it introduces a new
+    /// virtual function table slot.
     #[inline]
     pub fn is_pure_virtual(self) -> bool {
-        self.method_properties() == 0x05
+        matches!(self.method_properties(), 0x05 | 0x06)
     }
 
+    /// Returns whether the method introduces a new virtual function table slot.
+    ///
+    /// Such methods record their offset in the virtual function table.
     #[inline]
     pub fn is_intro_virtual(self) -> bool {
         matches!(self.method_properties(), 0x04 | 0x06)
     }
 
-    // TODO
+    #[inline]
+    pub fn is_friend(self) -> bool {
+        self.method_properties() == 0x03
+    }
+
+    /// Returns whether the function is generated by the compiler and does not exist.
+    #[inline]
+    pub fn is_pseudo(self) -> bool {
+        self.0 & 0x0020 != 0
+    }
+
+    /// Returns whether the class cannot be inherited.
+    #[inline]
+    pub fn is_noinherit(self) -> bool {
+        self.0 & 0x0040 != 0
+    }
+
+    /// Returns whether the class cannot be constructed.
+    #[inline]
+    pub fn is_noconstruct(self) -> bool {
+        self.0 & 0x0080 != 0
+    }
+
+    /// Returns whether the function is generated by the compiler and does exist.
+    #[inline]
+    pub fn is_compiler_generated(self) -> bool {
+        self.0 & 0x0100 != 0
+    }
+
+    /// Returns whether the method cannot be overridden.
+    #[inline]
+    pub fn is_sealed(self) -> bool {
+        self.0 & 0x0200 != 0
+    }
+}
+
+/// The kind of a method, as described by `CV_methodprop_e`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum MethodKind {
+    /// A regular, non-virtual method.
+    Vanilla,
+    /// A virtual method overriding a method of a base class.
+    Virtual,
+    /// A static method.
+    Static,
+    /// A friend method.
+    Friend,
+    /// A virtual method introducing a new virtual function table slot.
+    IntroVirtual,
+    /// A pure virtual method overriding a method of a base class.
+    PureVirtual,
+    /// A pure virtual method introducing a new virtual function table slot.
+    PureIntroVirtual,
+}
+
+/// The access protection of a field or method, as described by `CV_access_e`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Access {
+    /// No access protection, used for fields that are not class members.
+    None,
+    /// A `private` member.
+    Private,
+    /// A `protected` member.
+    Protected,
+    /// A `public` member.
+    Public,
 }
 
-#[allow(unused)]
-#[repr(u8)]
-enum Access {
-    None = 0x00,
-    Private = 0x01,
-    Protected = 0x02,
-    Public = 0x03,
+/// A calling convention, as described by `CV_call_e`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CallingConvention {
+    /// Near right to left push, caller pops stack (`__cdecl`).
+    NearC,
+    /// Far right to left push, caller pops stack.
+    FarC,
+    /// Near left to right push, callee pops stack (`__pascal`).
+    NearPascal,
+    /// Far left to right push, callee pops stack.
+    FarPascal,
+    /// Near left to right push with registers, callee pops stack (`__fastcall`).
+    NearFast,
+    /// Far left to right push with registers, callee pops stack.
+    FarFast,
+    /// Skipped (unused) call index.
+    Skipped,
+    /// Near standard call (`__stdcall`).
+    NearStd,
+    /// Far standard call.
+    FarStd,
+    /// Near system call (`__syscall`).
+    NearSys,
+    /// Far system call.
+    FarSys,
+    /// `this` call, with `this` passed in a register (`__thiscall`).
+    ThisCall,
+    /// MIPS call.
+    MipsCall,
+    /// Generic call sequence.
+    Generic,
+    /// Alpha call.
+    AlphaCall,
+    /// PowerPC call.
+    PpcCall,
+    /// Hitachi SuperH call.
+    ShCall,
+    /// ARM call.
+    ArmCall,
+    /// AM33 call.
+    Am33Call,
+    /// TriCore call.
+    TriCall,
+    /// Hitachi SuperH-5 call.
+    Sh5Call,
+    /// M32R call.
+    M32rCall,
+    /// Common Language Runtime call (`__clrcall`).
+    ClrCall,
+    /// Marker for routines that are always inlined and thus lack a convention.
+    Inline,
+    /// Near left to right push with registers, including vector registers (`__vectorcall`).
+    NearVector,
+    /// Swift calling convention.
+    Swift,
+}
+
+impl CallingConvention {
+    /// Returns the calling convention with the given `CV_call_e` value.
+    pub fn from_raw(value: u8) -> Option<Self> {
+        Some(match value {
+            0x00 => Self::NearC,
+            0x01 => Self::FarC,
+            0x02 => Self::NearPascal,
+            0x03 => Self::FarPascal,
+            0x04 => Self::NearFast,
+            0x05 => Self::FarFast,
+            0x06 => Self::Skipped,
+            0x07 => Self::NearStd,
+            0x08 => Self::FarStd,
+            0x09 => Self::NearSys,
+            0x0a => Self::FarSys,
+            0x0b => Self::ThisCall,
+            0x0c => Self::MipsCall,
+            0x0d => Self::Generic,
+            0x0e => Self::AlphaCall,
+            0x0f => Self::PpcCall,
+            0x10 => Self::ShCall,
+            0x11 => Self::ArmCall,
+            0x12 => Self::Am33Call,
+            0x13 => Self::TriCall,
+            0x14 => Self::Sh5Call,
+            0x15 => Self::M32rCall,
+            0x16 => Self::ClrCall,
+            0x17 => Self::Inline,
+            0x18 => Self::NearVector,
+            0x19 => Self::Swift,
+            _ => return None,
+        })
+    }
+
+    /// Returns the C++ keyword selecting this calling convention, such as `__stdcall`.
+    ///
+    /// Returns `None` for calling conventions that cannot be selected in source code.
+    pub fn keyword(self) -> Option<&'static str> {
+        Some(match self {
+            Self::NearC | Self::FarC => "__cdecl",
+            Self::NearPascal | Self::FarPascal => "__pascal",
+            Self::NearFast | Self::FarFast => "__fastcall",
+            Self::NearStd | Self::FarStd => "__stdcall",
+            Self::NearSys | Self::FarSys => "__syscall",
+            Self::ThisCall => "__thiscall",
+            Self::ClrCall => "__clrcall",
+            Self::NearVector => "__vectorcall",
+            Self::Swift => "__swiftcall",
+            _ => return None,
+        })
+    }
 }
 
 // CV_call_t and CV_funcattr_t are always found back to back
@@ -650,17 +1220,30 @@ typedef struct CV_funcattr_t {
 } CV_funcattr_t;
 */
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FunctionAttributes(u16);
 impl FunctionAttributes {
-    pub fn calling_convention(self) -> u8 {
+    /// Returns the calling convention, or `None` if the value is reserved or unknown.
+    pub fn calling_convention(self) -> Option<CallingConvention> {
+        CallingConvention::from_raw(self.raw_calling_convention())
+    }
+
+    /// Returns the raw `CV_call_e` value of the calling convention.
+    pub fn raw_calling_convention(self) -> u8 {
         (self.0 & 0xff) as u8
     }
+
+    /// Returns whether the function returns a C++ style user-defined type.
     pub fn cxx_return_udt(self) -> bool {
         (self.0 & 0x0100) > 0
     }
+
+    /// Returns whether the function is an instance constructor.
     pub fn is_constructor(self) -> bool {
         (self.0 & 0x0200) > 0
     }
+
+    /// Returns whether the function is an instance constructor of a class with virtual bases.
     pub fn is_constructor_with_virtual_bases(self) -> bool {
         (self.0 & 0x0400) > 0
     }
@@ -668,6 +1251,7 @@ impl FunctionAttributes {
 
 /// The kind of a `PointerType`.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum PointerKind {
     /// 16 bit pointer.
     Near16,
@@ -699,6 +1283,7 @@ pub enum PointerKind {
 
 /// The mode of a `PointerType`.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum PointerMode {
     /// A regular pointer.
     Pointer,
@@ -730,7 +1315,8 @@ struct lfPointerAttr {
 */
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub struct PointerAttributes(u32);
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PointerAttributes(pub(crate) u32);
 
 impl PointerAttributes {
     /// Indicates the type of pointer.
@@ -808,7 +1394,7 @@ impl PointerAttributes {
 
     /// The size of the pointer in bytes.
     pub fn size(self) -> u8 {
-        let size = ((self.0 >> 13) & 0x3f) as u8;
+        let size = self.recorded_size();
         if size != 0 {
             return size;
         }
@@ -820,16 +1406,103 @@ impl PointerAttributes {
         }
     }
 
+    /// The size of the pointer in bytes as recorded, or `0` if not specified.
+    pub(crate) fn recorded_size(self) -> u8 {
+        ((self.0 >> 13) & 0x3f) as u8
+    }
+
     /// Returns `true` if this is a MoCOM pointer (`^` or `%`).
     pub fn is_mocom(self) -> bool {
         (self.0 & 0x40000) != 0
     }
+
+    /// Returns `true` if this is the `this` pointer of a member function with an `&`
+    /// ref-qualifier.
+    pub fn is_lvalue_ref_this(self) -> bool {
+        (self.0 & 0x80000) != 0
+    }
+
+    /// Returns `true` if this is the `this` pointer of a member function with an `&&`
+    /// ref-qualifier.
+    pub fn is_rvalue_ref_this(self) -> bool {
+        (self.0 & 0x100000) != 0
+    }
+
+    /// Returns `true` if this is a 32-bit pointer, as declared with `__ptr32`.
+    pub fn is_ptr32(self) -> bool {
+        matches!(
+            self.pointer_kind(),
+            PointerKind::Near32 | PointerKind::Far32
+        )
+    }
+
+    /// Returns `true` if this is a 64-bit pointer, as declared with `__ptr64`.
+    pub fn is_ptr64(self) -> bool {
+        self.pointer_kind() == PointerKind::Ptr64
+    }
+}
+
+/// The representation of a pointer to member, as described by `CV_pmtype_e`.
+///
+/// MSVC picks the smallest representation that can address members of the containing class,
+/// depending on its inheritance model. This determines the size and layout of the pointer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum MemberPointerRepresentation {
+    /// Not specified, as emitted by compilers before VC8.
+    Undefined,
+    /// Pointer to data member of a class with single inheritance.
+    DataSingle,
+    /// Pointer to data member of a class with multiple inheritance.
+    DataMultiple,
+    /// Pointer to data member of a class with virtual inheritance.
+    DataVirtual,
+    /// Pointer to data member using the most general representation.
+    DataGeneral,
+    /// Pointer to member function of a class with single inheritance.
+    FunctionSingle,
+    /// Pointer to member function of a class with multiple inheritance.
+    FunctionMultiple,
+    /// Pointer to member function of a class with virtual inheritance.
+    FunctionVirtual,
+    /// Pointer to member function using the most general representation.
+    FunctionGeneral,
+}
+
+impl MemberPointerRepresentation {
+    /// Returns the representation with the given `CV_pmtype_e` value.
+    pub fn from_raw(value: u16) -> Option<Self> {
+        Some(match value {
+            0x00 => Self::Undefined,
+            0x01 => Self::DataSingle,
+            0x02 => Self::DataMultiple,
+            0x03 => Self::DataVirtual,
+            0x04 => Self::DataGeneral,
+            0x05 => Self::FunctionSingle,
+            0x06 => Self::FunctionMultiple,
+            0x07 => Self::FunctionVirtual,
+            0x08 => Self::FunctionGeneral,
+            _ => return None,
+        })
+    }
+
+    /// Returns `true` if this represents a pointer to member function.
+    pub fn is_function(self) -> bool {
+        matches!(
+            self,
+            Self::FunctionSingle
+                | Self::FunctionMultiple
+                | Self::FunctionVirtual
+                | Self::FunctionGeneral
+        )
+    }
 }
 
 /// The information parsed from a type record with kind
 /// `LF_CLASS`, `LF_CLASS_ST`, `LF_STRUCTURE`, `LF_STRUCTURE_ST` or `LF_INTERFACE`.
 // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L1631
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ClassType<'t> {
     pub kind: ClassKind,
 
@@ -857,6 +1530,7 @@ pub struct ClassType<'t> {
 
 /// Used by `ClassType` to distinguish class-like concepts.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum ClassKind {
     Class,
     Struct,
@@ -865,6 +1539,7 @@ pub enum ClassKind {
 
 /// The information parsed from a type record with kind `LF_MEMBER` or `LF_MEMBER_ST`.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct MemberType<'t> {
     pub attributes: FieldAttributes,
     pub field_type: TypeIndex,
@@ -874,6 +1549,7 @@ pub struct MemberType<'t> {
 
 /// The information parsed from a type record with kind `LF_MFUNCTION`.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct MemberFunctionType {
     pub return_type: TypeIndex,
     pub class_type: TypeIndex,
@@ -886,6 +1562,7 @@ pub struct MemberFunctionType {
 
 /// The information parsed from a type record with kind `LF_METHOD` or `LF_METHOD_ST`.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct OverloadedMethodType<'t> {
     pub count: u16,
     pub method_list: TypeIndex,
@@ -894,6 +1571,7 @@ pub struct OverloadedMethodType<'t> {
 
 /// The information parsed from a type record with kind `LF_ONEMETHOD` or `LF_ONEMETHOD_ST`.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct MethodType<'t> {
     pub attributes: FieldAttributes,
     pub method_type: TypeIndex,
@@ -903,6 +1581,7 @@ pub struct MethodType<'t> {
 
 /// The information parsed from a type record with kind `LF_STMEMBER` or `LF_STMEMBER_ST`.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct StaticMemberType<'t> {
     pub attributes: FieldAttributes,
     pub field_type: TypeIndex,
@@ -912,6 +1591,7 @@ pub struct StaticMemberType<'t> {
 /// The information parsed from a type record with kind
 /// `LF_NESTTYPE`, `LF_NESTTYPE_ST`, `LF_NESTTYPEEX`, or `LF_NESTTYPEEX_ST`.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct NestedType<'t> {
     pub attributes: FieldAttributes,
     pub nested_type: TypeIndex,
@@ -920,6 +1600,7 @@ pub struct NestedType<'t> {
 
 /// The information parsed from a type record with kind `LF_BCLASS` or `LF_BINTERFACE`.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct BaseClassType {
     pub kind: ClassKind,
     pub attributes: FieldAttributes,
@@ -931,6 +1612,7 @@ pub struct BaseClassType {
 
 /// The information parsed from a type record with kind `LF_VBCLASS` or `LF_IVBCLASS`.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct VirtualBaseClassType {
     pub direct: bool,
     pub attributes: FieldAttributes,
@@ -943,12 +1625,14 @@ pub struct VirtualBaseClassType {
 
 /// The information parsed from a type record with kind `LF_VFUNCTAB`.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct VirtualFunctionTablePointerType {
     pub table: TypeIndex,
 }
 
 /// The information parsed from a type record with kind `LF_PROCEDURE`.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ProcedureType {
     pub return_type: Option<TypeIndex>,
     pub attributes: FunctionAttributes,
@@ -958,14 +1642,18 @@ pub struct ProcedureType {
 
 /// The information parsed from a type record with kind `LF_POINTER`.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct PointerType {
     pub underlying_type: TypeIndex,
     pub attributes: PointerAttributes,
     pub containing_class: Option<TypeIndex>,
+    /// The representation of a pointer to member, present along with `containing_class`.
+    pub member_representation: Option<MemberPointerRepresentation>,
 }
 
 /// The information parsed from a type record with kind `LF_MODIFIER`.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ModifierType {
     pub underlying_type: TypeIndex,
     pub constant: bool,
@@ -975,6 +1663,7 @@ pub struct ModifierType {
 
 /// The information parsed from a type record with kind `LF_ENUM` or `LF_ENUM_ST`.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct EnumerationType<'t> {
     pub count: u16,
     pub properties: TypeProperties,
@@ -986,15 +1675,17 @@ pub struct EnumerationType<'t> {
 
 /// The information parsed from a type record with kind `LF_ENUMERATE` or `LF_ENUMERATE_ST`.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct EnumerateType<'t> {
     pub attributes: FieldAttributes,
-    pub value: Variant,
+    pub value: Variant<'t>,
     pub name: RawString<'t>,
 }
 
 /// The information parsed from a type record with kind
 /// `LF_ARRAY`, `LF_ARRAY_ST` or `LF_STRIDED_ARRAY`.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ArrayType {
     pub element_type: TypeIndex,
     pub indexing_type: TypeIndex,
@@ -1009,11 +1700,12 @@ pub struct ArrayType {
     /// Thus a `float[4][4]` has `dimensions: [16, 64]`. Determining array dimensions in terms
     /// of element counts requires determining the size of the `element_type` and iteratively
     /// dividing.
-    pub dimensions: Vec<u32>,
+    pub dimensions: Vec<u64>,
 }
 
 /// The information parsed from a type record with kind `LF_UNION` or `LF_UNION_ST`.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct UnionType<'t> {
     pub count: u16,
     pub properties: TypeProperties,
@@ -1025,6 +1717,7 @@ pub struct UnionType<'t> {
 
 /// The information parsed from a type record with kind `LF_BITFIELD`.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct BitfieldType {
     pub underlying_type: TypeIndex,
     pub length: u8,
@@ -1033,6 +1726,7 @@ pub struct BitfieldType {
 
 /// The information parsed from a type record with kind `LF_FIELDLIST`.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FieldList<'t> {
     pub fields: Vec<TypeData<'t>>,
 
@@ -1043,24 +1737,213 @@ pub struct FieldList<'t> {
 
 /// The information parsed from a type record with kind `LF_ARGLIST`.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ArgumentList {
     pub arguments: Vec<TypeIndex>,
 }
 
 /// The information parsed from a type record with kind `LF_METHODLIST`.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct MethodList {
     pub methods: Vec<MethodListEntry>,
 }
 
 /// An entry in a `MethodList`.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct MethodListEntry {
     pub attributes: FieldAttributes,
     pub method_type: TypeIndex,
     pub vtable_offset: Option<u32>,
 }
 
+/// The kind of an entry in a virtual function table, as described by `CV_VTS_desc_e`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum VirtualTableShapeDescriptor {
+    Near,
+    Far,
+    Thin,
+    Outer,
+    Meta,
+    Near32,
+    Far32,
+    Unknown(u8),
+}
+
+impl VirtualTableShapeDescriptor {
+    fn from_u8(value: u8) -> Self {
+        match value & 0x0f {
+            0 => Self::Near,
+            1 => Self::Far,
+            2 => Self::Thin,
+            3 => Self::Outer,
+            4 => Self::Meta,
+            5 => Self::Near32,
+            6 => Self::Far32,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+/// The information parsed from a type record with kind `LF_VTSHAPE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VirtualTableShapeType {
+    /// The kind of every entry in the virtual function table.
+    pub descriptors: Vec<VirtualTableShapeDescriptor>,
+}
+
+/// The information parsed from a type record with kind `LF_VFTABLE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VirtualFunctionTableType<'t> {
+    /// The class or structure owning the table.
+    pub owner: TypeIndex,
+    /// The table of the base class this table was derived from, if any.
+    pub base_table: Option<TypeIndex>,
+    /// Offset of the virtual function table pointer within the object layout.
+    pub vfptr_offset: u32,
+    /// The decorated name of the table.
+    pub name: RawString<'t>,
+    /// The decorated names of the methods in the table, in table order.
+    pub method_names: Vec<RawString<'t>>,
+}
+
+/// The information parsed from a type record with kind `LF_VFTPATH`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VirtualFunctionTablePathType {
+    /// The base classes along the path to the virtual function table.
+    pub bases: Vec<TypeIndex>,
+}
+
+/// The information parsed from a type record with kind `LF_LABEL`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LabelType {
+    /// Whether this is a far label, rather than a near label.
+    pub far: bool,
+}
+
+/// The information parsed from a type record with kind `LF_ALIAS` or `LF_ALIAS_ST`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AliasType<'t> {
+    pub underlying_type: TypeIndex,
+    pub name: RawString<'t>,
+}
+
+/// The information parsed from a type record with kind `LF_FRIENDFCN` or `LF_FRIENDFCN_ST`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FriendFunctionType<'t> {
+    pub function_type: TypeIndex,
+    pub name: RawString<'t>,
+}
+
+/// The information parsed from a type record with kind `LF_FRIENDCLS`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FriendClassType {
+    pub class_type: TypeIndex,
+}
+
+/// The information parsed from a type record with kind `LF_MEMBERMODIFY` or
+/// `LF_MEMBERMODIFY_ST`.
+///
+/// Managed classes use this field to change the access protection of a member inherited from a
+/// base class.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MemberModifyType<'t> {
+    /// The new attributes of the member.
+    pub attributes: FieldAttributes,
+    /// The base class declaring the member.
+    pub base_class: TypeIndex,
+    /// The name of the member.
+    pub name: RawString<'t>,
+}
+
+/// The information parsed from a type record with kind `LF_DIMARRAY` or `LF_DIMARRAY_ST`.
+///
+/// This describes an array with a rank and bounds given by a separate dimension record, as used
+/// for managed multi-dimensional arrays.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DimensionedArrayType<'t> {
+    /// The type of the array elements.
+    pub element_type: TypeIndex,
+    /// The dimension record describing the bounds of the array, such as `LF_DIMCONU`.
+    pub dimensions: TypeIndex,
+    /// The name of the array type.
+    pub name: RawString<'t>,
+}
+
+/// The information parsed from a type record with kind `LF_MANAGED` or `LF_MANAGED_ST`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ManagedType<'t> {
+    /// The name of the managed type.
+    pub name: RawString<'t>,
+}
+
+/// Identifies the vendor and format of an OEM type record.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum OemIdentifier {
+    /// An `LF_OEM` record, identified by a vendor and record id.
+    Id { oem: u16, record: u16 },
+    /// An `LF_OEM2` record, identified by a GUID.
+    Guid(Uuid),
+}
+
+/// The information parsed from a type record with kind `LF_OEM` or `LF_OEM2`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct OemType {
+    pub identifier: OemIdentifier,
+    /// Types referenced by this record.
+    pub indices: Vec<TypeIndex>,
+}
+
+/// The information parsed from a type record with kind `LF_PRECOMP` or `LF_PRECOMP_ST`.
+///
+/// This refers to types stored in the precompiled header object file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PrecompiledType<'t> {
+    /// The first type index included from the precompiled types.
+    pub start_index: u32,
+    /// The number of types included from the precompiled types.
+    pub count: u32,
+    /// The signature of the precompiled types.
+    pub signature: u32,
+    /// The name of the object file containing the precompiled types.
+    pub name: RawString<'t>,
+}
+
+/// The information parsed from a type record with kind `LF_ENDPRECOMP`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EndPrecompiledType {
+    /// The signature of the precompiled types.
+    pub signature: u32,
+}
+
+/// The information parsed from a type record with kind `LF_TYPESERVER` or `LF_TYPESERVER2`.
+///
+/// This refers to a PDB holding the types of an object file that was compiled with `/Zi`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TypeServerType<'t> {
+    /// Identifies the type server PDB.
+    pub identifier: PdbIdentifier,
+    /// The path to the type server PDB.
+    pub name: RawString<'t>,
+}
+
 /*
 // arrays:
 ParseBuf::from("\x03\x15\xa0\xdc\x0b\x00\x23\x00\x00\x00\x40\x00\x00\xf1").as_bytes(),
@@ -1099,3 +1982,231 @@ fn kind_1609() {
         })
     );
 }
+
+#[test]
+fn kind_1503() {
+    // LF_ARRAY of 0x1_0000_0000 bytes, with the size stored as LF_QUADWORD
+    let mut data = vec![0x03, 0x15];
+    data.extend_from_slice(&0x0020u32.to_le_bytes());
+    data.extend_from_slice(&0x0023u32.to_le_bytes());
+    data.extend_from_slice(&[0x09, 0x80]);
+    data.extend_from_slice(&0x1_0000_0000i64.to_le_bytes());
+    data.extend_from_slice(&[0x00, 0xf2, 0xf1]);
+
+    assert_eq!(
+        parse_type_data(&mut ParseBuffer::from(&data[..])).expect("parse"),
+        TypeData::Array(ArrayType {
+            element_type: TypeIndex(0x0020),
+            indexing_type: TypeIndex(0x0023),
+            stride: None,
+            dimensions: vec![0x1_0000_0000],
+        })
+    );
+
+    // negative sizes are invalid
+    let mut data = vec![0x03, 0x15];
+    data.extend_from_slice(&0x0020u32.to_le_bytes());
+    data.extend_from_slice(&0x0023u32.to_le_bytes());
+    data.extend_from_slice(&[0x01, 0x80, 0xff, 0xff, 0x00]);
+    assert!(matches!(
+        parse_type_data(&mut ParseBuffer::from(&data[..])),
+        Err(Error::UnexpectedNumericPrefix(0x8001))
+    ));
+}
+
+#[test]
+fn kind_000a() {
+    // LF_VTSHAPE with three entries: near32, near32, far
+    let data = &[0x0a, 0x00, 0x03, 0x00, 0x55, 0x01][..];
+
+    assert_eq!(
+        parse_type_data(&mut ParseBuffer::from(data)).expect("parse"),
+        TypeData::VirtualTableShape(VirtualTableShapeType {
+            descriptors: vec![
+                VirtualTableShapeDescriptor::Near32,
+                VirtualTableShapeDescriptor::Near32,
+                VirtualTableShapeDescriptor::Far,
+            ],
+        })
+    );
+}
+
+#[test]
+fn kind_151d() {
+    let mut data = vec![0x1d, 0x15];
+    data.extend_from_slice(&0x1000u32.to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&8u32.to_le_bytes());
+    data.extend_from_slice(&11u32.to_le_bytes());
+    data.extend_from_slice(b"vt\0foo\0bar\0");
+
+    assert_eq!(
+        parse_type_data(&mut ParseBuffer::from(&data[..])).expect("parse"),
+        TypeData::VirtualFunctionTable(VirtualFunctionTableType {
+            owner: TypeIndex(0x1000),
+            base_table: None,
+            vfptr_offset: 8,
+            name: RawString::from("vt"),
+            method_names: vec![RawString::from("foo"), RawString::from("bar")],
+        })
+    );
+}
+
+#[test]
+fn kind_1513() {
+    let mut data = vec![0x13, 0x15, 0x01, 0x00];
+    data.extend_from_slice(&0x1005u32.to_le_bytes());
+    data.extend_from_slice(b"Count\0");
+
+    assert_eq!(
+        parse_type_data(&mut ParseBuffer::from(&data[..])).expect("parse"),
+        TypeData::MemberModify(MemberModifyType {
+            attributes: FieldAttributes(1),
+            base_class: TypeIndex(0x1005),
+            name: RawString::from("Count"),
+        })
+    );
+}
+
+#[test]
+fn kind_1508() {
+    let mut data = vec![0x08, 0x15];
+    data.extend_from_slice(&0x0074u32.to_le_bytes());
+    data.extend_from_slice(&0x1007u32.to_le_bytes());
+    data.extend_from_slice(b"array<int,2>\0");
+
+    let parsed = parse_type_data(&mut ParseBuffer::from(&data[..])).expect("parse");
+    assert_eq!(
+        parsed,
+        TypeData::DimensionedArray(DimensionedArrayType {
+            element_type: TypeIndex(0x0074),
+            dimensions: TypeIndex(0x1007),
+            name: RawString::from("array<int,2>"),
+        })
+    );
+    assert_eq!(
+        parsed.referenced_types(),
+        [TypeIndex(0x0074), TypeIndex(0x1007)]
+    );
+}
+
+#[test]
+fn kind_1515() {
+    let mut data = vec![0x15, 0x15];
+    data.extend_from_slice(&[
+        0xa5, 0x3f, 0x3c, 0x2b, 0x2e, 0x5a, 0xb8, 0x44, 0x8b, 0xba, 0xc3, 0x30, 0x0f, 0xf6, 0x9f,
+        0x62,
+    ]);
+    data.extend_from_slice(&3u32.to_le_bytes());
+    data.extend_from_slice(b"vc140.pdb\0");
+
+    assert_eq!(
+        parse_type_data(&mut ParseBuffer::from(&data[..])).expect("parse"),
+        TypeData::TypeServer(TypeServerType {
+            identifier: PdbIdentifier::Guid {
+                guid: "2B3C3FA5-5A2E-44B8-8BBA-C3300FF69F62".parse().unwrap(),
+                age: 3,
+            },
+            name: RawString::from("vc140.pdb"),
+        })
+    );
+}
+
+#[test]
+fn referenced_types() {
+    let data = TypeData::FieldList(FieldList {
+        fields: vec![
+            TypeData::Member(MemberType {
+                attributes: FieldAttributes(3),
+                field_type: TypeIndex(0x1001),
+                offset: 0,
+                name: RawString::from("a"),
+            }),
+            TypeData::BaseClass(BaseClassType {
+                kind: ClassKind::Class,
+                attributes: FieldAttributes(3),
+                base_class: TypeIndex(0x1002),
+                offset: 0,
+            }),
+            TypeData::Enumerate(EnumerateType {
+                attributes: FieldAttributes(3),
+                value: Variant::U8(1),
+                name: RawString::from("b"),
+            }),
+        ],
+        continuation: Some(TypeIndex(0x1003)),
+    });
+
+    assert_eq!(
+        data.referenced_types(),
+        vec![TypeIndex(0x1001), TypeIndex(0x1002), TypeIndex(0x1003)]
+    );
+}
+
+#[test]
+fn function_attributes() {
+    // __vectorcall constructor of a class with virtual bases
+    let attributes = FunctionAttributes(0x18 | 0x0200 | 0x0400);
+    assert_eq!(
+        attributes.calling_convention(),
+        Some(CallingConvention::NearVector)
+    );
+    assert_eq!(attributes.raw_calling_convention(), 0x18);
+    assert!(attributes.is_constructor());
+    assert!(attributes.is_constructor_with_virtual_bases());
+    assert!(!attributes.cxx_return_udt());
+
+    assert_eq!(FunctionAttributes(0x1a).calling_convention(), None);
+    assert_eq!(CallingConvention::ThisCall.keyword(), Some("__thiscall"));
+    assert_eq!(CallingConvention::Inline.keyword(), None);
+}
+
+#[test]
+fn field_attributes() {
+    // public pure virtual method introducing a new slot, compiler generated
+    let attributes = FieldAttributes(0x0003 | (0x06 << 2) | 0x0100);
+    assert_eq!(attributes.access(), Access::Public);
+    assert_eq!(attributes.method_kind(), Some(MethodKind::PureIntroVirtual));
+    assert!(attributes.is_intro_virtual());
+    assert!(attributes.is_pure_virtual());
+    assert!(!attributes.is_virtual());
+    assert!(attributes.is_compiler_generated());
+    assert!(!attributes.is_pseudo());
+    assert!(!attributes.is_sealed());
+
+    let attributes = FieldAttributes((0x02 << 2) | 0x0200);
+    assert_eq!(attributes.method_kind(), Some(MethodKind::Static));
+    assert!(attributes.is_static());
+    assert!(attributes.is_sealed());
+
+    assert_eq!(FieldAttributes(0x07 << 2).method_kind(), None);
+}
+
+#[test]
+fn kind_1002_member_function() {
+    let mut data = vec![0x02, 0x10];
+    data.extend_from_slice(&0x1001u32.to_le_bytes());
+    // 64-bit pointer to member function, 16 bytes, restrict
+    data.extend_from_slice(&(0x0c | (0x03 << 5) | 0x1000 | (16 << 13) as u32).to_le_bytes());
+    data.extend_from_slice(&0x1003u32.to_le_bytes());
+    data.extend_from_slice(&0x06u16.to_le_bytes());
+
+    let pointer = match parse_type_data(&mut ParseBuffer::from(&data[..])).expect("parse") {
+        TypeData::Pointer(pointer) => pointer,
+        other => panic!("unexpected type {:?}", other),
+    };
+
+    assert_eq!(pointer.containing_class, Some(TypeIndex(0x1003)));
+    assert_eq!(
+        pointer.member_representation,
+        Some(MemberPointerRepresentation::FunctionMultiple)
+    );
+    assert!(pointer.attributes.is_ptr64());
+    assert!(!pointer.attributes.is_ptr32());
+    assert!(pointer.attributes.is_restrict());
+    assert!(!pointer.attributes.is_unaligned());
+    assert_eq!(pointer.attributes.size(), 16);
+    assert!(MemberPointerRepresentation::FunctionMultiple.is_function());
+    assert!(!MemberPointerRepresentation::DataVirtual.is_function());
+    assert_eq!(MemberPointerRepresentation::from_raw(0x09), None);
+}
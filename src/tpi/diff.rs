@@ -0,0 +1,299 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::common::*;
+use crate::tpi::compare::TypeComparator;
+use crate::tpi::data::TypeData;
+use crate::tpi::layout::{MemberLayout, RecordLayout};
+use crate::tpi::TypeInformation;
+use crate::FallibleIterator;
+
+/// A class, structure or union definition in one of the streams compared by
+/// [`TypeInformation::diff`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DiffRecord<'t> {
+    /// The index of the record definition.
+    pub index: TypeIndex,
+    /// The name of the record.
+    pub name: RawString<'t>,
+}
+
+/// A record whose layout differs between the compared streams.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordChange<'l, 'r> {
+    /// The layout of the record in the left stream.
+    pub left: RecordLayout<'l>,
+    /// The layout of the record in the right stream.
+    pub right: RecordLayout<'r>,
+    /// Whether the base classes or the virtual function table pointer differ.
+    pub bases_changed: bool,
+    /// Differences between the data members, in declaration order of the left record followed by
+    /// members only present in the right record.
+    pub members: Vec<MemberChange<'l, 'r>>,
+}
+
+/// A difference between the data members of a record in the compared streams.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MemberChange<'l, 'r> {
+    /// A member only present in the right record.
+    Added(MemberLayout<'r>),
+    /// A member only present in the left record.
+    Removed(MemberLayout<'l>),
+    /// A member whose offset, size, bitfield position or type differs.
+    Changed {
+        /// The member in the left record.
+        left: MemberLayout<'l>,
+        /// The member in the right record.
+        right: MemberLayout<'r>,
+    },
+}
+
+/// Differences between the classes, structures and unions of two type streams, as computed by
+/// [`TypeInformation::diff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypeDiff<'l, 'r> {
+    /// Records only defined in the right stream, ordered by type index.
+    pub added: Vec<DiffRecord<'r>>,
+    /// Records only defined in the left stream, ordered by type index.
+    pub removed: Vec<DiffRecord<'l>>,
+    /// Records defined in both streams with different layouts, ordered by their index in the left
+    /// stream.
+    pub changed: Vec<RecordChange<'l, 'r>>,
+}
+
+impl TypeDiff<'_, '_> {
+    /// Returns whether no differences were found.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+pub(crate) fn diff<'l, 'r>(
+    left: &'l TypeInformation<'_>,
+    right: &'r TypeInformation<'_>,
+) -> Result<TypeDiff<'l, 'r>> {
+    let left_records = definitions(left)?;
+    let right_records = definitions(right)?;
+
+    let left_sizer = left.sizer()?;
+    let right_sizer = right.sizer()?;
+    let mut comparator = TypeComparator::new(left_sizer.finder(), right_sizer.finder());
+
+    let right_names: HashMap<_, _> = right_records
+        .iter()
+        .map(|(key, record)| (*key, *record))
+        .collect();
+
+    let left_names: HashSet<_> = left_records.iter().map(|(key, _)| *key).collect();
+
+    let mut diff = TypeDiff::default();
+
+    for (key, record) in &left_records {
+        let other = match right_names.get(key) {
+            Some(other) => other,
+            None => {
+                diff.removed.push(*record);
+                continue;
+            }
+        };
+
+        // records without a complete layout cannot be compared member by member
+        let (left_layout, right_layout) = match (
+            left_sizer.layout_of(record.index),
+            right_sizer.layout_of(other.index),
+        ) {
+            (Ok(left_layout), Ok(right_layout)) => (left_layout, right_layout),
+            (Err(Error::UnsizedType(_)), _) | (_, Err(Error::UnsizedType(_))) => continue,
+            (Err(e), _) | (_, Err(e)) => return Err(e),
+        };
+
+        let bases_changed = !same_bases(&mut comparator, &left_layout, &right_layout)?;
+        let members = diff_members(&left_layout.members, &right_layout.members, |l, r| {
+            comparator.equivalent(l, r)
+        })?;
+
+        if bases_changed || !members.is_empty() || left_layout.size != right_layout.size {
+            diff.changed.push(RecordChange {
+                left: left_layout,
+                right: right_layout,
+                bases_changed,
+                members,
+            });
+        }
+    }
+
+    for (key, record) in &right_records {
+        if !left_names.contains(key) {
+            diff.added.push(*record);
+        }
+    }
+
+    Ok(diff)
+}
+
+/// Collects the record definitions of a stream with their identifying names, in index order.
+///
+/// Records are identified by their unique (decorated) name if available. Only the first definition
+/// of every name is kept.
+fn definitions<'t>(info: &'t TypeInformation<'_>) -> Result<Vec<(RawString<'t>, DiffRecord<'t>)>> {
+    let mut seen = HashSet::new();
+    let mut records = Vec::new();
+
+    let mut iter = info.iter();
+    while let Some(item) = iter.next()? {
+        let (name, unique_name) = match item.parse() {
+            Ok(TypeData::Class(class)) if !class.properties.forward_reference() => {
+                (class.name, class.unique_name)
+            }
+            Ok(TypeData::Union(union)) if !union.properties.forward_reference() => {
+                (union.name, union.unique_name)
+            }
+            _ => continue,
+        };
+
+        let key = unique_name.unwrap_or(name);
+        if seen.insert(key) {
+            records.push((
+                key,
+                DiffRecord {
+                    index: item.index(),
+                    name,
+                },
+            ));
+        }
+    }
+
+    Ok(records)
+}
+
+/// Returns whether two records have the same base classes and virtual function table pointer.
+fn same_bases(
+    comparator: &mut TypeComparator<'_, '_, '_>,
+    left: &RecordLayout<'_>,
+    right: &RecordLayout<'_>,
+) -> Result<bool> {
+    if left.vfptr != right.vfptr
+        || left.base_classes.len() != right.base_classes.len()
+        || left.virtual_base_classes.len() != right.virtual_base_classes.len()
+    {
+        return Ok(false);
+    }
+
+    for (l, r) in left.base_classes.iter().zip(&right.base_classes) {
+        if l.offset != r.offset
+            || l.size != r.size
+            || !comparator.equivalent(l.base_class, r.base_class)?
+        {
+            return Ok(false);
+        }
+    }
+
+    for (l, r) in left
+        .virtual_base_classes
+        .iter()
+        .zip(&right.virtual_base_classes)
+    {
+        if l.direct != r.direct
+            || l.vbptr_offset != r.vbptr_offset
+            || l.vbtable_index != r.vbtable_index
+            || !comparator.equivalent(l.base_class, r.base_class)?
+        {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Matches the members of two records by name and returns their differences.
+fn diff_members<'l, 'r, F>(
+    left: &[MemberLayout<'l>],
+    right: &[MemberLayout<'r>],
+    mut equivalent: F,
+) -> Result<Vec<MemberChange<'l, 'r>>>
+where
+    F: FnMut(TypeIndex, TypeIndex) -> Result<bool>,
+{
+    let mut changes = Vec::new();
+
+    for l in left {
+        match right.iter().find(|r| r.name == l.name) {
+            Some(r) => {
+                let changed = l.offset != r.offset
+                    || l.size != r.size
+                    || l.bitfield != r.bitfield
+                    || !equivalent(l.field_type, r.field_type)?;
+
+                if changed {
+                    changes.push(MemberChange::Changed {
+                        left: *l,
+                        right: *r,
+                    });
+                }
+            }
+            None => changes.push(MemberChange::Removed(*l)),
+        }
+    }
+
+    for r in right {
+        if !left.iter().any(|l| l.name == r.name) {
+            changes.push(MemberChange::Added(*r));
+        }
+    }
+
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(name: &'static str, offset: u64, size: u64) -> MemberLayout<'static> {
+        MemberLayout {
+            name: RawString::from(name),
+            field_type: TypeIndex(0x74),
+            offset,
+            size,
+            alignment: size,
+            bitfield: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_members() {
+        let left = [member("a", 0, 4), member("b", 4, 4), member("c", 8, 4)];
+        let right = [member("a", 0, 4), member("c", 4, 8), member("d", 12, 4)];
+
+        let changes = diff_members(&left, &right, |l, r| Ok(l == r)).expect("diff");
+        assert_eq!(
+            changes,
+            [
+                MemberChange::Removed(left[1]),
+                MemberChange::Changed {
+                    left: left[2],
+                    right: right[1],
+                },
+                MemberChange::Added(right[2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_member_types() {
+        let left = [member("a", 0, 4)];
+        let mut right = [member("a", 0, 4)];
+        right[0].field_type = TypeIndex(0x75);
+
+        let changes = diff_members(&left, &right, |l, r| Ok(l == r)).expect("diff");
+        assert_eq!(changes.len(), 1);
+
+        let changes = diff_members(&left, &right, |_, _| Ok(true)).expect("diff");
+        assert!(changes.is_empty());
+    }
+}
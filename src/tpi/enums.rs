@@ -0,0 +1,280 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::common::*;
+use crate::tpi::data::{EnumerationType, FieldAttributes, TypeData};
+use crate::tpi::primitive::{type_data_for_primitive, PrimitiveKind};
+use crate::tpi::TypeFinder;
+
+/// The value of an enumerator, interpreted according to the underlying type of its enumeration.
+///
+/// Enumerator values are stored in the smallest numeric leaf that can hold them, regardless of the
+/// underlying type. For instance, `-1` in an enumeration with underlying type `int` may be stored as
+/// an unsigned 32-bit leaf. Decoding the value with its underlying type restores the actual value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum EnumValue {
+    /// The value of an enumerator with a signed underlying type.
+    Signed(i128),
+    /// The value of an enumerator with an unsigned underlying type.
+    Unsigned(u128),
+}
+
+impl EnumValue {
+    /// Decodes a numeric leaf as a value of the given underlying type.
+    ///
+    /// The value is truncated to the size of the underlying type and sign-extended if the type is
+    /// signed. If the underlying type is not an integer type, the value is taken as it was stored.
+    /// Returns `None` if the leaf does not hold an integer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use pdb::{EnumValue, PrimitiveKind, Variant};
+    ///
+    /// let value = EnumValue::from_variant(Variant::U32(0xffff_ffff), PrimitiveKind::Long);
+    /// assert_eq!(value, Some(EnumValue::Signed(-1)));
+    ///
+    /// let value = EnumValue::from_variant(Variant::I8(-1), PrimitiveKind::UChar);
+    /// assert_eq!(value, Some(EnumValue::Unsigned(0xff)));
+    /// ```
+    pub fn from_variant(value: Variant<'_>, underlying_type: PrimitiveKind) -> Option<Self> {
+        // the two's complement bits of the value
+        let (raw, stored_signed) = match value {
+            Variant::U8(_) | Variant::U16(_) | Variant::U32(_) | Variant::U64(_) => {
+                (value.to_i128()?, false)
+            }
+            Variant::I8(_) | Variant::I16(_) | Variant::I32(_) | Variant::I64(_) => {
+                (value.to_i128()?, true)
+            }
+            Variant::U128(value) => (value as i128, false),
+            Variant::I128(value) => (value, true),
+            _ => return None,
+        };
+
+        let signed = match is_signed(underlying_type) {
+            Some(signed) => signed,
+            None if stored_signed => return Some(Self::Signed(raw)),
+            None => return Some(Self::Unsigned(raw as u128)),
+        };
+
+        // the size is known for all integer types
+        let bits = underlying_type.size().unwrap_or(16) as u32 * 8;
+        let unused = 128 - bits.min(128);
+
+        Some(if signed {
+            Self::Signed((raw << unused) >> unused)
+        } else {
+            Self::Unsigned(((raw as u128) << unused) >> unused)
+        })
+    }
+
+    /// Returns the value as a signed integer, if it can be represented as one.
+    pub fn to_i128(self) -> Option<i128> {
+        match self {
+            Self::Signed(value) => Some(value),
+            Self::Unsigned(value) => i128::try_from(value).ok(),
+        }
+    }
+
+    /// Returns the value as an unsigned integer, if it can be represented as one.
+    pub fn to_u128(self) -> Option<u128> {
+        match self {
+            Self::Signed(value) => u128::try_from(value).ok(),
+            Self::Unsigned(value) => Some(value),
+        }
+    }
+}
+
+impl fmt::Display for EnumValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Signed(value) => write!(f, "{}", value),
+            Self::Unsigned(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Returns whether an integer type is signed, or `None` if it is not an integer type.
+fn is_signed(kind: PrimitiveKind) -> Option<bool> {
+    Some(match kind {
+        PrimitiveKind::Char
+        | PrimitiveKind::RChar
+        | PrimitiveKind::I8
+        | PrimitiveKind::Short
+        | PrimitiveKind::I16
+        | PrimitiveKind::Long
+        | PrimitiveKind::I32
+        | PrimitiveKind::HRESULT
+        | PrimitiveKind::Quad
+        | PrimitiveKind::I64
+        | PrimitiveKind::Octa
+        | PrimitiveKind::I128 => true,
+
+        PrimitiveKind::UChar
+        | PrimitiveKind::U8
+        | PrimitiveKind::Char8
+        | PrimitiveKind::WChar
+        | PrimitiveKind::RChar16
+        | PrimitiveKind::UShort
+        | PrimitiveKind::U16
+        | PrimitiveKind::RChar32
+        | PrimitiveKind::ULong
+        | PrimitiveKind::U32
+        | PrimitiveKind::UQuad
+        | PrimitiveKind::U64
+        | PrimitiveKind::UOcta
+        | PrimitiveKind::U128
+        | PrimitiveKind::Bool8
+        | PrimitiveKind::Bool16
+        | PrimitiveKind::Bool32
+        | PrimitiveKind::Bool32FF
+        | PrimitiveKind::Bool64 => false,
+
+        _ => return None,
+    })
+}
+
+impl EnumerationType<'_> {
+    /// Returns the kind of the underlying type of this enumeration.
+    ///
+    /// Returns `None` if the underlying type is not a primitive value type, which compilers do not
+    /// emit.
+    pub fn underlying_kind(&self) -> Option<PrimitiveKind> {
+        if self.underlying_type.0 >= 0x1000 {
+            return None;
+        }
+
+        match type_data_for_primitive(self.underlying_type) {
+            Ok(TypeData::Primitive(primitive)) if primitive.indirection.is_none() => {
+                Some(primitive.kind)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// An enumerator of an enumeration, with its value decoded according to the underlying type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Enumerator<'t> {
+    /// The name of the enumerator.
+    pub name: RawString<'t>,
+    /// Access and other properties of the enumerator.
+    pub attributes: FieldAttributes,
+    /// The value of the enumerator.
+    pub value: EnumValue,
+}
+
+impl<'t> TypeFinder<'t> {
+    /// Returns the enumerators of an enumeration, in declaration order.
+    ///
+    /// Forward references are resolved to their definition, and continued field lists are
+    /// followed. Values are decoded according to the underlying type of the enumeration, see
+    /// [`EnumValue::from_variant`].
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnimplementedFeature` if the type is not an enumeration, its field list has an
+    ///   unexpected type, or an enumerator does not have an integer value
+    /// * `Error::TypeNotFound(index)` if a type does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if a type is not yet known by this finder
+    /// * `Error::UnimplementedTypeKind(kind)` if a type record is not understood by this library
+    pub fn enumerators(&self, index: TypeIndex) -> Result<Vec<Enumerator<'t>>> {
+        let definition = self.resolve_definition(index)?.unwrap_or(index);
+        let enumeration = match self.find(definition)?.parse()? {
+            TypeData::Enumeration(enumeration) => enumeration,
+            _ => return Err(Error::UnimplementedFeature("type is not an enumeration")),
+        };
+
+        // fall back to `int`, which is the underlying type of unscoped enumerations
+        let kind = enumeration.underlying_kind().unwrap_or(PrimitiveKind::I32);
+
+        let mut enumerators = Vec::new();
+
+        // forward references without a definition have no field list
+        let mut next = if enumeration.properties.forward_reference() {
+            None
+        } else {
+            Some(enumeration.fields)
+        };
+
+        while let Some(fields) = next {
+            let list = match self.find(fields)?.parse()? {
+                TypeData::FieldList(list) => list,
+                _ => return Err(Error::UnimplementedFeature("unexpected field list type")),
+            };
+
+            for field in list.fields {
+                if let TypeData::Enumerate(enumerate) = field {
+                    let value = EnumValue::from_variant(enumerate.value, kind)
+                        .ok_or(Error::UnimplementedFeature("non-integer enumerator values"))?;
+
+                    enumerators.push(Enumerator {
+                        name: enumerate.name,
+                        attributes: enumerate.attributes,
+                        value,
+                    });
+                }
+            }
+
+            next = list.continuation;
+        }
+
+        Ok(enumerators)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_variant() {
+        let decode = EnumValue::from_variant;
+
+        assert_eq!(
+            decode(Variant::I8(-1), PrimitiveKind::I32),
+            Some(EnumValue::Signed(-1))
+        );
+        assert_eq!(
+            decode(Variant::U32(0x8000_0000), PrimitiveKind::Long),
+            Some(EnumValue::Signed(i128::from(i32::MIN)))
+        );
+        assert_eq!(
+            decode(Variant::I64(-1), PrimitiveKind::U64),
+            Some(EnumValue::Unsigned(u128::from(u64::MAX)))
+        );
+        assert_eq!(
+            decode(Variant::U64(u64::MAX), PrimitiveKind::I64),
+            Some(EnumValue::Signed(-1))
+        );
+        assert_eq!(
+            decode(Variant::U16(0x1ff), PrimitiveKind::UChar),
+            Some(EnumValue::Unsigned(0xff))
+        );
+        assert_eq!(
+            decode(Variant::I16(-2), PrimitiveKind::F32),
+            Some(EnumValue::Signed(-2))
+        );
+        assert_eq!(
+            decode(Variant::I128(-1), PrimitiveKind::UOcta),
+            Some(EnumValue::Unsigned(u128::MAX))
+        );
+        assert_eq!(decode(Variant::F64(0), PrimitiveKind::I32), None);
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(EnumValue::Signed(-1).to_u128(), None);
+        assert_eq!(EnumValue::Signed(-1).to_i128(), Some(-1));
+        assert_eq!(EnumValue::Unsigned(u128::MAX).to_i128(), None);
+        assert_eq!(EnumValue::Unsigned(7).to_i128(), Some(7));
+        assert_eq!(EnumValue::Signed(-5).to_string(), "-5");
+    }
+}
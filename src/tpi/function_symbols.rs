@@ -0,0 +1,344 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::HashMap;
+
+use crate::common::*;
+use crate::modi::ModuleInfo;
+use crate::symbol::constants::{S_GPROC32_ID, S_LPROC32_DPC_ID, S_LPROC32_ID};
+use crate::symbol::SymbolData;
+use crate::tpi::id::IdData;
+use crate::tpi::{IdFinder, IdInformation, TypeFinder};
+use crate::FallibleIterator;
+
+/// The location of a procedure symbol in the symbols of a module.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ProcedureLocation {
+    /// Index of the module in the DBI module list.
+    pub module: usize,
+    /// Index of the [`ProcedureSymbol`](crate::ProcedureSymbol) in the symbols of the module.
+    pub symbol: SymbolIndex,
+}
+
+/// A function id that procedure symbols are matched against.
+#[derive(Clone, Debug)]
+struct FunctionCandidate<'t> {
+    id: IdIndex,
+    name: RawString<'t>,
+    /// The name including the scope of global functions, if they have one.
+    qualified_name: Option<Vec<u8>>,
+}
+
+/// Cross references between function ids and procedure symbols.
+///
+/// Inline sites and the inlinee line information of modules refer to functions by their
+/// `LF_FUNC_ID` or `LF_MFUNC_ID` record in the id information stream, while the out-of-line
+/// copies of these functions are described by [`ProcedureSymbol`](crate::ProcedureSymbol)s in the
+/// modules. `FunctionSymbols` connects the two in both directions.
+///
+/// Object files emit `S_GPROC32_ID` symbols that refer to their function id directly. When
+/// linking, these are rewritten to refer to the function type instead, so procedures in PDBs are
+/// matched to the function id with the same function type and name. Procedures that cannot be
+/// matched unambiguously, such as compiler-generated funclets, are omitted. Functions that were
+/// inlined everywhere do not have a procedure symbol.
+///
+/// Create it with [`FunctionSymbols::new`] and add the symbols of every module of interest with
+/// [`add_module`](Self::add_module).
+///
+/// # Example
+///
+/// ```
+/// # use pdb::FallibleIterator;
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(file)?;
+/// let id_information = pdb.id_information()?;
+/// let debug_information = pdb.debug_information()?;
+///
+/// let mut functions = pdb::FunctionSymbols::new(&id_information)?;
+/// let mut modules = debug_information.modules()?;
+/// let mut index = 0;
+/// while let Some(module) = modules.next()? {
+///     if let Some(info) = pdb.module_info(&module)? {
+///         functions.add_module(index, &info)?;
+///     }
+///     index += 1;
+/// }
+///
+/// // the out-of-line copies of a function referenced by an inline site
+/// for location in functions.procedures_of(pdb::IdIndex(0x1005)) {
+///     println!("module {} symbol {}", location.module, location.symbol);
+/// }
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FunctionSymbols<'t> {
+    candidates: HashMap<TypeIndex, Vec<FunctionCandidate<'t>>>,
+    procedures: HashMap<IdIndex, Vec<ProcedureLocation>>,
+    functions: HashMap<ProcedureLocation, IdIndex>,
+}
+
+impl<'t> FunctionSymbols<'t> {
+    /// Collects the function ids of an id information stream.
+    ///
+    /// No procedures are known until modules are added with [`add_module`](Self::add_module).
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the id stream is truncated
+    /// * `Error::UnexpectedIdKind(index)` if the scope of a function is not a string
+    pub fn new(id_information: &'t IdInformation<'_>) -> Result<Self> {
+        let mut finder = id_information.finder();
+        let mut candidates: HashMap<_, Vec<_>> = HashMap::new();
+
+        let mut iter = id_information.iter();
+        while let Some(item) = iter.next()? {
+            finder.update(&iter);
+
+            let (function_type, candidate) = match item.parse() {
+                Ok(IdData::Function(function)) => {
+                    let qualified_name = match function.scope {
+                        Some(scope) => {
+                            let mut name = finder.resolve_string(scope)?.into_owned();
+                            name.extend_from_slice(b"::");
+                            name.extend_from_slice(function.name.as_bytes());
+                            Some(name)
+                        }
+                        None => None,
+                    };
+
+                    let candidate = FunctionCandidate {
+                        id: item.index(),
+                        name: function.name,
+                        qualified_name,
+                    };
+                    (function.function_type, candidate)
+                }
+                Ok(IdData::MemberFunction(function)) => {
+                    // member function types include the class, so the name suffices
+                    let candidate = FunctionCandidate {
+                        id: item.index(),
+                        name: function.name,
+                        qualified_name: None,
+                    };
+                    (function.function_type, candidate)
+                }
+                _ => continue,
+            };
+
+            candidates.entry(function_type).or_default().push(candidate);
+        }
+
+        Ok(FunctionSymbols {
+            candidates,
+            procedures: HashMap::new(),
+            functions: HashMap::new(),
+        })
+    }
+
+    /// Matches the procedure symbols of a module to their function ids.
+    ///
+    /// `module` is the index of the module in the DBI module list, which is reported in the
+    /// [`ProcedureLocation`]s of its procedures.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnimplementedFeature` if the symbol data of the module is in an unsupported format
+    /// * `Error::UnexpectedEof` if the symbols of the module are truncated
+    pub fn add_module(&mut self, module: usize, info: &ModuleInfo<'_>) -> Result<()> {
+        let mut symbols = info.symbols()?;
+        while let Some(symbol) = symbols.next()? {
+            let procedure = match symbol.parse() {
+                Ok(SymbolData::Procedure(procedure)) => procedure,
+                _ => continue,
+            };
+
+            let id = match symbol.raw_kind() {
+                S_GPROC32_ID | S_LPROC32_ID | S_LPROC32_DPC_ID => {
+                    Some(IdIndex(procedure.type_index.0))
+                }
+                _ => self.match_procedure(procedure.type_index, procedure.name.as_bytes()),
+            };
+
+            if let Some(id) = id {
+                let location = ProcedureLocation {
+                    module,
+                    symbol: symbol.index(),
+                };
+                self.procedures.entry(id).or_default().push(location);
+                self.functions.insert(location, id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the locations of all procedure symbols implementing a function id.
+    ///
+    /// The id is usually the [`inlinee`](crate::InlineSiteSymbol::inlinee) of an inline site.
+    /// Functions can have several procedures, for instance if they are defined in a header and
+    /// compiled into several modules without being folded by the linker.
+    pub fn procedures_of(&self, id: IdIndex) -> &[ProcedureLocation] {
+        self.procedures.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the function id of the procedure symbol at the given location.
+    pub fn function_of(&self, location: ProcedureLocation) -> Option<IdIndex> {
+        self.functions.get(&location).copied()
+    }
+
+    /// Finds the function id with the given function type and name of a linked procedure.
+    fn match_procedure(&self, function_type: TypeIndex, name: &[u8]) -> Option<IdIndex> {
+        let candidates = self.candidates.get(&function_type)?;
+        let unqualified = unqualified_name(name);
+        let untemplated = strip_template_arguments(unqualified);
+
+        let mut matches = candidates.iter().filter(|candidate| {
+            let candidate = candidate.name.as_bytes();
+            candidate == unqualified || candidate == untemplated
+        });
+
+        let first = matches.next()?;
+        if matches.next().is_none() {
+            return Some(first.id);
+        }
+
+        // functions with the same signature in different namespaces
+        let mut qualified = candidates.iter().filter(|candidate| {
+            candidate.qualified_name.as_deref() == Some(name)
+                || candidate.qualified_name.as_deref() == Some(strip_template_arguments(name))
+        });
+        match (qualified.next(), qualified.next()) {
+            (Some(candidate), None) => Some(candidate.id),
+            _ => None,
+        }
+    }
+}
+
+impl<'t> IdFinder<'t> {
+    /// Returns the qualified name of a function id.
+    ///
+    /// For `LF_FUNC_ID` records, the name is prefixed with the namespace of the function, if any.
+    /// For `LF_MFUNC_ID` records, it is prefixed with the name of the class declaring the member
+    /// function, which is looked up in `type_finder`. Without a type finder, only the name of the
+    /// member function is returned.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedIdKind(index)` if the id is not a function, or its scope is not a string
+    /// * `Error::TypeNotFound(index)` if the id or one of the records it refers to does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if an id or type is not yet known by a finder
+    pub fn function_name(
+        &self,
+        index: IdIndex,
+        type_finder: Option<&TypeFinder<'_>>,
+    ) -> Result<String> {
+        let (scope, name) = match self.find(index)?.parse()? {
+            IdData::Function(function) => {
+                let scope = match function.scope {
+                    Some(scope) => Some(self.resolve_string(scope)?.into_owned()),
+                    None => None,
+                };
+                (scope, function.name)
+            }
+            IdData::MemberFunction(function) => {
+                let scope = match type_finder {
+                    Some(finder) => finder
+                        .find(function.parent)?
+                        .parse()?
+                        .name()
+                        .map(|name| name.as_bytes().to_vec()),
+                    None => None,
+                };
+                (scope, function.name)
+            }
+            _ => return Err(Error::UnexpectedIdKind(index.0)),
+        };
+
+        let mut qualified = scope.unwrap_or_default();
+        if !qualified.is_empty() {
+            qualified.extend_from_slice(b"::");
+        }
+        qualified.extend_from_slice(name.as_bytes());
+        Ok(String::from_utf8_lossy(&qualified).into_owned())
+    }
+}
+
+/// Returns the last component of a qualified C++ name, ignoring separators in template arguments
+/// and parameter lists.
+fn unqualified_name(name: &[u8]) -> &[u8] {
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < name.len() {
+        match name[i] {
+            b'<' | b'(' => depth += 1,
+            b'>' | b')' => depth = depth.saturating_sub(1),
+            b':' if depth == 0 && name.get(i + 1) == Some(&b':') => {
+                start = i + 2;
+                i += 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    &name[start..]
+}
+
+/// Removes trailing template arguments from a name, such as `<int>` in `max<int>`.
+fn strip_template_arguments(name: &[u8]) -> &[u8] {
+    if name.last() != Some(&b'>') {
+        return name;
+    }
+
+    let mut depth = 0usize;
+    for (i, &c) in name.iter().enumerate().rev() {
+        match c {
+            b'>' => depth += 1,
+            b'<' => {
+                depth -= 1;
+                if depth == 0 {
+                    return &name[..i];
+                }
+            }
+            _ => {}
+        }
+    }
+
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unqualified_name() {
+        assert_eq!(unqualified_name(b"main"), b"main");
+        assert_eq!(unqualified_name(b"Baz::f_public"), b"f_public");
+        assert_eq!(unqualified_name(b"a::b<c::d>::f<e::g>"), b"f<e::g>");
+        assert_eq!(
+            unqualified_name(b"`__scrt_common_main_seh'::`1'::filt$0"),
+            b"filt$0"
+        );
+    }
+
+    #[test]
+    fn test_strip_template_arguments() {
+        assert_eq!(strip_template_arguments(b"max"), b"max");
+        assert_eq!(strip_template_arguments(b"max<int>"), b"max");
+        assert_eq!(
+            strip_template_arguments(b"f<std::vector<int,std::allocator<int> > >"),
+            b"f"
+        );
+        assert_eq!(strip_template_arguments(b"operator>"), b"operator>");
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::BTreeMap;
+
+use crate::common::*;
+use crate::tpi::TypeFinder;
+
+/// The types transitively referenced by a type, along with the references between them.
+///
+/// Obtain a graph using [`TypeFinder::dependency_graph`]. Every node is a type record in the type
+/// stream; primitive types are not part of the graph. Forward references to classes, structures,
+/// unions and enumerations have an edge to their definition, if the stream contains one.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TypeGraph {
+    root: TypeIndex,
+    edges: BTreeMap<TypeIndex, Vec<TypeIndex>>,
+}
+
+impl TypeGraph {
+    /// The type the graph was built for.
+    pub fn root(&self) -> TypeIndex {
+        self.root
+    }
+
+    /// Returns the number of types in the graph, including the root.
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Returns whether the graph contains no types.
+    ///
+    /// This is only the case if the root is a primitive type.
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    /// Returns whether the given type is part of the graph.
+    pub fn contains(&self, index: TypeIndex) -> bool {
+        self.edges.contains_key(&index)
+    }
+
+    /// Returns an iterator over all types in the graph in ascending order of their indices.
+    pub fn types(&self) -> impl Iterator<Item = TypeIndex> + '_ {
+        self.edges.keys().copied()
+    }
+
+    /// Returns the types directly referenced by the given type, in ascending order.
+    ///
+    /// Returns an empty slice if the type is not part of the graph.
+    pub fn references(&self, index: TypeIndex) -> &[TypeIndex] {
+        self.edges.get(&index).map_or(&[], Vec::as_slice)
+    }
+}
+
+impl<'t> TypeFinder<'t> {
+    /// Builds the graph of all types transitively referenced by the given type.
+    ///
+    /// This follows every type index in the records, such as fields, base classes, argument lists,
+    /// element types and method lists. Forward references are resolved to their definitions using
+    /// [`resolve_definition`](Self::resolve_definition). Records that are not understood by this
+    /// library are included in the graph without references.
+    ///
+    /// All referenced types must be known by this `TypeFinder`, so it should be fully populated
+    /// before calling this.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::TypeNotFound(index)` if the type or one of the types it refers to does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if a type is not yet known by this finder
+    pub fn dependency_graph(&self, index: TypeIndex) -> Result<TypeGraph> {
+        let mut edges = BTreeMap::new();
+        let mut pending = vec![index];
+
+        while let Some(current) = pending.pop() {
+            if current.0 < self.minimum_index || edges.contains_key(&current) {
+                continue;
+            }
+
+            let mut references = match self.find(current)?.parse() {
+                Ok(data) => data.referenced_types(),
+                Err(Error::UnimplementedTypeKind(_)) => Vec::new(),
+                Err(e) => return Err(e),
+            };
+
+            if let Some(definition) = self.resolve_definition(current)? {
+                if definition != current {
+                    references.push(definition);
+                }
+            }
+
+            references.retain(|r| r.0 >= self.minimum_index);
+            references.sort_unstable();
+            references.dedup();
+
+            pending.extend(&references);
+            edges.insert(current, references);
+        }
+
+        Ok(TypeGraph { root: index, edges })
+    }
+
+    /// Returns all types transitively referenced by the given type, in ascending order.
+    ///
+    /// The type itself and primitive types are not included. This is the set of types required to
+    /// fully define the given type. See [`dependency_graph`](Self::dependency_graph) for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// let mut finder = type_information.finder();
+    /// let mut iter = type_information.iter();
+    /// while let Some(_) = iter.next()? {
+    ///     finder.update(&iter);
+    /// }
+    ///
+    /// // the field list of class Baz
+    /// let dependencies = finder.dependencies(pdb::TypeIndex(0x1064))?;
+    /// assert!(dependencies.contains(&pdb::TypeIndex(0x1063)));
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::TypeNotFound(index)` if the type or one of the types it refers to does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if a type is not yet known by this finder
+    pub fn dependencies(&self, index: TypeIndex) -> Result<Vec<TypeIndex>> {
+        let graph = self.dependency_graph(index)?;
+        Ok(graph.types().filter(|&i| i != index).collect())
+    }
+}
@@ -0,0 +1,481 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::convert::TryFrom;
+use std::marker::PhantomData;
+
+use crate::common::*;
+use crate::msf::Stream;
+use crate::pdbi::parse_bit_array;
+use crate::tpi::constants::{LF_UDT_MOD_SRC_LINE, LF_UDT_SRC_LINE};
+use crate::tpi::data::TypeData;
+use crate::tpi::header::{Header, Slice};
+use crate::tpi::id::{IdData, StringId};
+use crate::tpi::writer::encode_id;
+use crate::tpi::{IdInformation, Item, ItemInformation, TypeInformation};
+
+/// The hash stream of the type or id information stream.
+///
+/// The hash stream holds the hash value of every record, along with an index of record offsets.
+/// This allows looking up records without iterating over the entire stream. Depending on the
+/// stream, this is either [`TypeHashes`] or [`IdHashes`].
+#[derive(Debug)]
+pub struct ItemHashes<I> {
+    /// The number of hash buckets used to compute the hash values.
+    buckets: u32,
+    /// Pairs of hash value and item index, sorted by hash value.
+    hashes: Vec<(u32, u32)>,
+    /// Pairs of item index and offset of its record, sorted by item index.
+    offsets: Vec<(u32, u32)>,
+    /// Types taking precedence over others with the same name.
+    adjusters: Vec<HashAdjuster>,
+    _ph: PhantomData<I>,
+}
+
+/// The hash stream of the type information stream (TPI).
+///
+/// The hash stream holds the hash value of every type record, along with an index of record
+/// offsets. This allows looking up user-defined types by name without iterating over the entire
+/// type stream. Classes, structures, unions and enumerations are hashed by their name, which makes
+/// them available to [`find_by_name`](ItemHashes::find_by_name).
+///
+/// Obtain the hash stream using [`PDB::type_hashes`](crate::PDB::type_hashes).
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(file)?;
+/// let type_information = pdb.type_information()?;
+/// let hashes = pdb.type_hashes()?.expect("hash stream");
+///
+/// let index = hashes.find_by_name(&type_information, "_iobuf")?;
+/// assert!(index.is_some());
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+pub type TypeHashes = ItemHashes<TypeIndex>;
+
+/// The hash stream of the id information stream (IPI).
+///
+/// Most id records are hashed by their contents, which allows finding an existing record equal to
+/// a given [`IdData`] with [`find`](ItemHashes::find) without iterating over the entire id stream.
+/// This is how linkers deduplicate ids when merging them. Strings that are not split into
+/// substrings can be found by their contents with [`find_string`](ItemHashes::find_string), and
+/// the source locations of user-defined types by their type index with
+/// [`find_udt_source`](ItemHashes::find_udt_source).
+///
+/// Obtain the hash stream using [`PDB::id_hashes`](crate::PDB::id_hashes).
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(file)?;
+/// let id_information = pdb.id_information()?;
+/// let hashes = pdb.id_hashes()?.expect("hash stream");
+///
+/// let index = hashes.find_string(&id_information, b"foo.cpp")?;
+/// assert_eq!(index, Some(pdb::IdIndex(0x1017)));
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+pub type IdHashes = ItemHashes<IdIndex>;
+
+/// An entry of the hash adjuster table of the type information stream.
+///
+/// When several types share the same name, for example after an incremental link replaced a
+/// definition, the linker records which of them name lookups should resolve to. The type of an
+/// adjuster takes precedence over all other types with its name.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HashAdjuster {
+    /// The name of the type, as an offset into the PDB's [`StringTable`](crate::StringTable).
+    pub name: StringRef,
+    /// The type name lookups resolve to.
+    pub index: TypeIndex,
+}
+
+impl<I> ItemHashes<I>
+where
+    I: ItemIndex,
+{
+    pub(crate) fn parse(stream: Stream<'_>, header: &Header) -> Result<Self> {
+        if header.hash_key_size != 4 {
+            return Err(Error::UnimplementedFeature("hash key size other than 4"));
+        }
+        if header.hash_bucket_size == 0 {
+            return Err(Error::InvalidTypeInformationHeader("no hash buckets"));
+        }
+
+        let count = header.maximum_index.saturating_sub(header.minimum_index) as usize;
+        let mut buf = slice_buffer(&stream, header.hash_values)?;
+        if buf.len() / 4 < count {
+            return Err(Error::UnexpectedEof);
+        }
+
+        let mut hashes = Vec::with_capacity(count);
+        for i in 0..count {
+            hashes.push((buf.parse_u32()?, header.minimum_index + i as u32));
+        }
+        hashes.sort_unstable();
+
+        let mut buf = slice_buffer(&stream, header.ti_off)?;
+        let mut offsets = Vec::with_capacity(buf.len() / 8);
+        while buf.len() >= 8 {
+            offsets.push((buf.parse_u32()?, buf.parse_u32()?));
+        }
+        offsets.sort_unstable();
+
+        let adjusters = match header.hash_adj.size {
+            0 => Vec::new(),
+            _ => parse_adjusters(&mut slice_buffer(&stream, header.hash_adj)?)?,
+        };
+
+        Ok(Self {
+            buckets: header.hash_bucket_size,
+            hashes,
+            offsets,
+            adjusters,
+            _ph: PhantomData,
+        })
+    }
+
+    /// Returns the number of hash buckets.
+    pub fn buckets(&self) -> u32 {
+        self.buckets
+    }
+
+    /// Returns the indices of all records with the given hash value, before reducing it to a
+    /// bucket.
+    fn bucket(&self, hash: u32) -> impl Iterator<Item = I> + '_ {
+        let bucket = hash % self.buckets;
+        let start = self.hashes.partition_point(|&(hash, _)| hash < bucket);
+        self.hashes[start..]
+            .iter()
+            .take_while(move |&&(hash, _)| hash == bucket)
+            .map(|&(_, index)| I::from(index))
+    }
+
+    /// Returns pairs of item index and offset of its record, sorted by item index.
+    ///
+    /// Offsets are relative to the end of the stream header.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn index_offsets(&self) -> &[(u32, u32)] {
+        &self.offsets
+    }
+
+    /// Looks up a record by its index without an [`ItemFinder`](crate::ItemFinder).
+    ///
+    /// The hash stream records the offsets of records at regular intervals, usually every 8 KiB.
+    /// The record is found by scanning the stream from the closest preceding offset, so the cost
+    /// of a lookup does not depend on the size of the stream. This is cheaper than populating a
+    /// finder when only a few records are looked up, such as the inlinees of a handful of inline
+    /// sites.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let id_information = pdb.id_information()?;
+    /// let hashes = pdb.id_hashes()?.expect("hash stream");
+    ///
+    /// let id = hashes.find_item(&id_information, pdb::IdIndex(0x1005))?;
+    /// assert_eq!(id.parse()?.name().unwrap().to_string(), "main");
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::TypeNotFound(index)` if the record does not exist
+    /// * `Error::UnexpectedEof` if the stream is truncated
+    pub fn find_item<'t>(
+        &self,
+        information: &'t ItemInformation<'_, I>,
+        index: I,
+    ) -> Result<Item<'t, I>> {
+        let header = &information.header;
+        let raw_index: u32 = index.into();
+        if raw_index < header.minimum_index || raw_index >= header.maximum_index {
+            return Err(Error::TypeNotFound(raw_index));
+        }
+
+        // start at the closest preceding record with a known offset
+        let (mut current, offset) = match self.offsets.partition_point(|&(ti, _)| ti <= raw_index) {
+            0 => (header.minimum_index, 0),
+            position => self.offsets[position - 1],
+        };
+
+        let mut buf = information.stream.parse_buffer();
+        buf.take(header.header_size as usize + offset as usize)?;
+
+        loop {
+            let length = buf.parse_u16()? as usize;
+            let data = buf.take(length)?;
+            if current == raw_index {
+                return Ok(Item { index, data });
+            }
+            current += 1;
+        }
+    }
+}
+
+impl TypeHashes {
+    /// Returns the entries of the hash adjuster table, in the order they are stored.
+    pub fn adjusters(&self) -> &[HashAdjuster] {
+        &self.adjusters
+    }
+
+    /// Returns the indices of all types whose hash value matches the given name.
+    ///
+    /// The result may contain types with a different name that share the same hash bucket.
+    pub fn candidates(&self, name: &[u8]) -> impl Iterator<Item = TypeIndex> + '_ {
+        self.bucket(hash_v1(name))
+    }
+
+    /// Finds all classes, structures, unions and enumerations with the given name.
+    ///
+    /// The returned types are in the order of their type indices. Global definitions are hashed by
+    /// their name and are always found. Forward references and types declared in a function scope
+    /// are usually hashed by their record contents or unique name instead, so most of them cannot
+    /// be found by name.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::TypeNotFound(index)` if the hash stream refers to a type that does not exist
+    /// * `Error::UnexpectedEof` if the type stream is truncated
+    pub fn find_all_by_name(
+        &self,
+        types: &TypeInformation<'_>,
+        name: &str,
+    ) -> Result<Vec<TypeIndex>> {
+        let mut matches = Vec::new();
+        for index in self.candidates(name.as_bytes()) {
+            if let Ok(data) = self.find_item(types, index)?.parse() {
+                if data.name().map(|n| n.as_bytes()) == Some(name.as_bytes()) {
+                    matches.push(index);
+                }
+            }
+        }
+
+        matches.sort_unstable();
+        Ok(matches)
+    }
+
+    /// Finds the class, structure, union or enumeration with the given name.
+    ///
+    /// If the [hash adjuster table](Self::adjusters) contains a type with this name, that type is
+    /// returned, matching the resolution of the Microsoft tools. Otherwise, definitions are
+    /// preferred over forward references. If the name is only forward declared, the forward
+    /// reference is returned instead.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::TypeNotFound(index)` if the hash stream refers to a type that does not exist
+    /// * `Error::UnexpectedEof` if the type stream is truncated
+    pub fn find_by_name(
+        &self,
+        types: &TypeInformation<'_>,
+        name: &str,
+    ) -> Result<Option<TypeIndex>> {
+        if let Some(index) = self.adjusted(types, name)? {
+            return Ok(Some(index));
+        }
+
+        let matches = self.find_all_by_name(types, name)?;
+
+        for &index in &matches {
+            let forward_reference = match self.find_item(types, index)?.parse()? {
+                TypeData::Class(class) => class.properties.forward_reference(),
+                TypeData::Union(union) => union.properties.forward_reference(),
+                TypeData::Enumeration(enumeration) => enumeration.properties.forward_reference(),
+                _ => true,
+            };
+
+            if !forward_reference {
+                return Ok(Some(index));
+            }
+        }
+
+        Ok(matches.first().copied())
+    }
+
+    /// Returns the type of the hash adjuster with the given name, if there is one.
+    ///
+    /// Adjusters refer to their name by an offset into the string table, so the name of their type
+    /// record is compared instead.
+    fn adjusted(&self, types: &TypeInformation<'_>, name: &str) -> Result<Option<TypeIndex>> {
+        for adjuster in &self.adjusters {
+            if let Ok(data) = self.find_item(types, adjuster.index)?.parse() {
+                if data.name().map(|n| n.as_bytes()) == Some(name.as_bytes()) {
+                    return Ok(Some(adjuster.index));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl IdHashes {
+    /// Finds an id record with the same contents as `data`.
+    ///
+    /// The record is encoded the way [`IdInfoWriter`](crate::IdInfoWriter) would write it and
+    /// compared with the records sharing its hash value, so indices referenced by `data` must match
+    /// those of this stream. Records of user-defined type source locations are hashed by their type
+    /// instead of their contents; they are found as well.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::RecordTooLarge(size)` if `data` cannot be encoded in a single record
+    /// * `Error::TypeNotFound(index)` if the hash stream refers to an id that does not exist
+    /// * `Error::UnexpectedEof` if the id stream is truncated
+    pub fn find(&self, ids: &IdInformation<'_>, data: &IdData<'_>) -> Result<Option<IdIndex>> {
+        let record = encode_id(data)?;
+        let hash = match data {
+            IdData::UserDefinedTypeSource(source) => hash_v1(&source.udt.0.to_le_bytes()),
+            _ => {
+                // the length prefix is hashed along with the record
+                let mut prefixed = (record.len() as u16).to_le_bytes().to_vec();
+                prefixed.extend_from_slice(&record);
+                hash_v8(&prefixed)
+            }
+        };
+
+        for index in self.bucket(hash) {
+            if self.find_item(ids, index)?.data == record.as_slice() {
+                return Ok(Some(index));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Finds the `LF_STRING_ID` record containing exactly the given string.
+    ///
+    /// Only strings that are stored in a single record are found. Long strings split into
+    /// substrings, such as command lines, cannot be looked up by their contents.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::RecordTooLarge(size)` if the string does not fit into a single record
+    /// * `Error::TypeNotFound(index)` if the hash stream refers to an id that does not exist
+    /// * `Error::UnexpectedEof` if the id stream is truncated
+    pub fn find_string(&self, ids: &IdInformation<'_>, string: &[u8]) -> Result<Option<IdIndex>> {
+        let data = IdData::String(StringId {
+            name: RawString::from(string),
+            substrings: None,
+        });
+        self.find(ids, &data)
+    }
+
+    /// Finds the source location record of a user-defined type.
+    ///
+    /// This is the `LF_UDT_SRC_LINE` or `LF_UDT_MOD_SRC_LINE` record referring to `udt`, which
+    /// must be the index of a class, structure, union or enumeration definition.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::TypeNotFound(index)` if the hash stream refers to an id that does not exist
+    /// * `Error::UnexpectedEof` if the id stream is truncated
+    pub fn find_udt_source(
+        &self,
+        ids: &IdInformation<'_>,
+        udt: TypeIndex,
+    ) -> Result<Option<IdIndex>> {
+        for index in self.bucket(hash_v1(&udt.0.to_le_bytes())) {
+            let item = self.find_item(ids, index)?;
+            if !matches!(item.raw_kind(), LF_UDT_SRC_LINE | LF_UDT_MOD_SRC_LINE) {
+                continue;
+            }
+
+            if let Ok(IdData::UserDefinedTypeSource(source)) = item.parse() {
+                if source.udt == udt {
+                    return Ok(Some(index));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Parses the hash adjuster table, a serialized hash map from name to type index.
+///
+/// The map has the same layout as the named stream map: the number of entries, the capacity, bit
+/// arrays of present and deleted buckets, and one pair of name offset and type index per entry.
+fn parse_adjusters(buf: &mut ParseBuffer<'_>) -> Result<Vec<HashAdjuster>> {
+    let size = buf.parse_u32()? as usize;
+    let _capacity = buf.parse_u32()?;
+    parse_bit_array(buf)?;
+    parse_bit_array(buf)?;
+
+    if size > buf.len() / 8 {
+        return Err(Error::UnexpectedEof);
+    }
+
+    let mut adjusters = Vec::with_capacity(size);
+    for _ in 0..size {
+        adjusters.push(HashAdjuster {
+            name: StringRef(buf.parse_u32()?),
+            index: TypeIndex(buf.parse_u32()?),
+        });
+    }
+
+    Ok(adjusters)
+}
+
+/// Returns a buffer over a slice of the hash stream.
+fn slice_buffer<'a>(stream: &'a Stream<'_>, slice: Slice) -> Result<ParseBuffer<'a>> {
+    let start = usize::try_from(slice.offset).map_err(|_| Error::UnexpectedEof)?;
+    let end = start + slice.size as usize;
+    match stream.as_slice().get(start..end) {
+        Some(data) => Ok(ParseBuffer::from(data)),
+        None => Err(Error::UnexpectedEof),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_adjusters() {
+        let data = [
+            2, 0, 0, 0, // size
+            4, 0, 0, 0, // capacity
+            1, 0, 0, 0, 0x0a, 0, 0, 0, // present: buckets 1 and 3
+            0, 0, 0, 0, // deleted
+            0x10, 0, 0, 0, 0x00, 0x20, 0, 0, // name 0x10 => 0x2000
+            0x24, 0, 0, 0, 0x05, 0x10, 0, 0, // name 0x24 => 0x1005
+        ];
+
+        let adjusters = parse_adjusters(&mut ParseBuffer::from(&data[..])).expect("parse");
+        assert_eq!(
+            adjusters,
+            [
+                HashAdjuster {
+                    name: StringRef(0x10),
+                    index: TypeIndex(0x2000),
+                },
+                HashAdjuster {
+                    name: StringRef(0x24),
+                    index: TypeIndex(0x1005),
+                },
+            ]
+        );
+
+        assert!(parse_adjusters(&mut ParseBuffer::from(&data[..28])).is_err());
+    }
+}
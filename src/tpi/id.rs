@@ -38,7 +38,49 @@ pub enum IdData<'t> {
     UserDefinedTypeSource(UserDefinedTypeSourceId),
 }
 
-impl<'t> IdData<'t> {}
+impl<'t> IdData<'t> {
+    /// Return the name of this `IdData`, if any.
+    ///
+    /// For [`IdData::String`], this is the last part of the string only. Strings split into
+    /// substrings must be concatenated with the strings of their [`StringListId`].
+    pub fn name(&self) -> Option<RawString<'t>> {
+        match self {
+            Self::Function(data) => Some(data.name),
+            Self::MemberFunction(data) => Some(data.name),
+            Self::String(data) => Some(data.name),
+            Self::BuildInfo(_) | Self::StringList(_) | Self::UserDefinedTypeSource(_) => None,
+        }
+    }
+
+    /// Returns the indices of all ids directly referenced by this id.
+    ///
+    /// The indices are returned in the order they appear in the record. Ids in other modules, such
+    /// as the source files of [`UserDefinedTypeSourceFileRef::Remote`], are not included.
+    pub fn referenced_ids(&self) -> Vec<IdIndex> {
+        match self {
+            Self::Function(data) => data.scope.into_iter().collect(),
+            Self::MemberFunction(_) => Vec::new(),
+            Self::BuildInfo(data) => data.arguments.clone(),
+            Self::StringList(data) => data.substrings.clone(),
+            Self::String(data) => data.substrings.into_iter().collect(),
+            Self::UserDefinedTypeSource(data) => match data.source_file {
+                UserDefinedTypeSourceFileRef::Local(index) => vec![index],
+                UserDefinedTypeSourceFileRef::Remote(..) => Vec::new(),
+            },
+        }
+    }
+
+    /// Returns the indices of all types in the [`TypeInformation`](crate::TypeInformation)
+    /// referenced by this id.
+    pub fn referenced_types(&self) -> Vec<TypeIndex> {
+        match self {
+            Self::Function(data) => vec![data.function_type],
+            Self::MemberFunction(data) => vec![data.parent, data.function_type],
+            Self::UserDefinedTypeSource(data) => vec![data.udt],
+            Self::BuildInfo(_) | Self::StringList(_) | Self::String(_) => Vec::new(),
+        }
+    }
+}
 
 impl<'t> TryFromCtx<'t, scroll::Endian> for IdData<'t> {
     type Error = Error;
@@ -60,7 +102,7 @@ impl<'t> TryFromCtx<'t, scroll::Endian> for IdData<'t> {
             }),
             LF_BUILDINFO => IdData::BuildInfo({
                 let count = buf.parse::<u16>()?;
-                let mut arguments = Vec::with_capacity(count as usize);
+                let mut arguments = Vec::with_capacity(count.min(0x1000) as usize);
                 for _ in 0..count {
                     arguments.push(buf.parse()?);
                 }
@@ -68,7 +110,7 @@ impl<'t> TryFromCtx<'t, scroll::Endian> for IdData<'t> {
             }),
             LF_SUBSTR_LIST => IdData::StringList({
                 let count = buf.parse::<u32>()?;
-                let mut substrings = Vec::with_capacity(count as usize);
+                let mut substrings = Vec::with_capacity(count.min(0x1000) as usize);
                 for _ in 0..count {
                     substrings.push(buf.parse()?);
                 }
@@ -142,13 +184,17 @@ pub struct BuildInfoId {
 /// This Id is usually referenced by [`StringId`].
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct StringListId {
-    /// The list of substrings.
-    pub substrings: Vec<TypeIndex>,
+    /// Indexes of the substrings, each referring to a [`StringId`].
+    pub substrings: Vec<IdIndex>,
 }
 
 /// A string.
 ///
 /// This Id is usually referenced by [`FunctionId`] and contains the full namespace of a function.
+/// Long strings, such as command lines in [`BuildInfoId`], are split into several records. The
+/// full string is the concatenation of all strings in [`substrings`](Self::substrings), followed
+/// by [`name`](Self::name). Use [`IdFinder::resolve_string`](crate::ItemFinder::resolve_string)
+/// to obtain the full string.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct StringId<'t> {
     /// Index of the list of substrings.
@@ -182,3 +228,161 @@ pub struct UserDefinedTypeSourceId {
     /// Line number in the source file.
     pub line: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(data: &[u8]) -> Result<IdData<'_>> {
+        ParseBuffer::from(data).parse()
+    }
+
+    #[test]
+    fn kind_1601() {
+        let mut data = vec![0x01, 0x16];
+        data.extend_from_slice(&0x1003u32.to_le_bytes());
+        data.extend_from_slice(&0x100au32.to_le_bytes());
+        data.extend_from_slice(b"main\0");
+
+        let id = parse(&data).expect("parse");
+        assert_eq!(
+            id,
+            IdData::Function(FunctionId {
+                scope: Some(IdIndex(0x1003)),
+                function_type: TypeIndex(0x100a),
+                name: RawString::from("main"),
+            })
+        );
+        assert_eq!(id.name(), Some(RawString::from("main")));
+        assert_eq!(id.referenced_ids(), [IdIndex(0x1003)]);
+        assert_eq!(id.referenced_types(), [TypeIndex(0x100a)]);
+    }
+
+    #[test]
+    fn kind_1602() {
+        let mut data = vec![0x02, 0x16];
+        data.extend_from_slice(&0x1000u32.to_le_bytes());
+        data.extend_from_slice(&0x1003u32.to_le_bytes());
+        data.extend_from_slice(b"f_public\0");
+
+        let id = parse(&data).expect("parse");
+        assert_eq!(
+            id,
+            IdData::MemberFunction(MemberFunctionId {
+                parent: TypeIndex(0x1000),
+                function_type: TypeIndex(0x1003),
+                name: RawString::from("f_public"),
+            })
+        );
+        assert!(id.referenced_ids().is_empty());
+        assert_eq!(
+            id.referenced_types(),
+            [TypeIndex(0x1000), TypeIndex(0x1003)]
+        );
+    }
+
+    #[test]
+    fn kind_1603() {
+        let mut data = vec![0x03, 0x16, 0x02, 0x00];
+        data.extend_from_slice(&0x1015u32.to_le_bytes());
+        data.extend_from_slice(&0x1016u32.to_le_bytes());
+
+        let id = parse(&data).expect("parse");
+        assert_eq!(
+            id,
+            IdData::BuildInfo(BuildInfoId {
+                arguments: vec![IdIndex(0x1015), IdIndex(0x1016)],
+            })
+        );
+        assert_eq!(id.name(), None);
+        assert_eq!(id.referenced_ids(), [IdIndex(0x1015), IdIndex(0x1016)]);
+    }
+
+    #[test]
+    fn kind_1604() {
+        let mut data = vec![0x04, 0x16];
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&0x1019u32.to_le_bytes());
+        data.extend_from_slice(&0x101au32.to_le_bytes());
+
+        let id = parse(&data).expect("parse");
+        assert_eq!(
+            id,
+            IdData::StringList(StringListId {
+                substrings: vec![IdIndex(0x1019), IdIndex(0x101a)],
+            })
+        );
+        assert_eq!(id.referenced_ids(), [IdIndex(0x1019), IdIndex(0x101a)]);
+
+        // a count exceeding the record is an error, not a huge allocation
+        let mut truncated = vec![0x04, 0x16];
+        truncated.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(parse(&truncated), Err(Error::UnexpectedEof)));
+    }
+
+    #[test]
+    fn kind_1605() {
+        let mut data = vec![0x05, 0x16];
+        data.extend_from_slice(&0x101bu32.to_le_bytes());
+        data.extend_from_slice(b"\" -TP -X\0");
+
+        let id = parse(&data).expect("parse");
+        assert_eq!(
+            id,
+            IdData::String(StringId {
+                substrings: Some(IdIndex(0x101b)),
+                name: RawString::from("\" -TP -X"),
+            })
+        );
+        assert_eq!(id.referenced_ids(), [IdIndex(0x101b)]);
+    }
+
+    #[test]
+    fn kind_1606() {
+        let mut data = vec![0x06, 0x16];
+        data.extend_from_slice(&0x1064u32.to_le_bytes());
+        data.extend_from_slice(&0x1017u32.to_le_bytes());
+        data.extend_from_slice(&12u32.to_le_bytes());
+
+        let id = parse(&data).expect("parse");
+        assert_eq!(
+            id,
+            IdData::UserDefinedTypeSource(UserDefinedTypeSourceId {
+                udt: TypeIndex(0x1064),
+                source_file: UserDefinedTypeSourceFileRef::Local(IdIndex(0x1017)),
+                line: 12,
+            })
+        );
+        assert_eq!(id.referenced_ids(), [IdIndex(0x1017)]);
+        assert_eq!(id.referenced_types(), [TypeIndex(0x1064)]);
+    }
+
+    #[test]
+    fn kind_1607() {
+        let mut data = vec![0x07, 0x16];
+        data.extend_from_slice(&0x1064u32.to_le_bytes());
+        data.extend_from_slice(&0x92u32.to_le_bytes());
+        data.extend_from_slice(&12u32.to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes());
+
+        let id = parse(&data).expect("parse");
+        assert_eq!(
+            id,
+            IdData::UserDefinedTypeSource(UserDefinedTypeSourceId {
+                udt: TypeIndex(0x1064),
+                source_file: UserDefinedTypeSourceFileRef::Remote(1, StringRef(0x92)),
+                line: 12,
+            })
+        );
+        assert!(id.referenced_ids().is_empty());
+    }
+
+    #[test]
+    fn unknown_kind() {
+        let data = [0x08, 0x16, 0x00, 0x00];
+        assert!(matches!(
+            parse(&data),
+            Err(Error::UnimplementedTypeKind(0x1608))
+        ));
+    }
+}
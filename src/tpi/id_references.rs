@@ -0,0 +1,210 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::common::*;
+use crate::modi::ModuleInfo;
+use crate::symbol::constants::{S_GPROC32_ID, S_LPROC32_DPC_ID, S_LPROC32_ID};
+use crate::symbol::SymbolData;
+use crate::tpi::constants::{LF_BUILDINFO, LF_FUNC_ID, LF_MFUNC_ID, LF_STRING_ID};
+use crate::tpi::id::{IdData, UserDefinedTypeSourceFileRef};
+use crate::tpi::{IdFinder, IdInformation};
+use crate::FallibleIterator;
+
+/// The record containing an id reference, as reported by [`IdReferenceValidator`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum IdReferenceSource {
+    /// A symbol in the symbols of a module.
+    Symbol {
+        /// Index of the module in the DBI module list.
+        module: usize,
+        /// Index of the symbol in the symbols of the module.
+        symbol: SymbolIndex,
+    },
+    /// A record in the id information stream.
+    Id(IdIndex),
+}
+
+/// The reason an id reference is invalid.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum IdReferenceProblem {
+    /// The referenced id does not exist in the id information stream.
+    Missing,
+    /// The referenced id exists, but its record kind is not valid for the reference.
+    ///
+    /// Contains the raw kind of the referenced record.
+    UnexpectedKind(u16),
+}
+
+/// An invalid reference to the id information stream (IPI).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct DanglingIdReference {
+    /// The record containing the reference.
+    pub source: IdReferenceSource,
+    /// The referenced id.
+    pub id: IdIndex,
+    /// The reason the reference is invalid.
+    pub problem: IdReferenceProblem,
+}
+
+/// Checks that references into the id information stream resolve to records of the right kind.
+///
+/// Symbols and some id records refer to other records in the id information stream by their
+/// [`IdIndex`]. Damaged PDBs, or PDBs produced by tools merging id streams incorrectly, may
+/// contain references to ids that do not exist or that have an unexpected kind. Such references
+/// cause lookups with an [`IdFinder`] to fail later on. The following references
+/// are checked:
+///
+///  - the build information of [`BuildInfoSymbol`](crate::BuildInfoSymbol)s must be an
+///    `LF_BUILDINFO` record,
+///  - the inlinee of [`InlineSiteSymbol`](crate::InlineSiteSymbol)s and the function id of
+///    `S_GPROC32_ID` procedures must be an `LF_FUNC_ID` or `LF_MFUNC_ID` record,
+///  - the source file of `LF_UDT_SRC_LINE` records must be an `LF_STRING_ID` record.
+///
+/// Cross module references are not resolved and are therefore not checked.
+///
+/// Create the validator with [`IdReferenceValidator::new`], which checks the id stream itself,
+/// and check the symbols of every module of interest with [`add_module`](Self::add_module).
+///
+/// # Example
+///
+/// ```
+/// # use pdb::FallibleIterator;
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(file)?;
+/// let id_information = pdb.id_information()?;
+/// let debug_information = pdb.debug_information()?;
+///
+/// let mut validator = pdb::IdReferenceValidator::new(&id_information)?;
+/// let mut modules = debug_information.modules()?;
+/// let mut index = 0;
+/// while let Some(module) = modules.next()? {
+///     if let Some(info) = pdb.module_info(&module)? {
+///         validator.add_module(index, &info)?;
+///     }
+///     index += 1;
+/// }
+///
+/// for reference in validator.dangling() {
+///     println!("{:?} refers to {}: {:?}", reference.source, reference.id, reference.problem);
+/// }
+/// # assert!(validator.dangling().is_empty());
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Debug)]
+pub struct IdReferenceValidator<'t> {
+    finder: IdFinder<'t>,
+    dangling: Vec<DanglingIdReference>,
+}
+
+impl<'t> IdReferenceValidator<'t> {
+    /// Indexes an id information stream and checks the references between its records.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the id stream is truncated
+    pub fn new(id_information: &'t IdInformation<'_>) -> Result<Self> {
+        let mut finder = id_information.finder();
+        let mut sources = Vec::new();
+
+        let mut iter = id_information.iter();
+        while let Some(item) = iter.next()? {
+            finder.update(&iter);
+
+            if let Ok(IdData::UserDefinedTypeSource(data)) = item.parse() {
+                if let UserDefinedTypeSourceFileRef::Local(file) = data.source_file {
+                    sources.push((item.index(), file));
+                }
+            }
+        }
+
+        let mut validator = Self {
+            finder,
+            dangling: Vec::new(),
+        };
+
+        for (index, file) in sources {
+            validator.check(IdReferenceSource::Id(index), file, &[LF_STRING_ID])?;
+        }
+
+        Ok(validator)
+    }
+
+    /// Checks the references of the symbols of a module.
+    ///
+    /// `module` is the index of the module in the DBI module list, which is reported in the
+    /// [`IdReferenceSource`] of its invalid references.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnimplementedFeature` if the symbol data of the module is in an unsupported format
+    /// * `Error::UnexpectedEof` if the symbols of the module are truncated
+    pub fn add_module(&mut self, module: usize, info: &ModuleInfo<'_>) -> Result<()> {
+        let mut symbols = info.symbols()?;
+        while let Some(symbol) = symbols.next()? {
+            let (id, kinds) = match symbol.parse() {
+                Ok(SymbolData::BuildInfo(data)) => (data.id, &[LF_BUILDINFO][..]),
+                Ok(SymbolData::InlineSite(data)) => (data.inlinee, &[LF_FUNC_ID, LF_MFUNC_ID][..]),
+                Ok(SymbolData::Procedure(data)) => match symbol.raw_kind() {
+                    S_GPROC32_ID | S_LPROC32_ID | S_LPROC32_DPC_ID => {
+                        (IdIndex(data.type_index.0), &[LF_FUNC_ID, LF_MFUNC_ID][..])
+                    }
+                    _ => continue,
+                },
+                _ => continue,
+            };
+
+            let source = IdReferenceSource::Symbol {
+                module,
+                symbol: symbol.index(),
+            };
+            self.check(source, id, kinds)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns all invalid references found so far, in the order they were found.
+    pub fn dangling(&self) -> &[DanglingIdReference] {
+        &self.dangling
+    }
+
+    /// Returns whether all references checked so far are valid.
+    pub fn is_valid(&self) -> bool {
+        self.dangling.is_empty()
+    }
+
+    /// Records a reference to `id` if it does not resolve to one of the given record kinds.
+    fn check(&mut self, source: IdReferenceSource, id: IdIndex, kinds: &[u16]) -> Result<()> {
+        if id.is_cross_module() {
+            return Ok(());
+        }
+
+        // unlike types, ids have no primitive indices below the first record
+        let problem = match self.finder.find(id) {
+            _ if id.0 < self.finder.minimum_index => IdReferenceProblem::Missing,
+            Ok(item) if kinds.contains(&item.raw_kind()) => return Ok(()),
+            Ok(item) => IdReferenceProblem::UnexpectedKind(item.raw_kind()),
+            // the finder is fully populated, so unindexed ids are past the end of the stream
+            Err(Error::TypeNotFound(_)) | Err(Error::TypeNotIndexed(..)) => {
+                IdReferenceProblem::Missing
+            }
+            Err(error) => return Err(error),
+        };
+
+        self.dangling.push(DanglingIdReference {
+            source,
+            id,
+            problem,
+        });
+        Ok(())
+    }
+}
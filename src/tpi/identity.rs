@@ -0,0 +1,823 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::common::*;
+use crate::tpi::data::TypeData;
+use crate::tpi::remap::type_index_offsets;
+use crate::tpi::size::MAX_DEPTH;
+use crate::tpi::TypeFinder;
+
+/// Names given by compilers to types declared without a name.
+///
+/// Unlike `<unnamed-type-member>`, which is derived from the member declared with the type, these
+/// names are shared by all anonymous types and do not identify a type.
+const ANONYMOUS_NAMES: &[&[u8]] = &[b"<unnamed-tag>", b"<anonymous-tag>", b"__unnamed"];
+
+/// The kind of type described by a [`UniqueName`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum UniqueNameKind {
+    /// A structure, encoded as `U`.
+    Struct,
+    /// A class, encoded as `V`.
+    Class,
+    /// A union, encoded as `T`.
+    Union,
+    /// An enumeration, encoded as `W` followed by a digit for the underlying type.
+    Enum,
+}
+
+/// A decoded MSVC unique name of a class, structure, union or enumeration.
+///
+/// MSVC stores a decorated name alongside the name of user-defined types, such as
+/// `.?AUcomponents_type@?$__acrt_floating_type_traits@N@@`. Unlike the plain name, it is unique
+/// for types in anonymous namespaces and local types, which makes it the preferred key for
+/// matching forward references to definitions.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct UniqueName<'t> {
+    /// The kind of the type.
+    pub kind: UniqueNameKind,
+    /// The decorated qualified name, without the type prefix and the terminating `@@`.
+    pub decorated: RawString<'t>,
+}
+
+impl<'t> UniqueName<'t> {
+    /// Decodes a unique name as stored in [`TypeData::unique_name`].
+    ///
+    /// Returns `None` if the name is not a decorated type name, or if it uses an encoding that is
+    /// not supported.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use pdb::{RawString, UniqueName, UniqueNameKind};
+    ///
+    /// let name = UniqueName::parse(RawString::from(".?AVbad_cast@std@@")).unwrap();
+    /// assert_eq!(name.kind, UniqueNameKind::Class);
+    ///
+    /// let components = name.components().unwrap();
+    /// assert_eq!(components, [RawString::from("std"), RawString::from("bad_cast")]);
+    /// ```
+    pub fn parse(name: RawString<'t>) -> Option<Self> {
+        let bytes = name.as_bytes().strip_prefix(b".?A")?;
+
+        let (kind, rest) = match bytes.split_first()? {
+            (b'U', rest) => (UniqueNameKind::Struct, rest),
+            (b'V', rest) => (UniqueNameKind::Class, rest),
+            (b'T', rest) => (UniqueNameKind::Union, rest),
+            (b'W', [digit, rest @ ..]) if digit.is_ascii_digit() => (UniqueNameKind::Enum, rest),
+            _ => return None,
+        };
+
+        // template arguments and function scopes contain terminators of their own, and lambdas and
+        // other local types may carry a suffix after the terminator of the name
+        let mut decoration = Decoration::new(rest);
+        decoration.qualified_name()?;
+        let end = decoration.pos - 1;
+        let end = if rest[..end].ends_with(b"@") {
+            end - 1
+        } else {
+            end
+        };
+        Some(UniqueName {
+            kind,
+            decorated: RawString::from(&rest[..end]),
+        })
+    }
+
+    /// Returns the components of the qualified name, starting with the outermost scope.
+    ///
+    /// Returns `None` if the name contains template arguments, back references or other special
+    /// names, which require a full undecorator to split.
+    pub fn components(&self) -> Option<Vec<RawString<'t>>> {
+        let decorated: &'t [u8] = self.decorated.as_bytes();
+
+        let mut components = Vec::new();
+        for component in decorated.split(|&b| b == b'@') {
+            let special = match component.first() {
+                Some(b'?') => true,
+                Some(first) => component.len() == 1 && first.is_ascii_digit(),
+                None => true,
+            };
+
+            if special {
+                return None;
+            }
+
+            components.push(RawString::from(component));
+        }
+
+        components.reverse();
+        Some(components)
+    }
+
+    /// Returns the fully qualified C++ name encoded by this unique name.
+    ///
+    /// Unlike [`components`](Self::components), this also handles template arguments, such as in
+    /// `std::vector<int,std::allocator<int> >`. Returns `None` if the name cannot be undecorated.
+    #[cfg(feature = "demangle")]
+    pub fn demangled(&self) -> Option<String> {
+        let code = match self.kind {
+            UniqueNameKind::Struct => "U",
+            UniqueNameKind::Class => "V",
+            UniqueNameKind::Union => "T",
+            UniqueNameKind::Enum => "W4",
+        };
+
+        // unique names are the type part of an RTTI type descriptor, which can be undecorated
+        let descriptor = format!("??_R0?A{}{}@@@8", code, self.decorated);
+        let demangled =
+            msvc_demangler::demangle(&descriptor, msvc_demangler::DemangleFlags::NO_CLASS_TYPE)
+                .ok()?;
+
+        demangled
+            .strip_suffix("::`RTTI Type Descriptor'")
+            .map(str::to_owned)
+    }
+}
+
+/// A cursor over an MSVC decorated name, which finds the end of qualified names.
+///
+/// Only the structure of the name is parsed, as far as needed to skip over template arguments and
+/// the decorated names of functions declaring local types. Parsing fails on encodings that are not
+/// supported.
+struct Decoration<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    /// The number of nested names, symbols and types currently being parsed.
+    depth: usize,
+}
+
+impl<'a> Decoration<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Decoration {
+            bytes,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    /// Runs a parser for a nested component, failing once components are nested too deeply.
+    ///
+    /// Every recursion of the parser passes through a name fragment, a symbol or a type, which
+    /// keeps the stack bounded for arbitrary input.
+    fn nested(&mut self, parse: fn(&mut Self) -> Option<()>) -> Option<()> {
+        if self.depth >= MAX_DEPTH {
+            return None;
+        }
+
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        let matches = self.peek() == Some(byte);
+        if matches {
+            self.pos += 1;
+        }
+        matches
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        if self.eat(byte) {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    /// Skips a simple name, including its terminating `@`.
+    fn simple_name(&mut self) -> Option<()> {
+        let length = self.bytes[self.pos..].iter().position(|&b| b == b'@')?;
+        self.pos += length + 1;
+        Some(())
+    }
+
+    /// Parses an encoded number, which is either a single digit for the numbers 1 to 10 or hex
+    /// digits `A` to `P` terminated by `@`.
+    fn number(&mut self) -> Option<u64> {
+        match self.next()? {
+            digit @ b'0'..=b'9' => Some(u64::from(digit - b'0') + 1),
+            b'@' => Some(0),
+            first @ b'A'..=b'P' => {
+                let mut value = u64::from(first - b'A');
+                while !self.eat(b'@') {
+                    match self.next()? {
+                        digit @ b'A'..=b'P' => {
+                            value = value.checked_mul(16)? + u64::from(digit - b'A');
+                        }
+                        _ => return None,
+                    }
+                }
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Parses an encoded number, which is negative if prefixed with `?`.
+    fn signed_number(&mut self) -> Option<()> {
+        self.eat(b'?');
+        self.number().map(|_| ())
+    }
+
+    /// Skips a qualified name, including the `@` terminating the list of scopes.
+    fn qualified_name(&mut self) -> Option<()> {
+        while !self.eat(b'@') {
+            self.name_fragment()?;
+        }
+        Some(())
+    }
+
+    /// Skips a single component of a qualified name.
+    fn name_fragment(&mut self) -> Option<()> {
+        self.nested(Self::name_fragment_inner)
+    }
+
+    fn name_fragment_inner(&mut self) -> Option<()> {
+        match self.peek()? {
+            // back reference to a previous name
+            b'0'..=b'9' => {
+                self.pos += 1;
+                Some(())
+            }
+            b'?' => {
+                self.pos += 1;
+                match self.peek()? {
+                    b'$' => {
+                        self.pos += 1;
+                        self.template_name()
+                    }
+                    // anonymous namespace, such as `?A0x1234abcd@`
+                    b'A' if self.bytes[self.pos..].starts_with(b"A0x") => self.simple_name(),
+                    // local scope of a function, such as `?1??main@@YAHXZ`
+                    _ => {
+                        self.number()?;
+                        self.expect(b'?')?;
+                        self.symbol()
+                    }
+                }
+            }
+            _ => self.simple_name(),
+        }
+    }
+
+    /// Skips the name and arguments of a template after the leading `?$`.
+    fn template_name(&mut self) -> Option<()> {
+        if self.eat(b'?') {
+            self.operator_name()?;
+        } else {
+            self.simple_name()?;
+        }
+
+        while !self.eat(b'@') {
+            self.template_argument()?;
+        }
+        Some(())
+    }
+
+    /// Skips the code of an operator, constructor or other special name after the leading `?`.
+    fn operator_name(&mut self) -> Option<()> {
+        if self.eat(b'_') {
+            // `?_R` names of RTTI data carry further arguments
+            if self.next()? == b'R' {
+                return None;
+            }
+        } else {
+            self.next()?;
+        }
+        Some(())
+    }
+
+    /// Skips a decorated symbol, such as `?main@@YAHXZ`.
+    fn symbol(&mut self) -> Option<()> {
+        self.nested(Self::symbol_inner)
+    }
+
+    fn symbol_inner(&mut self) -> Option<()> {
+        self.expect(b'?')?;
+        if self.eat(b'?') {
+            self.operator_name()?;
+        } else {
+            self.name_fragment()?;
+        }
+        self.qualified_name()?;
+
+        match self.next()? {
+            // variables and static members, followed by their type and storage class
+            b'0'..=b'4' => {
+                self.data_type()?;
+                self.eat(b'E');
+                self.storage_class()
+            }
+            // global functions
+            b'Y' | b'Z' => self.function_type(),
+            // static member functions
+            b'C' | b'D' | b'K' | b'L' | b'S' | b'T' => self.function_type(),
+            // member functions, followed by the qualifiers of `this`
+            b'A' | b'B' | b'E' | b'F' | b'I' | b'J' | b'M' | b'N' | b'Q' | b'R' | b'U' | b'V' => {
+                self.eat(b'E');
+                self.storage_class()?;
+                self.function_type()
+            }
+            _ => None,
+        }
+    }
+
+    /// Skips a storage class or cv-qualifier, such as `A` for none or `B` for `const`.
+    fn storage_class(&mut self) -> Option<()> {
+        match self.next()? {
+            b'A'..=b'D' => Some(()),
+            _ => None,
+        }
+    }
+
+    /// Skips the calling convention, return type, parameters and exception specification of a
+    /// function.
+    fn function_type(&mut self) -> Option<()> {
+        // calling convention
+        self.next()?;
+
+        // return type, which constructors and destructors do not have
+        if !self.eat(b'@') {
+            self.return_type()?;
+        }
+
+        // parameters
+        if !self.eat(b'X') {
+            loop {
+                if self.eat(b'@') {
+                    break;
+                }
+                if self.eat(b'Z') {
+                    // variadic functions are terminated by `Z` instead of `@`
+                    break;
+                }
+                self.data_type()?;
+            }
+        }
+
+        // exception specification
+        self.expect(b'Z')
+    }
+
+    /// Skips a return type, which may carry a storage class.
+    fn return_type(&mut self) -> Option<()> {
+        if self.eat(b'?') {
+            self.storage_class()?;
+        }
+        self.data_type()
+    }
+
+    /// Skips a template argument, which is either a type or a constant.
+    fn template_argument(&mut self) -> Option<()> {
+        if self.eat(b'$') {
+            match self.next()? {
+                // integers and template parameters
+                b'0' | b'D' | b'Q' => self.signed_number(),
+                // pointers to symbols
+                b'1' => self.symbol(),
+                b'$' => self.extended_type(),
+                _ => None,
+            }
+        } else {
+            self.data_type()
+        }
+    }
+
+    /// Skips a type.
+    fn data_type(&mut self) -> Option<()> {
+        self.nested(Self::data_type_inner)
+    }
+
+    fn data_type_inner(&mut self) -> Option<()> {
+        match self.next()? {
+            // back reference to a previous type
+            b'0'..=b'9' => Some(()),
+            // primitive types
+            b'C'..=b'O' | b'X' => Some(()),
+            // extended primitive types, such as `_J` for `__int64`
+            b'_' => {
+                self.next()?;
+                Some(())
+            }
+            // pointers and references
+            b'A' | b'B' | b'P' | b'Q' | b'R' | b'S' => self.pointee(),
+            // unions, structures and classes
+            b'T' | b'U' | b'V' => self.qualified_name(),
+            // enumerations
+            b'W' => {
+                self.next()?;
+                self.qualified_name()
+            }
+            // arrays, with the number of dimensions followed by each dimension
+            b'Y' => {
+                for _ in 0..self.number()? {
+                    self.number()?;
+                }
+                self.data_type()
+            }
+            // cv-qualified types, such as in template arguments
+            b'?' => {
+                self.storage_class()?;
+                self.data_type()
+            }
+            b'$' => {
+                self.expect(b'$')?;
+                self.extended_type()
+            }
+            _ => None,
+        }
+    }
+
+    /// Skips the extended type after a leading `$$`.
+    fn extended_type(&mut self) -> Option<()> {
+        match self.next()? {
+            // rvalue references
+            b'Q' | b'R' => self.pointee(),
+            // cv-qualified types
+            b'C' => {
+                self.storage_class()?;
+                self.data_type()
+            }
+            // function types
+            b'A' => {
+                self.expect(b'6')?;
+                self.function_type()
+            }
+            // `std::nullptr_t`, empty parameter packs and pack separators
+            b'T' | b'V' | b'Z' => Some(()),
+            _ => None,
+        }
+    }
+
+    /// Skips the pointee of a pointer or reference.
+    fn pointee(&mut self) -> Option<()> {
+        if self.eat(b'6') {
+            // pointer to function
+            return self.function_type();
+        }
+
+        // `__ptr64`, `__unaligned` and `__restrict`
+        while self.eat(b'E') || self.eat(b'F') || self.eat(b'I') {}
+
+        self.storage_class()?;
+        self.data_type()
+    }
+}
+
+/// A stable identity of a class, structure, union or enumeration, as returned by
+/// [`TypeFinder::identity`].
+///
+/// Identities do not depend on type indices, so they can be used to match types across PDBs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TypeIdentity<'t> {
+    /// The type is identified by its decorated unique name.
+    UniqueName(RawString<'t>),
+    /// The type has no unique name and is identified by its name.
+    Name(RawString<'t>),
+    /// The type is anonymous and identified by its structure, see [`TypeFinder::fingerprint`].
+    Anonymous(u64),
+}
+
+/// A 64-bit FNV-1a hash, which is stable across platforms and versions.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+impl<'t> TypeFinder<'t> {
+    /// Returns a stable identity for a class, structure, union or enumeration.
+    ///
+    /// Forward references are resolved to their definition first. Types are identified by their
+    /// unique name if the compiler recorded one, and by their name otherwise. Types declared
+    /// without a name, which compilers call `<unnamed-tag>`, `<anonymous-tag>` or `__unnamed`,
+    /// are identified by a [fingerprint](Self::fingerprint) of their definition instead, so that
+    /// distinct anonymous types are not confused with each other.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// let mut finder = type_information.finder();
+    /// let mut iter = type_information.iter();
+    /// while let Some(_) = iter.next()? {
+    ///     finder.update(&iter);
+    /// }
+    ///
+    /// // the forward reference and the definition of std::exception
+    /// let declaration = finder.identity(pdb::TypeIndex(0x11bf))?;
+    /// let definition = finder.identity(pdb::TypeIndex(0x1212))?;
+    /// assert_eq!(declaration, definition);
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::NotARecordType(index)` if the type is not a class, structure, union or enumeration
+    /// * `Error::TypeNotFound(index)` if a type does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if a type is not yet known by this finder
+    pub fn identity(&self, index: TypeIndex) -> Result<TypeIdentity<'t>> {
+        self.identity_inner(index, &mut Vec::new())
+    }
+
+    /// Returns a hash of the structure of a type that does not depend on type indices.
+    ///
+    /// The hash covers the record of the type and, recursively, all records it refers to. Classes,
+    /// structures, unions and enumerations referred to are represented by their
+    /// [identity](Self::identity), so the fingerprint of a record only changes if the record or an
+    /// anonymous type it contains changes. The hash is stable across platforms and versions of this
+    /// library, but it is not a cryptographic hash.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::TypeNotFound(index)` if a type does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if a type is not yet known by this finder
+    pub fn fingerprint(&self, index: TypeIndex) -> Result<u64> {
+        let mut hasher = Fnv::new();
+        self.hash_structure(index, &mut hasher, &mut Vec::new())?;
+        Ok(hasher.0)
+    }
+
+    fn identity_inner(
+        &self,
+        index: TypeIndex,
+        visiting: &mut Vec<TypeIndex>,
+    ) -> Result<TypeIdentity<'t>> {
+        let definition = self.resolve_definition(index)?.unwrap_or(index);
+        let (name, unique_name) = match self.find(definition)?.parse() {
+            Ok(TypeData::Class(data)) => (data.name, data.unique_name),
+            Ok(TypeData::Union(data)) => (data.name, data.unique_name),
+            Ok(TypeData::Enumeration(data)) => (data.name, data.unique_name),
+            _ => return Err(Error::NotARecordType(index.0)),
+        };
+
+        let key = unique_name.unwrap_or(name);
+        if !is_anonymous(key) {
+            return Ok(match unique_name {
+                Some(unique_name) => TypeIdentity::UniqueName(unique_name),
+                None => TypeIdentity::Name(name),
+            });
+        }
+
+        let mut hasher = Fnv::new();
+        self.hash_structure(definition, &mut hasher, visiting)?;
+        Ok(TypeIdentity::Anonymous(hasher.0))
+    }
+
+    fn hash_structure(
+        &self,
+        index: TypeIndex,
+        hasher: &mut Fnv,
+        visiting: &mut Vec<TypeIndex>,
+    ) -> Result<()> {
+        if index.0 < self.minimum_index {
+            hasher.write(b"P");
+            hasher.write(&index.0.to_le_bytes());
+            return Ok(());
+        }
+
+        // records can refer to themselves through pointers
+        if let Some(depth) = visiting.iter().position(|&i| i == index) {
+            hasher.write(b"R");
+            hasher.write(&(depth as u32).to_le_bytes());
+            return Ok(());
+        }
+
+        if visiting.len() >= MAX_DEPTH {
+            hasher.write(b"D");
+            return Ok(());
+        }
+
+        let item = self.find(index)?;
+        let is_nested_record = !visiting.is_empty()
+            && matches!(
+                item.parse(),
+                Ok(TypeData::Class(_)) | Ok(TypeData::Union(_)) | Ok(TypeData::Enumeration(_))
+            );
+
+        visiting.push(index);
+        let result = if is_nested_record {
+            self.hash_identity(index, hasher, visiting)
+        } else {
+            self.hash_record(item.data, hasher, visiting)
+        };
+        visiting.pop();
+
+        result
+    }
+
+    fn hash_identity(
+        &self,
+        index: TypeIndex,
+        hasher: &mut Fnv,
+        visiting: &mut Vec<TypeIndex>,
+    ) -> Result<()> {
+        match self.identity_inner(index, visiting)? {
+            TypeIdentity::UniqueName(name) => {
+                hasher.write(b"U");
+                hasher.write(name.as_bytes());
+            }
+            TypeIdentity::Name(name) => {
+                hasher.write(b"N");
+                hasher.write(name.as_bytes());
+            }
+            TypeIdentity::Anonymous(hash) => {
+                hasher.write(b"A");
+                hasher.write(&hash.to_le_bytes());
+            }
+        }
+
+        // terminate the name
+        hasher.write(&[0]);
+        Ok(())
+    }
+
+    fn hash_record(
+        &self,
+        data: &[u8],
+        hasher: &mut Fnv,
+        visiting: &mut Vec<TypeIndex>,
+    ) -> Result<()> {
+        let offsets = match type_index_offsets(data) {
+            Ok(offsets) => offsets,
+            // records that are not understood can only be hashed as they are
+            Err(Error::UnimplementedTypeKind(_)) => {
+                hasher.write(b"B");
+                hasher.write(data);
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+
+        let mut record = data.to_vec();
+        let mut references = Vec::with_capacity(offsets.len());
+        for offset in offsets {
+            let slot = &mut record[offset..offset + 4];
+            references.push(TypeIndex(u32::from_le_bytes([
+                slot[0], slot[1], slot[2], slot[3],
+            ])));
+            slot.copy_from_slice(&[0; 4]);
+        }
+
+        hasher.write(b"T");
+        hasher.write(&(record.len() as u32).to_le_bytes());
+        hasher.write(&record);
+
+        for reference in references {
+            self.hash_structure(reference, hasher, visiting)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns whether a name or unique name is shared by all anonymous types.
+fn is_anonymous(name: RawString<'_>) -> bool {
+    let name = name.as_bytes();
+    ANONYMOUS_NAMES
+        .iter()
+        .any(|anonymous| name.windows(anonymous.len()).any(|w| w == *anonymous))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_unique_name() {
+        let parse = |name| UniqueName::parse(RawString::from(name));
+
+        let name = parse(".?AT<unnamed-type-u>@_TP_CALLBACK_ENVIRON_V3@@").unwrap();
+        assert_eq!(name.kind, UniqueNameKind::Union);
+        assert_eq!(
+            name.components().unwrap(),
+            [
+                RawString::from("_TP_CALLBACK_ENVIRON_V3"),
+                RawString::from("<unnamed-type-u>")
+            ]
+        );
+
+        let name = parse(".?AW4<unnamed-enum-__crt_maximum_pointer_shift>@@").unwrap();
+        assert_eq!(name.kind, UniqueNameKind::Enum);
+        assert_eq!(name.components().unwrap().len(), 1);
+
+        let name = parse(".?AV<lambda_f7f22ab5edc0698d5f6905b0d3f44752>@@`a26a2fd4").unwrap();
+        assert_eq!(
+            name.decorated,
+            RawString::from("<lambda_f7f22ab5edc0698d5f6905b0d3f44752>")
+        );
+
+        // template arguments cannot be split without undecorating
+        let name = parse(".?AUcomponents_type@?$__acrt_floating_type_traits@N@@").unwrap();
+        assert_eq!(name.kind, UniqueNameKind::Struct);
+        assert_eq!(name.components(), None);
+
+        let name = parse(".?AV?$vector@HV?$allocator@H@std@@@std@@").unwrap();
+        assert_eq!(
+            name.decorated,
+            RawString::from("?$vector@HV?$allocator@H@std@@@std")
+        );
+
+        // the decorated name of the function declaring a local type has terminators of its own
+        let name = parse(".?AUlocal@?1??main@@YAHXZ@").unwrap();
+        assert_eq!(name.decorated, RawString::from("local@?1??main@@YAHXZ"));
+        assert_eq!(name.components(), None);
+
+        let name = parse(concat!(
+            ".?AUunpack_index@?1??multiply_by_power_of_ten@__crt_strtox@@",
+            "YA_NAEAUbig_integer@2@I@Z@`71ca7cfb"
+        ))
+        .unwrap();
+        assert_eq!(
+            name.decorated,
+            RawString::from(
+                "unpack_index@?1??multiply_by_power_of_ten@__crt_strtox@@YA_NAEAUbig_integer@2@I@Z"
+            )
+        );
+
+        assert_eq!(parse("std::exception"), None);
+        assert_eq!(parse(".?AXfoo@@"), None);
+        assert_eq!(parse(".?AUfoo"), None);
+        assert_eq!(parse(".?AU<unnamed-tag>@_get_fname::2@"), None);
+    }
+
+    #[test]
+    fn test_parse_deeply_nested_unique_name() {
+        let nested = |pointers| format!(".?AU?$a@{}H@@", "PA".repeat(pointers));
+
+        let name = nested(8);
+        let name = UniqueName::parse(RawString::from(name.as_str())).unwrap();
+        assert_eq!(name.decorated, RawString::from("?$a@PAPAPAPAPAPAPAPAH"));
+
+        // nesting is bounded instead of overflowing the stack
+        let name = nested(30000);
+        assert_eq!(UniqueName::parse(RawString::from(name.as_str())), None);
+    }
+
+    #[test]
+    #[cfg(feature = "demangle")]
+    fn test_demangled() {
+        let demangled = |name| UniqueName::parse(RawString::from(name))?.demangled();
+
+        assert_eq!(
+            demangled(".?AV?$vector@HV?$allocator@H@std@@@std@@").as_deref(),
+            Some("std::vector<int,std::allocator<int> >")
+        );
+        assert_eq!(
+            demangled(".?AT<unnamed-type-u>@_TP_CALLBACK_ENVIRON_V3@@").as_deref(),
+            Some("_TP_CALLBACK_ENVIRON_V3::<unnamed-type-u>")
+        );
+        assert_eq!(
+            demangled(".?AW4<unnamed-enum-__crt_maximum_pointer_shift>@@").as_deref(),
+            Some("<unnamed-enum-__crt_maximum_pointer_shift>")
+        );
+    }
+
+    #[test]
+    fn test_is_anonymous() {
+        assert!(is_anonymous(RawString::from("<unnamed-tag>")));
+        assert!(is_anonymous(RawString::from(".?AU<unnamed-tag>@@")));
+        assert!(is_anonymous(RawString::from("Foo::__unnamed")));
+        assert!(!is_anonymous(RawString::from("Foo::<unnamed-type-u>")));
+    }
+
+    #[test]
+    fn test_fnv() {
+        let mut hasher = Fnv::new();
+        hasher.write(b"a");
+        assert_eq!(hasher.0, 0xaf63_dc4c_8601_ec8c);
+    }
+}
@@ -0,0 +1,353 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::ops::Range;
+
+use crate::common::*;
+use crate::tpi::data::*;
+use crate::tpi::primitive::PrimitiveKind;
+use crate::tpi::size::{TypeSizer, MAX_DEPTH};
+
+/// The layout of a class, structure or union, as computed by [`TypeSizer::layout_of`].
+///
+/// Offsets and sizes are in bytes and relative to the start of the record. Alignments are not
+/// recorded in the PDB and are inferred from the natural alignment of the members instead, which
+/// overestimates the alignment of records declared with `#pragma pack`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordLayout<'t> {
+    /// The index of the record definition, after following forward references.
+    pub index: TypeIndex,
+    /// The name of the record.
+    pub name: RawString<'t>,
+    /// The size of the record.
+    pub size: u64,
+    /// The inferred alignment of the record.
+    pub alignment: u64,
+    /// The virtual function table pointer introduced by this record, if any.
+    pub vfptr: Option<Range<u64>>,
+    /// Non-virtual base class subobjects.
+    pub base_classes: Vec<BaseClassLayout>,
+    /// Direct and indirect virtual base classes.
+    ///
+    /// The location of virtual base subobjects is determined at run time through the virtual base
+    /// table, so they are not part of [`padding`](Self::padding) analysis.
+    pub virtual_base_classes: Vec<VirtualBaseClassLayout>,
+    /// Non-static data members, in declaration order.
+    pub members: Vec<MemberLayout<'t>>,
+    /// Byte ranges that are not covered by any member, base class or hidden pointer.
+    ///
+    /// Trailing padding is only reported for records without virtual base classes.
+    pub padding: Vec<Range<u64>>,
+}
+
+/// A non-virtual base class subobject within a [`RecordLayout`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BaseClassLayout {
+    /// The type of the base class.
+    pub base_class: TypeIndex,
+    /// Offset of the subobject.
+    pub offset: u64,
+    /// Size of the subobject.
+    pub size: u64,
+    /// Inferred alignment of the subobject.
+    pub alignment: u64,
+}
+
+/// A virtual base class within a [`RecordLayout`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VirtualBaseClassLayout {
+    /// The type of the base class.
+    pub base_class: TypeIndex,
+    /// Whether the base class is a direct base of the record.
+    pub direct: bool,
+    /// Offset of the virtual base table pointer within the record.
+    pub vbptr_offset: u64,
+    /// Index of this base class within the virtual base table.
+    pub vbtable_index: u32,
+}
+
+/// A data member within a [`RecordLayout`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MemberLayout<'t> {
+    /// The name of the member.
+    pub name: RawString<'t>,
+    /// The declared type of the member. For bitfields, this is the `LF_BITFIELD` record.
+    pub field_type: TypeIndex,
+    /// Offset of the member, or of the storage unit holding a bitfield.
+    pub offset: u64,
+    /// Size of the member, or of the storage unit holding a bitfield.
+    pub size: u64,
+    /// Inferred alignment of the member.
+    pub alignment: u64,
+    /// The position of a bitfield within its storage unit.
+    pub bitfield: Option<BitfieldLayout>,
+}
+
+/// The position of a bitfield member within its storage unit.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BitfieldLayout {
+    /// Index of the lowest bit of the bitfield.
+    pub position: u8,
+    /// Number of bits of the bitfield.
+    pub length: u8,
+}
+
+impl<'t> TypeSizer<'t> {
+    /// Computes the layout of a class, structure or union.
+    ///
+    /// Forward references are resolved to their definitions, and modifiers and aliases are
+    /// followed to the record they refer to. See [`RecordLayout`] for the information returned.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::NotARecordType(index)` if the type is not a class, structure or union
+    /// * `Error::UnsizedType(index)` if the record or one of its members does not have a size
+    /// * `Error::TypeNotFound(index)` if the type or one of the types it refers to does not exist
+    /// * `Error::UnimplementedTypeKind(kind)` if a type record is not understood by this library
+    pub fn layout_of(&self, index: TypeIndex) -> Result<RecordLayout<'t>> {
+        let (index, name, size, fields) = self.record(index)?;
+
+        let mut layout = RecordLayout {
+            index,
+            name,
+            size,
+            alignment: 1,
+            vfptr: None,
+            base_classes: Vec::new(),
+            virtual_base_classes: Vec::new(),
+            members: Vec::new(),
+            padding: Vec::new(),
+        };
+
+        let mut covered = Vec::new();
+        for field in self.fields(fields)? {
+            match field {
+                TypeData::Member(member) => {
+                    let (size, alignment, bitfield) = match self.parse(member.field_type)? {
+                        TypeData::Bitfield(bitfield) => (
+                            self.size_of(bitfield.underlying_type)?,
+                            self.alignment_of(bitfield.underlying_type)?,
+                            Some(BitfieldLayout {
+                                position: bitfield.position,
+                                length: bitfield.length,
+                            }),
+                        ),
+                        _ => (
+                            self.size_of(member.field_type)?,
+                            self.alignment_of(member.field_type)?,
+                            None,
+                        ),
+                    };
+
+                    covered.push(member.offset..member.offset + size);
+                    layout.alignment = layout.alignment.max(alignment);
+                    layout.members.push(MemberLayout {
+                        name: member.name,
+                        field_type: member.field_type,
+                        offset: member.offset,
+                        size,
+                        alignment,
+                        bitfield,
+                    });
+                }
+                TypeData::BaseClass(base) => {
+                    let offset = u64::from(base.offset);
+                    let size = self.size_of(base.base_class)?;
+                    let alignment = self.alignment_of(base.base_class)?;
+
+                    covered.push(offset..offset + size);
+                    layout.alignment = layout.alignment.max(alignment);
+                    layout.base_classes.push(BaseClassLayout {
+                        base_class: base.base_class,
+                        offset,
+                        size,
+                        alignment,
+                    });
+                }
+                TypeData::VirtualBaseClass(base) => {
+                    let offset = u64::from(base.base_pointer_offset);
+                    let size = self.size_of(base.base_pointer)?;
+
+                    covered.push(offset..offset + size);
+                    layout.alignment = layout.alignment.max(size);
+                    layout.virtual_base_classes.push(VirtualBaseClassLayout {
+                        base_class: base.base_class,
+                        direct: base.direct,
+                        vbptr_offset: offset,
+                        vbtable_index: base.virtual_base_offset,
+                    });
+                }
+                TypeData::VirtualFunctionTablePointer(vfptr) => {
+                    let size = self.size_of(vfptr.table)?;
+
+                    covered.push(0..size);
+                    layout.alignment = layout.alignment.max(size);
+                    layout.vfptr = Some(0..size);
+                }
+                _ => {}
+            }
+        }
+
+        let end = if layout.virtual_base_classes.is_empty() {
+            layout.size
+        } else {
+            covered.iter().map(|r| r.end).max().unwrap_or(0)
+        };
+        layout.padding = find_gaps(covered, end);
+
+        Ok(layout)
+    }
+
+    /// Returns the inferred alignment of a type in bytes.
+    ///
+    /// The alignment is derived from the natural alignment of primitive types and pointers, as the
+    /// PDB does not record alignments. Arrays are aligned like their elements, and records like
+    /// their most strictly aligned member or base class.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnsizedType(index)` if the type does not have a size
+    /// * `Error::TypeNotFound(index)` if the type or one of the types it refers to does not exist
+    /// * `Error::UnimplementedTypeKind(kind)` if a type record is not understood by this library
+    pub fn alignment_of(&self, index: TypeIndex) -> Result<u64> {
+        let mut current = index;
+
+        for _ in 0..MAX_DEPTH {
+            let next = match self.parse(current)? {
+                TypeData::Primitive(primitive) => {
+                    let size = self.size_of(current)?;
+                    if primitive.indirection.is_some() {
+                        return Ok(natural_alignment(size, 16));
+                    }
+                    let max = match primitive.kind {
+                        PrimitiveKind::Octa
+                        | PrimitiveKind::UOcta
+                        | PrimitiveKind::I128
+                        | PrimitiveKind::U128
+                        | PrimitiveKind::F128 => 16,
+                        _ => 8,
+                    };
+                    return Ok(natural_alignment(size, max));
+                }
+                TypeData::Pointer(_) => return Ok(natural_alignment(self.size_of(current)?, 16)),
+                TypeData::Array(array) => array.element_type,
+                TypeData::Class(_) | TypeData::Union(_) => {
+                    return self.layout_of(current).map(|layout| layout.alignment);
+                }
+                TypeData::Enumeration(enumeration) => enumeration.underlying_type,
+                TypeData::Bitfield(bitfield) => bitfield.underlying_type,
+                TypeData::Modifier(modifier) => modifier.underlying_type,
+                TypeData::Alias(alias) => alias.underlying_type,
+                _ => return Err(Error::UnsizedType(current.0)),
+            };
+
+            current = next;
+        }
+
+        Err(Error::UnsizedType(index.0))
+    }
+
+    /// Resolves a record type to its definition.
+    fn record(
+        &self,
+        index: TypeIndex,
+    ) -> Result<(TypeIndex, RawString<'t>, u64, Option<TypeIndex>)> {
+        let mut current = index;
+
+        for _ in 0..MAX_DEPTH {
+            let next = match self.parse(current)? {
+                TypeData::Class(class) if class.properties.forward_reference() => self
+                    .definition(current)?
+                    .ok_or(Error::UnsizedType(current.0))?,
+                TypeData::Union(union) if union.properties.forward_reference() => self
+                    .definition(current)?
+                    .ok_or(Error::UnsizedType(current.0))?,
+                TypeData::Class(class) => {
+                    return Ok((current, class.name, class.size, class.fields));
+                }
+                TypeData::Union(union) => {
+                    return Ok((current, union.name, union.size, Some(union.fields)));
+                }
+                TypeData::Modifier(modifier) => modifier.underlying_type,
+                TypeData::Alias(alias) => alias.underlying_type,
+                _ => return Err(Error::NotARecordType(current.0)),
+            };
+
+            current = next;
+        }
+
+        Err(Error::NotARecordType(index.0))
+    }
+
+    /// Collects the fields of a record, following field list continuations.
+    fn fields(&self, mut fields: Option<TypeIndex>) -> Result<Vec<TypeData<'t>>> {
+        let mut result = Vec::new();
+
+        while let Some(index) = fields {
+            match self.parse(index)? {
+                TypeData::FieldList(list) => {
+                    result.extend(list.fields);
+                    fields = list.continuation;
+                }
+                _ => return Err(Error::UnimplementedFeature("unexpected field list type")),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// Returns the largest power of two dividing `size`, limited to `max`.
+fn natural_alignment(size: u64, max: u64) -> u64 {
+    match size {
+        0 => 1,
+        size => (size & size.wrapping_neg()).min(max),
+    }
+}
+
+/// Returns the ranges within `0..end` that are not covered by any of the given ranges.
+fn find_gaps(mut covered: Vec<Range<u64>>, end: u64) -> Vec<Range<u64>> {
+    covered.sort_by_key(|range| range.start);
+
+    let mut gaps = Vec::new();
+    let mut position = 0;
+    for range in covered {
+        if range.start > position {
+            gaps.push(position..range.start.min(end));
+        }
+        position = position.max(range.end);
+    }
+
+    if position < end {
+        gaps.push(position..end);
+    }
+
+    gaps.retain(|gap| !gap.is_empty());
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_gaps() {
+        assert_eq!(find_gaps(vec![], 4), vec![0..4]);
+        assert_eq!(find_gaps(vec![0..1, 4..8], 16), vec![1..4, 8..16]);
+        assert_eq!(find_gaps(vec![4..8, 0..4], 8), vec![]);
+        assert_eq!(find_gaps(vec![0..8, 2..4], 8), vec![]);
+    }
+
+    #[test]
+    fn test_natural_alignment() {
+        assert_eq!(natural_alignment(0, 8), 1);
+        assert_eq!(natural_alignment(6, 8), 2);
+        assert_eq!(natural_alignment(10, 8), 2);
+        assert_eq!(natural_alignment(16, 8), 8);
+        assert_eq!(natural_alignment(16, 16), 16);
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::common::*;
+use crate::tpi::data::{FieldAttributes, TypeData};
+use crate::tpi::size::MAX_DEPTH;
+use crate::tpi::TypeFinder;
+
+/// A non-static data member of a class, structure or union, as returned by
+/// [`TypeFinder::members`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Member<'t> {
+    /// The name of the member.
+    pub name: RawString<'t>,
+    /// Access and other properties of the member.
+    pub attributes: FieldAttributes,
+    /// The declared type of the member. For bitfields, this is the `LF_BITFIELD` record.
+    pub field_type: TypeIndex,
+    /// Offset of the member relative to the start of the requested type, in bytes.
+    ///
+    /// For members inherited from base classes, this includes the offset of the base class
+    /// subobject.
+    pub offset: u64,
+    /// The class, structure or union declaring the member.
+    pub class: TypeIndex,
+}
+
+impl<'t> TypeFinder<'t> {
+    /// Returns all fields of a class, structure, union or enumeration, in declaration order.
+    ///
+    /// `index` is either the type of the record or enumeration, or its field list. Forward
+    /// references are resolved to their definition. Field lists that are too long for a single
+    /// record are continued in further records (`LF_INDEX`), which are followed, so the result
+    /// contains the fields of all parts. Forward references without a definition have no fields.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::NotARecordType(index)` if the type has no field list
+    /// * `Error::UnimplementedFeature` if a field list has an unexpected type
+    /// * `Error::TypeNotFound(index)` if a type does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if a type is not yet known by this finder
+    /// * `Error::UnimplementedTypeKind(kind)` if a type record is not understood by this library
+    pub fn fields(&self, index: TypeIndex) -> Result<Vec<TypeData<'t>>> {
+        let definition = self.resolve_definition(index)?.unwrap_or(index);
+        let mut next = match self.find(definition)?.parse()? {
+            TypeData::Class(class) => class.fields,
+            TypeData::Union(union) if union.properties.forward_reference() => None,
+            TypeData::Union(union) => Some(union.fields),
+            TypeData::Enumeration(enumeration) if enumeration.properties.forward_reference() => {
+                None
+            }
+            TypeData::Enumeration(enumeration) => Some(enumeration.fields),
+            TypeData::FieldList(_) => Some(definition),
+            _ => return Err(Error::NotARecordType(index.0)),
+        };
+
+        let mut fields = Vec::new();
+        while let Some(current) = next {
+            match self.find(current)?.parse()? {
+                TypeData::FieldList(list) => {
+                    fields.extend(list.fields);
+                    next = list.continuation;
+                }
+                _ => return Err(Error::UnimplementedFeature("unexpected field list type")),
+            }
+        }
+
+        Ok(fields)
+    }
+
+    /// Returns the non-static data members of a class, structure or union, in declaration order.
+    ///
+    /// If `include_bases` is set, the members of non-virtual base classes are included before the
+    /// members of the class itself, in the order of the base classes and with offsets relative to
+    /// the start of the class. This applies recursively to the bases of base classes. Members of
+    /// virtual base classes are never included, as their location is only known at run time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// let mut finder = type_information.finder();
+    /// let mut iter = type_information.iter();
+    /// while let Some(_) = iter.next()? {
+    ///     finder.update(&iter);
+    /// }
+    ///
+    /// // std::bad_cast only inherits the members of std::exception
+    /// let bad_cast = pdb::TypeIndex(0x11d7);
+    /// assert!(finder.members(bad_cast, false)?.is_empty());
+    ///
+    /// let members = finder.members(bad_cast, true)?;
+    /// assert_eq!(members[0].name.to_string(), "_Data");
+    /// assert_eq!(members[0].offset, 8);
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::NotARecordType(index)` if the type is not a class, structure or union
+    /// * `Error::UnimplementedFeature` if a field list has an unexpected type
+    /// * `Error::TypeNotFound(index)` if a type does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if a type is not yet known by this finder
+    /// * `Error::UnimplementedTypeKind(kind)` if a type record is not understood by this library
+    pub fn members(&self, index: TypeIndex, include_bases: bool) -> Result<Vec<Member<'t>>> {
+        let mut members = Vec::new();
+        self.collect_members(index, 0, include_bases, 0, &mut members)?;
+        Ok(members)
+    }
+
+    fn collect_members(
+        &self,
+        index: TypeIndex,
+        offset: u64,
+        include_bases: bool,
+        depth: usize,
+        members: &mut Vec<Member<'t>>,
+    ) -> Result<()> {
+        let class = self.resolve_definition(index)?.unwrap_or(index);
+        match self.find(class)?.parse()? {
+            TypeData::Class(_) | TypeData::Union(_) => {}
+            _ => return Err(Error::NotARecordType(index.0)),
+        }
+
+        let fields = self.fields(class)?;
+
+        // base classes are laid out before the members of the derived class
+        if include_bases && depth < MAX_DEPTH {
+            for field in &fields {
+                if let TypeData::BaseClass(base) = field {
+                    let base_offset = offset + u64::from(base.offset);
+                    self.collect_members(base.base_class, base_offset, true, depth + 1, members)?;
+                }
+            }
+        }
+
+        for field in fields {
+            if let TypeData::Member(member) = field {
+                members.push(Member {
+                    name: member.name,
+                    attributes: member.attributes,
+                    field_type: member.field_type,
+                    offset: offset + member.offset,
+                    class,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::common::*;
+use crate::tpi::data::{FieldAttributes, TypeData};
+use crate::tpi::TypeFinder;
+
+/// A method declared by a class, structure or union.
+///
+/// Methods are declared either by an `LF_ONEMETHOD` field, or by an `LF_METHOD` field referring to
+/// an `LF_METHODLIST` with all overloads of the same name. Both are represented by one `Method`
+/// per overload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Method<'t> {
+    /// The name of the method.
+    pub name: RawString<'t>,
+    /// Access, method kind and other properties of the method.
+    pub attributes: FieldAttributes,
+    /// The `LF_MFUNCTION` type of the method.
+    pub method_type: TypeIndex,
+    /// Offset of the method's slot in the virtual function table, in bytes.
+    ///
+    /// This is only recorded for methods introducing a new slot. See
+    /// [`FieldAttributes::is_intro_virtual`].
+    pub vtable_offset: Option<u32>,
+}
+
+impl<'t> TypeFinder<'t> {
+    /// Returns all methods declared by a class, structure or union, in declaration order.
+    ///
+    /// `index` is either the type of the class, structure or union, or its field list. Forward
+    /// references are resolved to their definition, and continued field lists are followed.
+    /// Overloaded methods are expanded into one entry per overload.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::NotARecordType(index)` if the type is neither a record nor a field list
+    /// * `Error::UnimplementedFeature` if a field list or method list has an unexpected type
+    /// * `Error::TypeNotFound(index)` if a type does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if a type is not yet known by this finder
+    /// * `Error::UnimplementedTypeKind(kind)` if a type record is not understood by this library
+    pub fn methods(&self, index: TypeIndex) -> Result<Vec<Method<'t>>> {
+        let definition = self.resolve_definition(index)?.unwrap_or(index);
+        let mut next = match self.find(definition)?.parse()? {
+            TypeData::Class(class) => class.fields,
+            TypeData::Union(union) => Some(union.fields),
+            TypeData::FieldList(_) => Some(definition),
+            _ => return Err(Error::NotARecordType(index.0)),
+        };
+
+        let mut methods = Vec::new();
+
+        while let Some(fields) = next {
+            let list = match self.find(fields)?.parse()? {
+                TypeData::FieldList(list) => list,
+                _ => return Err(Error::UnimplementedFeature("unexpected field list type")),
+            };
+
+            for field in list.fields {
+                match field {
+                    TypeData::Method(method) => methods.push(Method {
+                        name: method.name,
+                        attributes: method.attributes,
+                        method_type: method.method_type,
+                        vtable_offset: method.vtable_offset,
+                    }),
+                    TypeData::OverloadedMethod(overloads) => {
+                        match self.find(overloads.method_list)?.parse()? {
+                            TypeData::MethodList(list) => {
+                                methods.extend(list.methods.into_iter().map(|entry| Method {
+                                    name: overloads.name,
+                                    attributes: entry.attributes,
+                                    method_type: entry.method_type,
+                                    vtable_offset: entry.vtable_offset,
+                                }));
+                            }
+                            _ => {
+                                return Err(Error::UnimplementedFeature(
+                                    "unexpected method list type",
+                                ))
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            next = list.continuation;
+        }
+
+        Ok(methods)
+    }
+}
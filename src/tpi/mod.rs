@@ -5,26 +5,95 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
+use std::collections::HashMap;
 use std::fmt;
 use std::marker::PhantomData;
 use std::result;
+use std::sync::OnceLock;
 
 use crate::common::*;
 use crate::msf::Stream;
 use crate::FallibleIterator;
 
+mod arena;
+mod bindings;
+mod build;
+mod compare;
 pub(crate) mod constants;
 mod data;
+mod diff;
+mod enums;
+mod function_symbols;
+mod graph;
+mod hash;
 mod header;
 mod id;
+mod id_references;
+mod identity;
+mod layout;
+mod members;
+mod methods;
+mod nested;
+mod object;
+mod owned;
+mod padding;
+#[cfg(feature = "rayon")]
+mod parallel;
 mod primitive;
+mod remap;
+mod search;
+mod signature;
+mod size;
+mod source;
+mod stats;
+mod string_id;
+mod template;
+mod visit;
+mod vtable;
+mod writer;
 
-use self::header::*;
+pub(crate) use self::header::Header;
 use self::primitive::type_data_for_primitive;
 
+pub use self::arena::TypeArena;
+pub use self::bindings::RustGenerator;
+pub use self::build::BuildInfo;
+pub use self::compare::TypeComparator;
 pub use self::data::*;
+pub use self::diff::{DiffRecord, MemberChange, RecordChange, TypeDiff};
+pub use self::enums::{EnumValue, Enumerator};
+pub use self::function_symbols::{FunctionSymbols, ProcedureLocation};
+pub use self::graph::TypeGraph;
+pub use self::hash::{HashAdjuster, IdHashes, ItemHashes, TypeHashes};
 pub use self::id::*;
-pub use self::primitive::{Indirection, PrimitiveKind, PrimitiveType};
+pub use self::id_references::{
+    DanglingIdReference, IdReferenceProblem, IdReferenceSource, IdReferenceValidator,
+};
+pub use self::identity::{TypeIdentity, UniqueName, UniqueNameKind};
+pub use self::layout::{
+    BaseClassLayout, BitfieldLayout, MemberLayout, RecordLayout, VirtualBaseClassLayout,
+};
+pub use self::members::Member;
+pub use self::methods::Method;
+pub use self::nested::Nested;
+pub use self::object::ObjectTypes;
+pub use self::owned::OwnedTypeData;
+pub use self::padding::PaddingReport;
+pub use self::primitive::{
+    Indirection, PrimitiveKind, PrimitiveKindInfo, PrimitiveType, PRIMITIVE_KINDS,
+};
+pub use self::remap::TypeIndexMap;
+#[cfg(feature = "regex")]
+pub use self::search::RegexSearch;
+pub use self::signature::FunctionSignature;
+pub use self::size::TypeSizer;
+pub(crate) use self::source::find_definition;
+pub use self::source::{TypeSource, TypeSources, UdtQuery, UdtSource};
+pub use self::stats::{KindStatistics, RecordSize, TypeStatistics};
+pub use self::template::TemplateName;
+pub use self::visit::{ParsedTypeIter, TypeVisitor};
+pub use self::vtable::VirtualTableSlot;
+pub use self::writer::IdInfoWriter;
 
 /// Zero-copy access to a PDB type or id stream.
 ///
@@ -53,6 +122,7 @@ pub use self::primitive::{Indirection, PrimitiveKind, PrimitiveType};
 ///  - [`ItemIndex`]: [`TypeIndex`] and [`IdIndex`]
 ///  - [`ItemIter`]: [`TypeIter`] and [`IdIter`]
 ///  - [`Item`]: [`Type`] and [`Id`]
+///  - [`ItemHashes`]: [`TypeHashes`] and [`IdHashes`]
 ///
 /// # Examples
 ///
@@ -63,7 +133,7 @@ pub use self::primitive::{Indirection, PrimitiveKind, PrimitiveType};
 /// #
 /// # fn test() -> pdb::Result<usize> {
 /// # let file = std::fs::File::open("fixtures/self/foo.pdb")?;
-/// # let mut pdb = pdb::PDB::open(file)?;
+/// # let pdb = pdb::PDB::open(file)?;
 ///
 /// let type_information = pdb.type_information()?;
 /// let mut type_finder = type_information.finder();
@@ -128,6 +198,7 @@ pub use self::primitive::{Indirection, PrimitiveKind, PrimitiveType};
 pub struct ItemInformation<'s, I> {
     stream: Stream<'s>,
     header: Header,
+    pointer_size: Option<u8>,
     _ph: PhantomData<&'s I>,
 }
 
@@ -143,6 +214,7 @@ where
         Ok(Self {
             stream,
             header,
+            pointer_size: None,
             _ph,
         })
     }
@@ -186,6 +258,41 @@ where
     pub fn finder(&self) -> ItemFinder<'_, I> {
         ItemFinder::new(self, 3)
     }
+
+    /// Returns an empty `ItemFinder` that stores the position of every `2^shift`-th item.
+    ///
+    /// Larger values of `shift` reduce the memory used by the finder in exchange for slower
+    /// lookups. See the [time/space trade-off](ItemFinder#timespace-trade-off) of `ItemFinder` for
+    /// guidance. [`finder`](Self::finder) uses a `shift` of 3.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shift` is larger than 16.
+    pub fn finder_with_shift(&self, shift: u8) -> ItemFinder<'_, I> {
+        assert!(shift <= 16, "ItemFinder shift must not exceed 16");
+        ItemFinder::new(self, shift)
+    }
+
+    /// Returns an `ItemFinder` populated from an index previously saved with
+    /// [`ItemFinder::export_index`].
+    ///
+    /// This skips iterating over the stream to populate the finder, which is useful for PDBs that
+    /// are opened repeatedly. The index is checked against the dimensions of this stream, but its
+    /// positions are not verified. Make sure to only import indices exported for the same PDB, for
+    /// instance by keying them with the PDB's GUID and age.
+    ///
+    /// An index exported from a partially populated finder can be completed by calling
+    /// [`ItemFinder::update`] while iterating.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidFinderIndex(reason)` if the index is malformed or was exported for a stream
+    ///   with different dimensions
+    pub fn finder_from_index(&self, index: &[u8]) -> Result<ItemFinder<'_, I>> {
+        let mut finder = ItemFinder::new(self, 3);
+        finder.import_index(index)?;
+        Ok(finder)
+    }
 }
 
 /// This buffer is used when a `Type` refers to a primitive type. It doesn't contain anything
@@ -193,6 +300,15 @@ where
 /// like a reasonable thing to do.
 const PRIMITIVE_TYPE: &[u8] = b"\xff\xff";
 
+/// Identifies an index exported by [`ItemFinder::export_index`].
+const FINDER_INDEX_MAGIC: &[u8; 4] = b"PDBF";
+
+/// The version of the exported `ItemFinder` index format.
+const FINDER_INDEX_VERSION: u32 = 1;
+
+/// Size of the header of an exported `ItemFinder` index in bytes.
+const FINDER_INDEX_HEADER_SIZE: usize = 28;
+
 /// Represents an entry in the type or id stream.
 ///
 /// An `Item` has been minimally processed and may not be correctly formed or even understood by
@@ -265,14 +381,16 @@ where
 /// In-memory index for efficient random-access to [`Item`]s by index.
 ///
 /// `ItemFinder` can be obtained via [`ItemInformation::finder`]. It starts out empty and must be
-/// populated by calling [`ItemFinder::update`] while iterating. There are two typedefs for easier
-/// use:
+/// populated by calling [`ItemFinder::update`] while iterating, or on demand by calling
+/// [`ItemFinder::index_until`]. There are two typedefs for easier use:
 ///
 ///  - [`TypeFinder`] for finding [`Type`]s in a [`TypeInformation`](crate::TypeInformation) (TPI stream).
 ///  - [`IdFinder`] for finding [`Id`]s in a [`IdInformation`](crate::IdInformation) (IPI stream).
 ///
-/// `ItemFinder` allocates all the memory it needs when it is first created. The footprint is
-/// directly proportional to the total number of types; see [`ItemInformation::len`].
+/// `ItemFinder` grows as it is populated. Once fully populated, the footprint is directly
+/// proportional to the total number of types; see [`ItemInformation::len`]. On very large streams,
+/// indexing only the items that are actually needed with [`ItemFinder::index_until`] keeps the
+/// footprint proportional to the highest index looked up instead.
 ///
 /// # Time/space trade-off
 ///
@@ -316,6 +434,8 @@ pub struct ItemFinder<'t, I> {
     maximum_index: u32,
     positions: Vec<u32>,
     shift: u8,
+    definitions: OnceLock<HashMap<&'t [u8], u32>>,
+    vftables: OnceLock<HashMap<u32, u32>>,
     _ph: PhantomData<&'t I>,
 }
 
@@ -327,11 +447,9 @@ where
         // maximum index is the highest index + 1.
         let count = info.header.maximum_index - info.header.minimum_index;
 
-        let round_base = (1 << shift) - 1;
-        let shifted_count = ((count + round_base) & !round_base) >> shift;
-        let mut positions = Vec::with_capacity(shifted_count as usize);
+        let mut positions = Vec::new();
 
-        if shifted_count > 0 {
+        if count > 0 {
             // add record zero, which is identical regardless of shift
             positions.push(info.header.header_size);
         }
@@ -342,6 +460,8 @@ where
             maximum_index: info.header.maximum_index,
             positions,
             shift,
+            definitions: OnceLock::new(),
+            vftables: OnceLock::new(),
             _ph: PhantomData,
         }
     }
@@ -381,11 +501,139 @@ where
         let (vec_index, iteration_count) = self.resolve(iterator.index);
         if iteration_count == 0 && vec_index == self.positions.len() {
             let pos = iterator.buf.pos();
-            assert!(pos < u32::max_value() as usize);
+            assert!(pos < u32::MAX as usize);
             self.positions.push(pos as u32);
         }
     }
 
+    /// Populates this `ItemFinder` up to at least `index` by scanning the stream from the last known
+    /// position.
+    ///
+    /// This allows indexing on demand instead of iterating over the entire stream up front. Memory
+    /// is only allocated for the items scanned. Calling this with an index that is already known
+    /// does nothing, and indices beyond the end of the stream populate the entire finder.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// let mut finder = type_information.finder();
+    /// finder.index_until(pdb::TypeIndex(0x1064))?;
+    ///
+    /// let baz = finder.find(pdb::TypeIndex(0x1064))?;
+    /// assert_eq!(baz.parse()?.name().unwrap().to_string(), "Baz");
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the stream is truncated
+    /// * `Error::TypeTooShort` if a record in the stream is malformed
+    pub fn index_until(&mut self, index: I) -> Result<()> {
+        let target: u32 = index.into();
+
+        let start = match self.positions.last() {
+            Some(&start) => start,
+            None => return Ok(()),
+        };
+
+        let mut buf = self.buffer.clone();
+        buf.take(start as usize)?;
+
+        let mut iter = ItemIter {
+            buf,
+            index: self.minimum_index + ((self.positions.len() as u32 - 1) << self.shift),
+            _ph: PhantomData,
+        };
+
+        while self.max_index().into() < target && iter.next()?.is_some() {
+            self.update(&iter);
+        }
+
+        Ok(())
+    }
+
+    /// Serializes the positions known by this `ItemFinder` into a compact binary index.
+    ///
+    /// The index can be loaded with [`ItemInformation::finder_from_index`] when the same PDB is
+    /// opened again. It stores one `u32` for every `2^shift` items, plus a small header.
+    pub fn export_index(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(FINDER_INDEX_HEADER_SIZE + self.positions.len() * 4);
+        data.extend_from_slice(FINDER_INDEX_MAGIC);
+        data.extend_from_slice(&FINDER_INDEX_VERSION.to_le_bytes());
+        data.extend_from_slice(&u32::from(self.shift).to_le_bytes());
+        data.extend_from_slice(&self.minimum_index.to_le_bytes());
+        data.extend_from_slice(&self.maximum_index.to_le_bytes());
+        data.extend_from_slice(&(self.buffer.len() as u32).to_le_bytes());
+        data.extend_from_slice(&(self.positions.len() as u32).to_le_bytes());
+        for position in &self.positions {
+            data.extend_from_slice(&position.to_le_bytes());
+        }
+        data
+    }
+
+    /// Replaces the positions of this `ItemFinder` with the ones stored in an exported index.
+    fn import_index(&mut self, data: &[u8]) -> Result<()> {
+        let stream_size = self.buffer.len();
+        if data.len() < FINDER_INDEX_HEADER_SIZE {
+            return Err(Error::InvalidFinderIndex("truncated header"));
+        }
+
+        let mut buf = ParseBuffer::from(data);
+        if buf.take(4)? != FINDER_INDEX_MAGIC {
+            return Err(Error::InvalidFinderIndex("unrecognized format"));
+        }
+
+        let version = buf.parse_u32()?;
+        let shift = buf.parse_u32()?;
+        let minimum_index = buf.parse_u32()?;
+        let maximum_index = buf.parse_u32()?;
+        let size = buf.parse_u32()? as usize;
+        let count = buf.parse_u32()? as usize;
+
+        if version != FINDER_INDEX_VERSION {
+            return Err(Error::InvalidFinderIndex("unsupported version"));
+        }
+        if shift != u32::from(self.shift) {
+            return Err(Error::InvalidFinderIndex("different time-space tradeoff"));
+        }
+        if minimum_index != self.minimum_index
+            || maximum_index != self.maximum_index
+            || size != stream_size
+        {
+            return Err(Error::InvalidFinderIndex("exported for a different stream"));
+        }
+
+        let round_base = (1 << self.shift) - 1;
+        let capacity = ((maximum_index - minimum_index + round_base) & !round_base) >> self.shift;
+        if count > capacity as usize || buf.len() != count * 4 {
+            return Err(Error::InvalidFinderIndex("invalid number of positions"));
+        }
+
+        let mut positions = Vec::with_capacity(capacity as usize);
+        for _ in 0..count {
+            let position = buf.parse_u32()?;
+            let unordered = positions.last().is_some_and(|&last| position <= last);
+            if unordered || position as usize > stream_size {
+                return Err(Error::InvalidFinderIndex("invalid position"));
+            }
+            positions.push(position);
+        }
+
+        if positions.first() != self.positions.first() {
+            return Err(Error::InvalidFinderIndex("invalid position"));
+        }
+
+        self.positions = positions;
+        Ok(())
+    }
+
     /// Find an `Item` by its index.
     ///
     /// # Errors
@@ -489,6 +737,144 @@ where
 /// [`ItemInformation`] for more information on accessing types.
 pub type TypeInformation<'s> = ItemInformation<'s, TypeIndex>;
 
+impl<'s> TypeInformation<'s> {
+    /// Returns the size of a pointer on the target machine in bytes, if known.
+    ///
+    /// [`PDB::type_information`](crate::PDB::type_information) derives this from the machine type
+    /// in the debug information header. It is used to size pointers whose records do not specify
+    /// a size, such as pointers to members in older PDBs.
+    pub fn pointer_size(&self) -> Option<u8> {
+        self.pointer_size
+    }
+
+    /// Overrides the size of a pointer on the target machine.
+    ///
+    /// This is useful for type streams that were not opened through
+    /// [`PDB::type_information`](crate::PDB::type_information), such as salvaged streams.
+    pub fn set_pointer_size(&mut self, pointer_size: Option<u8>) {
+        self.pointer_size = pointer_size;
+    }
+
+    /// Returns a [`TypeSizer`] for computing the sizes of types in this stream.
+    ///
+    /// This iterates over all types once to index them. Reuse the sizer to compute the sizes of
+    /// multiple types.
+    pub fn sizer(&self) -> Result<TypeSizer<'_>> {
+        TypeSizer::new(self)
+    }
+
+    /// Returns the size of the type with the given index in bytes.
+    ///
+    /// Forward references to classes, structures and unions are resolved to their definitions.
+    /// This indexes the entire stream on every call; use [`sizer`](Self::sizer) to compute the
+    /// sizes of multiple types. See [`TypeSizer`] for how sizes are determined.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// // 0x0074 is the primitive `int`
+    /// assert_eq!(type_information.size_of(pdb::TypeIndex(0x0074))?, 4);
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnsizedType(index)` if the type does not have a size
+    /// * `Error::TypeNotFound(index)` if the type or one of the types it refers to does not exist
+    /// * `Error::UnimplementedTypeKind(kind)` if a type record is not understood by this library
+    pub fn size_of(&self, index: TypeIndex) -> Result<u64> {
+        self.sizer()?.size_of(index)
+    }
+
+    /// Computes the layout of a class, structure or union.
+    ///
+    /// This indexes the entire stream on every call; use [`sizer`](Self::sizer) to compute the
+    /// layouts of multiple records. See [`TypeSizer::layout_of`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::NotARecordType(index)` if the type is not a class, structure or union
+    /// * `Error::UnsizedType(index)` if the record or one of its members does not have a size
+    /// * `Error::TypeNotFound(index)` if the type or one of the types it refers to does not exist
+    /// * `Error::UnimplementedTypeKind(kind)` if a type record is not understood by this library
+    pub fn layout_of(&self, index: TypeIndex) -> Result<RecordLayout<'_>> {
+        self.sizer()?.layout_of(index)
+    }
+
+    /// Finds all classes, structures, unions and enumerations with a name matching `pattern`.
+    ///
+    /// The pattern is matched against the entire name. `*` matches any sequence of characters,
+    /// including `::` and template arguments, and `?` matches a single character. A pattern
+    /// without wildcards finds types by their exact name. Forward references are included in the
+    /// result, which is ordered by type index.
+    ///
+    /// This iterates over the entire stream. To look up exact names in large PDBs, prefer
+    /// [`TypeHashes::find_all_by_name`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// let std_types = type_information.search("std::*")?;
+    /// assert!(!std_types.is_empty());
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the type stream is truncated
+    pub fn search(&self, pattern: &str) -> Result<Vec<TypeIndex>> {
+        self::search::search(self, pattern)
+    }
+
+    /// Compares the classes, structures and unions of this stream with those of another stream,
+    /// such as the type stream of the next build of the same program.
+    ///
+    /// Records are matched by their unique (decorated) name, or by their name if they do not have a
+    /// unique name. A record defined in both streams is reported as changed if its size, its base
+    /// classes or its data members differ. Members are matched by name and differ if their offset,
+    /// size or bitfield position differs, or if their types are not structurally identical as
+    /// decided by [`TypeComparator`]. Records whose layout cannot be computed are not compared.
+    ///
+    /// This indexes both streams.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// let diff = type_information.diff(&type_information)?;
+    /// assert!(diff.is_empty());
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::TypeNotFound(index)` if a type referred to by a record does not exist
+    /// * `Error::UnimplementedTypeKind(kind)` if a type record is not understood by this library
+    pub fn diff<'o>(&self, other: &'o TypeInformation<'_>) -> Result<TypeDiff<'_, 'o>> {
+        self::diff::diff(self, other)
+    }
+}
+
 /// In-memory index for efficient random-access of [`Type`]s by index.
 ///
 /// `TypeFinder` can be obtained via [`TypeInformation::finder`](ItemInformation::finder). See
@@ -498,6 +884,109 @@ pub type TypeFinder<'t> = ItemFinder<'t, TypeIndex>;
 /// An iterator over [`Type`]s returned by [`TypeInformation::iter`](ItemInformation::iter).
 pub type TypeIter<'t> = ItemIter<'t, TypeIndex>;
 
+impl<'t> TypeFinder<'t> {
+    /// Resolves a forward reference to the type record that defines it.
+    ///
+    /// Classes, structures, unions and enumerations are often referenced through a forward
+    /// reference, which only carries their name. This locates the definition with the same unique
+    /// name, or the same name if the forward reference has no unique name. Types that are not
+    /// forward references are returned unchanged.
+    ///
+    /// Returns `None` if the type is only declared and the stream does not contain a definition.
+    ///
+    /// The first call indexes the names of all definitions in the stream, regardless of how far
+    /// this `TypeFinder` has been populated. The returned index may therefore be beyond
+    /// [`max_index`](Self::max_index).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// let mut finder = type_information.finder();
+    /// let mut iter = type_information.iter();
+    /// while let Some(typ) = iter.next()? {
+    ///     finder.update(&iter);
+    ///
+    ///     if let Ok(pdb::TypeData::Class(class)) = typ.parse() {
+    ///         if class.properties.forward_reference() {
+    ///             let definition = finder.resolve_definition(typ.index())?;
+    ///             assert_ne!(definition, Some(typ.index()));
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::TypeNotFound(index)` if the type does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if the type is not yet known by this finder
+    /// * `Error::UnexpectedEof` if the type stream is truncated
+    pub fn resolve_definition(&self, index: TypeIndex) -> Result<Option<TypeIndex>> {
+        let (name, unique_name) = match self.find(index)?.parse() {
+            Ok(TypeData::Class(c)) if c.properties.forward_reference() => (c.name, c.unique_name),
+            Ok(TypeData::Union(u)) if u.properties.forward_reference() => (u.name, u.unique_name),
+            Ok(TypeData::Enumeration(e)) if e.properties.forward_reference() => {
+                (e.name, e.unique_name)
+            }
+            _ => return Ok(Some(index)),
+        };
+
+        let definitions = match self.definitions.get() {
+            Some(definitions) => definitions,
+            None => {
+                let definitions = self.index_definitions()?;
+                self.definitions.get_or_init(|| definitions)
+            }
+        };
+
+        let key = unique_name.unwrap_or(name);
+        Ok(definitions
+            .get(key.as_bytes())
+            .map(|&index| TypeIndex(index)))
+    }
+
+    /// Maps the unique names of all class, union and enumeration definitions to their indices.
+    fn index_definitions(&self) -> Result<HashMap<&'t [u8], u32>> {
+        let mut definitions = HashMap::new();
+
+        let mut buf = self.buffer.clone();
+        match self.positions.first() {
+            Some(&start) => buf.take(start as usize)?,
+            None => return Ok(definitions),
+        };
+
+        let mut iter = TypeIter {
+            buf,
+            index: self.minimum_index,
+            _ph: PhantomData,
+        };
+
+        while let Some(item) = iter.next()? {
+            let (properties, name, unique_name) = match item.parse() {
+                Ok(TypeData::Class(c)) => (c.properties, c.name, c.unique_name),
+                Ok(TypeData::Union(u)) => (u.properties, u.name, u.unique_name),
+                Ok(TypeData::Enumeration(e)) => (e.properties, e.name, e.unique_name),
+                _ => continue,
+            };
+
+            if !properties.forward_reference() {
+                let key = unique_name.unwrap_or(name);
+                definitions.entry(key.as_bytes()).or_insert(item.index().0);
+            }
+        }
+
+        Ok(definitions)
+    }
+}
+
 /// Information on a primitive type, class, or procedure.
 pub type Type<'t> = Item<'t, TypeIndex>;
 
@@ -518,6 +1007,26 @@ impl<'t> Item<'t, TypeIndex> {
             parse_type_data(&mut buf)
         }
     }
+
+    /// Parse this `Type` into `TypeData`, reusing vectors from `arena`.
+    ///
+    /// This behaves like [`parse`](Self::parse), but takes the vectors of field lists and
+    /// argument lists from the arena. Return the parsed data with [`TypeArena::recycle`] once it is
+    /// no longer needed to make its vectors available to subsequent calls.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnimplementedTypeKind(kind)` if the type record isn't currently understood by this
+    ///   library
+    /// * `Error::UnexpectedEof` if the type record is malformed
+    pub fn parse_in(&self, arena: &mut TypeArena<'t>) -> Result<TypeData<'t>> {
+        if self.index < TypeIndex(0x1000) {
+            type_data_for_primitive(self.index)
+        } else {
+            let mut buf = ParseBuffer::from(self.data);
+            parse_type_data_in(&mut buf, arena)
+        }
+    }
 }
 
 /// Zero-copy access to the PDB type stream (TPI).
@@ -530,6 +1039,11 @@ pub type IdInformation<'s> = ItemInformation<'s, IdIndex>;
 ///
 /// `IdFinder` can be obtained via [`IdInformation::finder`](ItemInformation::finder). See
 /// [`ItemFinder`] for more information.
+///
+/// Ids only refer to ids with lower indices, so resolving a few ids, such as the inlinees of the
+/// inline sites in one function, does not require indexing the entire stream. Populate the finder
+/// with [`index_until`](ItemFinder::index_until) up to the highest id needed instead, or look up
+/// individual ids with [`IdHashes::find_item`](ItemHashes::find_item).
 pub type IdFinder<'t> = ItemFinder<'t, IdIndex>;
 
 /// An iterator over [`Id`]s returned by [`IdInformation::iter`](ItemInformation::iter).
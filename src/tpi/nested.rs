@@ -0,0 +1,146 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::common::*;
+use crate::tpi::data::{FieldAttributes, TypeData};
+use crate::tpi::TypeFinder;
+
+/// A type declared in the scope of a class, structure or union, as returned by
+/// [`TypeFinder::nested_types`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Nested<'t> {
+    /// The name of the nested type, without the name of the enclosing type.
+    pub name: RawString<'t>,
+    /// Access and other properties of the nested type.
+    pub attributes: FieldAttributes,
+    /// The type as recorded in the field list, which may be a forward reference.
+    pub nested_type: TypeIndex,
+    /// The definition of the nested type, or `None` if it is only declared.
+    ///
+    /// For types other than classes, structures, unions and enumerations, this is the same as
+    /// [`nested_type`](Self::nested_type).
+    pub definition: Option<TypeIndex>,
+    /// Whether the name is a `typedef` of a type that is not declared in the enclosing type.
+    pub typedef: bool,
+}
+
+impl<'t> TypeFinder<'t> {
+    /// Returns the types declared in the scope of a class, structure or union, in declaration
+    /// order.
+    ///
+    /// Every `LF_NESTTYPE` and `LF_NESTTYPEEX` field is resolved to the definition of the type it
+    /// declares. Besides nested classes, structures, unions and enumerations, this includes member
+    /// `typedef`s, which are marked as such. Call this method with the definition of a nested type
+    /// to walk nested scopes further.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// let mut finder = type_information.finder();
+    /// let mut iter = type_information.iter();
+    /// while let Some(_) = iter.next()? {
+    ///     finder.update(&iter);
+    /// }
+    ///
+    /// let mut iter = type_information.iter();
+    /// while let Some(typ) = iter.next()? {
+    ///     if let Ok(pdb::TypeData::Class(class)) = typ.parse() {
+    ///         for nested in finder.nested_types(typ.index())? {
+    ///             println!("{}::{}", class.name, nested.name);
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::NotARecordType(index)` if the type is not a class, structure or union
+    /// * `Error::UnimplementedFeature` if a field list has an unexpected type
+    /// * `Error::TypeNotFound(index)` if a type does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if a type is not yet known by this finder
+    /// * `Error::UnimplementedTypeKind(kind)` if a type record is not understood by this library
+    pub fn nested_types(&self, index: TypeIndex) -> Result<Vec<Nested<'t>>> {
+        let class = self.resolve_definition(index)?.unwrap_or(index);
+        let scope = match self.find(class)?.parse()? {
+            TypeData::Class(data) => data.name,
+            TypeData::Union(data) => data.name,
+            _ => return Err(Error::NotARecordType(index.0)),
+        };
+
+        let mut nested_types = Vec::new();
+        for field in self.fields(class)? {
+            let nested = match field {
+                TypeData::Nested(nested) => nested,
+                _ => continue,
+            };
+
+            let declared_name = match self.find(nested.nested_type)?.parse() {
+                Ok(TypeData::Class(data)) => Some(data.name),
+                Ok(TypeData::Union(data)) => Some(data.name),
+                Ok(TypeData::Enumeration(data)) => Some(data.name),
+                _ => None,
+            };
+
+            let typedef = match declared_name {
+                Some(declared_name) => !is_scoped_name(declared_name, scope, nested.name),
+                None => true,
+            };
+
+            nested_types.push(Nested {
+                name: nested.name,
+                attributes: nested.attributes,
+                nested_type: nested.nested_type,
+                definition: self.resolve_definition(nested.nested_type)?,
+                typedef,
+            });
+        }
+
+        Ok(nested_types)
+    }
+}
+
+/// Returns whether `name` is `scope::member`.
+fn is_scoped_name(name: RawString<'_>, scope: RawString<'_>, member: RawString<'_>) -> bool {
+    let name = name.as_bytes();
+    let (scope, member) = (scope.as_bytes(), member.as_bytes());
+
+    name.len() == scope.len() + 2 + member.len()
+        && name.starts_with(scope)
+        && name[scope.len()..].starts_with(b"::")
+        && name.ends_with(member)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_scoped_name() {
+        let scoped = |name, scope, member| {
+            is_scoped_name(
+                RawString::from(name),
+                RawString::from(scope),
+                RawString::from(member),
+            )
+        };
+
+        assert!(scoped("Outer::Inner", "Outer", "Inner"));
+        assert!(scoped("ns::Outer::Inner", "ns::Outer", "Inner"));
+        assert!(!scoped("Other", "Outer", "Inner"));
+        assert!(!scoped("Outer::Other", "Outer", "Inner"));
+        assert!(!scoped("Outer:Inner", "Outer", "Inner"));
+    }
+}
@@ -0,0 +1,318 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::common::*;
+use crate::tpi::constants::{LF_ENDPRECOMP, LF_PRECOMP, LF_PRECOMP_ST};
+use crate::tpi::data::{parse_type_data, PrecompiledType, TypeData};
+use crate::tpi::{Item, Type, PRIMITIVE_TYPE};
+
+/// Signature of CodeView sections in the C13 format.
+const CV_SIGNATURE_C13: u32 = 4;
+
+/// The type records of an object file, read from its `.debug$T` section.
+///
+/// Type indices in an object file are local to that object. Objects compiled with `/Yu` refer to
+/// the types of the precompiled header through an `LF_PRECOMP` record, which reserves a range of
+/// type indices for types stored in the object that created the precompiled header with `/Yc`.
+/// That object ends its type records with a matching `LF_ENDPRECOMP` record. Use
+/// [`link_precompiled`](Self::link_precompiled) to serve the reserved indices from that object.
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// # fn section(records: &[&[u8]]) -> Vec<u8> {
+/// #     let mut data = 4u32.to_le_bytes().to_vec();
+/// #     for record in records {
+/// #         data.extend_from_slice(&(record.len() as u16).to_le_bytes());
+/// #         data.extend_from_slice(record);
+/// #     }
+/// #     data
+/// # }
+/// # let pch_section = section(&[
+/// #     b"\x01\x10\x74\x00\x00\x00\x01\x00",
+/// #     b"\x14\x00\x2a\x00\x00\x00",
+/// # ]);
+/// # let section = section(&[
+/// #     b"\x09\x15\x00\x10\x00\x00\x01\x00\x00\x00\x2a\x00\x00\x00pch.obj\x00",
+/// #     b"\x01\x10\x00\x10\x00\x00\x02\x00",
+/// # ]);
+/// // the `.debug$T` sections of an object using a precompiled header, and of the object that
+/// // created the precompiled header
+/// let types = pdb::ObjectTypes::parse(&section)?;
+/// let pch_types = pdb::ObjectTypes::parse(&pch_section)?;
+///
+/// let precompiled = types.precompiled().expect("uses a precompiled header");
+/// assert_eq!(precompiled.name.to_string(), "pch.obj");
+///
+/// let types = types.link_precompiled(&pch_types)?;
+/// for index in 0x1000..0x1002 {
+///     let data = types.find(pdb::TypeIndex(index))?.parse()?;
+///     assert!(matches!(data, pdb::TypeData::Modifier(_)));
+/// }
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Clone, Debug)]
+pub struct ObjectTypes<'t> {
+    /// Index of the first record.
+    first_index: u32,
+    /// The type records, excluding `LF_PRECOMP` and `LF_ENDPRECOMP`.
+    records: Vec<&'t [u8]>,
+    precompiled: Option<PrecompiledType<'t>>,
+    end_signature: Option<u32>,
+}
+
+impl<'t> ObjectTypes<'t> {
+    /// Parses the contents of a `.debug$T` section.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnimplementedFeature` if the section is not in the C13 format
+    /// * `Error::TypeTooShort` if a type record is too short to contain its kind
+    /// * `Error::UnexpectedEof` if the section is truncated
+    pub fn parse(data: &'t [u8]) -> Result<Self> {
+        let mut buf = ParseBuffer::from(data);
+        if buf.parse_u32()? != CV_SIGNATURE_C13 {
+            return Err(Error::UnimplementedFeature(
+                "CodeView type sections other than C13",
+            ));
+        }
+
+        let mut records = Vec::new();
+        let mut precompiled = None;
+        let mut end_signature = None;
+
+        while !buf.is_empty() {
+            let length = buf.parse_u16()? as usize;
+            if length < 2 {
+                return Err(Error::TypeTooShort);
+            }
+
+            let record = buf.take(length)?;
+            let kind = u16::from_le_bytes([record[0], record[1]]);
+            match kind {
+                LF_PRECOMP | LF_PRECOMP_ST if records.is_empty() && precompiled.is_none() => {
+                    if let TypeData::Precompiled(data) =
+                        parse_type_data(&mut ParseBuffer::from(record))?
+                    {
+                        precompiled = Some(data);
+                    }
+                }
+                LF_ENDPRECOMP => {
+                    if let TypeData::EndPrecompiled(data) =
+                        parse_type_data(&mut ParseBuffer::from(record))?
+                    {
+                        end_signature = Some(data.signature);
+                    }
+                }
+                _ => records.push(record),
+            }
+        }
+
+        let first_index = match precompiled {
+            Some(ref precompiled) => precompiled.start_index.saturating_add(precompiled.count),
+            None => 0x1000,
+        };
+
+        Ok(Self {
+            first_index,
+            records,
+            precompiled,
+            end_signature,
+        })
+    }
+
+    /// The `LF_PRECOMP` record of an object that was compiled using a precompiled header.
+    ///
+    /// Until [`link_precompiled`](Self::link_precompiled) is called, the type indices reserved by
+    /// this record cannot be found.
+    pub fn precompiled(&self) -> Option<&PrecompiledType<'t>> {
+        self.precompiled.as_ref()
+    }
+
+    /// The signature of the `LF_ENDPRECOMP` record of an object that created a precompiled header.
+    pub fn end_precompiled_signature(&self) -> Option<u32> {
+        self.end_signature
+    }
+
+    /// Returns the number of type records that can be found.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns whether there are no type records.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Returns the lowest type index that can be found.
+    pub fn minimum_index(&self) -> TypeIndex {
+        TypeIndex(self.first_index)
+    }
+
+    /// Returns the type records in the order of their type indices.
+    pub fn iter(&self) -> impl Iterator<Item = Type<'t>> + '_ {
+        self.records.iter().enumerate().map(move |(i, &data)| Item {
+            index: TypeIndex(self.first_index + i as u32),
+            data,
+        })
+    }
+
+    /// Finds a type by its index.
+    ///
+    /// Indices below `0x1000` refer to primitive types.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::TypeNotFound(index)` if the type does not exist, or is reserved for the types of
+    ///   a precompiled header that was not linked
+    pub fn find(&self, index: TypeIndex) -> Result<Type<'t>> {
+        if index.0 < 0x1000 {
+            return Ok(Item {
+                index,
+                data: PRIMITIVE_TYPE,
+            });
+        }
+
+        index
+            .0
+            .checked_sub(self.first_index)
+            .and_then(|offset| self.records.get(offset as usize))
+            .map(|&data| Item { index, data })
+            .ok_or(Error::TypeNotFound(index.0))
+    }
+
+    /// Combines the types of this object with the types of the precompiled header it uses.
+    ///
+    /// `precompiled` must be the types of the object that created the precompiled header named by
+    /// the `LF_PRECOMP` record. The first types of that object are assigned the indices reserved
+    /// by the `LF_PRECOMP` record, so that all types referenced by this object can be found.
+    ///
+    /// Objects without an `LF_PRECOMP` record are returned unchanged.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::PrecompiledTypesMismatch` if the signatures do not match, or the precompiled
+    ///   header object does not contain the number of types reserved by the `LF_PRECOMP` record
+    pub fn link_precompiled(&self, precompiled: &ObjectTypes<'t>) -> Result<Self> {
+        let reference = match self.precompiled {
+            Some(ref reference) => reference,
+            None => return Ok(self.clone()),
+        };
+
+        let count = reference.count as usize;
+        if precompiled.end_signature != Some(reference.signature)
+            || precompiled.first_index != reference.start_index
+            || precompiled.records.len() < count
+        {
+            return Err(Error::PrecompiledTypesMismatch);
+        }
+
+        let mut records = Vec::with_capacity(count + self.records.len());
+        records.extend_from_slice(&precompiled.records[..count]);
+        records.extend_from_slice(&self.records);
+
+        Ok(Self {
+            first_index: reference.start_index,
+            records,
+            precompiled: None,
+            end_signature: self.end_signature,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(records: &[&[u8]]) -> Vec<u8> {
+        let mut data = CV_SIGNATURE_C13.to_le_bytes().to_vec();
+        for record in records {
+            data.extend_from_slice(&(record.len() as u16).to_le_bytes());
+            data.extend_from_slice(record);
+        }
+        data
+    }
+
+    // LF_MODIFIER const of the given type
+    fn modifier(index: u32) -> Vec<u8> {
+        let mut record = vec![0x01, 0x10];
+        record.extend_from_slice(&index.to_le_bytes());
+        record.extend_from_slice(&[0x01, 0x00]);
+        record
+    }
+
+    fn precomp(start: u32, count: u32, signature: u32) -> Vec<u8> {
+        let mut record = vec![0x09, 0x15];
+        record.extend_from_slice(&start.to_le_bytes());
+        record.extend_from_slice(&count.to_le_bytes());
+        record.extend_from_slice(&signature.to_le_bytes());
+        record.extend_from_slice(b"pch.obj\0");
+        record
+    }
+
+    fn endprecomp(signature: u32) -> Vec<u8> {
+        let mut record = vec![0x14, 0x00];
+        record.extend_from_slice(&signature.to_le_bytes());
+        record
+    }
+
+    #[test]
+    fn test_link_precompiled() {
+        let pch = section(&[&modifier(0x74), &modifier(0x1000), &endprecomp(42)]);
+        let pch = ObjectTypes::parse(&pch).expect("parse pch");
+        assert_eq!(pch.end_precompiled_signature(), Some(42));
+        assert_eq!(pch.len(), 2);
+
+        let object = section(&[&precomp(0x1000, 2, 42), &modifier(0x1001)]);
+        let object = ObjectTypes::parse(&object).expect("parse");
+        assert_eq!(object.minimum_index(), TypeIndex(0x1002));
+        assert!(matches!(
+            object.find(TypeIndex(0x1000)),
+            Err(Error::TypeNotFound(0x1000))
+        ));
+
+        let linked = object.link_precompiled(&pch).expect("link");
+        assert_eq!(linked.minimum_index(), TypeIndex(0x1000));
+        assert!(linked.precompiled().is_none());
+
+        let indices: Vec<_> = linked.iter().map(|item| item.index()).collect();
+        assert_eq!(
+            indices,
+            vec![TypeIndex(0x1000), TypeIndex(0x1001), TypeIndex(0x1002)]
+        );
+
+        match linked.find(TypeIndex(0x1002)).unwrap().parse().unwrap() {
+            TypeData::Modifier(modifier) => {
+                assert_eq!(modifier.underlying_type, TypeIndex(0x1001))
+            }
+            other => panic!("unexpected type {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_link_precompiled_mismatch() {
+        let pch = section(&[&modifier(0x74), &endprecomp(42)]);
+        let pch = ObjectTypes::parse(&pch).expect("parse pch");
+
+        let object = section(&[&precomp(0x1000, 1, 43)]);
+        let object = ObjectTypes::parse(&object).expect("parse");
+        assert!(matches!(
+            object.link_precompiled(&pch),
+            Err(Error::PrecompiledTypesMismatch)
+        ));
+
+        let object = section(&[&precomp(0x1000, 2, 42)]);
+        let object = ObjectTypes::parse(&object).expect("parse");
+        assert!(matches!(
+            object.link_precompiled(&pch),
+            Err(Error::PrecompiledTypesMismatch)
+        ));
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::fmt;
+use std::mem;
+use std::slice;
+use std::sync::Arc;
+
+use crate::common::*;
+use crate::tpi::data::TypeData;
+
+/// A deep copy of a [`TypeData`] that does not borrow from the PDB.
+///
+/// `TypeData` refers to names stored in the type stream, which ties it to the lifetime of the
+/// `PDB`. `OwnedTypeData`, as returned by [`TypeData::to_owned`], copies these names into a buffer
+/// it owns. It is `'static` and `Send`, so it can be stored in caches or sent to other threads
+/// after the PDB has been dropped.
+///
+/// Access the type data with [`get`](Self::get). Strings borrowed from it cannot outlive the
+/// `OwnedTypeData`. Cloning shares the buffer of names.
+///
+/// # Example
+///
+/// ```
+/// # use pdb::FallibleIterator;
+/// # fn test() -> pdb::Result<()> {
+/// let owned = {
+///     let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+///     let pdb = pdb::PDB::open(file)?;
+///     let type_information = pdb.type_information()?;
+///
+///     let mut iter = type_information.iter();
+///     let typ = iter.next()?.expect("type");
+///     typ.parse()?.to_owned()
+/// };
+///
+/// std::thread::spawn(move || println!("{:?}", owned.get()))
+///     .join()
+///     .unwrap();
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Clone)]
+pub struct OwnedTypeData {
+    // `data` borrows from `_strings`; declared first so it is dropped first
+    data: TypeData<'static>,
+    _strings: Arc<[u8]>,
+}
+
+impl OwnedTypeData {
+    /// Returns the type data.
+    pub fn get(&self) -> &TypeData<'_> {
+        &self.data
+    }
+}
+
+impl fmt::Debug for OwnedTypeData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+impl PartialEq for OwnedTypeData {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl Eq for OwnedTypeData {}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for OwnedTypeData {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.get().serialize(serializer)
+    }
+}
+
+impl<'t> TypeData<'t> {
+    /// Returns a deep copy of this type data that does not borrow from the PDB.
+    ///
+    /// All strings, including the names of fields in field lists, are copied into a single buffer
+    /// owned by the returned [`OwnedTypeData`].
+    pub fn to_owned(&self) -> OwnedTypeData {
+        let mut data = self.clone();
+
+        let mut buffer = Vec::new();
+        data.visit_strings_mut(&mut |string| buffer.extend_from_slice(string.as_bytes()));
+        let strings: Arc<[u8]> = buffer.into();
+
+        // SAFETY: Every string is replaced below with one pointing into `strings`, which is owned
+        // by the result and never mutated. The heap allocation of an `Arc` does not move, and
+        // `get` only hands out strings bounded by the lifetime of the `OwnedTypeData`.
+        let mut data = unsafe { mem::transmute::<TypeData<'t>, TypeData<'static>>(data) };
+        let mut offset = 0;
+        data.visit_strings_mut(&mut |string| {
+            let bytes = &strings[offset..offset + string.len()];
+            offset += bytes.len();
+            *string =
+                RawString::from(unsafe { slice::from_raw_parts(bytes.as_ptr(), bytes.len()) });
+        });
+
+        OwnedTypeData {
+            data,
+            _strings: strings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tpi::data::*;
+
+    fn assert_send_static<T: Send + Sync + 'static>(_: &T) {}
+
+    #[test]
+    fn test_to_owned() {
+        let names = String::from("abc");
+        let data = TypeData::FieldList(FieldList {
+            fields: vec![
+                TypeData::Member(MemberType {
+                    attributes: FieldAttributes(3),
+                    field_type: TypeIndex(0x74),
+                    offset: 0,
+                    name: RawString::from(&names[0..1]),
+                }),
+                TypeData::Enumerate(EnumerateType {
+                    attributes: FieldAttributes(3),
+                    value: Variant::String(RawString::from(&names[1..2])),
+                    name: RawString::from(&names[2..3]),
+                }),
+            ],
+            continuation: None,
+        });
+
+        let owned = data.to_owned();
+        assert_send_static(&owned);
+        drop(names);
+
+        assert_eq!(owned.clone(), owned);
+        match owned.get() {
+            TypeData::FieldList(list) => {
+                assert_eq!(list.fields[0].name(), Some(RawString::from("a")));
+                assert_eq!(list.fields[1].name(), Some(RawString::from("c")));
+                match list.fields[1] {
+                    TypeData::Enumerate(ref enumerate) => {
+                        assert_eq!(enumerate.value, Variant::String(RawString::from("b")));
+                    }
+                    ref other => panic!("unexpected type {:?}", other),
+                }
+            }
+            other => panic!("unexpected type {:?}", other),
+        }
+    }
+}
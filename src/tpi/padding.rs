@@ -0,0 +1,344 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::common::*;
+use crate::tpi::data::TypeData;
+use crate::tpi::layout::RecordLayout;
+use crate::tpi::size::TypeSizer;
+use crate::tpi::TypeInformation;
+use crate::FallibleIterator;
+
+/// An analysis of the padding in a class, structure or union, in the spirit of `pahole`.
+///
+/// The report is derived from the [`RecordLayout`] of the record, and adds the members that
+/// straddle cache line boundaries and an estimate of the size the record could have if its
+/// members were reordered. Create it using [`TypeSizer::padding_report`], or
+/// [`TypeInformation::padding_reports`] to analyze all records of a type stream.
+///
+/// The `Display` implementation renders the report in a format resembling the output of
+/// `pahole`, with the offset and size of every member and the holes between them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaddingReport<'t> {
+    /// The layout of the record.
+    pub layout: RecordLayout<'t>,
+    /// Whether the record is a union.
+    pub union: bool,
+    /// The size of a cache line in bytes.
+    pub cacheline_size: u64,
+    /// Indices into [`layout.members`](RecordLayout::members) of members that cross a cache line
+    /// boundary.
+    pub cacheline_crossings: Vec<usize>,
+    /// The estimated size of the record if its data members were ordered by decreasing alignment.
+    ///
+    /// Base classes and hidden pointers keep their position. This equals the size of the record if
+    /// its members cannot be reordered, such as for unions, records with virtual base classes, and
+    /// records with overlapping members.
+    pub optimal_size: u64,
+}
+
+impl<'t> PaddingReport<'t> {
+    /// Returns the byte ranges of the record that are padding, see [`RecordLayout::padding`].
+    pub fn holes(&self) -> &[Range<u64>] {
+        &self.layout.padding
+    }
+
+    /// Returns the total number of padding bytes.
+    pub fn padding_size(&self) -> u64 {
+        self.holes().iter().map(|hole| hole.end - hole.start).sum()
+    }
+
+    /// Returns the number of bytes that could be saved by reordering the members.
+    pub fn savings(&self) -> u64 {
+        self.layout.size.saturating_sub(self.optimal_size)
+    }
+
+    /// Returns the number of cache lines spanned by the record.
+    pub fn cachelines(&self) -> u64 {
+        if self.cacheline_size == 0 {
+            return 0;
+        }
+
+        self.layout.size.div_ceil(self.cacheline_size)
+    }
+}
+
+impl fmt::Display for PaddingReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let layout = &self.layout;
+        let keyword = if self.union { "union" } else { "struct" };
+        writeln!(f, "{} {} {{", keyword, layout.name)?;
+
+        // interleave members and holes by offset
+        let mut holes = self.holes().iter().peekable();
+        for (i, member) in layout.members.iter().enumerate() {
+            while let Some(hole) = holes.next_if(|hole| hole.start <= member.offset) {
+                write_hole(f, hole)?;
+            }
+
+            let name = match member.bitfield {
+                Some(bitfield) => format!("{}:{}", member.name, bitfield.length),
+                None => member.name.to_string().into_owned(),
+            };
+            write!(
+                f,
+                "    {:<40} /* {:5} {:5} */",
+                name, member.offset, member.size
+            )?;
+            if self.cacheline_crossings.contains(&i) {
+                write!(f, " /* crosses cacheline */")?;
+            }
+            writeln!(f)?;
+        }
+        for hole in holes {
+            write_hole(f, hole)?;
+        }
+
+        writeln!(
+            f,
+            "    /* size: {}, cachelines: {}, members: {} */",
+            layout.size,
+            self.cachelines(),
+            layout.members.len()
+        )?;
+        writeln!(
+            f,
+            "    /* holes: {}, sum holes: {} */",
+            self.holes().len(),
+            self.padding_size()
+        )?;
+        if self.savings() > 0 {
+            writeln!(
+                f,
+                "    /* reordering saves {} bytes, optimal size: {} */",
+                self.savings(),
+                self.optimal_size
+            )?;
+        }
+        write!(f, "}};")
+    }
+}
+
+fn write_hole(f: &mut fmt::Formatter<'_>, hole: &Range<u64>) -> fmt::Result {
+    writeln!(
+        f,
+        "    /* XXX {} bytes hole at offset {} */",
+        hole.end - hole.start,
+        hole.start
+    )
+}
+
+impl<'t> TypeSizer<'t> {
+    /// Analyzes the padding of a class, structure or union.
+    ///
+    /// `cacheline_size` is the size of a cache line in bytes, typically `64`. See
+    /// [`PaddingReport`] for the information returned.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::NotARecordType(index)` if the type is not a class, structure or union
+    /// * `Error::UnsizedType(index)` if the record or one of its members does not have a size
+    /// * `Error::TypeNotFound(index)` if the type or one of the types it refers to does not exist
+    /// * `Error::UnimplementedTypeKind(kind)` if a type record is not understood by this library
+    pub fn padding_report(
+        &self,
+        index: TypeIndex,
+        cacheline_size: u64,
+    ) -> Result<PaddingReport<'t>> {
+        let layout = self.layout_of(index)?;
+        let union = matches!(self.parse(layout.index)?, TypeData::Union(_));
+
+        let cacheline_crossings = match cacheline_size {
+            0 => Vec::new(),
+            _ => layout
+                .members
+                .iter()
+                .enumerate()
+                .filter(|(_, member)| {
+                    member.size > 0
+                        && member.offset / cacheline_size
+                            != (member.offset + member.size - 1) / cacheline_size
+                })
+                .map(|(i, _)| i)
+                .collect(),
+        };
+
+        let optimal_size = if union {
+            layout.size
+        } else {
+            optimal_size(&layout)
+        };
+
+        Ok(PaddingReport {
+            layout,
+            union,
+            cacheline_size,
+            cacheline_crossings,
+            optimal_size,
+        })
+    }
+}
+
+impl<'s> TypeInformation<'s> {
+    /// Analyzes the padding of all classes, structures and unions defined in this stream.
+    ///
+    /// Records whose layout cannot be computed, for instance because a member has an incomplete
+    /// type, are skipped. Reports are ordered by type index. See [`TypeSizer::padding_report`] to
+    /// analyze a single record.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// for report in type_information.padding_reports(64)? {
+    ///     if report.savings() > 0 {
+    ///         println!("{}", report);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the type stream is truncated
+    /// * `Error::UnimplementedTypeKind(kind)` if a type record is not understood by this library
+    pub fn padding_reports(&self, cacheline_size: u64) -> Result<Vec<PaddingReport<'_>>> {
+        let sizer = self.sizer()?;
+        let mut reports = Vec::new();
+
+        let mut iter = self.iter();
+        while let Some(item) = iter.next()? {
+            let properties = match item.parse() {
+                Ok(TypeData::Class(data)) => data.properties,
+                Ok(TypeData::Union(data)) => data.properties,
+                _ => continue,
+            };
+
+            if properties.forward_reference() {
+                continue;
+            }
+
+            match sizer.padding_report(item.index(), cacheline_size) {
+                Ok(report) => reports.push(report),
+                Err(Error::UnsizedType(_)) | Err(Error::TypeNotFound(_)) => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(reports)
+    }
+}
+
+/// Estimates the size of a record if its data members were ordered by decreasing alignment.
+fn optimal_size(layout: &RecordLayout<'_>) -> u64 {
+    if !layout.virtual_base_classes.is_empty() || layout.members.is_empty() {
+        return layout.size;
+    }
+
+    // bitfields sharing a storage unit are moved together
+    let mut units: Vec<(Range<u64>, u64)> = Vec::with_capacity(layout.members.len());
+    for member in &layout.members {
+        let range = member.offset..member.offset + member.size;
+        match units.last() {
+            Some((last, _)) if member.bitfield.is_some() && *last == range => {}
+            _ => units.push((range, member.alignment)),
+        }
+    }
+
+    // members of anonymous unions overlap and cannot be reordered independently
+    let mut sorted: Vec<_> = units.iter().map(|(range, _)| range.clone()).collect();
+    sorted.sort_by_key(|range| range.start);
+    if sorted.windows(2).any(|pair| pair[0].end > pair[1].start) {
+        return layout.size;
+    }
+
+    let fixed_end = layout
+        .base_classes
+        .iter()
+        .map(|base| base.offset + base.size)
+        .chain(layout.vfptr.as_ref().map(|vfptr| vfptr.end))
+        .max()
+        .unwrap_or(0);
+
+    // place the most strictly aligned members first, keeping the declaration order otherwise
+    units.sort_by_key(|(_, alignment)| std::cmp::Reverse(*alignment));
+    let mut position = fixed_end;
+    for (range, alignment) in units {
+        position = align(position, alignment) + (range.end - range.start);
+    }
+
+    align(position, layout.alignment).min(layout.size)
+}
+
+fn align(position: u64, alignment: u64) -> u64 {
+    match alignment {
+        0 => position,
+        alignment => position.next_multiple_of(alignment),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tpi::layout::MemberLayout;
+
+    fn member(offset: u64, size: u64) -> MemberLayout<'static> {
+        MemberLayout {
+            name: RawString::from("m"),
+            field_type: TypeIndex(0x74),
+            offset,
+            size,
+            alignment: size,
+            bitfield: None,
+        }
+    }
+
+    fn layout(size: u64, members: Vec<MemberLayout<'static>>) -> RecordLayout<'static> {
+        RecordLayout {
+            index: TypeIndex(0x1000),
+            name: RawString::from("Foo"),
+            size,
+            alignment: members.iter().map(|m| m.alignment).max().unwrap_or(1),
+            vfptr: None,
+            base_classes: Vec::new(),
+            virtual_base_classes: Vec::new(),
+            members,
+            padding: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_optimal_size() {
+        // struct { char a; double b; char c; }
+        let layout = layout(24, vec![member(0, 1), member(8, 8), member(16, 1)]);
+        assert_eq!(optimal_size(&layout), 16);
+
+        // struct { double b; char a; char c; }
+        let packed = self::layout(16, vec![member(0, 8), member(8, 1), member(9, 1)]);
+        assert_eq!(optimal_size(&packed), 16);
+
+        // overlapping members of an anonymous union
+        let overlapping = self::layout(16, vec![member(0, 1), member(0, 8), member(8, 1)]);
+        assert_eq!(optimal_size(&overlapping), 16);
+    }
+
+    #[test]
+    fn test_align() {
+        assert_eq!(align(0, 8), 0);
+        assert_eq!(align(1, 8), 8);
+        assert_eq!(align(9, 4), 12);
+        assert_eq!(align(3, 0), 3);
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use rayon::prelude::*;
+
+use crate::common::*;
+use crate::tpi::{ItemFinder, ItemHashes, ItemInformation};
+
+/// A range of consecutive records scanned by a single task.
+#[derive(Clone, Copy, Debug)]
+struct Chunk {
+    /// Index of the first record in the chunk.
+    start_index: u32,
+    /// Index one past the last record in the chunk.
+    end_index: u32,
+    /// Position of the first record within the stream.
+    start: usize,
+    /// Position of the next chunk within the stream, or the end of the stream.
+    end: usize,
+}
+
+impl<'s, I> ItemInformation<'s, I>
+where
+    I: ItemIndex + Sync,
+{
+    /// Returns a fully populated [`ItemFinder`], scanning the stream on multiple threads.
+    ///
+    /// Populating an `ItemFinder` requires visiting every record, which takes a while for large
+    /// type and id streams. The hash stream of [`PDB::type_hashes`](crate::PDB::type_hashes) or
+    /// [`PDB::id_hashes`](crate::PDB::id_hashes) records the offsets of records at regular
+    /// intervals, which allows splitting the stream into chunks that are scanned in parallel using
+    /// the global [rayon](https://docs.rs/rayon) thread pool.
+    ///
+    /// The resulting finder is identical to one populated by iterating over the stream and calling
+    /// [`ItemFinder::update`](crate::ItemFinder::update) for every record.
+    ///
+    /// This requires the `rayon` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    /// let hashes = pdb.type_hashes()?.expect("hash stream");
+    ///
+    /// let finder = type_information.par_finder(&hashes)?;
+    /// let baz = finder.find(pdb::TypeIndex(0x1064))?;
+    /// assert_eq!(baz.parse()?.name().unwrap().to_string(), "Baz");
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidFinderIndex(reason)` if the offsets in the hash stream do not match the
+    ///   records
+    /// * `Error::UnexpectedEof` if the stream is truncated
+    pub fn par_finder(&self, hashes: &ItemHashes<I>) -> Result<ItemFinder<'_, I>> {
+        let mut finder = self.finder();
+        if self.is_empty() {
+            return Ok(finder);
+        }
+
+        let chunks = self.chunks(hashes)?;
+        let positions = chunks
+            .par_iter()
+            .map(|chunk| scan(&finder, chunk))
+            .collect::<Result<Vec<_>>>()?;
+
+        finder.positions.clear();
+        for chunk_positions in positions {
+            finder.positions.extend(chunk_positions);
+        }
+
+        Ok(finder)
+    }
+
+    /// Splits the records into chunks at the offsets recorded in the hash stream.
+    fn chunks(&self, hashes: &ItemHashes<I>) -> Result<Vec<Chunk>> {
+        let header_size = self.header.header_size as usize;
+        let stream_size = self.stream.as_slice().len();
+
+        // the first record always starts right after the header
+        let mut splits = vec![(self.header.minimum_index, header_size)];
+        for &(index, offset) in hashes.index_offsets() {
+            let (last_index, last_position) = splits[splits.len() - 1];
+            let position = header_size + offset as usize;
+
+            if index == last_index && position == last_position {
+                continue;
+            }
+            if index <= last_index
+                || index >= self.header.maximum_index
+                || position <= last_position
+                || position > stream_size
+            {
+                return Err(Error::InvalidFinderIndex(
+                    "hash stream offsets do not match records",
+                ));
+            }
+
+            splits.push((index, position));
+        }
+
+        let mut chunks = Vec::with_capacity(splits.len());
+        for (i, &(start_index, start)) in splits.iter().enumerate() {
+            let (end_index, end) = match splits.get(i + 1) {
+                Some(&next) => next,
+                None => (self.header.maximum_index, stream_size),
+            };
+
+            chunks.push(Chunk {
+                start_index,
+                end_index,
+                start,
+                end,
+            });
+        }
+
+        Ok(chunks)
+    }
+}
+
+/// Scans the records of a chunk and returns the positions that the finder stores for them.
+fn scan<I>(finder: &ItemFinder<'_, I>, chunk: &Chunk) -> Result<Vec<u32>> {
+    let mask = (1 << finder.shift) - 1;
+    let mut positions = Vec::new();
+
+    let mut buf = finder.buffer.clone();
+    buf.take(chunk.start)?;
+
+    for index in chunk.start_index..chunk.end_index {
+        if (index - finder.minimum_index) & mask == 0 {
+            positions.push(buf.pos() as u32);
+        }
+
+        let length = buf.parse_u16()?;
+        buf.take(length as usize)?;
+    }
+
+    // trailing data after the last record is ignored, like when iterating
+    if chunk.end_index != finder.maximum_index && buf.pos() != chunk.end {
+        return Err(Error::InvalidFinderIndex(
+            "hash stream offsets do not match records",
+        ));
+    }
+
+    // the finder also knows the end of the last record
+    if chunk.end_index == finder.maximum_index
+        && (chunk.end_index - finder.minimum_index) & mask == 0
+    {
+        positions.push(buf.pos() as u32);
+    }
+
+    Ok(positions)
+}
@@ -24,6 +24,7 @@ use crate::tpi::data::TypeData;
 
 /// Represents a primitive type like `void` or `char *`.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct PrimitiveType {
     /// The kind of the primitive type.
     pub kind: PrimitiveKind,
@@ -35,13 +36,38 @@ pub struct PrimitiveType {
 /// A simple type.
 #[non_exhaustive]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum PrimitiveKind {
     /// Uncharacterized type (no type)
     NoType,
 
+    /// Absolute symbol
+    Absolute,
+
+    /// Segment type
+    Segment,
+
     /// Void type
     Void,
 
+    /// OLE Automation `CURRENCY`, a 64-bit fixed point number
+    Currency,
+
+    /// Near BASIC string
+    NearBasicString,
+
+    /// Far BASIC string
+    FarBasicString,
+
+    /// Type not translated by cvpack
+    NotTranslated,
+
+    /// Bit
+    Bit,
+
+    /// Pascal `CHAR`
+    PascalChar,
+
     /// Character (byte)
     Char,
 
@@ -60,6 +86,9 @@ pub enum PrimitiveKind {
     /// "Really a 32-bit char"
     RChar32,
 
+    /// 8-bit UTF-8 character (`char8_t`)
+    Char8,
+
     /// Signed 8-bit integer
     I8,
 
@@ -135,9 +164,18 @@ pub enum PrimitiveKind {
     /// 128-bit floating point
     F128,
 
+    /// 16-bit complex number
+    Complex16,
+
     /// 32-bit complex number
     Complex32,
 
+    /// 32-bit partial precision complex number
+    Complex32PP,
+
+    /// 48-bit complex number
+    Complex48,
+
     /// 64-bit complex number
     Complex64,
 
@@ -156,6 +194,9 @@ pub enum PrimitiveKind {
     /// 32-bit boolean value
     Bool32,
 
+    /// 32-bit boolean value with `true` represented as all bits set
+    Bool32FF,
+
     /// 16-bit boolean value
     Bool64,
 
@@ -171,6 +212,7 @@ pub enum PrimitiveKind {
 /// definitions. While `PointerKind` can specify many more pointer types, including relative
 /// pointers, `Indirection` also contains a 128-bit variant.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Indirection {
     /// 16-bit ("near") pointer.
     Near16,
@@ -188,86 +230,544 @@ pub enum Indirection {
     Near128,
 }
 
+/// Describes a kind of primitive type.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PrimitiveKindInfo {
+    /// The lowest octet of the type indices referring to this kind.
+    pub code: u8,
+
+    /// The kind of the primitive type.
+    pub kind: PrimitiveKind,
+
+    /// The name of the type as spelled by Microsoft tools.
+    pub name: &'static str,
+
+    /// The size of a value of this type in bytes, or `None` if values do not have a size.
+    pub size: Option<u64>,
+}
+
+/// All kinds of primitive types, ordered by their code.
+///
+/// Every primitive type index combines one of these codes with an [`Indirection`] mode, see
+/// [`PrimitiveType::from_index`].
+pub const PRIMITIVE_KINDS: &[PrimitiveKindInfo] = &[
+    PrimitiveKindInfo {
+        code: 0x00,
+        kind: PrimitiveKind::NoType,
+        name: "<no type>",
+        size: None,
+    },
+    PrimitiveKindInfo {
+        code: 0x01,
+        kind: PrimitiveKind::Absolute,
+        name: "<absolute>",
+        size: None,
+    },
+    PrimitiveKindInfo {
+        code: 0x02,
+        kind: PrimitiveKind::Segment,
+        name: "<segment>",
+        size: Some(2),
+    },
+    PrimitiveKindInfo {
+        code: 0x03,
+        kind: PrimitiveKind::Void,
+        name: "void",
+        size: None,
+    },
+    PrimitiveKindInfo {
+        code: 0x04,
+        kind: PrimitiveKind::Currency,
+        name: "CURRENCY",
+        size: Some(8),
+    },
+    PrimitiveKindInfo {
+        code: 0x05,
+        kind: PrimitiveKind::NearBasicString,
+        name: "<near BASIC string>",
+        size: None,
+    },
+    PrimitiveKindInfo {
+        code: 0x06,
+        kind: PrimitiveKind::FarBasicString,
+        name: "<far BASIC string>",
+        size: None,
+    },
+    PrimitiveKindInfo {
+        code: 0x07,
+        kind: PrimitiveKind::NotTranslated,
+        name: "<not translated>",
+        size: None,
+    },
+    PrimitiveKindInfo {
+        code: 0x08,
+        kind: PrimitiveKind::HRESULT,
+        name: "HRESULT",
+        size: Some(4),
+    },
+    PrimitiveKindInfo {
+        code: 0x10,
+        kind: PrimitiveKind::Char,
+        name: "signed char",
+        size: Some(1),
+    },
+    PrimitiveKindInfo {
+        code: 0x11,
+        kind: PrimitiveKind::Short,
+        name: "short",
+        size: Some(2),
+    },
+    PrimitiveKindInfo {
+        code: 0x12,
+        kind: PrimitiveKind::Long,
+        name: "long",
+        size: Some(4),
+    },
+    PrimitiveKindInfo {
+        code: 0x13,
+        kind: PrimitiveKind::Quad,
+        name: "__int64",
+        size: Some(8),
+    },
+    PrimitiveKindInfo {
+        code: 0x14,
+        kind: PrimitiveKind::Octa,
+        name: "__int128",
+        size: Some(16),
+    },
+    PrimitiveKindInfo {
+        code: 0x20,
+        kind: PrimitiveKind::UChar,
+        name: "unsigned char",
+        size: Some(1),
+    },
+    PrimitiveKindInfo {
+        code: 0x21,
+        kind: PrimitiveKind::UShort,
+        name: "unsigned short",
+        size: Some(2),
+    },
+    PrimitiveKindInfo {
+        code: 0x22,
+        kind: PrimitiveKind::ULong,
+        name: "unsigned long",
+        size: Some(4),
+    },
+    PrimitiveKindInfo {
+        code: 0x23,
+        kind: PrimitiveKind::UQuad,
+        name: "unsigned __int64",
+        size: Some(8),
+    },
+    PrimitiveKindInfo {
+        code: 0x24,
+        kind: PrimitiveKind::UOcta,
+        name: "unsigned __int128",
+        size: Some(16),
+    },
+    PrimitiveKindInfo {
+        code: 0x30,
+        kind: PrimitiveKind::Bool8,
+        name: "bool",
+        size: Some(1),
+    },
+    PrimitiveKindInfo {
+        code: 0x31,
+        kind: PrimitiveKind::Bool16,
+        name: "__bool16",
+        size: Some(2),
+    },
+    PrimitiveKindInfo {
+        code: 0x32,
+        kind: PrimitiveKind::Bool32,
+        name: "__bool32",
+        size: Some(4),
+    },
+    PrimitiveKindInfo {
+        code: 0x33,
+        kind: PrimitiveKind::Bool64,
+        name: "__bool64",
+        size: Some(8),
+    },
+    PrimitiveKindInfo {
+        code: 0x40,
+        kind: PrimitiveKind::F32,
+        name: "float",
+        size: Some(4),
+    },
+    PrimitiveKindInfo {
+        code: 0x41,
+        kind: PrimitiveKind::F64,
+        name: "double",
+        size: Some(8),
+    },
+    PrimitiveKindInfo {
+        code: 0x42,
+        kind: PrimitiveKind::F80,
+        name: "__float80",
+        size: Some(10),
+    },
+    PrimitiveKindInfo {
+        code: 0x43,
+        kind: PrimitiveKind::F128,
+        name: "__float128",
+        size: Some(16),
+    },
+    PrimitiveKindInfo {
+        code: 0x44,
+        kind: PrimitiveKind::F48,
+        name: "__float48",
+        size: Some(6),
+    },
+    PrimitiveKindInfo {
+        code: 0x45,
+        kind: PrimitiveKind::F32PP,
+        name: "float",
+        size: Some(4),
+    },
+    PrimitiveKindInfo {
+        code: 0x46,
+        kind: PrimitiveKind::F16,
+        name: "__half",
+        size: Some(2),
+    },
+    PrimitiveKindInfo {
+        code: 0x50,
+        kind: PrimitiveKind::Complex32,
+        name: "_Complex float",
+        size: Some(8),
+    },
+    PrimitiveKindInfo {
+        code: 0x51,
+        kind: PrimitiveKind::Complex64,
+        name: "_Complex double",
+        size: Some(16),
+    },
+    PrimitiveKindInfo {
+        code: 0x52,
+        kind: PrimitiveKind::Complex80,
+        name: "_Complex __float80",
+        size: Some(20),
+    },
+    PrimitiveKindInfo {
+        code: 0x53,
+        kind: PrimitiveKind::Complex128,
+        name: "_Complex __float128",
+        size: Some(32),
+    },
+    PrimitiveKindInfo {
+        code: 0x54,
+        kind: PrimitiveKind::Complex48,
+        name: "_Complex __float48",
+        size: Some(12),
+    },
+    PrimitiveKindInfo {
+        code: 0x55,
+        kind: PrimitiveKind::Complex32PP,
+        name: "_Complex float",
+        size: Some(8),
+    },
+    PrimitiveKindInfo {
+        code: 0x56,
+        kind: PrimitiveKind::Complex16,
+        name: "_Complex __half",
+        size: Some(4),
+    },
+    PrimitiveKindInfo {
+        code: 0x60,
+        kind: PrimitiveKind::Bit,
+        name: "__bit",
+        size: None,
+    },
+    PrimitiveKindInfo {
+        code: 0x61,
+        kind: PrimitiveKind::PascalChar,
+        name: "__pascal_char",
+        size: Some(1),
+    },
+    PrimitiveKindInfo {
+        code: 0x62,
+        kind: PrimitiveKind::Bool32FF,
+        name: "__bool32",
+        size: Some(4),
+    },
+    PrimitiveKindInfo {
+        code: 0x68,
+        kind: PrimitiveKind::I8,
+        name: "__int8",
+        size: Some(1),
+    },
+    PrimitiveKindInfo {
+        code: 0x69,
+        kind: PrimitiveKind::U8,
+        name: "unsigned __int8",
+        size: Some(1),
+    },
+    PrimitiveKindInfo {
+        code: 0x70,
+        kind: PrimitiveKind::RChar,
+        name: "char",
+        size: Some(1),
+    },
+    PrimitiveKindInfo {
+        code: 0x71,
+        kind: PrimitiveKind::WChar,
+        name: "wchar_t",
+        size: Some(2),
+    },
+    PrimitiveKindInfo {
+        code: 0x72,
+        kind: PrimitiveKind::I16,
+        name: "__int16",
+        size: Some(2),
+    },
+    PrimitiveKindInfo {
+        code: 0x73,
+        kind: PrimitiveKind::U16,
+        name: "unsigned __int16",
+        size: Some(2),
+    },
+    PrimitiveKindInfo {
+        code: 0x74,
+        kind: PrimitiveKind::I32,
+        name: "int",
+        size: Some(4),
+    },
+    PrimitiveKindInfo {
+        code: 0x75,
+        kind: PrimitiveKind::U32,
+        name: "unsigned int",
+        size: Some(4),
+    },
+    PrimitiveKindInfo {
+        code: 0x76,
+        kind: PrimitiveKind::I64,
+        name: "__int64",
+        size: Some(8),
+    },
+    PrimitiveKindInfo {
+        code: 0x77,
+        kind: PrimitiveKind::U64,
+        name: "unsigned __int64",
+        size: Some(8),
+    },
+    PrimitiveKindInfo {
+        code: 0x78,
+        kind: PrimitiveKind::I128,
+        name: "__int128",
+        size: Some(16),
+    },
+    PrimitiveKindInfo {
+        code: 0x79,
+        kind: PrimitiveKind::U128,
+        name: "unsigned __int128",
+        size: Some(16),
+    },
+    PrimitiveKindInfo {
+        code: 0x7a,
+        kind: PrimitiveKind::RChar16,
+        name: "char16_t",
+        size: Some(2),
+    },
+    PrimitiveKindInfo {
+        code: 0x7b,
+        kind: PrimitiveKind::RChar32,
+        name: "char32_t",
+        size: Some(4),
+    },
+    PrimitiveKindInfo {
+        code: 0x7c,
+        kind: PrimitiveKind::Char8,
+        name: "char8_t",
+        size: Some(1),
+    },
+];
+
+impl PrimitiveKind {
+    /// Returns the entry of this kind in [`PRIMITIVE_KINDS`].
+    pub fn info(self) -> &'static PrimitiveKindInfo {
+        PRIMITIVE_KINDS
+            .iter()
+            .find(|info| info.kind == self)
+            .expect("all primitive kinds are listed")
+    }
+
+    /// Returns the name of this kind, such as `unsigned __int64`.
+    pub fn name(self) -> &'static str {
+        self.info().name
+    }
+
+    /// Returns the size of a value of this kind in bytes.
+    ///
+    /// Returns `None` for `void` and other kinds without a size.
+    pub fn size(self) -> Option<u64> {
+        self.info().size
+    }
+}
+
+impl Indirection {
+    /// All pointer modes, ordered by their mode bits.
+    const ALL: [Indirection; 7] = [
+        Indirection::Near16,
+        Indirection::Far16,
+        Indirection::Huge16,
+        Indirection::Near32,
+        Indirection::Far32,
+        Indirection::Near64,
+        Indirection::Near128,
+    ];
+
+    /// Returns the mode bits of this pointer mode in a primitive type index.
+    fn mode(self) -> u32 {
+        match self {
+            Indirection::Near16 => 0x100,
+            Indirection::Far16 => 0x200,
+            Indirection::Huge16 => 0x300,
+            Indirection::Near32 => 0x400,
+            Indirection::Far32 => 0x500,
+            Indirection::Near64 => 0x600,
+            Indirection::Near128 => 0x700,
+        }
+    }
+
+    /// Returns the size of a pointer with this mode in bytes.
+    pub fn size(self) -> u64 {
+        match self {
+            Indirection::Near16 => 2,
+            Indirection::Far16 | Indirection::Huge16 | Indirection::Near32 => 4,
+            Indirection::Far32 => 6,
+            Indirection::Near64 => 8,
+            Indirection::Near128 => 16,
+        }
+    }
+}
+
+impl PrimitiveType {
+    /// Decodes a primitive type index.
+    ///
+    /// Returns `None` if the index does not refer to a primitive type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use pdb::{Indirection, PrimitiveKind, PrimitiveType, TypeIndex};
+    ///
+    /// let primitive = PrimitiveType::from_index(TypeIndex(0x0678)).unwrap();
+    /// assert_eq!(primitive.kind, PrimitiveKind::I128);
+    /// assert_eq!(primitive.indirection, Some(Indirection::Near64));
+    /// assert_eq!(primitive.name(), "__int128*");
+    /// assert_eq!(primitive.size(), Some(8));
+    /// ```
+    pub fn from_index(index: TypeIndex) -> Option<Self> {
+        if index.0 >= 0x1000 {
+            return None;
+        }
+
+        let indirection = match index.0 & 0xf00 {
+            0x000 => None,
+            mode => Some(*Indirection::ALL.iter().find(|i| i.mode() == mode)?),
+        };
+
+        // primitive types are stored in the lowest octet
+        let code = (index.0 & 0xff) as u8;
+        let kind = PRIMITIVE_KINDS.iter().find(|info| info.code == code)?.kind;
+
+        Some(PrimitiveType { kind, indirection })
+    }
+
+    /// Returns the type index of this primitive type.
+    pub fn index(self) -> TypeIndex {
+        let mode = self.indirection.map_or(0, Indirection::mode);
+        TypeIndex(mode | u32::from(self.kind.info().code))
+    }
+
+    /// Returns the name of this primitive type, such as `wchar_t` or `char far*`.
+    pub fn name(self) -> String {
+        let name = self.kind.name();
+        match self.indirection {
+            None => name.to_string(),
+            Some(Indirection::Near16) => format!("{} near*", name),
+            Some(Indirection::Far16) | Some(Indirection::Far32) => format!("{} far*", name),
+            Some(Indirection::Huge16) => format!("{} huge*", name),
+            Some(_) => format!("{}*", name),
+        }
+    }
+
+    /// Returns the size of this primitive type in bytes.
+    ///
+    /// Pointers have the size of their pointer mode. Returns `None` for `void` and other kinds
+    /// without a size.
+    pub fn size(self) -> Option<u64> {
+        match self.indirection {
+            Some(indirection) => Some(indirection.size()),
+            None => self.kind.size(),
+        }
+    }
+
+    /// Returns all primitive types, ordered by their type index.
+    pub fn all() -> impl Iterator<Item = PrimitiveType> {
+        let modes = std::iter::once(None).chain(Indirection::ALL.iter().copied().map(Some));
+        modes.flat_map(|indirection| {
+            PRIMITIVE_KINDS.iter().map(move |info| PrimitiveType {
+                kind: info.kind,
+                indirection,
+            })
+        })
+    }
+}
+
 pub fn type_data_for_primitive(index: TypeIndex) -> Result<TypeData<'static>> {
     // https://github.com/Microsoft/microsoft-pdb/blob/082c5290e5aff028ae84e43affa8be717aa7af73/include/cvinfo.h#L326-L750
 
     // primitives live under 0x1000, and we should never reach here for non-primitive indexes
     assert!(index < TypeIndex(0x1000));
 
-    // indirection is stored in these bits
-    let indirection = match index.0 & 0xf00 {
-        0x000 => None,
-        0x100 => Some(Indirection::Near16),
-        0x200 => Some(Indirection::Far16),
-        0x300 => Some(Indirection::Huge16),
-        0x400 => Some(Indirection::Near32),
-        0x500 => Some(Indirection::Far32),
-        0x600 => Some(Indirection::Near64),
-        0x700 => Some(Indirection::Near128),
-        _ => {
-            return Err(Error::TypeNotFound(index.0));
+    match PrimitiveType::from_index(index) {
+        Some(primitive) => Ok(TypeData::Primitive(primitive)),
+        None => Err(Error::TypeNotFound(index.0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut count = 0;
+        for primitive in PrimitiveType::all() {
+            assert_eq!(
+                PrimitiveType::from_index(primitive.index()),
+                Some(primitive)
+            );
+            count += 1;
         }
-    };
-
-    // primitive types are stored in the lowest octet
-    let kind = match index.0 & 0xff {
-        0x00 => PrimitiveKind::NoType,
-
-        0x03 => PrimitiveKind::Void,
-        0x08 => PrimitiveKind::HRESULT,
-
-        0x10 => PrimitiveKind::Char,
-        0x20 => PrimitiveKind::UChar,
-        0x68 => PrimitiveKind::I8,
-        0x69 => PrimitiveKind::U8,
-
-        0x70 => PrimitiveKind::RChar,
-        0x71 => PrimitiveKind::WChar,
-        0x7a => PrimitiveKind::RChar16,
-        0x7b => PrimitiveKind::RChar32,
-
-        0x11 => PrimitiveKind::Short,
-        0x21 => PrimitiveKind::UShort,
-        0x72 => PrimitiveKind::I16,
-        0x73 => PrimitiveKind::U16,
-
-        0x12 => PrimitiveKind::Long,
-        0x22 => PrimitiveKind::ULong,
-        0x74 => PrimitiveKind::I32,
-        0x75 => PrimitiveKind::U32,
-
-        0x13 => PrimitiveKind::Quad,
-        0x23 => PrimitiveKind::UQuad,
-        0x76 => PrimitiveKind::I64,
-        0x77 => PrimitiveKind::U64,
-
-        0x14 => PrimitiveKind::Octa,
-        0x24 => PrimitiveKind::UOcta,
-        0x78 => PrimitiveKind::I128,
-        0x79 => PrimitiveKind::U128,
-
-        0x46 => PrimitiveKind::F16,
-        0x40 => PrimitiveKind::F32,
-        0x45 => PrimitiveKind::F32PP,
-        0x44 => PrimitiveKind::F48,
-        0x41 => PrimitiveKind::F64,
-        0x42 => PrimitiveKind::F80,
-        0x43 => PrimitiveKind::F128,
-
-        0x50 => PrimitiveKind::Complex32,
-        0x51 => PrimitiveKind::Complex64,
-        0x52 => PrimitiveKind::Complex80,
-        0x53 => PrimitiveKind::Complex128,
-
-        0x30 => PrimitiveKind::Bool8,
-        0x31 => PrimitiveKind::Bool16,
-        0x32 => PrimitiveKind::Bool32,
-        0x33 => PrimitiveKind::Bool64,
-
-        _ => {
-            return Err(Error::TypeNotFound(index.0));
+        assert_eq!(count, 8 * PRIMITIVE_KINDS.len());
+    }
+
+    #[test]
+    fn test_codes_unique() {
+        for (i, info) in PRIMITIVE_KINDS.iter().enumerate() {
+            assert_eq!(info.kind.info(), info);
+            assert!(PRIMITIVE_KINDS[..i]
+                .iter()
+                .all(|other| other.code < info.code));
         }
-    };
-
-    Ok(TypeData::Primitive(PrimitiveType { kind, indirection }))
+    }
+
+    #[test]
+    fn test_unknown_codes() {
+        assert_eq!(PrimitiveType::from_index(TypeIndex(0x0009)), None);
+        assert_eq!(PrimitiveType::from_index(TypeIndex(0x0874)), None);
+        assert_eq!(PrimitiveType::from_index(TypeIndex(0x1074)), None);
+    }
+
+    #[test]
+    fn test_names() {
+        let name = |index| PrimitiveType::from_index(TypeIndex(index)).unwrap().name();
+        assert_eq!(name(0x0071), "wchar_t");
+        assert_eq!(name(0x0408), "HRESULT*");
+        assert_eq!(name(0x0214), "__int128 far*");
+        assert_eq!(name(0x0603), "void*");
+    }
 }
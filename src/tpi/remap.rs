@@ -0,0 +1,324 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::common::*;
+use crate::tpi::constants::*;
+use crate::tpi::data::{parse_padding, parse_type_data, OemIdentifier, TypeData};
+use crate::tpi::Type;
+
+/// A table translating the type indices of one type stream into the type indices of another.
+///
+/// Merging type streams or removing unused types assigns new indices to the remaining types. All
+/// records referring to these types then have to be rewritten, which is what this table is used
+/// for. Indices below the minimum index of the source stream refer to primitive types and always
+/// map to themselves.
+///
+/// # Example
+///
+/// ```
+/// # use pdb::FallibleIterator;
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(file)?;
+/// let type_information = pdb.type_information()?;
+///
+/// // move all types up by 0x100 indices
+/// let mut map = pdb::TypeIndexMap::new(pdb::TypeIndex(0x1000));
+/// let mut iter = type_information.iter();
+/// while let Some(typ) = iter.next()? {
+///     map.insert(typ.index(), pdb::TypeIndex(typ.index().0 + 0x100));
+/// }
+///
+/// let mut iter = type_information.iter();
+/// while let Some(typ) = iter.next()? {
+///     let record = map.remap_type(&typ)?;
+///     assert_eq!(record.len(), typ.len());
+/// }
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypeIndexMap {
+    minimum_index: u32,
+    indices: Vec<Option<TypeIndex>>,
+}
+
+impl TypeIndexMap {
+    /// Creates an empty map for a type stream starting at `minimum_index`.
+    pub fn new(minimum_index: TypeIndex) -> Self {
+        TypeIndexMap {
+            minimum_index: minimum_index.0,
+            indices: Vec::new(),
+        }
+    }
+
+    /// Maps the type index `source` to `target`, replacing any previous mapping.
+    ///
+    /// Indices below the minimum index are ignored, since they always map to themselves.
+    pub fn insert(&mut self, source: TypeIndex, target: TypeIndex) {
+        let slot = match source.0.checked_sub(self.minimum_index) {
+            Some(slot) => slot as usize,
+            None => return,
+        };
+
+        if slot >= self.indices.len() {
+            self.indices.resize(slot + 1, None);
+        }
+
+        self.indices[slot] = Some(target);
+    }
+
+    /// Returns the index that `index` maps to, or `None` if it has not been mapped.
+    pub fn get(&self, index: TypeIndex) -> Option<TypeIndex> {
+        match index.0.checked_sub(self.minimum_index) {
+            Some(slot) => self.indices.get(slot as usize).copied().flatten(),
+            None => Some(index),
+        }
+    }
+
+    /// Returns the index that `index` maps to.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnmappedTypeIndex(index)` if the index has not been mapped
+    pub fn remap(&self, index: TypeIndex) -> Result<TypeIndex> {
+        self.get(index).ok_or(Error::UnmappedTypeIndex(index.0))
+    }
+
+    /// Rewrites all type indices referenced by a raw type record.
+    ///
+    /// The record starts with its kind and does not include the length prefix. The returned record
+    /// has the same length and differs only in the referenced type indices.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnmappedTypeIndex(index)` if a referenced index has not been mapped
+    /// * `Error::UnimplementedTypeKind(kind)` if the record is not understood by this library
+    /// * `Error::UnexpectedEof` if the record is truncated
+    pub fn remap_record(&self, record: &[u8]) -> Result<Vec<u8>> {
+        let mut remapped = record.to_vec();
+
+        for offset in type_index_offsets(record)? {
+            let bytes = &mut remapped[offset..offset + 4];
+            let index = TypeIndex(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+            bytes.copy_from_slice(&self.remap(index)?.0.to_le_bytes());
+        }
+
+        Ok(remapped)
+    }
+
+    /// Rewrites all type indices referenced by a type, see [`remap_record`](Self::remap_record).
+    pub fn remap_type(&self, typ: &Type<'_>) -> Result<Vec<u8>> {
+        self.remap_record(typ.data)
+    }
+
+    /// Rewrites all type indices referenced by parsed type data in place.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnmappedTypeIndex(index)` if a referenced index has not been mapped. The data is
+    ///   left unchanged in this case.
+    pub fn remap_type_data(&self, data: &mut TypeData<'_>) -> Result<()> {
+        if let Some(index) = data
+            .referenced_types()
+            .into_iter()
+            .find(|&index| self.get(index).is_none())
+        {
+            return Err(Error::UnmappedTypeIndex(index.0));
+        }
+
+        data.visit_type_indices_mut(&mut |index| {
+            if let Some(target) = self.get(*index) {
+                *index = target;
+            }
+        });
+
+        Ok(())
+    }
+}
+
+/// Returns the byte offsets of all type indices referenced by a raw type record.
+///
+/// The offsets are in the order of [`TypeData::referenced_types`].
+pub(crate) fn type_index_offsets(record: &[u8]) -> Result<Vec<usize>> {
+    let mut buf = ParseBuffer::from(record);
+
+    if buf.peek_u16()? != LF_FIELDLIST {
+        let data = parse_type_data(&mut buf)?;
+        return Ok(record_offsets(&data));
+    }
+
+    let mut offsets = Vec::new();
+    let mut continuation = None;
+
+    buf.parse_u16()?;
+    while !buf.is_empty() {
+        let start = buf.pos();
+
+        if buf.peek_u16()? == LF_INDEX {
+            buf.parse_u16()?;
+            buf.parse::<TypeIndex>()?;
+            continuation = Some(start + 2);
+        } else {
+            let field = parse_type_data(&mut buf)?;
+            offsets.extend(record_offsets(&field).into_iter().map(|o| start + o));
+        }
+
+        parse_padding(&mut buf)?;
+    }
+
+    offsets.extend(continuation);
+    Ok(offsets)
+}
+
+/// Returns the offsets of the type indices in a record other than a field list.
+fn record_offsets(data: &TypeData<'_>) -> Vec<usize> {
+    // all offsets include the two bytes of the record kind
+    match data {
+        TypeData::Class(data) => [
+            (data.fields, 6),
+            (data.derived_from, 10),
+            (data.vtable_shape, 14),
+        ]
+        .iter()
+        .filter_map(|&(index, offset)| index.map(|_| offset))
+        .collect(),
+        TypeData::Member(_)
+        | TypeData::StaticMember(_)
+        | TypeData::Nested(_)
+        | TypeData::MemberModify(_)
+        | TypeData::Method(_)
+        | TypeData::OverloadedMethod(_)
+        | TypeData::BaseClass(_)
+        | TypeData::VirtualFunctionTablePointer(_)
+        | TypeData::FriendFunction(_)
+        | TypeData::FriendClass(_) => vec![4],
+        TypeData::VirtualBaseClass(_) => vec![4, 8],
+        TypeData::MemberFunction(data) => match data.this_pointer_type {
+            Some(_) => vec![2, 6, 10, 18],
+            None => vec![2, 6, 18],
+        },
+        TypeData::Procedure(data) => match data.return_type {
+            Some(_) => vec![2, 10],
+            None => vec![10],
+        },
+        TypeData::Pointer(data) => match data.containing_class {
+            Some(_) => vec![2, 10],
+            None => vec![2],
+        },
+        TypeData::Modifier(_) | TypeData::Alias(_) | TypeData::Bitfield(_) => vec![2],
+        TypeData::Enumeration(_) => vec![6, 10],
+        TypeData::Array(_) | TypeData::DimensionedArray(_) => vec![2, 6],
+        TypeData::Union(_) => vec![6],
+        TypeData::VirtualFunctionTable(data) => match data.base_table {
+            Some(_) => vec![2, 6],
+            None => vec![2],
+        },
+        TypeData::ArgumentList(data) => (0..data.arguments.len()).map(|i| 6 + 4 * i).collect(),
+        TypeData::VirtualFunctionTablePath(data) => {
+            (0..data.bases.len()).map(|i| 6 + 4 * i).collect()
+        }
+        TypeData::Oem(data) => {
+            let start = match data.identifier {
+                OemIdentifier::Id { .. } => 10,
+                OemIdentifier::Guid(_) => 22,
+            };
+            (0..data.indices.len()).map(|i| start + 4 * i).collect()
+        }
+        TypeData::MethodList(data) => {
+            let mut offset = 2;
+            let mut offsets = Vec::with_capacity(data.methods.len());
+            for method in &data.methods {
+                // attributes and padding precede the method type
+                offsets.push(offset + 4);
+                offset += 8 + method.vtable_offset.map_or(0, |_| 4);
+            }
+            offsets
+        }
+        TypeData::FieldList(_)
+        | TypeData::Primitive(_)
+        | TypeData::Enumerate(_)
+        | TypeData::VirtualTableShape(_)
+        | TypeData::Label(_)
+        | TypeData::Managed(_)
+        | TypeData::Precompiled(_)
+        | TypeData::EndPrecompiled(_)
+        | TypeData::TypeServer(_) => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shifted(delta: u32) -> TypeIndexMap {
+        let mut map = TypeIndexMap::new(TypeIndex(0x1000));
+        for index in 0x1000..0x1100 {
+            map.insert(TypeIndex(index), TypeIndex(index + delta));
+        }
+        map
+    }
+
+    #[test]
+    fn test_get() {
+        let map = shifted(0x10);
+        assert_eq!(map.get(TypeIndex(0x74)), Some(TypeIndex(0x74)));
+        assert_eq!(map.get(TypeIndex(0x1000)), Some(TypeIndex(0x1010)));
+        assert_eq!(map.get(TypeIndex(0x2000)), None);
+        assert!(matches!(
+            map.remap(TypeIndex(0x2000)),
+            Err(Error::UnmappedTypeIndex(0x2000))
+        ));
+    }
+
+    #[test]
+    fn test_remap_pointer_to_member() {
+        // LF_POINTER to 0x1001, pointer to data member of class 0x1002
+        let record = [
+            0x02, 0x10, 0x01, 0x10, 0x00, 0x00, 0x4c, 0x00, 0x01, 0x00, 0x02, 0x10, 0x00, 0x00,
+            0x01, 0x00,
+        ];
+        let remapped = shifted(0x20).remap_record(&record).expect("remap");
+
+        let data = parse_type_data(&mut ParseBuffer::from(&remapped[..])).expect("parse");
+        assert_eq!(
+            data.referenced_types(),
+            [TypeIndex(0x1021), TypeIndex(0x1022)]
+        );
+    }
+
+    #[test]
+    fn test_remap_field_list() {
+        let record = [
+            0x03, 0x12, // LF_FIELDLIST
+            0x0d, 0x15, 0x03, 0x00, 0x74, 0x00, 0x00, 0x00, 0x00, 0x00, 0x61, 0x00, // int a
+            0x0d, 0x15, 0x03, 0x00, 0x05, 0x10, 0x00, 0x00, 0x04, 0x00, 0x62, 0x00, // b
+            0x04, 0x14, 0x07, 0x10, 0x00, 0x00, // LF_INDEX
+        ];
+        let remapped = shifted(0x20).remap_record(&record).expect("remap");
+
+        let data = parse_type_data(&mut ParseBuffer::from(&remapped[..])).expect("parse");
+        assert_eq!(
+            data.referenced_types(),
+            [TypeIndex(0x74), TypeIndex(0x1025), TypeIndex(0x1027)]
+        );
+    }
+
+    #[test]
+    fn test_remap_type_data() {
+        let record = [0x01, 0x10, 0x05, 0x10, 0x00, 0x00, 0x01, 0x00];
+        let mut data = parse_type_data(&mut ParseBuffer::from(&record[..])).expect("parse");
+
+        assert!(TypeIndexMap::new(TypeIndex(0x1000))
+            .remap_type_data(&mut data)
+            .is_err());
+
+        shifted(1).remap_type_data(&mut data).expect("remap");
+        assert_eq!(data.referenced_types(), [TypeIndex(0x1006)]);
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::common::*;
+use crate::tpi::data::TypeData;
+use crate::tpi::TypeInformation;
+#[cfg(feature = "regex")]
+use crate::tpi::TypeIter;
+use crate::FallibleIterator;
+
+/// Searches for classes, structures, unions and enumerations whose name matches `pattern`.
+pub(crate) fn search(info: &TypeInformation<'_>, pattern: &str) -> Result<Vec<TypeIndex>> {
+    let pattern = pattern.as_bytes();
+    let mut matches = Vec::new();
+
+    let mut iter = info.iter();
+    while let Some(item) = iter.next()? {
+        let name = match item.parse() {
+            Ok(TypeData::Class(c)) => c.name,
+            Ok(TypeData::Union(u)) => u.name,
+            Ok(TypeData::Enumeration(e)) => e.name,
+            _ => continue,
+        };
+
+        if wildcard_match(pattern, name.as_bytes()) {
+            matches.push(item.index());
+        }
+    }
+
+    Ok(matches)
+}
+
+/// An iterator over the classes, structures, unions and enumerations whose name matches a regular
+/// expression, as returned by [`TypeInformation::search_regex`].
+///
+/// Items are the index and the name of each matching type, in the order of the type stream.
+#[cfg(feature = "regex")]
+#[derive(Debug)]
+pub struct RegexSearch<'t, 'r> {
+    iter: TypeIter<'t>,
+    regex: &'r regex::bytes::Regex,
+}
+
+#[cfg(feature = "regex")]
+impl<'t> FallibleIterator for RegexSearch<'t, '_> {
+    type Item = (TypeIndex, RawString<'t>);
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        while let Some(item) = self.iter.next()? {
+            let name = match item.parse() {
+                Ok(TypeData::Class(c)) => c.name,
+                Ok(TypeData::Union(u)) => u.name,
+                Ok(TypeData::Enumeration(e)) => e.name,
+                _ => continue,
+            };
+
+            if self.regex.is_match(name.as_bytes()) {
+                return Ok(Some((item.index(), name)));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(feature = "regex")]
+impl<'s> TypeInformation<'s> {
+    /// Finds all classes, structures, unions and enumerations with a name matching `regex`.
+    ///
+    /// Unlike [`search`](Self::search), the regular expression matches anywhere in the name unless
+    /// it is anchored with `^` and `$`. Names are matched as bytes, so that names that are not
+    /// valid UTF-8 can be found as well. Matches are produced while iterating over the type
+    /// stream, without collecting the names of all types first. Forward references are included.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// let regex = regex::bytes::Regex::new(r"^std::.*_traits<").unwrap();
+    /// let mut matches = type_information.search_regex(&regex);
+    /// while let Some((index, name)) = matches.next()? {
+    ///     println!("{}: {}", index, name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn search_regex<'r>(&self, regex: &'r regex::bytes::Regex) -> RegexSearch<'_, 'r> {
+        RegexSearch {
+            iter: self.iter(),
+            regex,
+        }
+    }
+}
+
+/// Matches `name` against a pattern where `*` matches any sequence of bytes and `?` matches a
+/// single byte. All other bytes match literally.
+fn wildcard_match(pattern: &[u8], name: &[u8]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // position of the last `*` in the pattern and the name position it was tried at
+    let mut backtrack = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, start)) => {
+                    p = star + 1;
+                    n = start + 1;
+                    backtrack = Some((star, start + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::wildcard_match;
+
+    #[test]
+    fn test_exact() {
+        assert!(wildcard_match(b"Foo", b"Foo"));
+        assert!(!wildcard_match(b"Foo", b"Foobar"));
+        assert!(!wildcard_match(b"Foobar", b"Foo"));
+        assert!(wildcard_match(b"", b""));
+    }
+
+    #[test]
+    fn test_wildcards() {
+        assert!(wildcard_match(b"std::vector<*", b"std::vector<int>"));
+        assert!(!wildcard_match(b"std::vector<*", b"std::list<int>"));
+        assert!(wildcard_match(b"*::iterator", b"std::list<int>::iterator"));
+        assert!(wildcard_match(b"*", b""));
+        assert!(wildcard_match(b"a*b*c", b"aXbYbZc"));
+        assert!(!wildcard_match(b"a*b*c", b"aXbYbZ"));
+        assert!(wildcard_match(b"_?obuf", b"_iobuf"));
+        assert!(!wildcard_match(b"?", b""));
+    }
+}
@@ -0,0 +1,444 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::fmt;
+
+use crate::common::*;
+use crate::tpi::data::{ArgumentList, FunctionAttributes, PointerMode, TypeData};
+use crate::tpi::primitive::{PrimitiveKind, PrimitiveType};
+use crate::tpi::size::{type_size, MAX_DEPTH};
+use crate::tpi::TypeFinder;
+
+/// The signature of a procedure or member function type, as returned by
+/// [`TypeFinder::signature`].
+///
+/// All types are rendered as C++ type names using [`TypeFinder::type_name`]. The `Display`
+/// implementation renders the function type without a name, such as `int __cdecl(char*, int)`;
+/// use [`declaration`](Self::declaration) to render the signature of a named function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionSignature {
+    /// The return type of the function.
+    pub return_type: String,
+    /// The calling convention, such as `__cdecl` or `__thiscall`, if it is known.
+    pub calling_convention: Option<&'static str>,
+    /// The class declaring the function, for member functions.
+    pub class: Option<String>,
+    /// The type of the `this` pointer, for non-static member functions.
+    pub this_type: Option<String>,
+    /// The adjustment applied to the `this` pointer before calling the function, in bytes.
+    ///
+    /// This is non-zero for functions overriding a virtual function of a base class that is not
+    /// located at the start of the derived class.
+    pub this_adjustment: u32,
+    /// The types of the declared parameters, in declaration order.
+    pub parameters: Vec<String>,
+    /// Whether the function accepts additional arguments (`...`).
+    pub variadic: bool,
+    /// Whether the `this` pointer of a member function is `const`.
+    pub constant: bool,
+    /// Whether the `this` pointer of a member function is `volatile`.
+    pub volatile: bool,
+}
+
+impl FunctionSignature {
+    /// Returns whether this is a static member function.
+    pub fn is_static(&self) -> bool {
+        self.class.is_some() && self.this_type.is_none()
+    }
+
+    /// Renders the declaration of a function with this signature and the given name.
+    ///
+    /// The name is qualified with the declaring class of member functions, and a non-zero `this`
+    /// adjustment is appended as a comment:
+    ///
+    /// ```text
+    /// void __cdecl Derived::f(int) const /* this adjustment 8 */
+    /// ```
+    pub fn declaration(&self, name: &str) -> String {
+        let mut declaration = String::new();
+
+        if self.is_static() {
+            declaration.push_str("static ");
+        }
+
+        declaration.push_str(&self.return_type);
+        declaration.push(' ');
+
+        if let Some(calling_convention) = self.calling_convention {
+            declaration.push_str(calling_convention);
+            declaration.push(' ');
+        }
+
+        if let Some(ref class) = self.class {
+            declaration.push_str(class);
+            declaration.push_str("::");
+        }
+
+        declaration.push_str(name);
+        declaration.push_str(&parameter_list(self.parameters.clone(), self.variadic));
+        declaration.push_str(self.qualifiers());
+
+        if self.this_adjustment != 0 {
+            declaration.push_str(&format!(" /* this adjustment {} */", self.this_adjustment));
+        }
+
+        declaration
+    }
+
+    fn qualifiers(&self) -> &'static str {
+        cv_qualifiers(self.constant, self.volatile)
+    }
+}
+
+impl fmt::Display for FunctionSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.return_type)?;
+        if let Some(calling_convention) = self.calling_convention {
+            write!(f, " {}", calling_convention)?;
+        }
+        write!(
+            f,
+            "{}{}",
+            parameter_list(self.parameters.clone(), self.variadic),
+            self.qualifiers()
+        )
+    }
+}
+
+/// Returns the keyword of a calling convention.
+fn calling_convention_name(attributes: FunctionAttributes) -> Option<&'static str> {
+    attributes.calling_convention()?.keyword()
+}
+
+/// Appends a declarator to a type name, such as `*` to `int`.
+fn join(base: String, declarator: &str) -> String {
+    match declarator.chars().next() {
+        None => base,
+        Some('*') | Some('&') | Some('[') => base + declarator,
+        Some(_) => format!("{} {}", base, declarator),
+    }
+}
+
+/// Returns `const`, `volatile` or both, with a leading space.
+fn cv_qualifiers(constant: bool, volatile: bool) -> &'static str {
+    match (constant, volatile) {
+        (false, false) => "",
+        (true, false) => " const",
+        (false, true) => " volatile",
+        (true, true) => " const volatile",
+    }
+}
+
+impl<'t> TypeFinder<'t> {
+    /// Returns the C++ name of a type, such as `const char*` or `void (__cdecl *)(int)`.
+    ///
+    /// Classes, structures, unions and enumerations are referred to by name. Array bounds are
+    /// computed from the size of the element type, and omitted if it is not known.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnimplementedFeature` if the type or a type it refers to cannot be named, such as
+    ///   a field list
+    /// * `Error::TypeNotFound(index)` if a type does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if a type is not yet known by this finder
+    /// * `Error::UnimplementedTypeKind(kind)` if a type record is not understood by this library
+    pub fn type_name(&self, index: TypeIndex) -> Result<String> {
+        self.declare(index, String::new(), 0)
+    }
+
+    /// Returns the signature of a procedure (`LF_PROCEDURE`) or member function (`LF_MFUNCTION`).
+    ///
+    /// The return type and the types of all parameters are resolved to their names. For member
+    /// functions, the declaring class, the `this` pointer and its adjustment are included.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// let mut finder = type_information.finder();
+    /// let mut iter = type_information.iter();
+    /// while let Some(_) = iter.next()? {
+    ///     finder.update(&iter);
+    /// }
+    ///
+    /// let signature = finder.signature(pdb::TypeIndex(0x100a))?;
+    /// println!("{}", signature.declaration("function"));
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::NotAFunctionType(index)` if the type is neither a procedure nor a member function
+    /// * `Error::UnimplementedFeature` if a type the signature refers to cannot be named
+    /// * `Error::TypeNotFound(index)` if a type does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if a type is not yet known by this finder
+    /// * `Error::UnimplementedTypeKind(kind)` if a type record is not understood by this library
+    pub fn signature(&self, index: TypeIndex) -> Result<FunctionSignature> {
+        let (return_type, attributes, argument_list, member) = match self.find(index)?.parse()? {
+            TypeData::Procedure(data) => {
+                (data.return_type, data.attributes, data.argument_list, None)
+            }
+            TypeData::MemberFunction(data) => (
+                Some(data.return_type),
+                data.attributes,
+                data.argument_list,
+                Some(data),
+            ),
+            _ => return Err(Error::NotAFunctionType(index.0)),
+        };
+
+        let (parameters, variadic) = self.parameters(argument_list, 0)?;
+        let mut signature = FunctionSignature {
+            return_type: match return_type {
+                Some(return_type) => self.type_name(return_type)?,
+                None => "void".to_string(),
+            },
+            calling_convention: calling_convention_name(attributes),
+            class: None,
+            this_type: None,
+            this_adjustment: 0,
+            parameters,
+            variadic,
+            constant: false,
+            volatile: false,
+        };
+
+        if let Some(member) = member {
+            signature.class = Some(self.type_name(member.class_type)?);
+            signature.this_adjustment = member.this_adjustment;
+
+            if let Some(this_type) = member.this_pointer_type {
+                signature.this_type = Some(self.type_name(this_type)?);
+                let (constant, volatile) = self.this_qualifiers(this_type)?;
+                signature.constant = constant;
+                signature.volatile = volatile;
+            }
+        }
+
+        Ok(signature)
+    }
+
+    /// Returns whether the class a `this` pointer points to is `const` or `volatile`.
+    fn this_qualifiers(&self, this_type: TypeIndex) -> Result<(bool, bool)> {
+        if let TypeData::Pointer(pointer) = self.find(this_type)?.parse()? {
+            if let TypeData::Modifier(modifier) = self.find(pointer.underlying_type)?.parse()? {
+                return Ok((modifier.constant, modifier.volatile));
+            }
+        }
+
+        Ok((false, false))
+    }
+
+    /// Returns the names of the types in an argument list, and whether it ends in `...`.
+    fn parameters(&self, argument_list: TypeIndex, depth: usize) -> Result<(Vec<String>, bool)> {
+        let arguments = match self.find(argument_list)?.parse()? {
+            TypeData::ArgumentList(ArgumentList { arguments }) => arguments,
+            _ => return Err(Error::UnimplementedFeature("unexpected argument list type")),
+        };
+
+        let mut parameters = Vec::with_capacity(arguments.len());
+        let mut variadic = false;
+        for argument in arguments {
+            // a trailing `NoType` denotes variadic arguments
+            if let Some(PrimitiveType {
+                kind: PrimitiveKind::NoType,
+                indirection: None,
+            }) = PrimitiveType::from_index(argument)
+            {
+                variadic = true;
+                continue;
+            }
+
+            parameters.push(self.declare(argument, String::new(), depth + 1)?);
+        }
+
+        Ok((parameters, variadic))
+    }
+
+    /// Renders a type around a declarator, following the C++ declarator syntax.
+    fn declare(&self, index: TypeIndex, declarator: String, depth: usize) -> Result<String> {
+        if depth >= MAX_DEPTH {
+            return Err(Error::UnimplementedFeature("type nested too deeply"));
+        }
+
+        Ok(match self.find(index)?.parse()? {
+            TypeData::Primitive(primitive) => join(primitive.name(), &declarator),
+            TypeData::Class(data) => join(data.name.to_string().into_owned(), &declarator),
+            TypeData::Union(data) => join(data.name.to_string().into_owned(), &declarator),
+            TypeData::Enumeration(data) => join(data.name.to_string().into_owned(), &declarator),
+            TypeData::Alias(data) => join(data.name.to_string().into_owned(), &declarator),
+            TypeData::Modifier(modifier) => {
+                let qualifiers = cv_qualifiers(modifier.constant, modifier.volatile);
+                match self.find(modifier.underlying_type)?.parse()? {
+                    // qualifiers of pointers follow the pointer declarator
+                    TypeData::Pointer(_) => {
+                        let declarator = join(qualifiers.trim_start().to_string(), &declarator);
+                        self.declare(modifier.underlying_type, declarator, depth + 1)?
+                    }
+                    _ => {
+                        let name = self.declare(modifier.underlying_type, declarator, depth + 1)?;
+                        format!("{} {}", qualifiers.trim_start(), name)
+                            .trim_start()
+                            .to_string()
+                    }
+                }
+            }
+            TypeData::Pointer(pointer) => {
+                let attributes = pointer.attributes;
+                let symbol = match attributes.pointer_mode() {
+                    PointerMode::Pointer => "*".to_string(),
+                    PointerMode::LValueReference => "&".to_string(),
+                    PointerMode::RValueReference => "&&".to_string(),
+                    PointerMode::Member | PointerMode::MemberFunction => {
+                        match pointer.containing_class {
+                            Some(class) => {
+                                format!("{}::*", self.declare(class, String::new(), depth + 1)?)
+                            }
+                            None => "*".to_string(),
+                        }
+                    }
+                };
+
+                let qualifiers = cv_qualifiers(attributes.is_const(), attributes.is_volatile());
+                let declarator =
+                    if declarator.is_empty() || declarator.starts_with(['(', '[', '*', '&']) {
+                        format!("{}{}{}", symbol, qualifiers, declarator)
+                    } else {
+                        format!("{}{} {}", symbol, qualifiers, declarator)
+                    };
+
+                // pointers to functions and arrays need to bind tighter than their target
+                let declarator = match self.find(pointer.underlying_type)?.parse()? {
+                    TypeData::Procedure(procedure) => {
+                        match calling_convention_name(procedure.attributes) {
+                            Some(cc) => format!("({} {})", cc, declarator),
+                            None => format!("({})", declarator),
+                        }
+                    }
+                    TypeData::MemberFunction(function) => {
+                        match calling_convention_name(function.attributes) {
+                            Some(cc) => format!("({} {})", cc, declarator),
+                            None => format!("({})", declarator),
+                        }
+                    }
+                    TypeData::Array(_) => format!("({})", declarator),
+                    _ => declarator,
+                };
+
+                self.declare(pointer.underlying_type, declarator, depth + 1)?
+            }
+            TypeData::Array(array) => {
+                let bound = match (
+                    array.dimensions.last(),
+                    type_size(self, array.element_type, None),
+                ) {
+                    (Some(&size), Ok(element_size)) if element_size > 0 => {
+                        (size / element_size).to_string()
+                    }
+                    _ => String::new(),
+                };
+
+                let declarator = format!("{}[{}]", declarator, bound);
+                self.declare(array.element_type, declarator, depth + 1)?
+            }
+            TypeData::Procedure(procedure) => {
+                let (parameters, variadic) = self.parameters(procedure.argument_list, depth)?;
+                let declarator = format!("{}{}", declarator, parameter_list(parameters, variadic));
+                match procedure.return_type {
+                    Some(return_type) => self.declare(return_type, declarator, depth + 1)?,
+                    None => join("void".to_string(), &declarator),
+                }
+            }
+            TypeData::MemberFunction(function) => {
+                let (parameters, variadic) = self.parameters(function.argument_list, depth)?;
+                let (constant, volatile) = match function.this_pointer_type {
+                    Some(this_type) => self.this_qualifiers(this_type)?,
+                    None => (false, false),
+                };
+
+                let declarator = format!(
+                    "{}{}{}",
+                    declarator,
+                    parameter_list(parameters, variadic),
+                    cv_qualifiers(constant, volatile)
+                );
+                self.declare(function.return_type, declarator, depth + 1)?
+            }
+            TypeData::Bitfield(bitfield) => {
+                let name = self.declare(bitfield.underlying_type, declarator, depth + 1)?;
+                format!("{} : {}", name, bitfield.length)
+            }
+            _ => return Err(Error::UnimplementedFeature("type cannot be named")),
+        })
+    }
+}
+
+/// Renders the parameter list of a function type.
+fn parameter_list(mut parameters: Vec<String>, variadic: bool) -> String {
+    if variadic {
+        parameters.push("...".to_string());
+    }
+
+    format!("({})", parameters.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature() -> FunctionSignature {
+        FunctionSignature {
+            return_type: "int".to_string(),
+            calling_convention: Some("__thiscall"),
+            class: Some("Foo".to_string()),
+            this_type: Some("Foo*".to_string()),
+            this_adjustment: 0,
+            parameters: vec!["char*".to_string(), "int".to_string()],
+            variadic: false,
+            constant: true,
+            volatile: false,
+        }
+    }
+
+    #[test]
+    fn test_declaration() {
+        let mut signature = signature();
+        assert_eq!(
+            signature.declaration("get"),
+            "int __thiscall Foo::get(char*, int) const"
+        );
+        assert_eq!(signature.to_string(), "int __thiscall(char*, int) const");
+
+        signature.this_type = None;
+        signature.constant = false;
+        signature.variadic = true;
+        signature.this_adjustment = 8;
+        assert!(signature.is_static());
+        assert_eq!(
+            signature.declaration("get"),
+            "static int __thiscall Foo::get(char*, int, ...) /* this adjustment 8 */"
+        );
+    }
+
+    #[test]
+    fn test_join() {
+        assert_eq!(join("int".to_string(), ""), "int");
+        assert_eq!(join("int".to_string(), "* const"), "int* const");
+        assert_eq!(join("int".to_string(), "[4]"), "int[4]");
+        assert_eq!(
+            join("void".to_string(), "(__cdecl *)(int)"),
+            "void (__cdecl *)(int)"
+        );
+    }
+}
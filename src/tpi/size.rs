@@ -0,0 +1,231 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::common::*;
+use crate::tpi::data::*;
+use crate::tpi::{TypeFinder, TypeInformation};
+use crate::FallibleIterator;
+
+/// Maximum number of type records followed while computing the size of a single type.
+///
+/// Well-formed type streams only contain short chains of modifiers and aliases, so this merely
+/// guards against cycles in corrupt files.
+pub(crate) const MAX_DEPTH: usize = 64;
+
+/// Computes the sizes of types in a [`TypeInformation`] stream.
+///
+/// The sizer indexes all types when it is created, which allows it to resolve forward references
+/// to the definitions of classes, structures and unions using
+/// [`TypeFinder::resolve_definition`]. Create it once using
+/// [`TypeInformation::sizer`](crate::ItemInformation::sizer) to compute the sizes of many types.
+///
+/// Sizes are derived as follows:
+///
+///  - Primitive types have their natural size. Primitive pointers use the size of their
+///    indirection mode.
+///  - Pointers use the size recorded in the pointer attributes, which reflects the target machine.
+///    If the record does not specify a size, it is derived from the
+///    [pointer size](TypeInformation::pointer_size) of the target machine.
+///  - Arrays use the size in bytes recorded for their outermost dimension.
+///  - Classes, structures and unions use their declared size, following forward references.
+///  - Enumerations and bitfields use the size of their underlying type. Modifiers and aliases use
+///    the size of the type they refer to.
+///
+/// Other types, such as procedures and field lists, do not have a size.
+#[derive(Debug)]
+pub struct TypeSizer<'t> {
+    finder: TypeFinder<'t>,
+    pointer_size: Option<u8>,
+}
+
+impl<'t> TypeSizer<'t> {
+    pub(crate) fn new(info: &'t TypeInformation<'_>) -> Result<Self> {
+        let mut finder = info.finder();
+
+        let mut iter = info.iter();
+        while iter.next()?.is_some() {
+            finder.update(&iter);
+        }
+
+        Ok(Self {
+            finder,
+            pointer_size: info.pointer_size(),
+        })
+    }
+
+    /// Returns the size of the type with the given index in bytes.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnsizedType(index)` if the type does not have a size, such as a procedure or a
+    ///   class that is only declared
+    /// * `Error::TypeNotFound(index)` if the type or one of the types it refers to does not exist
+    /// * `Error::UnimplementedTypeKind(kind)` if a type record is not understood by this library
+    pub fn size_of(&self, index: TypeIndex) -> Result<u64> {
+        type_size(&self.finder, index, self.pointer_size)
+    }
+
+    /// Returns the size of a pointer on the target machine in bytes, if known.
+    pub fn pointer_size(&self) -> Option<u8> {
+        self.pointer_size
+    }
+
+    /// Parses the type with the given index.
+    pub(crate) fn parse(&self, index: TypeIndex) -> Result<TypeData<'t>> {
+        self.finder.find(index)?.parse()
+    }
+
+    /// Returns the finder indexing all types of the stream.
+    pub(crate) fn finder(&self) -> &TypeFinder<'t> {
+        &self.finder
+    }
+
+    /// Returns the definition of a forward referenced class, structure or union.
+    pub(crate) fn definition(&self, index: TypeIndex) -> Result<Option<TypeIndex>> {
+        self.finder.resolve_definition(index)
+    }
+}
+
+/// Returns the size of a type in bytes, see [`TypeSizer::size_of`].
+///
+/// Forward references are only resolved to definitions known by `finder`. `machine_pointer` is the
+/// size of a pointer on the target machine, if known.
+pub(crate) fn type_size(
+    finder: &TypeFinder<'_>,
+    index: TypeIndex,
+    machine_pointer: Option<u8>,
+) -> Result<u64> {
+    let mut current = index;
+
+    for _ in 0..MAX_DEPTH {
+        let next = match finder.find(current)?.parse()? {
+            TypeData::Primitive(primitive) => {
+                return primitive.size().ok_or(Error::UnsizedType(current.0));
+            }
+            TypeData::Pointer(pointer) => {
+                return pointer_size(&pointer, machine_pointer)
+                    .map(u64::from)
+                    .ok_or(Error::UnsizedType(current.0));
+            }
+            TypeData::Array(array) => {
+                return match array.dimensions.last() {
+                    Some(&size) => Ok(size),
+                    None => Err(Error::UnsizedType(current.0)),
+                };
+            }
+            TypeData::Class(class) => {
+                if !class.properties.forward_reference() {
+                    return Ok(class.size);
+                }
+                finder
+                    .resolve_definition(current)?
+                    .ok_or(Error::UnsizedType(current.0))?
+            }
+            TypeData::Union(union) => {
+                if !union.properties.forward_reference() {
+                    return Ok(union.size);
+                }
+                finder
+                    .resolve_definition(current)?
+                    .ok_or(Error::UnsizedType(current.0))?
+            }
+            TypeData::Enumeration(enumeration) => enumeration.underlying_type,
+            TypeData::Bitfield(bitfield) => bitfield.underlying_type,
+            TypeData::Modifier(modifier) => modifier.underlying_type,
+            TypeData::Alias(alias) => alias.underlying_type,
+            _ => return Err(Error::UnsizedType(current.0)),
+        };
+
+        current = next;
+    }
+
+    Err(Error::UnsizedType(index.0))
+}
+
+/// Returns the size of a pointer in bytes.
+///
+/// The size recorded in the pointer attributes takes precedence. Otherwise, it is derived from the
+/// size of a pointer on the target machine, following the representations MSVC uses for pointers
+/// to members.
+fn pointer_size(pointer: &PointerType, machine_pointer: Option<u8>) -> Option<u8> {
+    let representation = match pointer.member_representation {
+        Some(_) if pointer.attributes.recorded_size() != 0 => {
+            return Some(pointer.attributes.recorded_size());
+        }
+        Some(representation) => representation,
+        None => {
+            return match pointer.attributes.size() {
+                0 => machine_pointer,
+                size => Some(size),
+            };
+        }
+    };
+
+    // the pointer kind reflects `__ptr32` and `__ptr64` qualifiers
+    let machine_pointer = match pointer.attributes.pointer_kind() {
+        PointerKind::Near32 | PointerKind::Far32 => 4,
+        PointerKind::Ptr64 => 8,
+        _ => machine_pointer?,
+    };
+
+    // pointers to member functions are followed by adjustors of 4 bytes each, padded to the
+    // alignment of the function pointer
+    let function = |adjustors: u8| {
+        let size = machine_pointer + 4 * adjustors;
+        Some(size.next_multiple_of(machine_pointer))
+    };
+
+    match representation {
+        MemberPointerRepresentation::Undefined => None,
+        MemberPointerRepresentation::DataSingle | MemberPointerRepresentation::DataMultiple => {
+            Some(4)
+        }
+        MemberPointerRepresentation::DataVirtual => Some(8),
+        MemberPointerRepresentation::DataGeneral => Some(12),
+        MemberPointerRepresentation::FunctionSingle => function(0),
+        MemberPointerRepresentation::FunctionMultiple => function(1),
+        MemberPointerRepresentation::FunctionVirtual => function(2),
+        MemberPointerRepresentation::FunctionGeneral => function(3),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member_pointer(kind: u32, representation: MemberPointerRepresentation) -> PointerType {
+        PointerType {
+            underlying_type: TypeIndex(0x1000),
+            // pointer to member function without a recorded size
+            attributes: PointerAttributes(kind | (0x03 << 5)),
+            containing_class: Some(TypeIndex(0x1001)),
+            member_representation: Some(representation),
+        }
+    }
+
+    #[test]
+    fn test_member_pointer_size() {
+        let single = member_pointer(0x00, MemberPointerRepresentation::FunctionSingle);
+        assert_eq!(pointer_size(&single, Some(8)), Some(8));
+        assert_eq!(pointer_size(&single, None), None);
+
+        let multiple = member_pointer(0x00, MemberPointerRepresentation::FunctionMultiple);
+        assert_eq!(pointer_size(&multiple, Some(4)), Some(8));
+        assert_eq!(pointer_size(&multiple, Some(8)), Some(16));
+
+        let general = member_pointer(0x00, MemberPointerRepresentation::FunctionGeneral);
+        assert_eq!(pointer_size(&general, Some(4)), Some(16));
+        assert_eq!(pointer_size(&general, Some(8)), Some(24));
+
+        let data = member_pointer(0x00, MemberPointerRepresentation::DataVirtual);
+        assert_eq!(pointer_size(&data, Some(8)), Some(8));
+
+        // the pointer kind takes precedence over the machine
+        let ptr32 = member_pointer(0x0a, MemberPointerRepresentation::FunctionVirtual);
+        assert_eq!(pointer_size(&ptr32, Some(8)), Some(12));
+    }
+}
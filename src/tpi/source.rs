@@ -0,0 +1,240 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::common::*;
+use crate::strings::StringTable;
+use crate::tpi::data::TypeData;
+use crate::tpi::id::{IdData, UserDefinedTypeSourceFileRef};
+use crate::tpi::{IdInformation, TypeInformation};
+use crate::FallibleIterator;
+
+/// The location of the definition of a user-defined type, as returned by
+/// [`TypeSources::source_of`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TypeSource<'a> {
+    /// The path of the source file declaring the type.
+    pub file: RawString<'a>,
+    /// The line of the declaration in the source file.
+    pub line: u32,
+    /// Index of the module contributing the definition in the DBI module list.
+    ///
+    /// This is only known for records written by the linker (`LF_UDT_MOD_SRC_LINE`). Use
+    /// [`DebugInformation::modules`](crate::DebugInformation::modules) to look up the module.
+    pub module: Option<usize>,
+}
+
+/// The source locations of user-defined types, read from the id information stream (IPI).
+///
+/// The compiler records where every class, structure, union and enumeration is defined in an
+/// `LF_UDT_SRC_LINE` record. When linking, these are rewritten into `LF_UDT_MOD_SRC_LINE` records,
+/// which refer to the file name in the PDB's string table and to the module that contributed the
+/// definition. `TypeSources` resolves both kinds of records, so that the location of a type can be
+/// looked up directly by its index in the type information stream.
+///
+/// Use [`IdInformation::type_sources`](crate::ItemInformation::type_sources) to collect them.
+///
+/// # Example
+///
+/// ```
+/// # use pdb::FallibleIterator;
+/// # fn test() -> pdb::Result<()> {
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(file)?;
+/// let id_information = pdb.id_information()?;
+/// let string_table = pdb.string_table()?;
+/// let debug_information = pdb.debug_information()?;
+///
+/// let sources = id_information.type_sources(Some(&string_table))?;
+/// if let Some(source) = sources.source_of(pdb::TypeIndex(0x100e)) {
+///     let module = match source.module {
+///         Some(index) => debug_information.modules()?.nth(index)?,
+///         None => None,
+///     };
+///
+///     println!("{}:{}", source.file, source.line);
+///     if let Some(module) = module {
+///         println!("defined in {}", module.module_name());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TypeSources<'a> {
+    sources: HashMap<TypeIndex, TypeSource<'a>>,
+}
+
+impl<'a> TypeSources<'a> {
+    /// Returns the location of the definition of a user-defined type.
+    ///
+    /// Returns `None` if no location was recorded for the type. This is the case for forward
+    /// references and types that do not have a definition, such as pointers.
+    pub fn source_of(&self, index: TypeIndex) -> Option<TypeSource<'a>> {
+        self.sources.get(&index).copied()
+    }
+
+    /// Returns the number of types with a known location.
+    pub fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Returns whether no type has a known location.
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// Returns an iterator over all types with a known location, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (TypeIndex, TypeSource<'a>)> + '_ {
+        self.sources.iter().map(|(&index, &source)| (index, source))
+    }
+}
+
+impl<'s> IdInformation<'s> {
+    /// Collects the source locations of all user-defined types.
+    ///
+    /// `strings` is the string table of the PDB, which holds the file names of records written by
+    /// the linker. It can be omitted for the id stream of an object file, whose records refer to
+    /// file names in the id stream itself. If several locations are recorded for a type, the first
+    /// one is used.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::StreamNameNotFound` if a record refers to the string table, but none was given
+    /// * `Error::TypeNotFound(index)` if a record refers to a file name that does not exist
+    /// * `Error::UnexpectedEof` if the id stream is truncated or a file name is out of bounds
+    pub fn type_sources<'a>(
+        &'a self,
+        strings: Option<&'a StringTable<'_>>,
+    ) -> Result<TypeSources<'a>> {
+        let mut records = Vec::new();
+        let mut local_files = HashSet::new();
+
+        let mut iter = self.iter();
+        while let Some(item) = iter.next()? {
+            if let Ok(IdData::UserDefinedTypeSource(source)) = item.parse() {
+                if let UserDefinedTypeSourceFileRef::Local(file) = source.source_file {
+                    local_files.insert(file);
+                }
+                records.push(source);
+            }
+        }
+
+        // file names of compiler records are string ids in the same stream
+        let mut file_names = HashMap::with_capacity(local_files.len());
+        if !local_files.is_empty() {
+            let mut iter = self.iter();
+            while let Some(item) = iter.next()? {
+                if local_files.contains(&item.index()) {
+                    if let Ok(IdData::String(string)) = item.parse() {
+                        file_names.insert(item.index(), string.name);
+                    }
+                }
+            }
+        }
+
+        let mut sources = HashMap::with_capacity(records.len());
+        for record in records {
+            if sources.contains_key(&record.udt) {
+                continue;
+            }
+
+            let (file, module) = match record.source_file {
+                UserDefinedTypeSourceFileRef::Local(file) => match file_names.get(&file) {
+                    Some(&name) => (name, None),
+                    None => return Err(Error::TypeNotFound(file.0)),
+                },
+                UserDefinedTypeSourceFileRef::Remote(module, file) => {
+                    let strings = strings.ok_or(Error::StreamNameNotFound)?;
+                    // module numbers are one-based
+                    let module = usize::from(module).checked_sub(1);
+                    (strings.get(file)?, module)
+                }
+            };
+
+            sources.insert(
+                record.udt,
+                TypeSource {
+                    file,
+                    line: record.line,
+                    module,
+                },
+            );
+        }
+
+        Ok(TypeSources { sources })
+    }
+}
+
+/// Identifies a user-defined type for [`PDB::udt_source`](crate::PDB::udt_source).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UdtQuery<'q> {
+    /// The index of the type in the type information stream.
+    ///
+    /// Forward references are resolved to their definition.
+    Index(TypeIndex),
+    /// The fully qualified name of the type, such as `std::exception`.
+    Name(&'q str),
+}
+
+impl From<TypeIndex> for UdtQuery<'_> {
+    fn from(index: TypeIndex) -> Self {
+        Self::Index(index)
+    }
+}
+
+impl<'q> From<&'q str> for UdtQuery<'q> {
+    fn from(name: &'q str) -> Self {
+        Self::Name(name)
+    }
+}
+
+/// The resolved location of the definition of a user-defined type, as returned by
+/// [`PDB::udt_source`](crate::PDB::udt_source).
+///
+/// Unlike [`TypeSource`], this owns its strings and includes the name of the module contributing
+/// the definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UdtSource {
+    /// The index of the definition in the type information stream.
+    pub index: TypeIndex,
+    /// The path of the source file declaring the type.
+    pub file: String,
+    /// The line of the declaration in the source file.
+    pub line: u32,
+    /// Index of the module contributing the definition in the DBI module list, if known.
+    pub module: Option<usize>,
+    /// The name of the module contributing the definition, if known.
+    pub module_name: Option<String>,
+}
+
+/// Finds the definition of a class, structure, union or enumeration by iterating over all types.
+///
+/// This is the fallback of [`PDB::udt_source`](crate::PDB::udt_source) for type streams without
+/// a hash stream.
+pub(crate) fn find_definition(
+    types: &TypeInformation<'_>,
+    name: &str,
+) -> Result<Option<TypeIndex>> {
+    let mut iter = types.iter();
+    while let Some(item) = iter.next()? {
+        let (properties, type_name) = match item.parse() {
+            Ok(TypeData::Class(c)) => (c.properties, c.name),
+            Ok(TypeData::Union(u)) => (u.properties, u.name),
+            Ok(TypeData::Enumeration(e)) => (e.properties, e.name),
+            _ => continue,
+        };
+
+        if !properties.forward_reference() && type_name.as_bytes() == name.as_bytes() {
+            return Ok(Some(item.index()));
+        }
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,157 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+
+use crate::common::*;
+use crate::tpi::data::TypeData;
+use crate::tpi::TypeInformation;
+use crate::FallibleIterator;
+
+/// The number and size of the records of one kind, as collected by
+/// [`TypeInformation::statistics`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct KindStatistics {
+    /// The number of records of this kind.
+    pub count: usize,
+    /// The size of all records of this kind in bytes, including their length prefixes.
+    pub size: u64,
+}
+
+/// A record listed among the largest records of a type stream.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RecordSize {
+    /// The index of the type.
+    pub index: TypeIndex,
+    /// The raw kind of the record, such as `LF_FIELDLIST`.
+    pub kind: u16,
+    /// The size of the record in bytes, including its length prefix.
+    pub size: usize,
+}
+
+/// A summary of the records in a type stream, as returned by [`TypeInformation::statistics`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypeStatistics {
+    /// The number of records in the stream.
+    pub records: usize,
+    /// The size of all records in bytes, including their length prefixes.
+    pub size: u64,
+    /// The number and size of records per raw kind, such as `LF_CLASS`.
+    pub kinds: BTreeMap<u16, KindStatistics>,
+    /// The number of classes, structures, unions and enumerations that are only declared.
+    pub forward_references: usize,
+    /// The number of classes, structures, unions and enumerations that are defined.
+    pub definitions: usize,
+    /// The largest records, ordered by decreasing size.
+    pub largest: Vec<RecordSize>,
+}
+
+impl TypeStatistics {
+    /// Returns the average size of a record in bytes, or `0.0` if the stream is empty.
+    pub fn average_size(&self) -> f64 {
+        if self.records == 0 {
+            return 0.0;
+        }
+
+        self.size as f64 / self.records as f64
+    }
+}
+
+impl<'s> TypeInformation<'s> {
+    /// Collects statistics about the records of this type stream.
+    ///
+    /// This counts the records of every kind with their total size, distinguishes declarations of
+    /// classes, structures, unions and enumerations from their definitions, and lists the
+    /// `largest` records in the stream. Comparing these figures helps to find out which types
+    /// contribute most to the size of a PDB.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// let statistics = type_information.statistics(5)?;
+    /// for (kind, kind_statistics) in &statistics.kinds {
+    ///     println!("{:#06x}: {} bytes", kind, kind_statistics.size);
+    /// }
+    /// for record in &statistics.largest {
+    ///     println!("{}: {} bytes", record.index, record.size);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the type stream is truncated
+    pub fn statistics(&self, largest: usize) -> Result<TypeStatistics> {
+        let mut statistics = TypeStatistics::default();
+        let mut heap = BinaryHeap::with_capacity(largest + 1);
+
+        let mut iter = self.iter();
+        while let Some(item) = iter.next()? {
+            let size = item.len() + 2;
+            let kind = item.raw_kind();
+
+            statistics.records += 1;
+            statistics.size += size as u64;
+
+            let kind_statistics = statistics.kinds.entry(kind).or_default();
+            kind_statistics.count += 1;
+            kind_statistics.size += size as u64;
+
+            let properties = match item.parse() {
+                Ok(TypeData::Class(data)) => Some(data.properties),
+                Ok(TypeData::Union(data)) => Some(data.properties),
+                Ok(TypeData::Enumeration(data)) => Some(data.properties),
+                _ => None,
+            };
+
+            match properties {
+                Some(properties) if properties.forward_reference() => {
+                    statistics.forward_references += 1;
+                }
+                Some(_) => statistics.definitions += 1,
+                None => {}
+            }
+
+            // keep the smallest of the retained records at the top, preferring lower indices
+            heap.push(Reverse((size, Reverse(item.index()), kind)));
+            if heap.len() > largest {
+                heap.pop();
+            }
+        }
+
+        statistics.largest = heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((size, Reverse(index), kind))| RecordSize { index, kind, size })
+            .collect();
+
+        Ok(statistics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_average_size() {
+        let mut statistics = TypeStatistics::default();
+        assert_eq!(statistics.average_size(), 0.0);
+
+        statistics.records = 4;
+        statistics.size = 10;
+        assert_eq!(statistics.average_size(), 2.5);
+    }
+}
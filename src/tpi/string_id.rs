@@ -0,0 +1,115 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::borrow::Cow;
+
+use crate::common::*;
+use crate::tpi::id::{IdData, StringId};
+use crate::tpi::IdFinder;
+
+/// A pending step while concatenating a string id.
+enum Step<'t> {
+    /// Expand the string id at this index.
+    Expand(IdIndex),
+    /// Append the name of a string id after its substrings have been appended.
+    Append(RawString<'t>),
+}
+
+impl<'t> IdFinder<'t> {
+    /// Resolves an `LF_STRING_ID` record into the complete string.
+    ///
+    /// Long strings, such as compiler command lines, are split into several records. A
+    /// [`StringId`] may refer to an `LF_SUBSTR_LIST` of other string ids, which
+    /// precede its own name. Substrings that are split themselves are expanded as well. The result
+    /// borrows from the id stream if the string is not split.
+    ///
+    /// All records making up the string must be known by this `IdFinder`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let id_information = pdb.id_information()?;
+    /// let mut id_finder = id_information.finder();
+    ///
+    /// let mut iter = id_information.iter();
+    /// while let Some(id) = iter.next()? {
+    ///     id_finder.update(&iter);
+    ///     if let pdb::IdData::String(_) = id.parse()? {
+    ///         let string = id_finder.resolve_string(id.index())?;
+    ///         println!("{}", String::from_utf8_lossy(&string));
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedIdKind(index)` if the id is not a string, or refers to a record that is
+    ///   not a list of substrings or a string
+    /// * `Error::CyclicStringId(index)` if a string contains itself through its substrings
+    /// * `Error::TypeNotFound(index)` if the id or one of its substrings does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if an id is not yet known by this finder
+    pub fn resolve_string(&self, index: IdIndex) -> Result<Cow<'t, [u8]>> {
+        let string = self.string_id(index)?;
+        if string.substrings.is_none() {
+            return Ok(Cow::Borrowed(string.name.as_bytes()));
+        }
+
+        let mut bytes = Vec::new();
+        // indices of the strings currently being expanded, to detect cycles
+        let mut expanding = Vec::new();
+        let mut pending = vec![Step::Expand(index)];
+
+        while let Some(step) = pending.pop() {
+            let index = match step {
+                Step::Expand(index) => index,
+                Step::Append(name) => {
+                    expanding.pop();
+                    bytes.extend_from_slice(name.as_bytes());
+                    continue;
+                }
+            };
+
+            if expanding.contains(&index) {
+                return Err(Error::CyclicStringId(index.0));
+            }
+
+            let string = self.string_id(index)?;
+            let list = match string.substrings {
+                Some(list) => list,
+                None => {
+                    bytes.extend_from_slice(string.name.as_bytes());
+                    continue;
+                }
+            };
+
+            let substrings = match self.find(list)?.parse()? {
+                IdData::StringList(list) => list.substrings,
+                _ => return Err(Error::UnexpectedIdKind(list.0)),
+            };
+
+            expanding.push(index);
+            pending.push(Step::Append(string.name));
+            pending.extend(substrings.into_iter().rev().map(Step::Expand));
+        }
+
+        Ok(Cow::Owned(bytes))
+    }
+
+    fn string_id(&self, index: IdIndex) -> Result<StringId<'t>> {
+        match self.find(index)?.parse()? {
+            IdData::String(string) => Ok(string),
+            _ => Err(Error::UnexpectedIdKind(index.0)),
+        }
+    }
+}
@@ -0,0 +1,342 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::common::*;
+use crate::tpi::data::TypeData;
+use crate::tpi::TypeInformation;
+use crate::FallibleIterator;
+
+/// Operators that may follow the `operator` keyword and contain angle brackets, longest first.
+const ANGLE_OPERATORS: &[&[u8]] = &[
+    b"<=>", b"<<=", b">>=", b"->*", b"<<", b">>", b"<=", b">=", b"->", b"<", b">",
+];
+
+/// The name of a template instantiation, split into the name of the template and its arguments.
+///
+/// MSVC records instantiations of class templates under their full name, such as
+/// `std::vector<int,std::allocator<int> >`. `TemplateName` splits off the argument list of the
+/// innermost name component, so that all instantiations of a template can be found by comparing
+/// [`base`](Self::base). Arguments are not parsed further; call [`TemplateName::parse`] on an
+/// argument to split it as well.
+///
+/// Angle brackets in names that are not template argument lists are left alone. This includes
+/// generated names such as `<lambda_1>` and `<unnamed-tag>`, and operators such as `operator<`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateName<'t> {
+    /// The name of the template, including its enclosing scopes, such as `std::vector`.
+    pub base: RawString<'t>,
+    /// The template arguments, without surrounding white space.
+    pub arguments: Vec<RawString<'t>>,
+}
+
+impl<'t> TemplateName<'t> {
+    /// Splits the name of a template instantiation.
+    ///
+    /// Returns `None` if the innermost component of `name` does not have a template argument list.
+    /// In particular, a type nested in a template instantiation such as
+    /// `std::vector<int>::iterator` is not an instantiation itself.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::{RawString, TemplateName};
+    /// let name = TemplateName::parse(RawString::from("std::map<int,std::pair<int,char> >")).unwrap();
+    /// assert_eq!(name.base, RawString::from("std::map"));
+    /// assert_eq!(
+    ///     name.arguments,
+    ///     [RawString::from("int"), RawString::from("std::pair<int,char>")]
+    /// );
+    /// ```
+    pub fn parse(name: RawString<'t>) -> Option<Self> {
+        let bytes: &'t [u8] = name.as_bytes();
+        let open = template_arguments(bytes)?;
+
+        let mut arguments = Vec::new();
+        let inner = &bytes[open + 1..bytes.len() - 1];
+        if !trim(inner).is_empty() {
+            let mut start = 0;
+            for comma in top_level_commas(inner) {
+                arguments.push(RawString::from(trim(&inner[start..comma])));
+                start = comma + 1;
+            }
+            arguments.push(RawString::from(trim(&inner[start..])));
+        }
+
+        Some(TemplateName {
+            base: RawString::from(trim(&bytes[..open])),
+            arguments,
+        })
+    }
+}
+
+impl<'s> TypeInformation<'s> {
+    /// Finds all instantiations of a class template.
+    ///
+    /// `base` is the name of the template including its enclosing scopes, such as `std::map`. A
+    /// class, structure, union or enumeration is included if its name parses as a
+    /// [`TemplateName`] with this base. Forward references are included in the result, which is
+    /// ordered by type index.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// let instantiations = type_information.instantiations("__crt_char_traits")?;
+    /// assert!(!instantiations.is_empty());
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the type stream is truncated
+    pub fn instantiations(&self, base: &str) -> Result<Vec<TypeIndex>> {
+        let mut matches = Vec::new();
+
+        let mut iter = self.iter();
+        while let Some(item) = iter.next()? {
+            let name = match item.parse() {
+                Ok(TypeData::Class(data)) => data.name,
+                Ok(TypeData::Union(data)) => data.name,
+                Ok(TypeData::Enumeration(data)) => data.name,
+                _ => continue,
+            };
+
+            match TemplateName::parse(name) {
+                Some(template) if template.base.as_bytes() == base.as_bytes() => {
+                    matches.push(item.index());
+                }
+                _ => {}
+            }
+        }
+
+        Ok(matches)
+    }
+}
+
+fn is_identifier(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$'
+}
+
+/// Returns the position of the `<` opening the template argument list at the end of `name`.
+fn template_arguments(name: &[u8]) -> Option<usize> {
+    // openers of all unclosed brackets, and whether they open a template argument list
+    let mut stack = Vec::new();
+    let mut last = None;
+    // whether a `<` at the current position would open a template argument list
+    let mut after_name = false;
+
+    let mut i = 0;
+    while i < name.len() {
+        let byte = name[i];
+        match byte {
+            b'`' => {
+                // quoted names like `anonymous namespace'
+                let end = name[i..].iter().position(|&b| b == b'\'')?;
+                i += end + 1;
+                after_name = true;
+                continue;
+            }
+            b'o' if !after_name && name[i..].starts_with(b"operator") => {
+                i += b"operator".len();
+                let rest = &name[i..];
+                if let Some(op) = ANGLE_OPERATORS.iter().find(|op| rest.starts_with(op)) {
+                    i += op.len();
+                }
+                after_name = true;
+                continue;
+            }
+            b'<' | b'(' | b'[' => {
+                stack.push((i, byte == b'<' && after_name));
+                after_name = false;
+            }
+            b'>' | b')' | b']' => {
+                let (open, template) = stack.pop()?;
+                let expected = match name[open] {
+                    b'<' => b'>',
+                    b'(' => b')',
+                    _ => b']',
+                };
+                if byte != expected {
+                    return None;
+                }
+
+                last = if stack.is_empty() && template {
+                    Some((open, i))
+                } else {
+                    None
+                };
+                after_name = byte == b'>';
+            }
+            _ => after_name = is_identifier(byte) || (after_name && byte == b' '),
+        }
+
+        i += 1;
+    }
+
+    match (stack.is_empty(), last) {
+        (true, Some((open, close))) if close == name.len() - 1 => Some(open),
+        _ => None,
+    }
+}
+
+/// Returns the positions of commas in `arguments` that separate template arguments.
+fn top_level_commas(arguments: &[u8]) -> Vec<usize> {
+    let mut commas = Vec::new();
+    let mut depth = 0usize;
+    let mut after_name = false;
+
+    let mut i = 0;
+    while i < arguments.len() {
+        let byte = arguments[i];
+        match byte {
+            b'`' => {
+                if let Some(end) = arguments[i..].iter().position(|&b| b == b'\'') {
+                    i += end;
+                }
+            }
+            b'o' if !after_name && arguments[i..].starts_with(b"operator") => {
+                i += b"operator".len();
+                let rest = &arguments[i..];
+                if let Some(op) = ANGLE_OPERATORS.iter().find(|op| rest.starts_with(op)) {
+                    i += op.len();
+                }
+                after_name = true;
+                continue;
+            }
+            b'<' | b'(' | b'[' => depth += 1,
+            b'>' | b')' | b']' => depth = depth.saturating_sub(1),
+            b',' if depth == 0 => commas.push(i),
+            _ => {}
+        }
+
+        after_name = is_identifier(byte);
+        i += 1;
+    }
+
+    commas
+}
+
+fn trim(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|b| !b.is_ascii_whitespace());
+    let end = bytes.iter().rposition(|b| !b.is_ascii_whitespace());
+    match (start, end) {
+        (Some(start), Some(end)) => &bytes[start..=end],
+        _ => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(name: &str) -> Option<TemplateName<'_>> {
+        TemplateName::parse(RawString::from(name))
+    }
+
+    fn template<'t>(base: &'t str, arguments: &[&'t str]) -> Option<TemplateName<'t>> {
+        Some(TemplateName {
+            base: RawString::from(base),
+            arguments: arguments.iter().map(|&a| RawString::from(a)).collect(),
+        })
+    }
+
+    #[test]
+    fn test_simple() {
+        assert_eq!(parse("Foo<int>"), template("Foo", &["int"]));
+        assert_eq!(parse("Foo<>"), template("Foo", &[]));
+        assert_eq!(
+            parse("__crt_simd_traits<0,unsigned char>"),
+            template("__crt_simd_traits", &["0", "unsigned char"])
+        );
+        assert_eq!(parse("Foo"), None);
+        assert_eq!(parse(""), None);
+    }
+
+    #[test]
+    fn test_nested() {
+        assert_eq!(
+            parse("std::vector<int,std::allocator<int> >"),
+            template("std::vector", &["int", "std::allocator<int>"])
+        );
+        assert_eq!(
+            parse("std::map<int,std::map<int,int> >"),
+            template("std::map", &["int", "std::map<int,int>"])
+        );
+        assert_eq!(
+            parse("Outer<int>::Inner<char>"),
+            template("Outer<int>::Inner", &["char"])
+        );
+        assert_eq!(
+            parse("__acrt_floating_type_traits<double>::components_type"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_function_arguments() {
+        assert_eq!(
+            parse("dual_state_global<void (__cdecl*)(wchar_t const *,unsigned int)>"),
+            template(
+                "dual_state_global",
+                &["void (__cdecl*)(wchar_t const *,unsigned int)"]
+            )
+        );
+        assert_eq!(
+            parse("Foo<int (__cdecl*)(_exception *),char * *>"),
+            template("Foo", &["int (__cdecl*)(_exception *)", "char * *"])
+        );
+    }
+
+    #[test]
+    fn test_generated_names() {
+        assert_eq!(parse("<unnamed-tag>"), None);
+        assert_eq!(parse("_LARGE_INTEGER::<unnamed-type-u>"), None);
+        assert_eq!(
+            parse("foo::__l2::<lambda_777d11615145f89ee95b9466d285deeb>"),
+            None
+        );
+        assert_eq!(
+            parse("Foo<`anonymous namespace'::Bar>"),
+            template("Foo", &["`anonymous namespace'::Bar"])
+        );
+        assert_eq!(
+            parse("`anonymous namespace'::Foo<int>"),
+            template("`anonymous namespace'::Foo", &["int"])
+        );
+        assert_eq!(
+            parse("Foo<Bar::<lambda_1> >"),
+            template("Foo", &["Bar::<lambda_1>"])
+        );
+    }
+
+    #[test]
+    fn test_operators() {
+        assert_eq!(parse("Foo::operator<"), None);
+        assert_eq!(parse("Foo::operator>>"), None);
+        assert_eq!(
+            parse("Foo::operator< <int>"),
+            template("Foo::operator<", &["int"])
+        );
+        assert_eq!(
+            parse("Foo<&Bar::operator<,int>"),
+            template("Foo", &["&Bar::operator<", "int"])
+        );
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert_eq!(parse("Foo<int"), None);
+        assert_eq!(parse("Foo<int)>"), None);
+        assert_eq!(parse("Foo<int>>"), None);
+    }
+}
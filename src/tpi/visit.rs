@@ -0,0 +1,591 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::HashSet;
+
+use crate::common::*;
+use crate::tpi::data::*;
+use crate::tpi::primitive::PrimitiveType;
+use crate::tpi::{TypeFinder, TypeInformation, TypeIter};
+use crate::FallibleIterator;
+
+/// Receives type records walked by [`TypeInformation::visit`] or [`TypeFinder::walk`].
+///
+/// There are two ways to implement this trait:
+///
+///  * Override [`visit_type`](Self::visit_type) to receive every record as a whole. This trait is
+///    implemented for closures taking the index and data of every type, which is sufficient
+///    unless records that cannot be parsed need special handling.
+///  * Override the per-kind callbacks, such as [`visit_class`](Self::visit_class) or
+///    [`visit_pointer`](Self::visit_pointer), for the records of interest. The default
+///    implementation of `visit_type` dispatches to them using [`TypeData::accept`], and all
+///    callbacks do nothing by default. This avoids matching over all variants of [`TypeData`].
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// use pdb::{MemberType, TypeIndex, TypeVisitor};
+///
+/// #[derive(Default)]
+/// struct MemberCounter {
+///     members: usize,
+/// }
+///
+/// impl<'t> TypeVisitor<'t> for MemberCounter {
+///     fn visit_member(&mut self, _index: TypeIndex, _data: &MemberType<'t>) -> pdb::Result<()> {
+///         self.members += 1;
+///         Ok(())
+///     }
+/// }
+///
+/// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+/// let pdb = pdb::PDB::open(file)?;
+/// let type_information = pdb.type_information()?;
+///
+/// let mut counter = MemberCounter::default();
+/// type_information.visit(&mut counter)?;
+/// assert!(counter.members > 0);
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+pub trait TypeVisitor<'t> {
+    /// Called for every type record that was parsed successfully.
+    ///
+    /// Returning an error stops the walk and returns the error from
+    /// [`visit`](TypeInformation::visit) or [`walk`](TypeFinder::walk). The default implementation
+    /// dispatches to the per-kind callbacks using [`TypeData::accept`].
+    fn visit_type(&mut self, index: TypeIndex, data: TypeData<'t>) -> Result<()> {
+        data.accept(index, self)
+    }
+
+    /// Called for every type record that could not be parsed, with the raw kind of the record.
+    ///
+    /// The default implementation skips records that are not understood by this library and stops
+    /// the walk for all other errors.
+    fn visit_error(&mut self, index: TypeIndex, kind: u16, error: Error) -> Result<()> {
+        let _ = (index, kind);
+        match error {
+            Error::UnimplementedTypeKind(_) => Ok(()),
+            error => Err(error),
+        }
+    }
+
+    /// Called for [`TypeData::Primitive`] records.
+    fn visit_primitive(&mut self, index: TypeIndex, data: &PrimitiveType) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::Class`] records.
+    fn visit_class(&mut self, index: TypeIndex, data: &ClassType<'t>) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::Member`] records.
+    fn visit_member(&mut self, index: TypeIndex, data: &MemberType<'t>) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::MemberFunction`] records.
+    fn visit_member_function(&mut self, index: TypeIndex, data: &MemberFunctionType) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::OverloadedMethod`] records.
+    fn visit_overloaded_method(
+        &mut self,
+        index: TypeIndex,
+        data: &OverloadedMethodType<'t>,
+    ) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::Method`] records.
+    fn visit_method(&mut self, index: TypeIndex, data: &MethodType<'t>) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::StaticMember`] records.
+    fn visit_static_member(&mut self, index: TypeIndex, data: &StaticMemberType<'t>) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::Nested`] records.
+    fn visit_nested(&mut self, index: TypeIndex, data: &NestedType<'t>) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::MemberModify`] records.
+    fn visit_member_modify(&mut self, index: TypeIndex, data: &MemberModifyType<'t>) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::BaseClass`] records.
+    fn visit_base_class(&mut self, index: TypeIndex, data: &BaseClassType) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::VirtualBaseClass`] records.
+    fn visit_virtual_base_class(
+        &mut self,
+        index: TypeIndex,
+        data: &VirtualBaseClassType,
+    ) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::VirtualFunctionTablePointer`] records.
+    fn visit_virtual_function_table_pointer(
+        &mut self,
+        index: TypeIndex,
+        data: &VirtualFunctionTablePointerType,
+    ) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::Procedure`] records.
+    fn visit_procedure(&mut self, index: TypeIndex, data: &ProcedureType) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::Pointer`] records.
+    fn visit_pointer(&mut self, index: TypeIndex, data: &PointerType) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::Modifier`] records.
+    fn visit_modifier(&mut self, index: TypeIndex, data: &ModifierType) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::Enumeration`] records.
+    fn visit_enumeration(&mut self, index: TypeIndex, data: &EnumerationType<'t>) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::Enumerate`] records.
+    fn visit_enumerate(&mut self, index: TypeIndex, data: &EnumerateType<'t>) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::Array`] records.
+    fn visit_array(&mut self, index: TypeIndex, data: &ArrayType) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::DimensionedArray`] records.
+    fn visit_dimensioned_array(
+        &mut self,
+        index: TypeIndex,
+        data: &DimensionedArrayType<'t>,
+    ) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::Union`] records.
+    fn visit_union(&mut self, index: TypeIndex, data: &UnionType<'t>) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::Bitfield`] records.
+    fn visit_bitfield(&mut self, index: TypeIndex, data: &BitfieldType) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::FieldList`] records, before the fields of the list are visited.
+    fn visit_field_list(&mut self, index: TypeIndex, data: &FieldList<'t>) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::ArgumentList`] records.
+    fn visit_argument_list(&mut self, index: TypeIndex, data: &ArgumentList) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::MethodList`] records.
+    fn visit_method_list(&mut self, index: TypeIndex, data: &MethodList) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::VirtualTableShape`] records.
+    fn visit_virtual_table_shape(
+        &mut self,
+        index: TypeIndex,
+        data: &VirtualTableShapeType,
+    ) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::VirtualFunctionTable`] records.
+    fn visit_virtual_function_table(
+        &mut self,
+        index: TypeIndex,
+        data: &VirtualFunctionTableType<'t>,
+    ) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::VirtualFunctionTablePath`] records.
+    fn visit_virtual_function_table_path(
+        &mut self,
+        index: TypeIndex,
+        data: &VirtualFunctionTablePathType,
+    ) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::Label`] records.
+    fn visit_label(&mut self, index: TypeIndex, data: &LabelType) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::Alias`] records.
+    fn visit_alias(&mut self, index: TypeIndex, data: &AliasType<'t>) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::FriendFunction`] records.
+    fn visit_friend_function(
+        &mut self,
+        index: TypeIndex,
+        data: &FriendFunctionType<'t>,
+    ) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::FriendClass`] records.
+    fn visit_friend_class(&mut self, index: TypeIndex, data: &FriendClassType) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::Managed`] records.
+    fn visit_managed(&mut self, index: TypeIndex, data: &ManagedType<'t>) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::Oem`] records.
+    fn visit_oem(&mut self, index: TypeIndex, data: &OemType) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::Precompiled`] records.
+    fn visit_precompiled(&mut self, index: TypeIndex, data: &PrecompiledType<'t>) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::EndPrecompiled`] records.
+    fn visit_end_precompiled(&mut self, index: TypeIndex, data: &EndPrecompiledType) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+
+    /// Called for [`TypeData::TypeServer`] records.
+    fn visit_type_server(&mut self, index: TypeIndex, data: &TypeServerType<'t>) -> Result<()> {
+        let _ = (index, data);
+        Ok(())
+    }
+}
+
+impl<'t, F> TypeVisitor<'t> for F
+where
+    F: FnMut(TypeIndex, TypeData<'t>) -> Result<()>,
+{
+    fn visit_type(&mut self, index: TypeIndex, data: TypeData<'t>) -> Result<()> {
+        self(index, data)
+    }
+}
+
+impl<'t> TypeData<'t> {
+    /// Passes this record to the callback of `visitor` matching its kind.
+    ///
+    /// For field lists, [`visit_field_list`](TypeVisitor::visit_field_list) is called first, and
+    /// then every field is dispatched to its own callback. Fields are passed the `index` of the
+    /// field list that contains them. The continuation of the list is not followed.
+    ///
+    /// # Errors
+    ///
+    /// * any error returned by the visitor
+    pub fn accept<V>(&self, index: TypeIndex, visitor: &mut V) -> Result<()>
+    where
+        V: TypeVisitor<'t> + ?Sized,
+    {
+        match self {
+            TypeData::Primitive(data) => visitor.visit_primitive(index, data),
+            TypeData::Class(data) => visitor.visit_class(index, data),
+            TypeData::Member(data) => visitor.visit_member(index, data),
+            TypeData::MemberFunction(data) => visitor.visit_member_function(index, data),
+            TypeData::OverloadedMethod(data) => visitor.visit_overloaded_method(index, data),
+            TypeData::Method(data) => visitor.visit_method(index, data),
+            TypeData::StaticMember(data) => visitor.visit_static_member(index, data),
+            TypeData::Nested(data) => visitor.visit_nested(index, data),
+            TypeData::MemberModify(data) => visitor.visit_member_modify(index, data),
+            TypeData::BaseClass(data) => visitor.visit_base_class(index, data),
+            TypeData::VirtualBaseClass(data) => visitor.visit_virtual_base_class(index, data),
+            TypeData::VirtualFunctionTablePointer(data) => {
+                visitor.visit_virtual_function_table_pointer(index, data)
+            }
+            TypeData::Procedure(data) => visitor.visit_procedure(index, data),
+            TypeData::Pointer(data) => visitor.visit_pointer(index, data),
+            TypeData::Modifier(data) => visitor.visit_modifier(index, data),
+            TypeData::Enumeration(data) => visitor.visit_enumeration(index, data),
+            TypeData::Enumerate(data) => visitor.visit_enumerate(index, data),
+            TypeData::Array(data) => visitor.visit_array(index, data),
+            TypeData::DimensionedArray(data) => visitor.visit_dimensioned_array(index, data),
+            TypeData::Union(data) => visitor.visit_union(index, data),
+            TypeData::Bitfield(data) => visitor.visit_bitfield(index, data),
+            TypeData::FieldList(data) => {
+                visitor.visit_field_list(index, data)?;
+                for field in &data.fields {
+                    field.accept(index, visitor)?;
+                }
+                Ok(())
+            }
+            TypeData::ArgumentList(data) => visitor.visit_argument_list(index, data),
+            TypeData::MethodList(data) => visitor.visit_method_list(index, data),
+            TypeData::VirtualTableShape(data) => visitor.visit_virtual_table_shape(index, data),
+            TypeData::VirtualFunctionTable(data) => {
+                visitor.visit_virtual_function_table(index, data)
+            }
+            TypeData::VirtualFunctionTablePath(data) => {
+                visitor.visit_virtual_function_table_path(index, data)
+            }
+            TypeData::Label(data) => visitor.visit_label(index, data),
+            TypeData::Alias(data) => visitor.visit_alias(index, data),
+            TypeData::FriendFunction(data) => visitor.visit_friend_function(index, data),
+            TypeData::FriendClass(data) => visitor.visit_friend_class(index, data),
+            TypeData::Managed(data) => visitor.visit_managed(index, data),
+            TypeData::Oem(data) => visitor.visit_oem(index, data),
+            TypeData::Precompiled(data) => visitor.visit_precompiled(index, data),
+            TypeData::EndPrecompiled(data) => visitor.visit_end_precompiled(index, data),
+            TypeData::TypeServer(data) => visitor.visit_type_server(index, data),
+        }
+    }
+}
+
+impl<'t> TypeFinder<'t> {
+    /// Walks the type at `index` and passes it to `visitor`.
+    ///
+    /// If `recursive` is `true`, all types referenced by the record are walked as well, depth
+    /// first in the order returned by [`TypeData::referenced_types`]. Every type, including
+    /// primitive types, is visited at most once, even if it is referenced several times.
+    ///
+    /// All referenced types must be known by this `TypeFinder`, so it should be fully populated
+    /// before walking recursively.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    /// let mut type_finder = type_information.finder();
+    /// let mut iter = type_information.iter();
+    /// while let Some(_) = iter.next()? {
+    ///     type_finder.update(&iter);
+    /// }
+    ///
+    /// let mut visited = Vec::new();
+    /// let root = pdb::TypeIndex(0x1000);
+    /// type_finder.walk(root, true, &mut |index, _data| {
+    ///     visited.push(index);
+    ///     Ok(())
+    /// })?;
+    ///
+    /// assert_eq!(visited.first(), Some(&root));
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * any error returned by the visitor
+    /// * `Error::TypeNotFound(index)` if the type or one of the types it refers to does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if a type is not yet known by this finder
+    pub fn walk<V>(&self, index: TypeIndex, recursive: bool, visitor: &mut V) -> Result<()>
+    where
+        V: TypeVisitor<'t> + ?Sized,
+    {
+        let mut visited = HashSet::new();
+        let mut pending = vec![index];
+
+        while let Some(current) = pending.pop() {
+            if !visited.insert(current) {
+                continue;
+            }
+
+            let item = self.find(current)?;
+            match item.parse() {
+                Ok(data) => {
+                    if recursive {
+                        let start = pending.len();
+                        pending.extend(data.referenced_types());
+                        pending[start..].reverse();
+                    }
+                    visitor.visit_type(current, data)?;
+                }
+                Err(error) => visitor.visit_error(current, item.raw_kind(), error)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'s> TypeInformation<'s> {
+    /// Returns an iterator over the parsed type records of this stream, in stream order.
+    ///
+    /// The iterator yields the index and data of every type. By default, records that cannot be
+    /// parsed are returned as errors; call [`ParsedTypeIter::skip_unparseable`] to skip them
+    /// instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// let mut iter = type_information.iter_parsed().skip_unparseable();
+    /// while let Some((index, data)) = iter.next()? {
+    ///     if let Some(name) = data.name() {
+    ///         println!("{}: {}", index, name);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    pub fn iter_parsed(&self) -> ParsedTypeIter<'_> {
+        ParsedTypeIter {
+            iter: self.iter(),
+            skip_unparseable: false,
+        }
+    }
+
+    /// Parses every type record in stream order and passes it to `visitor`.
+    ///
+    /// To walk a single type and the types it references, see [`TypeFinder::walk`].
+    ///
+    /// This is a single linear pass over the stream. Unlike a [`TypeFinder`],
+    /// no index of record positions is built, so memory use does not grow with the size of the
+    /// stream. Visitors that need to look up referenced types should use a finder instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// let mut classes = 0;
+    /// type_information.visit(&mut |_index, data| {
+    ///     if let pdb::TypeData::Class(_) = data {
+    ///         classes += 1;
+    ///     }
+    ///     Ok(())
+    /// })?;
+    ///
+    /// assert!(classes > 0);
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * any error returned by the visitor
+    /// * `Error::UnexpectedEof` if the type stream is truncated
+    pub fn visit<'t, V>(&'t self, visitor: &mut V) -> Result<()>
+    where
+        V: TypeVisitor<'t> + ?Sized,
+    {
+        let mut iter = self.iter();
+        while let Some(item) = iter.next()? {
+            match item.parse() {
+                Ok(data) => visitor.visit_type(item.index(), data)?,
+                Err(error) => visitor.visit_error(item.index(), item.raw_kind(), error)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// An iterator over parsed type records, returned by [`TypeInformation::iter_parsed`].
+#[derive(Debug)]
+pub struct ParsedTypeIter<'t> {
+    iter: TypeIter<'t>,
+    skip_unparseable: bool,
+}
+
+impl<'t> ParsedTypeIter<'t> {
+    /// Skips records that cannot be parsed instead of returning an error for them.
+    ///
+    /// Errors reading the stream itself, such as a truncated record, are still returned.
+    pub fn skip_unparseable(mut self) -> Self {
+        self.skip_unparseable = true;
+        self
+    }
+}
+
+impl<'t> FallibleIterator for ParsedTypeIter<'t> {
+    type Item = (TypeIndex, TypeData<'t>);
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        while let Some(item) = self.iter.next()? {
+            match item.parse() {
+                Ok(data) => return Ok(Some((item.index(), data))),
+                Err(_) if self.skip_unparseable => continue,
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::{BTreeMap, HashMap};
+use std::marker::PhantomData;
+
+use crate::common::*;
+use crate::tpi::data::{FieldAttributes, MethodKind, TypeData};
+use crate::tpi::{TypeFinder, TypeIter};
+use crate::FallibleIterator;
+
+/// A slot in the virtual function table of a class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VirtualTableSlot<'t> {
+    /// Offset of the slot within the virtual function table, in bytes.
+    pub offset: u32,
+    /// The name of the method occupying the slot.
+    pub name: RawString<'t>,
+    /// The decorated name of the method, if the stream contains an `LF_VFTABLE` record for the
+    /// table.
+    pub decorated_name: Option<RawString<'t>>,
+    /// Access, method kind and other properties of the method.
+    pub attributes: FieldAttributes,
+    /// The `LF_MFUNCTION` type of the method.
+    pub method_type: TypeIndex,
+    /// The class declaring the method, which is either the class itself or one of its bases.
+    pub class: TypeIndex,
+}
+
+/// The members of a class that are relevant to its virtual function table.
+struct VirtualTableMembers {
+    /// Whether the class declares its own virtual function table pointer (`LF_VFUNCTAB`).
+    has_vfptr: bool,
+    /// The first non-virtual base class that has a virtual function table.
+    primary_base: Option<TypeIndex>,
+}
+
+impl<'t> TypeFinder<'t> {
+    /// Returns the slots of the primary virtual function table of a class, in table order.
+    ///
+    /// The primary table is the one referenced by the virtual function table pointer at the start
+    /// of the object. A class shares this table with its first polymorphic non-virtual base class,
+    /// unless it declares its own table pointer. Slots are introduced by methods with an explicit
+    /// table offset (see [`FieldAttributes::is_intro_virtual`]), and are taken over by overriding
+    /// methods in derived classes with the same name and parameters.
+    ///
+    /// Tables of further base classes are not included. Classes without virtual methods have an
+    /// empty table.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use pdb::FallibleIterator;
+    /// # fn test() -> pdb::Result<()> {
+    /// let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+    /// let pdb = pdb::PDB::open(file)?;
+    /// let type_information = pdb.type_information()?;
+    ///
+    /// let mut finder = type_information.finder();
+    /// let mut iter = type_information.iter();
+    /// while let Some(_) = iter.next()? {
+    ///     finder.update(&iter);
+    /// }
+    ///
+    /// // std::exception
+    /// let slots = finder.vtable_layout(pdb::TypeIndex(0x1212))?;
+    /// let names: Vec<_> = slots.iter().map(|slot| slot.name.to_string()).collect();
+    /// assert_eq!(names, ["__vecDelDtor", "what"]);
+    /// # Ok(())
+    /// # }
+    /// # test().unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `Error::NotARecordType(index)` if the type is not a class, structure or interface
+    /// * `Error::UnimplementedFeature` if a field list or method list has an unexpected type
+    /// * `Error::TypeNotFound(index)` if a type does not exist
+    /// * `Error::TypeNotIndexed(index, max_index)` if a type is not yet known by this finder
+    /// * `Error::UnimplementedTypeKind(kind)` if a type record is not understood by this library
+    pub fn vtable_layout(&self, index: TypeIndex) -> Result<Vec<VirtualTableSlot<'t>>> {
+        // collect the classes sharing the primary table, starting with the most derived one
+        let mut chain: Vec<TypeIndex> = Vec::new();
+        let mut next = Some(index);
+        while let Some(current) = next {
+            let definition = self.resolve_definition(current)?.unwrap_or(current);
+            if chain.contains(&definition) {
+                break;
+            }
+
+            let members = self.vtable_members(definition)?;
+            chain.push(definition);
+            next = if members.has_vfptr {
+                None
+            } else {
+                members.primary_base
+            };
+        }
+
+        let mut slots = BTreeMap::new();
+        for &class in chain.iter().rev() {
+            for method in self.methods(class)? {
+                let slot = VirtualTableSlot {
+                    offset: 0,
+                    name: method.name,
+                    decorated_name: None,
+                    attributes: method.attributes,
+                    method_type: method.method_type,
+                    class,
+                };
+
+                match (method.attributes.method_kind(), method.vtable_offset) {
+                    (_, Some(offset)) => {
+                        slots.insert(offset, VirtualTableSlot { offset, ..slot });
+                    }
+                    (Some(MethodKind::Virtual), None) | (Some(MethodKind::PureVirtual), None) => {
+                        let arguments = self.argument_list(method.method_type)?;
+                        for existing in slots.values_mut() {
+                            if existing.name == method.name
+                                && self.argument_list(existing.method_type)? == arguments
+                            {
+                                *existing = VirtualTableSlot {
+                                    offset: existing.offset,
+                                    ..slot
+                                };
+                                break;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut slots: Vec<_> = slots.into_values().collect();
+
+        let table = match chain.first() {
+            Some(class) => self.virtual_function_table(*class)?,
+            None => None,
+        };
+
+        if let Some(table) = table {
+            if let TypeData::VirtualFunctionTable(table) = self.find(table)?.parse()? {
+                if table.method_names.len() == slots.len() {
+                    for (slot, name) in slots.iter_mut().zip(table.method_names) {
+                        slot.decorated_name = Some(name);
+                    }
+                }
+            }
+        }
+
+        Ok(slots)
+    }
+
+    /// Returns whether a class has its own table pointer, and the base class sharing its table.
+    fn vtable_members(&self, class: TypeIndex) -> Result<VirtualTableMembers> {
+        let mut next = match self.find(class)?.parse()? {
+            TypeData::Class(data) => data.fields,
+            _ => return Err(Error::NotARecordType(class.0)),
+        };
+
+        let mut members = VirtualTableMembers {
+            has_vfptr: false,
+            primary_base: None,
+        };
+
+        while let Some(fields) = next {
+            let list = match self.find(fields)?.parse()? {
+                TypeData::FieldList(list) => list,
+                _ => return Err(Error::UnimplementedFeature("unexpected field list type")),
+            };
+
+            for field in list.fields {
+                match field {
+                    TypeData::VirtualFunctionTablePointer(_) => members.has_vfptr = true,
+                    TypeData::BaseClass(base) if members.primary_base.is_none() => {
+                        let base_class = self
+                            .resolve_definition(base.base_class)?
+                            .unwrap_or(base.base_class);
+
+                        if let TypeData::Class(data) = self.find(base_class)?.parse()? {
+                            if data.vtable_shape.is_some() {
+                                members.primary_base = Some(base_class);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            next = list.continuation;
+        }
+
+        Ok(members)
+    }
+
+    /// Returns the argument list of a member function type.
+    fn argument_list(&self, method_type: TypeIndex) -> Result<Option<TypeIndex>> {
+        Ok(match self.find(method_type)?.parse()? {
+            TypeData::MemberFunction(data) => Some(data.argument_list),
+            _ => None,
+        })
+    }
+
+    /// Returns the `LF_VFTABLE` record of the primary table of a class, if any.
+    fn virtual_function_table(&self, class: TypeIndex) -> Result<Option<TypeIndex>> {
+        let tables = match self.vftables.get() {
+            Some(tables) => tables,
+            None => {
+                let tables = self.index_vftables()?;
+                self.vftables.get_or_init(|| tables)
+            }
+        };
+
+        Ok(tables.get(&class.0).map(|&index| TypeIndex(index)))
+    }
+
+    /// Maps the owners of all primary `LF_VFTABLE` records to the indices of these records.
+    fn index_vftables(&self) -> Result<HashMap<u32, u32>> {
+        let mut tables = HashMap::new();
+
+        let mut buf = self.buffer.clone();
+        match self.positions.first() {
+            Some(&start) => buf.take(start as usize)?,
+            None => return Ok(tables),
+        };
+
+        let mut iter = TypeIter {
+            buf,
+            index: self.minimum_index,
+            _ph: PhantomData,
+        };
+
+        while let Some(item) = iter.next()? {
+            if let Ok(TypeData::VirtualFunctionTable(table)) = item.parse() {
+                if table.vfptr_offset == 0 {
+                    let owner = self.resolve_definition(table.owner)?.unwrap_or(table.owner);
+                    tables.entry(owner.0).or_insert(item.index().0);
+                }
+            }
+        }
+
+        Ok(tables)
+    }
+}
@@ -0,0 +1,365 @@
+// Copyright 2017 pdb Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use crate::common::*;
+use crate::tpi::build::BuildInfo;
+use crate::tpi::constants::*;
+use crate::tpi::id::{BuildInfoId, IdData, StringId, StringListId, UserDefinedTypeSourceFileRef};
+use crate::tpi::IdInformation;
+use crate::FallibleIterator;
+
+/// Version of the stream format written by Visual C++ 8.0 and later (`TPI80`).
+const STREAM_VERSION_V80: u32 = 20_040_203;
+
+/// Size of the stream header in bytes.
+const HEADER_SIZE: u32 = 56;
+
+/// Stream number indicating that the stream has no hash stream.
+const NO_HASH_STREAM: u16 = 0xffff;
+
+/// Number of hash buckets declared in the header, as written by the Microsoft toolchain.
+const HASH_BUCKET_COUNT: u32 = 0x3ffff;
+
+/// Maximum length of a record, excluding its length prefix.
+const MAX_RECORD_LENGTH: usize = 0xff00;
+
+/// Maximum length of a single `LF_STRING_ID`. Longer strings are split into substrings.
+const MAX_STRING_LENGTH: usize = 0xf000;
+
+/// Writes an id information stream (IPI).
+///
+/// Records are appended with [`add`](Self::add) and friends, which return the index assigned to
+/// the record. Identical records are only written once, so adding a record that already exists
+/// returns the index of the existing record. Start from an existing stream using
+/// [`IdInfoWriter::from_information`] to extend it, or from scratch using [`IdInfoWriter::new`].
+///
+/// The stream is written without a hash stream. It can be stored as stream 4 of a PDB, for
+/// instance using an [`MsfEditor`](crate::MsfEditor).
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// let mut writer = pdb::IdInfoWriter::new();
+///
+/// let build_info = writer.add_build_info(&pdb::BuildInfo {
+///     current_directory: Some("C:\\src".into()),
+///     build_tool: Some("cl.exe".into()),
+///     source_file: Some("main.cpp".into()),
+///     pdb_file: Some("vc140.pdb".into()),
+///     command_arguments: Some("-c -Zi".into()),
+/// })?;
+///
+/// let main = writer.add(&pdb::IdData::Function(pdb::FunctionId {
+///     scope: None,
+///     function_type: pdb::TypeIndex(0x1001),
+///     name: "main".into(),
+/// }))?;
+///
+/// let mut data = Vec::new();
+/// writer.write(&mut data)?;
+/// # assert!(build_info < main);
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Clone, Debug)]
+pub struct IdInfoWriter {
+    version: u32,
+    minimum_index: u32,
+    records: Vec<Vec<u8>>,
+    indices: HashMap<Vec<u8>, IdIndex>,
+}
+
+impl IdInfoWriter {
+    /// Creates a writer for an empty stream in the format of Visual C++ 8.0 and later.
+    pub fn new() -> Self {
+        Self {
+            version: STREAM_VERSION_V80,
+            minimum_index: 0x1000,
+            records: Vec::new(),
+            indices: HashMap::new(),
+        }
+    }
+
+    /// Creates a writer initialized with the records of an existing id information stream.
+    ///
+    /// Records are copied verbatim, including records that are not understood by this library.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::UnexpectedEof` if the id stream is truncated
+    pub fn from_information(information: &IdInformation<'_>) -> Result<Self> {
+        let mut writer = Self::new();
+        if !information.is_empty() {
+            writer.version = information.header.version;
+            writer.minimum_index = information.header.minimum_index;
+        }
+
+        let mut iter = information.iter();
+        while let Some(item) = iter.next()? {
+            writer.push(item.data.to_vec());
+        }
+
+        Ok(writer)
+    }
+
+    /// Returns the number of records in the stream.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns whether the stream contains no records.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Returns the index that will be assigned to the next new record.
+    pub fn next_index(&self) -> IdIndex {
+        IdIndex(self.minimum_index + self.records.len() as u32)
+    }
+
+    /// Adds a record and returns its index.
+    ///
+    /// String ids are written as given. Use [`add_string`](Self::add_string) to split long
+    /// strings into substrings automatically.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::RecordTooLarge(size)` if the record exceeds the maximum record size
+    pub fn add(&mut self, data: &IdData<'_>) -> Result<IdIndex> {
+        let record = encode_id(data)?;
+        Ok(self.push(record))
+    }
+
+    /// Adds a string id and returns its index.
+    ///
+    /// Strings that are too long for a single record are split into substrings, which are
+    /// referenced from the returned string id through a list of substrings. Use
+    /// [`IdFinder::resolve_string`](crate::ItemFinder::resolve_string) to read them back.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::RecordTooLarge(size)` if the string is too long to be split into substrings
+    pub fn add_string(&mut self, string: &[u8]) -> Result<IdIndex> {
+        let mut chunks: Vec<_> = string.chunks(MAX_STRING_LENGTH).collect();
+        let name = chunks.pop().unwrap_or_default();
+
+        let substrings = if chunks.is_empty() {
+            None
+        } else {
+            let mut substrings = Vec::with_capacity(chunks.len());
+            for chunk in chunks {
+                substrings.push(self.add_string_record(None, chunk)?);
+            }
+            Some(self.add(&IdData::StringList(StringListId { substrings }))?)
+        };
+
+        self.add_string_record(substrings, name)
+    }
+
+    /// Adds an `LF_BUILDINFO` record along with the strings it references, and returns its index.
+    ///
+    /// Missing arguments are written as null indices. Reference the build information from the
+    /// `S_BUILDINFO` symbol of the module it describes.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::RecordTooLarge(size)` if one of the strings is too long to be written
+    pub fn add_build_info(&mut self, info: &BuildInfo) -> Result<IdIndex> {
+        let strings = [
+            &info.current_directory,
+            &info.build_tool,
+            &info.source_file,
+            &info.pdb_file,
+            &info.command_arguments,
+        ];
+
+        let mut arguments = Vec::with_capacity(strings.len());
+        for string in strings {
+            arguments.push(match string {
+                Some(string) => self.add_string(string.as_bytes())?,
+                None => IdIndex(0),
+            });
+        }
+
+        self.add(&IdData::BuildInfo(BuildInfoId { arguments }))
+    }
+
+    /// Writes the id information stream.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::IoError` if returned by the `Write`
+    pub fn write<W: std::io::Write>(&self, mut writer: W) -> Result<()> {
+        let records_size: usize = self.records.iter().map(|r| r.len() + 2).sum();
+
+        let mut data = Vec::with_capacity(HEADER_SIZE as usize + records_size);
+        data.extend_from_slice(&self.version.to_le_bytes());
+        data.extend_from_slice(&HEADER_SIZE.to_le_bytes());
+        data.extend_from_slice(&self.minimum_index.to_le_bytes());
+        data.extend_from_slice(&self.next_index().0.to_le_bytes());
+        data.extend_from_slice(&(records_size as u32).to_le_bytes());
+        data.extend_from_slice(&NO_HASH_STREAM.to_le_bytes());
+        data.extend_from_slice(&NO_HASH_STREAM.to_le_bytes());
+        data.extend_from_slice(&4u32.to_le_bytes());
+        data.extend_from_slice(&HASH_BUCKET_COUNT.to_le_bytes());
+        // empty hash values, index offsets and hash adjusters
+        data.extend_from_slice(&[0; 24]);
+
+        for record in &self.records {
+            data.extend_from_slice(&(record.len() as u16).to_le_bytes());
+            data.extend_from_slice(record);
+        }
+
+        writer.write_all(&data)?;
+        Ok(())
+    }
+
+    fn add_string_record(&mut self, substrings: Option<IdIndex>, name: &[u8]) -> Result<IdIndex> {
+        self.add(&IdData::String(StringId {
+            substrings,
+            name: RawString::from(name),
+        }))
+    }
+
+    /// Appends a complete record, unless an identical record exists.
+    fn push(&mut self, record: Vec<u8>) -> IdIndex {
+        if let Some(&index) = self.indices.get(&record) {
+            return index;
+        }
+
+        let index = self.next_index();
+        self.indices.insert(record.clone(), index);
+        self.records.push(record);
+        index
+    }
+}
+
+impl Default for IdInfoWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Encodes an id record without its length prefix, padded to four bytes.
+///
+/// # Errors
+///
+/// * `Error::RecordTooLarge(size)` if the record exceeds the maximum record size
+pub(crate) fn encode_id(data: &IdData<'_>) -> Result<Vec<u8>> {
+    let mut record = Vec::new();
+    match data {
+        IdData::Function(data) => {
+            record.extend_from_slice(&LF_FUNC_ID.to_le_bytes());
+            record.extend_from_slice(&data.scope.map_or(0, |s| s.0).to_le_bytes());
+            record.extend_from_slice(&data.function_type.0.to_le_bytes());
+            push_cstring(&mut record, data.name);
+        }
+        IdData::MemberFunction(data) => {
+            record.extend_from_slice(&LF_MFUNC_ID.to_le_bytes());
+            record.extend_from_slice(&data.parent.0.to_le_bytes());
+            record.extend_from_slice(&data.function_type.0.to_le_bytes());
+            push_cstring(&mut record, data.name);
+        }
+        IdData::BuildInfo(data) => {
+            let count = u16::try_from(data.arguments.len())
+                .map_err(|_| Error::RecordTooLarge(data.arguments.len() * 4))?;
+            record.extend_from_slice(&LF_BUILDINFO.to_le_bytes());
+            record.extend_from_slice(&count.to_le_bytes());
+            for argument in &data.arguments {
+                record.extend_from_slice(&argument.0.to_le_bytes());
+            }
+        }
+        IdData::StringList(data) => {
+            record.extend_from_slice(&LF_SUBSTR_LIST.to_le_bytes());
+            record.extend_from_slice(&(data.substrings.len() as u32).to_le_bytes());
+            for substring in &data.substrings {
+                record.extend_from_slice(&substring.0.to_le_bytes());
+            }
+        }
+        IdData::String(data) => {
+            record.extend_from_slice(&LF_STRING_ID.to_le_bytes());
+            record.extend_from_slice(&data.substrings.map_or(0, |s| s.0).to_le_bytes());
+            push_cstring(&mut record, data.name);
+        }
+        IdData::UserDefinedTypeSource(data) => {
+            let leaf = match data.source_file {
+                UserDefinedTypeSourceFileRef::Local(_) => LF_UDT_SRC_LINE,
+                UserDefinedTypeSourceFileRef::Remote(..) => LF_UDT_MOD_SRC_LINE,
+            };
+            record.extend_from_slice(&leaf.to_le_bytes());
+            record.extend_from_slice(&data.udt.0.to_le_bytes());
+            match data.source_file {
+                UserDefinedTypeSourceFileRef::Local(file) => {
+                    record.extend_from_slice(&file.0.to_le_bytes());
+                    record.extend_from_slice(&data.line.to_le_bytes());
+                }
+                UserDefinedTypeSourceFileRef::Remote(module, file) => {
+                    record.extend_from_slice(&file.0.to_le_bytes());
+                    record.extend_from_slice(&data.line.to_le_bytes());
+                    record.extend_from_slice(&module.to_le_bytes());
+                }
+            }
+        }
+    }
+
+    // the length prefix and the record are aligned to four bytes
+    let padding = (4 - (record.len() + 2) % 4) % 4;
+    for remaining in (1..=padding).rev() {
+        record.push(LF_PAD0 as u8 + remaining as u8);
+    }
+
+    if record.len() > MAX_RECORD_LENGTH {
+        return Err(Error::RecordTooLarge(record.len()));
+    }
+
+    Ok(record)
+}
+
+fn push_cstring(record: &mut Vec<u8>, string: RawString<'_>) {
+    record.extend_from_slice(string.as_bytes());
+    record.push(0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_padding() {
+        let mut writer = IdInfoWriter::new();
+        let index = writer.add_string(b"ab").expect("add");
+        assert_eq!(index, IdIndex(0x1000));
+
+        // kind, substrings, name and one byte of padding after the length prefix
+        assert_eq!(
+            writer.records[0],
+            [0x05, 0x16, 0, 0, 0, 0, b'a', b'b', 0, 0xf1]
+        );
+        assert_eq!((writer.records[0].len() + 2) % 4, 0);
+
+        // identical records are deduplicated
+        assert_eq!(writer.add_string(b"ab").expect("add"), index);
+        assert_eq!(writer.len(), 1);
+    }
+
+    #[test]
+    fn test_split_string() {
+        let string = vec![b'x'; MAX_STRING_LENGTH * 2 + 5];
+
+        let mut writer = IdInfoWriter::new();
+        let index = writer.add_string(&string).expect("add");
+
+        // two identical chunks, the list of substrings and the remainder
+        assert_eq!(writer.len(), 3);
+        assert_eq!(index, IdIndex(0x1002));
+    }
+}
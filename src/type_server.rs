@@ -0,0 +1,177 @@
+//! Resolution of external type server PDBs.
+//!
+//! Object files compiled with `/Zi` do not contain their own type records. Instead, their
+//! `.debug$T` section holds a single `LF_TYPESERVER2` record naming the PDB written by the compiler
+//! (such as `vc140.pdb`), which holds the types of all objects compiled into it. Type indices
+//! referenced by such a module are indices into the type stream of that PDB.
+//!
+//! This module provides a hook for locating these PDBs, and a cache serving their type streams.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::common::*;
+use crate::pdb::PDB;
+use crate::pdbi::PdbIdentifier;
+use crate::tpi::{TypeInformation, TypeServerType};
+
+/// Locates the type server PDB referenced by an `LF_TYPESERVER` or `LF_TYPESERVER2` record.
+///
+/// Implementations return the type stream of the PDB, or `None` if it cannot be found. Closures
+/// with a matching signature implement this trait, which allows looking up PDBs in archives or on
+/// symbol servers.
+pub trait TypeServerResolver<'s> {
+    /// Returns the type information of the given type server.
+    fn resolve(&mut self, server: &TypeServerType<'_>) -> Result<Option<TypeInformation<'s>>>;
+}
+
+impl<'s, F> TypeServerResolver<'s> for F
+where
+    F: FnMut(&TypeServerType<'_>) -> Result<Option<TypeInformation<'s>>>,
+{
+    fn resolve(&mut self, server: &TypeServerType<'_>) -> Result<Option<TypeInformation<'s>>> {
+        self(server)
+    }
+}
+
+/// Resolves type servers by opening PDB files from the file system.
+///
+/// The path recorded in the type server record is tried first. As this is the path the PDB had on
+/// the build machine, the file name is then looked up in each of the search paths, in order. Only
+/// PDBs matching the GUID or signature and age of the record are accepted.
+#[derive(Clone, Debug, Default)]
+pub struct FileTypeServerResolver {
+    search_paths: Vec<PathBuf>,
+}
+
+impl FileTypeServerResolver {
+    /// Creates a resolver that only tries the paths recorded in type server records.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a directory in which to look for type server PDBs.
+    pub fn add_search_path<P>(&mut self, path: P) -> &mut Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.search_paths.push(path.into());
+        self
+    }
+
+    /// Returns the paths at which the type server PDB is looked for, in order.
+    pub fn candidates(&self, server: &TypeServerType<'_>) -> Vec<PathBuf> {
+        let name = server.name.to_string();
+        let mut candidates = vec![PathBuf::from(name.as_ref())];
+
+        // the recorded path usually uses Windows separators
+        let file_name = name.rsplit(['\\', '/']).next().unwrap_or_default();
+        if !file_name.is_empty() {
+            for directory in &self.search_paths {
+                candidates.push(directory.join(file_name));
+            }
+        }
+
+        candidates
+    }
+
+    fn open(path: &Path, identifier: &PdbIdentifier) -> Result<Option<TypeInformation<'static>>> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let pdb = PDB::open(file)?;
+        if !pdb.matches_identifier(identifier)? {
+            return Ok(None);
+        }
+
+        pdb.type_information().map(Some)
+    }
+}
+
+impl TypeServerResolver<'static> for FileTypeServerResolver {
+    fn resolve(&mut self, server: &TypeServerType<'_>) -> Result<Option<TypeInformation<'static>>> {
+        for path in self.candidates(server) {
+            if let Some(types) = Self::open(&path, &server.identifier)? {
+                return Ok(Some(types));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// A cache of type server PDBs, opened on demand through a [`TypeServerResolver`].
+///
+/// Every type server is resolved at most once, even if it could not be found.
+///
+/// # Example
+///
+/// ```
+/// # fn test() -> pdb::Result<()> {
+/// let mut resolver = pdb::FileTypeServerResolver::new();
+/// resolver.add_search_path("fixtures/self");
+/// let mut servers = pdb::TypeServers::new(resolver);
+///
+/// // as found in the `.debug$T` section of an object file compiled with /Zi
+/// let server = pdb::TypeServerType {
+///     identifier: pdb::PdbIdentifier::Guid {
+///         guid: "2B3C3FA5-5A2E-44B8-8BBA-C3300FF69F62".parse().unwrap(),
+///         age: 1,
+///     },
+///     name: pdb::RawString::from("C:\\build\\foo.pdb"),
+/// };
+///
+/// let types = servers.type_information(&server)?.expect("type server");
+/// assert!(!types.is_empty());
+/// # Ok(())
+/// # }
+/// # test().unwrap()
+/// ```
+#[derive(Debug)]
+pub struct TypeServers<'s, R> {
+    resolver: R,
+    servers: HashMap<PdbIdentifier, Option<TypeInformation<'s>>>,
+}
+
+impl<'s, R> TypeServers<'s, R>
+where
+    R: TypeServerResolver<'s>,
+{
+    /// Creates an empty cache using the given resolver.
+    pub fn new(resolver: R) -> Self {
+        Self {
+            resolver,
+            servers: HashMap::new(),
+        }
+    }
+
+    /// Returns the type information of the given type server, resolving it if necessary.
+    ///
+    /// Returns `None` if the resolver could not find the type server.
+    ///
+    /// # Errors
+    ///
+    /// Errors returned by the resolver are passed through. The type server is resolved again on
+    /// the next call.
+    pub fn type_information(
+        &mut self,
+        server: &TypeServerType<'_>,
+    ) -> Result<Option<&TypeInformation<'s>>> {
+        if !self.servers.contains_key(&server.identifier) {
+            let types = self.resolver.resolve(server)?;
+            self.servers.insert(server.identifier, types);
+        }
+
+        Ok(self.servers[&server.identifier].as_ref())
+    }
+
+    /// Returns the resolver used by this cache.
+    pub fn resolver(&self) -> &R {
+        &self.resolver
+    }
+}
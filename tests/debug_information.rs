@@ -2,7 +2,7 @@
 fn pdb_info() {
     let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
 
-    let mut pdb = pdb::PDB::open(file).expect("opening pdb");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
     let pdb_info = pdb.debug_information().expect("pdb information");
 
     assert_eq!(
@@ -10,3 +10,484 @@ fn pdb_info() {
         pdb::MachineType::Amd64
     );
 }
+
+#[test]
+fn dbi_header() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let header = pdb.debug_information().expect("dbi").header();
+
+    assert_eq!(header.toolchain_version(), Some((14, 0)));
+    assert_eq!(header.pdb_dll_build_version, 24210);
+    assert_eq!(header.pdb_dll_rbld_version, 0);
+    assert_eq!(header.age, 1);
+    assert_eq!(header.machine_type, 0x8664);
+    assert!(header.flags.incremental_link());
+    assert!(!header.flags.stripped());
+    assert!(!header.flags.ctypes());
+}
+
+#[test]
+fn section_map() {
+    use pdb::FallibleIterator;
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let dbi = pdb.debug_information().expect("dbi");
+    let section_map = dbi.section_map().expect("section map");
+
+    assert_eq!(section_map.segments(), 9);
+    assert_eq!(section_map.logical_segments(), 9);
+
+    let entries: Vec<_> = section_map.iter().collect().expect("entries");
+    assert_eq!(entries.len(), 9);
+
+    // .text
+    let text = entries[0];
+    assert_eq!(text.frame, 1);
+    assert_eq!(text.section_length, 0x5afb4);
+    assert!(text.flags.read() && text.flags.execute() && !text.flags.write());
+    assert!(text.flags.address_is_32_bit() && text.flags.is_selector());
+    assert!(text.contains(0x5afb3));
+    assert!(!text.contains(0x5afb4));
+
+    // .data
+    let data = section_map.find_frame(3).expect("find").expect("data");
+    assert!(data.flags.read() && data.flags.write() && !data.flags.execute());
+
+    // the final entry covers absolute addresses
+    assert!(entries[8].flags.is_absolute_address());
+    assert_eq!(section_map.find_frame(42).expect("find"), None);
+}
+
+#[test]
+fn ec_names() {
+    use pdb::FallibleIterator;
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let dbi = pdb.debug_information().expect("dbi");
+    let ec_names = dbi.ec_names().expect("ec names");
+
+    let modules: Vec<_> = dbi.modules().expect("modules").collect().expect("collect");
+
+    // assembler modules only record their source file
+    let memset = &modules[32];
+    let source_file = memset.ec_source_file().expect("source file");
+    assert_eq!(
+        ec_names.get(source_file).expect("name").to_string(),
+        "f:\\dd\\vctools\\crt\\vcruntime\\src\\string\\amd64\\memset.asm"
+    );
+    assert_eq!(memset.ec_pdb_file(), None);
+
+    let linker = &modules[193];
+    assert_eq!(linker.module_name(), "* Linker *");
+    assert_eq!(linker.ec_source_file(), None);
+    let pdb_file = linker.ec_pdb_file().expect("pdb file");
+    assert_eq!(
+        ec_names.get(pdb_file).expect("name").to_string(),
+        "c:\\Users\\User\\Desktop\\self\\foo.pdb"
+    );
+}
+
+#[test]
+fn type_server_map() {
+    use pdb::FallibleIterator;
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let dbi = pdb.debug_information().expect("dbi");
+    let type_server_map = dbi.type_server_map().expect("type server map");
+
+    assert!(type_server_map.is_empty());
+    assert_eq!(type_server_map.as_bytes(), &[] as &[u8]);
+    assert_eq!(type_server_map.mfc_type_server_index(), None);
+
+    let mut modules = dbi.modules().expect("modules");
+    while let Some(module) = modules.next().expect("module") {
+        assert_eq!(module.type_server_index(), None);
+    }
+}
+
+#[test]
+fn extra_streams() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let extra = pdb
+        .debug_information()
+        .expect("dbi")
+        .extra_streams()
+        .expect("extra streams");
+
+    assert_eq!(extra.section_headers, pdb::StreamIndex(11));
+    assert!(extra.fpo.is_none());
+    assert!(extra.fixup.is_none());
+    assert!(extra.omap_from_src.is_none());
+    assert!(extra.original_section_headers.is_none());
+
+    let sections = pdb.sections().expect("sections").expect("section headers");
+    assert_eq!(sections.len(), 8);
+
+    assert!(pdb
+        .original_sections()
+        .expect("original sections")
+        .is_none());
+    assert!(pdb.omap_from_src().expect("omap from src").is_none());
+    assert!(pdb.omap_to_src().expect("omap to src").is_none());
+    assert!(pdb.fixups().expect("fixups").is_none());
+    assert!(pdb.token_rid_map().expect("token rid map").is_none());
+    assert!(pdb.exception_data().expect("exception data").is_none());
+    assert!(pdb.xdata().expect("xdata").is_none());
+    assert!(pdb.pdata().expect("pdata").is_none());
+}
+
+#[test]
+fn file_info() {
+    use pdb::FallibleIterator;
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let dbi = pdb.debug_information().expect("dbi");
+    let file_info = dbi.file_info().expect("file info");
+
+    assert_eq!(file_info.module_count(), 194);
+    assert_eq!(file_info.file_count(), 3865);
+
+    let files: Vec<_> = file_info
+        .module_files(0)
+        .expect("module 0")
+        .collect()
+        .expect("files");
+    assert_eq!(files.len(), 5);
+    assert_eq!(
+        files[0].to_string(),
+        "c:\\users\\user\\desktop\\self\\foo.cpp"
+    );
+
+    // the linker module has no source files
+    let mut linker_files = file_info.module_files(193).expect("module 193");
+    assert!(linker_files.next().expect("next").is_none());
+    assert!(file_info.module_files(194).is_none());
+
+    assert_eq!(file_info.files().count().expect("count"), 3865);
+    let unique = file_info.unique_files().expect("unique files");
+    assert_eq!(unique.len(), 287);
+    assert_eq!(unique[0], files[0]);
+}
+
+#[test]
+fn module_lookup() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let dbi = pdb.debug_information().expect("dbi");
+
+    assert_eq!(dbi.module_count().expect("count"), 194);
+
+    let module = dbi.module(2).expect("module").expect("module 2");
+    assert_eq!(
+        module.module_name(),
+        "f:\\binaries\\Intermediate\\vctools\\libcmt.nativeproj__851063217\\objr\\amd64\\exe_main.obj"
+    );
+    let linker = dbi.module(193).expect("module").expect("module 193");
+    assert_eq!(linker.module_name(), "* Linker *");
+    assert!(dbi.module(194).expect("module").is_none());
+
+    let index = |name| dbi.module_by_name(name).expect("lookup").map(|(i, _)| i);
+    assert_eq!(index("c:\\Users\\User\\Desktop\\self\\foo.obj"), Some(0));
+    assert_eq!(index("C:/users/user/desktop/self/FOO.OBJ"), Some(0));
+    assert_eq!(index("foo.obj"), Some(0));
+    assert_eq!(index("exe_main.obj"), Some(2));
+    assert_eq!(index("LIBCMT.lib(exe_main.obj)"), Some(2));
+    assert_eq!(index("libucrt.lib(log10.obj)"), Some(156));
+    assert_eq!(index("* Linker *"), Some(193));
+    assert_eq!(index("Import:KERNEL32.dll"), Some(192));
+    assert_eq!(index("libucrt.lib(exe_main.obj)"), None);
+    assert_eq!(index("bar.obj"), None);
+}
+
+#[test]
+fn module_at() {
+    use pdb::PdbInternalSectionOffset;
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let dbi = pdb.debug_information().expect("dbi");
+    let module_at = |section, offset| {
+        dbi.module_at(PdbInternalSectionOffset::new(section, offset))
+            .expect("module_at")
+    };
+
+    // the contribution of foo.obj spans 0x54f0..0x5580
+    assert_eq!(module_at(1, 0x54f0), Some(0));
+    assert_eq!(module_at(1, 0x557f), Some(0));
+    assert_eq!(module_at(1, 0x5580), None);
+    assert_eq!(module_at(1, 0x55b0), Some(0));
+
+    // incremental linking thunks are contributed by the linker
+    assert_eq!(module_at(1, 0), Some(193));
+    assert_eq!(module_at(0, 0), None);
+    assert_eq!(module_at(42, 0), None);
+}
+
+#[test]
+fn write_debug_information() {
+    use pdb::FallibleIterator;
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let dbi = pdb.debug_information().expect("dbi");
+
+    let mut writer = pdb::DebugInfoWriter::from_information(&dbi).expect("writer");
+    writer.set_age(2);
+    let mut data = Vec::new();
+    writer.write(&mut data).expect("write");
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let mut editor = pdb::MsfEditor::open(file).expect("opening editor");
+    editor.replace_stream(3, data).expect("replace stream");
+    let mut output = Vec::new();
+    editor.write(&mut output).expect("write pdb");
+
+    let written = pdb::PDB::open(std::io::Cursor::new(output)).expect("opening pdb");
+    let written_dbi = written.debug_information().expect("dbi");
+
+    let (original, header) = (dbi.header(), written_dbi.header());
+    assert_eq!(header.age, 2);
+    assert_eq!(header.toolchain_version(), original.toolchain_version());
+    assert_eq!(header.pdb_dll_build_version, original.pdb_dll_build_version);
+    assert_eq!(header.symbol_records_stream, original.symbol_records_stream);
+    assert_eq!(header.flags, original.flags);
+    assert_eq!(header.machine_type, original.machine_type);
+
+    let modules: Vec<_> = dbi.modules().expect("modules").collect().expect("modules");
+    let written_modules: Vec<_> = written_dbi
+        .modules()
+        .expect("modules")
+        .collect()
+        .expect("modules");
+    assert_eq!(modules.len(), written_modules.len());
+    for (module, written_module) in modules.iter().zip(&written_modules) {
+        assert_eq!(
+            pdb::DBIModuleEntry::from_module(module),
+            pdb::DBIModuleEntry::from_module(written_module)
+        );
+    }
+
+    let files: Vec<_> = dbi
+        .file_info()
+        .expect("file info")
+        .files()
+        .collect()
+        .expect("files");
+    let written_files: Vec<_> = written_dbi
+        .file_info()
+        .expect("file info")
+        .files()
+        .collect()
+        .expect("files");
+    assert_eq!(files, written_files);
+
+    let contributions: Vec<_> = dbi
+        .section_contributions()
+        .expect("contributions")
+        .collect()
+        .expect("contributions");
+    let written_contributions: Vec<_> = written_dbi
+        .section_contributions()
+        .expect("contributions")
+        .collect()
+        .expect("contributions");
+    assert_eq!(contributions, written_contributions);
+
+    let section_map: Vec<_> = dbi
+        .section_map()
+        .expect("section map")
+        .iter()
+        .collect()
+        .expect("entries");
+    let written_section_map: Vec<_> = written_dbi
+        .section_map()
+        .expect("section map")
+        .iter()
+        .collect()
+        .expect("entries");
+    assert_eq!(section_map, written_section_map);
+
+    let linker = &written_modules[193];
+    let ec_names = written_dbi.ec_names().expect("ec names");
+    let pdb_file = ec_names.get(linker.ec_pdb_file().expect("pdb file"));
+    assert_eq!(
+        pdb_file.expect("name").to_string(),
+        "c:\\Users\\User\\Desktop\\self\\foo.pdb"
+    );
+
+    let extra = written_dbi.extra_streams().expect("extra streams");
+    assert_eq!(extra.section_headers, pdb::StreamIndex(11));
+    assert_eq!(
+        written.sections().expect("sections").map(|s| s.len()),
+        Some(8)
+    );
+
+    let info = written
+        .module_info(&written_modules[0])
+        .expect("module info");
+    assert!(
+        info.expect("module 0")
+            .symbols()
+            .expect("symbols")
+            .count()
+            .expect("count")
+            > 0
+    );
+}
+
+#[test]
+fn write_new_debug_information() {
+    use pdb::FallibleIterator;
+
+    let mut module = pdb::DBIModuleEntry::new(b"c:\\src\\main.obj", b"c:\\src\\main.obj");
+    module.source_files = vec![b"c:\\src\\main.cpp".to_vec(), b"c:\\src\\main.h".to_vec()];
+    let mut other = pdb::DBIModuleEntry::new(b"c:\\src\\other.obj", b"c:\\src\\other.obj");
+    other.source_files = vec![b"c:\\src\\main.h".to_vec()];
+
+    let contribution = pdb::DBISectionContribution {
+        offset: pdb::PdbInternalSectionOffset::new(1, 0x10),
+        size: 0x20,
+        characteristics: pdb::SectionCharacteristics(0x6000_0020),
+        module: 1,
+        data_crc: 0,
+        reloc_crc: 0,
+        coff_section: Some(3),
+    };
+    let entry = pdb::DBISectionMapEntry {
+        flags: pdb::DBISectionMapFlags(0x10d),
+        overlay: 0,
+        group: 0,
+        frame: 1,
+        section_name: 0xffff,
+        class_name: 0xffff,
+        offset: 0,
+        section_length: 0x1000,
+    };
+    let extra_streams = pdb::DBIExtraStreams {
+        section_headers: pdb::StreamIndex(4),
+        ..Default::default()
+    };
+
+    let mut writer = pdb::DebugInfoWriter::new();
+    assert_eq!(writer.add_module(module), 0);
+    assert_eq!(writer.add_module(other), 1);
+    writer
+        .set_machine_type(0x8664)
+        .set_toolchain_version(14, 10)
+        .set_pdb_dll_version(25_017, 1)
+        .set_flags(pdb::DBIFlags(0x2))
+        .set_extra_streams(extra_streams)
+        .add_section_contribution(contribution)
+        .add_section_map_entry(entry);
+
+    let mut data = Vec::new();
+    writer.write(&mut data).expect("write");
+
+    let mut msf = pdb::MsfWriter::new();
+    msf.add_stream(Vec::new());
+    msf.add_stream(Vec::new());
+    msf.add_stream(Vec::new());
+    msf.add_stream(data);
+    let mut file = Vec::new();
+    msf.write(&mut file).expect("write msf");
+
+    let pdb = pdb::PDB::open(std::io::Cursor::new(file)).expect("opening pdb");
+    let dbi = pdb.debug_information().expect("dbi");
+
+    let header = dbi.header();
+    assert_eq!(
+        dbi.machine_type().expect("machine type"),
+        pdb::MachineType::Amd64
+    );
+    assert_eq!(header.toolchain_version(), Some((14, 10)));
+    assert_eq!(header.pdb_dll_build_version, 25_017);
+    assert_eq!(header.pdb_dll_rbld_version, 1);
+    assert!(header.flags.stripped());
+
+    assert_eq!(dbi.module_count().expect("count"), 2);
+    let (index, module) = dbi
+        .module_by_name("other.obj")
+        .expect("lookup")
+        .expect("module");
+    assert_eq!(index, 1);
+    assert_eq!(module.object_file_name(), "c:\\src\\other.obj");
+    assert_eq!(pdb::DBIModuleEntry::from_module(&module).section, None);
+
+    let file_info = dbi.file_info().expect("file info");
+    let files: Vec<_> = file_info
+        .module_files(0)
+        .expect("files")
+        .collect()
+        .expect("files");
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[1].as_bytes(), b"c:\\src\\main.h");
+    assert_eq!(file_info.unique_files().expect("unique files").len(), 2);
+
+    let contributions: Vec<_> = dbi
+        .section_contributions()
+        .expect("contributions")
+        .collect()
+        .expect("contributions");
+    assert_eq!(contributions, vec![contribution]);
+    let address = pdb::PdbInternalSectionOffset::new(1, 0x2f);
+    assert_eq!(dbi.module_at(address).expect("module at"), Some(1));
+
+    let section_map = dbi.section_map().expect("section map");
+    assert_eq!(section_map.find_frame(1).expect("find"), Some(entry));
+
+    let extra = dbi.extra_streams().expect("extra streams");
+    assert_eq!(extra.section_headers, pdb::StreamIndex(4));
+    assert!(extra.fpo.is_none());
+
+    let ec_names = dbi.ec_names().expect("ec names");
+    assert_eq!(
+        ec_names.get(pdb::StringRef(0)).expect("empty").as_bytes(),
+        b""
+    );
+    assert!(dbi.type_server_map().expect("type server map").is_empty());
+}
+
+#[test]
+fn write_oversized_debug_information() {
+    let contribution = pdb::DBISectionContribution {
+        offset: pdb::PdbInternalSectionOffset::new(1, 0),
+        size: 0x10,
+        characteristics: pdb::SectionCharacteristics(0x6000_0020),
+        module: 0x1_0000,
+        data_crc: 0,
+        reloc_crc: 0,
+        coff_section: None,
+    };
+
+    // module indices are stored in 16 bits
+    let mut writer = pdb::DebugInfoWriter::new();
+    writer.add_section_contribution(contribution);
+    assert!(matches!(
+        writer.write(&mut Vec::new()),
+        Err(pdb::Error::UnimplementedFeature(_))
+    ));
+
+    // so are the numbers of source files of each module
+    let mut module = pdb::DBIModuleEntry::new(b"main.obj", b"main.obj");
+    module.source_files = vec![b"main.cpp".to_vec(); 0x1_0000];
+    let mut writer = pdb::DebugInfoWriter::new();
+    writer.add_module(module);
+    assert!(matches!(
+        writer.write(&mut Vec::new()),
+        Err(pdb::Error::UnimplementedFeature(_))
+    ));
+}
@@ -1,6 +1,6 @@
-//! Tests that IdInformation works on files where the IPI is missing (empty stream).
+//! Tests for IdInformation, including files where the IPI is missing (empty stream).
 
-use pdb::{FallibleIterator, IdIndex, PDB};
+use pdb::{FallibleIterator, IdData, IdIndex, PDB};
 
 fn open_file() -> std::fs::File {
     let path = "fixtures/symbol_server/0ea7c70545374958ad3307514bdfc8642-wntdll.pdb";
@@ -9,7 +9,7 @@ fn open_file() -> std::fs::File {
 
 #[test]
 fn test_missing_ipi() {
-    let mut pdb = PDB::open(open_file()).expect("opening pdb");
+    let pdb = PDB::open(open_file()).expect("opening pdb");
 
     let id_information = pdb.id_information().expect("get id information");
 
@@ -27,3 +27,537 @@ fn test_missing_ipi() {
     finder.find(IdIndex(0)).expect_err("find index");
     finder.find(IdIndex(4097)).expect_err("find index");
 }
+
+#[test]
+fn test_parse_all_ids() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("opening pdb");
+    let id_information = pdb.id_information().expect("get id information");
+    let mut finder = id_information.finder();
+
+    let mut count = 0;
+    let mut iter = id_information.iter();
+    while let Some(id) = iter.next().expect("next id") {
+        finder.update(&iter);
+        let data = id.parse().expect("parse id");
+
+        // references always point to earlier records
+        for index in data.referenced_ids() {
+            assert!(index < id.index());
+        }
+
+        // substring lists refer to strings
+        if let IdData::StringList(list) = data {
+            for index in list.substrings {
+                let substring = finder.find(index).expect("find").parse().expect("parse");
+                assert!(matches!(substring, IdData::String(_)));
+            }
+        }
+
+        count += 1;
+    }
+
+    assert_eq!(count, id_information.len());
+}
+
+#[test]
+fn test_build_info() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("opening pdb");
+    let id_information = pdb.id_information().expect("get id information");
+    let mut finder = id_information.finder();
+    let mut iter = id_information.iter();
+    while iter.next().expect("next id").is_some() {
+        finder.update(&iter);
+    }
+
+    let debug_information = pdb.debug_information().expect("debug information");
+    let mut modules = debug_information.modules().expect("modules");
+    let module = modules.next().expect("next module").expect("module");
+    let info = pdb
+        .module_info(&module)
+        .expect("module info")
+        .expect("module info present");
+
+    let id = info.build_info_id().expect("build info id");
+    assert_eq!(id, Some(IdIndex(0x101d)));
+
+    let build_info = finder.build_info(IdIndex(0x101d)).expect("build info");
+    assert_eq!(
+        build_info.current_directory.as_deref(),
+        Some("c:\\Users\\User\\Desktop\\self")
+    );
+    assert!(build_info
+        .build_tool
+        .as_deref()
+        .expect("build tool")
+        .ends_with("\\cl.EXE"));
+    assert_eq!(build_info.source_file.as_deref(), Some("foo.cpp"));
+    assert_eq!(
+        build_info.pdb_file.as_deref(),
+        Some("c:\\Users\\User\\Desktop\\self\\vc140.pdb")
+    );
+
+    // the command line is split across three string records
+    let arguments = build_info.command_arguments.expect("command arguments");
+    assert!(arguments.starts_with("-Od -GR -Zi -MT"));
+    assert!(arguments.contains("\\include\\10.0.14393.0\\winrt\" -TP -X"));
+    assert!(arguments.ends_with("-TP -X"));
+
+    assert!(matches!(
+        finder.build_info(IdIndex(0x1015)),
+        Err(pdb::Error::UnexpectedIdKind(0x1015))
+    ));
+}
+
+#[test]
+fn test_resolve_string() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("opening pdb");
+    let id_information = pdb.id_information().expect("get id information");
+    let mut finder = id_information.finder();
+    let mut iter = id_information.iter();
+    while iter.next().expect("next id").is_some() {
+        finder.update(&iter);
+    }
+
+    // strings that are not split are borrowed
+    let string = finder.resolve_string(IdIndex(0x1017)).expect("resolve");
+    assert!(matches!(string, std::borrow::Cow::Borrowed(b"foo.cpp")));
+
+    // 0x103a is preceded by twelve substrings in 0x1039
+    let mut expected = Vec::new();
+    for index in 0x102d..=0x1038 {
+        match finder.find(IdIndex(index)).expect("find").parse() {
+            Ok(IdData::String(string)) => expected.extend_from_slice(string.name.as_bytes()),
+            other => panic!("unexpected id {:?}", other),
+        }
+    }
+    expected.extend_from_slice(b" -w14619 -w14905");
+
+    let string = finder.resolve_string(IdIndex(0x103a)).expect("resolve");
+    assert!(string.starts_with(&expected));
+    assert!(string.ends_with(b"-Binl -X"));
+
+    assert!(matches!(
+        finder.resolve_string(IdIndex(0x1039)),
+        Err(pdb::Error::UnexpectedIdKind(0x1039))
+    ));
+}
+
+#[test]
+fn test_function_symbols() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("opening pdb");
+    let id_information = pdb.id_information().expect("get id information");
+    let debug_information = pdb.debug_information().expect("debug information");
+
+    let mut functions = pdb::FunctionSymbols::new(&id_information).expect("function symbols");
+    let mut modules = debug_information.modules().expect("modules");
+    let mut index = 0;
+    while let Some(module) = modules.next().expect("next module") {
+        if let Some(info) = pdb.module_info(&module).expect("module info") {
+            functions.add_module(index, &info).expect("add module");
+        }
+        index += 1;
+    }
+
+    // global function `main`
+    let main = pdb::ProcedureLocation {
+        module: 0,
+        symbol: pdb::SymbolIndex(0x298),
+    };
+    assert_eq!(functions.procedures_of(IdIndex(0x1005)), [main]);
+    assert_eq!(functions.function_of(main), Some(IdIndex(0x1005)));
+
+    // member function `Baz::f_public`
+    let f_public = pdb::ProcedureLocation {
+        module: 0,
+        symbol: pdb::SymbolIndex(0x6c),
+    };
+    assert_eq!(functions.function_of(f_public), Some(IdIndex(0x1000)));
+
+    // template arguments are not part of the function id name
+    let template = pdb::ProcedureLocation {
+        module: 0,
+        symbol: pdb::SymbolIndex(0x318),
+    };
+    assert_eq!(functions.function_of(template), Some(IdIndex(0x101e)));
+
+    // every match refers back to the same function
+    for index in 0x1000..0x1000 + id_information.len() as u32 {
+        for &location in functions.procedures_of(IdIndex(index)) {
+            assert_eq!(functions.function_of(location), Some(IdIndex(index)));
+        }
+    }
+}
+
+#[test]
+fn test_inlinee_resolver() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("opening pdb");
+    let string_table = pdb.string_table().expect("string table");
+
+    let type_information = pdb.type_information().expect("type information");
+    let mut type_finder = type_information.finder();
+    let mut types = type_information.iter();
+    while types.next().expect("next type").is_some() {
+        type_finder.update(&types);
+    }
+
+    let id_information = pdb.id_information().expect("id information");
+    let mut id_finder = id_information.finder();
+    let mut ids = id_information.iter();
+    while ids.next().expect("next id").is_some() {
+        id_finder.update(&ids);
+    }
+
+    let debug_information = pdb.debug_information().expect("debug information");
+    let mut modules = debug_information.modules().expect("modules");
+    let module = modules.nth(2).expect("module").expect("module 2");
+    let info = pdb
+        .module_info(&module)
+        .expect("module info")
+        .expect("module info 2");
+
+    let mut resolver = pdb::InlineeResolver::new(&info, &id_finder).expect("resolver");
+
+    // without a string table, the file is unknown
+    let inlinee = resolver.resolve(IdIndex(0x1064)).expect("resolve");
+    assert_eq!(inlinee.name, "invoke_main");
+    assert_eq!(inlinee.file, None);
+    assert_eq!(inlinee.line, Some(64));
+
+    resolver
+        .set_string_table(&string_table)
+        .set_type_finder(&type_finder);
+
+    let inlinee = resolver.resolve(IdIndex(0x1064)).expect("resolve");
+    assert_eq!(
+        inlinee.file.expect("file").to_string(),
+        "f:\\dd\\vctools\\crt\\vcstartup\\src\\startup\\exe_common.inl"
+    );
+
+    // member functions are qualified with their class
+    let inlinee = resolver.resolve(IdIndex(0x107a)).expect("resolve");
+    assert_eq!(inlinee.name, "__scrt_narrow_argv_policy::configure_argv");
+    assert_eq!(inlinee.line, Some(378));
+
+    // every inline site of the module resolves
+    let mut symbols = info.symbols().expect("symbols");
+    let mut count = 0;
+    while let Some(symbol) = symbols.next().expect("next symbol") {
+        if let Ok(pdb::SymbolData::InlineSite(site)) = symbol.parse() {
+            let inlinee = resolver.resolve(site.inlinee).expect("resolve");
+            assert!(inlinee.file.is_some());
+            assert!(resolver.inlinee(site.inlinee).is_some());
+            count += 1;
+        }
+    }
+    assert_eq!(count, 4);
+
+    // build information is not a function
+    assert!(matches!(
+        resolver.resolve(IdIndex(0x101d)),
+        Err(pdb::Error::UnexpectedIdKind(0x101d))
+    ));
+}
+
+#[test]
+fn test_write_ids() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("opening pdb");
+    let id_information = pdb.id_information().expect("get id information");
+
+    let mut writer = pdb::IdInfoWriter::from_information(&id_information).expect("writer");
+    assert_eq!(writer.len(), id_information.len());
+
+    // existing records are reused
+    let main = IdData::Function(pdb::FunctionId {
+        scope: None,
+        function_type: pdb::TypeIndex(0x100a),
+        name: "main".into(),
+    });
+    assert_eq!(writer.add(&main).expect("add"), IdIndex(0x1005));
+
+    let long_arguments = "-DLONG ".repeat(0x4000);
+    let build_info = pdb::BuildInfo {
+        current_directory: Some("C:\\src".into()),
+        build_tool: Some("C:\\bin\\cl.exe".into()),
+        source_file: Some("main.cpp".into()),
+        pdb_file: None,
+        command_arguments: Some(long_arguments.clone()),
+    };
+    let index = writer.add_build_info(&build_info).expect("add build info");
+    assert!(index.0 >= 0x1000 + id_information.len() as u32);
+
+    let mut data = Vec::new();
+    writer.write(&mut data).expect("write");
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let mut editor = pdb::MsfEditor::open(file).expect("opening editor");
+    editor.replace_stream(4, data).expect("replace stream");
+    let mut output = Vec::new();
+    editor.write(&mut output).expect("write pdb");
+
+    let pdb = PDB::open(std::io::Cursor::new(output)).expect("opening pdb");
+    let written = pdb.id_information().expect("get id information");
+    assert_eq!(written.len(), writer.len());
+
+    // original records are preserved
+    let mut original = id_information.iter();
+    let mut iter = written.iter();
+    while let Some(id) = original.next().expect("next id") {
+        let copy = iter.next().expect("next id").expect("id");
+        assert_eq!(copy.index(), id.index());
+        assert_eq!(copy.parse().expect("parse"), id.parse().expect("parse"));
+    }
+
+    let mut finder = written.finder();
+    let mut iter = written.iter();
+    while iter.next().expect("next id").is_some() {
+        finder.update(&iter);
+    }
+    assert_eq!(finder.build_info(index).expect("build info"), build_info);
+}
+
+#[test]
+fn test_id_hashes() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("opening pdb");
+    let id_information = pdb.id_information().expect("id information");
+    let hashes = pdb
+        .id_hashes()
+        .expect("id hashes")
+        .expect("hash stream exists");
+    assert_eq!(hashes.buckets(), 0x3ffff);
+
+    assert_eq!(
+        hashes
+            .find_string(&id_information, b"foo.cpp")
+            .expect("find string"),
+        Some(IdIndex(0x1017))
+    );
+    assert_eq!(
+        hashes
+            .find_string(&id_information, b"bar.cpp")
+            .expect("find string"),
+        None
+    );
+
+    assert_eq!(
+        hashes
+            .find_udt_source(&id_information, pdb::TypeIndex(0x101a))
+            .expect("find udt source"),
+        Some(IdIndex(0x1007))
+    );
+    assert_eq!(
+        hashes
+            .find_udt_source(&id_information, pdb::TypeIndex(0x1000))
+            .expect("find udt source"),
+        None
+    );
+
+    // records are found by their contents
+    let ids: Vec<_> = id_information.iter().collect().expect("collect ids");
+    for id in ids.iter().step_by(97) {
+        let data = id.parse().expect("parse");
+        assert_eq!(
+            hashes.find(&id_information, &data).expect("find"),
+            Some(id.index())
+        );
+    }
+}
+
+#[test]
+fn test_validate_id_references() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("opening pdb");
+    let id_information = pdb.id_information().expect("id information");
+    let debug_information = pdb.debug_information().expect("debug information");
+
+    let mut validator = pdb::IdReferenceValidator::new(&id_information).expect("validator");
+    let mut modules = debug_information.modules().expect("modules");
+    let mut index = 0;
+    while let Some(module) = modules.next().expect("next module") {
+        if let Some(info) = pdb.module_info(&module).expect("module info") {
+            validator.add_module(index, &info).expect("add module");
+        }
+        index += 1;
+    }
+    assert!(validator.is_valid());
+
+    // replace the id stream with one that does not match the symbols
+    let source = |file| {
+        IdData::UserDefinedTypeSource(pdb::UserDefinedTypeSourceId {
+            udt: pdb::TypeIndex(0x1064),
+            source_file: pdb::UserDefinedTypeSourceFileRef::Local(file),
+            line: 1,
+        })
+    };
+    let mut writer = pdb::IdInfoWriter::new();
+    let string = writer.add_string(b"foo.cpp").expect("add string");
+    writer.add(&source(string)).expect("add source");
+    let wrong_kind = writer.add(&source(IdIndex(0x1001))).expect("add source");
+    let missing = writer.add(&source(IdIndex(0x2000))).expect("add source");
+
+    let mut data = Vec::new();
+    writer.write(&mut data).expect("write");
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let mut editor = pdb::MsfEditor::open(file).expect("opening editor");
+    editor.replace_stream(4, data).expect("replace stream");
+    let mut output = Vec::new();
+    editor.write(&mut output).expect("write pdb");
+
+    let pdb = PDB::open(std::io::Cursor::new(output)).expect("opening pdb");
+    let id_information = pdb.id_information().expect("id information");
+    let mut validator = pdb::IdReferenceValidator::new(&id_information).expect("validator");
+    assert_eq!(
+        validator.dangling(),
+        [
+            pdb::DanglingIdReference {
+                source: pdb::IdReferenceSource::Id(wrong_kind),
+                id: IdIndex(0x1001),
+                problem: pdb::IdReferenceProblem::UnexpectedKind(0x1606),
+            },
+            pdb::DanglingIdReference {
+                source: pdb::IdReferenceSource::Id(missing),
+                id: IdIndex(0x2000),
+                problem: pdb::IdReferenceProblem::Missing,
+            },
+        ]
+    );
+
+    // the build information of the first module no longer exists
+    let debug_information = pdb.debug_information().expect("debug information");
+    let module = debug_information
+        .modules()
+        .expect("modules")
+        .next()
+        .expect("next module")
+        .expect("module 0");
+    let info = pdb
+        .module_info(&module)
+        .expect("module info")
+        .expect("module info 0");
+    validator.add_module(0, &info).expect("add module");
+
+    assert_eq!(
+        validator.dangling()[2..],
+        [pdb::DanglingIdReference {
+            source: pdb::IdReferenceSource::Symbol {
+                module: 0,
+                symbol: pdb::SymbolIndex(0x310),
+            },
+            id: IdIndex(0x101d),
+            problem: pdb::IdReferenceProblem::Missing,
+        }]
+    );
+}
+
+#[test]
+fn test_id_finder_on_demand() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("opening pdb");
+    let id_information = pdb.id_information().expect("id information");
+    let hashes = pdb
+        .id_hashes()
+        .expect("id hashes")
+        .expect("hash stream exists");
+
+    for shift in [0, 3, 6] {
+        let mut finder = id_information.finder_with_shift(shift);
+        assert!(matches!(
+            finder.find(IdIndex(0x1064)),
+            Err(pdb::Error::TypeNotIndexed(0x1064, _))
+        ));
+
+        // ids only refer to lower indices, so indexing up to an id suffices to resolve it
+        finder.index_until(IdIndex(0x1064)).expect("index");
+        assert!(finder.max_index() >= IdIndex(0x1064));
+        assert!(finder.max_index() < IdIndex(0x1064 + (1 << shift)));
+        assert_eq!(
+            finder.function_name(IdIndex(0x1064), None).expect("name"),
+            "invoke_main"
+        );
+    }
+
+    // lookups through the hash stream match the finder
+    let mut finder = id_information.finder();
+    finder.index_until(IdIndex(u32::MAX)).expect("index");
+    let ids: Vec<_> = id_information.iter().collect().expect("collect ids");
+    for id in ids.iter().step_by(89) {
+        let found = hashes
+            .find_item(&id_information, id.index())
+            .expect("find item");
+        assert_eq!(found, finder.find(id.index()).expect("find"));
+    }
+
+    let end = IdIndex(0x1000 + id_information.len() as u32);
+    assert!(matches!(
+        hashes.find_item(&id_information, end),
+        Err(pdb::Error::TypeNotFound(_))
+    ));
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn test_parallel_id_finder() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("opening pdb");
+    let id_information = pdb.id_information().expect("id information");
+    let hashes = pdb
+        .id_hashes()
+        .expect("id hashes")
+        .expect("hash stream exists");
+
+    let mut finder = id_information.finder();
+    let mut iter = id_information.iter();
+    while iter.next().expect("next id").is_some() {
+        finder.update(&iter);
+    }
+
+    let parallel = id_information.par_finder(&hashes).expect("parallel finder");
+    assert_eq!(parallel.export_index(), finder.export_index());
+}
+
+#[test]
+fn test_udt_source() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = PDB::open(file).expect("opening pdb");
+
+    let expected = pdb::UdtSource {
+        index: pdb::TypeIndex(0x1064),
+        file: "c:\\users\\user\\desktop\\self\\foo.cpp".into(),
+        line: 12,
+        module: Some(0),
+        module_name: Some("c:\\Users\\User\\Desktop\\self\\foo.obj".into()),
+    };
+    assert_eq!(
+        pdb.udt_source("Baz").expect("udt source"),
+        Some(expected.clone())
+    );
+    assert_eq!(
+        pdb.udt_source(pdb::TypeIndex(0x1064)).expect("udt source"),
+        Some(expected)
+    );
+
+    let source = pdb
+        .udt_source(pdb::TypeIndex(0x100e))
+        .expect("udt source")
+        .expect("source exists");
+    assert!(source.file.ends_with("vadefs.h"));
+    assert_eq!(source.line, 145);
+
+    // unknown names, primitive types and indices past the end have no source
+    assert_eq!(pdb.udt_source("NoSuchType").expect("udt source"), None);
+    assert_eq!(
+        pdb.udt_source(pdb::TypeIndex(0x0074)).expect("udt source"),
+        None
+    );
+    assert_eq!(
+        pdb.udt_source(pdb::TypeIndex(0xff_ffff))
+            .expect("udt source"),
+        None
+    );
+}
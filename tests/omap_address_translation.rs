@@ -12,7 +12,7 @@ fn open_file() -> std::fs::File {
 fn test_omap_section_zero() {
     // https://github.com/willglynn/pdb/issues/87
 
-    let mut pdb = pdb::PDB::open(open_file()).expect("opening pdb");
+    let pdb = pdb::PDB::open(open_file()).expect("opening pdb");
 
     let address = pdb::PdbInternalSectionOffset {
         offset: 0,
@@ -26,7 +26,7 @@ fn test_omap_section_zero() {
 
 #[test]
 fn test_omap_symbol() {
-    let mut pdb = pdb::PDB::open(open_file()).expect("opening pdb");
+    let pdb = pdb::PDB::open(open_file()).expect("opening pdb");
 
     let global_symbols = pdb.global_symbols().expect("global_symbols");
 
@@ -39,7 +39,7 @@ fn test_omap_symbol() {
                 .parse()
                 .ok()
                 .and_then(|d| d.name())
-                .map_or(false, |n| n == target_name);
+                .is_some_and(|n| n == target_name);
             Ok(matches)
         })
         .expect("iterate symbols")
@@ -72,7 +72,7 @@ fn test_omap_symbol() {
 
 #[test]
 fn test_omap_range() {
-    let mut pdb = pdb::PDB::open(open_file()).expect("opening pdb");
+    let pdb = pdb::PDB::open(open_file()).expect("opening pdb");
     let address_map = pdb.address_map().expect("address map");
 
     // Range partially covered by OMAPs
@@ -2,7 +2,7 @@
 fn pdb_info() {
     let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
 
-    let mut pdb = pdb::PDB::open(file).expect("opening pdb");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
     let pdb_info = pdb.pdb_information().expect("pdb information");
 
     assert_eq!(pdb_info.age, 2);
@@ -12,3 +12,191 @@ fn pdb_info() {
     );
     assert_eq!(pdb_info.signature, 0x587B_A621);
 }
+
+#[test]
+fn stream_names() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let pdb_info = pdb.pdb_information().expect("pdb information");
+    let names = pdb_info.stream_names().expect("stream names");
+
+    assert_eq!(names.len(), 4);
+    assert_eq!(names.get(b"mystream"), Some(pdb::StreamIndex(208)));
+    assert_eq!(names.get(b"missing"), None);
+
+    let layout = names.layout();
+    assert!(layout.capacity() as usize >= names.len());
+    assert_eq!(layout.entries().len(), names.len());
+
+    for (entry, name) in layout.entries().iter().zip(&names) {
+        assert_eq!(entry.stream_id, name.stream_id);
+        assert!(entry.bucket < layout.capacity());
+
+        let word = layout.present_words()[entry.bucket as usize / 32];
+        assert_ne!(word & (1 << (entry.bucket % 32)), 0);
+    }
+}
+
+#[test]
+fn features() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let pdb_info = pdb.pdb_information().expect("pdb information");
+    let features = pdb_info.features().expect("features");
+
+    assert_eq!(features.codes(), &[20_140_508]);
+    assert!(features.vc140());
+    assert!(!features.vc110());
+    assert!(!features.no_type_merge());
+    assert!(!features.minimal_debug_info());
+}
+
+#[test]
+fn identifier() {
+    let guid = "2B3C3FA5-5A2E-44B8-8BBA-C3300FF69F62".parse().unwrap();
+    let identifier = pdb::PdbIdentifier::Guid { guid, age: 26 };
+    assert_eq!(identifier.to_string(), "2B3C3FA55A2E44B88BBAC3300FF69F621A");
+
+    let identifier = pdb::PdbIdentifier::Signature {
+        signature: 0x0A2B_3C4D,
+        age: 26,
+    };
+    assert_eq!(identifier.to_string(), "0A2B3C4D1a");
+    assert_eq!(identifier.age(), 26);
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    assert_eq!(
+        pdb.identifier().expect("identifier"),
+        pdb::PdbIdentifier::Guid { guid, age: 1 }
+    );
+}
+
+#[test]
+fn link_info() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+
+    // The linker wrote an empty /LinkInfo stream for this PDB.
+    assert!(pdb.link_info().expect("link info").is_none());
+}
+
+#[test]
+fn type_merge_cache() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+
+    // This PDB was linked without type de-duplication.
+    assert!(pdb.type_merge_cache().expect("type merge cache").is_none());
+}
+
+#[test]
+fn write_information() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let info = pdb.pdb_information().expect("pdb information");
+    let original = pdb
+        .raw_stream(pdb::StreamIndex(1))
+        .expect("stream")
+        .unwrap();
+
+    // an unmodified stream is written identically
+    let mut writer = pdb::PdbInfoWriter::from_information(&info).expect("writer");
+    let mut data = Vec::new();
+    writer.write(&mut data).expect("write");
+    assert_eq!(data, original.as_slice());
+
+    let guid = "01234567-89AB-CDEF-0123-456789ABCDEF".parse().unwrap();
+    writer
+        .set_guid(guid)
+        .set_age(7)
+        .set_signature(0x1234_5678)
+        .set_stream_name(b"newstream", pdb::StreamIndex(42));
+    assert_eq!(
+        writer.remove_stream_name(b"mystream"),
+        Some(pdb::StreamIndex(208))
+    );
+
+    let mut data = Vec::new();
+    writer.write(&mut data).expect("write");
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let mut editor = pdb::MsfEditor::open(file).expect("opening editor");
+    editor.replace_stream(1, data).expect("replace stream");
+    let mut output = Vec::new();
+    editor.write(&mut output).expect("write pdb");
+
+    let pdb = pdb::PDB::open(std::io::Cursor::new(output)).expect("opening pdb");
+    let info = pdb.pdb_information().expect("pdb information");
+    assert_eq!(info.guid, guid);
+    assert_eq!(info.age, 7);
+    assert_eq!(info.signature, 0x1234_5678);
+
+    let names = info.stream_names().expect("stream names");
+    assert_eq!(names.len(), 4);
+    assert_eq!(names.get(b"newstream"), Some(pdb::StreamIndex(42)));
+    assert_eq!(names.get(b"mystream"), None);
+    assert_eq!(names.get(b"/names"), Some(pdb::StreamIndex(6)));
+    assert_eq!(info.features().expect("features").codes(), &[20_140_508]);
+}
+
+#[test]
+fn odd_stream_names() {
+    let mut writer = pdb::PdbInfoWriter::new();
+    writer
+        .set_stream_name(b"/names", pdb::StreamIndex(5))
+        .set_stream_name(b"caf\xe9", pdb::StreamIndex(6))
+        .set_stream_name(b"tail", pdb::StreamIndex(7));
+
+    let mut data = Vec::new();
+    writer.write(&mut data).expect("write");
+
+    // drop the terminator of the last name
+    let names_size = u32::from_le_bytes([data[28], data[29], data[30], data[31]]) as usize;
+    assert_eq!(data[32 + names_size - 1], 0);
+    data[32 + names_size - 1] = b'!';
+
+    let mut msf = pdb::MsfWriter::new();
+    msf.add_stream(Vec::new());
+    msf.add_stream(data);
+    let mut file = Vec::new();
+    msf.write(&mut file).expect("write msf");
+
+    let pdb = pdb::PDB::open(std::io::Cursor::new(file)).expect("opening pdb");
+    let info = pdb.pdb_information().expect("pdb information");
+    let names = info.stream_names().expect("stream names");
+    assert_eq!(names.len(), 3);
+
+    let name = names.iter().find(|n| n.stream_id == pdb::StreamIndex(6));
+    let name = name.expect("odd name");
+    assert_eq!(name.raw_name(), b"caf\xe9");
+    assert_eq!(name.name_lossy(), "caf\u{fffd}");
+
+    assert_eq!(names.get(b"tail!"), Some(pdb::StreamIndex(7)));
+}
+
+#[test]
+fn mini_pdb() {
+    let mut info = pdb::PdbInfoWriter::new();
+    info.set_features(vec![20_140_508, 0x494E_494D]);
+    let mut data = Vec::new();
+    info.write(&mut data).expect("write");
+
+    let mut msf = pdb::MsfWriter::new();
+    msf.add_stream(Vec::new());
+    msf.add_stream(data);
+    let mut file = Vec::new();
+    msf.write(&mut file).expect("write msf");
+
+    let pdb = pdb::PDB::open(std::io::Cursor::new(file)).expect("opening pdb");
+    assert!(pdb.is_mini_pdb().expect("mini pdb"));
+    assert!(matches!(pdb.type_information(), Err(pdb::Error::MiniPdb)));
+    assert!(matches!(pdb.id_information(), Err(pdb::Error::MiniPdb)));
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    assert!(!pdb.is_mini_pdb().expect("mini pdb"));
+    assert!(pdb.type_information().is_ok());
+}
@@ -3,7 +3,7 @@ use pdb::{FallibleIterator, Rva, PDB};
 #[test]
 fn test_module_lines() {
     let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
-    let mut pdb = PDB::open(file).expect("parse pdb");
+    let pdb = PDB::open(file).expect("parse pdb");
 
     let address_map = pdb.address_map().expect("address map");
     let string_table = pdb.string_table().expect("string table");
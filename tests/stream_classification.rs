@@ -0,0 +1,34 @@
+use pdb::{StreamIndex, StreamKind};
+
+#[test]
+fn classify_streams() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let streams = pdb.streams().expect("streams");
+
+    assert_eq!(streams.len(), pdb.stream_count() as usize);
+    for (number, stream) in streams.iter().enumerate() {
+        assert_eq!(stream.index, StreamIndex(number as u16));
+        assert_eq!(stream.size, pdb.stream_size(stream.index).expect("size"));
+    }
+
+    let kind = |index: u16| &streams[index as usize].kind;
+    assert_eq!(kind(0), &StreamKind::PreviousDirectory);
+    assert_eq!(kind(1), &StreamKind::PdbInformation);
+    assert_eq!(kind(2), &StreamKind::TypeInformation);
+    assert_eq!(kind(3), &StreamKind::DebugInformation);
+    assert_eq!(kind(4), &StreamKind::IdInformation);
+    assert_eq!(kind(6), &StreamKind::Named("/names".into()));
+    assert_eq!(kind(7), &StreamKind::GlobalSymbols);
+    assert_eq!(kind(8), &StreamKind::PublicSymbols);
+    assert_eq!(kind(9), &StreamKind::SymbolRecords);
+    assert_eq!(kind(11), &StreamKind::DebugStream("section_headers"));
+    assert_eq!(kind(208), &StreamKind::Named("mystream".into()));
+
+    assert!(streams.iter().any(|s| s.kind == StreamKind::TypeHash));
+    assert!(streams.iter().any(|s| s.kind == StreamKind::IdHash));
+    assert!(streams.iter().any(|s| s.kind == StreamKind::Module(0)));
+    assert!(!streams
+        .iter()
+        .any(|s| s.kind == StreamKind::Unknown && s.size.is_some()));
+}
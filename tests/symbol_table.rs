@@ -15,7 +15,7 @@ where
         )
     };
 
-    let mut pdb = pdb::PDB::open(file).expect("opening pdb");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
     let symbol_table = pdb.global_symbols().expect("global symbols");
 
     func(&symbol_table, is_fixture);
@@ -121,3 +121,27 @@ fn find_symbols() {
         }
     })
 }
+
+#[test]
+#[cfg(feature = "demangle")]
+fn demangle_symbols() {
+    setup(|global_symbols, is_fixture| {
+        if !is_fixture {
+            return;
+        }
+
+        let mut demangled = None;
+        let mut iter = global_symbols.iter();
+        while let Some(sym) = iter.next().expect("next symbol") {
+            let data = sym.parse().expect("symbol parsing");
+            if data.name() == Some("?static_f_public@Baz@@SAXXZ".into()) {
+                demangled = data.name_demangled();
+            }
+        }
+
+        assert_eq!(
+            demangled.as_deref(),
+            Some("public: static void __cdecl Baz::static_f_public(void)")
+        );
+    });
+}
@@ -13,7 +13,7 @@ where
     }
     .expect("opening file");
 
-    let mut pdb = pdb::PDB::open(file).expect("opening pdb");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
     let type_information = pdb.type_information().expect("type information");
 
     func(&type_information);
@@ -185,6 +185,1261 @@ fn bench_type_finder(b: &mut test::Bencher) {
 }
 */
 
+#[test]
+fn type_sizes() {
+    setup(|type_information| {
+        let sizer = type_information.sizer().expect("sizer");
+        assert_eq!(type_information.pointer_size(), Some(8));
+        assert_eq!(sizer.pointer_size(), Some(8));
+
+        assert_eq!(sizer.size_of(pdb::TypeIndex(0x0074)).expect("int"), 4);
+        assert_eq!(sizer.size_of(pdb::TypeIndex(0x0041)).expect("double"), 8);
+        assert_eq!(sizer.size_of(pdb::TypeIndex(0x0670)).expect("char *"), 8);
+        assert!(matches!(
+            sizer.size_of(pdb::TypeIndex(0x0003)),
+            Err(pdb::Error::UnsizedType(0x0003))
+        ));
+
+        let mut definitions = HashMap::new();
+        let mut forward_references = Vec::new();
+        let mut iter = type_information.iter();
+        while let Some(typ) = iter.next().expect("next type") {
+            match typ.parse() {
+                Ok(pdb::TypeData::Class(class)) => {
+                    let name = class.unique_name.unwrap_or(class.name).to_string();
+                    if class.properties.forward_reference() {
+                        forward_references.push((typ.index(), name));
+                    } else {
+                        assert_eq!(sizer.size_of(typ.index()).expect("size"), class.size);
+                        definitions.entry(name).or_insert(class.size);
+                    }
+                }
+                Ok(pdb::TypeData::Pointer(pointer)) => {
+                    let size = sizer.size_of(typ.index()).expect("size");
+                    assert_eq!(size, u64::from(pointer.attributes.size()));
+                }
+                _ => {}
+            }
+        }
+
+        let mut resolved = 0;
+        for (index, name) in forward_references {
+            if let Some(&size) = definitions.get(&name) {
+                assert_eq!(sizer.size_of(index).expect("size"), size);
+                resolved += 1;
+            }
+        }
+        assert!(resolved > 0);
+    });
+}
+
+#[test]
+fn record_layout() {
+    setup(|type_information| {
+        let sizer = type_information.sizer().expect("sizer");
+
+        let mut index = None;
+        let mut iter = type_information.iter();
+        while let Some(typ) = iter.next().expect("next type") {
+            if let Ok(pdb::TypeData::Class(class)) = typ.parse() {
+                if class.name.as_bytes() == b"__vc_attributes::moduleAttribute" {
+                    index = Some(typ.index());
+                }
+            }
+        }
+
+        // forward references are resolved to the definition
+        let layout = sizer
+            .layout_of(index.expect("moduleAttribute"))
+            .expect("layout");
+        assert_eq!(layout.size, 96);
+        assert_eq!(layout.alignment, 8);
+        assert!(layout.vfptr.is_none());
+        assert!(layout.base_classes.is_empty());
+        assert_eq!(layout.members.len(), 15);
+        assert_eq!(layout.padding, vec![4..8, 37..40, 52..56, 78..80]);
+
+        let lcid = &layout.members[4];
+        assert_eq!(lcid.name.as_bytes(), b"lcid");
+        assert_eq!((lcid.offset, lcid.size, lcid.alignment), (32, 4, 4));
+        assert!(lcid.bitfield.is_none());
+
+        assert!(matches!(
+            sizer.layout_of(pdb::TypeIndex(0x0074)),
+            Err(pdb::Error::NotARecordType(0x0074))
+        ));
+    });
+}
+
+#[test]
+fn padding_report() {
+    setup(|type_information| {
+        let reports = type_information
+            .padding_reports(64)
+            .expect("padding reports");
+        let report = reports
+            .iter()
+            .find(|report| report.layout.name.as_bytes() == b"__vc_attributes::moduleAttribute")
+            .expect("moduleAttribute");
+
+        assert!(!report.union);
+        assert_eq!(report.padding_size(), 13);
+        assert_eq!(report.cachelines(), 2);
+        assert!(report.cacheline_crossings.is_empty());
+        assert_eq!(report.optimal_size, 88);
+        assert_eq!(report.savings(), 8);
+        assert!(report
+            .to_string()
+            .contains("/* XXX 3 bytes hole at offset 37 */"));
+    });
+}
+
+#[test]
+fn owned_type_data() {
+    setup(|type_information| {
+        let mut owned = Vec::new();
+        let mut iter = type_information.iter();
+        while let Some(typ) = iter.next().expect("next type") {
+            if let Ok(data) = typ.parse() {
+                owned.push((data.clone(), data.to_owned()));
+            }
+        }
+
+        assert!(!owned.is_empty());
+        for (data, owned) in &owned {
+            assert_eq!(data, owned.get());
+        }
+    });
+}
+
+#[test]
+fn find_by_name() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+    let hashes = pdb
+        .type_hashes()
+        .expect("type hashes")
+        .expect("hash stream");
+
+    assert_eq!(
+        hashes.find_by_name(&type_information, "Baz").expect("find"),
+        Some(pdb::TypeIndex(0x1064))
+    );
+    assert_eq!(
+        hashes
+            .find_by_name(&type_information, "NoSuchType")
+            .expect("find"),
+        None
+    );
+
+    // every global definition can be found through the hash stream
+    let mut iter = type_information.iter();
+    while let Some(typ) = iter.next().expect("next type") {
+        let (name, properties) = match typ.parse() {
+            Ok(pdb::TypeData::Class(c)) => (c.name, c.properties),
+            Ok(pdb::TypeData::Union(u)) => (u.name, u.properties),
+            Ok(pdb::TypeData::Enumeration(e)) => (e.name, e.properties),
+            _ => continue,
+        };
+
+        // function-local types are hashed by their unique name
+        if properties.forward_reference()
+            || properties.scoped_definition()
+            || name.as_bytes().starts_with(b"<")
+        {
+            continue;
+        }
+
+        let found = hashes
+            .find_all_by_name(&type_information, &name.to_string())
+            .expect("find");
+        assert!(found.contains(&typ.index()), "{} not found", name);
+    }
+}
+
+#[test]
+fn hash_adjusters() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+    let strings = pdb.string_table().expect("string table");
+    let hashes = pdb
+        .type_hashes()
+        .expect("type hashes")
+        .expect("hash stream");
+
+    let adjusters = hashes.adjusters();
+    assert_eq!(adjusters.len(), 7);
+
+    for adjuster in adjusters {
+        let name = strings
+            .get(adjuster.name)
+            .expect("adjuster name")
+            .to_string();
+        assert_eq!(
+            hashes.find_by_name(&type_information, &name).expect("find"),
+            Some(adjuster.index)
+        );
+    }
+}
+
+#[test]
+fn search() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+
+    let baz = type_information.search("Baz").expect("search");
+    assert_eq!(baz.len(), 2);
+    assert!(baz.contains(&pdb::TypeIndex(0x1064)));
+
+    let std_types = type_information.search("std::*").expect("search");
+    assert_eq!(std_types.len(), 14);
+    let finder = {
+        let mut finder = type_information.finder();
+        let mut iter = type_information.iter();
+        while iter.next().expect("next type").is_some() {
+            finder.update(&iter);
+        }
+        finder
+    };
+    for index in std_types {
+        let data = finder.find(index).expect("find").parse().expect("parse");
+        let name = data.name().expect("name").to_string();
+        assert!(name.starts_with("std::"), "{}", name);
+    }
+
+    assert!(type_information
+        .search("NoSuch*Type")
+        .expect("search")
+        .is_empty());
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn search_regex() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+
+    let regex = regex::bytes::Regex::new("^std::").expect("regex");
+    let matches: Vec<_> = type_information
+        .search_regex(&regex)
+        .map(|(index, _)| Ok(index))
+        .collect()
+        .expect("search");
+    assert_eq!(matches, type_information.search("std::*").expect("search"));
+
+    let regex = regex::bytes::Regex::new("^Baz$").expect("regex");
+    let mut matches = type_information.search_regex(&regex);
+    let (index, name) = matches.next().expect("next").expect("match");
+    assert_eq!(name.as_bytes(), b"Baz");
+    assert!(type_information
+        .search("Baz")
+        .expect("search")
+        .contains(&index));
+}
+
+#[test]
+fn resolve_definition() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+
+    let mut finder = type_information.finder();
+    let mut iter = type_information.iter();
+    while iter.next().expect("next type").is_some() {
+        finder.update(&iter);
+    }
+
+    let mut iter = type_information.iter();
+    while let Some(typ) = iter.next().expect("next type") {
+        let (name, forward_reference) = match typ.parse() {
+            Ok(pdb::TypeData::Class(c)) => (c.name, c.properties.forward_reference()),
+            Ok(pdb::TypeData::Union(u)) => (u.name, u.properties.forward_reference()),
+            Ok(pdb::TypeData::Enumeration(e)) => (e.name, e.properties.forward_reference()),
+            _ => {
+                let resolved = finder.resolve_definition(typ.index()).expect("resolve");
+                assert_eq!(resolved, Some(typ.index()));
+                continue;
+            }
+        };
+
+        let resolved = finder.resolve_definition(typ.index()).expect("resolve");
+        if !forward_reference {
+            assert_eq!(resolved, Some(typ.index()));
+            continue;
+        }
+
+        if let Some(definition) = resolved {
+            let data = finder
+                .find(definition)
+                .expect("find")
+                .parse()
+                .expect("parse");
+            assert_eq!(data.name(), Some(name));
+            match data {
+                pdb::TypeData::Class(c) => assert!(!c.properties.forward_reference()),
+                pdb::TypeData::Union(u) => assert!(!u.properties.forward_reference()),
+                pdb::TypeData::Enumeration(e) => assert!(!e.properties.forward_reference()),
+                _ => panic!("unexpected definition {:?}", data),
+            }
+        }
+    }
+
+    // class Baz is forward declared at 0x1000 and defined at 0x1064
+    assert_eq!(
+        finder
+            .resolve_definition(pdb::TypeIndex(0x1000))
+            .expect("resolve"),
+        Some(pdb::TypeIndex(0x1064))
+    );
+}
+
+#[test]
+fn dependency_graph() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+
+    let mut finder = type_information.finder();
+    let mut iter = type_information.iter();
+    while iter.next().expect("next type").is_some() {
+        finder.update(&iter);
+    }
+
+    let mut iter = type_information.iter();
+    while let Some(typ) = iter.next().expect("next type") {
+        let graph = finder.dependency_graph(typ.index()).expect("graph");
+        assert_eq!(graph.root(), typ.index());
+        assert!(graph.contains(typ.index()));
+
+        // the graph is closed under references
+        for index in graph.types() {
+            for reference in graph.references(index) {
+                assert!(graph.contains(*reference));
+            }
+        }
+    }
+
+    // class Baz is forward declared at 0x1000 and defined at 0x1064 with field list 0x1063
+    let dependencies = finder.dependencies(pdb::TypeIndex(0x1000)).expect("deps");
+    assert!(dependencies.contains(&pdb::TypeIndex(0x1063)));
+    assert!(dependencies.contains(&pdb::TypeIndex(0x1064)));
+    assert!(!dependencies.contains(&pdb::TypeIndex(0x1000)));
+
+    let graph = finder
+        .dependency_graph(pdb::TypeIndex(0x0074))
+        .expect("graph");
+    assert!(graph.is_empty());
+}
+
+#[test]
+fn structural_equality() {
+    let open = || {
+        let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+        pdb::PDB::open(file).expect("opening pdb")
+    };
+
+    let left_pdb = open();
+    let left_types = left_pdb.type_information().expect("type information");
+    let mut left = left_types.finder();
+    let mut iter = left_types.iter();
+    while iter.next().expect("next type").is_some() {
+        left.update(&iter);
+    }
+
+    let right_pdb = open();
+    let right_types = right_pdb.type_information().expect("type information");
+    let mut right = right_types.finder();
+    let mut iter = right_types.iter();
+    while iter.next().expect("next type").is_some() {
+        right.update(&iter);
+    }
+
+    let mut comparator = pdb::TypeComparator::new(&left, &right);
+
+    // every type is identical to itself
+    let mut iter = left_types.iter();
+    while let Some(typ) = iter.next().expect("next type") {
+        assert!(comparator
+            .equivalent(typ.index(), typ.index())
+            .expect("compare"));
+    }
+
+    // class Baz is forward declared at 0x1000 and defined at 0x1064
+    assert!(!comparator
+        .equivalent(pdb::TypeIndex(0x1000), pdb::TypeIndex(0x1064))
+        .expect("compare"));
+    assert!(!comparator
+        .equivalent(pdb::TypeIndex(0x0074), pdb::TypeIndex(0x1064))
+        .expect("compare"));
+    assert!(comparator
+        .equivalent(pdb::TypeIndex(0x0074), pdb::TypeIndex(0x0074))
+        .expect("compare"));
+}
+
+#[test]
+fn finder_index() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+
+    let mut finder = type_information.finder();
+    let mut iter = type_information.iter();
+    while iter.next().expect("next type").is_some() {
+        finder.update(&iter);
+    }
+
+    let index = finder.export_index();
+    let imported = type_information
+        .finder_from_index(&index)
+        .expect("import index");
+    assert_eq!(imported.max_index(), finder.max_index());
+
+    let mut iter = type_information.iter();
+    while let Some(typ) = iter.next().expect("next type") {
+        let found = imported.find(typ.index()).expect("find");
+        assert_eq!(found.len(), typ.len());
+        assert_eq!(found.parse().ok(), typ.parse().ok());
+    }
+
+    // a partial index is completed by iterating
+    let mut partial = type_information.finder();
+    let mut iter = type_information.iter();
+    for _ in 0..100 {
+        iter.next().expect("next type");
+        partial.update(&iter);
+    }
+    let mut partial = type_information
+        .finder_from_index(&partial.export_index())
+        .expect("import index");
+    assert!(partial.max_index() < finder.max_index());
+    let mut iter = type_information.iter();
+    while iter.next().expect("next type").is_some() {
+        partial.update(&iter);
+    }
+    assert_eq!(partial.export_index(), index);
+
+    assert!(matches!(
+        type_information.finder_from_index(&index[..index.len() - 1]),
+        Err(pdb::Error::InvalidFinderIndex(_))
+    ));
+    assert!(matches!(
+        type_information.finder_from_index(b"garbage"),
+        Err(pdb::Error::InvalidFinderIndex(_))
+    ));
+}
+
+#[test]
+fn finder_on_demand() {
+    setup(|type_information| {
+        for shift in [0, 3, 5] {
+            let mut finder = type_information.finder_with_shift(shift);
+            assert!(matches!(
+                finder.find(pdb::TypeIndex(0x1064)),
+                Err(pdb::Error::TypeNotIndexed(0x1064, _))
+            ));
+
+            finder.index_until(pdb::TypeIndex(0x1064)).expect("index");
+            assert!(finder.max_index() >= pdb::TypeIndex(0x1064));
+            assert!(finder.max_index() < pdb::TypeIndex(0x1064 + (1 << shift)));
+            assert!(finder.find(pdb::TypeIndex(0x1064)).is_ok());
+
+            // indexing up to an earlier type does not change anything
+            let max_index = finder.max_index();
+            finder.index_until(pdb::TypeIndex(0x1000)).expect("index");
+            assert_eq!(finder.max_index(), max_index);
+
+            // indices beyond the end populate the whole finder
+            finder.index_until(pdb::TypeIndex(u32::MAX)).expect("index");
+            let mut iter = type_information.iter();
+            while let Some(typ) = iter.next().expect("next type") {
+                let found = finder.find(typ.index()).expect("find");
+                assert_eq!(found.len(), typ.len());
+            }
+        }
+    })
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_finder() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+    let hashes = pdb
+        .type_hashes()
+        .expect("type hashes")
+        .expect("hash stream");
+
+    let mut finder = type_information.finder();
+    let mut iter = type_information.iter();
+    while iter.next().expect("next type").is_some() {
+        finder.update(&iter);
+    }
+
+    let parallel = type_information
+        .par_finder(&hashes)
+        .expect("parallel finder");
+    assert_eq!(parallel.max_index(), finder.max_index());
+    assert_eq!(parallel.export_index(), finder.export_index());
+}
+
+#[test]
+fn type_servers() {
+    let server = |age| pdb::TypeServerType {
+        identifier: pdb::PdbIdentifier::Guid {
+            guid: "2B3C3FA5-5A2E-44B8-8BBA-C3300FF69F62".parse().unwrap(),
+            age,
+        },
+        name: pdb::RawString::from("C:\\build\\foo.pdb"),
+    };
+
+    let mut resolver = pdb::FileTypeServerResolver::new();
+    resolver.add_search_path("fixtures/self");
+    assert_eq!(
+        resolver.candidates(&server(1)),
+        vec![
+            std::path::PathBuf::from("C:\\build\\foo.pdb"),
+            std::path::Path::new("fixtures/self").join("foo.pdb"),
+        ]
+    );
+
+    let mut servers = pdb::TypeServers::new(resolver);
+    let types = servers.type_information(&server(1)).expect("resolve");
+    assert_eq!(types.map(|t| t.len()), Some(8406));
+
+    // the age does not match
+    assert!(servers
+        .type_information(&server(2))
+        .expect("resolve")
+        .is_none());
+
+    // a closure resolving every type server to the same PDB, counting calls
+    let mut calls = 0;
+    let mut servers = pdb::TypeServers::new(|_: &pdb::TypeServerType<'_>| {
+        calls += 1;
+        let file = std::fs::File::open("fixtures/self/foo.pdb")?;
+        pdb::PDB::open(file)?.type_information().map(Some)
+    });
+    assert!(servers
+        .type_information(&server(5))
+        .expect("resolve")
+        .is_some());
+    assert!(servers
+        .type_information(&server(5))
+        .expect("resolve")
+        .is_some());
+    drop(servers);
+    assert_eq!(calls, 1);
+}
+
+#[test]
+fn methods() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+
+    let mut finder = type_information.finder();
+    let mut iter = type_information.iter();
+    while iter.next().expect("next type").is_some() {
+        finder.update(&iter);
+    }
+
+    // std::exception
+    let methods = finder.methods(pdb::TypeIndex(0x1212)).expect("methods");
+    let names: Vec<_> = methods.iter().map(|m| m.name.to_string()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "exception",
+            "exception",
+            "exception",
+            "exception",
+            "operator=",
+            "~exception",
+            "what",
+            "__local_vftable_ctor_closure",
+            "__vecDelDtor",
+        ]
+    );
+
+    let what = &methods[6];
+    assert_eq!(
+        what.attributes.method_kind(),
+        Some(pdb::MethodKind::IntroVirtual)
+    );
+    assert_eq!(what.vtable_offset, Some(8));
+    assert!(methods[0].attributes.method_kind() == Some(pdb::MethodKind::Vanilla));
+
+    // every overload has its own type
+    let mut overloads: Vec<_> = methods[..4].iter().map(|m| m.method_type).collect();
+    overloads.dedup();
+    assert_eq!(overloads.len(), 4);
+
+    assert!(matches!(
+        finder.methods(pdb::TypeIndex(0x0074)),
+        Err(pdb::Error::NotARecordType(0x0074))
+    ));
+}
+
+#[test]
+fn flattened_members() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+
+    let mut finder = type_information.finder();
+    let mut iter = type_information.iter();
+    while iter.next().expect("next type").is_some() {
+        finder.update(&iter);
+    }
+
+    // pairNode, whose base class DNameNode has no data members besides the vfptr
+    let members = finder
+        .members(pdb::TypeIndex(0x14af), true)
+        .expect("members");
+    let layout: Vec<_> = members
+        .iter()
+        .map(|m| (m.name.to_string().into_owned(), m.offset))
+        .collect();
+    assert_eq!(
+        layout,
+        [
+            ("left".to_string(), 8),
+            ("right".to_string(), 16),
+            ("myLen".to_string(), 24)
+        ]
+    );
+    assert!(members.iter().all(|m| m.class == pdb::TypeIndex(0x14af)));
+
+    // std::bad_cast inherits from std::exception
+    let members = finder
+        .members(pdb::TypeIndex(0x11d7), true)
+        .expect("members");
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0].class, pdb::TypeIndex(0x1212));
+
+    // fields of enumerations are returned as well
+    let fields = finder.fields(pdb::TypeIndex(0x15c3)).expect("fields");
+    assert_eq!(fields.len(), 10);
+
+    assert!(matches!(
+        finder.members(pdb::TypeIndex(0x15c3), false),
+        Err(pdb::Error::NotARecordType(0x15c3))
+    ));
+}
+
+#[test]
+fn vtable_layout() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+
+    let mut finder = type_information.finder();
+    let mut iter = type_information.iter();
+    while iter.next().expect("next type").is_some() {
+        finder.update(&iter);
+    }
+
+    // std::bad_cast replaces the destructor of std::exception, but inherits `what`
+    let slots = finder
+        .vtable_layout(pdb::TypeIndex(0x11d7))
+        .expect("layout");
+    let slots: Vec<_> = slots
+        .iter()
+        .map(|slot| (slot.offset, slot.name.to_string(), slot.class))
+        .collect();
+    assert_eq!(
+        slots,
+        vec![
+            (0, "__vecDelDtor".into(), pdb::TypeIndex(0x11d7)),
+            (8, "what".into(), pdb::TypeIndex(0x1212)),
+        ]
+    );
+
+    // pairNode overrides all pure virtual methods of DNameNode
+    let slots = finder
+        .vtable_layout(pdb::TypeIndex(0x14af))
+        .expect("layout");
+    assert_eq!(slots.len(), 3);
+    for slot in &slots {
+        assert_eq!(slot.class, pdb::TypeIndex(0x14af));
+        assert_eq!(
+            slot.attributes.method_kind(),
+            Some(pdb::MethodKind::Virtual)
+        );
+    }
+
+    // IStream extends ISequentialStream, which extends IUnknown
+    let slots = finder
+        .vtable_layout(pdb::TypeIndex(0x174d))
+        .expect("layout");
+    let names: Vec<_> = slots.iter().map(|slot| slot.name.to_string()).collect();
+    assert_eq!(
+        &names[..6],
+        [
+            "QueryInterface",
+            "AddRef",
+            "Release",
+            "Read",
+            "Write",
+            "Seek"
+        ]
+    );
+    assert_eq!(slots.len(), 14);
+    assert!(slots.iter().all(|slot| slot.decorated_name.is_none()));
+
+    assert!(matches!(
+        finder.vtable_layout(pdb::TypeIndex(0x0074)),
+        Err(pdb::Error::NotARecordType(0x0074))
+    ));
+}
+
+#[test]
+fn enumerators() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+
+    let mut finder = type_information.finder();
+    let mut iter = type_information.iter();
+    while iter.next().expect("next type").is_some() {
+        finder.update(&iter);
+    }
+
+    // tagURLZONE
+    match finder.find(pdb::TypeIndex(0x15c3)).expect("find").parse() {
+        Ok(pdb::TypeData::Enumeration(data)) => {
+            assert_eq!(data.underlying_kind(), Some(pdb::PrimitiveKind::I32));
+        }
+        other => panic!("unexpected type {:?}", other),
+    }
+
+    let enumerators = finder
+        .enumerators(pdb::TypeIndex(0x15c3))
+        .expect("enumerators");
+    assert_eq!(enumerators.len(), 10);
+    assert_eq!(enumerators[0].name.to_string(), "URLZONE_INVALID");
+    assert_eq!(enumerators[0].value, pdb::EnumValue::Signed(-1));
+    assert_eq!(enumerators[1].value, pdb::EnumValue::Signed(0));
+
+    // `anonymous-namespace'::module_id
+    let enumerators = finder
+        .enumerators(pdb::TypeIndex(0x1402))
+        .expect("enumerators");
+    assert_eq!(enumerators[3].value, pdb::EnumValue::Unsigned(3));
+
+    assert!(matches!(
+        finder.enumerators(pdb::TypeIndex(0x1064)),
+        Err(pdb::Error::UnimplementedFeature(_))
+    ));
+}
+
+#[test]
+fn rust_bindings() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+    let sizer = type_information.sizer().expect("sizer");
+
+    let mut generator = pdb::RustGenerator::new(&sizer);
+    // std::exception, through its forward reference
+    assert_eq!(
+        generator.add(pdb::TypeIndex(0x11bf)).expect("add"),
+        "std_exception"
+    );
+    assert_eq!(
+        generator.add(pdb::TypeIndex(0x15c3)).expect("add"),
+        "tagURLZONE"
+    );
+    let code = generator.generate().expect("generate");
+
+    // the base class is emitted with its virtual function table pointer
+    assert!(code
+        .contains("pub struct std_exception {\n    pub vfptr: *const *const core::ffi::c_void,\n"));
+    assert!(code.contains("const _: () = assert!(core::mem::size_of::<std_exception>() == 24);"));
+
+    // members are emitted for types referenced by value
+    assert!(code.contains("pub struct std_exception_data {"));
+
+    // duplicate enumerator values become constants
+    assert!(code.contains("#[repr(i32)]"));
+    assert!(code.contains("    URLZONE_INVALID = -1,\n"));
+    assert!(code
+        .contains("    pub const URLZONE_LOCAL_MACHINE: Self = Self::URLZONE_PREDEFINED_MIN;\n"));
+
+    let mut generator = pdb::RustGenerator::new(&sizer);
+    assert!(matches!(
+        generator.add(pdb::TypeIndex(0x100a)),
+        Err(pdb::Error::UnsizedType(0x100a))
+    ));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serialize_type_data() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+
+    let mut finder = type_information.finder();
+    let mut iter = type_information.iter();
+    while iter.next().expect("next type").is_some() {
+        finder.update(&iter);
+    }
+
+    let baz = finder.find(pdb::TypeIndex(0x1064)).expect("find");
+    let data = baz.parse().expect("parse");
+    let json = serde_json::to_value(&data).expect("serialize");
+
+    assert_eq!(json["Class"]["name"], "Baz");
+    assert_eq!(json["Class"]["kind"], "Class");
+    assert_eq!(json["Class"]["fields"], 0x1063);
+    assert_eq!(json["Class"]["size"], 12);
+
+    // all records can be serialized
+    let mut iter = type_information.iter();
+    while let Some(typ) = iter.next().expect("next type") {
+        if let Ok(data) = typ.parse() {
+            serde_json::to_string(&data).expect("serialize");
+        }
+    }
+}
+
+#[test]
+fn remap_type_indices() {
+    setup(|type_information| {
+        let mut identity = pdb::TypeIndexMap::new(pdb::TypeIndex(0x1000));
+        let mut shifted = pdb::TypeIndexMap::new(pdb::TypeIndex(0x1000));
+        let mut iter = type_information.iter();
+        while let Some(typ) = iter.next().expect("next type") {
+            identity.insert(typ.index(), typ.index());
+            shifted.insert(typ.index(), pdb::TypeIndex(typ.index().0 + 0x8000));
+        }
+
+        let mut iter = type_information.iter();
+        while let Some(typ) = iter.next().expect("next type") {
+            let mut data = match typ.parse() {
+                Ok(data) => data,
+                Err(_) => continue,
+            };
+
+            let original = identity.remap_type(&typ).expect("remap record");
+            let record = shifted.remap_type(&typ).expect("remap record");
+            assert_eq!(record.len(), original.len());
+
+            // every reference to a non-primitive type changes exactly one byte
+            let references = data
+                .referenced_types()
+                .iter()
+                .filter(|index| index.0 >= 0x1000)
+                .count();
+            let changed = original.iter().zip(&record).filter(|(a, b)| a != b).count();
+            assert_eq!(changed, references, "{:?}", data);
+
+            shifted.remap_type_data(&mut data).expect("remap data");
+            assert!(data
+                .referenced_types()
+                .iter()
+                .all(|index| index.0 < 0x1000 || index.0 >= 0x9000));
+        }
+    })
+}
+
+#[test]
+fn diff_identical_streams() {
+    setup(|left| {
+        let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+        let pdb = pdb::PDB::open(file).expect("opening pdb");
+        let right = pdb.type_information().expect("type information");
+
+        let diff = left.diff(&right).expect("diff");
+        assert_eq!(diff.added, []);
+        assert_eq!(diff.removed, []);
+        assert_eq!(diff.changed, []);
+    })
+}
+
+#[test]
+fn visit_types() {
+    setup(|type_information| {
+        let mut next = 0x1000;
+        type_information
+            .visit(&mut |index: pdb::TypeIndex, _| {
+                assert_eq!(index.0, next);
+                next += 1;
+                Ok(())
+            })
+            .expect("visit");
+        assert_eq!((next - 0x1000) as usize, type_information.len());
+
+        // errors returned by the visitor stop the walk
+        let mut visited = 0;
+        let result = type_information.visit(&mut |index: pdb::TypeIndex, _| {
+            visited += 1;
+            match index.0 {
+                0x1004 => Err(pdb::Error::TypeNotFound(index.0)),
+                _ => Ok(()),
+            }
+        });
+        assert!(matches!(result, Err(pdb::Error::TypeNotFound(0x1004))));
+        assert_eq!(visited, 5);
+    })
+}
+
+#[test]
+fn walk_types() {
+    #[derive(Default)]
+    struct Counter {
+        classes: usize,
+        members: usize,
+    }
+
+    impl<'t> pdb::TypeVisitor<'t> for Counter {
+        fn visit_class(&mut self, _: pdb::TypeIndex, _: &pdb::ClassType<'t>) -> pdb::Result<()> {
+            self.classes += 1;
+            Ok(())
+        }
+
+        fn visit_member(&mut self, _: pdb::TypeIndex, _: &pdb::MemberType<'t>) -> pdb::Result<()> {
+            self.members += 1;
+            Ok(())
+        }
+    }
+
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+
+    // per-kind callbacks see the same records as a match over all types
+    let mut classes = 0;
+    let mut members = 0;
+    let mut iter = type_information.iter_parsed().skip_unparseable();
+    while let Some((_, data)) = iter.next().expect("next type") {
+        match data {
+            pdb::TypeData::Class(_) => classes += 1,
+            pdb::TypeData::FieldList(list) => {
+                members += list
+                    .fields
+                    .iter()
+                    .filter(|field| matches!(field, pdb::TypeData::Member(_)))
+                    .count()
+            }
+            _ => {}
+        }
+    }
+
+    let mut counter = Counter::default();
+    type_information.visit(&mut counter).expect("visit");
+    assert_eq!(counter.classes, classes);
+    assert_eq!(counter.members, members);
+
+    let mut finder = type_information.finder();
+    let mut iter = type_information.iter();
+    while iter.next().expect("next type").is_some() {
+        finder.update(&iter);
+    }
+
+    // class Baz is defined at 0x1064 with field list 0x1063
+    let root = pdb::TypeIndex(0x1064);
+    let mut visited = Vec::new();
+    finder
+        .walk(root, false, &mut |index, _| {
+            visited.push(index);
+            Ok(())
+        })
+        .expect("walk");
+    assert_eq!(visited, [root]);
+
+    let mut visited = Vec::new();
+    finder
+        .walk(root, true, &mut |index, _| {
+            visited.push(index);
+            Ok(())
+        })
+        .expect("walk");
+    assert_eq!(visited[0], root);
+    assert_eq!(visited[1], pdb::TypeIndex(0x1063));
+
+    // every type is visited once, and only types the root depends on are visited
+    let mut unique = visited.clone();
+    unique.sort_unstable();
+    unique.dedup();
+    assert_eq!(unique.len(), visited.len());
+
+    let dependencies = finder.dependencies(root).expect("deps");
+    for index in &visited[1..] {
+        assert!(index.0 < 0x1000 || dependencies.contains(index));
+    }
+}
+
+#[test]
+fn iter_parsed() {
+    setup(|type_information| {
+        let mut iter = type_information.iter();
+        let mut parsed = type_information.iter_parsed();
+        while let Some(typ) = iter.next().expect("next type") {
+            let (index, data) = parsed.next().expect("next parsed").expect("parsed type");
+            assert_eq!(index, typ.index());
+            assert_eq!(data, typ.parse().expect("parse"));
+        }
+        assert!(parsed.next().expect("next parsed").is_none());
+
+        let count = type_information
+            .iter_parsed()
+            .skip_unparseable()
+            .count()
+            .expect("count");
+        assert_eq!(count, type_information.len());
+    })
+}
+
+#[test]
+fn type_identity() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+
+    let mut finder = type_information.finder();
+    let mut iter = type_information.iter();
+    while iter.next().expect("next type").is_some() {
+        finder.update(&iter);
+    }
+
+    // the forward reference resolves to the definition
+    assert_eq!(
+        finder.identity(pdb::TypeIndex(0x11bf)).expect("identity"),
+        finder.identity(pdb::TypeIndex(0x1212)).expect("identity")
+    );
+
+    let exception = finder.identity(pdb::TypeIndex(0x1212)).expect("identity");
+    assert_eq!(
+        exception,
+        pdb::TypeIdentity::UniqueName(".?AVexception@std@@".into())
+    );
+
+    let unique_name = finder
+        .find(pdb::TypeIndex(0x1212))
+        .and_then(|typ| typ.parse())
+        .expect("parse")
+        .unique_name()
+        .expect("unique name");
+    let components = pdb::UniqueName::parse(unique_name)
+        .and_then(|name| name.components())
+        .expect("components");
+    assert_eq!(
+        components,
+        [
+            pdb::RawString::from("std"),
+            pdb::RawString::from("exception")
+        ]
+    );
+
+    // CsFrame has no unique name
+    assert!(matches!(
+        finder.identity(pdb::TypeIndex(0x30cd)),
+        Ok(pdb::TypeIdentity::Name(_))
+    ));
+
+    assert!(matches!(
+        finder.identity(pdb::TypeIndex(0x100a)),
+        Err(pdb::Error::NotARecordType(0x100a))
+    ));
+
+    let fingerprint = finder
+        .fingerprint(pdb::TypeIndex(0x14af))
+        .expect("fingerprint");
+    assert_eq!(
+        finder
+            .fingerprint(pdb::TypeIndex(0x14af))
+            .expect("fingerprint"),
+        fingerprint
+    );
+    assert_ne!(
+        finder
+            .fingerprint(pdb::TypeIndex(0x1212))
+            .expect("fingerprint"),
+        fingerprint
+    );
+}
+
+#[test]
+fn function_signatures() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+
+    let mut finder = type_information.finder();
+    let mut iter = type_information.iter();
+    while iter.next().expect("next type").is_some() {
+        finder.update(&iter);
+    }
+
+    // int main(int, char**)
+    let main = finder.signature(pdb::TypeIndex(0x100a)).expect("signature");
+    assert_eq!(main.declaration("main"), "int __cdecl main(int, char**)");
+    assert_eq!(main.to_string(), "int __cdecl(int, char**)");
+
+    // Baz::static_f_public()
+    let method = finder.signature(pdb::TypeIndex(0x1004)).expect("signature");
+    assert!(method.is_static());
+    assert_eq!(method.class.as_deref(), Some("Baz"));
+
+    let method = finder.signature(pdb::TypeIndex(0x1006)).expect("signature");
+    assert!(!method.is_static());
+    assert_eq!(method.this_type.as_deref(), Some("Baz* const"));
+    assert_eq!(method.parameters, ["int"]);
+
+    assert_eq!(
+        finder.type_name(pdb::TypeIndex(0x10ac)).expect("type name"),
+        "void (__cdecl *)(void*, void*)"
+    );
+    assert_eq!(
+        finder.type_name(pdb::TypeIndex(0x2bb1)).expect("type name"),
+        "const wchar_t (&)[3]"
+    );
+
+    assert!(matches!(
+        finder.signature(pdb::TypeIndex(0x1064)),
+        Err(pdb::Error::NotAFunctionType(0x1064))
+    ));
+}
+
+#[test]
+fn type_statistics() {
+    setup(|type_information| {
+        let statistics = type_information.statistics(10).expect("statistics");
+        assert_eq!(statistics.records, type_information.len());
+
+        let mut size = 0;
+        let mut iter = type_information.iter();
+        while let Some(typ) = iter.next().expect("next type") {
+            size += typ.len() as u64 + 2;
+        }
+        assert_eq!(statistics.size, size);
+
+        let kinds: usize = statistics.kinds.values().map(|kind| kind.count).sum();
+        assert_eq!(kinds, statistics.records);
+        assert!(statistics.definitions > 0);
+
+        assert_eq!(statistics.largest.len(), 10.min(statistics.records));
+        for pair in statistics.largest.windows(2) {
+            assert!(pair[0].size >= pair[1].size);
+        }
+    });
+}
+
+#[test]
+fn type_sources() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let id_information = pdb.id_information().expect("id information");
+    let string_table = pdb.string_table().expect("string table");
+    let debug_information = pdb.debug_information().expect("debug information");
+
+    let sources = id_information
+        .type_sources(Some(&string_table))
+        .expect("type sources");
+    assert!(!sources.is_empty());
+
+    let source = sources
+        .source_of(pdb::TypeIndex(0x100e))
+        .expect("source of va_list");
+    assert!(source
+        .file
+        .to_string()
+        .ends_with("microsoft visual studio 14.0\\vc\\include\\vadefs.h"));
+    assert_eq!(source.line, 145);
+
+    let module = debug_information
+        .modules()
+        .expect("modules")
+        .nth(source.module.expect("module"))
+        .expect("module")
+        .expect("module");
+    assert!(module.module_name().ends_with("foo.obj"));
+
+    // the linker records the module of every type
+    assert!(sources.iter().all(|(_, source)| source.module.is_some()));
+
+    assert!(matches!(
+        id_information.type_sources(None),
+        Err(pdb::Error::StreamNameNotFound)
+    ));
+}
+
+#[test]
+fn nested_types() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+
+    let mut finder = type_information.finder();
+    let mut iter = type_information.iter();
+    while iter.next().expect("next type").is_some() {
+        finder.update(&iter);
+    }
+
+    // EHExceptionRecord::EHParameters is declared through a forward reference
+    let nested = finder
+        .nested_types(pdb::TypeIndex(0x129a))
+        .expect("nested types");
+    assert_eq!(nested.len(), 1);
+    assert_eq!(nested[0].name.to_string(), "EHParameters");
+    assert_eq!(nested[0].nested_type, pdb::TypeIndex(0x1298));
+    assert_eq!(nested[0].definition, Some(pdb::TypeIndex(0x129f)));
+    assert!(!nested[0].typedef);
+
+    // lambdas declare typedefs of their function types
+    let nested = finder
+        .nested_types(pdb::TypeIndex(0x1399))
+        .expect("nested types");
+    assert!(nested.iter().all(|n| n.typedef));
+
+    assert!(matches!(
+        finder.nested_types(pdb::TypeIndex(0x100a)),
+        Err(pdb::Error::NotARecordType(0x100a))
+    ));
+}
+
+#[test]
+fn template_instantiations() {
+    let file = std::fs::File::open("fixtures/self/foo.pdb").expect("opening file");
+    let pdb = pdb::PDB::open(file).expect("opening pdb");
+    let type_information = pdb.type_information().expect("type information");
+
+    let instantiations = type_information
+        .instantiations("__crt_char_traits")
+        .expect("instantiations");
+    assert!(!instantiations.is_empty());
+
+    let mut arguments = std::collections::BTreeSet::new();
+    let mut iter = type_information.iter();
+    while let Some(typ) = iter.next().expect("next type") {
+        if !instantiations.contains(&typ.index()) {
+            continue;
+        }
+
+        let class = match typ.parse().expect("parse") {
+            pdb::TypeData::Class(class) => class,
+            other => panic!("unexpected type {:?}", other),
+        };
+
+        let template = pdb::TemplateName::parse(class.name).expect("template name");
+        assert_eq!(template.base.to_string(), "__crt_char_traits");
+        assert_eq!(template.arguments.len(), 1);
+        arguments.insert(template.arguments[0].to_string().into_owned());
+    }
+
+    assert_eq!(
+        arguments.into_iter().collect::<Vec<_>>(),
+        ["char", "wchar_t"]
+    );
+}
+
 /*
 #[test]
 fn type_length_histogram() {